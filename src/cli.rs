@@ -1,5 +1,5 @@
-use clap::{Parser, Subcommand, ValueEnum};
-use std::path::PathBuf;
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use std::path::{Path, PathBuf};
 
 /// Magick MCP - A Model Context Protocol server
 #[derive(Parser, Debug)]
@@ -13,19 +13,149 @@ pub struct Args {
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Check if ImageMagick is installed
-    Check,
+    Check {
+        /// Output format, json prints the typed check result
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+    /// Diagnose the ImageMagick installation, config file, and data directory
+    Doctor {
+        /// Output format, json prints the typed doctor report
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+    /// Install ImageMagick and verify the delegates functions need, for CI and fresh machines
+    Bootstrap {
+        /// Install non-interactively and print a JSON report
+        #[arg(long)]
+        ci: bool,
+        /// Function whose required delegates must be available
+        #[arg(long = "function")]
+        functions: Vec<String>,
+        /// Delegate which must be available, e.g. webp
+        #[arg(long = "delegate")]
+        delegates: Vec<String>,
+    },
     /// Start the MCP server
-    Mcp,
+    Mcp {
+        /// Exit after this many seconds without a request from the client
+        #[arg(long)]
+        idle_timeout: Option<u64>,
+        /// Keep running until the client disconnects, overriding --idle-timeout
+        #[arg(long)]
+        keep_alive: bool,
+        /// Refuse operations with likely-unintended consequences, such as overwriting the
+        /// source image, unless the call acknowledges them
+        #[arg(long)]
+        strict: bool,
+    },
     /// Install magick-mcp to MCP client configuration
     Install {
         /// Client type to install for
         #[arg(long, value_enum, default_value = "both")]
         r#type: ClientTypeArg,
+        /// Cursor config file to update instead of ~/.cursor/mcp.json, e.g. a Windows client's
+        /// config under /mnt/c when running inside WSL
+        #[arg(long)]
+        cursor_config: Option<PathBuf>,
+        /// Claude config file to update instead of ~/.claude.json
+        #[arg(long)]
+        claude_config: Option<PathBuf>,
+        /// Point every config installed to before at the current magick-mcp and ImageMagick
+        /// locations, e.g. after an upgrade moved them
+        #[arg(long, conflicts_with_all = ["cursor_config", "claude_config"])]
+        refresh_paths: bool,
     },
     /// Execute an ImageMagick command
     Magick {
         /// ImageMagick command arguments (e.g., "test.png -negate out.png")
         command: String,
+        /// Save the full standard output to this file and print only its first lines
+        #[arg(long)]
+        save_stdout: Option<PathBuf>,
+        /// Save standard error to this file, including warnings from a successful command
+        #[arg(long)]
+        save_stderr: Option<PathBuf>,
+    },
+    /// Encode an image at several formats and qualities and compare size, time, and SSIM
+    Bench {
+        /// Path to the image to encode
+        #[arg(long)]
+        input: String,
+        /// Formats to encode to, e.g. webp,avif,jpeg
+        #[arg(long, value_delimiter = ',', required = true)]
+        formats: Vec<String>,
+        /// Qualities to encode each format at, e.g. 60,75,90
+        #[arg(long, value_delimiter = ',', required = true)]
+        qualities: Vec<u32>,
+        /// Output format, json prints the results as JSON
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+    /// Render a small color preview of an image in the terminal
+    Preview {
+        /// Path to the image to preview
+        file: String,
+        /// Maximum preview width in terminal columns, at most 256
+        #[arg(long, default_value_t = 60)]
+        width: u32,
+    },
+    /// Rename images matching a glob using a template of metadata fields
+    Rename {
+        /// Glob pattern selecting files (e.g. "photos/*.jpg")
+        #[arg(long)]
+        glob: String,
+        /// Template for new names, e.g. "{date}_{width}x{height}_{orig}"
+        #[arg(long)]
+        template: String,
+        /// Apply the renames instead of printing the plan
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Find near-duplicate images and report, move, or delete all but the best copy
+    Dedupe {
+        /// Glob pattern selecting files (e.g. "photos/**/*.jpg")
+        #[arg(long)]
+        glob: String,
+        /// What to do with duplicates
+        #[arg(long, value_enum, default_value = "report")]
+        action: DedupeActionArg,
+        /// Directory duplicates are moved into when the action is move
+        #[arg(long, default_value = "duplicates")]
+        to: PathBuf,
+        /// Maximum perceptual hash distance between near-duplicates
+        #[arg(long, default_value_t = crate::DEFAULT_DEDUPE_THRESHOLD)]
+        threshold: u32,
+        /// Plan id printed by a previous dry run, required to move or delete
+        #[arg(long)]
+        confirm: Option<String>,
+    },
+    /// Inspect the history of tool calls made through the MCP server
+    History {
+        #[command(subcommand)]
+        history_command: HistoryCommands,
+    },
+    /// Manage the build cache reused by skip_if_newer runs
+    Cache {
+        #[command(subcommand)]
+        cache_command: CacheCommands,
+    },
+    /// Recover removed functions and overwritten outputs
+    Trash {
+        #[command(subcommand)]
+        trash_command: TrashCommands,
+    },
+    /// Call the MCP server's tools without a client
+    Tool {
+        #[command(subcommand)]
+        tool_command: ToolCommands,
+    },
+    /// Remove temporary files and reset locks left behind by crashed sessions
+    Clean,
+    /// Inspect crash reports written when magick-mcp panics
+    CrashReport {
+        #[command(subcommand)]
+        crash_report_command: CrashReportCommands,
     },
     /// Manage magick functions
     Func {
@@ -34,6 +164,74 @@ pub enum Commands {
     },
 }
 
+#[derive(Subcommand, Debug)]
+pub enum HistoryCommands {
+    /// Dump every recorded call with its arguments, result, and output file digests
+    Export {
+        /// Export format
+        #[arg(long, value_enum, default_value = "jsonl")]
+        format: HistoryFormat,
+        /// File to write, stdout when omitted
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CrashReportCommands {
+    /// Print the most recent crash report, to attach to an issue
+    Show,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CacheCommands {
+    /// Print the number and total size of cached outputs
+    Stats,
+    /// Remove every cached output
+    Clear,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TrashCommands {
+    /// List trashed files, newest first, with the ids to restore them by
+    List {
+        /// Output format, json prints the entries as JSON
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+    /// Put a trashed file back where it came from, trashing the file now there
+    Restore {
+        /// Id of the trashed file, as printed by trash list
+        id: String,
+    },
+    /// Remove every trashed file
+    Empty,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ToolCommands {
+    /// Call a tool as a client would and print its result as JSON
+    Call {
+        /// Name of the tool, e.g. resize
+        name: String,
+        /// The tool's arguments as a JSON object, e.g. '{"command": "logo.png -negate out.png"}'
+        #[arg(long, default_value = "{}")]
+        args: String,
+        /// Call the tool with the server in strict mode
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Print the input and output JSON Schemas of the tools as clients see them
+    Schema {
+        /// Print only this tool's schemas
+        #[arg(long)]
+        name: Option<String>,
+        /// Print the schemas of a server in strict mode
+        #[arg(long)]
+        strict: bool,
+    },
+}
+
 #[derive(Subcommand, Debug)]
 pub enum FuncCommands {
     /// List all available functions
@@ -50,6 +248,25 @@ pub enum FuncCommands {
         /// Input value to replace $input placeholders in commands
         #[arg(long)]
         input: Option<String>,
+        /// Value for a declared parameter as name=value, may be repeated
+        #[arg(long = "param", value_parser = parse_param)]
+        params: Vec<(String, String)>,
+        /// Only write the function's outputs if every command succeeds
+        #[arg(long)]
+        transactional: bool,
+    },
+    /// Render a function as Makefile targets or a justfile recipe
+    #[command(group(ArgGroup::new("target").required(true).args(["make", "just"])))]
+    Export {
+        /// Name of the function to render as Makefile targets
+        #[arg(long)]
+        make: Option<String>,
+        /// Name of the function to render as a justfile recipe
+        #[arg(long)]
+        just: Option<String>,
+        /// File to write, stdout when omitted
+        #[arg(long)]
+        out: Option<PathBuf>,
     },
     /// Save a function from a JSON file
     Save {
@@ -57,6 +274,23 @@ pub enum FuncCommands {
         #[arg(long)]
         file: PathBuf,
     },
+    /// Download the latest signed built-in functions without upgrading the binary
+    UpdateBuiltins {
+        /// URL of builtins.json, with its signature at the same URL plus .minisig
+        #[arg(long)]
+        url: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum HistoryFormat {
+    Jsonl,
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -76,10 +310,32 @@ impl From<ClientTypeArg> for crate::ClientType {
     }
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum DedupeActionArg {
+    Report,
+    Move,
+    Delete,
+}
+
 /// Handle command execution
 pub fn handle_command(command: Commands) {
+    crate::install_cleanup_guard();
+    crate::install_crash_reporter();
     match command {
-        Commands::Check => match crate::check() {
+        Commands::Check {
+            format: OutputFormat::Json,
+        } => {
+            let report = crate::check_report();
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&report).unwrap_or_default()
+            );
+            let healthy = report.installed && report.error.is_none();
+            std::process::exit(if healthy { 0 } else { 1 });
+        }
+        Commands::Check {
+            format: OutputFormat::Text,
+        } => match crate::check() {
             Ok(output) => {
                 println!("{output}");
                 std::process::exit(0);
@@ -89,25 +345,132 @@ pub fn handle_command(command: Commands) {
                 std::process::exit(1);
             }
         },
-        Commands::Mcp => {
+        Commands::Doctor { format } => {
+            let report = crate::doctor();
+            match format {
+                OutputFormat::Json => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&report).unwrap_or_default()
+                ),
+                OutputFormat::Text => {
+                    for check in &report.checks {
+                        println!("[{}] {}: {}", check.status, check.name, check.message);
+                    }
+                }
+            }
+            std::process::exit(if report.ok { 0 } else { 1 });
+        }
+        Commands::Bootstrap {
+            ci,
+            functions,
+            delegates,
+        } => {
+            let report = match crate::bootstrap(ci, &functions, &delegates) {
+                Ok(report) => report,
+                Err(e) if ci => {
+                    let output = serde_json::json!({"ok": false, "error": e.to_string()});
+                    println!(
+                        "{}",
+                        serde_json::to_string_pretty(&output).unwrap_or_default()
+                    );
+                    std::process::exit(1);
+                }
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    std::process::exit(1);
+                }
+            };
+            if ci {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&report).unwrap_or_default()
+                );
+            } else if report.ok {
+                println!(
+                    "ImageMagick {} is ready",
+                    report.magick.version.as_deref().unwrap_or_default()
+                );
+            } else {
+                if let Some(error) = &report.error {
+                    eprintln!("Error: {error}");
+                }
+                if !report.magick.installed && !report.commands.is_empty() {
+                    eprintln!("Run with --ci to install it with:");
+                    for command in &report.commands {
+                        eprintln!("  {command}");
+                    }
+                }
+            }
+            std::process::exit(if report.ok { 0 } else { 1 });
+        }
+        Commands::Mcp {
+            idle_timeout,
+            keep_alive,
+            strict,
+        } => {
+            let options = crate::mcp::ServerOptions {
+                idle_timeout: idle_timeout
+                    .filter(|_| !keep_alive)
+                    .map(std::time::Duration::from_secs),
+                strict,
+            };
             let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
-            if let Err(e) = rt.block_on(crate::mcp::run_server()) {
+            if let Err(e) = rt.block_on(crate::mcp::run_server(options)) {
                 eprintln!("Error running MCP server: {e}");
                 std::process::exit(1);
             }
+            // Exit rather than dropping the runtime, which would wait on the blocking stdin read
+            std::process::exit(0);
         }
-        Commands::Install { r#type } => {
+        Commands::Install {
+            r#type,
+            cursor_config,
+            claude_config,
+            refresh_paths,
+        } => {
+            if refresh_paths {
+                match crate::refresh_install_paths() {
+                    Ok(refreshed) => {
+                        if refreshed.is_empty() {
+                            println!("No recorded installs to refresh");
+                        }
+                        for config in refreshed {
+                            let status = match config.status {
+                                crate::RefreshStatus::Updated => "updated",
+                                crate::RefreshStatus::Unchanged => "unchanged",
+                                crate::RefreshStatus::Missing => "missing",
+                                crate::RefreshStatus::Removed => "no magick-mcp entry",
+                            };
+                            println!("{}: {status}", config.path.display());
+                        }
+                        std::process::exit(0);
+                    }
+                    Err(e) => {
+                        eprintln!("Error refreshing magick-mcp paths: {e}");
+                        std::process::exit(1);
+                    }
+                }
+            }
             let client_type: crate::ClientType = r#type.into();
-            let config_paths = match crate::ConfigPaths::from_home_dir() {
+            let mut config_paths = match crate::ConfigPaths::from_home_dir() {
                 Ok(paths) => paths,
                 Err(e) => {
                     eprintln!("Error getting config paths: {e}");
                     std::process::exit(1);
                 }
             };
+            if let Some(path) = cursor_config {
+                config_paths.cursor_path = path;
+            }
+            if let Some(path) = claude_config {
+                config_paths.claude_path = path;
+            }
             match crate::install(client_type, config_paths) {
                 Ok(_) => {
-                    println!("Successfully installed magick-mcp to MCP configuration");
+                    println!(
+                        "{}",
+                        crate::Message::InstallSucceeded.text(crate::language())
+                    );
                     std::process::exit(0);
                 }
                 Err(e) => {
@@ -116,13 +479,342 @@ pub fn handle_command(command: Commands) {
                 }
             }
         }
-        Commands::Magick { command } => match crate::magick(&command, None) {
+        Commands::Magick {
+            command,
+            save_stdout,
+            save_stderr,
+        } => {
+            let stream_files = crate::StreamFiles {
+                stdout: save_stdout,
+                stderr: save_stderr,
+            };
+            let output_policy = crate::OutputPolicy {
+                trash: crate::Trash::for_outputs(&trash_config()),
+                ..crate::OutputPolicy::default()
+            };
+            match crate::magick(&command, None, output_policy, stream_files) {
+                Ok(output) => {
+                    print!("{}", output.output);
+                    std::process::exit(0);
+                }
+                // Mirror magick itself so scripts can rely on its output streams and status
+                Err(crate::ShellError::NonZeroExit {
+                    exit_code,
+                    stdout,
+                    stderr,
+                    ..
+                }) => {
+                    print!("{stdout}");
+                    eprint!("{stderr}");
+                    std::process::exit(if (1..=255).contains(&exit_code) {
+                        exit_code
+                    } else {
+                        1
+                    });
+                }
+                Err(e) => {
+                    eprintln!("Error executing magick command: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Preview { file, width } => match crate::preview(&file, width) {
             Ok(output) => {
-                println!("{output}");
+                print!("{output}");
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Error previewing '{file}': {e}");
+                std::process::exit(1);
+            }
+        },
+        Commands::Rename {
+            glob,
+            template,
+            apply,
+        } => match crate::rename_by_metadata(&glob, &template, !apply, None) {
+            Ok(entries) => {
+                for entry in entries {
+                    let status = match entry.status {
+                        crate::RenameStatus::Planned => "planned".to_string(),
+                        crate::RenameStatus::Renamed => "renamed".to_string(),
+                        crate::RenameStatus::Skipped(reason) => format!("skipped: {reason}"),
+                    };
+                    println!(
+                        "{} -> {} ({status})",
+                        entry.from.display(),
+                        entry.to.display()
+                    );
+                }
+                if !apply {
+                    println!("{}", crate::Message::RenameDryRun.text(crate::language()));
+                }
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Error renaming files: {e}");
+                std::process::exit(1);
+            }
+        },
+        Commands::Dedupe {
+            glob,
+            action,
+            to,
+            threshold,
+            confirm,
+        } => {
+            let action = match action {
+                DedupeActionArg::Report => crate::DedupeAction::Report,
+                DedupeActionArg::Move => crate::DedupeAction::Move(to),
+                DedupeActionArg::Delete => crate::DedupeAction::Delete,
+            };
+            match crate::dedupe(&glob, threshold, &action, confirm.as_deref(), None) {
+                Ok(report) => {
+                    let groups: Vec<_> = report
+                        .groups
+                        .iter()
+                        .map(|group| {
+                            serde_json::json!({
+                                "keep": group.keep.to_string_lossy(),
+                                "duplicates": group
+                                    .duplicates
+                                    .iter()
+                                    .map(|path| path.to_string_lossy())
+                                    .collect::<Vec<_>>()
+                            })
+                        })
+                        .collect();
+                    let output = serde_json::json!({
+                        "groups": groups,
+                        "plan_id": report.plan_id,
+                        "applied": report.applied
+                    });
+                    println!(
+                        "{}",
+                        serde_json::to_string_pretty(&output).unwrap_or_default()
+                    );
+                    if !report.applied && action != crate::DedupeAction::Report {
+                        eprintln!(
+                            "Dry run only, pass --confirm {} to apply this plan",
+                            report.plan_id
+                        );
+                    }
+                    std::process::exit(0);
+                }
+                Err(e) => {
+                    eprintln!("Error deduplicating files: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::History {
+            history_command: HistoryCommands::Export { format: _, out },
+        } => match crate::export_history(out.as_deref()) {
+            Ok(count) => {
+                if let Some(out) = out {
+                    eprintln!("Exported {count} entries to {}", out.display());
+                }
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Error exporting history: {e}");
+                std::process::exit(1);
+            }
+        },
+        Commands::Cache { cache_command } => {
+            let (result, verb) = match cache_command {
+                CacheCommands::Stats => (crate::cache_stats(), "Cached"),
+                CacheCommands::Clear => (crate::clear_cache(), "Removed"),
+            };
+            match result {
+                Ok(stats) => {
+                    println!("{verb} {} outputs, {} bytes", stats.entries, stats.bytes);
+                    std::process::exit(0);
+                }
+                Err(e) => {
+                    eprintln!("Error managing cache: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Trash { trash_command } => match trash_command {
+            TrashCommands::List { format } => match crate::trash_list(&trash_config()) {
+                Ok(entries) => {
+                    match format {
+                        OutputFormat::Json => println!(
+                            "{}",
+                            serde_json::to_string_pretty(&entries).unwrap_or_default()
+                        ),
+                        OutputFormat::Text => {
+                            for entry in &entries {
+                                println!(
+                                    "{}  {:<8}  {} bytes  {}",
+                                    entry.id,
+                                    entry.kind.name(),
+                                    entry.bytes,
+                                    entry.original.display()
+                                );
+                            }
+                        }
+                    }
+                    std::process::exit(0);
+                }
+                Err(e) => {
+                    eprintln!("Error listing trash: {e}");
+                    std::process::exit(1);
+                }
+            },
+            TrashCommands::Restore { id } => match crate::trash_restore(&id, &trash_config()) {
+                Ok(entry) => {
+                    println!("Restored {}", entry.original.display());
+                    std::process::exit(0);
+                }
+                Err(e) => {
+                    eprintln!("Error restoring from trash: {e}");
+                    std::process::exit(1);
+                }
+            },
+            TrashCommands::Empty => match crate::empty_trash(&trash_config()) {
+                Ok(stats) => {
+                    println!("Removed {} files, {} bytes", stats.entries, stats.bytes);
+                    std::process::exit(0);
+                }
+                Err(e) => {
+                    eprintln!("Error emptying trash: {e}");
+                    std::process::exit(1);
+                }
+            },
+        },
+        Commands::Tool { tool_command } => match tool_command {
+            ToolCommands::Call { name, args, strict } => {
+                let arguments = match serde_json::from_str(&args) {
+                    Ok(serde_json::Value::Object(arguments)) => arguments,
+                    _ => {
+                        eprintln!("Error: --args must be a JSON object");
+                        std::process::exit(1);
+                    }
+                };
+                let options = crate::mcp::ServerOptions {
+                    strict,
+                    ..Default::default()
+                };
+                let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
+                match rt.block_on(crate::mcp::tool_call::call_tool(&name, arguments, options)) {
+                    Ok(result) => {
+                        let printed = match &result.structured_content {
+                            Some(structured) => serde_json::to_string_pretty(structured),
+                            None => serde_json::to_string_pretty(&result),
+                        };
+                        println!("{}", printed.unwrap_or_default());
+                        let failed = result.is_error.unwrap_or(false);
+                        std::process::exit(if failed { 1 } else { 0 });
+                    }
+                    Err(e) => {
+                        eprintln!("Error calling {name}: {e}");
+                        std::process::exit(1);
+                    }
+                }
+            }
+            ToolCommands::Schema { name, strict } => {
+                let options = crate::mcp::ServerOptions {
+                    strict,
+                    ..Default::default()
+                };
+                let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
+                let tools = match rt.block_on(crate::mcp::tool_call::list_tools(options)) {
+                    Ok(tools) => tools,
+                    Err(e) => {
+                        eprintln!("Error listing tools: {e}");
+                        std::process::exit(1);
+                    }
+                };
+                let schemas: Vec<serde_json::Value> = tools
+                    .iter()
+                    .filter(|tool| name.as_ref().is_none_or(|name| tool.name == *name))
+                    .map(|tool| {
+                        serde_json::json!({
+                            "name": tool.name,
+                            "description": tool.description,
+                            "inputSchema": tool.input_schema,
+                            "outputSchema": tool.output_schema,
+                        })
+                    })
+                    .collect();
+                let printed = match (&name, schemas.as_slice()) {
+                    (None, _) => serde_json::to_string_pretty(&schemas),
+                    (Some(_), [schema]) => serde_json::to_string_pretty(schema),
+                    (Some(name), _) => {
+                        eprintln!("Error: no tool named {name}");
+                        std::process::exit(1);
+                    }
+                };
+                println!("{}", printed.unwrap_or_default());
+                std::process::exit(0);
+            }
+        },
+        Commands::Bench {
+            input,
+            formats,
+            qualities,
+            format,
+        } => {
+            let options = crate::BenchOptions {
+                input,
+                formats,
+                qualities,
+            };
+            match crate::bench(&options) {
+                Ok(report) => {
+                    match format {
+                        OutputFormat::Json => println!(
+                            "{}",
+                            serde_json::to_string_pretty(&report).unwrap_or_default()
+                        ),
+                        OutputFormat::Text => print!("{}", report.table()),
+                    }
+                    std::process::exit(0);
+                }
+                Err(e) => {
+                    eprintln!("Error running benchmark: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::CrashReport {
+            crash_report_command: CrashReportCommands::Show,
+        } => match crate::latest_crash_report() {
+            Ok(Some((path, report))) => {
+                eprintln!("Crash report {}", path.display());
+                print!("{report}");
+                std::process::exit(0);
+            }
+            Ok(None) => {
+                println!("No crash reports");
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Error reading crash report: {e}");
+                std::process::exit(1);
+            }
+        },
+        Commands::Clean => match crate::clean(Some(Path::new("."))) {
+            Ok(report) => {
+                for path in &report.removed {
+                    println!("Removed {}", path.display());
+                }
+                for path in &report.released_locks {
+                    println!("Released {}", path.display());
+                }
+                println!(
+                    "Removed {} files, {} bytes, and released {} locks",
+                    report.removed.len(),
+                    report.bytes,
+                    report.released_locks.len()
+                );
                 std::process::exit(0);
             }
             Err(e) => {
-                eprintln!("Error executing magick command: {e}");
+                eprintln!("Error cleaning up: {e}");
                 std::process::exit(1);
             }
         },
@@ -130,13 +822,31 @@ pub fn handle_command(command: Commands) {
     }
 }
 
+/// The `[trash]` section of the config, or the defaults when the config cannot be read
+fn trash_config() -> crate::TrashConfig {
+    crate::load_config()
+        .map(|config| config.trash)
+        .unwrap_or_default()
+}
+
+/// Parse a `name=value` parameter for `func execute`
+fn parse_param(param: &str) -> Result<(String, String), String> {
+    param
+        .split_once('=')
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected name=value, got '{param}'"))
+}
+
 /// Handle function subcommand execution
 fn handle_func_command(func_command: FuncCommands) {
     match func_command {
-        FuncCommands::List => match crate::list_functions() {
+        FuncCommands::List => match crate::list_functions(Some(Path::new("."))) {
             Ok(functions) => {
                 if functions.is_empty() {
-                    println!("No functions found");
+                    println!(
+                        "{}",
+                        crate::Message::NoFunctionsFound.text(crate::language())
+                    );
                 } else {
                     for name in functions {
                         println!("{name}");
@@ -149,7 +859,7 @@ fn handle_func_command(func_command: FuncCommands) {
                 std::process::exit(1);
             }
         },
-        FuncCommands::Print { name } => match crate::load_function(&name) {
+        FuncCommands::Print { name } => match crate::load_function(&name, Some(Path::new("."))) {
             Ok(function) => {
                 println!("Name: {}", function.name);
                 println!("Commands:");
@@ -163,8 +873,13 @@ fn handle_func_command(func_command: FuncCommands) {
                 std::process::exit(1);
             }
         },
-        FuncCommands::Execute { name, input } => {
-            let function = match crate::load_function(&name) {
+        FuncCommands::Execute {
+            name,
+            input,
+            params,
+            transactional,
+        } => {
+            let function = match crate::load_function(&name, Some(Path::new("."))) {
                 Ok(f) => f,
                 Err(e) => {
                     eprintln!("Error loading function '{name}': {e}");
@@ -172,9 +887,23 @@ fn handle_func_command(func_command: FuncCommands) {
                 }
             };
             let input_ref = input.as_deref();
-            match crate::run_function(&function, None, input_ref) {
-                Ok(outputs) => {
-                    for output in outputs {
+            let params = params.into_iter().collect();
+            match crate::run_function(
+                &function,
+                None,
+                input_ref,
+                &params,
+                crate::OutputPolicy {
+                    trash: crate::Trash::for_outputs(&trash_config()),
+                    ..crate::OutputPolicy::default()
+                },
+                transactional,
+                &crate::load_config()
+                    .map(|config| config.functions)
+                    .unwrap_or_default(),
+            ) {
+                Ok(function_output) => {
+                    for output in function_output.outputs {
                         println!("{output}");
                     }
                     std::process::exit(0);
@@ -185,6 +914,38 @@ fn handle_func_command(func_command: FuncCommands) {
                 }
             }
         }
+        FuncCommands::Export { make, just, out } => {
+            let (name, format) = match (make, just) {
+                (Some(name), _) => (name, crate::ExportFormat::Make),
+                (None, Some(name)) => (name, crate::ExportFormat::Just),
+                (None, None) => unreachable!("clap requires --make or --just"),
+            };
+            let function = match crate::load_function(&name, Some(Path::new("."))) {
+                Ok(f) => f,
+                Err(e) => {
+                    eprintln!("Error loading function '{name}': {e}");
+                    std::process::exit(1);
+                }
+            };
+            let exported = match crate::export_function(&function, format) {
+                Ok(exported) => exported,
+                Err(e) => {
+                    eprintln!("Error exporting function '{name}': {e}");
+                    std::process::exit(1);
+                }
+            };
+            match out {
+                Some(out) => {
+                    if let Err(e) = std::fs::write(&out, exported) {
+                        eprintln!("Error writing '{}': {e}", out.display());
+                        std::process::exit(1);
+                    }
+                    eprintln!("Exported '{name}' to {}", out.display());
+                }
+                None => print!("{exported}"),
+            }
+            std::process::exit(0);
+        }
         FuncCommands::Save { file } => {
             let contents = match std::fs::read_to_string(&file) {
                 Ok(c) => c,
@@ -202,7 +963,7 @@ fn handle_func_command(func_command: FuncCommands) {
             };
             match crate::save_function(function) {
                 Ok(_) => {
-                    println!("Function saved successfully");
+                    println!("{}", crate::Message::FunctionSaved.text(crate::language()));
                     std::process::exit(0);
                 }
                 Err(e) => {
@@ -211,5 +972,25 @@ fn handle_func_command(func_command: FuncCommands) {
                 }
             }
         }
+        FuncCommands::UpdateBuiltins { url } => match crate::update_builtins(url.as_deref()) {
+            Ok(update) => {
+                if update.updated() {
+                    println!(
+                        "Updated built-in functions from version {} to {}",
+                        update.previous_version, update.version
+                    );
+                } else {
+                    println!(
+                        "Built-in functions are up to date (version {})",
+                        update.version
+                    );
+                }
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Error updating built-in functions: {e}");
+                std::process::exit(1);
+            }
+        },
     }
 }
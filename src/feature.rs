@@ -1,13 +1,105 @@
+mod batch;
+mod bootstrap;
+mod cache;
+mod capabilities;
 mod check;
+mod cleanup;
+mod config;
+mod crash;
+mod data_dir;
+mod doctor;
+mod faults;
 mod functions;
+mod history;
+mod i18n;
+mod incremental;
 mod install;
+mod legacy;
+mod lock;
 mod magick;
+mod ops;
+mod output;
+mod paths;
+mod project;
 mod shell;
+mod temp_file;
+mod template;
+mod trace;
+mod transaction;
+mod trash;
 mod which;
+mod wsl;
 
-pub use check::MagickChecker;
-pub use functions::{Function, FunctionRunner, FunctionStore, FunctionStoreError};
-pub use install::{ClientType, ConfigPaths, InstallError, MCPInstaller};
-pub(crate) use magick::MagickRunner;
+pub use batch::{
+    BatchFileResult, DEFAULT_DEDUPE_THRESHOLD, DedupeAction, DedupeReport, Deduper, DuplicateGroup,
+    MagickBatch, OrganizeBy, Organizer, Placement, PlacementStatus, RenameByMetadata, RenameEntry,
+    RenameStatus, WatermarkBatch, WatermarkFileResult,
+};
+pub use bootstrap::{BootstrapReport, Bootstrapper, PackageManager, required_delegates};
+pub use cache::{BuildCache, CacheStats};
+pub use capabilities::{Capabilities, CapabilityReport, ConfigCapabilities, FormatSupport};
+pub use check::{CheckReport, MagickChecker, ManagedEnvironment};
+pub use cleanup::{CleanReport, clean, install_cleanup_guard, remove_managed};
+pub use config::{
+    BuiltinsConfig, CONFIG_PATH_ENV, Config, ConfigError, FunctionsConfig, LimitsConfig,
+    MagickConfig, PresetConfig, ServerConfig, TrashConfig, config_path,
+};
+pub use crash::{begin_call, end_call, install_crash_reporter, latest_crash_report, log_line};
+pub(crate) use data_dir::data_dir;
+pub use doctor::{Doctor, DoctorCheck, DoctorReport, DoctorStatus};
+pub use faults::{FaultConfig, FaultyCommandRunner};
+pub use functions::{
+    BuiltinBundle, BuiltinUpdate, BuiltinUpdater, BuiltinsError, DEFAULT_BUILTINS_URL,
+    ExportFormat, Function, FunctionEdit, FunctionOutput, FunctionRunner, FunctionSource,
+    FunctionStore, FunctionStoreError, FunctionsWatcher, PROJECT_FUNCTIONS_DIR, ParamType,
+    export_function,
+};
+pub use history::{FileDigest, HistoryEntry, HistoryError, HistoryStore};
+pub use i18n::{Language, Message};
+pub use install::{
+    ClientType, ConfigPaths, InstallError, MCPInstaller, RefreshStatus, RefreshedConfig,
+};
+pub use legacy::{LegacyRewrite, rewrite_legacy};
+pub use lock::{LockScope, take_lock_wait};
+pub use magick::{MagickOutput, StreamFiles};
+pub(crate) use magick::{MagickRunner, strip_bad_prefix};
+pub use ops::{
+    Annotate, AnnotateOptions, AppIconFile, AppIconOptions, AppIcons, Append, AppendDirection,
+    AppendOptions, AppendedImage, ArgumentForm, Bench, BenchOptions, BenchReport, BenchResult,
+    Border, BorderOptions, Canvas, CanvasFill, CanvasOptions, CaptionOverlay,
+    CaptionOverlayOptions, ChannelColorspace, ChannelFile, ChannelHistogram, Channels,
+    CleanedDocument, Collage, CollageCell, CollageFit, CollageLayout, CollageMaker, CollageOptions,
+    Composite, CompositeOptions, ContrastStretch, Convert, ConvertOptions, ConvertedFile,
+    CostEstimate, Crop, CropOptions, CropRect, CropUnit, CroppedImage, DEFAULT_BUNDLE_DENSITY,
+    DEFAULT_FAVICON_DIR, DEFAULT_HISTOGRAM_BINS, DEFAULT_MIN_QUALITY, DEFAULT_PDF_DENSITY,
+    DEFAULT_POINT_SIZE, DEFAULT_PREVIEW_BYTES, DEFAULT_PREVIEW_DIMENSION,
+    DEFAULT_SNAPSHOT_THRESHOLD, DEFAULT_SRCSET_WIDTHS, DEFAULT_THUMBNAIL_SIZES, Density,
+    DensityChange, DensityOptions, DensityUnits, DiffMetric, DocumentCleanup,
+    DocumentCleanupOptions, DropShadow, EstimateOptions, EstimateVerdict, Estimator,
+    ExtractedFrame, Favicon, FaviconFile, FaviconOptions, Filter, FilterOperation, FilterOptions,
+    FontInfo, Frames, FramesOptions, FxEvaluator, HeifConversion, HeifConvert, HeifConvertOptions,
+    HeifFormat, HeifSupport, HelpTopic, HelpTopics, Histogram, IconPlatform, ImageDimensions,
+    ImageFormat, ImageHistogram, InlinePreview, InlinePreviewOptions, InlinePreviewer,
+    MIN_REDACT_STRENGTH, OperationError, Optimize, OptimizeOptions, OptimizedImage, OptionExample,
+    OptionHelp, OptionHelper, Orient, OrientOptions, OrientedImage, OutputSettings, PageSize,
+    PdfBundle, PdfBundleOptions, PdfPages, PdfPagesOptions, PreviewFormat, Previewer, PrintSize,
+    Redact, RedactOptions, RedactionMode, Region, RenderedPage, Resize, ResizeFit, ResizeOptions,
+    RgbaChannel, SnapshotCheck, SnapshotCheckOptions, SnapshotStatus, SnapshotUpdate, Snapshots,
+    Srcset, SrcsetFile, SrcsetOptions, StrictCheckOptions, StrictChecker, StrictConcern,
+    StrictFinding, StripMetadata, StripMetadataOptions, StrippedImage, Style, Stylize,
+    StylizeOptions, Thumbnail, ThumbnailFile, ThumbnailOptions, TrimOptions, Trimmed, Trimmer,
+    VerboseIdentify, VisualDiff, VisualDiffOptions, VisualDiffer, Watermark, WatermarkMark,
+    WatermarkOptions, WebFormat, parse_byte_size, picture_html,
+};
+pub(crate) use ops::{list_fonts, list_formats};
+pub use output::{ConflictStrategy, OutputPolicy, ResolvedOutput};
+pub(crate) use paths::canonical_path;
+pub use project::{PROJECT_CONFIG_FILE, ProjectConfig};
 pub use shell::{CommandRunner, DefaultCommandRunner, ShellError};
-pub use which::DefaultWhichChecker;
+pub(crate) use temp_file::TempFile;
+pub use template::TemplateError;
+pub(crate) use template::{Bindings, Template};
+pub use trace::{TRACE_ID_ENV, current_trace_id, new_trace_id, with_trace_id};
+pub use trash::{Trash, TrashEntry, TrashError, TrashKind, TrashStats};
+pub use which::{DefaultWhichChecker, WhichChecker};
+pub use wsl::translate_path;
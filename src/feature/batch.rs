@@ -0,0 +1,13 @@
+mod command;
+mod dedupe;
+mod glob;
+mod organize;
+mod parallel;
+mod rename;
+mod watermark;
+
+pub use command::{BatchFileResult, MagickBatch};
+pub use dedupe::{DEFAULT_DEDUPE_THRESHOLD, DedupeAction, DedupeReport, Deduper, DuplicateGroup};
+pub use organize::{OrganizeBy, Organizer, Placement, PlacementStatus};
+pub use rename::{RenameByMetadata, RenameEntry, RenameStatus};
+pub use watermark::{WatermarkBatch, WatermarkFileResult};
@@ -0,0 +1,290 @@
+use crate::feature::batch::glob::expand_glob;
+use crate::feature::batch::parallel::run_parallel;
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::output::OutputPolicy;
+use crate::feature::shell::CommandRunner;
+use crate::feature::template::{Bindings, Template};
+use crate::feature::transaction::Transaction;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Outcome of running the batch command against a single file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchFileResult {
+    pub input: PathBuf,
+    /// The path written, which differs from the requested one when renamed on conflict
+    pub output: PathBuf,
+    /// Whether the file was skipped because the output already existed or was up to date
+    pub skipped: bool,
+    /// The failure message, if the command failed for this file
+    pub error: Option<String>,
+}
+
+/// Runs a `magick` command template against every file matching a glob, in parallel
+pub struct MagickBatch<'a> {
+    command_runner: &'a (dyn CommandRunner + Sync),
+    workspace: Option<&'a Path>,
+    output_policy: OutputPolicy,
+    transactional: bool,
+}
+
+impl<'a> MagickBatch<'a> {
+    /// Create a new MagickBatch with the provided CommandRunner and optional workspace path
+    pub fn new(
+        command_runner: &'a (dyn CommandRunner + Sync),
+        workspace: Option<&'a Path>,
+    ) -> Self {
+        MagickBatch {
+            command_runner,
+            workspace,
+            output_policy: OutputPolicy::default(),
+            transactional: false,
+        }
+    }
+
+    /// Use the provided policy for each output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.output_policy = output_policy;
+        self
+    }
+
+    /// Stage every output and only move them into place if the command succeeds for every file
+    ///
+    /// When any file fails, no output is written and the files which succeeded report that
+    /// they were discarded.
+    pub fn with_transactional(mut self, transactional: bool) -> Self {
+        self.transactional = transactional;
+        self
+    }
+
+    /// Run the command template for every matching file
+    ///
+    /// `$input` is replaced with each matched file and `$output` with the file of the same name in
+    /// the output directory, using `extension` when provided. A failure on one file is reported in
+    /// its result and does not stop the rest of the batch, though in a transactional batch it
+    /// means none of the outputs are written.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Glob pattern selecting files, relative to the workspace
+    /// * `template` - `magick` arguments containing `$input` and `$output`, e.g. `$input -resize 50% $output`
+    /// * `output_dir` - Directory the outputs are written to
+    /// * `extension` - Optional extension for the outputs, e.g. `webp` to convert
+    ///
+    /// # Returns
+    ///
+    /// Returns per-file results in glob order, or an `OperationError` if the batch could not start
+    pub fn run(
+        &self,
+        pattern: &str,
+        template: &str,
+        output_dir: &Path,
+        extension: Option<&str>,
+    ) -> Result<Vec<BatchFileResult>, OperationError> {
+        let template = Template::parse(template)
+            .map_err(|e| OperationError::InvalidParameter(e.to_string()))?;
+        if !template.uses("input") || !template.uses("output") {
+            return Err(OperationError::InvalidParameter(
+                "the command template must contain both $input and $output".to_string(),
+            ));
+        }
+        if let Some(name) = template
+            .placeholders()
+            .find(|name| !matches!(*name, "input" | "output"))
+        {
+            return Err(OperationError::InvalidParameter(format!(
+                "unknown placeholder ${name}, only $input and $output are available; write $$ for a literal $"
+            )));
+        }
+        let base = self.workspace.unwrap_or(Path::new("."));
+        let inputs = expand_glob(base, pattern)?;
+        fs::create_dir_all(base.join(output_dir))?;
+
+        let mut seen = HashSet::new();
+        let jobs: Vec<(PathBuf, PathBuf, bool)> = inputs
+            .into_iter()
+            .map(|input| {
+                let mut output = output_dir.join(input.file_name().unwrap_or_default());
+                if let Some(extension) = extension {
+                    output.set_extension(extension.trim_start_matches('.'));
+                }
+                let unique = seen.insert(output.clone());
+                (input, output, unique)
+            })
+            .collect();
+
+        let transaction = self
+            .transactional
+            .then(|| Transaction::begin(self.workspace))
+            .transpose()?;
+        let mut results = run_parallel(&jobs, |(input, output, unique)| {
+            self.run_file(&template, transaction.as_ref(), input, output, *unique)
+        });
+        let Some(transaction) = transaction else {
+            return Ok(results);
+        };
+        if results.iter().all(|result| result.error.is_none()) {
+            transaction.commit(self.output_policy.overwrite_trash())?;
+        } else {
+            for result in results
+                .iter_mut()
+                .filter(|result| result.error.is_none() && !result.skipped)
+            {
+                result.error =
+                    Some("discarded because another file in the transaction failed".to_string());
+            }
+        }
+        Ok(results)
+    }
+
+    /// Run the template for a single file, capturing any failure in the result
+    fn run_file(
+        &self,
+        template: &Template,
+        transaction: Option<&Transaction>,
+        input: &Path,
+        output: &Path,
+        unique: bool,
+    ) -> BatchFileResult {
+        let mut result = BatchFileResult {
+            input: input.to_path_buf(),
+            output: output.to_path_buf(),
+            skipped: false,
+            error: None,
+        };
+        if !unique {
+            result.error = Some("another file has the same output name".to_string());
+            return result;
+        }
+        let input_str = input.to_string_lossy();
+        let output_str = output.to_string_lossy();
+        let bindings = Bindings::new()
+            .with("input", input_str.as_ref())
+            .with("output", output_str.as_ref());
+        let args = match template.render(&bindings) {
+            Ok(args) => args,
+            Err(e) => {
+                result.error = Some(e.to_string());
+                return result;
+            }
+        };
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+
+        let mut magick_runner = MagickRunner::new(self.command_runner, self.workspace)
+            .with_output_policy(self.output_policy.clone());
+        if let Some(transaction) = transaction {
+            magick_runner = magick_runner.with_transaction(transaction);
+        }
+        match magick_runner.execute_args(&arg_refs) {
+            Ok(_) => {
+                let resolved = magick_runner.last_resolved_output(&output_str);
+                result.output = resolved.path;
+                result.skipped = resolved.skipped;
+            }
+            Err(e) => result.error = Some(e.to_string()),
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    /// Mock implementation of CommandRunner which records arguments and fails for "bad" inputs
+    struct MockCommandRunner {
+        calls: Mutex<Vec<Vec<String>>>,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            if args[0].starts_with("bad") {
+                return Err(ShellError::ExecutionFailed {
+                    message: "corrupt image".to_string(),
+                    command: command.to_string(),
+                    args: args.join(" "),
+                });
+            }
+            self.calls
+                .lock()
+                .unwrap()
+                .push(args.iter().map(|s| s.to_string()).collect());
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_batch_substitutes_placeholders_per_file() {
+        let dir = TempDir::new().unwrap();
+        for name in ["a.png", "b.png", "bad.png", "notes.txt"] {
+            fs::write(dir.path().join(name), b"").unwrap();
+        }
+        let runner = MockCommandRunner {
+            calls: Mutex::new(Vec::new()),
+        };
+        let batch = MagickBatch::new(&runner, Some(dir.path()));
+
+        let results = batch
+            .run(
+                "*.png",
+                "$input -resize 50% $output",
+                Path::new("half"),
+                Some("webp"),
+            )
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].output, PathBuf::from("half/a.webp"));
+        assert!(results[0].error.is_none());
+        assert!(results[2].error.as_ref().unwrap().contains("corrupt image"));
+
+        let mut calls = runner.calls.lock().unwrap().clone();
+        calls.sort();
+        assert_eq!(calls[0], vec!["a.png", "-resize", "50%", "half/a.webp"]);
+        assert!(dir.path().join("half").is_dir());
+    }
+
+    #[test]
+    fn test_batch_requires_placeholders() {
+        let runner = MockCommandRunner {
+            calls: Mutex::new(Vec::new()),
+        };
+        let batch = MagickBatch::new(&runner, None);
+        let result = batch.run("*.png", "$input -negate out.png", Path::new("out"), None);
+        assert!(matches!(result, Err(OperationError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn test_transactional_batch_discards_outputs_when_a_file_fails() {
+        let dir = TempDir::new().unwrap();
+        for name in ["a.png", "bad.png"] {
+            fs::write(dir.path().join(name), b"").unwrap();
+        }
+        let runner = MockCommandRunner {
+            calls: Mutex::new(Vec::new()),
+        };
+        let batch = MagickBatch::new(&runner, Some(dir.path())).with_transactional(true);
+        let results = batch
+            .run("*.png", "$input -negate $output", Path::new("out"), None)
+            .unwrap();
+
+        assert!(results[0].error.as_ref().unwrap().contains("discarded"));
+        assert!(results[1].error.as_ref().unwrap().contains("corrupt image"));
+        let calls = runner.calls.lock().unwrap();
+        assert!(calls[0][2].contains(".magickmcp"));
+        assert_eq!(
+            fs::read_dir(dir.path().join(".magickmcp")).unwrap().count(),
+            0
+        );
+    }
+}
@@ -0,0 +1,389 @@
+use crate::feature::batch::glob::expand_glob;
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::identify;
+use crate::feature::ops::phash::{difference_hash, hamming_distance};
+use crate::feature::output::{self, ConflictStrategy};
+use crate::feature::shell::CommandRunner;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default maximum Hamming distance between hashes of near-duplicate images
+pub const DEFAULT_DEDUPE_THRESHOLD: u32 = 5;
+
+/// What to do with the duplicates in each group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DedupeAction {
+    /// Only report the groups
+    Report,
+    /// Move duplicates into the given directory
+    Move(PathBuf),
+    /// Delete duplicates
+    Delete,
+}
+
+/// A group of near-duplicate images
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    /// The highest-resolution copy, which is always kept
+    pub keep: PathBuf,
+    /// The remaining copies, which the action applies to
+    pub duplicates: Vec<PathBuf>,
+}
+
+/// Result of a dedupe run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DedupeReport {
+    pub groups: Vec<DuplicateGroup>,
+    /// Identifier of the plan, which must be passed back to apply it
+    pub plan_id: String,
+    /// Whether the action was applied
+    pub applied: bool,
+}
+
+/// A scanned image
+struct Candidate {
+    path: PathBuf,
+    hash: u64,
+    pixels: u64,
+    bytes: u64,
+}
+
+/// Groups near-duplicate images by perceptual hash and prunes all but the best copy
+pub struct Deduper<'a> {
+    magick_runner: MagickRunner<'a>,
+    base: PathBuf,
+}
+
+impl<'a> Deduper<'a> {
+    /// Create a new Deduper with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Deduper {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+            base: workspace
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(".")),
+        }
+    }
+
+    /// Find near-duplicates and optionally apply the action to them
+    ///
+    /// Move and delete always require a dry run first: without `confirm` only the plan is
+    /// returned, and the action is applied only when `confirm` matches the `plan_id` of the
+    /// current plan. This guarantees that the files acted on are the ones that were reviewed.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Glob pattern selecting files, relative to the workspace
+    /// * `threshold` - Maximum Hamming distance between hashes of near-duplicates
+    /// * `action` - What to do with the duplicates
+    /// * `confirm` - The `plan_id` from a previous dry run, to apply the action
+    ///
+    /// # Returns
+    ///
+    /// Returns the report, or an `OperationError` on failure or when `confirm` is stale
+    pub fn run(
+        &self,
+        pattern: &str,
+        threshold: u32,
+        action: &DedupeAction,
+        confirm: Option<&str>,
+    ) -> Result<DedupeReport, OperationError> {
+        let mut candidates = Vec::new();
+        for path in expand_glob(&self.base, pattern)? {
+            let name = path.to_string_lossy().to_string();
+            let hash = difference_hash(&self.magick_runner, &name)?;
+            let dimensions = identify::dimensions(&self.magick_runner, &name)?;
+            let bytes = fs::metadata(self.base.join(&path))?.len();
+            candidates.push(Candidate {
+                path,
+                hash,
+                pixels: u64::from(dimensions.width) * u64::from(dimensions.height),
+                bytes,
+            });
+        }
+
+        let groups = group_candidates(candidates, threshold);
+        let plan_id = plan_id(&groups, action);
+        let applied = match (action, confirm) {
+            (DedupeAction::Report, _) | (_, None) => false,
+            (_, Some(confirm)) if confirm != plan_id => {
+                return Err(OperationError::InvalidParameter(format!(
+                    "plan '{confirm}' does not match the current plan '{plan_id}', run a dry run again"
+                )));
+            }
+            (DedupeAction::Move(directory), Some(_)) => {
+                // Check every destination before moving anything
+                for duplicate in groups.iter().flat_map(|group| &group.duplicates) {
+                    if let Some(file_name) = duplicate.file_name() {
+                        self.magick_runner
+                            .check_write(&Path::new(directory).join(file_name))?;
+                    }
+                }
+                let target_dir = self.base.join(directory);
+                fs::create_dir_all(&target_dir)?;
+                for duplicate in groups.iter().flat_map(|group| &group.duplicates) {
+                    let Some(file_name) = duplicate.file_name() else {
+                        continue;
+                    };
+                    // Duplicates from different directories may share a name, so never replace
+                    // a file already moved
+                    let target = output::resolve_conflict(
+                        &target_dir.join(file_name),
+                        None,
+                        ConflictStrategy::Rename,
+                    )
+                    .unwrap_or_else(|| target_dir.join(file_name));
+                    fs::rename(self.base.join(duplicate), target)?;
+                }
+                true
+            }
+            (DedupeAction::Delete, Some(_)) => {
+                for duplicate in groups.iter().flat_map(|group| &group.duplicates) {
+                    fs::remove_file(self.base.join(duplicate))?;
+                }
+                true
+            }
+        };
+
+        Ok(DedupeReport {
+            groups,
+            plan_id,
+            applied,
+        })
+    }
+}
+
+/// Cluster candidates whose hash is within `threshold` of a group member
+///
+/// Only groups with at least one duplicate are returned. The copy with the most pixels is kept,
+/// falling back to the largest file and then the first path.
+fn group_candidates(candidates: Vec<Candidate>, threshold: u32) -> Vec<DuplicateGroup> {
+    let mut clusters: Vec<Vec<Candidate>> = Vec::new();
+    for candidate in candidates {
+        let existing = clusters.iter_mut().find(|cluster| {
+            cluster
+                .iter()
+                .any(|member| hamming_distance(member.hash, candidate.hash) <= threshold)
+        });
+        match existing {
+            Some(cluster) => cluster.push(candidate),
+            None => clusters.push(vec![candidate]),
+        }
+    }
+
+    clusters
+        .into_iter()
+        .filter(|cluster| cluster.len() > 1)
+        .map(|mut cluster| {
+            cluster.sort_by(|a, b| {
+                b.pixels
+                    .cmp(&a.pixels)
+                    .then(b.bytes.cmp(&a.bytes))
+                    .then(a.path.cmp(&b.path))
+            });
+            let mut paths = cluster.into_iter().map(|candidate| candidate.path);
+            let keep = paths.next().unwrap_or_default();
+            DuplicateGroup {
+                keep,
+                duplicates: paths.collect(),
+            }
+        })
+        .collect()
+}
+
+/// Stable identifier of a plan, so a dry run can be confirmed
+///
+/// The SHA-256 of the sorted groups and the action, so the id stays the same across processes
+/// and builds.
+fn plan_id(groups: &[DuplicateGroup], action: &DedupeAction) -> String {
+    let mut lines: Vec<String> = groups
+        .iter()
+        .map(|group| {
+            let mut duplicates: Vec<_> = group
+                .duplicates
+                .iter()
+                .map(|path| path.to_string_lossy())
+                .collect();
+            duplicates.sort();
+            format!(
+                "{}\0{}",
+                group.keep.to_string_lossy(),
+                duplicates.join("\0")
+            )
+        })
+        .collect();
+    lines.sort();
+    lines.push(match action {
+        DedupeAction::Report => "report".to_string(),
+        DedupeAction::Move(directory) => format!("move\0{}", directory.to_string_lossy()),
+        DedupeAction::Delete => "delete".to_string(),
+    });
+    let digest = Sha256::digest(lines.join("\n"));
+    digest[..8]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use tempfile::TempDir;
+
+    /// Mock implementation of CommandRunner answering hash and dimension queries per file
+    struct MockCommandRunner;
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            let is_large = args.iter().any(|arg| arg.starts_with("large"));
+            if args.contains(&"pgm:-") {
+                // Every image shares the same hash except "other.png"
+                let step = if args[0].starts_with("other") { 0 } else { 10 };
+                let row: Vec<String> = (0..9).map(|i| ((8 - i) * step).to_string()).collect();
+                Ok(format!(
+                    "P2\n9 8\n255\n{}\n",
+                    vec![row.join(" "); 8].join("\n")
+                ))
+            } else if is_large {
+                Ok("4000 3000".to_string())
+            } else {
+                Ok("400 300".to_string())
+            }
+        }
+    }
+
+    fn candidate(path: &str, hash: u64, pixels: u64) -> Candidate {
+        Candidate {
+            path: PathBuf::from(path),
+            hash,
+            pixels,
+            bytes: 0,
+        }
+    }
+
+    #[test]
+    fn test_group_keeps_highest_resolution() {
+        let groups = group_candidates(
+            vec![
+                candidate("small.jpg", 0b1111, 100),
+                candidate("large.jpg", 0b1110, 10_000),
+                candidate("unrelated.jpg", u64::MAX, 100),
+            ],
+            DEFAULT_DEDUPE_THRESHOLD,
+        );
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].keep, PathBuf::from("large.jpg"));
+        assert_eq!(groups[0].duplicates, vec![PathBuf::from("small.jpg")]);
+    }
+
+    #[test]
+    fn test_delete_requires_confirmed_plan() {
+        let dir = TempDir::new().unwrap();
+        for name in ["large.png", "small.png", "other.png"] {
+            fs::write(dir.path().join(name), b"").unwrap();
+        }
+        let runner = MockCommandRunner;
+        let deduper = Deduper::new(&runner, Some(dir.path()));
+
+        let dry_run = deduper
+            .run(
+                "*.png",
+                DEFAULT_DEDUPE_THRESHOLD,
+                &DedupeAction::Delete,
+                None,
+            )
+            .unwrap();
+        assert!(!dry_run.applied);
+        assert!(dir.path().join("small.png").exists());
+
+        let stale = deduper.run(
+            "*.png",
+            DEFAULT_DEDUPE_THRESHOLD,
+            &DedupeAction::Delete,
+            Some("0000000000000000"),
+        );
+        assert!(matches!(stale, Err(OperationError::InvalidParameter(_))));
+
+        let applied = deduper
+            .run(
+                "*.png",
+                DEFAULT_DEDUPE_THRESHOLD,
+                &DedupeAction::Delete,
+                Some(&dry_run.plan_id),
+            )
+            .unwrap();
+        assert!(applied.applied);
+        assert!(dir.path().join("large.png").exists());
+        assert!(dir.path().join("other.png").exists());
+        assert!(!dir.path().join("small.png").exists());
+    }
+
+    #[test]
+    fn test_move_keeps_duplicates_sharing_a_name() {
+        let dir = TempDir::new().unwrap();
+        for name in ["large.png", "a/small.png", "b/small.png"] {
+            let path = dir.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, name).unwrap();
+        }
+        let runner = MockCommandRunner;
+        let deduper = Deduper::new(&runner, Some(dir.path()));
+        let action = DedupeAction::Move(PathBuf::from("dupes"));
+
+        let dry_run = deduper
+            .run("**/*.png", DEFAULT_DEDUPE_THRESHOLD, &action, None)
+            .unwrap();
+        let applied = deduper
+            .run(
+                "**/*.png",
+                DEFAULT_DEDUPE_THRESHOLD,
+                &action,
+                Some(&dry_run.plan_id),
+            )
+            .unwrap();
+
+        assert!(applied.applied);
+        let mut moved: Vec<_> = fs::read_dir(dir.path().join("dupes"))
+            .unwrap()
+            .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect();
+        moved.sort();
+        assert_eq!(moved, vec!["a/small.png", "b/small.png"]);
+    }
+
+    #[test]
+    fn test_plan_id_is_stable() {
+        let groups = vec![DuplicateGroup {
+            keep: PathBuf::from("a.png"),
+            duplicates: vec![PathBuf::from("b.png"), PathBuf::from("c.png")],
+        }];
+        let reordered = vec![DuplicateGroup {
+            keep: PathBuf::from("a.png"),
+            duplicates: vec![PathBuf::from("c.png"), PathBuf::from("b.png")],
+        }];
+        let id = plan_id(&groups, &DedupeAction::Delete);
+        assert_eq!(id, "20abe356dd4d924f");
+        assert_eq!(id, plan_id(&reordered, &DedupeAction::Delete));
+    }
+
+    #[test]
+    fn test_plan_id_depends_on_action() {
+        let groups = vec![DuplicateGroup {
+            keep: PathBuf::from("a.png"),
+            duplicates: vec![PathBuf::from("b.png")],
+        }];
+        assert_ne!(
+            plan_id(&groups, &DedupeAction::Delete),
+            plan_id(&groups, &DedupeAction::Move(PathBuf::from("dupes")))
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use crate::feature::paths;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Expand a glob pattern into the matching files beneath a base directory
+///
+/// Supports `*` and `?` within a path segment, `[abc]` character classes, and `**` to match any
+/// number of directories. Hidden entries are only matched when the pattern segment itself starts
+/// with a dot. Names are compared in Unicode NFC, so decomposed names such as those macOS
+/// writes match the composed form typed in a pattern.
+///
+/// # Arguments
+///
+/// * `base` - Directory the pattern is relative to
+/// * `pattern` - The glob pattern, e.g. `photos/**/*.jpg`
+///
+/// # Returns
+///
+/// Returns matching file paths relative to `base`, sorted, or an IO error
+pub fn expand_glob(base: &Path, pattern: &str) -> io::Result<Vec<PathBuf>> {
+    let segments: Vec<&str> = pattern
+        .split(['/', '\\'])
+        .filter(|s| !s.is_empty() && *s != ".")
+        .collect();
+    let mut matches = Vec::new();
+    walk(base, PathBuf::new(), &segments, &mut matches)?;
+    matches.sort();
+    matches.dedup();
+    Ok(matches)
+}
+
+/// Recursively match the remaining pattern segments against `base/relative`
+fn walk(
+    base: &Path,
+    relative: PathBuf,
+    segments: &[&str],
+    matches: &mut Vec<PathBuf>,
+) -> io::Result<()> {
+    let dir = base.join(&relative);
+    let Some((segment, rest)) = segments.split_first() else {
+        if dir.is_file() {
+            matches.push(checked_path(relative)?);
+        }
+        return Ok(());
+    };
+
+    if *segment == "**" {
+        walk(base, relative.clone(), rest, matches)?;
+        for name in dir_entries(&dir)? {
+            if !name.to_string_lossy().starts_with('.') && dir.join(&name).is_dir() {
+                walk(base, relative.join(name), segments, matches)?;
+            }
+        }
+        return Ok(());
+    }
+
+    if !segment.contains(['*', '?', '[']) {
+        let next = relative.join(segment);
+        if base.join(&next).exists() {
+            walk(base, next, rest, matches)?;
+            return Ok(());
+        }
+        let segment = paths::normalize_name(segment);
+        for name in dir_entries(&dir)? {
+            if paths::normalize_name(&name.to_string_lossy()) == segment {
+                walk(base, relative.join(name), rest, matches)?;
+            }
+        }
+        return Ok(());
+    }
+
+    let pattern = paths::normalize_name(segment);
+    for name in dir_entries(&dir)? {
+        let text = name.to_string_lossy();
+        if text.starts_with('.') && !segment.starts_with('.') {
+            continue;
+        }
+        if wildcard_match(&pattern, &paths::normalize_name(&text)) {
+            walk(base, relative.join(name), rest, matches)?;
+        }
+    }
+    Ok(())
+}
+
+/// List entry names in a directory, treating a missing directory as empty
+fn dir_entries(dir: &Path) -> io::Result<Vec<OsString>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        names.push(entry?.file_name());
+    }
+    Ok(names)
+}
+
+/// Reject a matched path which is not valid Unicode
+///
+/// Such paths cannot be passed to ImageMagick intact, and replacing the invalid bytes would
+/// name a different file, so the match fails with the path instead.
+fn checked_path(path: PathBuf) -> io::Result<PathBuf> {
+    if path.to_str().is_none() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("file name '{}' is not valid Unicode", path.display()),
+        ));
+    }
+    Ok(path)
+}
+
+/// Match a single path segment against a wildcard pattern
+pub(crate) fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    match_from(&pattern, &name)
+}
+
+fn match_from(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skip| match_from(rest, &name[skip..])),
+        Some(('?', rest)) => !name.is_empty() && match_from(rest, &name[1..]),
+        Some(('[', rest)) => {
+            let Some(close) = rest.iter().position(|c| *c == ']') else {
+                return name.first() == Some(&'[') && match_from(rest, &name[1..]);
+            };
+            let Some(first) = name.first() else {
+                return false;
+            };
+            let (negate, class) = match rest[..close].split_first() {
+                Some(('!', class)) | Some(('^', class)) => (true, class),
+                _ => (false, &rest[..close]),
+            };
+            let mut found = false;
+            let mut i = 0;
+            while i < class.len() {
+                if i + 2 < class.len() && class[i + 1] == '-' {
+                    found |= (class[i]..=class[i + 2]).contains(first);
+                    i += 3;
+                } else {
+                    found |= class[i] == *first;
+                    i += 1;
+                }
+            }
+            found != negate && match_from(&rest[close + 1..], &name[1..])
+        }
+        Some((c, rest)) => name.first() == Some(c) && match_from(rest, &name[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn touch(dir: &TempDir, path: &str) {
+        let full = dir.path().join(path);
+        fs::create_dir_all(full.parent().unwrap()).unwrap();
+        fs::write(full, b"").unwrap();
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*.png", "photo.png"));
+        assert!(!wildcard_match("*.png", "photo.jpg"));
+        assert!(wildcard_match("img_??.jpg", "img_01.jpg"));
+        assert!(wildcard_match("[a-c]*", "banana"));
+        assert!(!wildcard_match("[!a-c]*", "banana"));
+    }
+
+    #[test]
+    fn test_expand_flat_pattern() {
+        let dir = TempDir::new().unwrap();
+        touch(&dir, "a.png");
+        touch(&dir, "b.png");
+        touch(&dir, "c.jpg");
+
+        let matches = expand_glob(dir.path(), "*.png").unwrap();
+        assert_eq!(
+            matches,
+            vec![PathBuf::from("a.png"), PathBuf::from("b.png")]
+        );
+    }
+
+    #[test]
+    fn test_expand_recursive_pattern() {
+        let dir = TempDir::new().unwrap();
+        touch(&dir, "top.png");
+        touch(&dir, "nested/deep/inner.png");
+        touch(&dir, ".hidden/secret.png");
+
+        let matches = expand_glob(dir.path(), "**/*.png").unwrap();
+        assert_eq!(
+            matches,
+            vec![
+                PathBuf::from("nested/deep/inner.png"),
+                PathBuf::from("top.png")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_literal_directory() {
+        let dir = TempDir::new().unwrap();
+        touch(&dir, "assets/icon.png");
+        touch(&dir, "other/icon.png");
+
+        let matches = expand_glob(dir.path(), "assets/*.png").unwrap();
+        assert_eq!(matches, vec![PathBuf::from("assets/icon.png")]);
+    }
+
+    #[test]
+    fn test_expand_matches_decomposed_names() {
+        let dir = TempDir::new().unwrap();
+        touch(&dir, "cafe\u{301}/me\u{301}nu.png");
+
+        let matches = expand_glob(dir.path(), "caf\u{e9}/m\u{e9}*.png").unwrap();
+        assert_eq!(matches, vec![PathBuf::from("cafe\u{301}/me\u{301}nu.png")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_expand_rejects_names_which_are_not_unicode() {
+        use std::os::unix::ffi::OsStrExt;
+        let dir = TempDir::new().unwrap();
+        touch(&dir, "good.png");
+        let name = std::ffi::OsStr::from_bytes(b"bad\xff.png");
+        fs::write(dir.path().join(name), b"").unwrap();
+
+        assert!(expand_glob(dir.path(), "good*.png").is_ok());
+        let error = expand_glob(dir.path(), "*.png").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,312 @@
+use crate::feature::batch::glob::expand_glob;
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::identify::{self, ImageMetadata};
+use crate::feature::shell::CommandRunner;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Longest side, in pixels, of an image classed as a thumbnail
+const THUMBNAIL_MAX_SIDE: u32 = 256;
+/// Longest side, in pixels, of an image classed as web-sized
+const WEB_MAX_SIDE: u32 = 2048;
+
+/// How images are grouped into subdirectories
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrganizeBy {
+    /// `YYYY/MM` from the EXIF capture date, or `undated`
+    Date,
+    /// `thumbnail`, `web`, or `print` based on the longest side
+    Dimensions,
+    /// Lowercase ImageMagick format name, e.g. `jpeg`
+    Format,
+}
+
+impl OrganizeBy {
+    /// Parse a grouping name
+    pub fn parse(name: &str) -> Result<Self, OperationError> {
+        match name {
+            "date" => Ok(OrganizeBy::Date),
+            "dimensions" => Ok(OrganizeBy::Dimensions),
+            "format" => Ok(OrganizeBy::Format),
+            other => Err(OperationError::InvalidParameter(format!(
+                "unknown grouping '{other}', expected date, dimensions, or format"
+            ))),
+        }
+    }
+
+    /// Subdirectory an image belongs in
+    fn directory(&self, metadata: &ImageMetadata) -> PathBuf {
+        match self {
+            OrganizeBy::Date => {
+                // Only ever build directories from digits, so metadata can't escape the destination
+                let year_month = metadata.date.as_deref().and_then(|date| {
+                    let mut parts = date.split('-');
+                    let (year, month) = (parts.next()?, parts.next()?);
+                    let digits =
+                        |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+                    (digits(year) && digits(month)).then(|| PathBuf::from(year).join(month))
+                });
+                year_month.unwrap_or_else(|| PathBuf::from("undated"))
+            }
+            OrganizeBy::Dimensions => {
+                let longest = metadata.width.max(metadata.height);
+                let class = if longest <= THUMBNAIL_MAX_SIDE {
+                    "thumbnail"
+                } else if longest <= WEB_MAX_SIDE {
+                    "web"
+                } else {
+                    "print"
+                };
+                PathBuf::from(class)
+            }
+            OrganizeBy::Format => PathBuf::from(metadata.format.to_lowercase()),
+        }
+    }
+}
+
+/// Outcome of placing a single file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlacementStatus {
+    /// The file would be placed (dry run)
+    Planned,
+    /// The file was moved
+    Moved,
+    /// The file was copied
+    Copied,
+    /// The file was left alone for the given reason
+    Skipped(String),
+}
+
+/// A single source/destination pair in an organize plan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placement {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub status: PlacementStatus,
+}
+
+/// Sorts images matching a glob into subdirectories
+pub struct Organizer<'a> {
+    magick_runner: MagickRunner<'a>,
+    base: PathBuf,
+}
+
+impl<'a> Organizer<'a> {
+    /// Create a new Organizer with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Organizer {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+            base: workspace
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(".")),
+        }
+    }
+
+    /// Plan and optionally apply the organization
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Glob pattern selecting files, relative to the workspace
+    /// * `by` - How images are grouped
+    /// * `destination` - Directory the group subdirectories are created in
+    /// * `copy` - Copy files instead of moving them
+    /// * `dry_run` - When true only the plan is returned and no files are touched
+    ///
+    /// # Returns
+    ///
+    /// Returns the plan with per-file status, or an `OperationError` on failure
+    pub fn run(
+        &self,
+        pattern: &str,
+        by: OrganizeBy,
+        destination: &Path,
+        copy: bool,
+        dry_run: bool,
+    ) -> Result<Vec<Placement>, OperationError> {
+        let files = expand_glob(&self.base, pattern)?;
+        let mut placements = Vec::new();
+        let mut targets = HashSet::new();
+        for from in files {
+            let metadata = identify::metadata(&self.magick_runner, &from.to_string_lossy())?;
+            let Some(file_name) = from.file_name() else {
+                continue;
+            };
+            let to = destination.join(by.directory(&metadata)).join(file_name);
+            self.magick_runner.check_write(&to)?;
+
+            let status = if to == from {
+                PlacementStatus::Skipped("already organized".to_string())
+            } else if !targets.insert(to.clone()) {
+                PlacementStatus::Skipped("another file has the same destination".to_string())
+            } else if self.base.join(&to).exists() {
+                PlacementStatus::Skipped("destination already exists".to_string())
+            } else if dry_run {
+                PlacementStatus::Planned
+            } else {
+                let target = self.base.join(&to);
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                if copy {
+                    fs::copy(self.base.join(&from), &target)?;
+                    PlacementStatus::Copied
+                } else {
+                    fs::rename(self.base.join(&from), &target)?;
+                    PlacementStatus::Moved
+                }
+            };
+            placements.push(Placement { from, to, status });
+        }
+        Ok(placements)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use tempfile::TempDir;
+
+    /// Mock implementation of CommandRunner returning fixed identify output
+    struct MockCommandRunner {
+        output: String,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            _args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            Ok(self.output.clone())
+        }
+    }
+
+    fn metadata(width: u32, height: u32, date: Option<&str>) -> ImageMetadata {
+        ImageMetadata {
+            width,
+            height,
+            format: "JPEG".to_string(),
+            date: date.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_directory_by_date() {
+        let by = OrganizeBy::Date;
+        assert_eq!(
+            by.directory(&metadata(10, 10, Some("2024-03-15"))),
+            PathBuf::from("2024/03")
+        );
+        assert_eq!(
+            by.directory(&metadata(10, 10, None)),
+            PathBuf::from("undated")
+        );
+        assert_eq!(
+            by.directory(&metadata(10, 10, Some("../../x-yz"))),
+            PathBuf::from("undated")
+        );
+    }
+
+    #[test]
+    fn test_directory_by_dimensions() {
+        let by = OrganizeBy::Dimensions;
+        assert_eq!(
+            by.directory(&metadata(128, 64, None)),
+            PathBuf::from("thumbnail")
+        );
+        assert_eq!(
+            by.directory(&metadata(1920, 1080, None)),
+            PathBuf::from("web")
+        );
+        assert_eq!(
+            by.directory(&metadata(6000, 4000, None)),
+            PathBuf::from("print")
+        );
+    }
+
+    #[test]
+    fn test_dry_run_reports_plan() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.jpg"), b"").unwrap();
+        let runner = MockCommandRunner {
+            output: "4000|3000|JPEG|".to_string(),
+        };
+        let organizer = Organizer::new(&runner, Some(dir.path()));
+
+        let plan = organizer
+            .run(
+                "*.jpg",
+                OrganizeBy::Format,
+                Path::new("sorted"),
+                false,
+                true,
+            )
+            .unwrap();
+
+        assert_eq!(plan[0].to, PathBuf::from("sorted/jpeg/a.jpg"));
+        assert_eq!(plan[0].status, PlacementStatus::Planned);
+        assert!(dir.path().join("a.jpg").exists());
+    }
+
+    #[test]
+    fn test_copy_keeps_original() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.jpg"), b"data").unwrap();
+        let runner = MockCommandRunner {
+            output: "100|100|JPEG|".to_string(),
+        };
+        let organizer = Organizer::new(&runner, Some(dir.path()));
+
+        let plan = organizer
+            .run("*.jpg", OrganizeBy::Dimensions, Path::new(""), true, false)
+            .unwrap();
+
+        assert_eq!(plan[0].status, PlacementStatus::Copied);
+        assert!(dir.path().join("a.jpg").exists());
+        assert!(dir.path().join("thumbnail/a.jpg").exists());
+    }
+
+    #[test]
+    fn test_destination_outside_sandbox_rejected() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.jpg"), b"data").unwrap();
+        fs::write(
+            dir.path().join(".magickmcp.toml"),
+            "sandbox_roots = [\"assets\"]\n",
+        )
+        .unwrap();
+        let runner = MockCommandRunner {
+            output: "100|100|JPEG|".to_string(),
+        };
+        let organizer = Organizer::new(&runner, Some(dir.path()));
+
+        let result = organizer.run(
+            "*.jpg",
+            OrganizeBy::Format,
+            Path::new("../sorted"),
+            false,
+            false,
+        );
+        assert!(matches!(
+            result,
+            Err(OperationError::Shell(ShellError::ProjectConfig { .. }))
+        ));
+        assert!(dir.path().join("a.jpg").exists());
+        assert!(!dir.path().join("../sorted").exists());
+
+        let plan = organizer
+            .run(
+                "*.jpg",
+                OrganizeBy::Format,
+                Path::new("assets"),
+                false,
+                false,
+            )
+            .unwrap();
+        assert_eq!(plan[0].status, PlacementStatus::Moved);
+    }
+}
@@ -0,0 +1,60 @@
+use crate::feature::trace::{current_trace_id, in_trace};
+use std::sync::Mutex;
+use std::thread;
+
+/// Run a job for every item on a pool of worker threads, keeping results in item order
+///
+/// Workers inherit the caller's trace id so commands they run are attributed to the same call.
+pub(crate) fn run_parallel<T, R, F>(items: &[T], job: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let results: Vec<Mutex<Option<R>>> = items.iter().map(|_| Mutex::new(None)).collect();
+    let next = Mutex::new(0usize);
+    let workers = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(items.len());
+
+    let trace_id = current_trace_id();
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                in_trace(trace_id.clone(), || {
+                    loop {
+                        let index = {
+                            let mut next = next.lock().unwrap_or_else(|e| e.into_inner());
+                            let index = *next;
+                            *next += 1;
+                            index
+                        };
+                        let Some(item) = items.get(index) else {
+                            break;
+                        };
+                        let result = job(item);
+                        *results[index].lock().unwrap_or_else(|e| e.into_inner()) = Some(result);
+                    }
+                })
+            });
+        }
+    });
+
+    results
+        .into_iter()
+        .filter_map(|result| result.into_inner().unwrap_or_else(|e| e.into_inner()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_parallel_keeps_order() {
+        let items: Vec<u32> = (0..50).collect();
+        let results = run_parallel(&items, |n| n * 2);
+        assert_eq!(results, (0..50).map(|n| n * 2).collect::<Vec<_>>());
+    }
+}
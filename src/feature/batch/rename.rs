@@ -0,0 +1,285 @@
+use crate::feature::batch::glob::expand_glob;
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::identify::{self, ImageMetadata};
+use crate::feature::shell::CommandRunner;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Fields which may appear in a rename template
+const TEMPLATE_FIELDS: &[&str] = &["date", "width", "height", "format", "orig", "ext", "index"];
+
+/// Outcome of a single planned rename
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameStatus {
+    /// The rename would be applied (dry run)
+    Planned,
+    /// The file was renamed
+    Renamed,
+    /// The file was left alone for the given reason
+    Skipped(String),
+}
+
+/// A single source/target pair in a rename plan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameEntry {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub status: RenameStatus,
+}
+
+/// Renames files matching a glob using a template of image metadata fields
+pub struct RenameByMetadata<'a> {
+    magick_runner: MagickRunner<'a>,
+    base: PathBuf,
+}
+
+impl<'a> RenameByMetadata<'a> {
+    /// Create a new RenameByMetadata with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        RenameByMetadata {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+            base: workspace
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(".")),
+        }
+    }
+
+    /// Plan and optionally apply renames
+    ///
+    /// The template may use `{date}`, `{width}`, `{height}`, `{format}`, `{orig}` (original file
+    /// stem), `{ext}`, and `{index}`. The original extension is appended unless the template
+    /// already includes `{ext}`.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Glob pattern selecting files, relative to the workspace
+    /// * `template` - Template for the new file stem
+    /// * `dry_run` - When true only the plan is returned and no files are touched
+    ///
+    /// # Returns
+    ///
+    /// Returns the rename plan with per-file status, or an `OperationError` on failure
+    pub fn run(
+        &self,
+        pattern: &str,
+        template: &str,
+        dry_run: bool,
+    ) -> Result<Vec<RenameEntry>, OperationError> {
+        validate_template(template)?;
+        let files = expand_glob(&self.base, pattern)?;
+
+        let mut entries = Vec::new();
+        let mut targets = HashSet::new();
+        for (index, from) in files.into_iter().enumerate() {
+            let metadata = identify::metadata(&self.magick_runner, &from.to_string_lossy())?;
+            let name = render_template(template, &from, &metadata, index + 1);
+            let to = from.with_file_name(&name);
+
+            let status = if !is_plain_file_name(&name) {
+                RenameStatus::Skipped(format!("'{name}' is not a valid file name"))
+            } else if to == from {
+                RenameStatus::Skipped("name unchanged".to_string())
+            } else if !targets.insert(to.clone()) {
+                RenameStatus::Skipped("another file renders to the same name".to_string())
+            } else if self.base.join(&to).exists() {
+                RenameStatus::Skipped("target already exists".to_string())
+            } else if dry_run {
+                self.magick_runner.check_write(&to)?;
+                RenameStatus::Planned
+            } else {
+                self.magick_runner.check_write(&to)?;
+                fs::rename(self.base.join(&from), self.base.join(&to))?;
+                RenameStatus::Renamed
+            };
+            entries.push(RenameEntry { from, to, status });
+        }
+        Ok(entries)
+    }
+}
+
+/// Ensure a template only references known fields
+fn validate_template(template: &str) -> Result<(), OperationError> {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}').ok_or_else(|| {
+            OperationError::InvalidParameter(format!("unclosed '{{' in template '{template}'"))
+        })?;
+        let field = &rest[start + 1..start + end];
+        if !TEMPLATE_FIELDS.contains(&field) {
+            return Err(OperationError::InvalidParameter(format!(
+                "unknown template field '{{{field}}}', expected one of {}",
+                TEMPLATE_FIELDS.join(", ")
+            )));
+        }
+        rest = &rest[start + end + 1..];
+    }
+    Ok(())
+}
+
+/// Whether a rendered name is a single file name, so renaming can't move the file elsewhere
+fn is_plain_file_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(component)), None) if component == name
+    )
+}
+
+/// Render a template into a file name for one file
+fn render_template(template: &str, from: &Path, metadata: &ImageMetadata, index: usize) -> String {
+    let stem = from
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = from
+        .extension()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let rendered = template
+        .replace("{date}", metadata.date.as_deref().unwrap_or("undated"))
+        .replace("{width}", &metadata.width.to_string())
+        .replace("{height}", &metadata.height.to_string())
+        .replace("{format}", &metadata.format.to_lowercase())
+        .replace("{orig}", &stem)
+        .replace("{index}", &format!("{index:03}"))
+        .replace("{ext}", &ext);
+    let sanitized: String = rendered
+        .chars()
+        .map(|c| {
+            if matches!(c, '/' | '\\' | ':') {
+                '-'
+            } else {
+                c
+            }
+        })
+        .collect();
+    if template.contains("{ext}") || ext.is_empty() {
+        sanitized
+    } else {
+        format!("{sanitized}.{ext}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use tempfile::TempDir;
+
+    /// Mock implementation of CommandRunner returning fixed identify output
+    struct MockCommandRunner {
+        output: String,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            _args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            Ok(self.output.clone())
+        }
+    }
+
+    fn metadata() -> ImageMetadata {
+        ImageMetadata {
+            width: 800,
+            height: 600,
+            format: "JPEG".to_string(),
+            date: Some("2024-03-15".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_render_template() {
+        let name = render_template(
+            "{date}_{width}x{height}_{orig}",
+            Path::new("IMG_0001.JPG"),
+            &metadata(),
+            1,
+        );
+        assert_eq!(name, "2024-03-15_800x600_IMG_0001.jpg");
+    }
+
+    #[test]
+    fn test_render_template_with_explicit_ext() {
+        let name = render_template("{index}.{ext}", Path::new("a.png"), &metadata(), 7);
+        assert_eq!(name, "007.png");
+    }
+
+    #[test]
+    fn test_validate_template() {
+        assert!(validate_template("{date}_{orig}").is_ok());
+        assert!(validate_template("{camera}").is_err());
+        assert!(validate_template("{date").is_err());
+    }
+
+    #[test]
+    fn test_rendered_name_cannot_leave_the_directory() {
+        assert!(is_plain_file_name("2024-03-15_a.jpg"));
+        assert!(!is_plain_file_name(".."));
+        assert!(!is_plain_file_name("."));
+        assert!(!is_plain_file_name(""));
+        assert!(!is_plain_file_name("../a.jpg"));
+
+        let name = render_template("{format}", Path::new("photos/a"), &metadata(), 1);
+        assert_eq!(name, "jpeg");
+        let mut dotted = metadata();
+        dotted.format = "..".to_string();
+        assert_eq!(
+            render_template("{format}", Path::new("photos/a"), &dotted, 1),
+            ".."
+        );
+
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("photos")).unwrap();
+        fs::write(dir.path().join("photos/a"), b"").unwrap();
+        let runner = MockCommandRunner {
+            output: "800|600|..|".to_string(),
+        };
+        let renamer = RenameByMetadata::new(&runner, Some(dir.path()));
+
+        let entries = renamer.run("photos/*", "{format}", false).unwrap();
+
+        assert!(matches!(entries[0].status, RenameStatus::Skipped(_)));
+        assert!(dir.path().join("photos/a").exists());
+    }
+
+    #[test]
+    fn test_dry_run_does_not_rename() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.jpg"), b"").unwrap();
+        let runner = MockCommandRunner {
+            output: "800|600|JPEG|2024:03:15 10:22:01".to_string(),
+        };
+        let renamer = RenameByMetadata::new(&runner, Some(dir.path()));
+
+        let entries = renamer.run("*.jpg", "{date}_{orig}", true).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].to, PathBuf::from("2024-03-15_a.jpg"));
+        assert_eq!(entries[0].status, RenameStatus::Planned);
+        assert!(dir.path().join("a.jpg").exists());
+    }
+
+    #[test]
+    fn test_apply_renames_and_skips_collisions() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.jpg"), b"").unwrap();
+        fs::write(dir.path().join("b.jpg"), b"").unwrap();
+        let runner = MockCommandRunner {
+            output: "800|600|JPEG|".to_string(),
+        };
+        let renamer = RenameByMetadata::new(&runner, Some(dir.path()));
+
+        let entries = renamer.run("*.jpg", "{width}x{height}", false).unwrap();
+
+        assert_eq!(entries[0].status, RenameStatus::Renamed);
+        assert!(matches!(entries[1].status, RenameStatus::Skipped(_)));
+        assert!(dir.path().join("800x600.jpg").exists());
+        assert!(dir.path().join("b.jpg").exists());
+    }
+}
@@ -0,0 +1,180 @@
+use crate::feature::batch::glob::expand_glob;
+use crate::feature::batch::parallel::run_parallel;
+use crate::feature::ops::{OperationError, Watermark, WatermarkOptions};
+use crate::feature::output::OutputPolicy;
+use crate::feature::shell::CommandRunner;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Outcome of watermarking a single file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatermarkFileResult {
+    pub input: PathBuf,
+    /// The path written, which differs from the requested one when renamed on conflict
+    pub output: PathBuf,
+    /// Whether the file was skipped because the output already existed
+    pub skipped: bool,
+    /// The failure message, if the file could not be watermarked
+    pub error: Option<String>,
+}
+
+/// Applies a logo watermark to every image matching a glob, in parallel
+pub struct WatermarkBatch<'a> {
+    command_runner: &'a (dyn CommandRunner + Sync),
+    workspace: Option<&'a Path>,
+    output_policy: OutputPolicy,
+}
+
+impl<'a> WatermarkBatch<'a> {
+    /// Create a new WatermarkBatch with the provided CommandRunner and optional workspace path
+    pub fn new(
+        command_runner: &'a (dyn CommandRunner + Sync),
+        workspace: Option<&'a Path>,
+    ) -> Self {
+        WatermarkBatch {
+            command_runner,
+            workspace,
+            output_policy: OutputPolicy::default(),
+        }
+    }
+
+    /// Use the provided policy for each output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.output_policy = output_policy;
+        self
+    }
+
+    /// Watermark every matching image into the output directory
+    ///
+    /// Files keep their names in the output directory. A failure on one file is reported in its
+    /// result and does not stop the rest of the batch.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Glob pattern selecting files, relative to the workspace
+    /// * `output_dir` - Directory the watermarked copies are written to
+    /// * `template` - Logo, gravity, opacity, and margin; input and output are ignored
+    ///
+    /// # Returns
+    ///
+    /// Returns per-file results in glob order, or an `OperationError` if the batch could not start
+    pub fn run(
+        &self,
+        pattern: &str,
+        output_dir: &Path,
+        template: &WatermarkOptions,
+    ) -> Result<Vec<WatermarkFileResult>, OperationError> {
+        let base = self.workspace.unwrap_or(Path::new("."));
+        let inputs = expand_glob(base, pattern)?;
+        fs::create_dir_all(base.join(output_dir))?;
+
+        let mut seen = HashSet::new();
+        let jobs: Vec<(PathBuf, PathBuf, bool)> = inputs
+            .into_iter()
+            .map(|input| {
+                let output = output_dir.join(input.file_name().unwrap_or_default());
+                let unique = seen.insert(output.clone());
+                (input, output, unique)
+            })
+            .collect();
+
+        Ok(run_parallel(&jobs, |(input, output, unique)| {
+            self.watermark_file(input, output, *unique, template)
+        }))
+    }
+
+    /// Watermark a single file, capturing any failure in the result
+    fn watermark_file(
+        &self,
+        input: &Path,
+        output: &Path,
+        unique: bool,
+        template: &WatermarkOptions,
+    ) -> WatermarkFileResult {
+        let mut result = WatermarkFileResult {
+            input: input.to_path_buf(),
+            output: output.to_path_buf(),
+            skipped: false,
+            error: None,
+        };
+        if !unique {
+            result.error = Some("another file has the same output name".to_string());
+            return result;
+        }
+        let mut options = template.clone();
+        options.input = input.to_string_lossy().to_string();
+        options.output = output.to_string_lossy().to_string();
+        match Watermark::new(self.command_runner, self.workspace)
+            .with_output_policy(self.output_policy.clone())
+            .run(&options)
+        {
+            Ok(resolved) => {
+                result.output = resolved.path;
+                result.skipped = resolved.skipped;
+            }
+            Err(e) => result.error = Some(e.to_string()),
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    /// Mock implementation of CommandRunner which records outputs and fails for "bad" inputs
+    struct MockCommandRunner {
+        outputs: Mutex<Vec<String>>,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            if args[0].starts_with("bad") {
+                return Err(ShellError::ExecutionFailed {
+                    message: "corrupt image".to_string(),
+                    command: command.to_string(),
+                    args: args.join(" "),
+                });
+            }
+            self.outputs
+                .lock()
+                .unwrap()
+                .push(args.last().unwrap().to_string());
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_batch_reports_per_file_results() {
+        let dir = TempDir::new().unwrap();
+        for name in ["a.jpg", "b.jpg", "bad.jpg"] {
+            fs::write(dir.path().join(name), b"").unwrap();
+        }
+        let runner = MockCommandRunner {
+            outputs: Mutex::new(Vec::new()),
+        };
+        let batch = WatermarkBatch::new(&runner, Some(dir.path()));
+        let template = WatermarkOptions::new("", "", "logo.png");
+
+        let results = batch.run("*.jpg", Path::new("out"), &template).unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].output, PathBuf::from("out/a.jpg"));
+        assert!(results[0].error.is_none());
+        assert!(results[2].error.is_some());
+        assert!(dir.path().join("out").is_dir());
+
+        let mut outputs = runner.outputs.lock().unwrap().clone();
+        outputs.sort();
+        assert_eq!(outputs, vec!["out/a.jpg", "out/b.jpg"]);
+    }
+}
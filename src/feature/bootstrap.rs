@@ -0,0 +1,404 @@
+use crate::feature::check::{CheckReport, MagickChecker};
+use crate::feature::functions::Function;
+use crate::feature::shell::CommandRunner;
+use crate::feature::which::WhichChecker;
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// Package managers ImageMagick can be installed with non-interactively
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageManager {
+    Brew,
+    Apt,
+    Dnf,
+    Apk,
+    Winget,
+}
+
+impl PackageManager {
+    /// Package managers to look for on the current platform, in order of preference
+    fn candidates() -> &'static [PackageManager] {
+        match std::env::consts::OS {
+            "macos" => &[PackageManager::Brew],
+            "windows" => &[PackageManager::Winget],
+            _ => &[
+                PackageManager::Apt,
+                PackageManager::Dnf,
+                PackageManager::Apk,
+                PackageManager::Brew,
+            ],
+        }
+    }
+
+    /// The binary which has to be on the PATH for this package manager
+    fn binary(&self) -> &'static str {
+        match self {
+            PackageManager::Brew => "brew",
+            PackageManager::Apt => "apt-get",
+            PackageManager::Dnf => "dnf",
+            PackageManager::Apk => "apk",
+            PackageManager::Winget => "winget",
+        }
+    }
+
+    /// Find the first supported package manager on the PATH
+    pub fn detect(which_checker: &dyn WhichChecker) -> Option<PackageManager> {
+        Self::candidates()
+            .iter()
+            .copied()
+            .find(|manager| which_checker.find(manager.binary()).is_ok())
+    }
+
+    /// Commands which install ImageMagick without prompting
+    ///
+    /// # Arguments
+    ///
+    /// * `sudo` - Whether to run system package managers through `sudo -n`
+    pub fn install_commands(&self, sudo: bool) -> Vec<Vec<String>> {
+        let commands: Vec<Vec<&str>> = match self {
+            PackageManager::Brew => vec![vec!["brew", "install", "imagemagick"]],
+            PackageManager::Apt => vec![
+                vec!["apt-get", "update"],
+                vec![
+                    "apt-get",
+                    "install",
+                    "-y",
+                    "--no-install-recommends",
+                    "imagemagick",
+                ],
+            ],
+            PackageManager::Dnf => vec![vec!["dnf", "install", "-y", "ImageMagick"]],
+            PackageManager::Apk => vec![vec!["apk", "add", "--no-cache", "imagemagick"]],
+            PackageManager::Winget => vec![vec![
+                "winget",
+                "install",
+                "--id",
+                "ImageMagick.Q16-HDRI",
+                "--exact",
+                "--accept-package-agreements",
+                "--accept-source-agreements",
+                "--disable-interactivity",
+            ]],
+        };
+        let system = matches!(
+            self,
+            PackageManager::Apt | PackageManager::Dnf | PackageManager::Apk
+        );
+        commands
+            .into_iter()
+            .map(|command| {
+                let prefix: &[&str] = if sudo && system { &["sudo", "-n"] } else { &[] };
+                prefix
+                    .iter()
+                    .chain(command.iter())
+                    .map(|s| s.to_string())
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Work out which delegates the commands of a function need
+///
+/// Looks at the file extensions and format prefixes of the arguments, plus text options which need
+/// FreeType.
+pub fn required_delegates(function: &Function) -> BTreeSet<String> {
+    let mut delegates = BTreeSet::new();
+    for token in function.commands.iter().flat_map(|c| c.split_whitespace()) {
+        let token = token.trim_matches(|c| c == '"' || c == '\'');
+        if matches!(token, "-annotate" | "-font" | "-pointsize")
+            || token.starts_with("label:")
+            || token.starts_with("caption:")
+        {
+            delegates.insert("freetype".to_string());
+            continue;
+        }
+        let format = match token.split_once(':') {
+            Some((prefix, _)) if !prefix.is_empty() && !prefix.contains(['/', '\\', '.']) => prefix,
+            _ => token.rsplit_once('.').map_or("", |(_, ext)| ext),
+        };
+        if let Some(delegate) = format_delegate(&format.to_ascii_lowercase()) {
+            delegates.insert(delegate.to_string());
+        }
+    }
+    delegates
+}
+
+/// The delegate library ImageMagick needs for a format, if any
+fn format_delegate(format: &str) -> Option<&'static str> {
+    match format {
+        "png" => Some("png"),
+        "jpg" | "jpeg" => Some("jpeg"),
+        "webp" => Some("webp"),
+        "tif" | "tiff" => Some("tiff"),
+        "heic" | "heif" | "avif" => Some("heic"),
+        "jxl" => Some("jxl"),
+        _ => None,
+    }
+}
+
+/// Typed result of bootstrapping ImageMagick on a machine
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BootstrapReport {
+    /// Whether ImageMagick is installed with every required delegate
+    pub ok: bool,
+    /// The package manager which was detected, if any
+    pub package_manager: Option<PackageManager>,
+    /// Install commands which were run, or would be run when not installing
+    pub commands: Vec<String>,
+    /// The ImageMagick installation after bootstrapping
+    pub magick: CheckReport,
+    /// Delegates the requested functions need
+    pub required_delegates: Vec<String>,
+    /// Required delegates the installation lacks
+    pub missing_delegates: Vec<String>,
+    /// Why the requirements could not be met
+    pub error: Option<String>,
+}
+
+/// Installs ImageMagick through the platform package manager and verifies required delegates
+pub struct Bootstrapper<'a> {
+    which_checker: &'a dyn WhichChecker,
+    command_runner: &'a dyn CommandRunner,
+    required_delegates: BTreeSet<String>,
+    install: bool,
+}
+
+impl<'a> Bootstrapper<'a> {
+    /// Create a new Bootstrapper which only reports what it would install
+    pub fn new(which_checker: &'a dyn WhichChecker, command_runner: &'a dyn CommandRunner) -> Self {
+        Bootstrapper {
+            which_checker,
+            command_runner,
+            required_delegates: BTreeSet::new(),
+            install: false,
+        }
+    }
+
+    /// Require the provided delegates, e.g. `webp` or `freetype`
+    pub fn with_required_delegates(mut self, delegates: impl IntoIterator<Item = String>) -> Self {
+        self.required_delegates.extend(delegates);
+        self
+    }
+
+    /// Install ImageMagick when it is missing instead of only reporting the commands
+    pub fn with_install(mut self, install: bool) -> Self {
+        self.install = install;
+        self
+    }
+
+    /// Install ImageMagick if needed and check the required delegates
+    pub fn run(&self) -> BootstrapReport {
+        let checker = MagickChecker::new(self.which_checker, self.command_runner);
+        let mut report = BootstrapReport {
+            magick: checker.report(),
+            required_delegates: self.required_delegates.iter().cloned().collect(),
+            ..BootstrapReport::default()
+        };
+
+        if !report.magick.installed {
+            report.package_manager = PackageManager::detect(self.which_checker);
+            let Some(manager) = report.package_manager else {
+                report.error = Some("No supported package manager was found".to_string());
+                return report;
+            };
+            let sudo = self.which_checker.find("sudo").is_ok();
+            let commands = manager.install_commands(sudo);
+            report.commands = commands.iter().map(|c| c.join(" ")).collect();
+            if !self.install {
+                report.error = Some("ImageMagick is not installed".to_string());
+                return report;
+            }
+            if let Err(e) = self.run_commands(&commands) {
+                report.error = Some(format!("Failed to install ImageMagick: {e}"));
+                return report;
+            }
+            report.magick = checker.report();
+            if !report.magick.installed {
+                report.error = Some(
+                    "ImageMagick was installed but `magick` is not on the PATH. The package may only provide ImageMagick 6"
+                        .to_string(),
+                );
+                return report;
+            }
+        }
+
+        report.missing_delegates = self
+            .required_delegates
+            .iter()
+            .filter(|delegate| !report.magick.delegates.contains(delegate))
+            .cloned()
+            .collect();
+        if !report.missing_delegates.is_empty() {
+            report.error = Some(format!(
+                "ImageMagick is missing required delegates: {}",
+                report.missing_delegates.join(" ")
+            ));
+            return report;
+        }
+        report.ok = report.magick.error.is_none();
+        report.error = report.magick.error.clone();
+        report
+    }
+
+    /// Run install commands in order, stopping at the first failure
+    fn run_commands(&self, commands: &[Vec<String>]) -> Result<(), String> {
+        for command in commands {
+            let args: Vec<&str> = command[1..].iter().map(String::as_str).collect();
+            self.command_runner
+                .execute(&command[0], &args, None)
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use crate::feature::which::WhichError;
+    use std::cell::{Cell, RefCell};
+    use std::path::PathBuf;
+
+    /// Mock implementation of WhichChecker where `magick` appears once installed
+    struct MockWhichChecker {
+        available: Vec<&'static str>,
+        installed: Cell<bool>,
+    }
+
+    impl WhichChecker for MockWhichChecker {
+        fn find(&self, command: &str) -> Result<PathBuf, WhichError> {
+            if self.available.contains(&command) || (command == "magick" && self.installed.get()) {
+                Ok(PathBuf::from("/usr/bin").join(command))
+            } else {
+                Err(WhichError::NotFound(command.to_string()))
+            }
+        }
+    }
+
+    /// Mock implementation of CommandRunner which installs ImageMagick with a few delegates
+    struct MockCommandRunner<'a> {
+        which_checker: &'a MockWhichChecker,
+        calls: RefCell<Vec<String>>,
+    }
+
+    impl CommandRunner for MockCommandRunner<'_> {
+        fn execute(
+            &self,
+            command: &str,
+            args: &[&str],
+            _working_dir: Option<&std::path::Path>,
+        ) -> Result<String, ShellError> {
+            if command == "magick" {
+                return Ok("Version: ImageMagick 7.1.2-8 Q16-HDRI\n\
+                           Delegates (built-in): freetype jpeg png\n"
+                    .to_string());
+            }
+            self.calls
+                .borrow_mut()
+                .push(format!("{command} {}", args.join(" ")));
+            self.which_checker.installed.set(true);
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_required_delegates() {
+        let function = Function {
+            name: "web".to_string(),
+            commands: vec![
+                "$input -resize 50% half.jpg".to_string(),
+                "half.jpg -font Arial -annotate +10+10 hi webp:out".to_string(),
+                "$input -negate photos/out.v2.TIFF".to_string(),
+            ],
+            params: Default::default(),
+        };
+
+        let delegates: Vec<String> = required_delegates(&function).into_iter().collect();
+
+        assert_eq!(delegates, vec!["freetype", "jpeg", "tiff", "webp"]);
+    }
+
+    #[test]
+    fn test_install_commands_use_sudo_for_system_managers() {
+        assert_eq!(
+            PackageManager::Dnf.install_commands(true),
+            vec![vec!["sudo", "-n", "dnf", "install", "-y", "ImageMagick"]]
+        );
+        assert_eq!(
+            PackageManager::Brew.install_commands(true),
+            vec![vec!["brew", "install", "imagemagick"]]
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_run_installs_and_reports_missing_delegates() {
+        let which_checker = MockWhichChecker {
+            available: vec!["apt-get"],
+            installed: Cell::new(false),
+        };
+        let runner = MockCommandRunner {
+            which_checker: &which_checker,
+            calls: RefCell::new(Vec::new()),
+        };
+
+        let report = Bootstrapper::new(&which_checker, &runner)
+            .with_required_delegates(["png".to_string(), "webp".to_string()])
+            .with_install(true)
+            .run();
+
+        assert!(!report.ok);
+        assert_eq!(report.package_manager, Some(PackageManager::Apt));
+        assert!(report.magick.installed);
+        assert_eq!(report.missing_delegates, vec!["webp"]);
+        assert_eq!(
+            *runner.calls.borrow(),
+            vec![
+                "apt-get update",
+                "apt-get install -y --no-install-recommends imagemagick"
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_run_without_install_only_reports_commands() {
+        let which_checker = MockWhichChecker {
+            available: vec!["dnf", "sudo"],
+            installed: Cell::new(false),
+        };
+        let runner = MockCommandRunner {
+            which_checker: &which_checker,
+            calls: RefCell::new(Vec::new()),
+        };
+
+        let report = Bootstrapper::new(&which_checker, &runner).run();
+
+        assert!(!report.ok);
+        assert_eq!(report.commands, vec!["sudo -n dnf install -y ImageMagick"]);
+        assert!(runner.calls.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_run_when_already_installed() {
+        let which_checker = MockWhichChecker {
+            available: vec![],
+            installed: Cell::new(true),
+        };
+        let runner = MockCommandRunner {
+            which_checker: &which_checker,
+            calls: RefCell::new(Vec::new()),
+        };
+
+        let report = Bootstrapper::new(&which_checker, &runner)
+            .with_required_delegates(["jpeg".to_string()])
+            .run();
+
+        assert!(report.ok);
+        assert!(report.commands.is_empty());
+        assert_eq!(report.error, None);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::feature::data_dir::{DataDirLock, data_dir};
+use crate::feature::output;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Size of the build cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    /// Number of cached outputs
+    pub entries: u64,
+    /// Total size of the cached outputs in bytes
+    pub bytes: u64,
+}
+
+/// Cache of command outputs keyed by the content of their inputs and the command
+///
+/// Keys ignore file names, so renamed inputs and identical transforms in other workspaces reuse
+/// the same output. The cache lives in the data directory and is shared by every workspace.
+pub struct BuildCache {
+    dir: Option<PathBuf>,
+}
+
+impl BuildCache {
+    /// Create a cache in the `cache` directory of the data directory
+    pub fn new() -> Self {
+        Self::at(data_dir().map(|dir| dir.join("cache")))
+    }
+
+    fn at(dir: Option<PathBuf>) -> Self {
+        BuildCache { dir }
+    }
+
+    /// The cache key for a command, or `None` when it reads no input files or writes a frame
+    /// pattern such as `out-%d.png`
+    ///
+    /// Input and output paths are replaced by placeholders and each input is identified by the
+    /// SHA-256 of its contents, along with the output format.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The command arguments, whose last argument is the output
+    /// * `base` - Directory the paths in the command are relative to
+    pub(crate) fn key(args: &[&str], base: &Path) -> Option<String> {
+        let (output, inputs) = args.split_last()?;
+        if output.contains('%') {
+            return None;
+        }
+        let mut hasher = Sha256::new();
+        let mut has_input = false;
+        for arg in inputs {
+            let (file, frames) = arg.split_at(arg.find('[').unwrap_or(arg.len()));
+            let contents = (!arg.starts_with('-'))
+                .then(|| fs::read(base.join(file)).ok())
+                .flatten();
+            match contents {
+                Some(contents) => {
+                    has_input = true;
+                    hasher.update(b"$input");
+                    hasher.update(Sha256::digest(contents));
+                    hasher.update(frames);
+                }
+                None => hasher.update(arg),
+            }
+            hasher.update([0]);
+        }
+        hasher.update(b"$output.");
+        hasher.update(output::output_format(output).unwrap_or_default());
+        has_input.then(|| hex(&hasher.finalize()))
+    }
+
+    /// Copy a cached output to `output`
+    ///
+    /// # Returns
+    ///
+    /// Returns whether the key was cached, or an `io::Error` if the copy fails
+    pub(crate) fn restore(&self, key: &str, output: &Path) -> io::Result<bool> {
+        let Some(dir) = &self.dir else {
+            return Ok(false);
+        };
+        let entry = dir.join(key);
+        if !entry.is_file() {
+            return Ok(false);
+        }
+        fs::copy(entry, output)?;
+        Ok(true)
+    }
+
+    /// Whether `output` already has the contents cached for the key
+    pub(crate) fn holds(&self, key: &str, output: &Path) -> bool {
+        let Some(dir) = &self.dir else {
+            return false;
+        };
+        match (fs::read(dir.join(key)), fs::read(output)) {
+            (Ok(cached), Ok(existing)) => cached == existing,
+            _ => false,
+        }
+    }
+
+    /// Copy a freshly written output into the cache
+    pub(crate) fn store(&self, key: &str, output: &Path) -> io::Result<()> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        let _lock = DataDirLock::acquire(dir)?;
+        fs::copy(output, dir.join(key))?;
+        Ok(())
+    }
+
+    /// Count the cached outputs and their size
+    pub fn stats(&self) -> io::Result<CacheStats> {
+        let mut stats = CacheStats::default();
+        for path in self.entries()? {
+            stats.entries += 1;
+            stats.bytes += fs::metadata(path)?.len();
+        }
+        Ok(stats)
+    }
+
+    /// Remove every cached output
+    ///
+    /// # Returns
+    ///
+    /// Returns the size of what was removed, or an `io::Error` on failure
+    pub fn clear(&self) -> io::Result<CacheStats> {
+        let Some(dir) = &self.dir else {
+            return Ok(CacheStats::default());
+        };
+        let _lock = DataDirLock::acquire(dir)?;
+        let stats = self.stats()?;
+        for path in self.entries()? {
+            fs::remove_file(path)?;
+        }
+        Ok(stats)
+    }
+
+    /// Paths of the cached outputs, skipping the lock file
+    fn entries(&self) -> io::Result<Vec<PathBuf>> {
+        let Some(dir) = self.dir.as_ref().filter(|dir| dir.is_dir()) else {
+            return Ok(Vec::new());
+        };
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let hidden = path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+            if path.is_file() && !hidden {
+                entries.push(path);
+            }
+        }
+        Ok(entries)
+    }
+}
+
+impl Default for BuildCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_key_ignores_file_names() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.png"), b"pixels").unwrap();
+        fs::write(dir.path().join("b.png"), b"pixels").unwrap();
+        fs::write(dir.path().join("c.png"), b"other").unwrap();
+
+        let key = BuildCache::key(&["a.png", "-negate", "out.png"], dir.path());
+        assert!(key.is_some());
+        assert_eq!(
+            key,
+            BuildCache::key(&["b.png", "-negate", "renamed.png"], dir.path())
+        );
+        assert_ne!(
+            key,
+            BuildCache::key(&["c.png", "-negate", "out.png"], dir.path())
+        );
+        assert_ne!(
+            key,
+            BuildCache::key(&["a.png", "-negate", "out.webp"], dir.path())
+        );
+        assert_ne!(
+            key,
+            BuildCache::key(&["a.png", "-flip", "out.png"], dir.path())
+        );
+        assert_eq!(BuildCache::key(&["xc:red", "out.png"], dir.path()), None);
+        assert_eq!(
+            BuildCache::key(&["a.png", "frame-%d.png"], dir.path()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_store_restore_and_clear() {
+        let dir = TempDir::new().unwrap();
+        let cache = BuildCache::at(Some(dir.path().join("cache")));
+        let output = dir.path().join("out.png");
+        fs::write(&output, b"result").unwrap();
+
+        assert!(!cache.restore("key", &output).unwrap());
+        cache.store("key", &output).unwrap();
+        assert_eq!(
+            cache.stats().unwrap(),
+            CacheStats {
+                entries: 1,
+                bytes: 6
+            }
+        );
+
+        let restored = dir.path().join("restored.png");
+        assert!(!cache.holds("key", &restored));
+        assert!(cache.restore("key", &restored).unwrap());
+        assert!(cache.holds("key", &restored));
+        assert_eq!(fs::read(restored).unwrap(), b"result");
+
+        assert_eq!(cache.clear().unwrap().entries, 1);
+        assert_eq!(cache.stats().unwrap(), CacheStats::default());
+    }
+}
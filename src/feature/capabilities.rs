@@ -0,0 +1,181 @@
+use crate::feature::check::{CheckReport, MagickChecker};
+use crate::feature::config::{Config, LimitsConfig};
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::list_formats;
+use crate::feature::shell::CommandRunner;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Names of the formats the installed ImageMagick can read and write
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FormatSupport {
+    pub readable: Vec<String>,
+    pub writable: Vec<String>,
+}
+
+/// The parts of magick-mcp's own config which affect what commands may do
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ConfigCapabilities {
+    /// Cost thresholds above which commands are warned about or refused
+    pub limits: LimitsConfig,
+    /// Command run in place of `magick`
+    pub magick_command: Option<String>,
+}
+
+/// Everything an agent needs to plan work against this installation, in one document
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CapabilityReport {
+    /// The ImageMagick version, path, and built-in delegates
+    pub magick: CheckReport,
+    pub formats: FormatSupport,
+    /// Resource limits ImageMagick enforces, from `policy.xml` and the environment, keyed by
+    /// resource, e.g. `memory` to `2GiB`
+    pub resource_limits: BTreeMap<String, String>,
+    pub config: ConfigCapabilities,
+    /// Why parts of the report could not be filled in
+    pub errors: Vec<String>,
+}
+
+/// Collects the capabilities of the ImageMagick installation and magick-mcp's config
+pub struct Capabilities<'a> {
+    checker: MagickChecker<'a>,
+    magick_runner: MagickRunner<'a>,
+    config: Config,
+}
+
+impl<'a> Capabilities<'a> {
+    /// Create a new Capabilities which checks ImageMagick with the provided checker and runner
+    pub fn new(
+        checker: MagickChecker<'a>,
+        command_runner: &'a dyn CommandRunner,
+        config: Config,
+    ) -> Self {
+        Capabilities {
+            checker,
+            magick_runner: MagickRunner::new(command_runner, None),
+            config,
+        }
+    }
+
+    /// Collect the report
+    ///
+    /// Formats and resource limits are only queried when ImageMagick is installed. Failures to
+    /// query them are listed in `errors` rather than failing the whole report.
+    pub fn run(&self) -> CapabilityReport {
+        let magick = self.checker.report();
+        let mut report = CapabilityReport {
+            formats: FormatSupport::default(),
+            resource_limits: BTreeMap::new(),
+            config: ConfigCapabilities {
+                limits: self.config.limits.clone(),
+                magick_command: self.config.magick.command.clone(),
+            },
+            errors: Vec::new(),
+            magick,
+        };
+        if !report.magick.installed {
+            return report;
+        }
+
+        match list_formats(&self.magick_runner) {
+            Ok(formats) => {
+                for format in formats {
+                    if format.readable {
+                        report.formats.readable.push(format.name.clone());
+                    }
+                    if format.writable {
+                        report.formats.writable.push(format.name);
+                    }
+                }
+            }
+            Err(e) => report.errors.push(format!("failed to list formats: {e}")),
+        }
+        match self.magick_runner.execute_args(&["-list", "resource"]) {
+            Ok(output) => report.resource_limits = parse_resource_limits(&output),
+            Err(e) => report
+                .errors
+                .push(format!("failed to list resource limits: {e}")),
+        }
+        report
+    }
+}
+
+/// Parse the output of `magick -list resource` into limits keyed by snake case resource name
+fn parse_resource_limits(output: &str) -> BTreeMap<String, String> {
+    output
+        .lines()
+        .filter(|line| line.starts_with(char::is_whitespace))
+        .filter_map(|line| line.trim().split_once(':'))
+        .map(|(name, limit)| {
+            (
+                name.trim().to_ascii_lowercase().replace(' ', "_"),
+                limit.trim().to_string(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use crate::feature::which::{WhichChecker, WhichError};
+    use std::path::{Path, PathBuf};
+
+    struct MockWhichChecker;
+
+    impl WhichChecker for MockWhichChecker {
+        fn find(&self, _command: &str) -> Result<PathBuf, WhichError> {
+            Ok(PathBuf::from("/usr/bin/magick"))
+        }
+    }
+
+    struct MockCommandRunner;
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            Ok(match args {
+                ["--version"] => "Version: ImageMagick 7.1.1-43 Q16-HDRI\n\
+                                  Delegates (built-in): jpeg png webp\n"
+                    .to_string(),
+                ["-list", "format"] => "   Format  Mode  Description\n\
+                    -------------------------------\n\
+                    \x20     PDF* PDF       r--   Portable Document Format\n\
+                    \x20     PNG* PNG       rw-   Portable Network Graphics\n"
+                    .to_string(),
+                ["-list", "resource"] => {
+                    "Resource limits:\n  Width: 16KP\n  Memory: 2GiB\n  List length: unlimited\n"
+                        .to_string()
+                }
+                _ => String::new(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_report_collects_formats_and_limits() {
+        let runner = MockCommandRunner;
+        let mut config = Config::default();
+        config.limits.max_memory_mb = Some(512);
+
+        let report = Capabilities::new(
+            MagickChecker::new(&MockWhichChecker, &runner),
+            &runner,
+            config,
+        )
+        .run();
+
+        assert_eq!(report.magick.version.as_deref(), Some("7.1.1-43"));
+        assert_eq!(report.formats.readable, ["PDF", "PNG"]);
+        assert_eq!(report.formats.writable, ["PNG"]);
+        assert_eq!(report.resource_limits["memory"], "2GiB");
+        assert_eq!(report.resource_limits["list_length"], "unlimited");
+        assert_eq!(report.config.limits.max_memory_mb, Some(512));
+        assert!(report.errors.is_empty());
+    }
+}
@@ -1,10 +1,131 @@
+use crate::feature::i18n::{Language, Message};
 use crate::feature::shell::CommandRunner;
 use crate::feature::which::WhichChecker;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Names Ghostscript's command line program is installed under, in order of preference
+const GHOSTSCRIPT_COMMANDS: &[&str] = if cfg!(windows) {
+    &["gswin64c", "gswin32c", "gs"]
+} else {
+    &["gs"]
+};
+
+/// Tool managing a project's environment, which may provide `magick` only inside its shell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ManagedEnvironment {
+    /// A devenv project, configured by `devenv.nix`
+    Devenv,
+    /// A Nix flake with a development shell, configured by `flake.nix`
+    NixFlake,
+    /// A `nix-shell` environment, configured by `shell.nix` or `default.nix`
+    Nix,
+    /// Tool versions managed by asdf, listed in `.tool-versions`
+    Asdf,
+}
+
+impl ManagedEnvironment {
+    /// Detect the environment of a project from its manifest files
+    ///
+    /// The more specific tools are checked first, since devenv projects usually also have a
+    /// `flake.nix`.
+    ///
+    /// # Arguments
+    ///
+    /// * `project_dir` - The project's root directory
+    pub fn detect(project_dir: &Path) -> Option<Self> {
+        [
+            ManagedEnvironment::Devenv,
+            ManagedEnvironment::NixFlake,
+            ManagedEnvironment::Nix,
+            ManagedEnvironment::Asdf,
+        ]
+        .into_iter()
+        .find(|environment| {
+            environment
+                .manifests()
+                .iter()
+                .any(|manifest| project_dir.join(manifest).is_file())
+        })
+    }
+
+    /// The files which configure the environment
+    fn manifests(self) -> &'static [&'static str] {
+        match self {
+            ManagedEnvironment::Devenv => &["devenv.nix"],
+            ManagedEnvironment::NixFlake => &["flake.nix"],
+            ManagedEnvironment::Nix => &["shell.nix", "default.nix"],
+            ManagedEnvironment::Asdf => &[".tool-versions"],
+        }
+    }
+
+    /// What to add to the manifest for the environment to provide ImageMagick
+    fn addition(self) -> &'static str {
+        match self {
+            ManagedEnvironment::Devenv | ManagedEnvironment::NixFlake => {
+                "packages = [ pkgs.imagemagick ];"
+            }
+            ManagedEnvironment::Nix => "buildInputs = [ pkgs.imagemagick ];",
+            ManagedEnvironment::Asdf => {
+                "asdf plugin add imagemagick\n  asdf install imagemagick latest\n  asdf set imagemagick latest"
+            }
+        }
+    }
+
+    /// A command which runs `magick` inside the environment
+    pub fn wrapper_command(self) -> &'static str {
+        match self {
+            ManagedEnvironment::Devenv => "devenv shell magick",
+            ManagedEnvironment::NixFlake => "nix develop -c magick",
+            ManagedEnvironment::Nix => "nix shell nixpkgs#imagemagick -c magick",
+            ManagedEnvironment::Asdf => "asdf exec magick",
+        }
+    }
+}
+
+/// Typed result of checking the ImageMagick installation
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CheckReport {
+    /// Whether `magick` was found on the PATH
+    pub installed: bool,
+    /// Where `magick` was found
+    pub path: Option<String>,
+    /// ImageMagick version, e.g. `7.1.2-8`
+    pub version: Option<String>,
+    /// Built-in delegate libraries, e.g. `png` or `webp`
+    pub delegates: Vec<String>,
+    /// Installation instructions when ImageMagick is not installed
+    pub instructions: Option<String>,
+    /// Why the version could not be read, when `magick` was found but failed to run
+    pub error: Option<String>,
+    /// Environment manager detected in the project, which may provide `magick` in its shell
+    pub environment: Option<ManagedEnvironment>,
+    /// Command configured to run in place of `magick`
+    pub command: Option<String>,
+}
+
+impl CheckReport {
+    /// Fill in the version and delegates from the output of `magick --version`
+    fn with_version_output(mut self, output: &str) -> Self {
+        for line in output.lines() {
+            if let Some(rest) = line.strip_prefix("Version: ImageMagick ") {
+                self.version = rest.split_whitespace().next().map(str::to_string);
+            } else if let Some(rest) = line.strip_prefix("Delegates (built-in):") {
+                self.delegates = rest.split_whitespace().map(str::to_string).collect();
+            }
+        }
+        self
+    }
+}
 
 /// Checker for ImageMagick installation
 pub struct MagickChecker<'a> {
     which_checker: &'a dyn WhichChecker,
     command_runner: &'a dyn CommandRunner,
+    language: Language,
+    project_dir: Option<PathBuf>,
+    magick_command: Option<String>,
 }
 
 impl<'a> MagickChecker<'a> {
@@ -13,17 +134,41 @@ impl<'a> MagickChecker<'a> {
         MagickChecker {
             which_checker,
             command_runner,
+            language: Language::default(),
+            project_dir: None,
+            magick_command: None,
         }
     }
 
+    /// Look for a Nix, devenv, or asdf environment in a project, tailoring the installation
+    /// instructions to its manifest
+    pub fn with_project_dir(mut self, project_dir: PathBuf) -> Self {
+        self.project_dir = Some(project_dir);
+        self
+    }
+
+    /// Check the command configured to run in place of `magick` rather than looking for
+    /// `magick` on the PATH
+    pub fn with_magick_command(mut self, command: Option<String>) -> Self {
+        self.magick_command = command.filter(|command| !command.trim().is_empty());
+        self
+    }
+
+    /// Give installation instructions in the provided language
+    pub fn with_language(mut self, language: Language) -> Self {
+        self.language = language;
+        self
+    }
+
     /// Check if ImageMagick is installed and return version or installation instructions
     pub fn check_magick(&self) -> Result<String, String> {
+        if self.magick_command.is_some() {
+            return self.version_output();
+        }
         match self.which_checker.find("magick") {
             Ok(_) => {
                 // ImageMagick is installed, get version
-                self.command_runner
-                    .execute("magick", &["--version"], None)
-                    .map_err(|e| format!("Failed to get ImageMagick version: {e}"))
+                self.version_output()
             }
             Err(_) => {
                 // ImageMagick is not installed, return platform-specific instructions
@@ -32,22 +177,141 @@ impl<'a> MagickChecker<'a> {
         }
     }
 
+    /// Check the installation and return a typed report
+    pub fn report(&self) -> CheckReport {
+        let base = CheckReport {
+            environment: self.environment(),
+            command: self.magick_command.clone(),
+            ..CheckReport::default()
+        };
+        if self.magick_command.is_some() {
+            // The configured command may only resolve `magick` once it runs
+            return match self.version_output() {
+                Ok(output) => CheckReport {
+                    installed: true,
+                    ..base
+                }
+                .with_version_output(&output),
+                Err(error) => CheckReport {
+                    error: Some(error),
+                    instructions: Some(self.get_installation_instructions()),
+                    ..base
+                },
+            };
+        }
+        let path = match self.which_checker.find("magick") {
+            Ok(path) => path,
+            Err(_) => {
+                return CheckReport {
+                    instructions: Some(self.get_installation_instructions()),
+                    ..base
+                };
+            }
+        };
+        let report = CheckReport {
+            installed: true,
+            path: Some(path.to_string_lossy().to_string()),
+            ..base
+        };
+        match self.version_output() {
+            Ok(output) => report.with_version_output(&output),
+            Err(error) => CheckReport {
+                error: Some(error),
+                ..report
+            },
+        }
+    }
+
+    /// Check that Ghostscript, which ImageMagick delegates PDF and PostScript reading to, is on
+    /// the PATH
+    ///
+    /// # Returns
+    ///
+    /// Returns the path to Ghostscript, or installation instructions when it is missing
+    pub fn check_ghostscript(&self) -> Result<PathBuf, String> {
+        GHOSTSCRIPT_COMMANDS
+            .iter()
+            .find_map(|command| self.which_checker.find(command).ok())
+            .ok_or_else(|| self.ghostscript_instructions())
+    }
+
+    /// The output of `magick --version`, run through the configured command if there is one
+    fn version_output(&self) -> Result<String, String> {
+        self.command_runner
+            .execute("magick", &["--version"], None)
+            .map_err(|e| format!("Failed to get ImageMagick version: {e}"))
+    }
+
+    /// The environment manager of the project, if one was given and uses one
+    fn environment(&self) -> Option<ManagedEnvironment> {
+        self.project_dir
+            .as_deref()
+            .and_then(ManagedEnvironment::detect)
+    }
+
     /// Get platform-specific installation instructions
     fn get_installation_instructions(&self) -> String {
+        let text = |message: Message| message.text(self.language);
+        if let Some(environment) = self.environment() {
+            return format!(
+                "{}\n\n{} ({}):\n  {}\n\n{}\n  [magick]\n  command = \"{}\"",
+                text(Message::NotInstalled),
+                text(Message::AddToEnvironment),
+                environment.manifests()[0],
+                environment.addition(),
+                text(Message::UseWrapperCommand),
+                environment.wrapper_command()
+            );
+        }
         let os = std::env::consts::OS;
         let instructions = match os {
-            "macos" => "Install ImageMagick using Homebrew:\n  brew install imagemagick",
-            "linux" => {
-                "Install ImageMagick using your package manager:\n  sudo apt install imagemagick\n  or\n  sudo dnf install ImageMagick"
-            }
-            "windows" => {
-                "Download and install ImageMagick from the official website.\n  Use winget: winget install ImageMagick.Q16-HDRI"
-            }
-            _ => "Install ImageMagick using your system's package manager.",
+            "macos" => format!(
+                "{}\n  brew install imagemagick",
+                text(Message::InstallWithHomebrew)
+            ),
+            "linux" => format!(
+                "{}\n  sudo apt install imagemagick\n  {}\n  sudo dnf install ImageMagick",
+                text(Message::InstallWithPackageManager),
+                text(Message::Or)
+            ),
+            "windows" => format!(
+                "{}\n  {} winget install ImageMagick.Q16-HDRI",
+                text(Message::DownloadFromWebsite),
+                text(Message::InstallWithWinget)
+            ),
+            _ => text(Message::InstallWithSystemPackageManager).to_string(),
         };
 
         format!(
-            "ImageMagick is not installed.\n\n{instructions}\n\nFor more details, visit: https://imagemagick.org/script/download.php"
+            "{}\n\n{instructions}\n\n{} https://imagemagick.org/script/download.php",
+            text(Message::NotInstalled),
+            text(Message::MoreDetails)
+        )
+    }
+
+    /// Get platform-specific Ghostscript installation instructions
+    fn ghostscript_instructions(&self) -> String {
+        let text = |message: Message| message.text(self.language);
+        let commands = match std::env::consts::OS {
+            "macos" => "brew install ghostscript".to_string(),
+            "linux" => format!(
+                "sudo apt install ghostscript\n  {}\n  sudo dnf install ghostscript",
+                text(Message::Or)
+            ),
+            "windows" => "winget install ArtifexSoftware.GhostScript".to_string(),
+            _ => {
+                return format!(
+                    "{}\n\n{} https://ghostscript.com/releases/gsdnld.html",
+                    text(Message::GhostscriptNotInstalled),
+                    text(Message::MoreDetails)
+                );
+            }
+        };
+        format!(
+            "{}\n\n{}\n  {commands}\n\n{} https://ghostscript.com/releases/gsdnld.html",
+            text(Message::GhostscriptNotInstalled),
+            text(Message::InstallGhostscriptWith),
+            text(Message::MoreDetails)
         )
     }
 }
@@ -58,6 +322,7 @@ mod tests {
     use crate::feature::shell::{CommandRunner, ShellError};
     use crate::feature::which::{WhichChecker, WhichError};
     use std::path::PathBuf;
+    use tempfile::TempDir;
 
     /// Mock implementation of WhichChecker for testing
     pub struct MockWhichChecker {
@@ -172,4 +437,161 @@ mod tests {
             _ => {} // Other platforms get generic message
         }
     }
+
+    #[test]
+    fn test_report_installed() {
+        let which_checker = MockWhichChecker { found: true };
+        let command_runner = MockCommandRunner {
+            output: "Version: ImageMagick 7.1.2-8 Q16-HDRI aarch64 23437\nDelegates (built-in): bzlib freetype png webp\n".to_string(),
+            should_fail: false,
+        };
+        let checker = MagickChecker::new(&which_checker, &command_runner);
+
+        let report = checker.report();
+
+        assert_eq!(
+            report,
+            CheckReport {
+                installed: true,
+                path: Some("/usr/bin/magick".to_string()),
+                version: Some("7.1.2-8".to_string()),
+                delegates: vec![
+                    "bzlib".to_string(),
+                    "freetype".to_string(),
+                    "png".to_string(),
+                    "webp".to_string()
+                ],
+                instructions: None,
+                error: None,
+                environment: None,
+                command: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_report_not_installed() {
+        let which_checker = MockWhichChecker { found: false };
+        let command_runner = MockCommandRunner {
+            output: String::new(),
+            should_fail: false,
+        };
+        let checker = MagickChecker::new(&which_checker, &command_runner);
+
+        let report = checker.report();
+
+        assert!(!report.installed);
+        assert!(
+            report
+                .instructions
+                .unwrap()
+                .contains("ImageMagick is not installed")
+        );
+    }
+
+    #[test]
+    fn test_localized_instructions() {
+        let which_checker = MockWhichChecker { found: false };
+        let command_runner = MockCommandRunner {
+            output: String::new(),
+            should_fail: false,
+        };
+        let checker =
+            MagickChecker::new(&which_checker, &command_runner).with_language(Language::German);
+        let instructions = checker.check_magick().unwrap();
+        assert!(instructions.starts_with("ImageMagick ist nicht installiert."));
+        assert!(instructions.contains("Weitere Details finden Sie unter:"));
+        assert!(instructions.contains("https://imagemagick.org/script/download.php"));
+    }
+
+    #[test]
+    fn test_detect_managed_environment() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(ManagedEnvironment::detect(dir.path()), None);
+
+        std::fs::write(dir.path().join(".tool-versions"), "nodejs 22.0.0\n").unwrap();
+        assert_eq!(
+            ManagedEnvironment::detect(dir.path()),
+            Some(ManagedEnvironment::Asdf)
+        );
+        std::fs::write(dir.path().join("shell.nix"), "").unwrap();
+        assert_eq!(
+            ManagedEnvironment::detect(dir.path()),
+            Some(ManagedEnvironment::Nix)
+        );
+        std::fs::write(dir.path().join("flake.nix"), "").unwrap();
+        std::fs::write(dir.path().join("devenv.nix"), "").unwrap();
+        assert_eq!(
+            ManagedEnvironment::detect(dir.path()),
+            Some(ManagedEnvironment::Devenv)
+        );
+    }
+
+    #[test]
+    fn test_report_managed_environment_instructions() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("flake.nix"), "").unwrap();
+        let which_checker = MockWhichChecker { found: false };
+        let command_runner = MockCommandRunner {
+            output: String::new(),
+            should_fail: false,
+        };
+        let checker = MagickChecker::new(&which_checker, &command_runner)
+            .with_project_dir(dir.path().to_path_buf());
+
+        let report = checker.report();
+
+        assert!(!report.installed);
+        assert_eq!(report.environment, Some(ManagedEnvironment::NixFlake));
+        let instructions = report.instructions.unwrap();
+        assert!(instructions.contains("(flake.nix):\n  packages = [ pkgs.imagemagick ];"));
+        assert!(instructions.contains("[magick]\n  command = \"nix develop -c magick\""));
+    }
+
+    #[test]
+    fn test_report_configured_command_skips_path_lookup() {
+        let which_checker = MockWhichChecker { found: false };
+        let command_runner = MockCommandRunner {
+            output: "Version: ImageMagick 7.1.2-8 Q16-HDRI x86_64\n".to_string(),
+            should_fail: false,
+        };
+        let checker = MagickChecker::new(&which_checker, &command_runner)
+            .with_magick_command(Some("devenv shell magick".to_string()));
+
+        let report = checker.report();
+
+        assert!(report.installed);
+        assert_eq!(report.path, None);
+        assert_eq!(report.command.as_deref(), Some("devenv shell magick"));
+        assert_eq!(report.version.as_deref(), Some("7.1.2-8"));
+    }
+
+    #[test]
+    fn test_check_ghostscript() {
+        struct GhostscriptWhichChecker;
+        impl WhichChecker for GhostscriptWhichChecker {
+            fn find(&self, command: &str) -> Result<PathBuf, WhichError> {
+                match command {
+                    "gs" => Ok(PathBuf::from("/usr/bin/gs")),
+                    _ => Err(WhichError::NotFound(command.to_string())),
+                }
+            }
+        }
+        let command_runner = MockCommandRunner {
+            output: String::new(),
+            should_fail: false,
+        };
+
+        let checker = MagickChecker::new(&GhostscriptWhichChecker, &command_runner);
+        assert_eq!(
+            checker.check_ghostscript(),
+            Ok(PathBuf::from("/usr/bin/gs"))
+        );
+
+        let which_checker = MockWhichChecker { found: true };
+        let checker = MagickChecker::new(&which_checker, &command_runner);
+        let instructions = checker.check_ghostscript().unwrap_err();
+        assert!(instructions.starts_with("Ghostscript is not installed."));
+        assert!(instructions.contains("https://ghostscript.com/releases/gsdnld.html"));
+    }
 }
@@ -0,0 +1,226 @@
+use crate::feature::data_dir::{LOCK_FILE_NAME, data_dir};
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, Once};
+use std::time::{Duration, SystemTime};
+
+/// Prefix of every temporary file magick-mcp creates in the system temporary directory
+pub(crate) const TEMP_PREFIX: &str = "magick-mcp-";
+
+/// How old a leftover must be before `clean` treats it as abandoned by a crashed session
+///
+/// Temporary files are short lived, so anything older than this is not in use by a running
+/// server.
+const STALE_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// Temporary files this process is writing, removed if it exits abnormally
+static MANAGED: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+static GUARD: Once = Once::new();
+
+/// Files and locks left behind by crashed sessions, as removed by `clean`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CleanReport {
+    /// Temporary and partially written files which were removed
+    pub removed: Vec<PathBuf>,
+    /// Total size of the removed files in bytes
+    pub bytes: u64,
+    /// Lock files whose recorded owner no longer holds them, which were reset
+    pub released_locks: Vec<PathBuf>,
+}
+
+/// Track a temporary path so it is removed if the process panics or is interrupted
+pub(crate) fn register(path: &Path) {
+    if let Ok(mut managed) = MANAGED.lock() {
+        managed.push(path.to_path_buf());
+    }
+}
+
+/// Stop tracking a temporary path once it has been removed or renamed into place
+pub(crate) fn unregister(path: &Path) {
+    if let Ok(mut managed) = MANAGED.lock() {
+        managed.retain(|managed| managed != path);
+    }
+}
+
+/// Remove every temporary path this process is still writing
+///
+/// Called when the process is about to exit without running destructors. The lock is only
+/// tried, so a panic raised while it is held cannot deadlock the cleanup.
+pub fn remove_managed() {
+    let Ok(mut managed) = MANAGED.try_lock() else {
+        return;
+    };
+    for path in managed.drain(..) {
+        let _ = fs::remove_file(&path).or_else(|_| fs::remove_dir(&path));
+    }
+}
+
+/// Install a panic hook which removes this process's temporary files before panicking
+///
+/// The previous hook still runs afterwards. Installing the guard more than once has no effect.
+pub fn install_cleanup_guard() {
+    GUARD.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            remove_managed();
+            previous(info);
+        }));
+    });
+}
+
+/// Remove leftovers from sessions which crashed or were killed
+///
+/// This removes `magick-mcp-*` files and directories from the temporary directory and partially written `.tmp`
+/// files from the data directory and the workspace's `.magickmcp` directory, once they are older
+/// than an hour. Lock files still recording an owner which no longer holds them are reset.
+///
+/// # Arguments
+///
+/// * `workspace` - Optional workspace whose incremental build state is also cleaned
+pub fn clean(workspace: Option<&Path>) -> io::Result<CleanReport> {
+    let mut dirs = vec![std::env::temp_dir()];
+    dirs.extend(data_dir());
+    dirs.extend(workspace.map(|workspace| workspace.join(".magickmcp")));
+    clean_dirs(&dirs, SystemTime::now() - STALE_AFTER)
+}
+
+fn clean_dirs(dirs: &[PathBuf], cutoff: SystemTime) -> io::Result<CleanReport> {
+    let mut report = CleanReport::default();
+    let temp_dir = std::env::temp_dir();
+    for dir in dirs.iter().filter(|dir| dir.is_dir()) {
+        // The system temporary directory is shared, so only its top level magick-mcp files are
+        // considered
+        let recursive = *dir != temp_dir;
+        clean_dir(dir, recursive, cutoff, &mut report)?;
+    }
+    Ok(report)
+}
+
+fn clean_dir(
+    dir: &Path,
+    recursive: bool,
+    cutoff: SystemTime,
+    report: &mut CleanReport,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let metadata = entry.metadata()?;
+        let stale = metadata.modified().is_ok_and(|modified| modified < cutoff);
+        if metadata.is_dir() {
+            if recursive {
+                clean_dir(&path, recursive, cutoff, report)?;
+            } else if name.starts_with(TEMP_PREFIX) && stale {
+                remove_temp_dir(&path, report)?;
+            }
+            continue;
+        }
+        if name == LOCK_FILE_NAME {
+            if release_lock(&path)? {
+                report.released_locks.push(path);
+            }
+            continue;
+        }
+        let leftover = if recursive {
+            name.starts_with('.') && name.ends_with(".tmp")
+        } else {
+            name.starts_with(TEMP_PREFIX)
+        };
+        if leftover && stale {
+            fs::remove_file(&path)?;
+            report.bytes += metadata.len();
+            report.removed.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Remove a temporary file's private directory along with whatever was left in it
+fn remove_temp_dir(dir: &Path, report: &mut CleanReport) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        report.bytes += fs::metadata(&path)?.len();
+        fs::remove_file(&path)?;
+        report.removed.push(path);
+    }
+    fs::remove_dir(dir)
+}
+
+/// Reset a lock file which records an owner but is not locked by any process
+fn release_lock(path: &Path) -> io::Result<bool> {
+    if fs::metadata(path)?.len() == 0 {
+        return Ok(false);
+    }
+    let file = OpenOptions::new().write(true).open(path)?;
+    if file.try_lock().is_err() {
+        return Ok(false);
+    }
+    file.set_len(0)?;
+    file.unlock()?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::data_dir::DataDirLock;
+    use tempfile::TempDir;
+
+    fn is_managed(path: &Path) -> bool {
+        MANAGED
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|managed| managed == path)
+    }
+
+    #[test]
+    fn test_register_and_unregister() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("magick-mcp-partial.png");
+
+        register(&path);
+        assert!(is_managed(&path));
+
+        unregister(&path);
+        assert!(!is_managed(&path));
+    }
+
+    #[test]
+    fn test_clean_removes_stale_leftovers() {
+        let dir = TempDir::new().unwrap();
+        let data = dir.path().join("data");
+        fs::create_dir_all(data.join("functions")).unwrap();
+        fs::write(data.join("functions/.gray.json.42.tmp"), b"par").unwrap();
+        fs::write(data.join("functions/gray.json"), b"{}").unwrap();
+        fs::write(data.join(LOCK_FILE_NAME), b"pid=42 acquired_at=0\n").unwrap();
+
+        let future = SystemTime::now() + Duration::from_secs(60);
+        let report = clean_dirs(std::slice::from_ref(&data), future).unwrap();
+
+        assert_eq!(
+            report.removed,
+            vec![data.join("functions/.gray.json.42.tmp")]
+        );
+        assert_eq!(report.bytes, 3);
+        assert_eq!(report.released_locks, vec![data.join(LOCK_FILE_NAME)]);
+        assert!(data.join("functions/gray.json").exists());
+        assert!(fs::read(data.join(LOCK_FILE_NAME)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_clean_keeps_recent_files_and_held_locks() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join(".outputs.json.42.tmp"), b"partial").unwrap();
+        let _lock = DataDirLock::acquire(dir.path()).unwrap();
+
+        let past = SystemTime::now() - STALE_AFTER;
+        let report = clean_dirs(&[dir.path().to_path_buf()], past).unwrap();
+
+        assert_eq!(report, CleanReport::default());
+    }
+}
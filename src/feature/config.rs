@@ -0,0 +1,358 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+
+/// Environment variable which points at a config file to use instead of the default location
+pub const CONFIG_PATH_ENV: &str = "MAGICK_MCP_CONFIG";
+
+/// Error type for loading the config file
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("IO error reading config '{path}': {source}")]
+    IoError {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("Failed to parse config '{path}': {source}")]
+    ParseError {
+        path: String,
+        source: toml::de::Error,
+    },
+    #[error("Unknown preset '{name}', configured presets: {available}")]
+    UnknownPreset { name: String, available: String },
+}
+
+/// Settings read from `config.toml`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Language for user-facing messages such as `de` or `ja`, overriding `LANG`
+    pub language: Option<String>,
+    /// How the MCP server presents itself to clients
+    pub server: ServerConfig,
+    /// Where updated built-in functions come from
+    pub builtins: BuiltinsConfig,
+    /// Cost thresholds for estimated memory and time of commands
+    pub limits: LimitsConfig,
+    /// How ImageMagick is run
+    pub magick: MagickConfig,
+    /// Named output settings accepted as `preset` by the resize, convert_format, and optimize
+    /// tools, e.g. `[presets.web]`
+    pub presets: BTreeMap<String, PresetConfig>,
+    /// How long removed functions and overwritten outputs are kept
+    pub trash: TrashConfig,
+    /// What functions may read when they run
+    pub functions: FunctionsConfig,
+}
+
+/// Overrides for the metadata the MCP server reports to clients
+///
+/// Lets organizations embedding magick-mcp brand the server and give agents house rules.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// Server name, defaults to `magick-mcp`
+    pub name: Option<String>,
+    /// Human readable server title
+    pub title: Option<String>,
+    /// Website shown by clients for the server
+    pub website_url: Option<String>,
+    /// Extra instructions appended to the built-in ones, e.g. "Always write outputs to ./generated"
+    pub instructions: Option<String>,
+    /// Workspace for tool calls which name none, when neither the session nor the client's
+    /// roots provide one
+    pub workspace: Option<String>,
+}
+
+/// Overrides for the built-in function update channel
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct BuiltinsConfig {
+    /// URL of `builtins.json`, whose signature is expected at the same URL plus `.minisig`
+    pub url: Option<String>,
+    /// Base64 minisign public key the built-ins must be signed with
+    pub public_key: Option<String>,
+}
+
+/// Thresholds applied to the estimated cost of a command
+///
+/// Above a `warn_` threshold the estimate advises downscaling first; above a `max_` threshold the
+/// magick tool refuses to run the command.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LimitsConfig {
+    pub warn_memory_mb: Option<u64>,
+    pub max_memory_mb: Option<u64>,
+    pub warn_seconds: Option<f64>,
+    pub max_seconds: Option<f64>,
+}
+
+/// Output settings bundled under a name, such as `web` or `print`
+///
+/// Every field is optional, and arguments given with a tool call take precedence over the
+/// preset's values.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PresetConfig {
+    /// Quality from 0 to 100, the highest quality tried by optimize
+    pub quality: Option<u32>,
+    /// Width in pixels larger images are shrunk to fit
+    pub max_width: Option<u32>,
+    /// Height in pixels larger images are shrunk to fit
+    pub max_height: Option<u32>,
+    /// Remove profiles and metadata
+    pub strip: Option<bool>,
+    /// Colorspace to convert to, e.g. `sRGB` or `CMYK`
+    pub colorspace: Option<String>,
+    /// Format convert_format writes when none is given, e.g. `webp`
+    pub format: Option<String>,
+}
+
+/// How ImageMagick is run
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct MagickConfig {
+    /// Command run in place of `magick`, e.g. `nix shell nixpkgs#imagemagick -c magick` when
+    /// ImageMagick is only available inside a Nix, devenv, or asdf environment
+    pub command: Option<String>,
+}
+
+impl MagickConfig {
+    /// The configured command split into its program and leading arguments
+    ///
+    /// Returns `None` when no command is configured or it is blank.
+    pub fn command_args(&self) -> Option<Vec<String>> {
+        let args: Vec<String> = self
+            .command
+            .as_deref()?
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        (!args.is_empty()).then_some(args)
+    }
+}
+
+/// Days a trashed file is kept when the config sets no retention
+const DEFAULT_TRASH_RETENTION_DAYS: u64 = 7;
+
+/// Retention of the trash holding removed functions and overwritten outputs
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TrashConfig {
+    /// Days a trashed file is kept before it is purged, defaults to 7
+    pub retention_days: Option<u64>,
+    /// Keep a copy of an existing output before a command overwrites it, off by default
+    pub outputs: bool,
+}
+
+impl TrashConfig {
+    /// How long a trashed file is kept
+    pub fn retention(&self) -> Duration {
+        let days = self.retention_days.unwrap_or(DEFAULT_TRASH_RETENTION_DAYS);
+        Duration::from_secs(days.saturating_mul(24 * 60 * 60))
+    }
+}
+
+/// What functions may read when they run
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct FunctionsConfig {
+    /// Environment variables commands may read as `${env.NAME}`, none by default
+    pub env: Vec<String>,
+}
+
+/// Get the path of the config file
+///
+/// Uses `MAGICK_MCP_CONFIG` when set, otherwise the platform config directory:
+/// - Linux: `~/.config/magick-mcp/config.toml`
+/// - macOS: `~/Library/Application Support/magick-mcp/config.toml`
+/// - Windows: `C:\Users\<user>\AppData\Roaming\magick-mcp\config.toml`
+///
+/// Returns `None` if the config directory cannot be determined.
+pub fn config_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(CONFIG_PATH_ENV).filter(|p| !p.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+    dirs::config_dir().map(|dir| dir.join("magick-mcp").join("config.toml"))
+}
+
+impl Config {
+    /// Look up a preset by name
+    ///
+    /// # Returns
+    ///
+    /// Returns the preset, or `ConfigError::UnknownPreset` naming the configured presets
+    pub fn preset(&self, name: &str) -> Result<&PresetConfig, ConfigError> {
+        self.presets
+            .get(name)
+            .ok_or_else(|| ConfigError::UnknownPreset {
+                name: name.to_string(),
+                available: if self.presets.is_empty() {
+                    "none".to_string()
+                } else {
+                    self.presets.keys().cloned().collect::<Vec<_>>().join(", ")
+                },
+            })
+    }
+
+    /// Load the config from the default location, using defaults when there is no config file
+    pub fn load() -> Result<Self, ConfigError> {
+        match config_path() {
+            Some(path) if path.exists() => Self::from_path(&path),
+            _ => Ok(Config::default()),
+        }
+    }
+
+    /// Load the config from a specific file
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to a TOML config file
+    ///
+    /// # Returns
+    ///
+    /// Returns the parsed `Config`, or a `ConfigError` if it cannot be read or parsed
+    pub fn from_path(path: &Path) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path).map_err(|source| ConfigError::IoError {
+            path: path.display().to_string(),
+            source,
+        })?;
+        toml::from_str(&contents).map_err(|source| ConfigError::ParseError {
+            path: path.display().to_string(),
+            source,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_from_path_reads_server_section() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(
+            &path,
+            r#"
+[server]
+name = "acme-images"
+instructions = "Always write outputs to ./generated"
+workspace = "/srv/images"
+"#,
+        )
+        .unwrap();
+
+        let config = Config::from_path(&path).unwrap();
+
+        assert_eq!(config.server.name.as_deref(), Some("acme-images"));
+        assert_eq!(config.server.title, None);
+        assert_eq!(
+            config.server.instructions.as_deref(),
+            Some("Always write outputs to ./generated")
+        );
+        assert_eq!(config.server.workspace.as_deref(), Some("/srv/images"));
+    }
+
+    #[test]
+    fn test_from_path_empty_file_uses_defaults() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "").unwrap();
+
+        assert_eq!(Config::from_path(&path).unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_from_path_reports_parse_errors() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "[server]\nname = 3\n").unwrap();
+
+        assert!(matches!(
+            Config::from_path(&path),
+            Err(ConfigError::ParseError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_presets() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(
+            &path,
+            r#"
+[presets.web]
+quality = 80
+max_width = 1920
+strip = true
+format = "webp"
+
+[presets.print]
+colorspace = "CMYK"
+"#,
+        )
+        .unwrap();
+
+        let config = Config::from_path(&path).unwrap();
+
+        let web = config.preset("web").unwrap();
+        assert_eq!(web.quality, Some(80));
+        assert_eq!(web.max_width, Some(1920));
+        assert_eq!(web.max_height, None);
+        assert_eq!(web.strip, Some(true));
+        assert_eq!(web.format.as_deref(), Some("webp"));
+        assert_eq!(
+            config.preset("print").unwrap().colorspace.as_deref(),
+            Some("CMYK")
+        );
+        assert_eq!(
+            config.preset("archive").unwrap_err().to_string(),
+            "Unknown preset 'archive', configured presets: print, web"
+        );
+    }
+
+    #[test]
+    fn test_trash_retention() {
+        assert_eq!(
+            TrashConfig::default().retention(),
+            Duration::from_secs(7 * 24 * 60 * 60)
+        );
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "[trash]\nretention_days = 30\noutputs = true\n").unwrap();
+
+        let config = Config::from_path(&path).unwrap();
+        assert_eq!(config.trash.retention_days, Some(30));
+        assert!(config.trash.outputs);
+        assert!(!TrashConfig::default().outputs);
+    }
+
+    #[test]
+    fn test_magick_command_args() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(
+            &path,
+            "[magick]\ncommand = \"nix shell nixpkgs#imagemagick -c magick\"\n",
+        )
+        .unwrap();
+
+        let config = Config::from_path(&path).unwrap();
+
+        assert_eq!(
+            config.magick.command_args().unwrap(),
+            vec!["nix", "shell", "nixpkgs#imagemagick", "-c", "magick"]
+        );
+        assert_eq!(MagickConfig::default().command_args(), None);
+        let blank = MagickConfig {
+            command: Some("  ".to_string()),
+        };
+        assert_eq!(blank.command_args(), None);
+    }
+}
@@ -0,0 +1,292 @@
+use crate::feature::data_dir::data_dir;
+use serde_json::{Map, Value};
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, Once};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Number of recent log lines kept for crash reports
+const LOG_LINES: usize = 50;
+
+/// Directory in the data directory crash reports are written to
+const CRASH_DIR: &str = "crashes";
+
+/// Recent log lines, oldest first
+static LOG: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Tool calls currently running, described without their argument values
+static ACTIVE_CALLS: Mutex<Vec<ActiveCall>> = Mutex::new(Vec::new());
+
+static REPORTER: Once = Once::new();
+
+/// A running tool call, as named in a crash report
+#[derive(Debug, Clone, PartialEq)]
+struct ActiveCall {
+    trace_id: String,
+    tool: String,
+    arguments: Value,
+}
+
+/// Print a log line to stderr, keeping it for a crash report
+pub fn log_line(line: String) {
+    eprintln!("{line}");
+    if let Ok(mut log) = LOG.lock() {
+        if log.len() == LOG_LINES {
+            log.pop_front();
+        }
+        log.push_back(line);
+    }
+}
+
+/// Record that a tool call started, so a crash while it runs names it
+///
+/// Argument values can hold private paths and text, so only their shape is kept.
+pub fn begin_call(trace_id: &str, tool: &str, arguments: &Value) {
+    if let Ok(mut calls) = ACTIVE_CALLS.lock() {
+        calls.push(ActiveCall {
+            trace_id: trace_id.to_string(),
+            tool: tool.to_string(),
+            arguments: redact(arguments),
+        });
+    }
+}
+
+/// Record that a tool call finished
+pub fn end_call(trace_id: &str) {
+    if let Ok(mut calls) = ACTIVE_CALLS.lock() {
+        calls.retain(|call| call.trace_id != trace_id);
+    }
+}
+
+/// Install a panic hook which writes a crash report to the data directory and prints its path
+///
+/// The report holds the version, the panic message and backtrace, the last log lines, and the
+/// running tool calls with their argument values redacted. Nothing is sent anywhere. The
+/// previous hook still runs afterwards. Installing the reporter more than once has no effect.
+pub fn install_crash_reporter() {
+    REPORTER.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if let Some(dir) = crash_dir() {
+                match write_report(&dir, info) {
+                    Ok(path) => eprintln!(
+                        "magick-mcp crashed, a report was written to {}\nRun 'magick-mcp crash-report show' to view it",
+                        path.display()
+                    ),
+                    Err(e) => eprintln!("magick-mcp crashed and failed to write a report: {e}"),
+                }
+            }
+            previous(info);
+        }));
+    });
+}
+
+/// The most recent crash report
+///
+/// # Returns
+///
+/// Returns the report's path and contents, `None` if there are no reports, or an `io::Error`
+/// if the report cannot be read
+pub fn latest_crash_report() -> io::Result<Option<(PathBuf, String)>> {
+    match crash_dir() {
+        Some(dir) => latest_report_in(&dir),
+        None => Ok(None),
+    }
+}
+
+fn crash_dir() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(CRASH_DIR))
+}
+
+fn write_report(dir: &Path, info: &PanicHookInfo) -> io::Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let message = match info.payload().downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => info
+            .payload()
+            .downcast_ref::<String>()
+            .cloned()
+            .unwrap_or_else(|| "unknown panic".to_string()),
+    };
+    let location = info
+        .location()
+        .map(|location| format!("{}:{}", location.file(), location.line()))
+        .unwrap_or_default();
+    // The hook may run while a lock is held by the panicking thread, so locks are only tried
+    let calls = ACTIVE_CALLS
+        .try_lock()
+        .map(|calls| calls.clone())
+        .unwrap_or_default();
+    let log: Vec<String> = LOG
+        .try_lock()
+        .map(|log| log.iter().cloned().collect())
+        .unwrap_or_default();
+    let report = render_report(
+        timestamp,
+        &format!("{message} at {location}"),
+        &calls,
+        &log,
+        &Backtrace::force_capture().to_string(),
+    );
+
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("crash-{timestamp}-{}.txt", std::process::id()));
+    fs::write(&path, report)?;
+    Ok(path)
+}
+
+fn render_report(
+    timestamp: u64,
+    panic: &str,
+    calls: &[ActiveCall],
+    log: &[String],
+    backtrace: &str,
+) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, "magick-mcp crash report");
+    let _ = writeln!(report, "version: {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(
+        report,
+        "platform: {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    let _ = writeln!(report, "timestamp: {timestamp}");
+    let _ = writeln!(report, "panic: {panic}");
+    let _ = writeln!(report, "\nactive tool calls:");
+    if calls.is_empty() {
+        let _ = writeln!(report, "  none");
+    }
+    for call in calls {
+        let _ = writeln!(
+            report,
+            "  trace_id={} tool={} arguments={}",
+            call.trace_id, call.tool, call.arguments
+        );
+    }
+    let _ = writeln!(report, "\nrecent log:");
+    for line in log {
+        let _ = writeln!(report, "  {line}");
+    }
+    let _ = writeln!(report, "\nbacktrace:\n{backtrace}");
+    report
+}
+
+/// Replace every string in a value with a placeholder, keeping keys, numbers, and flags
+fn redact(value: &Value) -> Value {
+    match value {
+        Value::String(_) => Value::String("<redacted>".to_string()),
+        Value::Array(items) => Value::Array(items.iter().map(redact).collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, field)| (key.clone(), redact(field)))
+                .collect::<Map<_, _>>(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn latest_report_in(dir: &Path) -> io::Result<Option<(PathBuf, String)>> {
+    if !dir.is_dir() {
+        return Ok(None);
+    }
+    let mut latest: Option<(SystemTime, PathBuf)> = None;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().is_none_or(|extension| extension != "txt") {
+            continue;
+        }
+        let modified = entry.metadata()?.modified()?;
+        if latest.as_ref().is_none_or(|(newest, _)| modified > *newest) {
+            latest = Some((modified, path));
+        }
+    }
+    match latest {
+        Some((_, path)) => {
+            let contents = fs::read_to_string(&path)?;
+            Ok(Some((path, contents)))
+        }
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_redact_keeps_shape_only() {
+        let arguments = json!({
+            "input": "/home/me/passport.png",
+            "inputs": ["a.png", "b.png"],
+            "quality": 80,
+            "strip": true,
+            "params": { "text": "secret" }
+        });
+
+        assert_eq!(
+            redact(&arguments),
+            json!({
+                "input": "<redacted>",
+                "inputs": ["<redacted>", "<redacted>"],
+                "quality": 80,
+                "strip": true,
+                "params": { "text": "<redacted>" }
+            })
+        );
+    }
+
+    #[test]
+    fn test_render_report() {
+        let calls = vec![ActiveCall {
+            trace_id: "abc".to_string(),
+            tool: "resize".to_string(),
+            arguments: json!({ "input": "<redacted>" }),
+        }];
+        let log = vec!["magick-mcp trace_id=xyz tool=crop status=ok elapsed_ms=5".to_string()];
+
+        let report = render_report(42, "boom at src/lib.rs:1", &calls, &log, "frame 0");
+
+        assert!(report.contains(&format!("version: {}", env!("CARGO_PKG_VERSION"))));
+        assert!(report.contains("panic: boom at src/lib.rs:1"));
+        assert!(report.contains(r#"trace_id=abc tool=resize arguments={"input":"<redacted>"}"#));
+        assert!(report.contains("  magick-mcp trace_id=xyz tool=crop"));
+        assert!(report.ends_with("backtrace:\nframe 0\n"));
+    }
+
+    #[test]
+    fn test_latest_report_in() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(
+            latest_report_in(&temp_dir.path().join("missing"))
+                .unwrap()
+                .is_none()
+        );
+
+        let older = temp_dir.path().join("crash-1-1.txt");
+        let newer = temp_dir.path().join("crash-2-1.txt");
+        fs::write(&older, "older").unwrap();
+        fs::write(&newer, "newer").unwrap();
+        let file = fs::File::options().write(true).open(&older).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(60))
+            .unwrap();
+
+        assert_eq!(
+            latest_report_in(temp_dir.path()).unwrap(),
+            Some((newer, "newer".to_string()))
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use crate::feature::cleanup;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Name of the lock file guarding writes to the data directory
+pub(crate) const LOCK_FILE_NAME: &str = ".lock";
+
+/// Get the platform-specific directory shared by every magick-mcp process
+///
+/// Returns `None` if the data directory cannot be determined.
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("magick-mcp"))
+}
+
+/// Exclusive lock over a data directory, shared between magick-mcp processes
+///
+/// Several clients may each launch their own server, so every write to shared state such as
+/// functions happens while holding this lock. The lock is an OS file lock, which is released when
+/// it is dropped or when the owning process exits, so a crashed server never leaves a stale lock
+/// behind. While held, the lock file records the owner's pid and when it was acquired to help
+/// diagnose a process that holds it for too long.
+pub struct DataDirLock {
+    file: File,
+}
+
+impl DataDirLock {
+    /// Acquire the lock for a directory, waiting for any other process holding it
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory to lock, which is created if it does not exist
+    ///
+    /// # Returns
+    ///
+    /// Returns the held lock, or an `io::Error` if the lock file cannot be opened or locked
+    pub fn acquire(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(dir.join(LOCK_FILE_NAME))?;
+        file.lock()?;
+
+        let acquired_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        file.set_len(0)?;
+        writeln!(file, "pid={} acquired_at={acquired_at}", std::process::id())?;
+        Ok(DataDirLock { file })
+    }
+}
+
+impl Drop for DataDirLock {
+    fn drop(&mut self) {
+        let _ = self.file.set_len(0);
+        let _ = self.file.unlock();
+    }
+}
+
+/// Write a file so other processes see either the old or the new contents, never a partial write
+///
+/// The contents are written to a temporary file next to the target which is then renamed over it.
+/// The temporary file is removed if the process panics or is interrupted before the rename, and
+/// `magick-mcp clean` removes any a crash leaves behind.
+///
+/// # Arguments
+///
+/// * `path` - The file to write
+/// * `contents` - The new contents of the file
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let temp_path = path.with_file_name(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+    cleanup::register(&temp_path);
+    let result = fs::write(&temp_path, contents).and_then(|_| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    cleanup::unregister(&temp_path);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_acquire_records_owner() {
+        let dir = TempDir::new().unwrap();
+        let lock = DataDirLock::acquire(dir.path()).unwrap();
+
+        let contents = fs::read_to_string(dir.path().join(LOCK_FILE_NAME)).unwrap();
+        assert!(contents.starts_with(&format!("pid={} ", std::process::id())));
+
+        drop(lock);
+        let contents = fs::read_to_string(dir.path().join(LOCK_FILE_NAME)).unwrap();
+        assert!(contents.is_empty());
+    }
+
+    #[test]
+    fn test_lock_is_exclusive_until_dropped() {
+        let dir = TempDir::new().unwrap();
+        let lock = DataDirLock::acquire(dir.path()).unwrap();
+
+        let other = File::open(dir.path().join(LOCK_FILE_NAME)).unwrap();
+        assert!(other.try_lock().is_err());
+
+        drop(lock);
+        assert!(other.try_lock().is_ok());
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_contents() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("function.json");
+        fs::write(&path, "old").unwrap();
+
+        write_atomic(&path, "new").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,272 @@
+use crate::feature::cache::BuildCache;
+use crate::feature::check::{CheckReport, MagickChecker};
+use crate::feature::config::{Config, config_path};
+use crate::feature::data_dir::{DataDirLock, data_dir};
+use crate::feature::functions::functions_dir;
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+
+/// Delegates most image workflows rely on
+const RECOMMENDED_DELEGATES: &[&str] = &["png", "jpeg", "webp", "freetype"];
+
+/// Outcome of a single doctor check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DoctorStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for DoctorStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            DoctorStatus::Ok => "ok",
+            DoctorStatus::Warning => "warning",
+            DoctorStatus::Error => "error",
+        };
+        write!(f, "{label}")
+    }
+}
+
+/// A single diagnostic performed by the doctor
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DoctorCheck {
+    /// Short identifier of the check, e.g. `imagemagick`
+    pub name: String,
+    pub status: DoctorStatus,
+    /// What was found, or how to fix it
+    pub message: String,
+}
+
+impl DoctorCheck {
+    fn new(name: &str, status: DoctorStatus, message: impl Into<String>) -> Self {
+        DoctorCheck {
+            name: name.to_string(),
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+/// Typed result of diagnosing the environment magick-mcp runs in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DoctorReport {
+    /// False when any check failed with an error, warnings do not count
+    pub ok: bool,
+    /// The ImageMagick installation details
+    pub magick: CheckReport,
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    /// Build a report from individual checks
+    fn new(magick: CheckReport, checks: Vec<DoctorCheck>) -> Self {
+        DoctorReport {
+            ok: checks.iter().all(|c| c.status != DoctorStatus::Error),
+            magick,
+            checks,
+        }
+    }
+}
+
+/// Diagnoses the ImageMagick installation, config file, and data directory
+pub struct Doctor<'a> {
+    checker: MagickChecker<'a>,
+}
+
+impl<'a> Doctor<'a> {
+    /// Create a new Doctor which checks ImageMagick with the provided checker
+    pub fn new(checker: MagickChecker<'a>) -> Self {
+        Doctor { checker }
+    }
+
+    /// Run every check
+    pub fn run(&self) -> DoctorReport {
+        let magick = self.checker.report();
+        let mut checks = magick_checks(&magick);
+        checks.push(config_check());
+        checks.push(data_dir_check());
+        checks.push(functions_dir_check());
+        checks.push(cache_check());
+        DoctorReport::new(magick, checks)
+    }
+}
+
+/// Checks for the ImageMagick binary and its delegates
+fn magick_checks(report: &CheckReport) -> Vec<DoctorCheck> {
+    if !report.installed {
+        let message = [&report.error, &report.instructions]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        return vec![DoctorCheck::new(
+            "imagemagick",
+            DoctorStatus::Error,
+            message,
+        )];
+    }
+    if let Some(error) = &report.error {
+        return vec![DoctorCheck::new(
+            "imagemagick",
+            DoctorStatus::Error,
+            error.clone(),
+        )];
+    }
+    let version = report.version.as_deref().unwrap_or("unknown version");
+    let location = match (&report.command, &report.path) {
+        (Some(command), _) => format!("run with '{command}'"),
+        (None, path) => format!("at {}", path.as_deref().unwrap_or_default()),
+    };
+    let mut checks = vec![DoctorCheck::new(
+        "imagemagick",
+        DoctorStatus::Ok,
+        format!("ImageMagick {version} {location}"),
+    )];
+
+    let missing: Vec<&str> = RECOMMENDED_DELEGATES
+        .iter()
+        .copied()
+        .filter(|delegate| !report.delegates.iter().any(|d| d == delegate))
+        .collect();
+    checks.push(if missing.is_empty() {
+        DoctorCheck::new(
+            "delegates",
+            DoctorStatus::Ok,
+            format!("Built-in delegates: {}", report.delegates.join(" ")),
+        )
+    } else {
+        DoctorCheck::new(
+            "delegates",
+            DoctorStatus::Warning,
+            format!(
+                "Missing recommended delegates: {}. Formats which need them will fail",
+                missing.join(" ")
+            ),
+        )
+    });
+    checks
+}
+
+/// Check that the config file, if any, can be parsed
+fn config_check() -> DoctorCheck {
+    let path = config_path()
+        .map(|p| p.display().to_string())
+        .unwrap_or_default();
+    match Config::load() {
+        Ok(_) if config_path().is_some_and(|p| p.exists()) => {
+            DoctorCheck::new("config", DoctorStatus::Ok, format!("Loaded {path}"))
+        }
+        Ok(_) => DoctorCheck::new(
+            "config",
+            DoctorStatus::Ok,
+            format!("No config file at {path}, using defaults"),
+        ),
+        Err(e) => DoctorCheck::new("config", DoctorStatus::Error, e.to_string()),
+    }
+}
+
+/// Check that the shared data directory is writable
+fn data_dir_check() -> DoctorCheck {
+    let Some(dir) = data_dir() else {
+        return DoctorCheck::new(
+            "data_dir",
+            DoctorStatus::Error,
+            "Data directory could not be determined",
+        );
+    };
+    match DataDirLock::acquire(&dir) {
+        Ok(_) => DoctorCheck::new(
+            "data_dir",
+            DoctorStatus::Ok,
+            format!("{} is writable", dir.display()),
+        ),
+        Err(e) => DoctorCheck::new(
+            "data_dir",
+            DoctorStatus::Error,
+            format!("{} is not writable: {e}", dir.display()),
+        ),
+    }
+}
+
+/// Check that saved functions can be written
+fn functions_dir_check() -> DoctorCheck {
+    let Some(dir) = functions_dir() else {
+        return DoctorCheck::new(
+            "functions_dir",
+            DoctorStatus::Error,
+            "Functions directory could not be determined",
+        );
+    };
+    let probe = dir.join(".write-check");
+    let written = fs::create_dir_all(&dir)
+        .and_then(|_| fs::write(&probe, b""))
+        .and_then(|_| fs::remove_file(&probe));
+    match written {
+        Ok(()) => DoctorCheck::new(
+            "functions_dir",
+            DoctorStatus::Ok,
+            format!("{} is writable", dir.display()),
+        ),
+        Err(e) => DoctorCheck::new(
+            "functions_dir",
+            DoctorStatus::Error,
+            format!("{} is not writable: {e}", dir.display()),
+        ),
+    }
+}
+
+/// Report the size of the build cache
+fn cache_check() -> DoctorCheck {
+    match BuildCache::new().stats() {
+        Ok(stats) => DoctorCheck::new(
+            "cache",
+            DoctorStatus::Ok,
+            format!("{} cached outputs, {} bytes", stats.entries, stats.bytes),
+        ),
+        Err(e) => DoctorCheck::new(
+            "cache",
+            DoctorStatus::Warning,
+            format!("Cache could not be read: {e}"),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_magick_checks_warn_about_missing_delegates() {
+        let report = CheckReport {
+            installed: true,
+            path: Some("/usr/bin/magick".to_string()),
+            version: Some("7.1.2-8".to_string()),
+            delegates: vec!["png".to_string(), "jpeg".to_string()],
+            ..CheckReport::default()
+        };
+
+        let checks = magick_checks(&report);
+
+        assert_eq!(checks[0].status, DoctorStatus::Ok);
+        assert_eq!(checks[0].message, "ImageMagick 7.1.2-8 at /usr/bin/magick");
+        assert_eq!(checks[1].status, DoctorStatus::Warning);
+        assert!(checks[1].message.contains("webp freetype"));
+    }
+
+    #[test]
+    fn test_report_is_not_ok_with_errors() {
+        let report = CheckReport {
+            instructions: Some("Install it".to_string()),
+            ..CheckReport::default()
+        };
+
+        let doctor_report = DoctorReport::new(report.clone(), magick_checks(&report));
+
+        assert!(!doctor_report.ok);
+        assert_eq!(doctor_report.checks[0].message, "Install it");
+    }
+}
@@ -0,0 +1,369 @@
+use crate::feature::shell::{CommandOutput, CommandRunner, ShellError};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Environment variable which enables fault injection in debug builds, e.g.
+/// `timeout=0.05,exit=0.1,utf8=0.05,seed=7`
+pub const FAULTS_ENV: &str = "MAGICK_MCP_FAULTS";
+
+/// Bytes returned in place of a command's output by a garbage UTF-8 fault
+const GARBAGE: &[u8] = &[0xff, 0xfe, 0xc3, 0x28, 0xa0, 0xa1];
+
+/// Error type for an invalid `MAGICK_MCP_FAULTS` value
+#[derive(Debug, Error)]
+pub enum FaultConfigError {
+    #[error("unknown fault '{0}', expected timeout, exit, utf8, seed, or delay_ms")]
+    UnknownKey(String),
+    #[error("invalid value '{value}' for '{key}'")]
+    InvalidValue { key: String, value: String },
+    #[error("fault probabilities add up to {0}, which is more than 1")]
+    TooLikely(f64),
+}
+
+/// How often each kind of fault is injected
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FaultConfig {
+    /// Probability a command times out
+    pub timeout: f64,
+    /// Probability a command exits with a nonzero code
+    pub exit: f64,
+    /// Probability a command's output is not valid UTF-8
+    pub garbage_utf8: f64,
+    /// Seed for the random choices, so a failing run can be repeated. Seeded from the clock
+    /// when `None`
+    pub seed: Option<u64>,
+    /// How long a command which times out hangs first, in milliseconds
+    pub delay_ms: u64,
+}
+
+impl FaultConfig {
+    /// Parse a comma separated list of `key=value` pairs, such as `exit=0.2,seed=7`
+    pub fn parse(spec: &str) -> Result<Self, FaultConfigError> {
+        let mut config = FaultConfig::default();
+        for pair in spec
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+        {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let invalid = || FaultConfigError::InvalidValue {
+                key: key.to_string(),
+                value: value.to_string(),
+            };
+            let probability = || {
+                value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|probability| (0.0..=1.0).contains(probability))
+                    .ok_or_else(invalid)
+            };
+            match key {
+                "timeout" => config.timeout = probability()?,
+                "exit" => config.exit = probability()?,
+                "utf8" => config.garbage_utf8 = probability()?,
+                "seed" => config.seed = Some(value.parse().map_err(|_| invalid())?),
+                "delay_ms" => config.delay_ms = value.parse().map_err(|_| invalid())?,
+                _ => return Err(FaultConfigError::UnknownKey(key.to_string())),
+            }
+        }
+        let total = config.timeout + config.exit + config.garbage_utf8;
+        if total > 1.0 {
+            return Err(FaultConfigError::TooLikely(total));
+        }
+        Ok(config)
+    }
+
+    /// Read the config from `MAGICK_MCP_FAULTS`
+    ///
+    /// Faults are only injected by debug builds, so release builds ignore the variable. An
+    /// invalid value is reported on stderr and ignored.
+    pub fn from_env() -> Option<Self> {
+        if !cfg!(debug_assertions) {
+            return None;
+        }
+        let spec = std::env::var(FAULTS_ENV).ok()?;
+        match FaultConfig::parse(&spec) {
+            Ok(config) => Some(config),
+            Err(e) => {
+                eprintln!("magick-mcp ignoring {FAULTS_ENV}: {e}");
+                None
+            }
+        }
+    }
+}
+
+/// A fault chosen for one command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fault {
+    Timeout,
+    Exit,
+    GarbageUtf8,
+}
+
+/// CommandRunner decorator which fails commands at random, for resilience testing
+///
+/// Each command either runs on the wrapped runner or is replaced by a fault: a timeout
+/// reported as an execution failure, a nonzero exit, or output which is not valid UTF-8. The
+/// choices come from a seeded generator, so a run with a fixed seed fails the same commands
+/// every time.
+pub struct FaultyCommandRunner<R> {
+    inner: R,
+    config: FaultConfig,
+    state: Mutex<u64>,
+}
+
+impl<R: CommandRunner> FaultyCommandRunner<R> {
+    /// Wrap a runner, injecting faults as often as the config says
+    pub fn new(inner: R, config: FaultConfig) -> Self {
+        let seed = config.seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_nanos() as u64)
+                .unwrap_or_default()
+        });
+        FaultyCommandRunner {
+            inner,
+            config,
+            state: Mutex::new(seed),
+        }
+    }
+
+    /// Choose whether the next command fails, and how
+    fn next_fault(&self) -> Option<Fault> {
+        let roll = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            next_random(&mut state)
+        };
+        let config = &self.config;
+        if roll < config.timeout {
+            Some(Fault::Timeout)
+        } else if roll < config.timeout + config.exit {
+            Some(Fault::Exit)
+        } else if roll < config.timeout + config.exit + config.garbage_utf8 {
+            Some(Fault::GarbageUtf8)
+        } else {
+            None
+        }
+    }
+
+    /// The error a fault is reported as
+    fn fault_error(&self, fault: Fault, command: &str, args: &[&str]) -> ShellError {
+        match fault {
+            Fault::Timeout => {
+                std::thread::sleep(Duration::from_millis(self.config.delay_ms));
+                ShellError::ExecutionFailed {
+                    message: "timed out (injected fault)".to_string(),
+                    command: command.to_string(),
+                    args: args.join(" "),
+                }
+            }
+            Fault::Exit => ShellError::NonZeroExit {
+                exit_code: 1,
+                command: command.to_string(),
+                args: args.join(" "),
+                stdout: String::new(),
+                stderr: "injected fault".to_string(),
+            },
+            Fault::GarbageUtf8 => ShellError::InvalidUtf8 {
+                command: command.to_string(),
+                args: args.join(" "),
+            },
+        }
+    }
+}
+
+impl<R: CommandRunner> CommandRunner for FaultyCommandRunner<R> {
+    fn execute(
+        &self,
+        command: &str,
+        args: &[&str],
+        working_dir: Option<&Path>,
+    ) -> Result<String, ShellError> {
+        match self.next_fault() {
+            Some(fault) => Err(self.fault_error(fault, command, args)),
+            None => self.inner.execute(command, args, working_dir),
+        }
+    }
+
+    fn execute_with_stderr(
+        &self,
+        command: &str,
+        args: &[&str],
+        working_dir: Option<&Path>,
+    ) -> Result<CommandOutput, ShellError> {
+        match self.next_fault() {
+            Some(fault) => Err(self.fault_error(fault, command, args)),
+            None => self.inner.execute_with_stderr(command, args, working_dir),
+        }
+    }
+
+    fn execute_capped(
+        &self,
+        command: &str,
+        args: &[&str],
+        working_dir: Option<&Path>,
+        max_output: usize,
+    ) -> Result<Vec<u8>, ShellError> {
+        // Raw output is never decoded here, so garbage is returned for the caller to handle
+        match self.next_fault() {
+            Some(Fault::GarbageUtf8) => Ok(GARBAGE.to_vec()),
+            Some(fault) => Err(self.fault_error(fault, command, args)),
+            None => self
+                .inner
+                .execute_capped(command, args, working_dir, max_output),
+        }
+    }
+}
+
+/// Advance a splitmix64 generator, returning a number in `[0, 1)`
+fn next_random(state: &mut u64) -> f64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::functions::{Function, FunctionRunner};
+    use crate::feature::magick::MagickRunner;
+    use std::collections::BTreeMap;
+    use tempfile::TempDir;
+
+    /// Writes each command's first argument to its output
+    struct WritingCommandRunner;
+
+    impl CommandRunner for WritingCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            let output = working_dir.unwrap().join(args[args.len() - 1]);
+            std::fs::write(output, args[0]).unwrap();
+            Ok(String::new())
+        }
+    }
+
+    fn faulty(config: &str) -> FaultyCommandRunner<WritingCommandRunner> {
+        FaultyCommandRunner::new(WritingCommandRunner, FaultConfig::parse(config).unwrap())
+    }
+
+    #[test]
+    fn test_parse_fault_config() {
+        assert_eq!(
+            FaultConfig::parse("timeout=0.1, exit=0.2,utf8=0.05,seed=7,delay_ms=50").unwrap(),
+            FaultConfig {
+                timeout: 0.1,
+                exit: 0.2,
+                garbage_utf8: 0.05,
+                seed: Some(7),
+                delay_ms: 50,
+            }
+        );
+        assert!(matches!(
+            FaultConfig::parse("crash=0.1"),
+            Err(FaultConfigError::UnknownKey(_))
+        ));
+        assert!(matches!(
+            FaultConfig::parse("exit=2"),
+            Err(FaultConfigError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            FaultConfig::parse("exit=0.6,timeout=0.6"),
+            Err(FaultConfigError::TooLikely(_))
+        ));
+    }
+
+    #[test]
+    fn test_seeded_faults_repeat() {
+        let outcomes = || {
+            let runner = faulty("exit=0.5,seed=42");
+            (0..32)
+                .map(|_| runner.next_fault().is_some())
+                .collect::<Vec<_>>()
+        };
+
+        let first = outcomes();
+        assert_eq!(first, outcomes());
+        assert!(first.contains(&true) && first.contains(&false));
+    }
+
+    #[test]
+    fn test_injected_faults_surface_as_shell_errors() {
+        let dir = TempDir::new().unwrap();
+        let run = |config: &str| {
+            let runner = faulty(config);
+            MagickRunner::new(&runner, Some(dir.path())).execute_args(&["in.png", "out.png"])
+        };
+
+        assert!(matches!(
+            run("timeout=1"),
+            Err(ShellError::ExecutionFailed { .. })
+        ));
+        assert!(matches!(
+            run("exit=1"),
+            Err(ShellError::NonZeroExit { exit_code: 1, .. })
+        ));
+        assert!(matches!(run("utf8=1"), Err(ShellError::InvalidUtf8 { .. })));
+        assert!(run("exit=0").is_ok());
+
+        let runner = faulty("utf8=1");
+        assert_eq!(
+            runner
+                .execute_capped("magick", &["in.png", "png:-"], None, 1024)
+                .unwrap(),
+            GARBAGE
+        );
+    }
+
+    #[test]
+    fn test_transactional_runs_are_all_or_nothing_under_faults() {
+        let function = Function {
+            name: "pipeline".to_string(),
+            commands: vec![
+                "$input -negate negated.png".to_string(),
+                "${step1.output} -resize 50% half.png".to_string(),
+                "${step2.output} -blur 0x2 blurred.png".to_string(),
+            ],
+            params: Default::default(),
+        };
+        let outputs = ["negated.png", "half.png", "blurred.png"];
+        let mut failures = 0;
+
+        for seed in 0..32 {
+            let dir = TempDir::new().unwrap();
+            let runner = faulty(&format!("timeout=0.1,exit=0.1,utf8=0.1,seed={seed}"));
+            let function_runner =
+                FunctionRunner::new(&runner, Some(dir.path())).with_transactional(true);
+
+            let result = function_runner.run(&function, Some("photo.png"), &BTreeMap::new());
+
+            let written = outputs
+                .iter()
+                .filter(|output| dir.path().join(output).exists())
+                .count();
+            match result {
+                Ok(_) => assert_eq!(written, outputs.len(), "seed {seed}"),
+                Err(_) => {
+                    failures += 1;
+                    assert_eq!(written, 0, "seed {seed}");
+                    assert_eq!(
+                        std::fs::read_dir(dir.path().join(".magickmcp"))
+                            .unwrap()
+                            .count(),
+                        0,
+                        "seed {seed}"
+                    );
+                }
+            }
+        }
+        assert!(failures > 0 && failures < 32);
+    }
+}
@@ -1,10 +1,22 @@
+mod builtins;
+mod export;
 mod model;
+mod params;
 mod path;
 mod runner;
 mod store;
+mod watch;
 
+pub use builtins::{
+    BuiltinBundle, BuiltinUpdate, BuiltinUpdater, BuiltinsError, DEFAULT_BUILTINS_URL,
+};
+pub use export::{ExportFormat, export_function};
 pub use model::Function;
+pub use params::ParamType;
 #[allow(unused_imports)]
 pub use path::functions_dir;
-pub use runner::FunctionRunner;
-pub use store::{FunctionStore, FunctionStoreError};
+pub use runner::{FunctionOutput, FunctionRunner};
+pub use store::{
+    FunctionEdit, FunctionSource, FunctionStore, FunctionStoreError, PROJECT_FUNCTIONS_DIR,
+};
+pub use watch::FunctionsWatcher;
@@ -0,0 +1,249 @@
+use crate::feature::config::ConfigError;
+use crate::feature::data_dir::{DataDirLock, data_dir, write_atomic};
+use crate::feature::functions::model::Function;
+use crate::feature::shell::{CommandRunner, ShellError};
+use minisign_verify::{PublicKey, Signature};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Built-in functions shipped with the binary
+const EMBEDDED_BUILTINS: &str = include_str!("builtins.json");
+
+/// Release asset the latest built-in functions are downloaded from
+pub const DEFAULT_BUILTINS_URL: &str =
+    "https://github.com/ncipollo/magick-mcp/releases/latest/download/builtins.json";
+
+/// Minisign public key the built-ins are signed with, set by release builds
+const BUILTINS_PUBLIC_KEY: Option<&str> = option_env!("MAGICK_MCP_BUILTINS_PUBLIC_KEY");
+
+/// Error type for updating built-in functions
+#[derive(Debug, Error)]
+pub enum BuiltinsError {
+    #[error("Failed to download built-in functions: {0}")]
+    DownloadFailed(ShellError),
+    #[error(
+        "No public key is configured to verify built-in functions. Set public_key in the [builtins] section of config.toml"
+    )]
+    MissingPublicKey,
+    #[error("Invalid signature for built-in functions: {0}")]
+    InvalidSignature(String),
+    #[error("Failed to parse built-in functions: {0}")]
+    ParseError(#[from] serde_json::Error),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error("Data directory not found")]
+    DataDirNotFound,
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+/// A versioned set of curated functions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuiltinBundle {
+    /// Increases with every published set
+    pub version: u32,
+    /// The curated functions
+    pub functions: Vec<Function>,
+}
+
+/// Outcome of checking for newer built-in functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuiltinUpdate {
+    /// Version of the built-ins before the update
+    pub previous_version: u32,
+    /// Version of the built-ins after the update
+    pub version: u32,
+}
+
+impl BuiltinUpdate {
+    /// Whether a newer set was installed
+    pub fn updated(&self) -> bool {
+        self.version > self.previous_version
+    }
+}
+
+/// Path the downloaded built-ins are stored at
+pub(crate) fn updated_builtins_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("builtins.json"))
+}
+
+/// The built-in functions, preferring a downloaded set newer than the embedded one
+pub(crate) fn builtins() -> BuiltinBundle {
+    let embedded: BuiltinBundle =
+        serde_json::from_str(EMBEDDED_BUILTINS).expect("embedded built-ins are valid JSON");
+    updated_builtins_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str::<BuiltinBundle>(&contents).ok())
+        .filter(|downloaded| downloaded.version > embedded.version)
+        .unwrap_or(embedded)
+}
+
+/// Check a downloaded bundle against its minisign signature and parse it
+///
+/// # Arguments
+///
+/// * `contents` - The downloaded `builtins.json`
+/// * `signature` - The downloaded `builtins.json.minisig`
+/// * `public_key` - Base64 minisign public key the bundle must be signed with
+pub(crate) fn verify_bundle(
+    contents: &str,
+    signature: &str,
+    public_key: &str,
+) -> Result<BuiltinBundle, BuiltinsError> {
+    let invalid = |e: minisign_verify::Error| BuiltinsError::InvalidSignature(e.to_string());
+    let public_key = PublicKey::from_base64(public_key.trim()).map_err(invalid)?;
+    let signature = Signature::decode(signature).map_err(invalid)?;
+    public_key
+        .verify(contents.as_bytes(), &signature, false)
+        .map_err(invalid)?;
+    Ok(serde_json::from_str(contents)?)
+}
+
+/// Downloads signed built-in functions from a release channel
+pub struct BuiltinUpdater<'a> {
+    command_runner: &'a dyn CommandRunner,
+    url: String,
+    public_key: Option<String>,
+}
+
+impl<'a> BuiltinUpdater<'a> {
+    /// Create an updater for the default release channel and signing key
+    pub fn new(command_runner: &'a dyn CommandRunner) -> Self {
+        BuiltinUpdater {
+            command_runner,
+            url: DEFAULT_BUILTINS_URL.to_string(),
+            public_key: BUILTINS_PUBLIC_KEY.map(str::to_string),
+        }
+    }
+
+    /// Download from a different URL, with the signature at the same URL plus `.minisig`
+    pub fn with_url(mut self, url: &str) -> Self {
+        self.url = url.to_string();
+        self
+    }
+
+    /// Verify downloads with a different minisign public key
+    pub fn with_public_key(mut self, public_key: &str) -> Self {
+        self.public_key = Some(public_key.to_string());
+        self
+    }
+
+    /// Download, verify, and install the latest built-ins if they are newer
+    ///
+    /// # Returns
+    ///
+    /// Returns the previous and current versions, or a `BuiltinsError` if the download, signature,
+    /// or install fails
+    pub fn update(&self) -> Result<BuiltinUpdate, BuiltinsError> {
+        let public_key = self
+            .public_key
+            .as_deref()
+            .ok_or(BuiltinsError::MissingPublicKey)?;
+        let contents = self.download(&self.url)?;
+        let signature = self.download(&format!("{}.minisig", self.url))?;
+        let bundle = verify_bundle(&contents, &signature, public_key)?;
+
+        let previous_version = builtins().version;
+        if bundle.version > previous_version {
+            let dir = data_dir().ok_or(BuiltinsError::DataDirNotFound)?;
+            let _lock = DataDirLock::acquire(&dir)?;
+            let path = updated_builtins_path().ok_or(BuiltinsError::DataDirNotFound)?;
+            write_atomic(&path, contents)?;
+        }
+        Ok(BuiltinUpdate {
+            previous_version,
+            version: bundle.version.max(previous_version),
+        })
+    }
+
+    /// Download a URL as text using `curl`
+    fn download(&self, url: &str) -> Result<String, BuiltinsError> {
+        self.command_runner
+            .execute("curl", &["-fsSL", "--max-time", "30", url], None)
+            .map_err(BuiltinsError::DownloadFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    const TEST_PUBLIC_KEY: &str = "RWQBAgMEBQYHCAOhB7/zzhC+HXDdGOdLwJln5NYwm6UNXx3chmQSVTG4";
+    const TEST_BUNDLE: &str = r#"{"version":2,"functions":[{"name":"invert","commands":["$input -negate inverted.png"]}]}"#;
+    const TEST_SIGNATURE: &str = "untrusted comment: signature from magick-mcp test key
+RUQBAgMEBQYHCIbl2o8M+haRFAHHhRm7e6qE9BxE83pA6VGGpsPXoJRaOlvjk98gIobYRSMbAc8He3mbzFDFOs19N6RyGxsubQg=
+trusted comment: timestamp:1700000000\tfile:builtins.json
+NpbOfJ8x4RnNTNTcnbpf0NuQ3RZPpsdYsHIv3jT+l0ODn9Chwb7OaZbVvOByGXsNQ1EkBZwJGczmHwqTUk13Cg==
+";
+
+    /// Mock implementation of CommandRunner serving a bundle and signature
+    struct MockCommandRunner {
+        bundle: String,
+        urls: RefCell<Vec<String>>,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            _working_dir: Option<&std::path::Path>,
+        ) -> Result<String, ShellError> {
+            let url = args.last().unwrap().to_string();
+            self.urls.borrow_mut().push(url.clone());
+            if url.ends_with(".minisig") {
+                Ok(TEST_SIGNATURE.to_string())
+            } else {
+                Ok(self.bundle.clone())
+            }
+        }
+    }
+
+    #[test]
+    fn test_embedded_builtins_parse() {
+        let embedded: BuiltinBundle = serde_json::from_str(EMBEDDED_BUILTINS).unwrap();
+        assert!(!embedded.functions.is_empty());
+    }
+
+    #[test]
+    fn test_verify_bundle() {
+        let bundle = verify_bundle(TEST_BUNDLE, TEST_SIGNATURE, TEST_PUBLIC_KEY).unwrap();
+        assert_eq!(bundle.version, 2);
+        assert_eq!(bundle.functions[0].name, "invert");
+    }
+
+    #[test]
+    fn test_verify_bundle_rejects_tampering() {
+        let tampered = TEST_BUNDLE.replace("-negate", "-flip");
+        assert!(matches!(
+            verify_bundle(&tampered, TEST_SIGNATURE, TEST_PUBLIC_KEY),
+            Err(BuiltinsError::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn test_update_rejects_tampered_download_before_writing() {
+        let runner = MockCommandRunner {
+            bundle: TEST_BUNDLE.replace("inverted", "hacked"),
+            urls: RefCell::new(Vec::new()),
+        };
+        let updater = BuiltinUpdater::new(&runner)
+            .with_url("https://example.com/builtins.json")
+            .with_public_key(TEST_PUBLIC_KEY);
+
+        assert!(matches!(
+            updater.update(),
+            Err(BuiltinsError::InvalidSignature(_))
+        ));
+        assert_eq!(
+            *runner.urls.borrow(),
+            vec![
+                "https://example.com/builtins.json",
+                "https://example.com/builtins.json.minisig"
+            ]
+        );
+    }
+}
@@ -0,0 +1,334 @@
+use crate::feature::functions::model::Function;
+use crate::feature::functions::params::ParamType;
+use crate::feature::output;
+use crate::feature::template::{Template, TemplateError};
+
+/// Prefix of placeholders resolved from environment variables, e.g. `${env.HOME}`
+const ENV_PREFIX: &str = "env.";
+
+/// Build system a function is exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A Makefile with a target per written file, so unchanged outputs are not rebuilt
+    Make,
+    /// A justfile recipe running the commands in order
+    Just,
+}
+
+impl ExportFormat {
+    /// Reference to a variable holding an input or parameter value
+    fn variable(self, name: &str) -> String {
+        match self {
+            ExportFormat::Make => format!("$({})", name.to_ascii_uppercase()),
+            ExportFormat::Just => format!("{{{{{name}}}}}"),
+        }
+    }
+
+    /// Reference to an environment variable
+    fn env(self, name: &str) -> String {
+        match self {
+            ExportFormat::Make => format!("$({name})"),
+            ExportFormat::Just => format!("{{{{env_var(\"{name}\")}}}}"),
+        }
+    }
+
+    /// Escape literal command text so the build system passes it through unchanged
+    fn escape(self, text: &str) -> String {
+        match self {
+            ExportFormat::Make => text.replace('$', "$$"),
+            ExportFormat::Just => text.replace("{{", "{{{{"),
+        }
+    }
+}
+
+/// A function command rendered in the build system's syntax
+struct Step {
+    /// Shell-quoted `magick` arguments
+    args: Vec<String>,
+    /// Arguments before shell quoting, for matching them against earlier outputs
+    unquoted: Vec<String>,
+    /// The file the command writes, if it writes a single file
+    output: Option<String>,
+}
+
+/// Render a function as Makefile targets or a justfile recipe
+///
+/// Placeholders become variables: `$input` is `INPUT` in a Makefile and the `input` recipe
+/// parameter in a justfile, and declared parameters likewise. `${env.NAME}` reads the
+/// environment variable and `${stepN.output}` is the file the Nth command writes.
+///
+/// In a Makefile, each command writing a file is a target depending on the input, path
+/// parameters, and earlier outputs it reads, so make runs the steps in dependency order and
+/// skips outputs which are up to date. Commands which don't write a file become phony targets
+/// which run after the command before them. The function's name is a phony target building
+/// every step.
+///
+/// # Arguments
+///
+/// * `function` - The function to export
+/// * `format` - The build system to render for
+///
+/// # Returns
+///
+/// Returns the file contents, or a `TemplateError` if a command is malformed or uses a
+/// placeholder the function does not provide
+pub fn export_function(function: &Function, format: ExportFormat) -> Result<String, TemplateError> {
+    let mut steps: Vec<Step> = Vec::new();
+    for command in &function.commands {
+        let template = Template::parse(command)?;
+        let unquoted = template.render_with(
+            |text| format.escape(text),
+            |name| placeholder(function, format, &steps, name),
+        )?;
+        let arg_refs: Vec<&str> = unquoted.iter().map(String::as_str).collect();
+        let output = output::output_path(&arg_refs).map(|path| path.to_string_lossy().to_string());
+        steps.push(Step {
+            args: unquoted.iter().map(|arg| shell_quote(arg)).collect(),
+            unquoted,
+            output,
+        });
+    }
+    let variables = variables(function);
+    let target = target_name(&function.name);
+    Ok(match format {
+        ExportFormat::Make => render_make(function, &target, &variables, &steps),
+        ExportFormat::Just => render_just(&target, &variables, &steps),
+    })
+}
+
+/// The build system reference for a placeholder
+fn placeholder(
+    function: &Function,
+    format: ExportFormat,
+    steps: &[Step],
+    name: &str,
+) -> Result<String, TemplateError> {
+    let unbound = || TemplateError::Unbound {
+        name: name.to_string(),
+    };
+    if name == "input" || function.params.contains_key(name) {
+        return Ok(format.variable(name));
+    }
+    if let Some(variable) = name.strip_prefix(ENV_PREFIX) {
+        return Ok(format.env(variable));
+    }
+    let step = name
+        .strip_prefix("step")
+        .and_then(|rest| rest.strip_suffix(".output"))
+        .and_then(|index| index.parse::<usize>().ok())
+        .ok_or_else(unbound)?;
+    step.checked_sub(1)
+        .and_then(|index| steps.get(index))
+        .and_then(|step| step.output.clone())
+        .ok_or_else(unbound)
+}
+
+/// The variables a function takes: `input` when a command uses it, then its parameters
+fn variables(function: &Function) -> Vec<String> {
+    let mut variables = Vec::new();
+    if function.uses_input() {
+        variables.push("input".to_string());
+    }
+    variables.extend(function.params.keys().cloned());
+    variables
+}
+
+/// A target or recipe name made of the characters both build systems accept
+fn target_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+fn render_make(function: &Function, target: &str, variables: &[String], steps: &[Step]) -> String {
+    let usage: String = variables
+        .iter()
+        .map(|name| format!(" {}=...", name.to_ascii_uppercase()))
+        .collect();
+    let mut make = format!(
+        "# Generated by magick-mcp from the '{}' function\n# Usage: make {target}{usage}\n\n",
+        function.name
+    );
+    for name in variables {
+        make.push_str(&format!("{} ?=\n", name.to_ascii_uppercase()));
+    }
+    if !variables.is_empty() {
+        make.push('\n');
+    }
+
+    // Files make can depend on: the input, path parameters, and outputs written so far
+    let mut files: Vec<String> = variables
+        .iter()
+        .filter(|name| *name == "input" || function.params.get(*name) == Some(&ParamType::Path))
+        .map(|name| ExportFormat::Make.variable(name))
+        .collect();
+    let mut targets: Vec<String> = Vec::new();
+    let mut phony = vec![target.to_string()];
+    let mut rules = String::new();
+    for (index, step) in steps.iter().enumerate() {
+        let inputs = match &step.output {
+            Some(_) => &step.unquoted[..step.unquoted.len() - 1],
+            None => &step.unquoted[..],
+        };
+        let mut dependencies: Vec<String> = Vec::new();
+        for input in inputs {
+            let path = output::strip_format_prefix(input);
+            let path = path.split('[').next().unwrap_or(path);
+            if files.iter().any(|file| file == path) && !dependencies.iter().any(|d| d == path) {
+                dependencies.push(path.to_string());
+            }
+        }
+        if let Some(previous) = targets.last().filter(|previous| phony.contains(previous))
+            && !dependencies.contains(previous)
+        {
+            dependencies.push(previous.clone());
+        }
+        let step_target = match step
+            .output
+            .as_deref()
+            .filter(|output| is_file_target(output))
+        {
+            Some(output) => {
+                files.push(output.to_string());
+                output.to_string()
+            }
+            None => {
+                let name = format!("{target}-step{}", index + 1);
+                phony.push(name.clone());
+                name
+            }
+        };
+        rules.push_str(&format!(
+            "\n{step_target}:{}\n\tmagick {}\n",
+            dependencies
+                .iter()
+                .map(|dependency| format!(" {dependency}"))
+                .collect::<String>(),
+            step.args.join(" ")
+        ));
+        if !targets.contains(&step_target) {
+            targets.push(step_target);
+        }
+    }
+    make.push_str(&format!(".PHONY: {}\n\n", phony.join(" ")));
+    make.push_str(&format!(
+        "{target}:{}\n",
+        targets
+            .iter()
+            .map(|target| format!(" {target}"))
+            .collect::<String>()
+    ));
+    make.push_str(&rules);
+    make
+}
+
+fn render_just(target: &str, variables: &[String], steps: &[Step]) -> String {
+    let parameters: String = variables.iter().map(|name| format!(" {name}")).collect();
+    let mut just = format!("# Generated by magick-mcp\n\n{target}{parameters}:\n");
+    for step in steps {
+        just.push_str(&format!("    magick {}\n", step.args.join(" ")));
+    }
+    just
+}
+
+/// Whether make can use an output as a file target
+///
+/// Frame patterns such as `out-%d.png` would be read as pattern rules, and names with spaces as
+/// several targets.
+fn is_file_target(output: &str) -> bool {
+    !output.contains(['%', ' ', '\t'])
+}
+
+/// Quote an argument for the shell running the recipe when it contains anything but plain
+/// path characters
+fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_./%:=,@+-".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn function(commands: &[&str]) -> Function {
+        Function {
+            name: "thumb".to_string(),
+            commands: commands.iter().map(|command| command.to_string()).collect(),
+            params: BTreeMap::from([("size".to_string(), ParamType::Geometry)]),
+        }
+    }
+
+    #[test]
+    fn test_export_make_orders_targets_by_dependency() {
+        let function = function(&[
+            "$input -resize $size resized.png",
+            "${step1.output} -colorspace Gray \"gray $$.png\"",
+            "identify resized.png",
+            "resized.png -quality 80 final.jpg",
+        ]);
+
+        let make = export_function(&function, ExportFormat::Make).unwrap();
+
+        assert!(make.contains("# Usage: make thumb INPUT=... SIZE=...\n"));
+        assert!(make.contains("INPUT ?=\nSIZE ?=\n"));
+        assert!(make.contains(".PHONY: thumb thumb-step2 thumb-step3\n"));
+        assert!(make.contains("thumb: resized.png thumb-step2 thumb-step3 final.jpg\n"));
+        assert!(make.contains(
+            "\nresized.png: $(INPUT)\n\tmagick '$(INPUT)' -resize '$(SIZE)' resized.png\n"
+        ));
+        assert!(make.contains(
+            "\nthumb-step2: resized.png\n\tmagick resized.png -colorspace Gray 'gray $$.png'\n"
+        ));
+        assert!(
+            make.contains(
+                "\nthumb-step3: resized.png thumb-step2\n\tmagick identify resized.png\n"
+            )
+        );
+        assert!(make.contains("\nfinal.jpg: resized.png thumb-step3\n"));
+    }
+
+    #[test]
+    fn test_export_just() {
+        let function = function(&[
+            "$input -resize $size 'it'\"'\"'s.png'",
+            "${step1.output} -annotate +0+0 '{{x}}' ${env.OUT_DIR}/out.png",
+        ]);
+
+        let just = export_function(&function, ExportFormat::Just).unwrap();
+
+        assert_eq!(
+            just,
+            "# Generated by magick-mcp\n\nthumb input size:\n    magick '{{input}}' -resize '{{size}}' 'it'\\''s.png'\n    magick 'it'\\''s.png' -annotate +0+0 '{{{{x}}' '{{env_var(\"OUT_DIR\")}}/out.png'\n"
+        );
+    }
+
+    #[test]
+    fn test_export_rejects_unknown_placeholders() {
+        let result = export_function(
+            &function(&["$input -negate $color out.png"]),
+            ExportFormat::Make,
+        );
+        assert_eq!(
+            result,
+            Err(TemplateError::Unbound {
+                name: "color".to_string()
+            })
+        );
+        let result = export_function(&function(&["identify ${step1.output}"]), ExportFormat::Just);
+        assert!(result.is_err());
+    }
+}
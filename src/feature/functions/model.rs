@@ -1,4 +1,7 @@
+use crate::feature::functions::params::ParamType;
+use crate::feature::template::Template;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// A function containing a series of ImageMagick commands
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -7,6 +10,37 @@ pub struct Function {
     pub name: String,
     /// Array of magick commands to execute in sequence
     pub commands: Vec<String>,
+    /// Parameters the commands use as `$name`, with the type their values must have
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, ParamType>,
+}
+
+impl Function {
+    /// Whether any command uses the `$input` placeholder
+    pub fn uses_input(&self) -> bool {
+        self.commands
+            .iter()
+            .any(|command| Template::parse(command).is_ok_and(|template| template.uses("input")))
+    }
+
+    /// Check that the function can be run: it has a name and at least one command, and every
+    /// command is a valid template
+    ///
+    /// # Returns
+    ///
+    /// Returns a message describing the first problem found
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("name must not be empty".to_string());
+        }
+        if self.commands.is_empty() {
+            return Err("commands must not be empty".to_string());
+        }
+        for (index, command) in self.commands.iter().enumerate() {
+            Template::parse(command).map_err(|e| format!("command {index}: {e}"))?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -21,6 +55,7 @@ mod tests {
                 "input.png -negate output1.png".to_string(),
                 "output1.png -resize 50% output2.png".to_string(),
             ],
+            params: Default::default(),
         };
 
         let json = serde_json::to_string(&function).unwrap();
@@ -29,4 +64,20 @@ mod tests {
         assert_eq!(function.name, deserialized.name);
         assert_eq!(function.commands, deserialized.commands);
     }
+
+    #[test]
+    fn test_validate() {
+        let mut function = Function {
+            name: "thumb".to_string(),
+            commands: vec!["$input -thumbnail $size $output".to_string()],
+            params: Default::default(),
+        };
+        assert!(function.validate().is_ok());
+
+        function.commands[0] = "$input -thumbnail ${size $output".to_string();
+        assert!(function.validate().unwrap_err().starts_with("command 0:"));
+
+        function.commands.clear();
+        assert!(function.validate().is_err());
+    }
 }
@@ -0,0 +1,163 @@
+use crate::feature::output::strip_format_prefix;
+use serde::{Deserialize, Serialize};
+
+/// Type of a function parameter, which values substituted for it are validated against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParamType {
+    /// A file path, e.g. `photos/a.png`
+    Path,
+    /// A number, e.g. `50` or `-1.5`
+    Number,
+    /// An ImageMagick geometry, e.g. `800x600>` or `+10+20`
+    Geometry,
+    /// A color name, hex value, or function, e.g. `white`, `#ff000080`, or `rgb(255,0,0)`
+    Color,
+    /// Free text such as a caption
+    Text,
+}
+
+impl ParamType {
+    /// Check a value substituted for a parameter of this type
+    ///
+    /// Every type rejects values which look like options, such as `-write`, so a value can't
+    /// add options to a command. Paths also can't start with `@`, `|`, or `+`, or use a coder
+    /// prefix such as `text:`, which would make ImageMagick read something other than the file.
+    ///
+    /// # Returns
+    ///
+    /// Returns a message describing why the value was rejected
+    pub fn validate(self, value: &str) -> Result<(), String> {
+        if value.is_empty() {
+            return Err("must not be empty".to_string());
+        }
+        if value.chars().any(char::is_control) {
+            return Err("must not contain control characters".to_string());
+        }
+        if looks_like_option(value) {
+            return Err(format!("'{value}' looks like an option"));
+        }
+        match self {
+            ParamType::Path => validate_path(value),
+            ParamType::Number => value
+                .parse::<f64>()
+                .ok()
+                .filter(|number| number.is_finite())
+                .map(|_| ())
+                .ok_or_else(|| format!("'{value}' is not a number")),
+            ParamType::Geometry => validate_geometry(value),
+            ParamType::Color => validate_color(value),
+            ParamType::Text if value.starts_with('@') => {
+                Err("text must not start with '@', which reads a file".to_string())
+            }
+            ParamType::Text => Ok(()),
+        }
+    }
+}
+
+/// Whether a value would be read as an option, e.g. `-write` or `+profile`
+fn looks_like_option(value: &str) -> bool {
+    let mut chars = value.chars();
+    matches!(chars.next(), Some('-' | '+')) && chars.next().is_some_and(char::is_alphabetic)
+}
+
+fn validate_path(value: &str) -> Result<(), String> {
+    if value.starts_with(['-', '+', '@', '|']) {
+        return Err(format!(
+            "path '{value}' must not start with '{}'",
+            &value[..1]
+        ));
+    }
+    if strip_format_prefix(value).len() < value.len() {
+        return Err(format!(
+            "path '{value}' must not start with a format prefix such as 'png:'"
+        ));
+    }
+    Ok(())
+}
+
+fn validate_geometry(value: &str) -> Result<(), String> {
+    let valid = value.chars().any(|c| c.is_ascii_digit())
+        && value
+            .chars()
+            .all(|c| c.is_ascii_digit() || ".xX%!<>^@+-".contains(c));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "'{value}' is not a geometry such as 800x600 or +10+20"
+        ))
+    }
+}
+
+fn validate_color(value: &str) -> Result<(), String> {
+    let valid = if let Some(hex) = value.strip_prefix('#') {
+        [3, 4, 6, 8, 12, 16].contains(&hex.len()) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    } else if let Some((name, arguments)) = value.split_once('(') {
+        name.chars().all(|c| c.is_ascii_alphabetic())
+            && arguments.strip_suffix(')').is_some_and(|arguments| {
+                arguments
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || " .,%".contains(c))
+            })
+    } else {
+        value.chars().all(|c| c.is_ascii_alphanumeric())
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "'{value}' is not a color such as white, #ff0000, or rgb(255,0,0)"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_options_for_every_type() {
+        for param_type in [
+            ParamType::Path,
+            ParamType::Number,
+            ParamType::Geometry,
+            ParamType::Color,
+            ParamType::Text,
+        ] {
+            assert!(param_type.validate("-write").is_err());
+            assert!(param_type.validate("+profile").is_err());
+            assert!(param_type.validate("").is_err());
+            assert!(param_type.validate("a\nb").is_err());
+        }
+    }
+
+    #[test]
+    fn test_validate_types() {
+        assert!(ParamType::Path.validate("My Photos/a.png").is_ok());
+        assert!(ParamType::Path.validate("C:\\photos\\a.png").is_ok());
+        assert!(ParamType::Path.validate("@list.txt").is_err());
+        assert!(ParamType::Path.validate("text:/etc/passwd").is_err());
+        assert!(ParamType::Path.validate("-").is_err());
+
+        assert!(ParamType::Number.validate("-1.5").is_ok());
+        assert!(ParamType::Number.validate("NaN").is_err());
+        assert!(ParamType::Number.validate("50%").is_err());
+
+        assert!(ParamType::Geometry.validate("800x600>").is_ok());
+        assert!(ParamType::Geometry.validate("+10-20").is_ok());
+        assert!(ParamType::Geometry.validate("50%").is_ok());
+        assert!(ParamType::Geometry.validate("800x600 -write x").is_err());
+
+        assert!(ParamType::Color.validate("white").is_ok());
+        assert!(ParamType::Color.validate("#ff000080").is_ok());
+        assert!(ParamType::Color.validate("rgba(255, 0, 0, 0.5)").is_ok());
+        assert!(ParamType::Color.validate("#ff00").is_ok());
+        assert!(ParamType::Color.validate("#ff0").is_ok());
+        assert!(ParamType::Color.validate("#ff00g0").is_err());
+        assert!(ParamType::Color.validate("rgb(1,2,3) x").is_err());
+
+        assert!(ParamType::Text.validate("-5 degrees").is_ok());
+        assert!(ParamType::Text.validate("@secret.txt").is_err());
+    }
+}
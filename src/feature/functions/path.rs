@@ -1,3 +1,4 @@
+use crate::feature::data_dir::data_dir;
 use std::path::PathBuf;
 
 /// Get the platform-specific directory for storing magick functions
@@ -9,7 +10,7 @@ use std::path::PathBuf;
 ///
 /// Returns `None` if the data directory cannot be determined.
 pub fn functions_dir() -> Option<PathBuf> {
-    dirs::data_dir().map(|dir| dir.join("magick-mcp").join("functions"))
+    data_dir().map(|dir| dir.join("functions"))
 }
 
 #[cfg(test)]
@@ -1,11 +1,31 @@
 use crate::feature::functions::model::Function;
+use crate::feature::functions::params::ParamType;
 use crate::feature::magick::MagickRunner;
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
 use crate::feature::shell::{CommandRunner, ShellError};
+use crate::feature::template::{Bindings, Template};
+use crate::feature::transaction::Transaction;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::path::Path;
 
+/// Output of a function run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionOutput {
+    /// Text written to standard output by each command
+    pub outputs: Vec<String>,
+    /// Files written (or skipped) by the commands, after applying the output policy
+    pub files: Vec<ResolvedOutput>,
+}
+
 /// Runner for executing magick functions (sequences of commands)
 pub struct FunctionRunner<'a> {
-    magick_runner: MagickRunner<'a>,
+    command_runner: &'a dyn CommandRunner,
+    workspace: Option<&'a Path>,
+    output_policy: OutputPolicy,
+    transactional: bool,
+    env: Vec<String>,
+    resolved_outputs: RefCell<Vec<ResolvedOutput>>,
 }
 
 impl<'a> FunctionRunner<'a> {
@@ -17,16 +37,56 @@ impl<'a> FunctionRunner<'a> {
     /// * `workspace` - Optional workspace path to set as the working directory
     pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
         FunctionRunner {
-            magick_runner: MagickRunner::new(command_runner, workspace),
+            command_runner,
+            workspace,
+            output_policy: OutputPolicy::default(),
+            transactional: false,
+            env: Vec::new(),
+            resolved_outputs: RefCell::new(Vec::new()),
         }
     }
 
+    /// Use the provided policy for files written by the function's commands
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.output_policy = output_policy;
+        self
+    }
+
+    /// Stage every file the commands write and only move them into place once all succeed
+    ///
+    /// When a command fails, the staged files are discarded and the workspace is left as it
+    /// was before the run.
+    pub fn with_transactional(mut self, transactional: bool) -> Self {
+        self.transactional = transactional;
+        self
+    }
+
+    /// Let commands read the listed environment variables as `${env.NAME}`
+    ///
+    /// Commands reading any other variable fail, so a function can't leak secrets from the
+    /// server's environment into its arguments.
+    pub fn with_env(mut self, env: Vec<String>) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Take the files written (or skipped) by commands run so far
+    pub fn take_resolved_outputs(&self) -> Vec<ResolvedOutput> {
+        self.resolved_outputs.take()
+    }
+
     /// Execute all commands in a function sequentially
     ///
+    /// Commands are templates whose placeholders are `$input`, the function's declared
+    /// parameters, `${env.NAME}` for environment variables allowed by `with_env`, and
+    /// `${stepN.output}` for the file written by the Nth command. The input and parameter values are validated against their
+    /// types before any command runs, with `$input` treated as a path.
+    ///
     /// # Arguments
     ///
     /// * `function` - The function containing commands to execute
     /// * `input` - Optional input value to replace `$input` placeholders in commands
+    /// * `params` - Values for the function's declared parameters
     ///
     /// # Returns
     ///
@@ -34,38 +94,116 @@ impl<'a> FunctionRunner<'a> {
     ///
     /// # Errors
     ///
-    /// Returns `ShellError::MissingInputVariable` if a command contains `$input` but no input was provided
-    pub fn run(&self, function: &Function, input: Option<&str>) -> Result<Vec<String>, ShellError> {
-        let mut outputs = Vec::new();
-        for command in &function.commands {
-            // Check if command contains $input placeholder
-            if command.contains("$input") {
-                // If $input is found but no input provided, return error
-                let input_value = input.ok_or(ShellError::MissingInputVariable)?;
-                // Replace $input with the provided value
-                let processed_command = command.replace("$input", input_value);
-                let output = self.magick_runner.execute(&processed_command)?;
-                outputs.push(output);
-            } else {
-                // No $input placeholder, execute command as-is
-                let output = self.magick_runner.execute(command)?;
-                outputs.push(output);
-            }
+    /// Returns `ShellError::MissingInputVariable` if a command contains `$input` but no input was provided,
+    /// `ShellError::InvalidParameterValue` if a value is undeclared or has the wrong type, or
+    /// `ShellError::TransactionFailed` if a transactional run could not stage or commit its files
+    pub fn run(
+        &self,
+        function: &Function,
+        input: Option<&str>,
+        params: &BTreeMap<String, String>,
+    ) -> Result<Vec<String>, ShellError> {
+        let mut bindings = Bindings::new().with_env(&self.env);
+        if let Some(input) = input {
+            validate_value("input", ParamType::Path, input)?;
+            bindings.set("input", input);
+        }
+        for (name, value) in params {
+            let param_type = function.params.get(name).copied().ok_or_else(|| {
+                ShellError::InvalidParameterValue {
+                    name: name.clone(),
+                    message: format!(
+                        "function '{}' does not declare this parameter",
+                        function.name
+                    ),
+                }
+            })?;
+            validate_value(name, param_type, value)?;
+            bindings.set(name, value.as_str());
+        }
+
+        let transaction = if self.transactional {
+            Some(
+                Transaction::begin(self.workspace).map_err(|e| ShellError::TransactionFailed {
+                    message: format!("failed to create the staging area: {e}"),
+                })?,
+            )
+        } else {
+            None
+        };
+        let mut magick_runner = MagickRunner::new(self.command_runner, self.workspace)
+            .with_output_policy(self.output_policy.clone());
+        if let Some(transaction) = &transaction {
+            magick_runner = magick_runner.with_transaction(transaction);
+        }
+        let result = run_commands(&magick_runner, function, input, bindings);
+        let files = magick_runner.take_resolved_outputs();
+        if let Some(transaction) = transaction {
+            // Dropping the transaction discards what the failed run staged
+            let outputs = result?;
+            transaction
+                .commit(self.output_policy.overwrite_trash())
+                .map_err(|e| ShellError::TransactionFailed {
+                    message: format!("failed to move staged outputs into place: {e}"),
+                })?;
+            self.resolved_outputs.borrow_mut().extend(files);
+            return Ok(outputs);
         }
-        Ok(outputs)
+        self.resolved_outputs.borrow_mut().extend(files);
+        result
     }
 }
 
+/// Run each command of a function in turn, binding `${stepN.output}` as files are written
+fn run_commands(
+    magick_runner: &MagickRunner,
+    function: &Function,
+    input: Option<&str>,
+    mut bindings: Bindings,
+) -> Result<Vec<String>, ShellError> {
+    let mut outputs = Vec::new();
+    for (index, command) in function.commands.iter().enumerate() {
+        let template = Template::parse(command)?;
+        if input.is_none() && template.uses("input") {
+            return Err(ShellError::MissingInputVariable);
+        }
+        let args = template.render(&bindings)?;
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        let recorded = magick_runner.resolved_output_count();
+        outputs.push(magick_runner.execute_args(&arg_refs)?);
+        let written = magick_runner.latest_output_path();
+        if let Some(written) = written.filter(|_| magick_runner.resolved_output_count() > recorded)
+        {
+            bindings.set(
+                &format!("step{}.output", index + 1),
+                written.to_string_lossy(),
+            );
+        }
+    }
+    Ok(outputs)
+}
+
+fn validate_value(name: &str, param_type: ParamType, value: &str) -> Result<(), ShellError> {
+    param_type
+        .validate(value)
+        .map_err(|message| ShellError::InvalidParameterValue {
+            name: name.to_string(),
+            message,
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::feature::shell::{CommandRunner, ShellError};
+    use crate::feature::template::TemplateError;
 
     /// Mock implementation of CommandRunner for testing
     struct MockCommandRunner {
         output: String,
         should_fail: bool,
         call_count: std::cell::RefCell<usize>,
+        calls: std::cell::RefCell<Vec<Vec<String>>>,
     }
 
     impl MockCommandRunner {
@@ -74,6 +212,7 @@ mod tests {
                 output,
                 should_fail,
                 call_count: std::cell::RefCell::new(0),
+                calls: std::cell::RefCell::new(Vec::new()),
             }
         }
     }
@@ -82,10 +221,13 @@ mod tests {
         fn execute(
             &self,
             _command: &str,
-            _args: &[&str],
+            args: &[&str],
             _working_dir: Option<&std::path::Path>,
         ) -> Result<String, ShellError> {
             *self.call_count.borrow_mut() += 1;
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|arg| arg.to_string()).collect());
             if self.should_fail {
                 Err(ShellError::NonZeroExit {
                     exit_code: 1,
@@ -110,9 +252,10 @@ mod tests {
                 "input.png -negate output1.png".to_string(),
                 "output1.png -resize 50% output2.png".to_string(),
             ],
+            params: Default::default(),
         };
 
-        let result = function_runner.run(&function, None);
+        let result = function_runner.run(&function, None, &BTreeMap::new());
         assert!(result.is_ok());
         let outputs = result.unwrap();
         assert_eq!(outputs.len(), 2);
@@ -131,9 +274,10 @@ mod tests {
                 "input.png -negate output1.png".to_string(),
                 "output1.png -resize 50% output2.png".to_string(),
             ],
+            params: Default::default(),
         };
 
-        let result = function_runner.run(&function, None);
+        let result = function_runner.run(&function, None, &BTreeMap::new());
         assert!(result.is_err());
         assert_eq!(*failing_runner.call_count.borrow(), 1);
     }
@@ -145,9 +289,10 @@ mod tests {
         let function = Function {
             name: "test".to_string(),
             commands: vec![],
+            params: Default::default(),
         };
 
-        let result = function_runner.run(&function, None);
+        let result = function_runner.run(&function, None, &BTreeMap::new());
         assert!(result.is_ok());
         let outputs = result.unwrap();
         assert_eq!(outputs.len(), 0);
@@ -161,9 +306,10 @@ mod tests {
         let function = Function {
             name: "test".to_string(),
             commands: vec!["$input -negate output.png".to_string()],
+            params: Default::default(),
         };
 
-        let result = function_runner.run(&function, Some("photo.png"));
+        let result = function_runner.run(&function, Some("photo.png"), &BTreeMap::new());
         assert!(result.is_ok());
         let outputs = result.unwrap();
         assert_eq!(outputs.len(), 1);
@@ -177,9 +323,10 @@ mod tests {
         let function = Function {
             name: "test".to_string(),
             commands: vec!["$input -negate output.png".to_string()],
+            params: Default::default(),
         };
 
-        let result = function_runner.run(&function, None);
+        let result = function_runner.run(&function, None, &BTreeMap::new());
         assert!(result.is_err());
         if let Err(ShellError::MissingInputVariable) = result {
             // Expected error
@@ -189,4 +336,212 @@ mod tests {
         // Should not execute any commands
         assert_eq!(*mock_runner.call_count.borrow(), 0);
     }
+
+    #[test]
+    fn test_run_function_substitutes_placeholders() {
+        let mock_runner = MockCommandRunner::new("Success".to_string(), false);
+        let function_runner = FunctionRunner::new(&mock_runner, None);
+        let function = Function {
+            name: "test".to_string(),
+            commands: vec![
+                "$input -negate negated.png".to_string(),
+                "${step1.output} -resize 50% \"half size.png\"".to_string(),
+            ],
+            params: Default::default(),
+        };
+
+        function_runner
+            .run(&function, Some("My Photos/$1.png"), &BTreeMap::new())
+            .unwrap();
+
+        let calls = mock_runner.calls.borrow();
+        assert_eq!(calls[0], vec!["My Photos/$1.png", "-negate", "negated.png"]);
+        assert_eq!(
+            calls[1],
+            vec!["negated.png", "-resize", "50%", "half size.png"]
+        );
+    }
+
+    #[test]
+    fn test_run_function_env_allowlist() {
+        let mock_runner = MockCommandRunner::new("Success".to_string(), false);
+        let function = Function {
+            name: "test".to_string(),
+            commands: vec!["in.png -comment ${env.PATH} out.png".to_string()],
+            params: Default::default(),
+        };
+
+        let result = FunctionRunner::new(&mock_runner, None)
+            .with_env(vec!["HOME".to_string()])
+            .run(&function, None, &BTreeMap::new());
+        assert!(matches!(
+            result,
+            Err(ShellError::Template(TemplateError::EnvNotAllowed { name })) if name == "PATH"
+        ));
+        assert_eq!(*mock_runner.call_count.borrow(), 0);
+
+        FunctionRunner::new(&mock_runner, None)
+            .with_env(vec!["PATH".to_string()])
+            .run(&function, None, &BTreeMap::new())
+            .unwrap();
+        assert_eq!(
+            mock_runner.calls.borrow()[0][2],
+            std::env::var("PATH").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_run_function_validates_params() {
+        let mock_runner = MockCommandRunner::new("Success".to_string(), false);
+        let function_runner = FunctionRunner::new(&mock_runner, None);
+        let function = Function {
+            name: "resize".to_string(),
+            commands: vec!["$input -resize $size -background $color out.png".to_string()],
+            params: BTreeMap::from([
+                ("size".to_string(), ParamType::Geometry),
+                ("color".to_string(), ParamType::Color),
+            ]),
+        };
+        let params = |size: &str| {
+            BTreeMap::from([
+                ("size".to_string(), size.to_string()),
+                ("color".to_string(), "white".to_string()),
+            ])
+        };
+
+        function_runner
+            .run(&function, Some("in.png"), &params("800x600>"))
+            .unwrap();
+        assert_eq!(
+            mock_runner.calls.borrow()[0],
+            vec![
+                "in.png",
+                "-resize",
+                "800x600>",
+                "-background",
+                "white",
+                "out.png"
+            ]
+        );
+
+        let rejected = [
+            function_runner.run(&function, Some("in.png"), &params("-write")),
+            function_runner.run(&function, Some("-write"), &params("50%")),
+            function_runner.run(
+                &function,
+                Some("in.png"),
+                &BTreeMap::from([("extra".to_string(), "1".to_string())]),
+            ),
+        ];
+        for result in rejected {
+            assert!(matches!(
+                result,
+                Err(ShellError::InvalidParameterValue { .. })
+            ));
+        }
+        assert_eq!(*mock_runner.call_count.borrow(), 1);
+    }
+
+    /// Writes each command's first argument to its output, failing for `missing.png`
+    struct WritingCommandRunner {
+        calls: std::cell::RefCell<Vec<Vec<String>>>,
+    }
+
+    impl CommandRunner for WritingCommandRunner {
+        fn execute(
+            &self,
+            command: &str,
+            args: &[&str],
+            working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|arg| arg.to_string()).collect());
+            if args[0] == "missing.png" {
+                return Err(ShellError::ExecutionFailed {
+                    message: "unable to open image".to_string(),
+                    command: command.to_string(),
+                    args: args.join(" "),
+                });
+            }
+            let output = working_dir.unwrap().join(args[args.len() - 1]);
+            std::fs::write(output, args[0]).unwrap();
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_transactional_run_moves_outputs_into_place() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let runner = WritingCommandRunner {
+            calls: std::cell::RefCell::new(Vec::new()),
+        };
+        let function_runner = FunctionRunner::new(&runner, Some(dir.path()))
+            .with_output_policy(OutputPolicy {
+                create_dirs: true,
+                ..Default::default()
+            })
+            .with_transactional(true);
+        let function = Function {
+            name: "test".to_string(),
+            commands: vec![
+                "$input -negate negated.png".to_string(),
+                "${step1.output} -resize 50% out/half.png".to_string(),
+            ],
+            params: Default::default(),
+        };
+
+        function_runner
+            .run(&function, Some("photo.png"), &BTreeMap::new())
+            .unwrap();
+
+        let staged = runner.calls.borrow()[1][0].clone();
+        assert!(staged.contains(".magickmcp") && staged.ends_with("negated.png"));
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("negated.png")).unwrap(),
+            "photo.png"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("out/half.png")).unwrap(),
+            staged
+        );
+        assert_eq!(
+            function_runner.take_resolved_outputs(),
+            vec![
+                ResolvedOutput::written("negated.png"),
+                ResolvedOutput::written("out/half.png")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transactional_run_discards_outputs_on_failure() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let runner = WritingCommandRunner {
+            calls: std::cell::RefCell::new(Vec::new()),
+        };
+        let function_runner =
+            FunctionRunner::new(&runner, Some(dir.path())).with_transactional(true);
+        let function = Function {
+            name: "test".to_string(),
+            commands: vec![
+                "$input -negate negated.png".to_string(),
+                "missing.png -negate other.png".to_string(),
+            ],
+            params: Default::default(),
+        };
+
+        let result = function_runner.run(&function, Some("photo.png"), &BTreeMap::new());
+
+        assert!(result.is_err());
+        assert_eq!(runner.calls.borrow().len(), 2);
+        assert!(!dir.path().join("negated.png").exists());
+        assert_eq!(
+            std::fs::read_dir(dir.path().join(".magickmcp"))
+                .unwrap()
+                .count(),
+            0
+        );
+        assert!(function_runner.take_resolved_outputs().is_empty());
+    }
 }
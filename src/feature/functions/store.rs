@@ -1,9 +1,17 @@
+use crate::feature::config::{ConfigError, TrashConfig};
+use crate::feature::data_dir::{DataDirLock, data_dir, write_atomic};
+use crate::feature::functions::builtins::builtins;
 use crate::feature::functions::model::Function;
-use crate::feature::functions::path::functions_dir;
+use crate::feature::project::ProjectConfig;
+use crate::feature::trash::{Trash, TrashError, TrashKind};
+use serde::Serialize;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+/// Directory of project functions within a workspace, unless `.magickmcp.toml` sets `functions_dir`
+pub const PROJECT_FUNCTIONS_DIR: &str = ".magickmcp/functions";
+
 /// Error type for function store operations
 #[derive(Debug, Error)]
 pub enum FunctionStoreError {
@@ -15,26 +23,169 @@ pub enum FunctionStoreError {
     ParseError(#[from] serde_json::Error),
     #[error("Function '{0}' not found")]
     FunctionNotFound(String),
+    #[error("Function '{0}' already exists")]
+    FunctionExists(String),
+    #[error("Invalid edit: {0}")]
+    InvalidEdit(String),
+    #[error(transparent)]
+    ProjectConfig(#[from] ConfigError),
+    #[error(transparent)]
+    Trash(#[from] TrashError),
+}
+
+/// Where a loaded function was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FunctionSource {
+    /// The workspace's project functions directory
+    Project,
+    /// Saved to the global functions directory
+    Saved,
+    /// Shipped with magick-mcp or installed by `func update-builtins`
+    Builtin,
+}
+
+/// A saved function file which cannot be used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidFunction {
+    /// Path to the function file
+    pub path: PathBuf,
+    /// Why the function cannot be used
+    pub error: String,
+}
+
+/// A partial change to a stored function
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FunctionEdit {
+    /// Give the function a new name
+    Rename(String),
+    /// Add a command after the last one
+    Append(String),
+    /// Add a command before the command at `index`, or at the end when `index` is the length
+    Insert { index: usize, command: String },
+    /// Remove the command at `index`
+    Remove(usize),
+    /// Replace the whole command list
+    Replace(Vec<String>),
+}
+
+impl FunctionEdit {
+    /// Apply the edit to a function
+    fn apply(&self, function: &mut Function) -> Result<(), FunctionStoreError> {
+        let out_of_range = |index: usize| {
+            FunctionStoreError::InvalidEdit(format!(
+                "index {index} is out of range for {} commands",
+                function.commands.len()
+            ))
+        };
+        match self {
+            FunctionEdit::Rename(name) => {
+                if !is_valid_name(name) {
+                    return Err(FunctionStoreError::InvalidEdit(format!(
+                        "'{name}' is not a valid function name"
+                    )));
+                }
+                function.name = name.clone()
+            }
+            FunctionEdit::Append(command) => function.commands.push(command.clone()),
+            FunctionEdit::Insert { index, command } => {
+                if *index > function.commands.len() {
+                    return Err(out_of_range(*index));
+                }
+                function.commands.insert(*index, command.clone());
+            }
+            FunctionEdit::Remove(index) => {
+                if *index >= function.commands.len() {
+                    return Err(out_of_range(*index));
+                }
+                function.commands.remove(*index);
+            }
+            FunctionEdit::Replace(commands) => function.commands = commands.clone(),
+        }
+        Ok(())
+    }
 }
 
 /// Store for loading and saving magick functions
-pub struct FunctionStore;
+///
+/// With a workspace, functions in the project's functions directory are layered over the global
+/// store and take precedence when loading, listing, and updating. Saving and deleting only touch
+/// the global store.
+pub struct FunctionStore {
+    data_dir: Option<PathBuf>,
+    workspace: Option<PathBuf>,
+    trash: Trash,
+}
 
 impl FunctionStore {
     /// Create a new FunctionStore instance
     pub fn new() -> Self {
-        FunctionStore
+        Self::in_data_dir(data_dir())
+    }
+
+    /// Create a store keeping its functions and trash in the provided data directory
+    pub(crate) fn in_data_dir(data_dir: Option<PathBuf>) -> Self {
+        FunctionStore {
+            trash: Trash::in_data_dir(data_dir.clone(), &TrashConfig::default()),
+            data_dir,
+            workspace: None,
+        }
+    }
+
+    /// Keep deleted functions in the provided trash instead of one with the default retention
+    pub fn with_trash(mut self, trash: Trash) -> Self {
+        self.trash = trash;
+        self
+    }
+
+    /// Get the global functions directory
+    fn functions_dir(&self) -> Result<PathBuf, FunctionStoreError> {
+        self.data_dir
+            .as_ref()
+            .map(|dir| dir.join("functions"))
+            .ok_or(FunctionStoreError::FunctionsDirNotFound)
+    }
+
+    /// Layer the functions of the provided workspace over the global store
+    pub fn with_workspace(mut self, workspace: Option<&Path>) -> Self {
+        self.workspace = workspace.map(Path::to_path_buf);
+        self
+    }
+
+    /// Get where the workspace keeps its project functions, whether or not the directory exists
+    pub(crate) fn project_functions_path(&self) -> Result<Option<PathBuf>, FunctionStoreError> {
+        let Some(workspace) = &self.workspace else {
+            return Ok(None);
+        };
+        let relative = ProjectConfig::discover(workspace)?
+            .and_then(|project| project.functions_dir)
+            .unwrap_or_else(|| PROJECT_FUNCTIONS_DIR.to_string());
+        Ok(Some(workspace.join(relative)))
+    }
+
+    /// Get the project functions directory, if the workspace has one
+    fn project_dir(&self) -> Result<Option<PathBuf>, FunctionStoreError> {
+        Ok(self.project_functions_path()?.filter(|dir| dir.is_dir()))
     }
 
     /// Get the path to a function file
     fn function_path(&self, name: &str) -> Result<PathBuf, FunctionStoreError> {
-        let dir = functions_dir().ok_or(FunctionStoreError::FunctionsDirNotFound)?;
+        let dir = self.functions_dir()?;
         Ok(dir.join(format!("{name}.json")))
     }
 
+    /// Lock the data directory shared with other magick-mcp processes
+    fn lock(&self) -> Result<DataDirLock, FunctionStoreError> {
+        let dir = self
+            .data_dir
+            .as_ref()
+            .ok_or(FunctionStoreError::FunctionsDirNotFound)?;
+        Ok(DataDirLock::acquire(dir)?)
+    }
+
     /// Ensure the functions directory exists
     fn ensure_dir(&self) -> Result<PathBuf, FunctionStoreError> {
-        let dir = functions_dir().ok_or(FunctionStoreError::FunctionsDirNotFound)?;
+        let dir = self.functions_dir()?;
         fs::create_dir_all(&dir)?;
         Ok(dir)
     }
@@ -49,14 +200,16 @@ impl FunctionStore {
     ///
     /// Returns `Ok(())` on success, or a `FunctionStoreError` on failure
     pub fn save(&self, function: &Function) -> Result<(), FunctionStoreError> {
+        let _lock = self.lock()?;
         self.ensure_dir()?;
         let path = self.function_path(&function.name)?;
         let json = serde_json::to_string_pretty(function)?;
-        fs::write(path, json)?;
+        write_atomic(&path, json)?;
         Ok(())
     }
 
-    /// Load a function from disk
+    /// Load a function from disk, preferring the project's functions and falling back to the
+    /// built-in functions
     ///
     /// # Arguments
     ///
@@ -66,41 +219,131 @@ impl FunctionStore {
     ///
     /// Returns the `Function` on success, or a `FunctionStoreError` on failure
     pub fn load(&self, name: &str) -> Result<Function, FunctionStoreError> {
+        self.locate(name).map(|(function, _)| function)
+    }
+
+    /// Load a function and report which layer of the store it came from
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the function to load
+    ///
+    /// # Returns
+    ///
+    /// Returns the `Function` and its `FunctionSource`, or a `FunctionStoreError` on failure
+    pub fn locate(&self, name: &str) -> Result<(Function, FunctionSource), FunctionStoreError> {
+        if let Some(dir) = self.project_dir()? {
+            let path = dir.join(format!("{name}.json"));
+            if path.is_file() {
+                let function = serde_json::from_str(&fs::read_to_string(&path)?)?;
+                return Ok((function, FunctionSource::Project));
+            }
+        }
         let path = self.function_path(name)?;
         if !path.exists() {
-            return Err(FunctionStoreError::FunctionNotFound(name.to_string()));
+            return builtins()
+                .functions
+                .into_iter()
+                .find(|function| function.name == name)
+                .map(|function| (function, FunctionSource::Builtin))
+                .ok_or_else(|| FunctionStoreError::FunctionNotFound(name.to_string()));
         }
         let contents = fs::read_to_string(&path)?;
         let function: Function = serde_json::from_str(&contents)?;
-        Ok(function)
+        Ok((function, FunctionSource::Saved))
     }
 
     /// List all available function names
     ///
+    /// Project functions come first, then saved functions, then built-in functions, skipping any
+    /// name already listed.
+    ///
     /// # Returns
     ///
     /// Returns a vector of function names, or a `FunctionStoreError` on failure
     pub fn list(&self) -> Result<Vec<String>, FunctionStoreError> {
-        let dir = functions_dir().ok_or(FunctionStoreError::FunctionsDirNotFound)?;
-
-        if !dir.exists() {
-            return Ok(Vec::new());
-        }
+        let dir = self.functions_dir()?;
 
         let mut functions = Vec::new();
-        for entry in fs::read_dir(&dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("json") {
-                if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
-                    functions.push(name.to_string());
-                }
+        if let Some(project_dir) = self.project_dir()? {
+            functions.extend(function_names(&project_dir)?);
+        }
+        let saved = if dir.exists() {
+            function_names(&dir)?
+        } else {
+            Vec::new()
+        };
+        let builtin = builtins().functions.into_iter().map(|f| f.name);
+        for name in saved.into_iter().chain(builtin) {
+            if !functions.contains(&name) {
+                functions.push(name);
             }
         }
         Ok(functions)
     }
 
-    /// Delete a function from disk
+    /// Apply edits to a saved function and save the result
+    ///
+    /// Edits apply in order and are saved only if all of them succeed. A project function is
+    /// edited in the project's functions directory, while editing a built-in function saves an
+    /// edited copy which takes precedence over it.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the function to edit
+    /// * `edits` - The edits to apply
+    ///
+    /// # Returns
+    ///
+    /// Returns the updated `Function`, or a `FunctionStoreError` on failure
+    pub fn update(
+        &self,
+        name: &str,
+        edits: &[FunctionEdit],
+    ) -> Result<Function, FunctionStoreError> {
+        let (mut function, source) = self.locate(name)?;
+        for edit in edits {
+            edit.apply(&mut function)?;
+        }
+        if function.commands.is_empty() {
+            return Err(FunctionStoreError::InvalidEdit(
+                "a function needs at least one command".to_string(),
+            ));
+        }
+
+        let _lock = self.lock()?;
+        let dir = match source {
+            FunctionSource::Project => self
+                .project_dir()?
+                .ok_or_else(|| FunctionStoreError::FunctionNotFound(name.to_string()))?,
+            FunctionSource::Saved | FunctionSource::Builtin => self.ensure_dir()?,
+        };
+        let path = dir.join(format!("{}.json", function.name));
+        if function.name != name && path.exists() {
+            return Err(FunctionStoreError::FunctionExists(function.name));
+        }
+        write_atomic(&path, serde_json::to_string_pretty(&function)?)?;
+        if function.name != name && source != FunctionSource::Builtin {
+            fs::remove_file(dir.join(format!("{name}.json")))?;
+        }
+        Ok(function)
+    }
+
+    /// Check every saved function file, so hand edits which break one are noticed
+    ///
+    /// # Returns
+    ///
+    /// Returns the files which cannot be used with the reason, or a `FunctionStoreError` if
+    /// the functions directory cannot be read
+    pub fn invalid_saved(&self) -> Result<Vec<InvalidFunction>, FunctionStoreError> {
+        let dir = self.functions_dir()?;
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        invalid_functions(&dir)
+    }
+
+    /// Delete a function from disk, keeping a copy in the trash
     ///
     /// # Arguments
     ///
@@ -110,15 +353,66 @@ impl FunctionStore {
     ///
     /// Returns `Ok(())` on success, or a `FunctionStoreError` on failure
     pub fn delete(&self, name: &str) -> Result<(), FunctionStoreError> {
+        let _lock = self.lock()?;
         let path = self.function_path(name)?;
         if !path.exists() {
             return Err(FunctionStoreError::FunctionNotFound(name.to_string()));
         }
+        self.trash.keep(&path, TrashKind::Function)?;
         fs::remove_file(path)?;
         Ok(())
     }
 }
 
+/// Whether a name can be used as a function's file name
+///
+/// Names must not be empty, contain path separators, or start with `.`, so every function file
+/// stays in its functions directory.
+fn is_valid_name(name: &str) -> bool {
+    !name.trim().is_empty() && !name.contains(['/', '\\']) && !name.starts_with('.')
+}
+
+/// Names of the `.json` function files in a directory
+fn function_names(dir: &Path) -> Result<Vec<String>, FunctionStoreError> {
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file()
+            && path.extension().and_then(|s| s.to_str()) == Some("json")
+            && let Some(name) = path.file_stem().and_then(|s| s.to_str())
+        {
+            names.push(name.to_string());
+        }
+    }
+    Ok(names)
+}
+
+/// Parse and validate each `.json` function file in a directory, returning those which fail
+fn invalid_functions(dir: &Path) -> Result<Vec<InvalidFunction>, FunctionStoreError> {
+    let mut invalid = Vec::new();
+    for name in function_names(dir)? {
+        let path = dir.join(format!("{name}.json"));
+        let checked = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| serde_json::from_str::<Function>(&json).map_err(|e| e.to_string()))
+            .and_then(|function| {
+                function.validate()?;
+                if function.name != name {
+                    return Err(format!(
+                        "name '{}' does not match the file name",
+                        function.name
+                    ));
+                }
+                Ok(())
+            });
+        if let Err(error) = checked {
+            invalid.push(InvalidFunction { path, error });
+        }
+    }
+    invalid.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(invalid)
+}
+
 impl Default for FunctionStore {
     fn default() -> Self {
         Self::new()
@@ -131,26 +425,114 @@ mod tests {
 
     #[test]
     fn test_save_and_load_function() {
-        let store = FunctionStore::new();
+        let data_dir = tempfile::TempDir::new().unwrap();
+        let store = FunctionStore::in_data_dir(Some(data_dir.path().to_path_buf()));
         let function = Function {
             name: "test_save_load".to_string(),
             commands: vec!["test.png -negate output.png".to_string()],
+            params: Default::default(),
         };
 
-        // This test requires the functions directory to exist
-        // In a real scenario, we'd use a temp directory
-        if functions_dir().is_some() {
-            let _ = store.save(&function);
-            let loaded = store.load("test_save_load");
-            if loaded.is_ok() {
-                let func = loaded.unwrap();
-                assert_eq!(func.name, function.name);
-                assert_eq!(func.commands, function.commands);
-                let _ = store.delete("test_save_load");
+        store.save(&function).unwrap();
+        let loaded = store.load("test_save_load").unwrap();
+        assert_eq!(loaded.name, function.name);
+        assert_eq!(loaded.commands, function.commands);
+        assert!(
+            data_dir
+                .path()
+                .join("functions/test_save_load.json")
+                .is_file()
+        );
+    }
+
+    #[test]
+    fn test_function_edits() {
+        let mut function = Function {
+            name: "edit".to_string(),
+            commands: vec!["a".to_string(), "c".to_string()],
+            params: Default::default(),
+        };
+        let edits = [
+            FunctionEdit::Insert {
+                index: 1,
+                command: "b".to_string(),
+            },
+            FunctionEdit::Append("d".to_string()),
+            FunctionEdit::Remove(0),
+            FunctionEdit::Rename("edited".to_string()),
+        ];
+        for edit in &edits {
+            edit.apply(&mut function).unwrap();
+        }
+        assert_eq!(function.name, "edited");
+        assert_eq!(function.commands, ["b", "c", "d"]);
+
+        assert!(FunctionEdit::Remove(3).apply(&mut function).is_err());
+        assert!(
+            FunctionEdit::Insert {
+                index: 4,
+                command: "e".to_string()
             }
+            .apply(&mut function)
+            .is_err()
+        );
+        FunctionEdit::Replace(vec!["x".to_string()])
+            .apply(&mut function)
+            .unwrap();
+        assert_eq!(function.commands, ["x"]);
+
+        for name in ["", "../escape", "nested/name", ".hidden"] {
+            assert!(
+                FunctionEdit::Rename(name.to_string())
+                    .apply(&mut function)
+                    .is_err()
+            );
         }
     }
 
+    #[test]
+    fn test_update_saved_and_project_functions() {
+        let data_dir = tempfile::TempDir::new().unwrap();
+        let workspace = tempfile::TempDir::new().unwrap();
+        let project_dir = workspace.path().join(PROJECT_FUNCTIONS_DIR);
+        fs::create_dir_all(&project_dir).unwrap();
+        fs::write(
+            project_dir.join("banner.json"),
+            r#"{"name":"banner","commands":["$input -resize 1200x banner.png"]}"#,
+        )
+        .unwrap();
+        let store = FunctionStore::in_data_dir(Some(data_dir.path().to_path_buf()))
+            .with_workspace(Some(workspace.path()));
+        store
+            .save(&Function {
+                name: "saved".to_string(),
+                commands: vec!["a.png b.png".to_string()],
+                params: Default::default(),
+            })
+            .unwrap();
+
+        let renamed = store
+            .update("saved", &[FunctionEdit::Rename("renamed".to_string())])
+            .unwrap();
+        assert_eq!(renamed.name, "renamed");
+        assert!(data_dir.path().join("functions/renamed.json").is_file());
+        assert!(!data_dir.path().join("functions/saved.json").exists());
+
+        store
+            .update(
+                "banner",
+                &[
+                    FunctionEdit::Append("banner.png -strip banner.webp".to_string()),
+                    FunctionEdit::Rename("hero".to_string()),
+                ],
+            )
+            .unwrap();
+        assert!(!project_dir.join("banner.json").exists());
+        assert_eq!(store.locate("hero").unwrap().1, FunctionSource::Project);
+        assert_eq!(store.load("hero").unwrap().commands.len(), 2);
+        assert!(!data_dir.path().join("functions/hero.json").exists());
+    }
+
     #[test]
     fn test_load_nonexistent_function() {
         let store = FunctionStore::new();
@@ -172,18 +554,115 @@ mod tests {
     }
 
     #[test]
-    fn test_delete_function() {
+    fn test_builtin_functions_are_listed_and_loaded() {
         let store = FunctionStore::new();
+        assert!(store.list().unwrap().contains(&"grayscale".to_string()));
+        assert_eq!(store.load("grayscale").unwrap().name, "grayscale");
+    }
+
+    #[test]
+    fn test_project_functions_take_precedence() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let dir = workspace.path().join(PROJECT_FUNCTIONS_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("grayscale.json"),
+            r#"{"name":"grayscale","commands":["$input -colorspace Gray -level 10% gray.png"]}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("project_banner.json"),
+            r#"{"name":"project_banner","commands":["$input -resize 1200x banner.png"]}"#,
+        )
+        .unwrap();
+        let store = FunctionStore::new().with_workspace(Some(workspace.path()));
+
+        let names = store.list().unwrap();
+        assert_eq!(
+            names[..2],
+            ["grayscale", "project_banner"].map(String::from)
+        );
+        assert_eq!(names.iter().filter(|n| *n == "grayscale").count(), 1);
+        assert!(store.load("grayscale").unwrap().commands[0].contains("-level 10%"));
+        assert!(FunctionStore::new().load("project_banner").is_err());
+        assert_eq!(
+            store.locate("grayscale").unwrap().1,
+            FunctionSource::Project
+        );
+        assert_eq!(
+            FunctionStore::new().locate("grayscale").unwrap().1,
+            FunctionSource::Builtin
+        );
+    }
+
+    #[test]
+    fn test_project_functions_dir_from_project_config() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        fs::write(
+            workspace.path().join(".magickmcp.toml"),
+            "functions_dir = \"pipelines\"\n",
+        )
+        .unwrap();
+        fs::create_dir(workspace.path().join("pipelines")).unwrap();
+        fs::write(
+            workspace.path().join("pipelines/hero.json"),
+            r#"{"name":"hero","commands":["$input -resize 1600x hero.png"]}"#,
+        )
+        .unwrap();
+        let store = FunctionStore::new().with_workspace(Some(workspace.path()));
+
+        assert_eq!(store.load("hero").unwrap().name, "hero");
+    }
+
+    #[test]
+    fn test_delete_function() {
+        let data_dir = tempfile::TempDir::new().unwrap();
+        let store = FunctionStore::in_data_dir(Some(data_dir.path().to_path_buf()));
         let function = Function {
             name: "test_delete".to_string(),
             commands: vec!["test.png -negate output.png".to_string()],
+            params: Default::default(),
         };
 
-        if functions_dir().is_some() {
-            let _ = store.save(&function);
-            assert!(store.load("test_delete").is_ok());
-            assert!(store.delete("test_delete").is_ok());
-            assert!(store.load("test_delete").is_err());
-        }
+        store.save(&function).unwrap();
+        assert!(store.load("test_delete").is_ok());
+        assert!(store.delete("test_delete").is_ok());
+        assert!(store.load("test_delete").is_err());
+
+        let trashed =
+            Trash::in_data_dir(Some(data_dir.path().to_path_buf()), &TrashConfig::default())
+                .list()
+                .unwrap();
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].kind, TrashKind::Function);
+    }
+
+    #[test]
+    fn test_invalid_functions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let write = |name: &str, json: &str| fs::write(dir.path().join(name), json).unwrap();
+        write(
+            "good.json",
+            r#"{"name": "good", "commands": ["$input -negate $output"]}"#,
+        );
+        write("broken.json", r#"{"name": "broken", "commands": ["#);
+        write(
+            "renamed.json",
+            r#"{"name": "other", "commands": ["a.png b.png"]}"#,
+        );
+        write("empty.json", r#"{"name": "empty", "commands": []}"#);
+        write("notes.txt", "not a function");
+
+        let invalid = invalid_functions(dir.path()).unwrap();
+
+        let names: Vec<_> = invalid
+            .iter()
+            .map(|invalid| invalid.path.file_name().unwrap().to_string_lossy())
+            .collect();
+        assert_eq!(names, ["broken.json", "empty.json", "renamed.json"]);
+        assert_eq!(
+            invalid[2].error,
+            "name 'other' does not match the file name"
+        );
     }
 }
@@ -0,0 +1,150 @@
+use crate::feature::functions::builtins::updated_builtins_path;
+use crate::feature::functions::path::functions_dir;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Name, modification time, and size of a watched file
+type FileStamp = (PathBuf, Option<SystemTime>, u64);
+
+/// Detects changes to the saved functions, the downloaded built-ins, and the project functions of
+/// the active workspace by polling
+///
+/// Each poll compares the name, modification time, and size of every file against the previous
+/// poll, which catches saves, deletes, and imports from any process without a platform specific
+/// file notification API.
+pub struct FunctionsWatcher {
+    paths: Vec<PathBuf>,
+    project_dir: Option<PathBuf>,
+    stamps: Vec<FileStamp>,
+}
+
+impl FunctionsWatcher {
+    /// Create a watcher over the functions directory and the downloaded built-ins
+    pub fn new() -> Self {
+        Self::watching(
+            functions_dir()
+                .into_iter()
+                .chain(updated_builtins_path())
+                .collect(),
+        )
+    }
+
+    fn watching(paths: Vec<PathBuf>) -> Self {
+        let stamps = stamps(&paths);
+        FunctionsWatcher {
+            paths,
+            project_dir: None,
+            stamps,
+        }
+    }
+
+    /// Also watch the project functions directory of the active workspace, replacing the one
+    /// watched before
+    ///
+    /// The directory need not exist yet. Switching directories counts as a change at the next
+    /// poll when the two hold different files, since the functions available change with it.
+    pub fn watch_project_dir(&mut self, dir: Option<PathBuf>) {
+        self.project_dir = dir;
+    }
+
+    /// Whether anything changed since the watcher was created or last polled
+    pub fn poll(&mut self) -> bool {
+        let paths: Vec<PathBuf> = self
+            .paths
+            .iter()
+            .chain(&self.project_dir)
+            .cloned()
+            .collect();
+        let stamps = stamps(&paths);
+        if stamps == self.stamps {
+            return false;
+        }
+        self.stamps = stamps;
+        true
+    }
+}
+
+impl Default for FunctionsWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Stamp each watched file, and each file directly inside a watched directory
+fn stamps(paths: &[PathBuf]) -> Vec<FileStamp> {
+    let mut stamps = Vec::new();
+    for path in paths {
+        match fs::read_dir(path) {
+            Ok(entries) => {
+                let mut files: Vec<PathBuf> = entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .collect();
+                files.sort();
+                stamps.extend(files.iter().filter_map(|file| stamp(file)));
+            }
+            Err(_) => stamps.extend(stamp(path)),
+        }
+    }
+    stamps
+}
+
+fn stamp(path: &Path) -> Option<FileStamp> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((path.to_path_buf(), metadata.modified().ok(), metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_poll_detects_save_and_delete() {
+        let dir = TempDir::new().unwrap();
+        let functions = dir.path().join("functions");
+        let mut watcher =
+            FunctionsWatcher::watching(vec![functions.clone(), dir.path().join("builtins.json")]);
+        assert!(!watcher.poll());
+
+        fs::create_dir(&functions).unwrap();
+        fs::write(functions.join("thumb.json"), "{}").unwrap();
+        assert!(watcher.poll());
+        assert!(!watcher.poll());
+
+        fs::write(dir.path().join("builtins.json"), "{}").unwrap();
+        assert!(watcher.poll());
+
+        fs::remove_file(functions.join("thumb.json")).unwrap();
+        assert!(watcher.poll());
+        assert!(!watcher.poll());
+    }
+
+    #[test]
+    fn test_poll_detects_project_function_edits() {
+        let dir = TempDir::new().unwrap();
+        let first = dir.path().join("first/.magickmcp/functions");
+        let second = dir.path().join("second/.magickmcp/functions");
+        fs::create_dir_all(&first).unwrap();
+        fs::create_dir_all(&second).unwrap();
+        fs::write(first.join("banner.json"), "{}").unwrap();
+        let mut watcher = FunctionsWatcher::watching(vec![dir.path().join("functions")]);
+
+        watcher.watch_project_dir(Some(first.clone()));
+        assert!(watcher.poll());
+        assert!(!watcher.poll());
+
+        fs::write(first.join("banner.json"), r#"{"name":"banner"}"#).unwrap();
+        assert!(watcher.poll());
+        assert!(!watcher.poll());
+
+        watcher.watch_project_dir(Some(second.clone()));
+        assert!(watcher.poll());
+        assert!(!watcher.poll());
+        fs::write(second.join("cover.json"), "{}").unwrap();
+        assert!(watcher.poll());
+
+        watcher.watch_project_dir(None);
+        assert!(watcher.poll());
+    }
+}
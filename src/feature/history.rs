@@ -0,0 +1,260 @@
+use crate::feature::data_dir::{DataDirLock, data_dir};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Error type for reading and writing the execution history
+#[derive(Debug, Error)]
+pub enum HistoryError {
+    #[error("Data directory not found")]
+    DataDirNotFound,
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Failed to serialize history: {0}")]
+    SerializeError(#[from] serde_json::Error),
+}
+
+/// SHA-256 digest of a file a tool call wrote
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileDigest {
+    /// Path as reported by the tool, relative to the workspace when one was given
+    pub path: String,
+    /// Hex SHA-256 of the file contents, `None` when the file could not be read
+    pub sha256: Option<String>,
+}
+
+/// A single recorded tool call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub trace_id: String,
+    /// Seconds since the Unix epoch when the call finished
+    pub timestamp: u64,
+    pub tool: String,
+    /// Arguments the tool was called with
+    pub arguments: Value,
+    /// `ok`, `error`, or `invalid`
+    pub status: String,
+    pub elapsed_ms: u64,
+    /// Structured result or error data returned to the client
+    pub result: Option<Value>,
+    /// Digests of the files the call reported writing
+    pub files: Vec<FileDigest>,
+}
+
+impl HistoryEntry {
+    /// Record a finished call, hashing the files its result mentions
+    ///
+    /// # Arguments
+    ///
+    /// * `trace_id` - The trace ID assigned to the call
+    /// * `tool` - The tool name
+    /// * `arguments` - The arguments passed to the tool
+    /// * `status` - `ok`, `error`, or `invalid`
+    /// * `elapsed_ms` - How long the call took
+    /// * `result` - Structured result or error data, if any
+    pub fn new(
+        trace_id: &str,
+        tool: &str,
+        arguments: Value,
+        status: &str,
+        elapsed_ms: u64,
+        result: Option<Value>,
+    ) -> Self {
+        let workspace = arguments
+            .get("workspace")
+            .and_then(Value::as_str)
+            .map(PathBuf::from);
+        let files = result
+            .as_ref()
+            .map(|result| file_digests(result, workspace.as_deref()))
+            .unwrap_or_default();
+        HistoryEntry {
+            trace_id: trace_id.to_string(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            tool: tool.to_string(),
+            arguments,
+            status: status.to_string(),
+            elapsed_ms,
+            result,
+            files,
+        }
+    }
+}
+
+/// Append-only log of tool calls, stored as JSON lines in the data directory
+pub struct HistoryStore {
+    path: Option<PathBuf>,
+}
+
+impl HistoryStore {
+    /// Create a store using `history.jsonl` in the data directory
+    pub fn new() -> Self {
+        Self::in_data_dir(data_dir())
+    }
+
+    /// Create a store using `history.jsonl` in the provided data directory
+    pub(crate) fn in_data_dir(data_dir: Option<PathBuf>) -> Self {
+        Self::at(data_dir.map(|dir| dir.join("history.jsonl")))
+    }
+
+    fn at(path: Option<PathBuf>) -> Self {
+        HistoryStore { path }
+    }
+
+    fn path(&self) -> Result<&Path, HistoryError> {
+        self.path.as_deref().ok_or(HistoryError::DataDirNotFound)
+    }
+
+    /// Append an entry to the history
+    pub fn append(&self, entry: &HistoryEntry) -> Result<(), HistoryError> {
+        let path = self.path()?;
+        let dir = path.parent().ok_or(HistoryError::DataDirNotFound)?;
+        let _lock = DataDirLock::acquire(dir)?;
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// Read every entry, oldest first, skipping lines which cannot be parsed
+    pub fn entries(&self) -> Result<Vec<HistoryEntry>, HistoryError> {
+        let path = self.path()?;
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        Ok(fs::read_to_string(path)?
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Write every entry as JSON lines
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of entries written, or a `HistoryError` on failure
+    pub fn export_jsonl(&self, writer: &mut dyn Write) -> Result<usize, HistoryError> {
+        let entries = self.entries()?;
+        for entry in &entries {
+            serde_json::to_writer(&mut *writer, entry)?;
+            writer.write_all(b"\n")?;
+        }
+        Ok(entries.len())
+    }
+}
+
+impl Default for HistoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Hash every file named by an `output` or `path` field anywhere in a tool result
+fn file_digests(result: &Value, workspace: Option<&Path>) -> Vec<FileDigest> {
+    let mut paths = Vec::new();
+    collect_paths(result, &mut paths);
+    paths.dedup();
+    paths
+        .into_iter()
+        .map(|path| {
+            let resolved = match workspace {
+                Some(workspace) => workspace.join(&path),
+                None => PathBuf::from(&path),
+            };
+            let sha256 = fs::read(&resolved).ok().map(|contents| {
+                Sha256::digest(contents)
+                    .iter()
+                    .map(|byte| format!("{byte:02x}"))
+                    .collect()
+            });
+            FileDigest { path, sha256 }
+        })
+        .collect()
+}
+
+fn collect_paths(value: &Value, paths: &mut Vec<String>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                match value {
+                    Value::String(path) if key == "output" || key == "path" => {
+                        paths.push(path.clone())
+                    }
+                    other => collect_paths(other, paths),
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_paths(item, paths)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_entry_hashes_reported_files() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("out.png"), b"abc").unwrap();
+        let arguments = json!({ "workspace": dir.path() });
+        let result = json!({
+            "files": [{ "path": "out.png" }, { "path": "missing.png" }],
+            "success": true
+        });
+
+        let entry = HistoryEntry::new("abc123", "resize", arguments, "ok", 12, Some(result));
+
+        assert_eq!(
+            entry.files,
+            vec![
+                FileDigest {
+                    path: "out.png".to_string(),
+                    sha256: Some(
+                        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+                            .to_string()
+                    ),
+                },
+                FileDigest {
+                    path: "missing.png".to_string(),
+                    sha256: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_append_and_export() {
+        let dir = TempDir::new().unwrap();
+        let store = HistoryStore::at(Some(dir.path().join("history.jsonl")));
+        let first = HistoryEntry::new("a", "magick", json!({}), "ok", 1, None);
+        let second = HistoryEntry::new("b", "crop", json!({}), "error", 2, None);
+        store.append(&first).unwrap();
+        store.append(&second).unwrap();
+
+        let mut exported = Vec::new();
+        let count = store.export_jsonl(&mut exported).unwrap();
+
+        assert_eq!(count, 2);
+        let lines: Vec<HistoryEntry> = String::from_utf8(exported)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines, vec![first, second]);
+    }
+}
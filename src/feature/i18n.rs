@@ -0,0 +1,306 @@
+/// Languages user-facing messages are available in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+    German,
+    French,
+    Japanese,
+}
+
+/// Locale environment variables in the order POSIX gives them precedence
+const LOCALE_VARS: &[&str] = &["LC_ALL", "LC_MESSAGES", "LANG"];
+
+impl Language {
+    /// Parse a language code or locale such as `de`, `pt_BR`, or `fr_FR.UTF-8`
+    ///
+    /// Returns `None` for unsupported languages.
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        let code = locale
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match code.as_str() {
+            "en" | "c" | "posix" => Some(Language::English),
+            "es" => Some(Language::Spanish),
+            "de" => Some(Language::German),
+            "fr" => Some(Language::French),
+            "ja" => Some(Language::Japanese),
+            _ => None,
+        }
+    }
+
+    /// Pick the language from the configured one, falling back to the locale environment
+    ///
+    /// # Arguments
+    ///
+    /// * `configured` - The `language` set in the config file, if any
+    pub fn detect(configured: Option<&str>) -> Self {
+        let locales: Vec<String> = LOCALE_VARS
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .collect();
+        Self::select(configured, &locales)
+    }
+
+    /// Pick the first non-empty setting, using English when it is not a supported language
+    fn select(configured: Option<&str>, locales: &[String]) -> Self {
+        configured
+            .into_iter()
+            .chain(locales.iter().map(String::as_str))
+            .find(|value| !value.is_empty())
+            .and_then(Self::from_locale)
+            .unwrap_or_default()
+    }
+}
+
+/// User-facing messages with translations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+    NotInstalled,
+    InstallWithHomebrew,
+    InstallWithPackageManager,
+    Or,
+    DownloadFromWebsite,
+    InstallWithWinget,
+    InstallWithSystemPackageManager,
+    MoreDetails,
+    AddToEnvironment,
+    UseWrapperCommand,
+    GhostscriptNotInstalled,
+    InstallGhostscriptWith,
+    InstallSucceeded,
+    NoFunctionsFound,
+    FunctionSaved,
+    RenameDryRun,
+}
+
+impl Message {
+    /// The message in the given language
+    pub fn text(self, language: Language) -> &'static str {
+        match language {
+            Language::English => self.english(),
+            Language::Spanish => self.spanish(),
+            Language::German => self.german(),
+            Language::French => self.french(),
+            Language::Japanese => self.japanese(),
+        }
+    }
+
+    fn english(self) -> &'static str {
+        match self {
+            Message::NotInstalled => "ImageMagick is not installed.",
+            Message::InstallWithHomebrew => "Install ImageMagick using Homebrew:",
+            Message::InstallWithPackageManager => "Install ImageMagick using your package manager:",
+            Message::Or => "or",
+            Message::DownloadFromWebsite => {
+                "Download and install ImageMagick from the official website."
+            }
+            Message::InstallWithWinget => "Use winget:",
+            Message::InstallWithSystemPackageManager => {
+                "Install ImageMagick using your system's package manager."
+            }
+            Message::MoreDetails => "For more details, visit:",
+            Message::AddToEnvironment => {
+                "Add ImageMagick to the packages of this project's environment"
+            }
+            Message::UseWrapperCommand => {
+                "Or run ImageMagick through the environment by setting in config.toml:"
+            }
+            Message::GhostscriptNotInstalled => {
+                "Ghostscript is not installed. ImageMagick needs it to read PDF, PostScript, and EPS files."
+            }
+            Message::InstallGhostscriptWith => "Install Ghostscript with:",
+            Message::InstallSucceeded => "Successfully installed magick-mcp to MCP configuration",
+            Message::NoFunctionsFound => "No functions found",
+            Message::FunctionSaved => "Function saved successfully",
+            Message::RenameDryRun => "Dry run only, pass --apply to rename files",
+        }
+    }
+
+    fn spanish(self) -> &'static str {
+        match self {
+            Message::NotInstalled => "ImageMagick no está instalado.",
+            Message::InstallWithHomebrew => "Instale ImageMagick con Homebrew:",
+            Message::InstallWithPackageManager => "Instale ImageMagick con su gestor de paquetes:",
+            Message::Or => "o",
+            Message::DownloadFromWebsite => {
+                "Descargue e instale ImageMagick desde el sitio web oficial."
+            }
+            Message::InstallWithWinget => "Con winget:",
+            Message::InstallWithSystemPackageManager => {
+                "Instale ImageMagick con el gestor de paquetes de su sistema."
+            }
+            Message::MoreDetails => "Para más detalles, visite:",
+            Message::AddToEnvironment => {
+                "Añada ImageMagick a los paquetes del entorno de este proyecto"
+            }
+            Message::UseWrapperCommand => {
+                "O ejecute ImageMagick a través del entorno configurando en config.toml:"
+            }
+            Message::GhostscriptNotInstalled => {
+                "Ghostscript no está instalado. ImageMagick lo necesita para leer archivos PDF, PostScript y EPS."
+            }
+            Message::InstallGhostscriptWith => "Instale Ghostscript con:",
+            Message::InstallSucceeded => {
+                "magick-mcp se instaló correctamente en la configuración de MCP"
+            }
+            Message::NoFunctionsFound => "No se encontraron funciones",
+            Message::FunctionSaved => "Función guardada correctamente",
+            Message::RenameDryRun => "Solo simulación, pase --apply para renombrar los archivos",
+        }
+    }
+
+    fn german(self) -> &'static str {
+        match self {
+            Message::NotInstalled => "ImageMagick ist nicht installiert.",
+            Message::InstallWithHomebrew => "Installieren Sie ImageMagick mit Homebrew:",
+            Message::InstallWithPackageManager => {
+                "Installieren Sie ImageMagick mit Ihrem Paketmanager:"
+            }
+            Message::Or => "oder",
+            Message::DownloadFromWebsite => {
+                "Laden Sie ImageMagick von der offiziellen Website herunter und installieren Sie es."
+            }
+            Message::InstallWithWinget => "Mit winget:",
+            Message::InstallWithSystemPackageManager => {
+                "Installieren Sie ImageMagick mit dem Paketmanager Ihres Systems."
+            }
+            Message::MoreDetails => "Weitere Details finden Sie unter:",
+            Message::AddToEnvironment => {
+                "Fügen Sie ImageMagick zu den Paketen der Umgebung dieses Projekts hinzu"
+            }
+            Message::UseWrapperCommand => {
+                "Oder führen Sie ImageMagick über die Umgebung aus, indem Sie in config.toml festlegen:"
+            }
+            Message::GhostscriptNotInstalled => {
+                "Ghostscript ist nicht installiert. ImageMagick benötigt es zum Lesen von PDF-, PostScript- und EPS-Dateien."
+            }
+            Message::InstallGhostscriptWith => "Installieren Sie Ghostscript mit:",
+            Message::InstallSucceeded => {
+                "magick-mcp wurde erfolgreich in der MCP-Konfiguration installiert"
+            }
+            Message::NoFunctionsFound => "Keine Funktionen gefunden",
+            Message::FunctionSaved => "Funktion erfolgreich gespeichert",
+            Message::RenameDryRun => {
+                "Nur Probelauf, übergeben Sie --apply, um die Dateien umzubenennen"
+            }
+        }
+    }
+
+    fn french(self) -> &'static str {
+        match self {
+            Message::NotInstalled => "ImageMagick n'est pas installé.",
+            Message::InstallWithHomebrew => "Installez ImageMagick avec Homebrew :",
+            Message::InstallWithPackageManager => {
+                "Installez ImageMagick avec votre gestionnaire de paquets :"
+            }
+            Message::Or => "ou",
+            Message::DownloadFromWebsite => {
+                "Téléchargez et installez ImageMagick depuis le site officiel."
+            }
+            Message::InstallWithWinget => "Avec winget :",
+            Message::InstallWithSystemPackageManager => {
+                "Installez ImageMagick avec le gestionnaire de paquets de votre système."
+            }
+            Message::MoreDetails => "Pour plus de détails, consultez :",
+            Message::AddToEnvironment => {
+                "Ajoutez ImageMagick aux paquets de l'environnement de ce projet"
+            }
+            Message::UseWrapperCommand => {
+                "Ou exécutez ImageMagick via l'environnement en définissant dans config.toml :"
+            }
+            Message::GhostscriptNotInstalled => {
+                "Ghostscript n'est pas installé. ImageMagick en a besoin pour lire les fichiers PDF, PostScript et EPS."
+            }
+            Message::InstallGhostscriptWith => "Installez Ghostscript avec :",
+            Message::InstallSucceeded => "magick-mcp a bien été installé dans la configuration MCP",
+            Message::NoFunctionsFound => "Aucune fonction trouvée",
+            Message::FunctionSaved => "Fonction enregistrée",
+            Message::RenameDryRun => {
+                "Simulation uniquement, passez --apply pour renommer les fichiers"
+            }
+        }
+    }
+
+    fn japanese(self) -> &'static str {
+        match self {
+            Message::NotInstalled => "ImageMagick がインストールされていません。",
+            Message::InstallWithHomebrew => "Homebrew で ImageMagick をインストールしてください:",
+            Message::InstallWithPackageManager => {
+                "パッケージマネージャーで ImageMagick をインストールしてください:"
+            }
+            Message::Or => "または",
+            Message::DownloadFromWebsite => {
+                "公式サイトから ImageMagick をダウンロードしてインストールしてください。"
+            }
+            Message::InstallWithWinget => "winget を使う場合:",
+            Message::InstallWithSystemPackageManager => {
+                "システムのパッケージマネージャーで ImageMagick をインストールしてください。"
+            }
+            Message::MoreDetails => "詳細は次を参照してください:",
+            Message::AddToEnvironment => {
+                "このプロジェクトの環境のパッケージに ImageMagick を追加してください"
+            }
+            Message::UseWrapperCommand => {
+                "または config.toml に次を設定して環境経由で ImageMagick を実行してください:"
+            }
+            Message::GhostscriptNotInstalled => {
+                "Ghostscript がインストールされていません。ImageMagick が PDF、PostScript、EPS ファイルを読み込むには Ghostscript が必要です。"
+            }
+            Message::InstallGhostscriptWith => {
+                "次のコマンドで Ghostscript をインストールしてください:"
+            }
+            Message::InstallSucceeded => "magick-mcp を MCP 設定にインストールしました",
+            Message::NoFunctionsFound => "関数が見つかりません",
+            Message::FunctionSaved => "関数を保存しました",
+            Message::RenameDryRun => {
+                "ドライランのみです。ファイル名を変更するには --apply を指定してください"
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_locale() {
+        assert_eq!(Language::from_locale("de_DE.UTF-8"), Some(Language::German));
+        assert_eq!(Language::from_locale("fr"), Some(Language::French));
+        assert_eq!(Language::from_locale("ja-JP"), Some(Language::Japanese));
+        assert_eq!(Language::from_locale("C"), Some(Language::English));
+        assert_eq!(Language::from_locale("pt_BR.UTF-8"), None);
+    }
+
+    #[test]
+    fn test_select_prefers_config_then_locale() {
+        let locales = vec!["es_ES.UTF-8".to_string()];
+        assert_eq!(Language::select(Some("de"), &locales), Language::German);
+        assert_eq!(Language::select(None, &locales), Language::Spanish);
+        assert_eq!(
+            Language::select(Some(""), &["".to_string(), "ja_JP".to_string()]),
+            Language::Japanese
+        );
+        assert_eq!(Language::select(None, &[]), Language::English);
+        assert_eq!(
+            Language::select(None, &["pt_BR".to_string()]),
+            Language::English
+        );
+    }
+
+    #[test]
+    fn test_text() {
+        assert_eq!(
+            Message::NoFunctionsFound.text(Language::English),
+            "No functions found"
+        );
+        assert_eq!(
+            Message::NoFunctionsFound.text(Language::German),
+            "Keine Funktionen gefunden"
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use crate::feature::data_dir::{DataDirLock, write_atomic};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Directory within a workspace holding magick-mcp's incremental build state
+const STATE_DIR: &str = ".magickmcp";
+
+/// File recording the hash of the command which last wrote each output
+const STATE_FILE: &str = "outputs.json";
+
+/// Whether an output is already current for a command
+///
+/// An output is current when it exists, no input file named in the command is newer than it, and
+/// it was last written by the same command.
+///
+/// # Arguments
+///
+/// * `args` - The command arguments, whose last argument is the output
+/// * `output` - The output path, relative to the workspace
+/// * `workspace` - The workspace root
+pub(crate) fn is_current(args: &[&str], output: &Path, workspace: &Path) -> bool {
+    let Some(written) = modified(&workspace.join(output)) else {
+        return false;
+    };
+    let inputs_older = args[..args.len().saturating_sub(1)]
+        .iter()
+        .filter(|arg| !arg.starts_with('-'))
+        .filter_map(|arg| modified(&workspace.join(arg.split('[').next().unwrap_or(arg))))
+        .all(|input| input <= written);
+    inputs_older
+        && read_state(workspace).get(&output.to_string_lossy().to_string())
+            == Some(&command_hash(args))
+}
+
+/// Record the command which wrote an output, so later runs can tell whether it is current
+pub(crate) fn record(args: &[&str], output: &Path, workspace: &Path) -> io::Result<()> {
+    let dir = workspace.join(STATE_DIR);
+    let _lock = DataDirLock::acquire(&dir)?;
+    let mut state = read_state(workspace);
+    state.insert(output.to_string_lossy().to_string(), command_hash(args));
+    let json = serde_json::to_string_pretty(&state).map_err(io::Error::other)?;
+    write_atomic(&dir.join(STATE_FILE), json)
+}
+
+fn read_state(workspace: &Path) -> BTreeMap<String, String> {
+    fs::read_to_string(workspace.join(STATE_DIR).join(STATE_FILE))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn command_hash(args: &[&str]) -> String {
+    Sha256::digest(args.join("\0"))
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_is_current() {
+        let dir = TempDir::new().unwrap();
+        let args = ["in.png", "-negate", "out.png"];
+        let output = Path::new("out.png");
+        fs::write(dir.path().join("in.png"), b"in").unwrap();
+        assert!(!is_current(&args, output, dir.path()));
+
+        fs::write(dir.path().join("out.png"), b"out").unwrap();
+        assert!(!is_current(&args, output, dir.path()));
+
+        record(&args, output, dir.path()).unwrap();
+        assert!(is_current(&args, output, dir.path()));
+        assert!(!is_current(
+            &["in.png", "-flip", "out.png"],
+            output,
+            dir.path()
+        ));
+
+        let later = SystemTime::now() + Duration::from_secs(60);
+        fs::File::options()
+            .write(true)
+            .open(dir.path().join("in.png"))
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(!is_current(&args, output, dir.path()));
+    }
+}
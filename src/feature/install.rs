@@ -1,3 +1,6 @@
+use crate::feature::data_dir::{data_dir, write_atomic};
+use crate::feature::wsl::{self, Host};
+use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -30,10 +33,52 @@ impl ConfigPaths {
     }
 }
 
+/// Name of the file in the data directory recording the client configs magick-mcp was added to
+const INSTALL_STATE_FILE: &str = "installs.json";
+
+/// A client config magick-mcp was added to, with the locations it was installed with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct InstalledConfig {
+    path: PathBuf,
+    /// The magick-mcp executable the config launches
+    exe: PathBuf,
+    /// Where `magick` was found on the PATH at the time
+    magick: Option<PathBuf>,
+}
+
+/// Client configs magick-mcp was added to, stored in the data directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct InstallState {
+    configs: Vec<InstalledConfig>,
+}
+
+/// What refreshing a recorded client config did
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RefreshStatus {
+    /// The entry was rewritten with the current locations
+    Updated,
+    /// The entry already used the current locations
+    Unchanged,
+    /// The config file no longer exists
+    Missing,
+    /// The config no longer has a magick-mcp entry, so it was left alone
+    Removed,
+}
+
+/// The outcome of refreshing one client config
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RefreshedConfig {
+    pub path: PathBuf,
+    pub status: RefreshStatus,
+}
+
 /// Installer for MCP configuration
 pub struct MCPInstaller {
     client_type: ClientType,
     config_paths: ConfigPaths,
+    state_path: Option<PathBuf>,
+    magick_path: Option<PathBuf>,
 }
 
 #[derive(Error, Debug)]
@@ -56,43 +101,88 @@ impl MCPInstaller {
         MCPInstaller {
             client_type,
             config_paths,
+            state_path: None,
+            magick_path: None,
+        }
+    }
+
+    /// Record the configs which were updated in `installs.json` in the data directory, so
+    /// `refresh_paths` can find them
+    pub fn with_recorded_installs(self) -> Self {
+        match data_dir() {
+            Some(dir) => self.with_state_file(dir.join(INSTALL_STATE_FILE)),
+            None => self,
         }
     }
 
+    fn with_state_file(mut self, path: PathBuf) -> Self {
+        self.state_path = Some(path);
+        self
+    }
+
+    /// The current location of `magick`, recorded with each config
+    pub fn with_magick_path(mut self, path: Option<PathBuf>) -> Self {
+        self.magick_path = path;
+        self
+    }
+
     /// Install magick-mcp to the specified client(s)
     pub fn install(&self) -> Result<(), InstallError> {
-        match self.client_type {
-            ClientType::Cursor => {
-                self.update_config(&self.config_paths.cursor_path)?;
-            }
-            ClientType::Claude => {
-                self.update_config(&self.config_paths.claude_path)?;
-            }
-            ClientType::Both => {
-                self.update_config(&self.config_paths.cursor_path)?;
-                self.update_config(&self.config_paths.claude_path)?;
+        let paths = match self.client_type {
+            ClientType::Cursor => vec![&self.config_paths.cursor_path],
+            ClientType::Claude => vec![&self.config_paths.claude_path],
+            ClientType::Both => vec![
+                &self.config_paths.cursor_path,
+                &self.config_paths.claude_path,
+            ],
+        };
+        let exe_path = current_exe()?;
+        let mut state = self.load_state()?;
+        for path in paths {
+            self.update_config(path, &exe_path)?;
+            state.configs.retain(|config| &config.path != path);
+            state.configs.push(InstalledConfig {
+                path: path.clone(),
+                exe: exe_path.clone(),
+                magick: self.magick_path.clone(),
+            });
+        }
+        self.save_state(&state)
+    }
+
+    /// Rewrite every recorded config with the current executable and `magick` locations
+    ///
+    /// Upgrades such as `cargo install` or `brew upgrade` can move the executable, leaving
+    /// clients launching a path which no longer exists. The magick-mcp entry's command is
+    /// rewritten, and where its `env.PATH` lists the directory `magick` used to be found in,
+    /// that directory is replaced with the current one. Other settings in the entry are kept.
+    ///
+    /// # Returns
+    ///
+    /// Returns what happened to each recorded config, or an `InstallError` if one cannot be
+    /// read or written
+    pub fn refresh_paths(&self) -> Result<Vec<RefreshedConfig>, InstallError> {
+        let exe_path = current_exe()?;
+        let mut state = self.load_state()?;
+        let mut refreshed = Vec::new();
+        for recorded in &mut state.configs {
+            let status = self.refresh_config(recorded, &exe_path)?;
+            if status == RefreshStatus::Updated {
+                recorded.exe = exe_path.clone();
+                recorded.magick = self.magick_path.clone();
             }
+            refreshed.push(RefreshedConfig {
+                path: recorded.path.clone(),
+                status,
+            });
         }
-        Ok(())
+        self.save_state(&state)?;
+        Ok(refreshed)
     }
 
     /// Update a single configuration file
-    fn update_config(&self, path: &Path) -> Result<(), InstallError> {
-        // Get the path to the magick-mcp executable
-        let exe_path =
-            std::env::current_exe().map_err(|e| InstallError::ExePathError(e.to_string()))?;
-
-        // Read existing config or create new one
-        let mut config: Value = if path.exists() {
-            let contents = fs::read_to_string(path)?;
-            if contents.trim().is_empty() {
-                json!({})
-            } else {
-                serde_json::from_str(&contents)?
-            }
-        } else {
-            json!({})
-        };
+    fn update_config(&self, path: &Path, exe_path: &Path) -> Result<(), InstallError> {
+        let mut config = read_config(path)?;
 
         // Ensure mcpServers object exists
         if config.get("mcpServers").is_none() {
@@ -107,25 +197,162 @@ impl MCPInstaller {
                 InstallError::InvalidConfig("mcpServers is not an object".to_string())
             })?;
 
-        // Add or update magick-mcp server entry
-        mcp_servers.insert(
-            "magick-mcp".to_string(),
-            json!({
-                "command": exe_path.to_string_lossy().to_string(),
-                "args": ["mcp"]
-            }),
-        );
+        // Add or update magick-mcp server entry, keeping settings such as env
+        let current = self.server_entry(path, exe_path);
+        match mcp_servers
+            .get_mut("magick-mcp")
+            .and_then(Value::as_object_mut)
+        {
+            Some(entry) => {
+                entry.insert("command".to_string(), current["command"].clone());
+                entry.insert("args".to_string(), current["args"].clone());
+            }
+            None => {
+                mcp_servers.insert("magick-mcp".to_string(), current);
+            }
+        }
+
+        write_config(path, &config)
+    }
+
+    /// Rewrite the magick-mcp entry of a recorded config if its locations are out of date
+    fn refresh_config(
+        &self,
+        recorded: &InstalledConfig,
+        exe_path: &Path,
+    ) -> Result<RefreshStatus, InstallError> {
+        if !recorded.path.exists() {
+            return Ok(RefreshStatus::Missing);
+        }
+        let mut config = read_config(&recorded.path)?;
+        let Some(entry) = config
+            .get_mut("mcpServers")
+            .and_then(|servers| servers.get_mut("magick-mcp"))
+            .and_then(Value::as_object_mut)
+        else {
+            return Ok(RefreshStatus::Removed);
+        };
+        let previous = entry.clone();
+
+        let current = self.server_entry(&recorded.path, exe_path);
+        entry.insert("command".to_string(), current["command"].clone());
+        entry.insert("args".to_string(), current["args"].clone());
+        let old_dir = recorded.magick.as_deref().and_then(Path::parent);
+        let new_dir = self.magick_path.as_deref().and_then(Path::parent);
+        if let (Some(old_dir), Some(new_dir)) = (old_dir, new_dir)
+            && let Some(Value::String(path_var)) =
+                entry.get_mut("env").and_then(|env| env.get_mut("PATH"))
+        {
+            *path_var = replace_path_entry(path_var, old_dir, new_dir);
+        }
+
+        if *entry == previous {
+            return Ok(RefreshStatus::Unchanged);
+        }
+        write_config(&recorded.path, &config)?;
+        Ok(RefreshStatus::Updated)
+    }
+
+    /// The magick-mcp entry for a config on this host
+    fn server_entry(&self, config_path: &Path, exe_path: &Path) -> Value {
+        let distro = std::env::var("WSL_DISTRO_NAME").ok();
+        server_entry(Host::current(), exe_path, config_path, distro.as_deref())
+    }
+
+    fn load_state(&self) -> Result<InstallState, InstallError> {
+        match &self.state_path {
+            Some(path) if path.exists() => Ok(serde_json::from_str(&fs::read_to_string(path)?)?),
+            _ => Ok(InstallState::default()),
+        }
+    }
 
-        // Create parent directory if it doesn't exist
+    fn save_state(&self, state: &InstallState) -> Result<(), InstallError> {
+        let Some(path) = &self.state_path else {
+            return Ok(());
+        };
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)?;
         }
+        write_atomic(path, serde_json::to_string_pretty(state)?)?;
+        Ok(())
+    }
+}
 
-        // Write updated config back to file
-        let pretty_json = serde_json::to_string_pretty(&config)?;
-        fs::write(path, pretty_json)?;
+/// The path to the running magick-mcp executable
+fn current_exe() -> Result<PathBuf, InstallError> {
+    std::env::current_exe().map_err(|e| InstallError::ExePathError(e.to_string()))
+}
 
-        Ok(())
+/// Read a client config, treating a missing or empty file as an empty config
+fn read_config(path: &Path) -> Result<Value, InstallError> {
+    if !path.exists() {
+        return Ok(json!({}));
+    }
+    let contents = fs::read_to_string(path)?;
+    if contents.trim().is_empty() {
+        Ok(json!({}))
+    } else {
+        Ok(serde_json::from_str(&contents)?)
+    }
+}
+
+/// Write a client config, creating its directory if needed
+fn write_config(path: &Path, config: &Value) -> Result<(), InstallError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let pretty_json = serde_json::to_string_pretty(config)?;
+    fs::write(path, pretty_json)?;
+    Ok(())
+}
+
+/// Replace a directory in a `PATH` style list, leaving the other entries in place
+fn replace_path_entry(path_var: &str, old_dir: &Path, new_dir: &Path) -> String {
+    let separator = if path_var.contains(';') { ';' } else { ':' };
+    path_var
+        .split(separator)
+        .map(|dir| {
+            if Path::new(dir) == old_dir {
+                new_dir.to_string_lossy().to_string()
+            } else {
+                dir.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(&separator.to_string())
+}
+
+/// The `mcpServers` entry launching this executable from a client config
+///
+/// A client on the other side of WSL from the executable cannot start it directly. A Windows
+/// client's config written from inside WSL launches it through `wsl.exe` in the same
+/// distribution, and a WSL client's config written from Windows uses the `/mnt` path of the
+/// Windows executable, which WSL can run.
+///
+/// # Arguments
+///
+/// * `host` - Where the executable runs
+/// * `exe_path` - Path to the executable on the host
+/// * `config_path` - The client config being written
+/// * `distro` - The WSL distribution the executable is installed in, when known
+fn server_entry(host: Host, exe_path: &Path, config_path: &Path, distro: Option<&str>) -> Value {
+    let exe = exe_path.to_string_lossy().to_string();
+    if !wsl::is_foreign_config(host, config_path) {
+        return json!({ "command": exe, "args": ["mcp"] });
+    }
+    match host {
+        Host::Wsl => {
+            let mut args: Vec<&str> = Vec::new();
+            if let Some(distro) = distro {
+                args.extend(["-d", distro]);
+            }
+            args.extend(["-e", &exe, "mcp"]);
+            json!({ "command": "wsl.exe", "args": args })
+        }
+        _ => json!({
+            "command": wsl::translate_path_for(Host::Wsl, &exe),
+            "args": ["mcp"]
+        }),
     }
 }
 
@@ -314,4 +541,86 @@ mod tests {
                 .contains("magick-mcp")
         );
     }
+
+    #[test]
+    fn test_server_entry_across_wsl() {
+        let linux_exe = Path::new("/home/me/.cargo/bin/magick-mcp");
+        let windows_config = Path::new("/mnt/c/Users/me/.cursor/mcp.json");
+        assert_eq!(
+            server_entry(Host::Wsl, linux_exe, windows_config, Some("Ubuntu")),
+            json!({
+                "command": "wsl.exe",
+                "args": ["-d", "Ubuntu", "-e", "/home/me/.cargo/bin/magick-mcp", "mcp"]
+            })
+        );
+        assert_eq!(
+            server_entry(
+                Host::Wsl,
+                linux_exe,
+                Path::new("/home/me/.claude.json"),
+                None
+            ),
+            json!({ "command": "/home/me/.cargo/bin/magick-mcp", "args": ["mcp"] })
+        );
+
+        let windows_exe = Path::new(r"C:\Users\me\.cargo\bin\magick-mcp.exe");
+        let wsl_config = Path::new(r"\\wsl$\Ubuntu\home\me\.claude.json");
+        assert_eq!(
+            server_entry(Host::Windows, windows_exe, wsl_config, None),
+            json!({
+                "command": "/mnt/c/Users/me/.cargo/bin/magick-mcp.exe",
+                "args": ["mcp"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_refresh_paths_rewrites_recorded_configs() {
+        let temp_dir = TempDir::new().unwrap();
+        let cursor_path = temp_dir.path().join("mcp.json");
+        let claude_path = temp_dir.path().join("claude.json");
+        let state_path = temp_dir.path().join("data").join(INSTALL_STATE_FILE);
+        let config_paths = ConfigPaths {
+            cursor_path: cursor_path.clone(),
+            claude_path: claude_path.clone(),
+        };
+        MCPInstaller::new(ClientType::Both, config_paths.clone())
+            .with_state_file(state_path.clone())
+            .with_magick_path(Some(PathBuf::from("/usr/local/bin/magick")))
+            .install()
+            .unwrap();
+
+        // Simulate an upgrade which moved the executable and magick
+        fs::write(
+            &cursor_path,
+            r#"{"mcpServers": {"magick-mcp": {"command": "/old/magick-mcp", "args": ["mcp"], "env": {"PATH": "/usr/local/bin:/usr/bin"}}}}"#,
+        )
+        .unwrap();
+        fs::remove_file(&claude_path).unwrap();
+
+        let refreshed = MCPInstaller::new(ClientType::Both, config_paths)
+            .with_state_file(state_path)
+            .with_magick_path(Some(PathBuf::from("/opt/homebrew/bin/magick")))
+            .refresh_paths()
+            .unwrap();
+
+        assert_eq!(
+            refreshed,
+            vec![
+                RefreshedConfig {
+                    path: cursor_path.clone(),
+                    status: RefreshStatus::Updated
+                },
+                RefreshedConfig {
+                    path: claude_path,
+                    status: RefreshStatus::Missing
+                },
+            ]
+        );
+        let config: Value =
+            serde_json::from_str(&fs::read_to_string(&cursor_path).unwrap()).unwrap();
+        let entry = &config["mcpServers"]["magick-mcp"];
+        assert_ne!(entry["command"], "/old/magick-mcp");
+        assert_eq!(entry["env"]["PATH"], "/opt/homebrew/bin:/usr/bin");
+    }
 }
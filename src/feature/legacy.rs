@@ -0,0 +1,143 @@
+use serde::Serialize;
+
+/// ImageMagick 6 programs which are subcommands of `magick` in ImageMagick 7
+///
+/// `convert` is missing since its IM7 equivalent is `magick` itself, so it is dropped instead.
+const SUBCOMMANDS: &[&str] = &[
+    "animate",
+    "compare",
+    "composite",
+    "conjure",
+    "display",
+    "identify",
+    "import",
+    "mogrify",
+    "montage",
+    "stream",
+];
+
+/// Options ImageMagick 7 removed, with their replacements
+const OPTION_ALIASES: &[(&str, &[&str])] = &[
+    ("-matte", &["-alpha", "set"]),
+    ("+matte", &["-alpha", "off"]),
+    ("-average", &["-evaluate-sequence", "mean"]),
+    ("-maximum", &["-evaluate-sequence", "max"]),
+    ("-minimum", &["-evaluate-sequence", "min"]),
+    ("-deconstruct", &["-layers", "CompareAny"]),
+    ("-map", &["-remap"]),
+    ("-affinity", &["-remap"]),
+    ("-median", &["-statistic", "Median"]),
+];
+
+/// A legacy ImageMagick 6 command rewritten to ImageMagick 7 syntax
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LegacyRewrite {
+    /// The command as it was given
+    pub original: String,
+    /// The command which is run instead
+    pub command: String,
+    /// What was changed, e.g. `convert -> magick`
+    pub changes: Vec<String>,
+}
+
+/// Rewrite a command written for ImageMagick 6 into ImageMagick 7 syntax
+///
+/// A leading `convert` is dropped, since the server runs `magick` itself, and other IM6
+/// programs such as `identify` or `mogrify` become `magick` subcommands. Paths and `.exe` names
+/// such as `/usr/bin/convert` or `Identify.exe` are recognized too. Options removed in IM7, such
+/// as `-matte` or `-average`, are replaced with their IM7 spelling.
+///
+/// # Arguments
+///
+/// * `command` - ImageMagick command arguments
+///
+/// # Returns
+///
+/// Returns the rewrite, or `None` when the command needs no changes
+pub fn rewrite_legacy(command: &str) -> Option<LegacyRewrite> {
+    let mut args: Vec<String> = command.split_whitespace().map(str::to_string).collect();
+    let mut changes = Vec::new();
+
+    if let Some(first) = args.first() {
+        let program = program_name(first);
+        if program == "convert" {
+            changes.push(format!("{first} -> magick"));
+            args.remove(0);
+        } else if SUBCOMMANDS.contains(&program.as_str()) && *first != program {
+            changes.push(format!("{first} -> magick {program}"));
+            args[0] = program;
+        }
+    }
+
+    let mut rewritten = Vec::with_capacity(args.len());
+    for arg in args {
+        match OPTION_ALIASES.iter().find(|(alias, _)| *alias == arg) {
+            Some((alias, replacement)) => {
+                let replacement = replacement.join(" ");
+                changes.push(format!("{alias} -> {replacement}"));
+                rewritten.push(replacement);
+            }
+            None => rewritten.push(arg),
+        }
+    }
+
+    (!changes.is_empty()).then(|| LegacyRewrite {
+        original: command.to_string(),
+        command: rewritten.join(" "),
+        changes,
+    })
+}
+
+/// The lowercase program name of an argument, without its directory or `.exe` suffix
+fn program_name(arg: &str) -> String {
+    let program = arg
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(arg)
+        .to_ascii_lowercase();
+    match program.strip_suffix(".exe") {
+        Some(stem) => stem.to_string(),
+        None => program,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_convert() {
+        let rewrite = rewrite_legacy("convert in.png -matte out.png").unwrap();
+
+        assert_eq!(rewrite.command, "in.png -alpha set out.png");
+        assert_eq!(
+            rewrite.changes,
+            vec!["convert -> magick", "-matte -> -alpha set"]
+        );
+        assert_eq!(
+            rewrite_legacy("C:\\ImageMagick\\convert.exe a.png b.png")
+                .unwrap()
+                .command,
+            "a.png b.png"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_subcommands() {
+        assert_eq!(
+            rewrite_legacy("/usr/bin/identify -verbose x.png")
+                .unwrap()
+                .command,
+            "identify -verbose x.png"
+        );
+        assert_eq!(
+            rewrite_legacy("frames-*.png -average mean.png")
+                .unwrap()
+                .command,
+            "frames-*.png -evaluate-sequence mean mean.png"
+        );
+        assert_eq!(rewrite_legacy("identify -verbose x.png"), None);
+        assert_eq!(rewrite_legacy("in.png -negate out.png"), None);
+        assert_eq!(rewrite_legacy("converted.png out.png"), None);
+    }
+}
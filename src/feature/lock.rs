@@ -0,0 +1,151 @@
+use crate::feature::ops::OperationError;
+use crate::feature::trace::current_trace_id;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// Which writes a command serializes against while it runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LockScope {
+    /// Run without waiting for other commands
+    #[default]
+    None,
+    /// Wait for other commands writing the same output file
+    Output,
+    /// Wait for every other locked command in the same workspace
+    Workspace,
+}
+
+impl LockScope {
+    /// Parse a lock scope name
+    pub fn parse(name: &str) -> Result<Self, OperationError> {
+        match name {
+            "none" => Ok(LockScope::None),
+            "output" => Ok(LockScope::Output),
+            "workspace" => Ok(LockScope::Workspace),
+            other => Err(OperationError::InvalidParameter(format!(
+                "unknown lock scope '{other}', expected none, output, or workspace"
+            ))),
+        }
+    }
+}
+
+/// Paths currently locked within this process
+///
+/// A path conflicts with any locked path it is inside of or contains, so a workspace lock waits
+/// for output locks within the workspace and the reverse.
+struct PathLocks {
+    held: Mutex<Vec<PathBuf>>,
+    released: Condvar,
+}
+
+static PATH_LOCKS: LazyLock<PathLocks> = LazyLock::new(|| PathLocks {
+    held: Mutex::new(Vec::new()),
+    released: Condvar::new(),
+});
+
+/// Total time each traced request has spent waiting for locks
+static LOCK_WAITS: LazyLock<Mutex<HashMap<String, Duration>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// A held path lock, released when dropped
+pub(crate) struct PathLock {
+    path: PathBuf,
+}
+
+impl PathLock {
+    /// Block until no conflicting path is locked, then lock `path`
+    ///
+    /// The time spent waiting is added to the current request's lock wait.
+    pub(crate) fn acquire(path: &Path) -> Self {
+        let started = Instant::now();
+        let conflicts = |held: &Vec<PathBuf>| {
+            held.iter()
+                .any(|locked| locked.starts_with(path) || path.starts_with(locked))
+        };
+        let guard = PATH_LOCKS.held.lock().unwrap_or_else(|e| e.into_inner());
+        let mut held = PATH_LOCKS
+            .released
+            .wait_while(guard, |held| conflicts(held))
+            .unwrap_or_else(|e| e.into_inner());
+        held.push(path.to_path_buf());
+        drop(held);
+
+        if let Some(trace_id) = current_trace_id() {
+            let mut waits = LOCK_WAITS.lock().unwrap_or_else(|e| e.into_inner());
+            *waits.entry(trace_id).or_default() += started.elapsed();
+        }
+        PathLock {
+            path: path.to_path_buf(),
+        }
+    }
+}
+
+impl Drop for PathLock {
+    fn drop(&mut self) {
+        let mut held = PATH_LOCKS.held.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(index) = held.iter().position(|locked| *locked == self.path) {
+            held.swap_remove(index);
+        }
+        drop(held);
+        PATH_LOCKS.released.notify_all();
+    }
+}
+
+/// Take the time a request spent waiting for locks
+///
+/// # Returns
+///
+/// Returns the total wait, or `None` if the request never took a lock
+pub fn take_lock_wait(trace_id: &str) -> Option<Duration> {
+    LOCK_WAITS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(trace_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::trace::in_trace;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(LockScope::parse("output").unwrap(), LockScope::Output);
+        assert_eq!(LockScope::parse("workspace").unwrap(), LockScope::Workspace);
+        assert!(LockScope::parse("file").is_err());
+    }
+
+    #[test]
+    fn test_workspace_lock_waits_for_output_lock() {
+        let output = PathLock::acquire(Path::new("/lock-test/ws/out.png"));
+        let acquired = Arc::new(AtomicBool::new(false));
+        let waiter = {
+            let acquired = acquired.clone();
+            std::thread::spawn(move || {
+                in_trace(Some("lock-test".to_string()), || {
+                    let _workspace = PathLock::acquire(Path::new("/lock-test/ws"));
+                    acquired.store(true, Ordering::SeqCst);
+                })
+            })
+        };
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(!acquired.load(Ordering::SeqCst));
+
+        drop(output);
+        waiter.join().unwrap();
+
+        assert!(acquired.load(Ordering::SeqCst));
+        assert!(take_lock_wait("lock-test").unwrap() >= Duration::from_millis(50));
+        assert_eq!(take_lock_wait("lock-test"), None);
+    }
+
+    #[test]
+    fn test_unrelated_paths_do_not_wait() {
+        let _first = PathLock::acquire(Path::new("/lock-test/a/out.png"));
+        let _second = PathLock::acquire(Path::new("/lock-test/b/out.png"));
+    }
+}
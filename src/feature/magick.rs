@@ -1,10 +1,59 @@
+use crate::feature::cache::BuildCache;
+use crate::feature::incremental;
+use crate::feature::legacy::LegacyRewrite;
+use crate::feature::lock::{LockScope, PathLock};
+use crate::feature::output::{self, OutputPolicy, ResolvedOutput};
+use crate::feature::paths;
+use crate::feature::project::ProjectConfig;
 use crate::feature::shell::{CommandRunner, ShellError};
-use std::path::Path;
+use crate::feature::transaction::Transaction;
+use crate::feature::trash::TrashKind;
+use crate::feature::wsl;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use std::cell::{OnceCell, RefCell};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Largest image a command may write to standard output
+pub(crate) const MAX_STDOUT_BYTES: usize = 16 * 1024 * 1024;
+
+/// Lines of a saved stream kept in the summary returned in its place
+const SUMMARY_LINES: usize = 20;
+
+/// Files the raw output streams of a command are saved to
+///
+/// When standard output is saved, only a summary of it is returned, so verbose output such as
+/// `identify -verbose` can be kept on disk without passing all of it back.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StreamFiles {
+    /// File to save standard output to, relative to the workspace
+    pub stdout: Option<PathBuf>,
+    /// File to save standard error to, relative to the workspace
+    pub stderr: Option<PathBuf>,
+}
+
+/// Output of a single `magick` command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MagickOutput {
+    /// Text written to standard output
+    pub output: String,
+    /// The file written, if the command writes one
+    pub file: Option<ResolvedOutput>,
+    /// How the command was rewritten from legacy ImageMagick 6 syntax before running
+    pub rewrite: Option<LegacyRewrite>,
+}
 
 /// Runner for executing ImageMagick commands
 pub(crate) struct MagickRunner<'a> {
     command_runner: &'a dyn CommandRunner,
     workspace: Option<&'a Path>,
+    output_policy: OutputPolicy,
+    resolved_outputs: RefCell<Vec<ResolvedOutput>>,
+    project: OnceCell<Option<ProjectConfig>>,
+    cache: BuildCache,
+    stream_files: StreamFiles,
+    transaction: Option<&'a Transaction>,
 }
 
 impl<'a> MagickRunner<'a> {
@@ -18,9 +67,35 @@ impl<'a> MagickRunner<'a> {
         MagickRunner {
             command_runner,
             workspace,
+            output_policy: OutputPolicy::default(),
+            resolved_outputs: RefCell::new(Vec::new()),
+            project: OnceCell::new(),
+            cache: BuildCache::new(),
+            stream_files: StreamFiles::default(),
+            transaction: None,
         }
     }
 
+    /// Use the provided policy for files written by commands
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.output_policy = output_policy;
+        self
+    }
+
+    /// Save the output streams of commands to files
+    pub fn with_stream_files(mut self, stream_files: StreamFiles) -> Self {
+        self.stream_files = stream_files;
+        self
+    }
+
+    /// Write outputs to a transaction's staging area instead of the workspace
+    ///
+    /// Commands reading an output staged earlier in the transaction read the staged file.
+    pub fn with_transaction(mut self, transaction: &'a Transaction) -> Self {
+        self.transaction = Some(transaction);
+        self
+    }
+
     /// Execute an ImageMagick command by parsing the command string
     ///
     /// # Arguments
@@ -32,14 +107,426 @@ impl<'a> MagickRunner<'a> {
     /// Returns the command output as a String, or a ShellError if execution fails
     pub fn execute(&self, command: &str) -> Result<String, ShellError> {
         let args: Vec<&str> = command.split_whitespace().collect();
-        self.command_runner.execute("magick", &args, self.workspace)
+        self.execute_args(&args)
+    }
+
+    /// Execute an ImageMagick command from pre-split arguments
+    ///
+    /// Unlike `execute`, no whitespace splitting is performed, so individual arguments may
+    /// contain spaces (e.g. caption text).
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The arguments to pass to `magick`
+    ///
+    /// # Returns
+    ///
+    /// Returns the command output as a String, or a ShellError if execution fails
+    pub fn execute_args(&self, args: &[&str]) -> Result<String, ShellError> {
+        // Paths written for the other side of WSL are translated before anything reads them
+        let translated = wsl::translate_args(args);
+        let translated_refs: Vec<&str>;
+        let args = match &translated {
+            Some(translated) => {
+                translated_refs = translated.iter().map(String::as_str).collect();
+                &translated_refs[..]
+            }
+            None => args,
+        };
+        let (Some(project), Some(workspace)) = (self.project()?, self.workspace) else {
+            return self.execute_resolved(args);
+        };
+        let args = apply_project(project, workspace, args)?;
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.execute_resolved(&arg_refs)
     }
+
+    /// Execute a command after project conventions have been applied
+    fn execute_resolved(&self, args: &[&str]) -> Result<String, ShellError> {
+        let staged_inputs = self
+            .transaction
+            .and_then(|transaction| transaction.read_staged(args));
+        let args: Vec<&str> = match &staged_inputs {
+            Some(staged) => staged.iter().map(String::as_str).collect(),
+            None => args.to_vec(),
+        };
+        if output::writes_stdout(&args)? {
+            return self.execute_to_stdout(&args);
+        }
+        self.prepare_output(&args)?;
+        let _lock = self.lock(&args);
+        let Some(requested) = output::output_path(&args) else {
+            return self.run(&args);
+        };
+        let base = self.workspace.unwrap_or(Path::new("."));
+        // Staged inputs are not in the workspace yet, so their outputs can't be up to date
+        let incremental = self.output_policy.skip_if_newer && staged_inputs.is_none();
+        if incremental && incremental::is_current(&args, &requested, base) {
+            self.resolved_outputs.borrow_mut().push(ResolvedOutput {
+                path: requested,
+                skipped: true,
+            });
+            return Ok(String::new());
+        }
+        let resolved =
+            output::resolve_conflict(&requested, self.workspace, self.output_policy.on_conflict);
+        let Some(path) = resolved else {
+            self.resolved_outputs.borrow_mut().push(ResolvedOutput {
+                path: requested,
+                skipped: true,
+            });
+            return Ok(String::new());
+        };
+        let written = match self.transaction {
+            Some(transaction) => {
+                Some(
+                    transaction
+                        .stage(&path)
+                        .map_err(|e| ShellError::ExecutionFailed {
+                            message: format!("failed to stage output '{}': {e}", path.display()),
+                            command: "magick".to_string(),
+                            args: args.join(" "),
+                        })?,
+                )
+            }
+            None => (path != requested).then(|| path.clone()),
+        };
+        let written_path = match (&written, self.transaction) {
+            (Some(staged), Some(_)) => staged.clone(),
+            _ => base.join(&path),
+        };
+
+        let cache_key = incremental.then(|| BuildCache::key(&args, base)).flatten();
+        // A cache hit restoring the bytes already there replaces nothing worth keeping
+        let unchanged = cache_key
+            .as_ref()
+            .is_some_and(|key| self.cache.holds(key, &written_path));
+        if written.is_none() && !unchanged {
+            self.trash_overwritten(&written_path);
+        }
+        if let Some(key) = &cache_key
+            && self.cache.restore(key, &written_path).unwrap_or(false)
+        {
+            self.record(&args, &path, base);
+            self.resolved_outputs
+                .borrow_mut()
+                .push(ResolvedOutput::written(path));
+            return Ok(String::new());
+        }
+
+        let result = match &written {
+            Some(written) => {
+                let last = output::replace_output_path(args[args.len() - 1], written);
+                let mut redirected = args[..args.len() - 1].to_vec();
+                redirected.push(&last);
+                self.run(&redirected)
+            }
+            None => self.run(&args),
+        };
+        if incremental && result.is_ok() {
+            self.record(&args, &path, base);
+            if let Some(key) = &cache_key {
+                // Failing to cache only means the next run cannot reuse this output
+                let _ = self.cache.store(key, &written_path);
+            }
+        }
+        self.resolved_outputs
+            .borrow_mut()
+            .push(ResolvedOutput::written(path));
+        result
+    }
+
+    /// Keep a copy of an existing output about to be overwritten, when the policy asks for it
+    fn trash_overwritten(&self, path: &Path) {
+        let Some(trash) = self.output_policy.overwrite_trash() else {
+            return;
+        };
+        if path.is_file() {
+            // Failing to trash only means the overwritten output cannot be restored
+            let _ = trash.keep(path, TrashKind::Output);
+        }
+    }
+
+    /// Record that a command wrote an output, once it is in place if a transaction is staging it
+    fn record(&self, args: &[&str], path: &Path, base: &Path) {
+        match self.transaction {
+            Some(transaction) => transaction.record_on_commit(args, path),
+            // Failing to record only means the next run cannot skip this output
+            None => {
+                let _ = incremental::record(args, path, base);
+            }
+        }
+    }
+
+    /// Run `magick`, saving its output streams when asked to
+    ///
+    /// Saved streams are replaced by a summary in the result, including in the error when the
+    /// command fails.
+    fn run(&self, args: &[&str]) -> Result<String, ShellError> {
+        let StreamFiles { stdout, stderr } = &self.stream_files;
+        if stdout.is_none() && stderr.is_none() {
+            return self.command_runner.execute("magick", args, self.workspace);
+        }
+        match self
+            .command_runner
+            .execute_with_stderr("magick", args, self.workspace)
+        {
+            Ok(output) => {
+                self.save_stream(stderr, &output.stderr)?;
+                self.save_stream(stdout, &output.stdout)
+            }
+            Err(ShellError::NonZeroExit {
+                exit_code,
+                command,
+                args,
+                stdout: out,
+                stderr: err,
+            }) => Err(ShellError::NonZeroExit {
+                exit_code,
+                command,
+                args,
+                stdout: self.save_stream(stdout, &out)?,
+                stderr: self.save_stream(stderr, &err)?,
+            }),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Write a stream to its file, if it has one, returning the text to report in its place
+    fn save_stream(&self, file: &Option<PathBuf>, text: &str) -> Result<String, ShellError> {
+        let Some(file) = file else {
+            return Ok(text.to_string());
+        };
+        let path = self.workspace.unwrap_or(Path::new(".")).join(file);
+        fs::write(&path, text).map_err(|e| ShellError::SaveOutputFailed {
+            path: file.display().to_string(),
+            message: e.to_string(),
+        })?;
+        Ok(summarize(text, file))
+    }
+
+    /// Execute a command which writes to standard output, capping how much it may write
+    ///
+    /// Text formats such as `json:-` are returned as they are and binary images base64 encoded.
+    fn execute_to_stdout(&self, args: &[&str]) -> Result<String, ShellError> {
+        let bytes =
+            self.command_runner
+                .execute_capped("magick", args, self.workspace, MAX_STDOUT_BYTES)?;
+        Ok(String::from_utf8(bytes).unwrap_or_else(|e| STANDARD.encode(e.into_bytes())))
+    }
+
+    /// Take the lock the output policy asks for, held while the output is resolved and written
+    fn lock(&self, args: &[&str]) -> Option<PathLock> {
+        let workspace = self.workspace.unwrap_or(Path::new("."));
+        let path = match self.output_policy.lock {
+            LockScope::None => return None,
+            LockScope::Workspace => workspace.to_path_buf(),
+            LockScope::Output => workspace.join(output::output_path(args)?),
+        };
+        let path: PathBuf = std::path::absolute(&path).unwrap_or(path);
+        Some(PathLock::acquire(&path))
+    }
+
+    /// Check a file about to be written directly against the workspace's allowed formats and
+    /// sandbox roots
+    pub fn check_write(&self, path: &Path) -> Result<(), ShellError> {
+        let (Some(project), Some(workspace)) = (self.project()?, self.workspace) else {
+            return Ok(());
+        };
+        project
+            .check_write(path, workspace)
+            .map_err(|message| ShellError::ProjectConfig { message })
+    }
+
+    /// The workspace's `.magickmcp.toml`, discovered on first use
+    fn project(&self) -> Result<Option<&ProjectConfig>, ShellError> {
+        if let Some(project) = self.project.get() {
+            return Ok(project.as_ref());
+        }
+        let discovered = match self.workspace {
+            Some(workspace) => {
+                ProjectConfig::discover(workspace).map_err(|e| ShellError::ProjectConfig {
+                    message: e.to_string(),
+                })?
+            }
+            None => None,
+        };
+        Ok(self.project.get_or_init(|| discovered).as_ref())
+    }
+
+    /// Take the outputs written (or skipped) by commands run so far
+    ///
+    /// Paths are reported without any `\\?\` prefix so they read as the user wrote them.
+    pub fn take_resolved_outputs(&self) -> Vec<ResolvedOutput> {
+        let mut outputs = self.resolved_outputs.take();
+        for output in &mut outputs {
+            output.path = paths::strip_verbatim(&output.path);
+        }
+        outputs
+    }
+
+    /// The number of outputs recorded and not yet taken
+    pub fn resolved_output_count(&self) -> usize {
+        self.resolved_outputs.borrow().len()
+    }
+
+    /// The path written by the most recent command which wrote a file, leaving it recorded
+    pub fn latest_output_path(&self) -> Option<PathBuf> {
+        self.resolved_outputs
+            .borrow()
+            .last()
+            .map(|output| paths::strip_verbatim(&output.path))
+    }
+
+    /// The output of the most recent command, falling back to the requested path
+    pub fn last_resolved_output(&self, requested: &str) -> ResolvedOutput {
+        self.take_resolved_outputs()
+            .pop()
+            .unwrap_or_else(|| ResolvedOutput::written(requested))
+    }
+
+    /// Validate the output directory before running, creating it if the policy allows
+    ///
+    /// ImageMagick fails opaquely when the output directory is missing, so this reports the
+    /// directory by name instead.
+    fn prepare_output(&self, args: &[&str]) -> Result<(), ShellError> {
+        let Some(directory) = output::missing_output_dir(args, self.workspace) else {
+            return Ok(());
+        };
+        if !self.output_policy.create_dirs {
+            return Err(ShellError::MissingOutputDirectory {
+                directory: directory.display().to_string(),
+            });
+        }
+        if self.transaction.is_some() {
+            // Created when the transaction commits
+            return Ok(());
+        }
+        let resolved = match self.workspace {
+            Some(workspace) => workspace.join(&directory),
+            None => directory.clone(),
+        };
+        fs::create_dir_all(&resolved).map_err(|e| ShellError::ExecutionFailed {
+            message: format!(
+                "failed to create output directory '{}': {e}",
+                directory.display()
+            ),
+            command: "magick".to_string(),
+            args: args.join(" "),
+        })
+    }
+}
+
+/// Programs a command sent to the magick tool must not start with, since the server runs
+/// `magick` itself and `convert` is only the legacy ImageMagick 6 spelling of it
+const BAD_PREFIXES: &[&str] = &["magick", "convert"];
+
+/// The corrected command when a command starts with `magick`
+///
+/// Agents often include the program name, which ImageMagick would read as an input file. Paths
+/// and `.exe` names such as `/usr/bin/magick` or `magick.exe` are recognized too, and so is
+/// `magick convert`. A command starting with `convert` alone is legacy ImageMagick 6 syntax,
+/// which is rewritten rather than rejected. Returns `None` when the command has no such prefix.
+pub(crate) fn strip_bad_prefix(command: &str) -> Option<String> {
+    let mut args: Vec<&str> = command.split_whitespace().collect();
+    let mut stripped = false;
+    while let Some(first) = args.first() {
+        let program = first
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or(first)
+            .to_ascii_lowercase();
+        let program = program.strip_suffix(".exe").unwrap_or(&program);
+        if !BAD_PREFIXES.contains(&program) || (!stripped && program != "magick") {
+            break;
+        }
+        args.remove(0);
+        stripped = true;
+    }
+    stripped.then(|| args.join(" "))
+}
+
+/// The first lines of a saved stream, noting how many more were saved to its file
+fn summarize(text: &str, file: &Path) -> String {
+    let total = text.lines().count();
+    if total <= SUMMARY_LINES {
+        return text.to_string();
+    }
+    let mut summary: String = text
+        .lines()
+        .take(SUMMARY_LINES)
+        .map(|line| format!("{line}\n"))
+        .collect();
+    summary.push_str(&format!(
+        "... {} more lines saved to {}\n",
+        total - SUMMARY_LINES,
+        file.display()
+    ));
+    summary
+}
+
+/// Apply a project's quality presets, output directory, allowed formats, and sandbox roots
+fn apply_project(
+    project: &ProjectConfig,
+    workspace: &Path,
+    args: &[&str],
+) -> Result<Vec<String>, ShellError> {
+    let rejected = |message: String| ShellError::ProjectConfig { message };
+    let mut args = project.expand_quality_presets(args).map_err(rejected)?;
+    for target in write_targets(&args) {
+        project
+            .check_output(
+                Path::new(output::strip_format_prefix(target)),
+                output::output_format(target).as_deref(),
+                workspace,
+            )
+            .map_err(rejected)?;
+    }
+    let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+    let Some(mut output) = output::output_path(&arg_refs) else {
+        return Ok(args);
+    };
+    let last = args.len() - 1;
+    if let Some(placed) = project.place_output(&output) {
+        if let Some(parent) = placed.parent() {
+            fs::create_dir_all(workspace.join(parent)).map_err(|e| {
+                rejected(format!(
+                    "failed to create output directory '{}': {e}",
+                    parent.display()
+                ))
+            })?;
+        }
+        args[last] = output::replace_output_path(&args[last], &placed);
+        output = placed;
+    }
+    project
+        .check_output(
+            &output,
+            output::output_format(&args[last]).as_deref(),
+            workspace,
+        )
+        .map_err(rejected)?;
+    Ok(args)
+}
+
+/// Files written by `-write` options, leaving out streams and in-memory `mpr:` registers
+fn write_targets(args: &[String]) -> impl Iterator<Item = &str> {
+    args.windows(2)
+        .filter(|pair| pair[0] == "-write")
+        .map(|pair| pair[1].as_str())
+        .filter(|target| {
+            !target.ends_with(':')
+                && !target.starts_with("mpr:")
+                && output::stream_descriptor(target).is_none()
+        })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::feature::config::TrashConfig;
     use crate::feature::shell::{CommandRunner, ShellError};
+    use crate::feature::trash::Trash;
 
     /// Mock implementation of CommandRunner for testing
     pub struct MockCommandRunner {
@@ -139,6 +626,160 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_execute_args_preserves_whitespace() {
+        let mock_runner = MockCommandRunner::new("Annotated".to_string(), false);
+        let magick_runner = MagickRunner::new(&mock_runner, None);
+        let result = magick_runner.execute_args(&["in.png", "caption:hello world", "out.png"]);
+
+        assert!(result.is_ok());
+        assert_eq!(
+            *mock_runner.captured_args.borrow(),
+            vec!["in.png", "caption:hello world", "out.png"]
+        );
+    }
+
+    #[test]
+    fn test_missing_output_directory() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mock_runner = MockCommandRunner::new("Success".to_string(), false);
+        let magick_runner = MagickRunner::new(&mock_runner, Some(dir.path()));
+        let result = magick_runner.execute("in.png -negate out/neg.png");
+
+        assert!(matches!(
+            result,
+            Err(ShellError::MissingOutputDirectory { directory }) if directory == "out"
+        ));
+        assert!(mock_runner.captured_command.borrow().is_none());
+    }
+
+    #[test]
+    fn test_create_output_directory() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mock_runner = MockCommandRunner::new("Success".to_string(), false);
+        let magick_runner =
+            MagickRunner::new(&mock_runner, Some(dir.path())).with_output_policy(OutputPolicy {
+                create_dirs: true,
+                ..OutputPolicy::default()
+            });
+        let result = magick_runner.execute("in.png -negate out/nested/neg.png");
+
+        assert!(result.is_ok());
+        assert!(dir.path().join("out/nested").is_dir());
+    }
+
+    #[test]
+    fn test_conflict_rename() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(dir.path().join("out.png"), b"").unwrap();
+        let mock_runner = MockCommandRunner::new("Success".to_string(), false);
+        let magick_runner =
+            MagickRunner::new(&mock_runner, Some(dir.path())).with_output_policy(OutputPolicy {
+                on_conflict: output::ConflictStrategy::Rename,
+                ..OutputPolicy::default()
+            });
+        magick_runner.execute("in.png -negate out.png").unwrap();
+
+        assert_eq!(
+            *mock_runner.captured_args.borrow(),
+            vec!["in.png", "-negate", "out-1.png"]
+        );
+        assert_eq!(
+            magick_runner.take_resolved_outputs(),
+            vec![ResolvedOutput::written("out-1.png")]
+        );
+    }
+
+    #[test]
+    fn test_conflict_skip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(dir.path().join("out.png"), b"").unwrap();
+        let mock_runner = MockCommandRunner::new("Success".to_string(), false);
+        let magick_runner =
+            MagickRunner::new(&mock_runner, Some(dir.path())).with_output_policy(OutputPolicy {
+                on_conflict: output::ConflictStrategy::Skip,
+                ..OutputPolicy::default()
+            });
+        magick_runner.execute("in.png -negate out.png").unwrap();
+
+        assert!(mock_runner.captured_command.borrow().is_none());
+        assert!(magick_runner.last_resolved_output("out.png").skipped);
+    }
+
+    #[test]
+    fn test_trash_overwritten() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(dir.path().join("out.png"), b"old").unwrap();
+        let trash = Trash::in_data_dir(Some(dir.path().join("data")), &TrashConfig::default());
+        let run = |policy: OutputPolicy| {
+            let mock_runner = MockCommandRunner::new("Success".to_string(), false);
+            let magick_runner =
+                MagickRunner::new(&mock_runner, Some(dir.path())).with_output_policy(policy);
+            magick_runner.execute("in.png -negate out.png").unwrap();
+        };
+
+        // Trashing is off unless the policy has a trash, and never happens without an overwrite
+        run(OutputPolicy::default());
+        run(OutputPolicy {
+            on_conflict: output::ConflictStrategy::Rename,
+            trash: Some(trash.clone()),
+            ..OutputPolicy::default()
+        });
+        assert!(trash.list().unwrap().is_empty());
+
+        run(OutputPolicy {
+            trash: Some(trash.clone()),
+            ..OutputPolicy::default()
+        });
+        let entries = trash.list().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].kind, TrashKind::Output);
+    }
+
+    #[test]
+    fn test_skip_if_newer() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(dir.path().join("in.png"), b"").unwrap();
+        let mock_runner = MockCommandRunner::new("Success".to_string(), false);
+        let policy = OutputPolicy {
+            skip_if_newer: true,
+            ..OutputPolicy::default()
+        };
+        let magick_runner =
+            MagickRunner::new(&mock_runner, Some(dir.path())).with_output_policy(policy.clone());
+        magick_runner.execute("in.png -negate out.png").unwrap();
+        assert!(!magick_runner.last_resolved_output("out.png").skipped);
+
+        fs::write(dir.path().join("out.png"), b"").unwrap();
+        let mock_runner = MockCommandRunner::new("Success".to_string(), false);
+        let magick_runner =
+            MagickRunner::new(&mock_runner, Some(dir.path())).with_output_policy(policy);
+        magick_runner.execute("in.png -negate out.png").unwrap();
+
+        assert!(mock_runner.captured_command.borrow().is_none());
+        assert!(magick_runner.last_resolved_output("out.png").skipped);
+    }
+
+    #[test]
+    fn test_stdin_pseudo_file_is_rejected() {
+        let mock_runner = MockCommandRunner::new("Success".to_string(), false);
+        let magick_runner = MagickRunner::new(&mock_runner, None);
+        let result = magick_runner.execute("- -negate out.png");
+
+        assert!(matches!(result, Err(ShellError::PseudoFile { .. })));
+        assert!(mock_runner.captured_command.borrow().is_none());
+    }
+
+    #[test]
+    fn test_stdout_pseudo_file_returns_output() {
+        let mock_runner = MockCommandRunner::new("{\"image\":{}}".to_string(), false);
+        let magick_runner = MagickRunner::new(&mock_runner, None);
+        let result = magick_runner.execute("in.png json:-").unwrap();
+
+        assert_eq!(result, "{\"image\":{}}");
+        assert!(magick_runner.take_resolved_outputs().is_empty());
+    }
+
     #[test]
     fn test_multiple_operations() {
         let mock_runner = MockCommandRunner::new("Modified".to_string(), false);
@@ -163,4 +804,99 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_project_config_applied() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(
+            dir.path().join(".magickmcp.toml"),
+            "output_dir = \"generated\"\nallowed_formats = [\"webp\"]\n\n[quality_presets]\nweb = 80\n",
+        )
+        .unwrap();
+        let mock_runner = MockCommandRunner::new("Success".to_string(), false);
+        let magick_runner = MagickRunner::new(&mock_runner, Some(dir.path()));
+
+        magick_runner
+            .execute("in.png -quality web out.webp")
+            .unwrap();
+
+        assert_eq!(
+            *mock_runner.captured_args.borrow(),
+            vec!["in.png", "-quality", "80", "generated/out.webp"]
+        );
+        assert!(dir.path().join("generated").is_dir());
+        assert_eq!(
+            magick_runner.last_resolved_output("out.webp").path,
+            Path::new("generated/out.webp")
+        );
+
+        let rejected = magick_runner.execute("in.png out.png");
+        assert!(matches!(rejected, Err(ShellError::ProjectConfig { .. })));
+        let rejected = magick_runner.execute("in.png -write copy.png out.webp");
+        assert!(matches!(rejected, Err(ShellError::ProjectConfig { .. })));
+        assert!(
+            magick_runner
+                .execute("in.png -write mpr:copy -write copy.webp out.webp")
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_stream_files_save_output_and_summarize() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let verbose: String = (1..=30).map(|n| format!("line {n}\n")).collect();
+        let mock_runner = MockCommandRunner::new(verbose.clone(), false);
+        let magick_runner =
+            MagickRunner::new(&mock_runner, Some(dir.path())).with_stream_files(StreamFiles {
+                stdout: Some(PathBuf::from("verbose.txt")),
+                stderr: Some(PathBuf::from("warnings.txt")),
+            });
+
+        let summary = magick_runner.execute("identify -verbose in.png").unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.path().join("verbose.txt")).unwrap(),
+            verbose
+        );
+        assert!(dir.path().join("warnings.txt").exists());
+        assert!(summary.starts_with("line 1\nline 2\n"));
+        assert!(summary.ends_with("... 10 more lines saved to verbose.txt\n"));
+    }
+
+    #[test]
+    fn test_stream_files_saved_when_command_fails() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mock_runner = MockCommandRunner::new(String::new(), true);
+        let magick_runner =
+            MagickRunner::new(&mock_runner, Some(dir.path())).with_stream_files(StreamFiles {
+                stdout: None,
+                stderr: Some(PathBuf::from("errors.txt")),
+            });
+
+        assert!(magick_runner.execute("identify in.png").is_err());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("errors.txt")).unwrap(),
+            "Mock error"
+        );
+    }
+
+    #[test]
+    fn test_strip_bad_prefix() {
+        assert_eq!(
+            strip_bad_prefix("magick in.png -negate out.png").as_deref(),
+            Some("in.png -negate out.png")
+        );
+        assert_eq!(
+            strip_bad_prefix("magick convert in.png out.webp").as_deref(),
+            Some("in.png out.webp")
+        );
+        assert_eq!(
+            strip_bad_prefix("C:\\Tools\\Magick.exe in.png out.png").as_deref(),
+            Some("in.png out.png")
+        );
+        assert_eq!(strip_bad_prefix("/usr/bin/convert in.png out.png"), None);
+        assert_eq!(strip_bad_prefix("in.png -negate out.png"), None);
+        assert_eq!(strip_bad_prefix("identify -verbose in.png"), None);
+        assert_eq!(strip_bad_prefix("magick_logo.png out.png"), None);
+    }
 }
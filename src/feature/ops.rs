@@ -0,0 +1,122 @@
+mod annotate;
+mod app_icons;
+mod append;
+mod bench;
+mod border;
+mod canvas;
+mod caption;
+mod channels;
+mod collage;
+mod composite;
+mod convert;
+mod crop;
+mod density;
+mod document;
+mod estimate;
+mod favicon;
+mod filter;
+mod fonts;
+pub(crate) mod formats;
+mod frames;
+mod fx;
+mod heif;
+mod help_topic;
+mod histogram;
+pub(crate) mod identify;
+mod inline_preview;
+mod optimize;
+mod option_help;
+mod orient;
+mod output_settings;
+mod pdf_bundle;
+mod pdf_pages;
+pub(crate) mod phash;
+mod preview;
+mod redact;
+mod resize;
+mod snapshot;
+mod srcset;
+mod strict_check;
+mod strip_metadata;
+mod stylize;
+mod thumbnail;
+mod trim;
+mod verbose;
+mod visual_diff;
+mod watermark;
+
+use crate::feature::shell::ShellError;
+use thiserror::Error;
+
+pub use annotate::{Annotate, AnnotateOptions, DEFAULT_POINT_SIZE};
+pub use app_icons::{AppIconFile, AppIconOptions, AppIcons, IconPlatform};
+pub use append::{Append, AppendDirection, AppendOptions, AppendedImage};
+pub use bench::{Bench, BenchOptions, BenchReport, BenchResult};
+pub use border::{Border, BorderOptions, DropShadow};
+pub use canvas::{Canvas, CanvasFill, CanvasOptions};
+pub use caption::{CaptionOverlay, CaptionOverlayOptions};
+pub use channels::{ChannelColorspace, ChannelFile, Channels, RgbaChannel};
+pub use collage::{Collage, CollageCell, CollageFit, CollageLayout, CollageMaker, CollageOptions};
+pub use composite::{Composite, CompositeOptions};
+pub use convert::{Convert, ConvertOptions, ConvertedFile};
+pub use crop::{Crop, CropOptions, CropRect, CropUnit, CroppedImage};
+pub use density::{Density, DensityChange, DensityOptions, DensityUnits, PrintSize};
+pub use document::{CleanedDocument, ContrastStretch, DocumentCleanup, DocumentCleanupOptions};
+pub use estimate::{CostEstimate, EstimateOptions, EstimateVerdict, Estimator};
+pub use favicon::{DEFAULT_FAVICON_DIR, Favicon, FaviconFile, FaviconOptions};
+pub use filter::{Filter, FilterOperation, FilterOptions};
+pub use fonts::FontInfo;
+pub(crate) use fonts::list_fonts;
+pub use formats::ImageFormat;
+pub(crate) use formats::list_formats;
+pub use frames::{ExtractedFrame, Frames, FramesOptions};
+pub use fx::FxEvaluator;
+pub use heif::{HeifConversion, HeifConvert, HeifConvertOptions, HeifFormat, HeifSupport};
+pub use help_topic::{HelpTopic, HelpTopics};
+pub use histogram::{ChannelHistogram, DEFAULT_HISTOGRAM_BINS, Histogram, ImageHistogram};
+pub use identify::ImageDimensions;
+pub use inline_preview::{
+    DEFAULT_PREVIEW_BYTES, DEFAULT_PREVIEW_DIMENSION, InlinePreview, InlinePreviewOptions,
+    InlinePreviewer, PreviewFormat,
+};
+pub use optimize::{
+    DEFAULT_MIN_QUALITY, Optimize, OptimizeOptions, OptimizedImage, parse_byte_size,
+};
+pub use option_help::{ArgumentForm, OptionExample, OptionHelp, OptionHelper};
+pub use orient::{Orient, OrientOptions, OrientedImage};
+pub use output_settings::OutputSettings;
+pub use pdf_bundle::{DEFAULT_BUNDLE_DENSITY, PageSize, PdfBundle, PdfBundleOptions};
+pub use pdf_pages::{DEFAULT_PDF_DENSITY, PdfPages, PdfPagesOptions, RenderedPage};
+pub use preview::Previewer;
+pub use redact::{MIN_REDACT_STRENGTH, Redact, RedactOptions, RedactionMode, Region};
+pub use resize::{Resize, ResizeFit, ResizeOptions};
+pub use snapshot::{
+    DEFAULT_SNAPSHOT_THRESHOLD, SnapshotCheck, SnapshotCheckOptions, SnapshotStatus,
+    SnapshotUpdate, Snapshots,
+};
+pub use srcset::{
+    DEFAULT_SRCSET_WIDTHS, Srcset, SrcsetFile, SrcsetOptions, WebFormat, picture_html,
+};
+pub use strict_check::{StrictCheckOptions, StrictChecker, StrictConcern, StrictFinding};
+pub use strip_metadata::{StripMetadata, StripMetadataOptions, StrippedImage};
+pub use stylize::{Style, Stylize, StylizeOptions};
+pub use thumbnail::{DEFAULT_THUMBNAIL_SIZES, Thumbnail, ThumbnailFile, ThumbnailOptions};
+pub use trim::{TrimOptions, Trimmed, Trimmer};
+pub use verbose::VerboseIdentify;
+pub use visual_diff::{DiffMetric, VisualDiff, VisualDiffOptions, VisualDiffer};
+pub use watermark::{Watermark, WatermarkMark, WatermarkOptions};
+
+/// Error type for typed image operations
+#[derive(Debug, Error)]
+pub enum OperationError {
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+    #[error("Failed to parse ImageMagick output: {0}")]
+    ParseError(String),
+    #[error(transparent)]
+    Shell(#[from] ShellError),
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("{0}")]
+    MissingDependency(String),
+}
@@ -0,0 +1,224 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::caption::escape_caption_text;
+use crate::feature::ops::watermark::parse_gravity;
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// Point size text is drawn at when none is given
+pub const DEFAULT_POINT_SIZE: f64 = 24.0;
+
+/// Options for drawing text on an image
+#[derive(Debug, Clone)]
+pub struct AnnotateOptions {
+    /// Path to the source image
+    pub input: String,
+    /// Path to write the annotated image to
+    pub output: String,
+    /// Text to draw, kept exactly as given including spaces and newlines
+    pub text: String,
+    /// Edge or corner the position is measured from, e.g. `NorthWest`
+    pub gravity: String,
+    /// Horizontal distance in pixels from the gravity edge
+    pub x: i32,
+    /// Vertical distance in pixels from the gravity edge
+    pub y: i32,
+    /// Rotation of the text in degrees, clockwise
+    pub angle: f64,
+    /// Font name or path, uses ImageMagick's default when `None`
+    pub font: Option<String>,
+    /// Text size in points
+    pub point_size: f64,
+    /// Text fill color
+    pub fill: String,
+    /// Text outline color, no outline when `None`
+    pub stroke: Option<String>,
+    /// Outline width in pixels
+    pub stroke_width: f64,
+    /// Color of a box drawn behind the text, no box when `None`
+    pub background: Option<String>,
+}
+
+impl AnnotateOptions {
+    /// Create options drawing black text at the top left corner
+    pub fn new(input: &str, output: &str, text: &str) -> Self {
+        AnnotateOptions {
+            input: input.to_string(),
+            output: output.to_string(),
+            text: text.to_string(),
+            gravity: "NorthWest".to_string(),
+            x: 0,
+            y: 0,
+            angle: 0.0,
+            font: None,
+            point_size: DEFAULT_POINT_SIZE,
+            fill: "black".to_string(),
+            stroke: None,
+            stroke_width: 1.0,
+            background: None,
+        }
+    }
+}
+
+/// Operation which draws text on an image
+pub struct Annotate<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> Annotate<'a> {
+    /// Create a new Annotate with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Annotate {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Draw the text and write the output image
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output on success, or an `OperationError` on failure
+    pub fn run(&self, options: &AnnotateOptions) -> Result<ResolvedOutput, OperationError> {
+        let args = build_args(options)?;
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        Ok(self.magick_runner.last_resolved_output(&options.output))
+    }
+}
+
+/// Build the `magick` arguments drawing the text
+///
+/// The text is passed to `-annotate` as a single argument, so it never goes through the
+/// whitespace splitting of a command string.
+fn build_args(options: &AnnotateOptions) -> Result<Vec<String>, OperationError> {
+    if options.text.is_empty() {
+        return Err(OperationError::InvalidParameter(
+            "text must not be empty".to_string(),
+        ));
+    }
+    if options.point_size <= 0.0 {
+        return Err(OperationError::InvalidParameter(
+            "point_size must be greater than 0".to_string(),
+        ));
+    }
+    let gravity = parse_gravity(&options.gravity)?;
+
+    let mut args = vec![options.input.clone()];
+    if let Some(font) = &options.font {
+        args.extend(["-font".to_string(), font.clone()]);
+    }
+    args.extend([
+        "-pointsize".to_string(),
+        options.point_size.to_string(),
+        "-fill".to_string(),
+        options.fill.clone(),
+    ]);
+    if let Some(stroke) = &options.stroke {
+        args.extend([
+            "-stroke".to_string(),
+            stroke.clone(),
+            "-strokewidth".to_string(),
+            options.stroke_width.to_string(),
+        ]);
+    }
+    if let Some(background) = &options.background {
+        args.extend(["-undercolor".to_string(), background.clone()]);
+    }
+    args.extend([
+        "-gravity".to_string(),
+        gravity.to_string(),
+        "-annotate".to_string(),
+        format!(
+            "{angle}x{angle}{x:+}{y:+}",
+            angle = options.angle,
+            x = options.x,
+            y = options.y
+        ),
+        escape_caption_text(&options.text),
+        options.output.clone(),
+    ]);
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_args_defaults() {
+        let options = AnnotateOptions::new("in.png", "out.png", "Hello,  world");
+
+        assert_eq!(
+            build_args(&options).unwrap(),
+            vec![
+                "in.png",
+                "-pointsize",
+                "24",
+                "-fill",
+                "black",
+                "-gravity",
+                "NorthWest",
+                "-annotate",
+                "0x0+0+0",
+                "Hello,  world",
+                "out.png"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_args_styled() {
+        let mut options = AnnotateOptions::new("in.png", "out.png", "50% off");
+        options.gravity = "south".to_string();
+        options.x = -10;
+        options.y = 20;
+        options.angle = 15.0;
+        options.font = Some("DejaVu-Sans-Bold".to_string());
+        options.stroke = Some("white".to_string());
+        options.stroke_width = 2.5;
+        options.background = Some("#00000080".to_string());
+
+        let args = build_args(&options).unwrap();
+
+        assert_eq!(args[1..3], ["-font", "DejaVu-Sans-Bold"]);
+        assert!(
+            args.windows(4)
+                .any(|window| window == ["-stroke", "white", "-strokewidth", "2.5"])
+        );
+        assert!(
+            args.windows(2)
+                .any(|window| window == ["-undercolor", "#00000080"])
+        );
+        assert_eq!(
+            args[args.len() - 6..],
+            [
+                "-gravity",
+                "South",
+                "-annotate",
+                "15x15-10+20",
+                "50%% off",
+                "out.png"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        assert!(build_args(&AnnotateOptions::new("in.png", "out.png", "")).is_err());
+
+        let mut options = AnnotateOptions::new("in.png", "out.png", "text");
+        options.gravity = "middle".to_string();
+        assert!(build_args(&options).is_err());
+
+        let mut options = AnnotateOptions::new("in.png", "out.png", "text");
+        options.point_size = 0.0;
+        assert!(build_args(&options).is_err());
+    }
+}
@@ -0,0 +1,389 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::identify;
+use crate::feature::shell::CommandRunner;
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Smallest source accepted, matching the App Store marketing icon
+const MIN_SOURCE_SIZE: u32 = 1024;
+
+/// Default location of the iOS icon set, relative to the workspace
+pub const DEFAULT_IOS_DIR: &str = "ios/AppIcon.appiconset";
+/// Default location of the Android resource directory, relative to the workspace
+pub const DEFAULT_ANDROID_DIR: &str = "android/app/src/main/res";
+
+/// Every icon Xcode expects in a universal AppIcon set, as (size, points, scale, idiom)
+const IOS_ICONS: &[(&str, f64, u32, &str)] = &[
+    ("20x20", 20.0, 2, "iphone"),
+    ("20x20", 20.0, 3, "iphone"),
+    ("29x29", 29.0, 2, "iphone"),
+    ("29x29", 29.0, 3, "iphone"),
+    ("40x40", 40.0, 2, "iphone"),
+    ("40x40", 40.0, 3, "iphone"),
+    ("60x60", 60.0, 2, "iphone"),
+    ("60x60", 60.0, 3, "iphone"),
+    ("20x20", 20.0, 1, "ipad"),
+    ("20x20", 20.0, 2, "ipad"),
+    ("29x29", 29.0, 1, "ipad"),
+    ("29x29", 29.0, 2, "ipad"),
+    ("40x40", 40.0, 1, "ipad"),
+    ("40x40", 40.0, 2, "ipad"),
+    ("76x76", 76.0, 1, "ipad"),
+    ("76x76", 76.0, 2, "ipad"),
+    ("83.5x83.5", 83.5, 2, "ipad"),
+    ("1024x1024", 1024.0, 1, "ios-marketing"),
+];
+
+/// Android launcher icon densities and their pixel sizes
+const ANDROID_DENSITIES: &[(&str, u32)] = &[
+    ("mdpi", 48),
+    ("hdpi", 72),
+    ("xhdpi", 96),
+    ("xxhdpi", 144),
+    ("xxxhdpi", 192),
+];
+
+/// Platforms icons can be generated for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconPlatform {
+    Ios,
+    Android,
+    Both,
+}
+
+impl IconPlatform {
+    /// Parse a platform name
+    pub fn parse(name: &str) -> Result<Self, OperationError> {
+        match name.to_ascii_lowercase().as_str() {
+            "ios" => Ok(IconPlatform::Ios),
+            "android" => Ok(IconPlatform::Android),
+            "both" => Ok(IconPlatform::Both),
+            other => Err(OperationError::InvalidParameter(format!(
+                "unknown platform '{other}', expected ios, android, or both"
+            ))),
+        }
+    }
+
+    fn includes_ios(&self) -> bool {
+        matches!(self, IconPlatform::Ios | IconPlatform::Both)
+    }
+
+    fn includes_android(&self) -> bool {
+        matches!(self, IconPlatform::Android | IconPlatform::Both)
+    }
+}
+
+/// Options for generating app icons
+#[derive(Debug, Clone)]
+pub struct AppIconOptions {
+    /// Path to the square source image, at least 1024px
+    pub input: String,
+    /// Platforms to generate for
+    pub platform: IconPlatform,
+    /// The `AppIcon.appiconset` directory to write iOS icons into
+    pub ios_dir: String,
+    /// The Android `res` directory to write `mipmap-*` directories into
+    pub android_dir: String,
+}
+
+impl AppIconOptions {
+    /// Create options for both platforms using conventional project paths
+    pub fn new(input: &str) -> Self {
+        AppIconOptions {
+            input: input.to_string(),
+            platform: IconPlatform::Both,
+            ios_dir: DEFAULT_IOS_DIR.to_string(),
+            android_dir: DEFAULT_ANDROID_DIR.to_string(),
+        }
+    }
+}
+
+/// A file written by the icon generator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppIconFile {
+    pub path: PathBuf,
+    /// Pixel size of the icon, or `None` for metadata such as `Contents.json`
+    pub size: Option<u32>,
+}
+
+/// Operation which generates platform icon sets from one source image
+pub struct AppIcons<'a> {
+    magick_runner: MagickRunner<'a>,
+    base: PathBuf,
+}
+
+impl<'a> AppIcons<'a> {
+    /// Create a new AppIcons with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        AppIcons {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+            base: workspace
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(".")),
+        }
+    }
+
+    /// Generate the icon sets
+    ///
+    /// # Returns
+    ///
+    /// Returns every written file, or an `OperationError` on failure
+    pub fn run(&self, options: &AppIconOptions) -> Result<Vec<AppIconFile>, OperationError> {
+        let source = identify::dimensions(&self.magick_runner, &options.input)?;
+        if source.width != source.height {
+            return Err(OperationError::InvalidParameter(format!(
+                "source must be square, got {}x{}",
+                source.width, source.height
+            )));
+        }
+        if source.width < MIN_SOURCE_SIZE {
+            return Err(OperationError::InvalidParameter(format!(
+                "source must be at least {MIN_SOURCE_SIZE}px, got {}px",
+                source.width
+            )));
+        }
+
+        let mut files = Vec::new();
+        if options.platform.includes_ios() {
+            files.extend(self.generate_ios(&options.input, Path::new(&options.ios_dir))?);
+        }
+        if options.platform.includes_android() {
+            files.extend(self.generate_android(&options.input, Path::new(&options.android_dir))?);
+        }
+        Ok(files)
+    }
+
+    /// Run `magick` with owned arguments
+    fn execute(&self, args: Vec<String>) -> Result<(), OperationError> {
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        Ok(())
+    }
+
+    /// Write every iOS icon size and the `Contents.json` describing them
+    fn generate_ios(&self, input: &str, dir: &Path) -> Result<Vec<AppIconFile>, OperationError> {
+        let contents_path = dir.join("Contents.json");
+        self.magick_runner.check_write(&contents_path)?;
+        fs::create_dir_all(self.base.join(dir))?;
+        let mut files: Vec<AppIconFile> = Vec::new();
+        let mut images = Vec::new();
+        for (size, points, scale, idiom) in IOS_ICONS {
+            let pixels = (points * f64::from(*scale)).round() as u32;
+            let filename = format!("Icon-{pixels}.png");
+            let path = dir.join(&filename);
+            if !files.iter().any(|file| file.path == path) {
+                self.execute(ios_args(input, pixels, &path.to_string_lossy()))?;
+                files.push(AppIconFile {
+                    path,
+                    size: Some(pixels),
+                });
+            }
+            images.push(json!({
+                "size": size,
+                "idiom": idiom,
+                "filename": filename,
+                "scale": format!("{scale}x")
+            }));
+        }
+
+        let contents = json!({
+            "images": images,
+            "info": { "version": 1, "author": "xcode" }
+        });
+        let serialized = serde_json::to_string_pretty(&contents)
+            .map_err(|e| OperationError::ParseError(e.to_string()))?;
+        fs::write(self.base.join(&contents_path), serialized)?;
+        files.push(AppIconFile {
+            path: contents_path,
+            size: None,
+        });
+        Ok(files)
+    }
+
+    /// Write square and round launcher icons into each `mipmap-*` density directory
+    fn generate_android(
+        &self,
+        input: &str,
+        res_dir: &Path,
+    ) -> Result<Vec<AppIconFile>, OperationError> {
+        let mut files = Vec::new();
+        for (density, pixels) in ANDROID_DENSITIES {
+            let dir = res_dir.join(format!("mipmap-{density}"));
+            fs::create_dir_all(self.base.join(&dir))?;
+
+            let square = dir.join("ic_launcher.png");
+            self.execute(android_args(
+                input,
+                *pixels,
+                false,
+                &square.to_string_lossy(),
+            ))?;
+            files.push(AppIconFile {
+                path: square,
+                size: Some(*pixels),
+            });
+
+            let round = dir.join("ic_launcher_round.png");
+            self.execute(android_args(input, *pixels, true, &round.to_string_lossy()))?;
+            files.push(AppIconFile {
+                path: round,
+                size: Some(*pixels),
+            });
+        }
+        Ok(files)
+    }
+}
+
+/// Arguments for an iOS icon, flattened onto white because the App Store rejects alpha
+fn ios_args(input: &str, pixels: u32, output: &str) -> Vec<String> {
+    vec![
+        input.to_string(),
+        "-resize".to_string(),
+        format!("{pixels}x{pixels}"),
+        "-background".to_string(),
+        "white".to_string(),
+        "-alpha".to_string(),
+        "remove".to_string(),
+        "-alpha".to_string(),
+        "off".to_string(),
+        "-strip".to_string(),
+        output.to_string(),
+    ]
+}
+
+/// Arguments for an Android launcher icon, optionally masked to a circle
+fn android_args(input: &str, pixels: u32, round: bool, output: &str) -> Vec<String> {
+    let mut args = vec![
+        input.to_string(),
+        "-resize".to_string(),
+        format!("{pixels}x{pixels}"),
+    ];
+    if round {
+        let center = f64::from(pixels) / 2.0;
+        args.extend([
+            "(".to_string(),
+            "+clone".to_string(),
+            "-alpha".to_string(),
+            "transparent".to_string(),
+            "-fill".to_string(),
+            "white".to_string(),
+            "-draw".to_string(),
+            format!("circle {center},{center} {center},0"),
+            ")".to_string(),
+            "-compose".to_string(),
+            "DstIn".to_string(),
+            "-composite".to_string(),
+        ]);
+    }
+    args.extend(["-strip".to_string(), output.to_string()]);
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+    use tempfile::TempDir;
+
+    /// Mock implementation of CommandRunner reporting fixed dimensions and recording outputs
+    struct MockCommandRunner {
+        dimensions: String,
+        outputs: RefCell<Vec<String>>,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            if args[0] == "identify" {
+                return Ok(self.dimensions.clone());
+            }
+            self.outputs
+                .borrow_mut()
+                .push(args.last().unwrap().to_string());
+            Ok(String::new())
+        }
+    }
+
+    fn mock(dimensions: &str) -> MockCommandRunner {
+        MockCommandRunner {
+            dimensions: dimensions.to_string(),
+            outputs: RefCell::new(Vec::new()),
+        }
+    }
+
+    #[test]
+    fn test_ios_icons_and_contents() {
+        let dir = TempDir::new().unwrap();
+        let runner = mock("1024 1024");
+        let icons = AppIcons::new(&runner, Some(dir.path()));
+        let mut options = AppIconOptions::new("icon.png");
+        options.platform = IconPlatform::Ios;
+
+        let files = icons.run(&options).unwrap();
+
+        // 13 distinct pixel sizes plus Contents.json
+        assert_eq!(files.len(), 14);
+        assert_eq!(runner.outputs.borrow().len(), 13);
+        let contents: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(dir.path().join(DEFAULT_IOS_DIR).join("Contents.json")).unwrap(),
+        )
+        .unwrap();
+        let images = contents["images"].as_array().unwrap();
+        assert_eq!(images.len(), IOS_ICONS.len());
+        assert_eq!(images[16]["size"], "83.5x83.5");
+        assert_eq!(images[16]["filename"], "Icon-167.png");
+    }
+
+    #[test]
+    fn test_android_layout() {
+        let dir = TempDir::new().unwrap();
+        let runner = mock("2048 2048");
+        let icons = AppIcons::new(&runner, Some(dir.path()));
+        let mut options = AppIconOptions::new("icon.png");
+        options.platform = IconPlatform::Android;
+
+        let files = icons.run(&options).unwrap();
+
+        assert_eq!(files.len(), 10);
+        assert_eq!(
+            files[9].path,
+            PathBuf::from("android/app/src/main/res/mipmap-xxxhdpi/ic_launcher_round.png")
+        );
+        assert!(
+            dir.path()
+                .join("android/app/src/main/res/mipmap-mdpi")
+                .is_dir()
+        );
+    }
+
+    #[test]
+    fn test_rejects_non_square_or_small_source() {
+        let runner = mock("1024 768");
+        let icons = AppIcons::new(&runner, None);
+        assert!(matches!(
+            icons.run(&AppIconOptions::new("icon.png")),
+            Err(OperationError::InvalidParameter(_))
+        ));
+
+        let runner = mock("512 512");
+        let icons = AppIcons::new(&runner, None);
+        assert!(matches!(
+            icons.run(&AppIconOptions::new("icon.png")),
+            Err(OperationError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_round_icon_args() {
+        let args = android_args("icon.png", 48, true, "out.png");
+        assert!(args.contains(&"circle 24,24 24,0".to_string()));
+        assert_eq!(args.last().unwrap(), "out.png");
+    }
+}
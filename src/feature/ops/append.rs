@@ -0,0 +1,235 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::identify::{self, ImageDimensions};
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// Which way images are joined
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AppendDirection {
+    /// Side by side, left to right
+    #[default]
+    Horizontal,
+    /// Stacked, top to bottom
+    Vertical,
+}
+
+impl AppendDirection {
+    /// Parse a direction name
+    pub fn parse(name: &str) -> Result<Self, OperationError> {
+        match name {
+            "horizontal" => Ok(AppendDirection::Horizontal),
+            "vertical" => Ok(AppendDirection::Vertical),
+            other => Err(OperationError::InvalidParameter(format!(
+                "unknown direction '{other}', expected horizontal or vertical"
+            ))),
+        }
+    }
+}
+
+/// Options for joining images into one
+#[derive(Debug, Clone)]
+pub struct AppendOptions {
+    /// Paths to the images, in the order they are joined
+    pub inputs: Vec<String>,
+    /// Path to write the combined image to
+    pub output: String,
+    /// Which way the images are joined
+    pub direction: AppendDirection,
+    /// Pixels of background between neighboring images
+    pub spacing: u32,
+    /// Color of the spacing and of the area beside smaller images, ImageMagick's white by default
+    pub background: Option<String>,
+}
+
+impl AppendOptions {
+    /// Create options joining the images side by side without spacing
+    pub fn new(inputs: Vec<String>, output: &str) -> Self {
+        AppendOptions {
+            inputs,
+            output: output.to_string(),
+            direction: AppendDirection::Horizontal,
+            spacing: 0,
+            background: None,
+        }
+    }
+}
+
+/// The result of joining images
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppendedImage {
+    /// The written output
+    pub output: ResolvedOutput,
+    /// Dimensions of the combined image, `None` when writing it was skipped
+    pub dimensions: Option<ImageDimensions>,
+}
+
+/// Operation which joins images side by side or stacks them
+pub struct Append<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> Append<'a> {
+    /// Create a new Append with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Append {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Join the images, write the result, and measure it
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output with its dimensions, or an `OperationError` on failure
+    pub fn run(&self, options: &AppendOptions) -> Result<AppendedImage, OperationError> {
+        let args = build_args(options)?;
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        let output = self.magick_runner.last_resolved_output(&options.output);
+        let dimensions = if output.skipped {
+            None
+        } else {
+            Some(identify::dimensions(
+                &self.magick_runner,
+                &output.path.to_string_lossy(),
+            )?)
+        };
+        Ok(AppendedImage { output, dimensions })
+    }
+}
+
+/// Build the `magick` arguments joining the images
+///
+/// Spacing is added by splicing background onto the leading edge of every image and chopping
+/// it off the first one after joining, so it only appears between images.
+fn build_args(options: &AppendOptions) -> Result<Vec<String>, OperationError> {
+    if options.inputs.len() < 2 {
+        return Err(OperationError::InvalidParameter(
+            "at least two inputs are required".to_string(),
+        ));
+    }
+    let mut args = Vec::new();
+    if let Some(background) = &options.background {
+        args.extend(["-background".to_string(), background.clone()]);
+    }
+    args.extend(options.inputs.iter().cloned());
+    let (edge, append) = match options.direction {
+        AppendDirection::Horizontal => (format!("{}x0", options.spacing), "+append"),
+        AppendDirection::Vertical => (format!("0x{}", options.spacing), "-append"),
+    };
+    if options.spacing > 0 {
+        args.extend(["-splice".to_string(), edge.clone()]);
+    }
+    args.push(append.to_string());
+    if options.spacing > 0 {
+        args.extend(["-chop".to_string(), edge, "+repage".to_string()]);
+    }
+    args.push(options.output.clone());
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+
+    /// Reports 430x200 for every identify
+    struct MockCommandRunner {
+        calls: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl MockCommandRunner {
+        fn new() -> Self {
+            MockCommandRunner {
+                calls: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|arg| arg.to_string()).collect());
+            match args[0] {
+                "identify" => Ok("430 200".to_string()),
+                _ => Ok(String::new()),
+            }
+        }
+    }
+
+    fn inputs() -> Vec<String> {
+        vec!["a.png".to_string(), "b.png".to_string()]
+    }
+
+    #[test]
+    fn test_build_args() {
+        let options = AppendOptions::new(inputs(), "row.png");
+        assert_eq!(
+            build_args(&options).unwrap(),
+            ["a.png", "b.png", "+append", "row.png"]
+        );
+
+        let mut options = AppendOptions::new(inputs(), "column.png");
+        options.direction = AppendDirection::Vertical;
+        options.spacing = 10;
+        options.background = Some("none".to_string());
+        assert_eq!(
+            build_args(&options).unwrap(),
+            [
+                "-background",
+                "none",
+                "a.png",
+                "b.png",
+                "-splice",
+                "0x10",
+                "-append",
+                "-chop",
+                "0x10",
+                "+repage",
+                "column.png"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_run_reports_dimensions() {
+        let runner = MockCommandRunner::new();
+        let mut options = AppendOptions::new(inputs(), "row.png");
+        options.spacing = 30;
+
+        let appended = Append::new(&runner, None).run(&options).unwrap();
+
+        assert_eq!(appended.output, ResolvedOutput::written("row.png"));
+        assert_eq!(
+            appended.dimensions,
+            Some(ImageDimensions {
+                width: 430,
+                height: 200
+            })
+        );
+        assert_eq!(runner.calls.borrow()[1].last().unwrap(), "row.png[0]");
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        assert!(AppendDirection::parse("diagonal").is_err());
+
+        let options = AppendOptions::new(vec!["a.png".to_string()], "row.png");
+        assert!(build_args(&options).is_err());
+    }
+}
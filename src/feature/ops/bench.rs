@@ -0,0 +1,307 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::shell::{CommandRunner, ShellError};
+use crate::feature::temp_file::TempFile;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+/// Options for comparing encoder settings on one image
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    /// Path to the image to encode
+    pub input: String,
+    /// Formats to encode to, e.g. `webp`
+    pub formats: Vec<String>,
+    /// Qualities to encode each format at
+    pub qualities: Vec<u32>,
+}
+
+/// How one format and quality performed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BenchResult {
+    pub format: String,
+    pub quality: u32,
+    /// Size of the encoded file in bytes
+    pub bytes: u64,
+    /// Time taken to encode in milliseconds
+    pub elapsed_ms: u64,
+    /// Structural similarity to the original, where 1 is identical
+    pub ssim: f64,
+}
+
+/// Every result of comparing encoder settings
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BenchReport {
+    pub input: String,
+    /// Size of the input file in bytes
+    pub input_bytes: u64,
+    pub results: Vec<BenchResult>,
+}
+
+impl BenchReport {
+    /// Render the results as an aligned table, with sizes relative to the input
+    pub fn table(&self) -> String {
+        let mut table = format!(
+            "{:<8} {:>7} {:>12} {:>7} {:>9} {:>8}\n",
+            "format", "quality", "bytes", "size", "time_ms", "ssim"
+        );
+        for result in &self.results {
+            let ratio = if self.input_bytes == 0 {
+                0.0
+            } else {
+                result.bytes as f64 * 100.0 / self.input_bytes as f64
+            };
+            let _ = writeln!(
+                table,
+                "{:<8} {:>7} {:>12} {:>6.1}% {:>9} {:>8.4}",
+                result.format, result.quality, result.bytes, ratio, result.elapsed_ms, result.ssim
+            );
+        }
+        table
+    }
+}
+
+/// Operation which encodes an image at every combination of format and quality
+pub struct Bench<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> Bench<'a> {
+    /// Create a new Bench with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Bench {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Encode the matrix, measuring the time, size, and similarity of each encoding
+    ///
+    /// Encodings are written to temporary files which are removed afterwards.
+    ///
+    /// # Returns
+    ///
+    /// Returns the report on success, or an `OperationError` on failure
+    pub fn run(&self, options: &BenchOptions) -> Result<BenchReport, OperationError> {
+        if options.formats.is_empty() || options.qualities.is_empty() {
+            return Err(OperationError::InvalidParameter(
+                "at least one format and one quality are required".to_string(),
+            ));
+        }
+        if let Some(quality) = options.qualities.iter().find(|quality| **quality > 100) {
+            return Err(OperationError::InvalidParameter(format!(
+                "quality {quality} must be between 0 and 100"
+            )));
+        }
+        let input_bytes = fs::metadata(&options.input)?.len();
+
+        let mut results = Vec::new();
+        for format in &options.formats {
+            for quality in &options.qualities {
+                results.push(self.encode(&options.input, format, *quality)?);
+            }
+        }
+        Ok(BenchReport {
+            input: options.input.clone(),
+            input_bytes,
+            results,
+        })
+    }
+
+    fn encode(
+        &self,
+        input: &str,
+        format: &str,
+        quality: u32,
+    ) -> Result<BenchResult, OperationError> {
+        let encoded = TempFile::new(format)?;
+        let encoded_path = encoded.path().to_string_lossy().to_string();
+        let quality_arg = quality.to_string();
+
+        let started = Instant::now();
+        self.magick_runner
+            .execute_args(&[input, "-quality", &quality_arg, &encoded_path])?;
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+
+        Ok(BenchResult {
+            format: format.to_ascii_lowercase(),
+            quality,
+            bytes: fs::metadata(encoded.path())?.len(),
+            elapsed_ms,
+            ssim: ssim(&self.magick_runner, input, &encoded_path, "null:")?,
+        })
+    }
+}
+
+/// Measure the structural similarity of an image to the original, where 1 is identical
+///
+/// `compare` writes the metric to standard error and exits with 1 when the images differ at
+/// all, which is expected for lossy encodings, and with 0 when they are identical.
+///
+/// # Arguments
+///
+/// * `magick_runner` - Runner for the `compare` command
+/// * `original` - Path to the original image
+/// * `other` - Path to the image to measure
+/// * `diff` - Path to write an image highlighting the differences to, or `null:` for none
+pub(crate) fn ssim(
+    magick_runner: &MagickRunner,
+    original: &str,
+    other: &str,
+    diff: &str,
+) -> Result<f64, OperationError> {
+    let Some(stderr) = compare(magick_runner, &["-metric", "SSIM", original, other, diff])? else {
+        return Ok(1.0);
+    };
+    parse_metric(&stderr)
+        .ok_or_else(|| OperationError::ParseError(format!("unexpected compare output: {stderr}")))
+}
+
+/// Run `compare` with the given arguments
+///
+/// # Returns
+///
+/// Returns the metric output when the images differ, `None` when they are identical, or an
+/// `OperationError` if `compare` fails
+pub(crate) fn compare(
+    magick_runner: &MagickRunner,
+    args: &[&str],
+) -> Result<Option<String>, OperationError> {
+    let mut compare_args = vec!["compare"];
+    compare_args.extend_from_slice(args);
+    match magick_runner.execute_args(&compare_args) {
+        Ok(_) => Ok(None),
+        Err(ShellError::NonZeroExit {
+            exit_code: 1,
+            stderr,
+            ..
+        }) => Ok(Some(stderr)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Read the metric from `compare` output such as `0.9731` or `0.9731 (0.9731)`
+fn parse_metric(output: &str) -> Option<f64> {
+    output.split_whitespace().next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use tempfile::TempDir;
+
+    /// Writes encoded files and reports a fixed SSIM from `compare`
+    struct MockCommandRunner {
+        calls: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|arg| arg.to_string()).collect());
+            if args[0] == "compare" {
+                return Err(ShellError::NonZeroExit {
+                    exit_code: 1,
+                    command: command.to_string(),
+                    args: args.join(" "),
+                    stdout: String::new(),
+                    stderr: "0.9731 (0.9731)".to_string(),
+                });
+            }
+            let quality: usize = args[2].parse().unwrap();
+            fs::write(args[3], vec![0; quality]).unwrap();
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_bench_encodes_matrix() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("photo.jpg");
+        fs::write(&input, vec![0; 200]).unwrap();
+        let runner = MockCommandRunner {
+            calls: RefCell::new(Vec::new()),
+        };
+        let options = BenchOptions {
+            input: input.to_string_lossy().to_string(),
+            formats: vec!["webp".to_string(), "JPEG".to_string()],
+            qualities: vec![60, 90],
+        };
+
+        let report = Bench::new(&runner, None).run(&options).unwrap();
+
+        let matrix: Vec<(&str, u32, u64)> = report
+            .results
+            .iter()
+            .map(|result| (result.format.as_str(), result.quality, result.bytes))
+            .collect();
+        assert_eq!(
+            matrix,
+            vec![
+                ("webp", 60, 60),
+                ("webp", 90, 90),
+                ("jpeg", 60, 60),
+                ("jpeg", 90, 90)
+            ]
+        );
+        assert!(report.results.iter().all(|result| result.ssim == 0.9731));
+        assert_eq!(report.input_bytes, 200);
+        // Each encoding is compared against the original, then removed
+        let calls = runner.calls.borrow();
+        assert_eq!(calls.len(), 8);
+        assert_eq!(calls[1][..3], ["compare", "-metric", "SSIM"]);
+        assert!(!Path::new(&calls[0][3]).exists());
+    }
+
+    #[test]
+    fn test_report_table() {
+        let report = BenchReport {
+            input: "photo.jpg".to_string(),
+            input_bytes: 1000,
+            results: vec![BenchResult {
+                format: "webp".to_string(),
+                quality: 75,
+                bytes: 250,
+                elapsed_ms: 12,
+                ssim: 0.98765,
+            }],
+        };
+
+        let table = report.table();
+
+        let row = table.lines().nth(1).unwrap();
+        assert_eq!(
+            row.split_whitespace().collect::<Vec<_>>(),
+            ["webp", "75", "250", "25.0%", "12", "0.9877"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        let runner = MockCommandRunner {
+            calls: RefCell::new(Vec::new()),
+        };
+        let bench = Bench::new(&runner, None);
+        let options = BenchOptions {
+            input: "photo.jpg".to_string(),
+            formats: vec!["webp".to_string()],
+            qualities: vec![],
+        };
+        assert!(bench.run(&options).is_err());
+
+        let options = BenchOptions {
+            qualities: vec![101],
+            ..options
+        };
+        assert!(bench.run(&options).is_err());
+    }
+}
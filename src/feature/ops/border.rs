@@ -0,0 +1,287 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// A drop shadow cast behind an image
+#[derive(Debug, Clone, PartialEq)]
+pub struct DropShadow {
+    /// Shadow color
+    pub color: String,
+    /// Shadow opacity from 0 (invisible) to 100 (opaque)
+    pub opacity: u32,
+    /// Blur radius of the shadow's edge, as a Gaussian sigma in pixels
+    pub blur: f64,
+    /// Horizontal distance in pixels the shadow is moved right
+    pub x: i32,
+    /// Vertical distance in pixels the shadow is moved down
+    pub y: i32,
+}
+
+impl Default for DropShadow {
+    /// A soft black shadow down and to the right
+    fn default() -> Self {
+        DropShadow {
+            color: "black".to_string(),
+            opacity: 60,
+            blur: 4.0,
+            x: 6,
+            y: 6,
+        }
+    }
+}
+
+/// Options for adding a border, frame, rounded corners, or drop shadow to an image
+///
+/// The effects are applied in the order corners, border, frame, shadow, so a border follows
+/// the rounded outline's box and the shadow falls behind everything else.
+#[derive(Debug, Clone)]
+pub struct BorderOptions {
+    /// Path to the source image
+    pub input: String,
+    /// Path to write the result to, which needs a format with transparency for rounded
+    /// corners and shadows
+    pub output: String,
+    /// Radius in pixels to round the corners to
+    pub corner_radius: Option<u32>,
+    /// Width in pixels of a solid border, no border when 0
+    pub border_width: u32,
+    /// Color of the solid border
+    pub border_color: String,
+    /// Width in pixels of a bevelled frame, no frame when 0
+    pub frame_width: u32,
+    /// Color of the bevelled frame
+    pub frame_color: String,
+    /// Shadow cast behind the image
+    pub shadow: Option<DropShadow>,
+}
+
+impl BorderOptions {
+    /// Create options which change nothing until an effect is set
+    pub fn new(input: &str, output: &str) -> Self {
+        BorderOptions {
+            input: input.to_string(),
+            output: output.to_string(),
+            corner_radius: None,
+            border_width: 0,
+            border_color: "black".to_string(),
+            frame_width: 0,
+            frame_color: "gray".to_string(),
+            shadow: None,
+        }
+    }
+}
+
+/// Operation which adds borders, frames, rounded corners, and drop shadows to an image
+pub struct Border<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> Border<'a> {
+    /// Create a new Border with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Border {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Apply the effects and write the result
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output on success, or an `OperationError` on failure
+    pub fn run(&self, options: &BorderOptions) -> Result<ResolvedOutput, OperationError> {
+        let args = build_args(options)?;
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        Ok(self.magick_runner.last_resolved_output(&options.output))
+    }
+}
+
+/// Build the `magick` arguments applying the effects
+///
+/// Rounded corners use the usual mask recipe: a quarter circle drawn into one corner of the
+/// image's opacity mask is mirrored into the other three by flipping and multiplying, then
+/// copied back as the alpha channel.
+fn build_args(options: &BorderOptions) -> Result<Vec<String>, OperationError> {
+    let radius = options.corner_radius.filter(|radius| *radius > 0);
+    if radius.is_none()
+        && options.border_width == 0
+        && options.frame_width == 0
+        && options.shadow.is_none()
+    {
+        return Err(OperationError::InvalidParameter(
+            "at least one of corner_radius, border_width, frame_width, or shadow is required"
+                .to_string(),
+        ));
+    }
+
+    let mut args = vec![options.input.clone()];
+    if let Some(radius) = radius {
+        args.extend(
+            [
+                "(",
+                "+clone",
+                "-alpha",
+                "extract",
+                "-draw",
+                &format!("fill black polygon 0,0 0,{radius} {radius},0 fill white circle {radius},{radius} {radius},0"),
+                "(",
+                "+clone",
+                "-flip",
+                ")",
+                "-compose",
+                "Multiply",
+                "-composite",
+                "(",
+                "+clone",
+                "-flop",
+                ")",
+                "-compose",
+                "Multiply",
+                "-composite",
+                ")",
+                "-alpha",
+                "off",
+                "-compose",
+                "CopyOpacity",
+                "-composite",
+                "-compose",
+                "Over",
+            ]
+            .map(str::to_string),
+        );
+    }
+    if options.border_width > 0 {
+        args.extend([
+            "-bordercolor".to_string(),
+            options.border_color.clone(),
+            "-border".to_string(),
+            options.border_width.to_string(),
+        ]);
+    }
+    if options.frame_width > 0 {
+        let bevel = (options.frame_width / 3).max(1);
+        args.extend([
+            "-mattecolor".to_string(),
+            options.frame_color.clone(),
+            "-frame".to_string(),
+            format!(
+                "{width}x{width}+{bevel}+{bevel}",
+                width = options.frame_width
+            ),
+        ]);
+    }
+    if let Some(shadow) = &options.shadow {
+        if shadow.opacity > 100 {
+            return Err(OperationError::InvalidParameter(format!(
+                "shadow opacity {} must be between 0 and 100",
+                shadow.opacity
+            )));
+        }
+        args.extend([
+            "(".to_string(),
+            "+clone".to_string(),
+            "-background".to_string(),
+            shadow.color.clone(),
+            "-shadow".to_string(),
+            format!(
+                "{}x{}{:+}{:+}",
+                shadow.opacity, shadow.blur, shadow.x, shadow.y
+            ),
+            ")".to_string(),
+            "+swap".to_string(),
+            "-background".to_string(),
+            "none".to_string(),
+            "-layers".to_string(),
+            "merge".to_string(),
+        ]);
+    }
+    args.extend(["+repage".to_string(), options.output.clone()]);
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_border_and_frame_args() {
+        let mut options = BorderOptions::new("in.jpg", "out.jpg");
+        options.border_width = 10;
+        options.border_color = "white".to_string();
+        options.frame_width = 12;
+
+        assert_eq!(
+            build_args(&options).unwrap(),
+            vec![
+                "in.jpg",
+                "-bordercolor",
+                "white",
+                "-border",
+                "10",
+                "-mattecolor",
+                "gray",
+                "-frame",
+                "12x12+4+4",
+                "+repage",
+                "out.jpg"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rounded_corners_and_shadow_args() {
+        let mut options = BorderOptions::new("in.png", "out.png");
+        options.corner_radius = Some(20);
+        options.shadow = Some(DropShadow::default());
+
+        let args = build_args(&options).unwrap();
+
+        assert!(args.contains(
+            &"fill black polygon 0,0 0,20 20,0 fill white circle 20,20 20,0".to_string()
+        ));
+        assert!(
+            args.windows(2)
+                .any(|window| window == ["-compose", "CopyOpacity"])
+        );
+        assert_eq!(
+            args[args.len() - 14..],
+            [
+                "(",
+                "+clone",
+                "-background",
+                "black",
+                "-shadow",
+                "60x4+6+6",
+                ")",
+                "+swap",
+                "-background",
+                "none",
+                "-layers",
+                "merge",
+                "+repage",
+                "out.png"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        assert!(build_args(&BorderOptions::new("in.png", "out.png")).is_err());
+
+        let mut options = BorderOptions::new("in.png", "out.png");
+        options.shadow = Some(DropShadow {
+            opacity: 150,
+            ..DropShadow::default()
+        });
+        assert!(build_args(&options).is_err());
+    }
+}
@@ -0,0 +1,370 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// Largest width or height of a generated canvas
+const MAX_CANVAS_DIMENSION: u32 = 16384;
+
+/// Built-in ImageMagick patterns
+const PATTERNS: &[&str] = &[
+    "bricks",
+    "checkerboard",
+    "circles",
+    "crosshatch",
+    "crosshatch30",
+    "crosshatch45",
+    "fishscales",
+    "hexagons",
+    "horizontal",
+    "horizontal2",
+    "horizontal3",
+    "horizontalsaw",
+    "hs_bdiagonal",
+    "hs_cross",
+    "hs_diagcross",
+    "hs_fdiagonal",
+    "hs_horizontal",
+    "hs_vertical",
+    "left30",
+    "left45",
+    "leftshingle",
+    "octagons",
+    "right30",
+    "right45",
+    "rightshingle",
+    "smallfishscales",
+    "vertical",
+    "vertical2",
+    "vertical3",
+    "verticalbricks",
+    "verticalleftshingle",
+    "verticalrightshingle",
+    "verticalsaw",
+];
+
+/// What a new image is filled with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CanvasFill {
+    /// A single color
+    Solid,
+    /// A linear gradient between two colors
+    Gradient,
+    /// A radial gradient from the center outwards
+    RadialGradient,
+    /// Random fractal clouds
+    Plasma,
+    /// A tiled built-in pattern such as `checkerboard`
+    Pattern,
+}
+
+impl CanvasFill {
+    /// Parse a fill name
+    pub fn parse(name: &str) -> Result<Self, OperationError> {
+        match name {
+            "solid" => Ok(CanvasFill::Solid),
+            "gradient" => Ok(CanvasFill::Gradient),
+            "radial_gradient" => Ok(CanvasFill::RadialGradient),
+            "plasma" => Ok(CanvasFill::Plasma),
+            "pattern" => Ok(CanvasFill::Pattern),
+            other => Err(OperationError::InvalidParameter(format!(
+                "unknown fill '{other}', expected solid, gradient, radial_gradient, plasma, or pattern"
+            ))),
+        }
+    }
+}
+
+/// Options for generating a new image
+#[derive(Debug, Clone)]
+pub struct CanvasOptions {
+    /// Path to write the image to
+    pub output: String,
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// What the image is filled with
+    pub fill: CanvasFill,
+    /// Color of a solid fill, defaults to white
+    pub color: Option<String>,
+    /// Start color of a gradient or plasma, or the color of a pattern's black
+    pub from: Option<String>,
+    /// End color of a gradient or plasma, or the color of a pattern's white
+    pub to: Option<String>,
+    /// Direction of a linear gradient in degrees, where 0 runs top to bottom
+    pub angle: Option<f64>,
+    /// Name of the pattern, e.g. `checkerboard`
+    pub pattern: Option<String>,
+    /// Seed making plasma reproducible
+    pub seed: Option<u32>,
+}
+
+impl CanvasOptions {
+    /// Create options for a canvas of the given size and fill
+    pub fn new(output: &str, width: u32, height: u32, fill: CanvasFill) -> Self {
+        CanvasOptions {
+            output: output.to_string(),
+            width,
+            height,
+            fill,
+            color: None,
+            from: None,
+            to: None,
+            angle: None,
+            pattern: None,
+            seed: None,
+        }
+    }
+}
+
+/// Operation which generates new images from ImageMagick's pseudo-image sources
+pub struct Canvas<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> Canvas<'a> {
+    /// Create a new Canvas with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Canvas {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Generate the image and write it
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output on success, or an `OperationError` on failure
+    pub fn run(&self, options: &CanvasOptions) -> Result<ResolvedOutput, OperationError> {
+        let args = build_args(options)?;
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        Ok(self.magick_runner.last_resolved_output(&options.output))
+    }
+}
+
+/// Build the `magick` arguments generating the image
+///
+/// Solid fills use `xc:`, gradients `gradient:` and `radial-gradient:`, plasma `plasma:`, and
+/// patterns `pattern:`, whose black and white are remapped with `+level-colors` when colors are
+/// given.
+fn build_args(options: &CanvasOptions) -> Result<Vec<String>, OperationError> {
+    let dimensions = 1..=MAX_CANVAS_DIMENSION;
+    if !dimensions.contains(&options.width) || !dimensions.contains(&options.height) {
+        return Err(OperationError::InvalidParameter(format!(
+            "width and height must be between 1 and {MAX_CANVAS_DIMENSION}"
+        )));
+    }
+    let reject = |given: bool, name: &str, fills: &str| {
+        if given {
+            Err(OperationError::InvalidParameter(format!(
+                "{name} only applies to {fills}"
+            )))
+        } else {
+            Ok(())
+        }
+    };
+    let fill = options.fill;
+    reject(
+        options.color.is_some() && fill != CanvasFill::Solid,
+        "color",
+        "solid",
+    )?;
+    reject(
+        (options.from.is_some() || options.to.is_some()) && fill == CanvasFill::Solid,
+        "from and to",
+        "gradient, radial_gradient, plasma, and pattern",
+    )?;
+    reject(
+        options.angle.is_some() && fill != CanvasFill::Gradient,
+        "angle",
+        "gradient",
+    )?;
+    reject(
+        options.seed.is_some() && fill != CanvasFill::Plasma,
+        "seed",
+        "plasma",
+    )?;
+    reject(
+        options.pattern.is_some() && fill != CanvasFill::Pattern,
+        "pattern",
+        "pattern",
+    )?;
+
+    let mut args = vec![
+        "-size".to_string(),
+        format!("{}x{}", options.width, options.height),
+    ];
+    match fill {
+        CanvasFill::Solid => {
+            args.push(format!(
+                "xc:{}",
+                options.color.as_deref().unwrap_or("white")
+            ));
+        }
+        CanvasFill::Gradient => {
+            if let Some(angle) = options.angle {
+                args.extend(["-define".to_string(), format!("gradient:angle={angle}")]);
+            }
+            args.push(format!(
+                "gradient:{}",
+                color_range(options, "white", "black")?
+            ));
+        }
+        CanvasFill::RadialGradient => {
+            args.push(format!(
+                "radial-gradient:{}",
+                color_range(options, "white", "black")?
+            ));
+        }
+        CanvasFill::Plasma => {
+            if let Some(seed) = options.seed {
+                args.extend(["-seed".to_string(), seed.to_string()]);
+            }
+            if options.from.is_none() && options.to.is_none() {
+                args.push("plasma:fractal".to_string());
+            } else {
+                args.push(format!(
+                    "plasma:{}",
+                    color_range(options, "white", "black")?
+                ));
+            }
+        }
+        CanvasFill::Pattern => {
+            let pattern = options.pattern.as_deref().ok_or_else(|| {
+                OperationError::InvalidParameter("pattern fill requires a pattern".to_string())
+            })?;
+            if !PATTERNS.contains(&pattern.to_ascii_lowercase().as_str()) {
+                return Err(OperationError::InvalidParameter(format!(
+                    "unknown pattern '{pattern}', expected one of: {}",
+                    PATTERNS.join(", ")
+                )));
+            }
+            args.push(format!("pattern:{}", pattern.to_ascii_lowercase()));
+            if options.from.is_some() || options.to.is_some() {
+                args.extend([
+                    "+level-colors".to_string(),
+                    format!(
+                        "{},{}",
+                        options.from.as_deref().unwrap_or("black"),
+                        options.to.as_deref().unwrap_or("white")
+                    ),
+                ]);
+            }
+        }
+    }
+    args.push(options.output.clone());
+    Ok(args)
+}
+
+/// The `from-to` colors of a gradient or plasma, with defaults for missing ends
+///
+/// The colors are joined with `-`, so a color containing one would be misread.
+fn color_range(options: &CanvasOptions, from: &str, to: &str) -> Result<String, OperationError> {
+    let from = options.from.as_deref().unwrap_or(from);
+    let to = options.to.as_deref().unwrap_or(to);
+    if from.contains('-') || to.contains('-') {
+        return Err(OperationError::InvalidParameter(format!(
+            "colors '{from}' and '{to}' may not contain '-'"
+        )));
+    }
+    Ok(format!("{from}-{to}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(fill: CanvasFill) -> CanvasOptions {
+        CanvasOptions::new("out.png", 640, 480, fill)
+    }
+
+    #[test]
+    fn test_build_args_for_each_fill() {
+        let mut solid = options(CanvasFill::Solid);
+        solid.color = Some("#336699".to_string());
+        assert_eq!(
+            build_args(&solid).unwrap(),
+            ["-size", "640x480", "xc:#336699", "out.png"]
+        );
+
+        let mut gradient = options(CanvasFill::Gradient);
+        gradient.from = Some("navy".to_string());
+        gradient.to = Some("skyblue".to_string());
+        gradient.angle = Some(90.0);
+        assert_eq!(
+            build_args(&gradient).unwrap(),
+            [
+                "-size",
+                "640x480",
+                "-define",
+                "gradient:angle=90",
+                "gradient:navy-skyblue",
+                "out.png"
+            ]
+        );
+
+        assert_eq!(
+            build_args(&options(CanvasFill::RadialGradient)).unwrap()[2],
+            "radial-gradient:white-black"
+        );
+
+        let mut plasma = options(CanvasFill::Plasma);
+        plasma.seed = Some(7);
+        assert_eq!(
+            build_args(&plasma).unwrap(),
+            [
+                "-size",
+                "640x480",
+                "-seed",
+                "7",
+                "plasma:fractal",
+                "out.png"
+            ]
+        );
+
+        let mut pattern = options(CanvasFill::Pattern);
+        pattern.pattern = Some("Checkerboard".to_string());
+        pattern.from = Some("gray80".to_string());
+        assert_eq!(
+            build_args(&pattern).unwrap(),
+            [
+                "-size",
+                "640x480",
+                "pattern:checkerboard",
+                "+level-colors",
+                "gray80,white",
+                "out.png"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        assert!(CanvasFill::parse("noise").is_err());
+
+        let zero = CanvasOptions::new("out.png", 0, 480, CanvasFill::Solid);
+        assert!(build_args(&zero).is_err());
+
+        let mut color = options(CanvasFill::Gradient);
+        color.color = Some("red".to_string());
+        assert!(build_args(&color).is_err());
+
+        assert!(build_args(&options(CanvasFill::Pattern)).is_err());
+
+        let mut pattern = options(CanvasFill::Pattern);
+        pattern.pattern = Some("stripes".to_string());
+        assert!(build_args(&pattern).is_err());
+
+        let mut dashed = options(CanvasFill::Gradient);
+        dashed.from = Some("rgb(0,0,0)-".to_string());
+        assert!(build_args(&dashed).is_err());
+    }
+}
@@ -0,0 +1,209 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::identify::{self, ImageDimensions};
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// Fraction of the image width available to each caption band
+const CAPTION_WIDTH_RATIO: f64 = 0.9;
+/// Fraction of the image height available to each caption band
+const CAPTION_HEIGHT_RATIO: f64 = 0.2;
+/// Fraction of the image height left between a caption band and the image edge
+const CAPTION_MARGIN_RATIO: f64 = 0.02;
+
+/// Options for placing top/bottom caption text on an image
+#[derive(Debug, Clone)]
+pub struct CaptionOverlayOptions {
+    /// Path to the source image
+    pub input: String,
+    /// Path to write the captioned image to
+    pub output: String,
+    /// Text placed along the top edge
+    pub top_text: Option<String>,
+    /// Text placed along the bottom edge
+    pub bottom_text: Option<String>,
+    /// Font name or path, uses ImageMagick's default when `None`
+    pub font: Option<String>,
+    /// Text fill color
+    pub fill: String,
+    /// Text outline color
+    pub stroke: String,
+    /// Outline width in pixels, derived from the image size when `None`
+    pub stroke_width: Option<f64>,
+}
+
+impl CaptionOverlayOptions {
+    /// Create options with white text and a black outline
+    pub fn new(input: impl Into<String>, output: impl Into<String>) -> Self {
+        CaptionOverlayOptions {
+            input: input.into(),
+            output: output.into(),
+            top_text: None,
+            bottom_text: None,
+            font: None,
+            fill: "white".to_string(),
+            stroke: "black".to_string(),
+            stroke_width: None,
+        }
+    }
+}
+
+/// Operation which overlays meme-style captions on an image
+pub struct CaptionOverlay<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> CaptionOverlay<'a> {
+    /// Create a new CaptionOverlay with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        CaptionOverlay {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Render the captions and write the output image
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output on success, or an `OperationError` on failure
+    pub fn run(&self, options: &CaptionOverlayOptions) -> Result<ResolvedOutput, OperationError> {
+        if options.top_text.is_none() && options.bottom_text.is_none() {
+            return Err(OperationError::InvalidParameter(
+                "at least one of top_text or bottom_text is required".to_string(),
+            ));
+        }
+        let dims = identify::dimensions(&self.magick_runner, &options.input)?;
+        let args = build_args(options, dims);
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        Ok(self.magick_runner.last_resolved_output(&options.output))
+    }
+}
+
+/// Build the `magick` arguments for a caption overlay
+///
+/// Each caption is rendered with `caption:` into a fixed-size band so ImageMagick wraps the
+/// text and picks the largest point size that fits, then composited onto the image.
+fn build_args(options: &CaptionOverlayOptions, dims: ImageDimensions) -> Vec<String> {
+    let band_width = scaled(dims.width, CAPTION_WIDTH_RATIO);
+    let band_height = scaled(dims.height, CAPTION_HEIGHT_RATIO);
+    let margin = (f64::from(dims.height) * CAPTION_MARGIN_RATIO).round() as u32;
+    let stroke_width = options
+        .stroke_width
+        .unwrap_or_else(|| (f64::from(dims.width.min(dims.height)) / 250.0).max(1.0));
+
+    let mut args = vec![options.input.clone()];
+    let captions = [
+        (options.top_text.as_deref(), "north"),
+        (options.bottom_text.as_deref(), "south"),
+    ];
+    for (text, gravity) in captions {
+        let Some(text) = text else { continue };
+        args.push("(".to_string());
+        args.extend([
+            "-size".to_string(),
+            format!("{band_width}x{band_height}"),
+            "-background".to_string(),
+            "none".to_string(),
+        ]);
+        if let Some(font) = &options.font {
+            args.extend(["-font".to_string(), font.clone()]);
+        }
+        args.extend([
+            "-fill".to_string(),
+            options.fill.clone(),
+            "-stroke".to_string(),
+            options.stroke.clone(),
+            "-strokewidth".to_string(),
+            format!("{stroke_width}"),
+            "-gravity".to_string(),
+            "center".to_string(),
+            format!("caption:{}", escape_caption_text(text)),
+            ")".to_string(),
+            "-gravity".to_string(),
+            gravity.to_string(),
+            "-geometry".to_string(),
+            format!("+0+{margin}"),
+            "-composite".to_string(),
+        ]);
+    }
+    args.push(options.output.clone());
+    args
+}
+
+/// Scale a pixel length by a ratio, never returning less than one pixel
+fn scaled(length: u32, ratio: f64) -> u32 {
+    ((f64::from(length) * ratio).round() as u32).max(1)
+}
+
+/// Escape text so ImageMagick renders it literally
+///
+/// A leading `@` would make ImageMagick read the text from a file and `%` starts a format escape.
+pub(crate) fn escape_caption_text(text: &str) -> String {
+    let escaped = text.replace('%', "%%");
+    match escaped.strip_prefix('@') {
+        Some(rest) => format!("\\@{rest}"),
+        None => escaped,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dims() -> ImageDimensions {
+        ImageDimensions {
+            width: 1000,
+            height: 500,
+        }
+    }
+
+    #[test]
+    fn test_build_args_top_and_bottom() {
+        let mut options = CaptionOverlayOptions::new("in.png", "out.png");
+        options.top_text = Some("one does not simply".to_string());
+        options.bottom_text = Some("caption images".to_string());
+
+        let args = build_args(&options, dims());
+
+        assert_eq!(args.first().unwrap(), "in.png");
+        assert_eq!(args.last().unwrap(), "out.png");
+        assert!(args.contains(&"caption:one does not simply".to_string()));
+        assert!(args.contains(&"caption:caption images".to_string()));
+        assert!(args.contains(&"900x100".to_string()));
+        assert!(args.contains(&"north".to_string()));
+        assert!(args.contains(&"south".to_string()));
+        assert!(args.contains(&"+0+10".to_string()));
+        assert_eq!(args.iter().filter(|a| *a == "-composite").count(), 2);
+    }
+
+    #[test]
+    fn test_build_args_with_font_and_stroke() {
+        let mut options = CaptionOverlayOptions::new("in.png", "out.png");
+        options.bottom_text = Some("bottom".to_string());
+        options.font = Some("Impact".to_string());
+        options.stroke_width = Some(4.0);
+
+        let args = build_args(&options, dims());
+
+        let font_index = args.iter().position(|a| a == "-font").unwrap();
+        assert_eq!(args[font_index + 1], "Impact");
+        let stroke_index = args.iter().position(|a| a == "-strokewidth").unwrap();
+        assert_eq!(args[stroke_index + 1], "4");
+        assert!(!args.contains(&"north".to_string()));
+    }
+
+    #[test]
+    fn test_escape_caption_text() {
+        assert_eq!(escape_caption_text("100% done"), "100%% done");
+        assert_eq!(escape_caption_text("@secrets.txt"), "\\@secrets.txt");
+        assert_eq!(escape_caption_text("plain"), "plain");
+    }
+}
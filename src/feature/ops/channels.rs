@@ -0,0 +1,605 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// Colorspaces whose channels can be split and recombined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelColorspace {
+    Srgb,
+    Cmyk,
+    Lab,
+    Hsl,
+    Ycbcr,
+}
+
+impl ChannelColorspace {
+    /// Parse a colorspace name, case-insensitively
+    pub fn parse(name: &str) -> Result<Self, OperationError> {
+        match name.to_ascii_lowercase().as_str() {
+            "srgb" | "rgb" => Ok(ChannelColorspace::Srgb),
+            "cmyk" => Ok(ChannelColorspace::Cmyk),
+            "lab" => Ok(ChannelColorspace::Lab),
+            "hsl" => Ok(ChannelColorspace::Hsl),
+            "ycbcr" => Ok(ChannelColorspace::Ycbcr),
+            other => Err(OperationError::InvalidParameter(format!(
+                "unsupported colorspace '{other}', expected one of sRGB, CMYK, Lab, HSL, YCbCr"
+            ))),
+        }
+    }
+
+    /// The ImageMagick name of the colorspace
+    pub fn magick_name(&self) -> &'static str {
+        match self {
+            ChannelColorspace::Srgb => "sRGB",
+            ChannelColorspace::Cmyk => "CMYK",
+            ChannelColorspace::Lab => "Lab",
+            ChannelColorspace::Hsl => "HSL",
+            ChannelColorspace::Ycbcr => "YCbCr",
+        }
+    }
+
+    /// Names of the color channels, in the order ImageMagick separates them
+    pub fn channel_names(&self) -> &'static [&'static str] {
+        match self {
+            ChannelColorspace::Srgb => &["red", "green", "blue"],
+            ChannelColorspace::Cmyk => &["cyan", "magenta", "yellow", "black"],
+            ChannelColorspace::Lab => &["lightness", "a", "b"],
+            ChannelColorspace::Hsl => &["hue", "saturation", "lightness"],
+            ChannelColorspace::Ycbcr => &["luma", "blue_difference", "red_difference"],
+        }
+    }
+
+    /// The `-channel` selector of each color channel, in the order of `channel_names`
+    ///
+    /// ImageMagick names the first three channels of every colorspace but CMYK red, green,
+    /// and blue.
+    fn channel_selectors(&self) -> &'static [&'static str] {
+        match self {
+            ChannelColorspace::Cmyk => &["C", "M", "Y", "K"],
+            _ => &["R", "G", "B"],
+        }
+    }
+}
+
+/// A channel of an RGBA image, for extracting or swapping single channels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RgbaChannel {
+    Red,
+    Green,
+    Blue,
+    Alpha,
+}
+
+impl RgbaChannel {
+    /// Every channel, in the order they are extracted by default
+    pub const ALL: [RgbaChannel; 4] = [
+        RgbaChannel::Red,
+        RgbaChannel::Green,
+        RgbaChannel::Blue,
+        RgbaChannel::Alpha,
+    ];
+
+    /// Parse a channel name, case-insensitively
+    pub fn parse(name: &str) -> Result<Self, OperationError> {
+        match name.to_ascii_lowercase().as_str() {
+            "red" | "r" => Ok(RgbaChannel::Red),
+            "green" | "g" => Ok(RgbaChannel::Green),
+            "blue" | "b" => Ok(RgbaChannel::Blue),
+            "alpha" | "a" => Ok(RgbaChannel::Alpha),
+            other => Err(OperationError::InvalidParameter(format!(
+                "unknown channel '{other}', expected red, green, blue, or alpha"
+            ))),
+        }
+    }
+
+    /// The channel's name, as ImageMagick's channel expressions and the results use it
+    pub fn name(self) -> &'static str {
+        match self {
+            RgbaChannel::Red => "red",
+            RgbaChannel::Green => "green",
+            RgbaChannel::Blue => "blue",
+            RgbaChannel::Alpha => "alpha",
+        }
+    }
+}
+
+/// A single channel file produced by a split
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelFile {
+    pub channel: String,
+    pub path: String,
+}
+
+/// Operation which separates and recombines image channels
+pub struct Channels<'a> {
+    magick_runner: MagickRunner<'a>,
+    workspace: Option<&'a Path>,
+}
+
+impl<'a> Channels<'a> {
+    /// Create a new Channels with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Channels {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+            workspace,
+        }
+    }
+
+    /// Use the provided policy for the output files
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Separate an image into one grayscale file per channel
+    ///
+    /// Each channel is written by its own command, so the output policy applies to each file.
+    /// Alpha is extracted as a mask, opaque pixels white, so images without transparency give
+    /// a white file.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Path to the source image
+    /// * `colorspace` - Colorspace to convert to before separating
+    /// * `output_pattern` - Output path containing `%d`, replaced by the channel index
+    /// * `include_alpha` - Whether to also write the alpha channel
+    ///
+    /// # Returns
+    ///
+    /// Returns the channel files which exist after the split, under the names the output
+    /// policy chose, or an `OperationError` on failure
+    pub fn split(
+        &self,
+        input: &str,
+        colorspace: ChannelColorspace,
+        output_pattern: &str,
+        include_alpha: bool,
+    ) -> Result<Vec<ChannelFile>, OperationError> {
+        if !output_pattern.contains("%d") {
+            return Err(OperationError::InvalidParameter(
+                "output pattern must contain %d for the channel index".to_string(),
+            ));
+        }
+        let names = colorspace.channel_names();
+        let mut files = Vec::new();
+        for (index, (channel, selector)) in
+            names.iter().zip(colorspace.channel_selectors()).enumerate()
+        {
+            let output = output_pattern.replace("%d", &index.to_string());
+            self.magick_runner.execute_args(&[
+                input,
+                "-colorspace",
+                colorspace.magick_name(),
+                "-channel",
+                selector,
+                "-separate",
+                &output,
+            ])?;
+            files.extend(self.written_file(channel, &output));
+        }
+        if include_alpha {
+            let output = output_pattern.replace("%d", &names.len().to_string());
+            self.magick_runner
+                .execute_args(&[input, "-alpha", "extract", &output])?;
+            files.extend(self.written_file("alpha", &output));
+        }
+        Ok(files)
+    }
+
+    /// The file the last command wrote for a channel, if it exists
+    fn written_file(&self, channel: &str, output: &str) -> Option<ChannelFile> {
+        let written = self.magick_runner.last_resolved_output(output);
+        let exists = match self.workspace {
+            Some(workspace) => workspace.join(&written.path).exists(),
+            None => written.path.exists(),
+        };
+        exists.then(|| ChannelFile {
+            channel: channel.to_string(),
+            path: written.path.to_string_lossy().into_owned(),
+        })
+    }
+
+    /// Combine per-channel grayscale images into a single image
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Channel images in colorspace order, optionally followed by alpha
+    /// * `colorspace` - Colorspace the channels belong to
+    /// * `output` - Path to write the combined image to
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output, or an `OperationError` on failure
+    pub fn combine(
+        &self,
+        inputs: &[String],
+        colorspace: ChannelColorspace,
+        output: &str,
+    ) -> Result<ResolvedOutput, OperationError> {
+        let expected = colorspace.channel_names().len();
+        if inputs.len() != expected && inputs.len() != expected + 1 {
+            return Err(OperationError::InvalidParameter(format!(
+                "{} expects {} channel images (or {} with alpha), got {}",
+                colorspace.magick_name(),
+                expected,
+                expected + 1,
+                inputs.len()
+            )));
+        }
+        let mut args: Vec<&str> = inputs.iter().map(String::as_str).collect();
+        args.extend(["-set", "colorspace", colorspace.magick_name(), "-combine"]);
+        args.push(output);
+        self.magick_runner.execute_args(&args)?;
+        Ok(self.magick_runner.last_resolved_output(output))
+    }
+
+    /// Write chosen channels of an image to one grayscale file each
+    ///
+    /// Alpha is extracted as a mask, opaque pixels white, so images without transparency give
+    /// a white file.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Path to the source image
+    /// * `channels` - The channels to write, in order
+    /// * `output_pattern` - Output path containing `{channel}`, replaced by the channel name
+    ///
+    /// # Returns
+    ///
+    /// Returns the channel files as written, or an `OperationError` on failure
+    pub fn extract(
+        &self,
+        input: &str,
+        channels: &[RgbaChannel],
+        output_pattern: &str,
+    ) -> Result<Vec<ChannelFile>, OperationError> {
+        if !output_pattern.contains("{channel}") {
+            return Err(OperationError::InvalidParameter(
+                "output pattern must contain {channel} for the channel name".to_string(),
+            ));
+        }
+        if channels.is_empty() {
+            return Err(OperationError::InvalidParameter(
+                "at least one channel is required".to_string(),
+            ));
+        }
+        let mut files = Vec::new();
+        for channel in channels {
+            let output = output_pattern.replace("{channel}", channel.name());
+            let args = match channel {
+                RgbaChannel::Alpha => vec![input, "-alpha", "extract", &output],
+                _ => vec![input, "-channel", channel.name(), "-separate", &output],
+            };
+            self.magick_runner.execute_args(&args)?;
+            let written = self.magick_runner.last_resolved_output(&output);
+            files.push(ChannelFile {
+                channel: channel.name().to_string(),
+                path: written.path.to_string_lossy().into_owned(),
+            });
+        }
+        Ok(files)
+    }
+
+    /// Exchange two channels of an image
+    ///
+    /// An image without an alpha channel is given an opaque one first when alpha is swapped.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Path to the source image
+    /// * `first` - One channel to swap
+    /// * `second` - The channel to swap it with
+    /// * `output` - Path to write the swapped image to
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output, or an `OperationError` on failure
+    pub fn swap(
+        &self,
+        input: &str,
+        first: RgbaChannel,
+        second: RgbaChannel,
+        output: &str,
+    ) -> Result<ResolvedOutput, OperationError> {
+        if first == second {
+            return Err(OperationError::InvalidParameter(format!(
+                "cannot swap {} with itself",
+                first.name()
+            )));
+        }
+        let expression = format!("{}<=>{}", first.name(), second.name());
+        let mut args = vec![input];
+        if first == RgbaChannel::Alpha || second == RgbaChannel::Alpha {
+            args.extend(["-alpha", "set"]);
+        }
+        args.extend(["-channel-fx", &expression, output]);
+        self.magick_runner.execute_args(&args)?;
+        Ok(self.magick_runner.last_resolved_output(output))
+    }
+
+    /// Replace an image's alpha channel with the brightness of a mask image
+    ///
+    /// White areas of the mask become opaque and black areas transparent. The mask is aligned
+    /// with the top left corner of the image, so it should have the same size.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Path to the source image
+    /// * `mask` - Path to the mask image
+    /// * `output` - Path to write the masked image to
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output, or an `OperationError` on failure
+    pub fn apply_alpha_mask(
+        &self,
+        input: &str,
+        mask: &str,
+        output: &str,
+    ) -> Result<ResolvedOutput, OperationError> {
+        self.magick_runner.execute_args(&[
+            input,
+            "-alpha",
+            "off",
+            "(",
+            mask,
+            "-colorspace",
+            "gray",
+            "-alpha",
+            "off",
+            ")",
+            "-compose",
+            "CopyOpacity",
+            "-composite",
+            output,
+        ])?;
+        Ok(self.magick_runner.last_resolved_output(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::output::ConflictStrategy;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// Mock implementation of CommandRunner which records arguments
+    ///
+    /// Given a working directory, it also creates the output file the command names last,
+    /// unless it is `unwritten`.
+    struct MockCommandRunner {
+        captured_args: RefCell<Vec<String>>,
+        unwritten: Option<&'static str>,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            *self.captured_args.borrow_mut() = args.iter().map(|s| s.to_string()).collect();
+            if let (Some(dir), Some(output)) = (working_dir, args.last())
+                && self.unwritten != Some(*output)
+            {
+                fs::write(dir.join(output), b"gray").unwrap();
+            }
+            Ok(String::new())
+        }
+    }
+
+    fn mock() -> MockCommandRunner {
+        MockCommandRunner {
+            captured_args: RefCell::new(Vec::new()),
+            unwritten: None,
+        }
+    }
+
+    #[test]
+    fn test_split_cmyk() {
+        let runner = mock();
+        let workspace = TempDir::new().unwrap();
+        let channels = Channels::new(&runner, Some(workspace.path()));
+        let files = channels
+            .split("print.tif", ChannelColorspace::Cmyk, "plate-%d.tif", false)
+            .unwrap();
+
+        assert_eq!(files.len(), 4);
+        assert_eq!(files[3].channel, "black");
+        assert_eq!(files[3].path, "plate-3.tif");
+        assert_eq!(
+            *runner.captured_args.borrow(),
+            vec![
+                "print.tif",
+                "-colorspace",
+                "CMYK",
+                "-channel",
+                "K",
+                "-separate",
+                "plate-3.tif"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_with_alpha() {
+        let runner = mock();
+        let workspace = TempDir::new().unwrap();
+        let channels = Channels::new(&runner, Some(workspace.path()));
+        let files = channels
+            .split("logo.png", ChannelColorspace::Srgb, "logo-%d.png", true)
+            .unwrap();
+
+        assert_eq!(files.len(), 4);
+        assert_eq!(files[3].channel, "alpha");
+        assert_eq!(
+            *runner.captured_args.borrow(),
+            vec!["logo.png", "-alpha", "extract", "logo-3.png"]
+        );
+    }
+
+    #[test]
+    fn test_split_reports_only_files_written() {
+        let runner = MockCommandRunner {
+            unwritten: Some("logo-3.png"),
+            ..mock()
+        };
+        let workspace = TempDir::new().unwrap();
+        let channels = Channels::new(&runner, Some(workspace.path()));
+        let files = channels
+            .split("logo.png", ChannelColorspace::Srgb, "logo-%d.png", true)
+            .unwrap();
+
+        let reported: Vec<&str> = files.iter().map(|file| file.channel.as_str()).collect();
+        assert_eq!(reported, vec!["red", "green", "blue"]);
+    }
+
+    #[test]
+    fn test_split_applies_the_conflict_strategy() {
+        let runner = mock();
+        let workspace = TempDir::new().unwrap();
+        fs::write(workspace.path().join("plate-0.tif"), b"kept").unwrap();
+        let policy = OutputPolicy {
+            on_conflict: ConflictStrategy::Rename,
+            ..OutputPolicy::default()
+        };
+        let channels = Channels::new(&runner, Some(workspace.path())).with_output_policy(policy);
+        let files = channels
+            .split("print.tif", ChannelColorspace::Srgb, "plate-%d.tif", false)
+            .unwrap();
+
+        assert_eq!(files[0].path, "plate-0-1.tif");
+        assert_eq!(files[1].path, "plate-1.tif");
+        assert_eq!(
+            fs::read(workspace.path().join("plate-0.tif")).unwrap(),
+            b"kept"
+        );
+    }
+
+    #[test]
+    fn test_split_requires_index_placeholder() {
+        let runner = mock();
+        let channels = Channels::new(&runner, None);
+        let result = channels.split("logo.png", ChannelColorspace::Srgb, "logo.png", false);
+        assert!(matches!(result, Err(OperationError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn test_combine_validates_channel_count() {
+        let runner = mock();
+        let channels = Channels::new(&runner, None);
+        let inputs = vec!["c.tif".to_string(), "m.tif".to_string()];
+        let result = channels.combine(&inputs, ChannelColorspace::Cmyk, "out.tif");
+        assert!(matches!(result, Err(OperationError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn test_combine_args() {
+        let runner = mock();
+        let channels = Channels::new(&runner, None);
+        let inputs = vec![
+            "r.png".to_string(),
+            "g.png".to_string(),
+            "b.png".to_string(),
+        ];
+        channels
+            .combine(&inputs, ChannelColorspace::Srgb, "out.png")
+            .unwrap();
+
+        assert_eq!(
+            *runner.captured_args.borrow(),
+            vec![
+                "r.png",
+                "g.png",
+                "b.png",
+                "-set",
+                "colorspace",
+                "sRGB",
+                "-combine",
+                "out.png"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_channels() {
+        let runner = mock();
+        let channels = Channels::new(&runner, None);
+        let files = channels
+            .extract(
+                "logo.png",
+                &[RgbaChannel::Green, RgbaChannel::Alpha],
+                "logo-{channel}.png",
+            )
+            .unwrap();
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "logo-green.png");
+        assert_eq!(files[1].channel, "alpha");
+        assert_eq!(
+            *runner.captured_args.borrow(),
+            vec!["logo.png", "-alpha", "extract", "logo-alpha.png"]
+        );
+
+        let result = channels.extract("logo.png", &RgbaChannel::ALL, "logo.png");
+        assert!(matches!(result, Err(OperationError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn test_swap_channels() {
+        let runner = mock();
+        let channels = Channels::new(&runner, None);
+        channels
+            .swap("logo.png", RgbaChannel::Red, RgbaChannel::Alpha, "out.png")
+            .unwrap();
+
+        assert_eq!(
+            *runner.captured_args.borrow(),
+            vec![
+                "logo.png",
+                "-alpha",
+                "set",
+                "-channel-fx",
+                "red<=>alpha",
+                "out.png"
+            ]
+        );
+
+        let result = channels.swap("logo.png", RgbaChannel::Blue, RgbaChannel::Blue, "out.png");
+        assert!(matches!(result, Err(OperationError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn test_apply_alpha_mask() {
+        let runner = mock();
+        let channels = Channels::new(&runner, None);
+        let output = channels
+            .apply_alpha_mask("photo.jpg", "mask.png", "cutout.png")
+            .unwrap();
+
+        assert_eq!(output, ResolvedOutput::written("cutout.png"));
+        let args = runner.captured_args.borrow();
+        assert_eq!(args[3..6], ["(", "mask.png", "-colorspace"]);
+        assert!(
+            args.windows(2)
+                .any(|window| window == ["-compose", "CopyOpacity"])
+        );
+    }
+
+    #[test]
+    fn test_parse_colorspace() {
+        assert_eq!(
+            ChannelColorspace::parse("cmyk").unwrap(),
+            ChannelColorspace::Cmyk
+        );
+        assert!(ChannelColorspace::parse("xyz").is_err());
+        assert_eq!(RgbaChannel::parse("A").unwrap(), RgbaChannel::Alpha);
+        assert!(RgbaChannel::parse("cyan").is_err());
+    }
+}
@@ -0,0 +1,448 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+/// Number of images a collage arranges
+const IMAGE_COUNT: RangeInclusive<usize> = 2..=9;
+
+/// Widest collage in pixels, which keeps every offset and height within `u32`
+const MAX_COLLAGE_WIDTH: u32 = 16384;
+
+/// How images are arranged on the collage
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CollageLayout {
+    /// Square cells in rows and columns, as close to square as the count allows, with a short
+    /// last row centered
+    #[default]
+    Grid,
+    /// The first image across the full width at 16:9, the others in a row of squares below it
+    HeroStrip,
+    /// Square cells in a single row
+    SideBySide,
+}
+
+impl CollageLayout {
+    /// Parse a layout name
+    pub fn parse(name: &str) -> Result<Self, OperationError> {
+        match name {
+            "grid" => Ok(CollageLayout::Grid),
+            "hero_strip" => Ok(CollageLayout::HeroStrip),
+            "side_by_side" => Ok(CollageLayout::SideBySide),
+            other => Err(OperationError::InvalidParameter(format!(
+                "unknown layout '{other}', expected grid, hero_strip, or side_by_side"
+            ))),
+        }
+    }
+}
+
+/// How an image is sized into its cell
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CollageFit {
+    /// Cover the cell, cropping what overflows it from the center
+    #[default]
+    Fill,
+    /// Fit within the cell, leaving background beside it
+    Fit,
+}
+
+impl CollageFit {
+    /// Parse a fit name
+    pub fn parse(name: &str) -> Result<Self, OperationError> {
+        match name {
+            "fill" => Ok(CollageFit::Fill),
+            "fit" => Ok(CollageFit::Fit),
+            other => Err(OperationError::InvalidParameter(format!(
+                "unknown fit '{other}', expected fill or fit"
+            ))),
+        }
+    }
+}
+
+/// Options for arranging images into a collage
+#[derive(Debug, Clone)]
+pub struct CollageOptions {
+    /// Paths to the images, in the order they are placed
+    pub inputs: Vec<String>,
+    /// Path to write the collage to
+    pub output: String,
+    /// How the images are arranged
+    pub layout: CollageLayout,
+    /// How each image is sized into its cell
+    pub fit: CollageFit,
+    /// Width of the collage in pixels; the height follows from the layout
+    pub width: u32,
+    /// Pixels of background between cells and around the edge
+    pub gap: u32,
+    /// Color of the gaps, e.g. `white` or `none`
+    pub background: String,
+}
+
+impl CollageOptions {
+    /// Create options arranging the images in a 1200 pixel wide grid with 10 pixel white gaps
+    pub fn new(inputs: Vec<String>, output: &str) -> Self {
+        CollageOptions {
+            inputs,
+            output: output.to_string(),
+            layout: CollageLayout::Grid,
+            fit: CollageFit::Fill,
+            width: 1200,
+            gap: 10,
+            background: "white".to_string(),
+        }
+    }
+}
+
+/// Where one image is placed on the collage
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollageCell {
+    /// Path to the image
+    pub input: String,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// The result of making a collage
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collage {
+    /// The written output
+    pub output: ResolvedOutput,
+    pub width: u32,
+    pub height: u32,
+    /// The cell of each image, in the order given
+    pub cells: Vec<CollageCell>,
+}
+
+/// Operation which arranges images into a collage
+pub struct CollageMaker<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> CollageMaker<'a> {
+    /// Create a new CollageMaker with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        CollageMaker {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Lay out the cells, then size each image into its cell and compose them in one command
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output with its size and cells, or an `OperationError` on failure
+    pub fn run(&self, options: &CollageOptions) -> Result<Collage, OperationError> {
+        let (cells, height) = layout(options)?;
+        let args = build_args(options, &cells, height);
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        Ok(Collage {
+            output: self.magick_runner.last_resolved_output(&options.output),
+            width: options.width,
+            height,
+            cells,
+        })
+    }
+}
+
+/// Place each input in a cell of the layout
+///
+/// # Returns
+///
+/// Returns the cells in input order and the height of the collage, or an `OperationError`
+/// when the count of inputs is out of range, the width is above `MAX_COLLAGE_WIDTH`, or the
+/// width leaves no room for a cell
+fn layout(options: &CollageOptions) -> Result<(Vec<CollageCell>, u32), OperationError> {
+    let count = options.inputs.len();
+    if !IMAGE_COUNT.contains(&count) {
+        return Err(OperationError::InvalidParameter(format!(
+            "a collage takes {} to {} images, got {count}",
+            IMAGE_COUNT.start(),
+            IMAGE_COUNT.end()
+        )));
+    }
+    if options.width > MAX_COLLAGE_WIDTH {
+        return Err(OperationError::InvalidParameter(format!(
+            "collage width must be at most {MAX_COLLAGE_WIDTH} pixels, got {}",
+            options.width
+        )));
+    }
+    let gap = options.gap;
+    // Each cell is `size` wide with gaps between cells and around the edge
+    let cell_size = |columns: u32| {
+        let gaps = gap.saturating_mul(columns + 1);
+        options
+            .width
+            .checked_sub(gaps)
+            .map(|free| free / columns)
+            .filter(|size| *size > 0)
+            .ok_or_else(|| {
+                OperationError::InvalidParameter(format!(
+                    "width {} leaves no room for {columns} cells with a gap of {gap}",
+                    options.width
+                ))
+            })
+    };
+    let cell = |index: usize, x: u32, y: u32, width: u32, height: u32| CollageCell {
+        input: options.inputs[index].clone(),
+        x,
+        y,
+        width,
+        height,
+    };
+
+    let count = count as u32;
+    let mut cells = Vec::new();
+    let height = match options.layout {
+        CollageLayout::Grid => {
+            let columns = (1..=count).find(|c| c * c >= count).unwrap_or(count);
+            let rows = count.div_ceil(columns);
+            let size = cell_size(columns)?;
+            for index in 0..count {
+                let (row, column) = (index / columns, index % columns);
+                // Center a short last row
+                let in_row = (count - row * columns).min(columns);
+                let offset = (columns - in_row) * (size + gap) / 2;
+                cells.push(cell(
+                    index as usize,
+                    gap + offset + column * (size + gap),
+                    gap + row * (size + gap),
+                    size,
+                    size,
+                ));
+            }
+            gap + rows * (size + gap)
+        }
+        CollageLayout::HeroStrip => {
+            let hero_width = cell_size(1)?;
+            let hero_height = (hero_width * 9 / 16).max(1);
+            cells.push(cell(0, gap, gap, hero_width, hero_height));
+            let size = cell_size(count - 1)?;
+            let y = gap + hero_height + gap;
+            for index in 1..count {
+                cells.push(cell(
+                    index as usize,
+                    gap + (index - 1) * (size + gap),
+                    y,
+                    size,
+                    size,
+                ));
+            }
+            y + size + gap
+        }
+        CollageLayout::SideBySide => {
+            let size = cell_size(count)?;
+            for index in 0..count {
+                cells.push(cell(
+                    index as usize,
+                    gap + index * (size + gap),
+                    gap,
+                    size,
+                    size,
+                ));
+            }
+            gap + size + gap
+        }
+    };
+    Ok((cells, height))
+}
+
+/// Build the `magick` arguments composing the collage
+///
+/// Each image is read alone in parentheses, turned upright, and sized to its cell, then
+/// composed onto the background canvas at the cell's corner. Only the first frame of each
+/// input is used.
+fn build_args(options: &CollageOptions, cells: &[CollageCell], height: u32) -> Vec<String> {
+    let mut args = vec![
+        "-size".to_string(),
+        format!("{}x{height}", options.width),
+        format!("xc:{}", options.background),
+        "-background".to_string(),
+        options.background.clone(),
+    ];
+    for cell in cells {
+        let size = format!("{}x{}", cell.width, cell.height);
+        let resize = match options.fit {
+            CollageFit::Fill => format!("{size}^"),
+            CollageFit::Fit => size.clone(),
+        };
+        args.extend([
+            "(".to_string(),
+            format!("{}[0]", cell.input),
+            "-auto-orient".to_string(),
+            "-resize".to_string(),
+            resize,
+            "-gravity".to_string(),
+            "center".to_string(),
+            "-extent".to_string(),
+            size,
+            ")".to_string(),
+            "-gravity".to_string(),
+            "northwest".to_string(),
+            "-geometry".to_string(),
+            format!("+{}+{}", cell.x, cell.y),
+            "-composite".to_string(),
+        ]);
+    }
+    args.push(options.output.clone());
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+
+    struct MockCommandRunner {
+        calls: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|arg| arg.to_string()).collect());
+            Ok(String::new())
+        }
+    }
+
+    fn inputs(count: usize) -> Vec<String> {
+        (1..=count).map(|index| format!("{index}.jpg")).collect()
+    }
+
+    fn positions(cells: &[CollageCell]) -> Vec<(u32, u32, u32, u32)> {
+        cells
+            .iter()
+            .map(|cell| (cell.x, cell.y, cell.width, cell.height))
+            .collect()
+    }
+
+    #[test]
+    fn test_grid_centers_short_last_row() {
+        let mut options = CollageOptions::new(inputs(5), "collage.png");
+        options.width = 340;
+
+        let (cells, height) = layout(&options).unwrap();
+
+        // Three columns of 100 with 10 pixel gaps, the last two cells centered under them
+        assert_eq!(
+            positions(&cells),
+            vec![
+                (10, 10, 100, 100),
+                (120, 10, 100, 100),
+                (230, 10, 100, 100),
+                (65, 120, 100, 100),
+                (175, 120, 100, 100),
+            ]
+        );
+        assert_eq!(height, 230);
+    }
+
+    #[test]
+    fn test_hero_strip() {
+        let mut options = CollageOptions::new(inputs(3), "collage.png");
+        options.layout = CollageLayout::HeroStrip;
+        options.width = 340;
+
+        let (cells, height) = layout(&options).unwrap();
+
+        assert_eq!(
+            positions(&cells),
+            vec![
+                (10, 10, 320, 180),
+                (10, 200, 155, 155),
+                (175, 200, 155, 155)
+            ]
+        );
+        assert_eq!(height, 365);
+    }
+
+    #[test]
+    fn test_side_by_side_composes_each_image() {
+        let runner = MockCommandRunner {
+            calls: RefCell::new(Vec::new()),
+        };
+        let mut options = CollageOptions::new(inputs(2), "pair.png");
+        options.layout = CollageLayout::SideBySide;
+        options.fit = CollageFit::Fit;
+        options.width = 230;
+        options.background = "none".to_string();
+
+        let collage = CollageMaker::new(&runner, None).run(&options).unwrap();
+
+        assert_eq!((collage.width, collage.height), (230, 120));
+        assert_eq!(collage.output, ResolvedOutput::written("pair.png"));
+        let calls = runner.calls.borrow();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(
+            calls[0][..5],
+            ["-size", "230x120", "xc:none", "-background", "none"]
+        );
+        assert_eq!(
+            calls[0][5..20],
+            [
+                "(",
+                "1.jpg[0]",
+                "-auto-orient",
+                "-resize",
+                "100x100",
+                "-gravity",
+                "center",
+                "-extent",
+                "100x100",
+                ")",
+                "-gravity",
+                "northwest",
+                "-geometry",
+                "+10+10",
+                "-composite"
+            ]
+        );
+        assert!(calls[0].contains(&"+120+10".to_string()));
+        assert_eq!(calls[0].last().unwrap(), "pair.png");
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        assert!(layout(&CollageOptions::new(inputs(1), "collage.png")).is_err());
+        assert!(layout(&CollageOptions::new(inputs(10), "collage.png")).is_err());
+
+        let mut options = CollageOptions::new(inputs(9), "collage.png");
+        options.width = 40;
+        assert!(layout(&options).is_err());
+
+        options.width = u32::MAX;
+        assert!(layout(&options).is_err());
+        options.width = MAX_COLLAGE_WIDTH;
+        options.gap = u32::MAX;
+        assert!(layout(&options).is_err());
+        options.gap = 0;
+        for kind in [
+            CollageLayout::Grid,
+            CollageLayout::HeroStrip,
+            CollageLayout::SideBySide,
+        ] {
+            options.layout = kind;
+            assert!(layout(&options).is_ok());
+        }
+
+        assert!(CollageLayout::parse("mosaic").is_err());
+        assert!(CollageFit::parse("stretch").is_err());
+    }
+}
@@ -0,0 +1,217 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::watermark::parse_gravity;
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// Blend modes supported by the composite operation, as ImageMagick names them
+const BLEND_MODES: &[&str] = &[
+    "Over",
+    "Multiply",
+    "Screen",
+    "Overlay",
+    "Darken",
+    "Lighten",
+    "ColorDodge",
+    "ColorBurn",
+    "HardLight",
+    "SoftLight",
+    "Difference",
+    "Exclusion",
+    "Plus",
+    "Minus",
+];
+
+/// Normalize a blend mode name, ignoring case, `-`, and `_`, to the form ImageMagick expects
+fn parse_blend(blend: &str) -> Result<&'static str, OperationError> {
+    let normalized: String = blend.chars().filter(|c| !matches!(c, '-' | '_')).collect();
+    BLEND_MODES
+        .iter()
+        .find(|name| name.eq_ignore_ascii_case(&normalized))
+        .copied()
+        .ok_or_else(|| {
+            OperationError::InvalidParameter(format!(
+                "unknown blend mode '{blend}', expected one of {}",
+                BLEND_MODES.join(", ")
+            ))
+        })
+}
+
+/// Options for overlaying one image on another
+#[derive(Debug, Clone)]
+pub struct CompositeOptions {
+    /// Path to the base image
+    pub base: String,
+    /// Path to the image placed on top of the base
+    pub overlay: String,
+    /// Path to write the result to
+    pub output: String,
+    /// Where the overlay is placed, e.g. `Center`
+    pub gravity: String,
+    /// Horizontal offset in pixels from the gravity position
+    pub x: i32,
+    /// Vertical offset in pixels from the gravity position
+    pub y: i32,
+    /// How overlay pixels are combined with the base, e.g. `Multiply`
+    pub blend: String,
+    /// Overlay opacity from 0 (invisible) to 100 (opaque)
+    pub opacity: f64,
+}
+
+impl CompositeOptions {
+    /// Create options placing the overlay opaque in the top left corner
+    pub fn new(base: &str, overlay: &str, output: &str) -> Self {
+        CompositeOptions {
+            base: base.to_string(),
+            overlay: overlay.to_string(),
+            output: output.to_string(),
+            gravity: "NorthWest".to_string(),
+            x: 0,
+            y: 0,
+            blend: "Over".to_string(),
+            opacity: 100.0,
+        }
+    }
+}
+
+/// Operation which overlays one image on another
+pub struct Composite<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> Composite<'a> {
+    /// Create a new Composite with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Composite {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Composite the overlay onto the base and write the result
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output on success, or an `OperationError` on failure
+    pub fn run(&self, options: &CompositeOptions) -> Result<ResolvedOutput, OperationError> {
+        let args = build_args(options)?;
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        Ok(self.magick_runner.last_resolved_output(&options.output))
+    }
+}
+
+/// Build the `magick` arguments for a composite
+///
+/// The base always comes first and the overlay second, which is the ordering agents tend to get
+/// wrong. Opacity scales the overlay's alpha channel so it works with every blend mode.
+fn build_args(options: &CompositeOptions) -> Result<Vec<String>, OperationError> {
+    if !(0.0..=100.0).contains(&options.opacity) {
+        return Err(OperationError::InvalidParameter(format!(
+            "opacity {} must be between 0 and 100",
+            options.opacity
+        )));
+    }
+    let gravity = parse_gravity(&options.gravity)?;
+    let blend = parse_blend(&options.blend)?;
+
+    let mut args = vec![options.base.clone()];
+    if options.opacity < 100.0 {
+        args.extend([
+            "(".to_string(),
+            options.overlay.clone(),
+            "-alpha".to_string(),
+            "set".to_string(),
+            "-channel".to_string(),
+            "A".to_string(),
+            "-evaluate".to_string(),
+            "multiply".to_string(),
+            format!("{}", options.opacity / 100.0),
+            "+channel".to_string(),
+            ")".to_string(),
+        ]);
+    } else {
+        args.push(options.overlay.clone());
+    }
+    args.extend([
+        "-gravity".to_string(),
+        gravity.to_string(),
+        "-geometry".to_string(),
+        format!("{:+}{:+}", options.x, options.y),
+        "-compose".to_string(),
+        blend.to_string(),
+        "-composite".to_string(),
+        options.output.clone(),
+    ]);
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_args_defaults() {
+        let options = CompositeOptions::new("base.png", "overlay.png", "out.png");
+        assert_eq!(
+            build_args(&options).unwrap(),
+            vec![
+                "base.png",
+                "overlay.png",
+                "-gravity",
+                "NorthWest",
+                "-geometry",
+                "+0+0",
+                "-compose",
+                "Over",
+                "-composite",
+                "out.png"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_args_with_blend_offset_and_opacity() {
+        let mut options = CompositeOptions::new("base.png", "texture.png", "out.png");
+        options.gravity = "center".to_string();
+        options.x = -12;
+        options.y = 8;
+        options.blend = "soft-light".to_string();
+        options.opacity = 40.0;
+
+        let args = build_args(&options).unwrap();
+
+        assert_eq!(args[1..4], ["(", "texture.png", "-alpha"]);
+        assert!(args.contains(&"0.4".to_string()));
+        assert_eq!(
+            args[12..],
+            [
+                "-gravity",
+                "Center",
+                "-geometry",
+                "-12+8",
+                "-compose",
+                "SoftLight",
+                "-composite",
+                "out.png"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_args_validates_options() {
+        let mut options = CompositeOptions::new("base.png", "overlay.png", "out.png");
+        options.blend = "glow".to_string();
+        assert!(build_args(&options).is_err());
+
+        options.blend = "over".to_string();
+        options.opacity = 120.0;
+        assert!(build_args(&options).is_err());
+    }
+}
@@ -0,0 +1,260 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::formats::list_formats;
+use crate::feature::ops::{OperationError, OutputSettings};
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Options for converting an image to another format
+#[derive(Debug, Clone)]
+pub struct ConvertOptions {
+    /// Path to the source image
+    pub input: String,
+    /// Path to write the converted image to, defaults to the input with the format's extension
+    pub output: Option<String>,
+    /// Target format name, e.g. `webp` or `PNG`
+    pub format: String,
+    /// Quality or compression level from 0 to 100
+    pub quality: Option<u32>,
+    /// Compression type, e.g. `LZW` for TIFF or `Lossless` for JPEG 2000
+    pub compression: Option<String>,
+    /// Finishing settings applied before writing
+    pub settings: OutputSettings,
+}
+
+impl ConvertOptions {
+    /// Create options converting the input to the format next to the original
+    pub fn new(input: &str, format: &str) -> Self {
+        ConvertOptions {
+            input: input.to_string(),
+            output: None,
+            format: format.to_string(),
+            quality: None,
+            compression: None,
+            settings: OutputSettings::default(),
+        }
+    }
+
+    /// The output path, defaulting to the input path with the format's extension
+    fn output_path(&self) -> String {
+        self.output.clone().unwrap_or_else(|| {
+            Path::new(&self.input)
+                .with_extension(self.format.to_ascii_lowercase())
+                .to_string_lossy()
+                .to_string()
+        })
+    }
+}
+
+/// The result of a conversion
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConvertedFile {
+    /// The written output
+    pub output: ResolvedOutput,
+    /// Size of the output file in bytes
+    pub size: u64,
+}
+
+/// Operation which converts an image to another format
+pub struct Convert<'a> {
+    magick_runner: MagickRunner<'a>,
+    base: PathBuf,
+}
+
+impl<'a> Convert<'a> {
+    /// Create a new Convert with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Convert {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+            base: workspace
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(".")),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Convert the image, first checking that ImageMagick can write the target format
+    ///
+    /// # Returns
+    ///
+    /// Returns the written file and its size, or an `OperationError` on failure
+    pub fn run(&self, options: &ConvertOptions) -> Result<ConvertedFile, OperationError> {
+        let format = self.writable_format(&options.format)?;
+        let output = options.output_path();
+        let args = build_args(options, &format, &output)?;
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+
+        let resolved = self.magick_runner.last_resolved_output(&output);
+        let size = fs::metadata(self.base.join(&resolved.path))?.len();
+        Ok(ConvertedFile {
+            output: resolved,
+            size,
+        })
+    }
+
+    /// Find the format in `magick -list format`, returning its canonical name
+    fn writable_format(&self, format: &str) -> Result<String, OperationError> {
+        let formats = list_formats(&self.magick_runner)?;
+        let known = formats
+            .iter()
+            .find(|known| known.name.eq_ignore_ascii_case(format))
+            .ok_or_else(|| {
+                OperationError::InvalidParameter(format!(
+                    "format '{format}' is not supported by this ImageMagick installation"
+                ))
+            })?;
+        if !known.writable {
+            return Err(OperationError::InvalidParameter(format!(
+                "format '{}' can be read but not written by this ImageMagick installation",
+                known.name
+            )));
+        }
+        Ok(known.name.clone())
+    }
+}
+
+/// Build the `magick` arguments for a conversion
+///
+/// The output is prefixed with the format so it is used even when the extension differs.
+fn build_args(
+    options: &ConvertOptions,
+    format: &str,
+    output: &str,
+) -> Result<Vec<String>, OperationError> {
+    let mut args = vec![options.input.clone()];
+    args.extend(options.settings.args()?);
+    if let Some(quality) = options.quality {
+        if quality > 100 {
+            return Err(OperationError::InvalidParameter(format!(
+                "quality {quality} must be between 0 and 100"
+            )));
+        }
+        args.extend(["-quality".to_string(), quality.to_string()]);
+    }
+    if let Some(compression) = &options.compression {
+        args.extend(["-compress".to_string(), compression.clone()]);
+    }
+    args.push(format!("{}:{output}", format.to_ascii_lowercase()));
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+    use tempfile::TempDir;
+
+    /// Mock implementation of CommandRunner which lists a few formats and writes converted files
+    struct MockCommandRunner {
+        calls: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|s| s.to_string()).collect());
+            if args == ["-list", "format"] {
+                return Ok("     PNG* PNG       rw-   Portable Network Graphics\n\
+                           WEBP* WEBP      rw+   WebP Image Format\n\
+                           3FR  DNG       r--   Hasselblad Raw Format\n"
+                    .to_string());
+            }
+            let output = args.last().unwrap();
+            let path = output.split_once(':').map_or(*output, |(_, path)| path);
+            fs::write(working_dir.unwrap().join(path), b"converted").unwrap();
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_run_converts_and_reports_size() {
+        let dir = TempDir::new().unwrap();
+        let runner = MockCommandRunner {
+            calls: RefCell::new(Vec::new()),
+        };
+        let convert = Convert::new(&runner, Some(dir.path()));
+        let mut options = ConvertOptions::new("photos/cat.png", "webp");
+        options.output = Some("cat.webp".to_string());
+        options.quality = Some(80);
+
+        let converted = convert.run(&options).unwrap();
+
+        assert_eq!(converted.output, ResolvedOutput::written("cat.webp"));
+        assert_eq!(converted.size, 9);
+        assert_eq!(
+            runner.calls.borrow()[1],
+            vec!["photos/cat.png", "-quality", "80", "webp:cat.webp"]
+        );
+    }
+
+    #[test]
+    fn test_run_rejects_unsupported_formats() {
+        let runner = MockCommandRunner {
+            calls: RefCell::new(Vec::new()),
+        };
+        let convert = Convert::new(&runner, None);
+
+        let unknown = convert.run(&ConvertOptions::new("cat.png", "heic"));
+        assert!(matches!(unknown, Err(OperationError::InvalidParameter(_))));
+
+        let read_only = convert.run(&ConvertOptions::new("cat.png", "3fr"));
+        assert!(matches!(
+            read_only,
+            Err(OperationError::InvalidParameter(_))
+        ));
+        assert_eq!(runner.calls.borrow().len(), 2);
+    }
+
+    #[test]
+    fn test_default_output_path() {
+        let options = ConvertOptions::new("photos/cat.png", "WEBP");
+        assert_eq!(options.output_path(), "photos/cat.webp");
+    }
+
+    #[test]
+    fn test_build_args_with_compression() {
+        let mut options = ConvertOptions::new("scan.png", "tiff");
+        options.compression = Some("LZW".to_string());
+        assert_eq!(
+            build_args(&options, "TIFF", "scan.tiff").unwrap(),
+            vec!["scan.png", "-compress", "LZW", "tiff:scan.tiff"]
+        );
+
+        options.quality = Some(101);
+        assert!(build_args(&options, "TIFF", "scan.tiff").is_err());
+    }
+
+    #[test]
+    fn test_build_args_with_settings() {
+        let mut options = ConvertOptions::new("photo.jpg", "webp");
+        options.quality = Some(80);
+        options.settings.max_width = Some(1920);
+        options.settings.strip = true;
+        assert_eq!(
+            build_args(&options, "WEBP", "photo.webp").unwrap(),
+            vec![
+                "photo.jpg",
+                "-resize",
+                "1920x>",
+                "-strip",
+                "-quality",
+                "80",
+                "webp:photo.webp"
+            ]
+        );
+    }
+}
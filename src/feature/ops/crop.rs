@@ -0,0 +1,342 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::identify::{self, ImageDimensions};
+use crate::feature::ops::watermark::parse_gravity;
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// Units the crop rectangle is given in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CropUnit {
+    /// Pixels
+    #[default]
+    Pixels,
+    /// Percent of the image width and height
+    Percent,
+}
+
+impl CropUnit {
+    /// Parse a unit name
+    pub fn parse(name: &str) -> Result<Self, OperationError> {
+        match name {
+            "pixels" => Ok(CropUnit::Pixels),
+            "percent" => Ok(CropUnit::Percent),
+            other => Err(OperationError::InvalidParameter(format!(
+                "unknown unit '{other}', expected pixels or percent"
+            ))),
+        }
+    }
+}
+
+/// Options for cropping an image
+///
+/// Either `width` and `height` or `aspect_ratio` select the region.
+#[derive(Debug, Clone)]
+pub struct CropOptions {
+    /// Path to the source image
+    pub input: String,
+    /// Path to write the cropped image to
+    pub output: String,
+    /// Left edge of the region, defaults to 0
+    pub x: Option<f64>,
+    /// Top edge of the region, defaults to 0
+    pub y: Option<f64>,
+    /// Width of the region
+    pub width: Option<f64>,
+    /// Height of the region
+    pub height: Option<f64>,
+    /// Units of `x`, `y`, `width`, and `height`
+    pub unit: CropUnit,
+    /// Crop the largest region with this aspect ratio, e.g. `16:9`
+    pub aspect_ratio: Option<String>,
+    /// Where the aspect ratio region is placed, e.g. `Center`
+    pub gravity: String,
+}
+
+impl CropOptions {
+    /// Create options with no region selected yet
+    pub fn new(input: &str, output: &str) -> Self {
+        CropOptions {
+            input: input.to_string(),
+            output: output.to_string(),
+            x: None,
+            y: None,
+            width: None,
+            height: None,
+            unit: CropUnit::Pixels,
+            aspect_ratio: None,
+            gravity: "Center".to_string(),
+        }
+    }
+}
+
+/// A crop region in pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CropRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CropRect {
+    /// The region as ImageMagick geometry, e.g. `100x50+10+20`
+    pub fn geometry(&self) -> String {
+        format!("{}x{}+{}+{}", self.width, self.height, self.x, self.y)
+    }
+}
+
+/// The result of a crop
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CroppedImage {
+    /// The written output
+    pub output: ResolvedOutput,
+    /// The region which was cropped, in pixels
+    pub rect: CropRect,
+}
+
+/// Operation which crops an image without exposing ImageMagick geometry syntax
+pub struct Crop<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> Crop<'a> {
+    /// Create a new Crop with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Crop {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Crop the image after checking the region against its dimensions
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output and the region in pixels, or an `OperationError` if the region
+    /// is invalid or falls outside the image
+    pub fn run(&self, options: &CropOptions) -> Result<CroppedImage, OperationError> {
+        let dimensions = identify::dimensions(&self.magick_runner, &options.input)?;
+        let rect = resolve_rect(options, dimensions)?;
+        let geometry = rect.geometry();
+        self.magick_runner.execute_args(&[
+            &options.input,
+            "-crop",
+            &geometry,
+            "+repage",
+            &options.output,
+        ])?;
+        Ok(CroppedImage {
+            output: self.magick_runner.last_resolved_output(&options.output),
+            rect,
+        })
+    }
+}
+
+/// Work out the crop region in pixels and check it lies inside the image
+fn resolve_rect(
+    options: &CropOptions,
+    dimensions: ImageDimensions,
+) -> Result<CropRect, OperationError> {
+    let invalid = |message: String| Err(OperationError::InvalidParameter(message));
+    if let Some(ratio) = &options.aspect_ratio {
+        if options.width.is_some() || options.height.is_some() {
+            return invalid("pass either width and height or aspect_ratio, not both".to_string());
+        }
+        return aspect_rect(ratio, &options.gravity, dimensions);
+    }
+
+    let (Some(width), Some(height)) = (options.width, options.height) else {
+        return invalid("width and height are required unless aspect_ratio is set".to_string());
+    };
+    let to_pixels = |value: f64, size: u32| match options.unit {
+        CropUnit::Pixels => value,
+        CropUnit::Percent => value * f64::from(size) / 100.0,
+    };
+    let x = to_pixels(options.x.unwrap_or(0.0), dimensions.width).round();
+    let y = to_pixels(options.y.unwrap_or(0.0), dimensions.height).round();
+    let width = to_pixels(width, dimensions.width).round();
+    let height = to_pixels(height, dimensions.height).round();
+
+    if x < 0.0 || y < 0.0 || width < 1.0 || height < 1.0 {
+        return invalid(
+            "x and y must not be negative and width and height must be at least 1 pixel"
+                .to_string(),
+        );
+    }
+    if x + width > f64::from(dimensions.width) || y + height > f64::from(dimensions.height) {
+        return invalid(format!(
+            "region {width}x{height}+{x}+{y} is outside the {}x{} image",
+            dimensions.width, dimensions.height
+        ));
+    }
+    Ok(CropRect {
+        x: x as u32,
+        y: y as u32,
+        width: width as u32,
+        height: height as u32,
+    })
+}
+
+/// The largest region with an aspect ratio such as `16:9`, placed at the gravity
+fn aspect_rect(
+    ratio: &str,
+    gravity: &str,
+    dimensions: ImageDimensions,
+) -> Result<CropRect, OperationError> {
+    let parsed = ratio
+        .split_once(':')
+        .and_then(|(w, h)| Some((w.trim().parse::<f64>().ok()?, h.trim().parse::<f64>().ok()?)))
+        .filter(|(w, h)| *w > 0.0 && *h > 0.0);
+    let Some((ratio_width, ratio_height)) = parsed else {
+        return Err(OperationError::InvalidParameter(format!(
+            "aspect ratio '{ratio}' must look like 16:9"
+        )));
+    };
+    let gravity = parse_gravity(gravity)?;
+
+    let (image_width, image_height) = (f64::from(dimensions.width), f64::from(dimensions.height));
+    let (width, height) = if image_width / image_height > ratio_width / ratio_height {
+        (
+            (image_height * ratio_width / ratio_height).round(),
+            image_height,
+        )
+    } else {
+        (
+            image_width,
+            (image_width * ratio_height / ratio_width).round(),
+        )
+    };
+    let (width, height) = (width.max(1.0) as u32, height.max(1.0) as u32);
+    let spare_x = dimensions.width - width;
+    let spare_y = dimensions.height - height;
+    let x = match gravity {
+        "NorthWest" | "West" | "SouthWest" => 0,
+        "NorthEast" | "East" | "SouthEast" => spare_x,
+        _ => spare_x / 2,
+    };
+    let y = match gravity {
+        "NorthWest" | "North" | "NorthEast" => 0,
+        "SouthWest" | "South" | "SouthEast" => spare_y,
+        _ => spare_y / 2,
+    };
+    Ok(CropRect {
+        x,
+        y,
+        width,
+        height,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+
+    const IMAGE: ImageDimensions = ImageDimensions {
+        width: 1920,
+        height: 1080,
+    };
+
+    /// Mock implementation of CommandRunner reporting a 1920x1080 image
+    struct MockCommandRunner {
+        calls: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|s| s.to_string()).collect());
+            if args[0] == "identify" {
+                return Ok("1920 1080".to_string());
+            }
+            Ok(String::new())
+        }
+    }
+
+    fn rect(x: f64, y: f64, width: f64, height: f64, unit: CropUnit) -> CropOptions {
+        let mut options = CropOptions::new("in.png", "out.png");
+        options.x = Some(x);
+        options.y = Some(y);
+        options.width = Some(width);
+        options.height = Some(height);
+        options.unit = unit;
+        options
+    }
+
+    #[test]
+    fn test_run_crops_with_pixel_geometry() {
+        let runner = MockCommandRunner {
+            calls: RefCell::new(Vec::new()),
+        };
+        let crop = Crop::new(&runner, None);
+
+        let cropped = crop
+            .run(&rect(10.0, 20.0, 300.0, 200.0, CropUnit::Pixels))
+            .unwrap();
+
+        assert_eq!(cropped.rect.geometry(), "300x200+10+20");
+        assert_eq!(
+            runner.calls.borrow()[1],
+            vec!["in.png", "-crop", "300x200+10+20", "+repage", "out.png"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_rect_percent() {
+        let options = rect(25.0, 50.0, 50.0, 50.0, CropUnit::Percent);
+        assert_eq!(
+            resolve_rect(&options, IMAGE).unwrap().geometry(),
+            "960x540+480+540"
+        );
+    }
+
+    #[test]
+    fn test_resolve_rect_rejects_out_of_bounds() {
+        let options = rect(1800.0, 0.0, 200.0, 100.0, CropUnit::Pixels);
+        let error = resolve_rect(&options, IMAGE).unwrap_err().to_string();
+        assert!(error.contains("outside the 1920x1080 image"));
+
+        let missing = CropOptions::new("in.png", "out.png");
+        assert!(resolve_rect(&missing, IMAGE).is_err());
+    }
+
+    #[test]
+    fn test_resolve_rect_aspect_ratio() {
+        let mut options = CropOptions::new("in.png", "out.png");
+        options.aspect_ratio = Some("1:1".to_string());
+        assert_eq!(
+            resolve_rect(&options, IMAGE).unwrap().geometry(),
+            "1080x1080+420+0"
+        );
+
+        options.gravity = "east".to_string();
+        assert_eq!(
+            resolve_rect(&options, IMAGE).unwrap().geometry(),
+            "1080x1080+840+0"
+        );
+
+        options.aspect_ratio = Some("4:1".to_string());
+        options.gravity = "south".to_string();
+        assert_eq!(
+            resolve_rect(&options, IMAGE).unwrap().geometry(),
+            "1920x480+0+600"
+        );
+
+        options.aspect_ratio = Some("wide".to_string());
+        assert!(resolve_rect(&options, IMAGE).is_err());
+    }
+}
@@ -0,0 +1,316 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// Centimeters in an inch
+const CM_PER_INCH: f64 = 2.54;
+
+/// `identify -format` string producing output readable by `PrintSize::parse`
+const PRINT_SIZE_FORMAT: &str = "%w|%h|%x|%y|%U";
+
+/// Unit a density is given in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DensityUnits {
+    /// Pixels per inch, i.e. DPI
+    #[default]
+    PixelsPerInch,
+    /// Pixels per centimeter
+    PixelsPerCentimeter,
+}
+
+impl DensityUnits {
+    /// Parse a unit name, case-insensitively
+    pub fn parse(name: &str) -> Result<Self, OperationError> {
+        match name.to_ascii_lowercase().as_str() {
+            "inch" | "ppi" | "dpi" | "pixelsperinch" => Ok(DensityUnits::PixelsPerInch),
+            "cm" | "ppcm" | "pixelspercentimeter" => Ok(DensityUnits::PixelsPerCentimeter),
+            other => Err(OperationError::InvalidParameter(format!(
+                "unknown units '{other}', expected inch or cm"
+            ))),
+        }
+    }
+
+    /// The ImageMagick name of the unit
+    pub fn magick_name(self) -> &'static str {
+        match self {
+            DensityUnits::PixelsPerInch => "PixelsPerInch",
+            DensityUnits::PixelsPerCentimeter => "PixelsPerCentimeter",
+        }
+    }
+}
+
+/// Options for changing the density of an image
+#[derive(Debug, Clone)]
+pub struct DensityOptions {
+    /// Path to the source image
+    pub input: String,
+    /// Path to write the image to
+    pub output: String,
+    /// The new density, in `units`
+    pub density: f64,
+    /// Unit the density is given in
+    pub units: DensityUnits,
+    /// Whether to resample the pixels so the print size stays the same, rather than only
+    /// changing the density recorded in the file
+    pub resample: bool,
+}
+
+impl DensityOptions {
+    /// Create options recording a density in pixels per inch without touching the pixels
+    pub fn new(input: &str, output: &str, density: f64) -> Self {
+        DensityOptions {
+            input: input.to_string(),
+            output: output.to_string(),
+            density,
+            units: DensityUnits::PixelsPerInch,
+            resample: false,
+        }
+    }
+}
+
+/// Pixel size and density of an image, from which its printed size follows
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrintSize {
+    pub width: u32,
+    pub height: u32,
+    /// Horizontal density in `units`
+    pub x_density: f64,
+    /// Vertical density in `units`
+    pub y_density: f64,
+    /// ImageMagick name of the density's unit, `Undefined` when the file records none
+    pub units: String,
+}
+
+impl PrintSize {
+    /// Parse the output of `identify -format` using `PRINT_SIZE_FORMAT`
+    pub fn parse(output: &str) -> Option<Self> {
+        let mut parts = output.trim().splitn(5, '|');
+        let width = parts.next()?.parse().ok()?;
+        let height = parts.next()?.parse().ok()?;
+        // Older releases append the unit to the resolution, e.g. `72 PixelsPerInch`
+        let mut density =
+            || -> Option<f64> { parts.next()?.split_whitespace().next()?.parse().ok() };
+        let x_density = density()?;
+        let y_density = density()?;
+        let units = parts.next()?.trim().to_string();
+        Some(PrintSize {
+            width,
+            height,
+            x_density,
+            y_density,
+            units,
+        })
+    }
+
+    /// Printed width and height in inches, `None` when the image has no density
+    ///
+    /// A density without a unit is taken to be per inch, as ImageMagick does.
+    pub fn inches(&self) -> Option<(f64, f64)> {
+        if self.x_density <= 0.0 || self.y_density <= 0.0 {
+            return None;
+        }
+        let per_inch = if self.units == DensityUnits::PixelsPerCentimeter.magick_name() {
+            CM_PER_INCH
+        } else {
+            1.0
+        };
+        Some((
+            f64::from(self.width) / (self.x_density * per_inch),
+            f64::from(self.height) / (self.y_density * per_inch),
+        ))
+    }
+
+    /// Printed width and height in centimeters, `None` when the image has no density
+    pub fn centimeters(&self) -> Option<(f64, f64)> {
+        self.inches()
+            .map(|(width, height)| (width * CM_PER_INCH, height * CM_PER_INCH))
+    }
+}
+
+/// The result of changing an image's density
+#[derive(Debug, Clone, PartialEq)]
+pub struct DensityChange {
+    /// The written output
+    pub output: ResolvedOutput,
+    /// Size and density of the input
+    pub before: PrintSize,
+    /// Size and density of the output, `None` when writing it was skipped
+    pub after: Option<PrintSize>,
+}
+
+/// Operation which sets or resamples the density of an image for print
+pub struct Density<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> Density<'a> {
+    /// Create a new Density with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Density {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Change the density and measure the image before and after
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output with both print sizes, or an `OperationError` on failure
+    pub fn run(&self, options: &DensityOptions) -> Result<DensityChange, OperationError> {
+        let args = build_args(options)?;
+        let before = self.print_size(&options.input)?;
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        let output = self.magick_runner.last_resolved_output(&options.output);
+        let after = if output.skipped {
+            None
+        } else {
+            Some(self.print_size(&output.path.to_string_lossy())?)
+        };
+        Ok(DensityChange {
+            output,
+            before,
+            after,
+        })
+    }
+
+    fn print_size(&self, path: &str) -> Result<PrintSize, OperationError> {
+        let frame = format!("{path}[0]");
+        let output =
+            self.magick_runner
+                .execute_args(&["identify", "-format", PRINT_SIZE_FORMAT, &frame])?;
+        PrintSize::parse(&output).ok_or_else(|| {
+            OperationError::ParseError(format!("unexpected identify output: {output}"))
+        })
+    }
+}
+
+/// Build the `magick` arguments changing the density
+///
+/// `-density` only rewrites the recorded density, so the print size changes, while
+/// `-resample` scales the pixels from the current density to the new one, so the print size
+/// stays the same. The units are set first so both read the density in the requested unit.
+fn build_args(options: &DensityOptions) -> Result<Vec<String>, OperationError> {
+    if !options.density.is_finite() || options.density <= 0.0 {
+        return Err(OperationError::InvalidParameter(format!(
+            "density {} must be a positive number",
+            options.density
+        )));
+    }
+    let operator = if options.resample {
+        "-resample"
+    } else {
+        "-density"
+    };
+    Ok(vec![
+        options.input.clone(),
+        "-units".to_string(),
+        options.units.magick_name().to_string(),
+        operator.to_string(),
+        options.density.to_string(),
+        options.output.clone(),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+
+    /// Reports a 72 ppi input and a 300 ppi output
+    struct MockCommandRunner {
+        calls: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl MockCommandRunner {
+        fn new() -> Self {
+            MockCommandRunner {
+                calls: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|arg| arg.to_string()).collect());
+            match args {
+                ["identify", .., "poster.png[0]"] => {
+                    Ok("3000|2400|72|72|PixelsPerInch".to_string())
+                }
+                ["identify", ..] => Ok("3000|2400|300|300|PixelsPerInch".to_string()),
+                _ => Ok(String::new()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_set_density_reports_print_size() {
+        let runner = MockCommandRunner::new();
+        let options = DensityOptions::new("poster.png", "print.png", 300.0);
+
+        let change = Density::new(&runner, None).run(&options).unwrap();
+
+        assert_eq!(change.output, ResolvedOutput::written("print.png"));
+        assert_eq!(change.before.inches(), Some((3000.0 / 72.0, 2400.0 / 72.0)));
+        assert_eq!(change.after.unwrap().inches(), Some((10.0, 8.0)));
+        assert_eq!(
+            runner.calls.borrow()[1],
+            [
+                "poster.png",
+                "-units",
+                "PixelsPerInch",
+                "-density",
+                "300",
+                "print.png"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resample_in_centimeters() {
+        let mut options = DensityOptions::new("poster.png", "print.png", 118.11);
+        options.units = DensityUnits::PixelsPerCentimeter;
+        options.resample = true;
+
+        let args = build_args(&options).unwrap();
+
+        assert_eq!(args[2], "PixelsPerCentimeter");
+        assert_eq!(args[3..5], ["-resample", "118.11"]);
+    }
+
+    #[test]
+    fn test_parse_print_size() {
+        let size =
+            PrintSize::parse("200|100|40 PixelsPerCentimeter|40|PixelsPerCentimeter").unwrap();
+        assert_eq!(size.x_density, 40.0);
+        let (width, height) = size.centimeters().unwrap();
+        assert!((width - 5.0).abs() < 1e-9);
+        assert!((height - 2.5).abs() < 1e-9);
+
+        let size = PrintSize::parse("200|100|0|0|Undefined").unwrap();
+        assert_eq!(size.inches(), None);
+    }
+
+    #[test]
+    fn test_invalid_density() {
+        let options = DensityOptions::new("poster.png", "print.png", 0.0);
+        assert!(build_args(&options).is_err());
+        assert!(DensityUnits::parse("furlong").is_err());
+    }
+}
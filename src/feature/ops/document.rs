@@ -0,0 +1,305 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::identify::{self, ImageDimensions};
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// Percentage of the darkest and lightest pixels clipped when stretching contrast
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContrastStretch {
+    /// Percentage of pixels turned black
+    pub black_point: f64,
+    /// Percentage of pixels turned white
+    pub white_point: f64,
+}
+
+impl Default for ContrastStretch {
+    fn default() -> Self {
+        ContrastStretch {
+            black_point: 1.0,
+            white_point: 1.0,
+        }
+    }
+}
+
+/// Options for cleaning up a scanned document
+///
+/// The steps are applied in the order deskew, contrast stretch, background whitening,
+/// binarization, and each is skipped when `None`.
+#[derive(Debug, Clone)]
+pub struct DocumentCleanupOptions {
+    /// Path to the scanned page
+    pub input: String,
+    /// Path to write the cleaned page to
+    pub output: String,
+    /// Straighten the page, with the threshold percentage `-deskew` detects its lines at
+    pub deskew: Option<f64>,
+    /// Spread the page's tones over the full range
+    pub contrast_stretch: Option<ContrastStretch>,
+    /// Turn everything brighter than this percentage white, removing paper tint and
+    /// show-through
+    pub whiten_background: Option<f64>,
+    /// Reduce the page to black and white, with pixels brighter than this percentage white
+    pub binarize: Option<f64>,
+}
+
+impl DocumentCleanupOptions {
+    /// Create options which deskew, stretch contrast, and whiten the background, without
+    /// binarizing
+    pub fn new(input: &str, output: &str) -> Self {
+        DocumentCleanupOptions {
+            input: input.to_string(),
+            output: output.to_string(),
+            deskew: Some(40.0),
+            contrast_stretch: Some(ContrastStretch::default()),
+            whiten_background: Some(85.0),
+            binarize: None,
+        }
+    }
+}
+
+/// The result of cleaning up a scanned document
+#[derive(Debug, Clone, PartialEq)]
+pub struct CleanedDocument {
+    /// The written output
+    pub output: ResolvedOutput,
+    /// Dimensions of the output, which grow when deskewing rotates the page
+    pub dimensions: Option<ImageDimensions>,
+    /// Degrees the page was rotated by to straighten it, when deskewed
+    pub deskew_angle: Option<f64>,
+}
+
+/// Operation which straightens and cleans up scanned documents
+pub struct DocumentCleanup<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> DocumentCleanup<'a> {
+    /// Create a new DocumentCleanup with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        DocumentCleanup {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Clean up the page and read the dimensions of the result
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output, its dimensions, and the deskew angle, or an
+    /// `OperationError` on failure
+    pub fn run(&self, options: &DocumentCleanupOptions) -> Result<CleanedDocument, OperationError> {
+        let args = build_args(options)?;
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        let printed = self.magick_runner.execute_args(&arg_refs)?;
+        let output = self.magick_runner.last_resolved_output(&options.output);
+        let dimensions = if output.skipped {
+            None
+        } else {
+            Some(identify::dimensions(
+                &self.magick_runner,
+                &output.path.to_string_lossy(),
+            )?)
+        };
+        let deskew_angle = options
+            .deskew
+            .and_then(|_| printed.lines().next()?.trim().parse().ok());
+        Ok(CleanedDocument {
+            output,
+            dimensions,
+            deskew_angle,
+        })
+    }
+}
+
+/// Build the `magick` arguments cleaning up the page
+///
+/// Deskewing fills the uncovered corners white and prints the angle it found. Whitening maps
+/// everything at or above its threshold to white with `-level`, and binarizing converts to
+/// grayscale before `-threshold` so every channel is cut at the same brightness.
+fn build_args(options: &DocumentCleanupOptions) -> Result<Vec<String>, OperationError> {
+    if options.deskew.is_none()
+        && options.contrast_stretch.is_none()
+        && options.whiten_background.is_none()
+        && options.binarize.is_none()
+    {
+        return Err(OperationError::InvalidParameter(
+            "at least one of deskew, contrast_stretch, whiten_background, or binarize is required"
+                .to_string(),
+        ));
+    }
+    let mut args = vec![options.input.clone()];
+    if let Some(threshold) = options.deskew {
+        check_percent("deskew threshold", threshold)?;
+        args.extend([
+            "-background".to_string(),
+            "white".to_string(),
+            "-deskew".to_string(),
+            format!("{threshold}%"),
+            "+repage".to_string(),
+            "-print".to_string(),
+            "%[deskew:angle]\\n".to_string(),
+        ]);
+    }
+    if let Some(stretch) = options.contrast_stretch {
+        check_percent("black point", stretch.black_point)?;
+        check_percent("white point", stretch.white_point)?;
+        if stretch.black_point + stretch.white_point >= 100.0 {
+            return Err(OperationError::InvalidParameter(format!(
+                "black point {} and white point {} must add up to less than 100",
+                stretch.black_point, stretch.white_point
+            )));
+        }
+        args.extend([
+            "-contrast-stretch".to_string(),
+            format!("{}%x{}%", stretch.black_point, stretch.white_point),
+        ]);
+    }
+    if let Some(threshold) = options.whiten_background {
+        check_percent("whiten_background threshold", threshold)?;
+        if threshold == 0.0 {
+            return Err(OperationError::InvalidParameter(
+                "whiten_background threshold must be above 0".to_string(),
+            ));
+        }
+        args.extend(["-level".to_string(), format!("0%,{threshold}%")]);
+    }
+    if let Some(threshold) = options.binarize {
+        check_percent("binarize threshold", threshold)?;
+        args.extend([
+            "-colorspace".to_string(),
+            "Gray".to_string(),
+            "-threshold".to_string(),
+            format!("{threshold}%"),
+        ]);
+    }
+    args.push(options.output.clone());
+    Ok(args)
+}
+
+/// Check that a percentage is between 0 and 100
+fn check_percent(name: &str, value: f64) -> Result<(), OperationError> {
+    if !value.is_finite() || !(0.0..=100.0).contains(&value) {
+        return Err(OperationError::InvalidParameter(format!(
+            "{name} {value} must be between 0 and 100"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+
+    /// Prints a deskew angle and reports the straightened page's dimensions
+    struct MockCommandRunner {
+        calls: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|arg| arg.to_string()).collect());
+            Ok(if args[0] == "identify" {
+                "2550 3300".to_string()
+            } else {
+                "-1.25\n".to_string()
+            })
+        }
+    }
+
+    #[test]
+    fn test_cleanup_runs_every_step_in_order() {
+        let runner = MockCommandRunner {
+            calls: RefCell::new(Vec::new()),
+        };
+        let mut options = DocumentCleanupOptions::new("scan.jpg", "clean.png");
+        options.binarize = Some(60.0);
+
+        let cleaned = DocumentCleanup::new(&runner, None).run(&options).unwrap();
+
+        assert_eq!(cleaned.deskew_angle, Some(-1.25));
+        assert_eq!(
+            cleaned.dimensions,
+            Some(ImageDimensions {
+                width: 2550,
+                height: 3300
+            })
+        );
+        let calls = runner.calls.borrow();
+        assert_eq!(
+            calls[0],
+            vec![
+                "scan.jpg",
+                "-background",
+                "white",
+                "-deskew",
+                "40%",
+                "+repage",
+                "-print",
+                "%[deskew:angle]\\n",
+                "-contrast-stretch",
+                "1%x1%",
+                "-level",
+                "0%,85%",
+                "-colorspace",
+                "Gray",
+                "-threshold",
+                "60%",
+                "clean.png"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cleanup_without_deskew_has_no_angle() {
+        let runner = MockCommandRunner {
+            calls: RefCell::new(Vec::new()),
+        };
+        let mut options = DocumentCleanupOptions::new("scan.jpg", "clean.png");
+        options.deskew = None;
+
+        let cleaned = DocumentCleanup::new(&runner, None).run(&options).unwrap();
+
+        assert_eq!(cleaned.deskew_angle, None);
+        assert!(!runner.calls.borrow()[0].contains(&"-deskew".to_string()));
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        let mut options = DocumentCleanupOptions::new("scan.jpg", "clean.png");
+        options.deskew = None;
+        options.contrast_stretch = None;
+        options.whiten_background = None;
+        assert!(build_args(&options).is_err());
+
+        options.binarize = Some(150.0);
+        assert!(build_args(&options).is_err());
+
+        options.binarize = None;
+        options.contrast_stretch = Some(ContrastStretch {
+            black_point: 60.0,
+            white_point: 40.0,
+        });
+        assert!(build_args(&options).is_err());
+
+        options.contrast_stretch = None;
+        options.whiten_background = Some(0.0);
+        assert!(build_args(&options).is_err());
+    }
+}
@@ -0,0 +1,356 @@
+use crate::feature::config::LimitsConfig;
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::identify;
+use crate::feature::shell::CommandRunner;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Operators which resample the image, changing its pixel count
+const RESAMPLING_OPERATORS: &[&str] = &[
+    "-resize",
+    "-scale",
+    "-sample",
+    "-thumbnail",
+    "-adaptive-resize",
+    "-extent",
+];
+
+/// Operators which read a neighbourhood or evaluate an expression for every pixel
+const HEAVY_OPERATORS: &[&str] = &[
+    "-adaptive-blur",
+    "-adaptive-sharpen",
+    "-blur",
+    "-convolve",
+    "-distort",
+    "-fx",
+    "-gaussian-blur",
+    "-liquid-rescale",
+    "-morphology",
+    "-motion-blur",
+    "-sharpen",
+    "-unsharp",
+];
+
+/// Nanoseconds spent per pixel reading, writing, or applying a simple operator
+const LIGHT_NS_PER_PIXEL: f64 = 10.0;
+
+/// Nanoseconds spent per pixel applying a heavy operator
+const HEAVY_NS_PER_PIXEL: f64 = 150.0;
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+/// Options for estimating the cost of a command
+#[derive(Debug, Clone)]
+pub struct EstimateOptions {
+    /// ImageMagick command arguments, as passed to the magick tool
+    pub command: String,
+    /// Width of the input, read from the input files when omitted
+    pub width: Option<u32>,
+    /// Height of the input, read from the input files when omitted
+    pub height: Option<u32>,
+    /// Bits per channel in the pixel cache, 16 for the common Q16 build
+    pub depth: u32,
+    /// Channels per pixel, 4 for RGBA
+    pub channels: u32,
+}
+
+impl EstimateOptions {
+    /// Create options for a Q16 RGBA build, reading input sizes from the files
+    pub fn new(command: &str) -> Self {
+        EstimateOptions {
+            command: command.to_string(),
+            width: None,
+            height: None,
+            depth: 16,
+            channels: 4,
+        }
+    }
+}
+
+/// Whether a command is cheap enough to run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EstimateVerdict {
+    Ok,
+    /// Above a warning threshold; consider downscaling first
+    Warn,
+    /// Above a maximum threshold; the command will not be run
+    Refuse,
+}
+
+/// Predicted memory and time cost of a command
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostEstimate {
+    /// Pixels across every input
+    pub input_pixels: u64,
+    /// Largest pixel count the command reaches after resampling
+    pub peak_pixels: u64,
+    /// Pixel cache memory for the inputs and the largest intermediate image
+    pub memory_bytes: u64,
+    pub memory_mb: f64,
+    pub seconds: f64,
+    pub verdict: EstimateVerdict,
+    /// Thresholds which were exceeded
+    pub warnings: Vec<String>,
+}
+
+/// Operation which predicts the cost of a command from ImageMagick's pixel cache model
+///
+/// Every image held in the cache takes width × height × channels × depth / 8 bytes. Time is a
+/// rough per-pixel rate for each operator, weighted up for neighbourhood operators like blurs.
+pub struct Estimator<'a> {
+    magick_runner: MagickRunner<'a>,
+    base: PathBuf,
+    limits: LimitsConfig,
+}
+
+impl<'a> Estimator<'a> {
+    /// Create a new Estimator with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Estimator {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+            base: workspace
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(".")),
+            limits: LimitsConfig::default(),
+        }
+    }
+
+    /// Use the provided thresholds to decide the verdict
+    pub fn with_limits(mut self, limits: LimitsConfig) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Estimate the cost of a command
+    ///
+    /// # Returns
+    ///
+    /// Returns the `CostEstimate`, or an `OperationError` if the inputs cannot be identified
+    pub fn estimate(&self, options: &EstimateOptions) -> Result<CostEstimate, OperationError> {
+        if options.depth == 0 || options.channels == 0 {
+            return Err(OperationError::InvalidParameter(
+                "depth and channels must be greater than zero".to_string(),
+            ));
+        }
+        let args: Vec<&str> = options.command.split_whitespace().collect();
+        let input_pixels = match (options.width, options.height) {
+            (Some(width), Some(height)) => (width as u64).saturating_mul(height as u64),
+            (None, None) => self.input_pixels(&args)?,
+            _ => {
+                return Err(OperationError::InvalidParameter(
+                    "width and height must be given together".to_string(),
+                ));
+            }
+        };
+
+        let mut current = input_pixels;
+        let mut peak = input_pixels;
+        let mut nanos = 2.0 * LIGHT_NS_PER_PIXEL * input_pixels as f64;
+        for (index, arg) in args.iter().enumerate() {
+            if RESAMPLING_OPERATORS.contains(arg)
+                && let Some(geometry) = args.get(index + 1)
+            {
+                current = resampled_pixels(current, geometry);
+                peak = peak.max(current);
+            }
+            if HEAVY_OPERATORS.contains(arg) {
+                nanos += HEAVY_NS_PER_PIXEL * current as f64;
+            } else if arg.starts_with('-') || arg.starts_with('+') {
+                nanos += LIGHT_NS_PER_PIXEL * current as f64;
+            }
+        }
+
+        let bytes_per_pixel = options.channels as u64 * options.depth.div_ceil(8) as u64;
+        let memory_bytes = input_pixels
+            .saturating_add(peak)
+            .saturating_mul(bytes_per_pixel);
+        let seconds = nanos / 1e9;
+        let (mut verdict, mut warnings) = self.limits.check(memory_bytes, seconds);
+        // A saturated count means the real cost is beyond anything the limits could allow
+        if memory_bytes == u64::MAX {
+            verdict = EstimateVerdict::Refuse;
+            warnings.push("image size overflows the pixel count".to_string());
+        }
+        Ok(CostEstimate {
+            input_pixels,
+            peak_pixels: peak,
+            memory_bytes,
+            memory_mb: memory_bytes as f64 / BYTES_PER_MB as f64,
+            seconds,
+            verdict,
+            warnings,
+        })
+    }
+
+    /// Total pixels of the input files named in a command, excluding its output
+    fn input_pixels(&self, args: &[&str]) -> Result<u64, OperationError> {
+        let mut pixels = 0;
+        for arg in args.iter().take(args.len().saturating_sub(1)) {
+            let file = arg.split('[').next().unwrap_or(arg);
+            if arg.starts_with('-') || !self.base.join(file).is_file() {
+                continue;
+            }
+            let dimensions = identify::dimensions(&self.magick_runner, file)?;
+            pixels = (dimensions.width as u64)
+                .saturating_mul(dimensions.height as u64)
+                .saturating_add(pixels);
+        }
+        if pixels == 0 {
+            return Err(OperationError::InvalidParameter(
+                "no input files found in the command; pass width and height".to_string(),
+            ));
+        }
+        Ok(pixels)
+    }
+}
+
+impl LimitsConfig {
+    /// Compare a cost against the thresholds, returning the verdict and the exceeded thresholds
+    pub(crate) fn check(&self, memory_bytes: u64, seconds: f64) -> (EstimateVerdict, Vec<String>) {
+        let memory_mb = memory_bytes / BYTES_PER_MB;
+        let mut verdict = EstimateVerdict::Ok;
+        let mut warnings = Vec::new();
+        let mut exceeded = |level: EstimateVerdict, message: String| {
+            if level == EstimateVerdict::Refuse || verdict == EstimateVerdict::Ok {
+                verdict = level;
+            }
+            warnings.push(message);
+        };
+        if let Some(max) = self.max_memory_mb.filter(|max| memory_mb > *max) {
+            exceeded(
+                EstimateVerdict::Refuse,
+                format!("memory {memory_mb} MB exceeds max_memory_mb {max}"),
+            );
+        } else if let Some(warn) = self.warn_memory_mb.filter(|warn| memory_mb > *warn) {
+            exceeded(
+                EstimateVerdict::Warn,
+                format!("memory {memory_mb} MB exceeds warn_memory_mb {warn}"),
+            );
+        }
+        if let Some(max) = self.max_seconds.filter(|max| seconds > *max) {
+            exceeded(
+                EstimateVerdict::Refuse,
+                format!("time {seconds:.1}s exceeds max_seconds {max}"),
+            );
+        } else if let Some(warn) = self.warn_seconds.filter(|warn| seconds > *warn) {
+            exceeded(
+                EstimateVerdict::Warn,
+                format!("time {seconds:.1}s exceeds warn_seconds {warn}"),
+            );
+        }
+        (verdict, warnings)
+    }
+}
+
+/// Pixel count after applying a resize geometry such as `50%`, `800x600`, or `800x`
+///
+/// Unrecognized geometries leave the count unchanged, and counts too large for a `u64`
+/// saturate at `u64::MAX`.
+fn resampled_pixels(pixels: u64, geometry: &str) -> u64 {
+    let geometry = geometry.trim_end_matches(['!', '>', '<', '^']);
+    if let Some(percent) = geometry.strip_suffix('%') {
+        let scale = percent.parse::<f64>().unwrap_or(100.0) / 100.0;
+        return (pixels as f64 * scale * scale) as u64;
+    }
+    let (width, height) = geometry.split_once('x').unwrap_or((geometry, ""));
+    match (width.parse::<u64>().ok(), height.parse::<u64>().ok()) {
+        (Some(width), Some(height)) => width.saturating_mul(height),
+        // A single dimension keeps the aspect ratio, so treat the image as square
+        (Some(side), None) | (None, Some(side)) => side.saturating_mul(side),
+        (None, None) => pixels,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::fs;
+    use tempfile::TempDir;
+
+    struct MockCommandRunner {
+        output: String,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            _args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            Ok(self.output.clone())
+        }
+    }
+
+    #[test]
+    fn test_estimate_reads_input_dimensions() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("in.png"), b"").unwrap();
+        let runner = MockCommandRunner {
+            output: "1000 1000".to_string(),
+        };
+        let estimator = Estimator::new(&runner, Some(dir.path()));
+
+        let estimate = estimator
+            .estimate(&EstimateOptions::new("in.png -resize 200% out.png"))
+            .unwrap();
+
+        assert_eq!(estimate.input_pixels, 1_000_000);
+        assert_eq!(estimate.peak_pixels, 4_000_000);
+        assert_eq!(estimate.memory_bytes, 5_000_000 * 8);
+        assert_eq!(estimate.verdict, EstimateVerdict::Ok);
+    }
+
+    #[test]
+    fn test_estimate_applies_limits() {
+        let runner = MockCommandRunner {
+            output: String::new(),
+        };
+        let estimator = Estimator::new(&runner, None).with_limits(LimitsConfig {
+            warn_memory_mb: Some(100),
+            max_memory_mb: Some(1000),
+            warn_seconds: Some(1.0),
+            ..LimitsConfig::default()
+        });
+        let mut options = EstimateOptions::new("in.png -blur 0x8 out.png");
+        options.width = Some(10_000);
+        options.height = Some(10_000);
+
+        let estimate = estimator.estimate(&options).unwrap();
+
+        assert_eq!(estimate.verdict, EstimateVerdict::Refuse);
+        assert_eq!(estimate.warnings.len(), 2);
+
+        options.width = Some(100);
+        options.height = Some(100);
+        assert_eq!(
+            estimator.estimate(&options).unwrap().verdict,
+            EstimateVerdict::Ok
+        );
+    }
+
+    #[test]
+    fn test_estimate_without_inputs() {
+        let runner = MockCommandRunner {
+            output: String::new(),
+        };
+        let estimator = Estimator::new(&runner, None);
+        assert!(
+            estimator
+                .estimate(&EstimateOptions::new("missing.png out.png"))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_resampled_pixels() {
+        assert_eq!(resampled_pixels(400, "50%"), 100);
+        assert_eq!(resampled_pixels(400, "30x20!"), 600);
+        assert_eq!(resampled_pixels(400, "10x"), 100);
+        assert_eq!(resampled_pixels(400, "+repage"), 400);
+        assert_eq!(resampled_pixels(400, "4294967296x4294967296"), u64::MAX);
+    }
+}
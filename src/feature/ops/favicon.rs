@@ -0,0 +1,228 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::shell::CommandRunner;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default directory the favicon set is written to, relative to the workspace
+pub const DEFAULT_FAVICON_DIR: &str = "favicons";
+
+/// PNG icons in a favicon set, as (file name, pixel size)
+const FAVICON_PNGS: &[(&str, u32)] = &[
+    ("favicon-16x16.png", 16),
+    ("favicon-32x32.png", 32),
+    ("favicon-48x48.png", 48),
+    ("apple-touch-icon.png", 180),
+    ("android-chrome-192x192.png", 192),
+    ("android-chrome-512x512.png", 512),
+];
+
+/// Sizes embedded in `favicon.ico`
+const ICO_SIZES: [u32; 3] = [16, 32, 48];
+
+/// Options for generating a favicon set
+#[derive(Debug, Clone)]
+pub struct FaviconOptions {
+    /// Path to the source image, ideally square and at least 512px
+    pub input: String,
+    /// Directory to write the favicon set into
+    pub output_dir: String,
+}
+
+impl FaviconOptions {
+    /// Create options writing the set into the default directory
+    pub fn new(input: &str) -> Self {
+        FaviconOptions {
+            input: input.to_string(),
+            output_dir: DEFAULT_FAVICON_DIR.to_string(),
+        }
+    }
+}
+
+/// A file written by the favicon generator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaviconFile {
+    pub path: PathBuf,
+    /// Pixel sizes in the file, several for `favicon.ico`
+    pub sizes: Vec<u32>,
+}
+
+/// Operation which generates a favicon set from one source image
+pub struct Favicon<'a> {
+    magick_runner: MagickRunner<'a>,
+    base: PathBuf,
+}
+
+impl<'a> Favicon<'a> {
+    /// Create a new Favicon with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Favicon {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+            base: workspace
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(".")),
+        }
+    }
+
+    /// Generate the PNG icons and a multi-size `favicon.ico`
+    ///
+    /// Sources which are not square are centered on a transparent square canvas.
+    ///
+    /// # Returns
+    ///
+    /// Returns every written file, or an `OperationError` on failure
+    pub fn run(&self, options: &FaviconOptions) -> Result<Vec<FaviconFile>, OperationError> {
+        let dir = Path::new(&options.output_dir);
+        fs::create_dir_all(self.base.join(dir))?;
+
+        let mut files = Vec::new();
+        for (name, pixels) in FAVICON_PNGS {
+            let path = dir.join(name);
+            self.execute(png_args(&options.input, *pixels, &path.to_string_lossy()))?;
+            files.push(FaviconFile {
+                path,
+                sizes: vec![*pixels],
+            });
+        }
+
+        let ico = dir.join("favicon.ico");
+        self.execute(ico_args(&options.input, &ico.to_string_lossy()))?;
+        files.push(FaviconFile {
+            path: ico,
+            sizes: ICO_SIZES.to_vec(),
+        });
+        Ok(files)
+    }
+
+    /// Run `magick` with owned arguments
+    fn execute(&self, args: Vec<String>) -> Result<(), OperationError> {
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        Ok(())
+    }
+}
+
+/// Arguments for a square PNG icon
+///
+/// The Apple touch icon is flattened onto white, since iOS fills transparency with black.
+fn png_args(input: &str, pixels: u32, output: &str) -> Vec<String> {
+    let size = format!("{pixels}x{pixels}");
+    let mut args = vec![
+        input.to_string(),
+        "-resize".to_string(),
+        size.clone(),
+        "-background".to_string(),
+        "none".to_string(),
+        "-gravity".to_string(),
+        "center".to_string(),
+        "-extent".to_string(),
+        size,
+    ];
+    if output.ends_with("apple-touch-icon.png") {
+        args.extend([
+            "-background".to_string(),
+            "white".to_string(),
+            "-alpha".to_string(),
+            "remove".to_string(),
+            "-alpha".to_string(),
+            "off".to_string(),
+        ]);
+    }
+    args.extend(["-strip".to_string(), output.to_string()]);
+    args
+}
+
+/// Arguments for a `favicon.ico` holding every ICO size
+fn ico_args(input: &str, output: &str) -> Vec<String> {
+    let largest = ICO_SIZES[ICO_SIZES.len() - 1];
+    let sizes: Vec<String> = ICO_SIZES.iter().rev().map(u32::to_string).collect();
+    vec![
+        input.to_string(),
+        "-resize".to_string(),
+        format!("{largest}x{largest}"),
+        "-background".to_string(),
+        "none".to_string(),
+        "-gravity".to_string(),
+        "center".to_string(),
+        "-extent".to_string(),
+        format!("{largest}x{largest}"),
+        "-define".to_string(),
+        format!("icon:auto-resize={}", sizes.join(",")),
+        output.to_string(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+    use tempfile::TempDir;
+
+    /// Mock implementation of CommandRunner recording every command
+    struct MockCommandRunner {
+        calls: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|arg| arg.to_string()).collect());
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_favicon_set() {
+        let dir = TempDir::new().unwrap();
+        let runner = MockCommandRunner {
+            calls: RefCell::new(Vec::new()),
+        };
+
+        let files = Favicon::new(&runner, Some(dir.path()))
+            .run(&FaviconOptions::new("logo.png"))
+            .unwrap();
+
+        assert_eq!(files.len(), 7);
+        assert_eq!(
+            files[3].path,
+            PathBuf::from("favicons/apple-touch-icon.png")
+        );
+        assert_eq!(files[6].path, PathBuf::from("favicons/favicon.ico"));
+        assert_eq!(files[6].sizes, [16, 32, 48]);
+        assert!(dir.path().join(DEFAULT_FAVICON_DIR).is_dir());
+
+        let calls = runner.calls.borrow();
+        assert!(calls[3].contains(&"remove".to_string()));
+        assert!(!calls[5].contains(&"remove".to_string()));
+        assert!(calls[6].contains(&"icon:auto-resize=48,32,16".to_string()));
+    }
+
+    #[test]
+    fn test_png_args_pad_to_square() {
+        let args = png_args("logo.png", 32, "favicon-32x32.png");
+
+        assert_eq!(
+            args,
+            [
+                "logo.png",
+                "-resize",
+                "32x32",
+                "-background",
+                "none",
+                "-gravity",
+                "center",
+                "-extent",
+                "32x32",
+                "-strip",
+                "favicon-32x32.png"
+            ]
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// Largest number of despeckle passes
+const MAX_DESPECKLE_PASSES: u32 = 10;
+
+/// Largest denoise median filter radius, giving a 51x51 window
+const MAX_DENOISE_RADIUS: u32 = 25;
+
+/// A blurring, sharpening, or noise reducing filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterOperation {
+    /// Gaussian blur
+    Blur,
+    /// Sharpen edges
+    Sharpen,
+    /// Unsharp mask, sharpening only edges with enough contrast
+    Unsharp,
+    /// Remove speckles while keeping edges
+    Despeckle,
+    /// Median filter removing salt and pepper noise
+    Denoise,
+}
+
+impl FilterOperation {
+    /// Parse an operation name
+    pub fn parse(name: &str) -> Result<Self, OperationError> {
+        match name {
+            "blur" => Ok(FilterOperation::Blur),
+            "sharpen" => Ok(FilterOperation::Sharpen),
+            "unsharp" => Ok(FilterOperation::Unsharp),
+            "despeckle" => Ok(FilterOperation::Despeckle),
+            "denoise" => Ok(FilterOperation::Denoise),
+            other => Err(OperationError::InvalidParameter(format!(
+                "unknown operation '{other}', expected blur, sharpen, unsharp, despeckle, or denoise"
+            ))),
+        }
+    }
+
+    /// Strength used when none is given
+    fn default_strength(self) -> f64 {
+        match self {
+            FilterOperation::Blur => 2.0,
+            FilterOperation::Sharpen
+            | FilterOperation::Unsharp
+            | FilterOperation::Despeckle
+            | FilterOperation::Denoise => 1.0,
+        }
+    }
+}
+
+/// Options for blurring, sharpening, or denoising an image
+#[derive(Debug, Clone)]
+pub struct FilterOptions {
+    /// Path to the source image
+    pub input: String,
+    /// Path to write the filtered image to
+    pub output: String,
+    /// The filter to apply
+    pub operation: FilterOperation,
+    /// How strongly to filter: the Gaussian sigma in pixels for blur, sharpen, and unsharp, the
+    /// number of passes for despeckle, and the radius in pixels for denoise. Uses a moderate
+    /// default for the operation when `None`
+    pub strength: Option<f64>,
+    /// For unsharp, how much of the difference is added back, defaults to 1
+    pub amount: Option<f64>,
+    /// For unsharp, the fraction of the range an edge's contrast must exceed to be
+    /// sharpened, defaults to 0.05
+    pub threshold: Option<f64>,
+}
+
+impl FilterOptions {
+    /// Create options applying the operation at its default strength
+    pub fn new(input: &str, output: &str, operation: FilterOperation) -> Self {
+        FilterOptions {
+            input: input.to_string(),
+            output: output.to_string(),
+            operation,
+            strength: None,
+            amount: None,
+            threshold: None,
+        }
+    }
+}
+
+/// Operation which blurs, sharpens, or denoises an image
+pub struct Filter<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> Filter<'a> {
+    /// Create a new Filter with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Filter {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Apply the filter and write the output image
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output on success, or an `OperationError` on failure
+    pub fn run(&self, options: &FilterOptions) -> Result<ResolvedOutput, OperationError> {
+        let args = build_args(options)?;
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        Ok(self.magick_runner.last_resolved_output(&options.output))
+    }
+}
+
+/// Build the `magick` arguments applying the filter
+///
+/// Blur, sharpen, and unsharp take a radius of 0 so ImageMagick picks the radius which suits
+/// the sigma.
+fn build_args(options: &FilterOptions) -> Result<Vec<String>, OperationError> {
+    let strength = options
+        .strength
+        .unwrap_or_else(|| options.operation.default_strength());
+    if !strength.is_finite() || strength <= 0.0 {
+        return Err(OperationError::InvalidParameter(format!(
+            "strength {strength} must be greater than 0"
+        )));
+    }
+    if options.operation != FilterOperation::Unsharp
+        && (options.amount.is_some() || options.threshold.is_some())
+    {
+        return Err(OperationError::InvalidParameter(
+            "amount and threshold only apply to unsharp".to_string(),
+        ));
+    }
+
+    let mut args = vec![options.input.clone()];
+    match options.operation {
+        FilterOperation::Blur => args.extend(["-blur".to_string(), format!("0x{strength}")]),
+        FilterOperation::Sharpen => args.extend(["-sharpen".to_string(), format!("0x{strength}")]),
+        FilterOperation::Unsharp => {
+            let amount = options.amount.unwrap_or(1.0);
+            let threshold = options.threshold.unwrap_or(0.05);
+            if amount < 0.0 || !(0.0..=1.0).contains(&threshold) {
+                return Err(OperationError::InvalidParameter(
+                    "amount must be at least 0 and threshold between 0 and 1".to_string(),
+                ));
+            }
+            args.extend([
+                "-unsharp".to_string(),
+                format!("0x{strength}+{amount}+{threshold}"),
+            ]);
+        }
+        FilterOperation::Despeckle => {
+            let passes = strength.round() as u32;
+            if !(1..=MAX_DESPECKLE_PASSES).contains(&passes) {
+                return Err(OperationError::InvalidParameter(format!(
+                    "despeckle strength is a number of passes from 1 to {MAX_DESPECKLE_PASSES}"
+                )));
+            }
+            args.extend((0..passes).map(|_| "-despeckle".to_string()));
+        }
+        FilterOperation::Denoise => {
+            let radius = strength.round() as u32;
+            if !(1..=MAX_DENOISE_RADIUS).contains(&radius) {
+                return Err(OperationError::InvalidParameter(format!(
+                    "denoise strength is a filter radius from 1 to {MAX_DENOISE_RADIUS}"
+                )));
+            }
+            let size = radius * 2 + 1;
+            args.extend([
+                "-statistic".to_string(),
+                "Median".to_string(),
+                format!("{size}x{size}"),
+            ]);
+        }
+    }
+    args.push(options.output.clone());
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(operation: FilterOperation, strength: Option<f64>) -> Vec<String> {
+        let mut options = FilterOptions::new("in.png", "out.png", operation);
+        options.strength = strength;
+        build_args(&options).unwrap()
+    }
+
+    #[test]
+    fn test_build_args_for_each_operation() {
+        assert_eq!(
+            args(FilterOperation::Blur, None),
+            ["in.png", "-blur", "0x2", "out.png"]
+        );
+        assert_eq!(
+            args(FilterOperation::Sharpen, Some(1.5)),
+            ["in.png", "-sharpen", "0x1.5", "out.png"]
+        );
+        assert_eq!(
+            args(FilterOperation::Unsharp, None),
+            ["in.png", "-unsharp", "0x1+1+0.05", "out.png"]
+        );
+        assert_eq!(
+            args(FilterOperation::Despeckle, Some(2.0)),
+            ["in.png", "-despeckle", "-despeckle", "out.png"]
+        );
+        assert_eq!(
+            args(FilterOperation::Denoise, Some(2.0)),
+            ["in.png", "-statistic", "Median", "5x5", "out.png"]
+        );
+    }
+
+    #[test]
+    fn test_unsharp_amount_and_threshold() {
+        let mut options = FilterOptions::new("in.png", "out.png", FilterOperation::Unsharp);
+        options.strength = Some(0.8);
+        options.amount = Some(1.5);
+        options.threshold = Some(0.0);
+
+        assert_eq!(build_args(&options).unwrap()[2], "0x0.8+1.5+0");
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        assert!(FilterOperation::parse("emboss").is_err());
+
+        let mut options = FilterOptions::new("in.png", "out.png", FilterOperation::Blur);
+        options.strength = Some(0.0);
+        assert!(build_args(&options).is_err());
+
+        let mut options = FilterOptions::new("in.png", "out.png", FilterOperation::Blur);
+        options.amount = Some(1.0);
+        assert!(build_args(&options).is_err());
+
+        let mut options = FilterOptions::new("in.png", "out.png", FilterOperation::Despeckle);
+        options.strength = Some(50.0);
+        assert!(build_args(&options).is_err());
+    }
+
+    #[test]
+    fn test_denoise_radius_bounds() {
+        let mut options = FilterOptions::new("in.png", "out.png", FilterOperation::Denoise);
+        for strength in [0.4, 25.6, 1e12, f64::MAX] {
+            options.strength = Some(strength);
+            assert!(matches!(
+                build_args(&options),
+                Err(OperationError::InvalidParameter(_))
+            ));
+        }
+
+        assert_eq!(
+            args(FilterOperation::Denoise, Some(0.5)),
+            ["in.png", "-statistic", "Median", "3x3", "out.png"]
+        );
+        assert_eq!(
+            args(FilterOperation::Denoise, Some(25.0)),
+            ["in.png", "-statistic", "Median", "51x51", "out.png"]
+        );
+    }
+}
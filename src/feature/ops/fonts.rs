@@ -0,0 +1,120 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use serde::Serialize;
+
+/// A font known to the installed ImageMagick
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FontInfo {
+    /// Name passed to `-font`, e.g. `DejaVu-Sans-Bold`
+    pub name: String,
+    /// Font family, e.g. `DejaVu Sans`
+    pub family: Option<String>,
+    /// Style such as `Normal` or `Italic`
+    pub style: Option<String>,
+    /// Stretch such as `Normal` or `Condensed`
+    pub stretch: Option<String>,
+    /// Weight from 100 to 900, where 400 is regular and 700 bold
+    pub weight: Option<u32>,
+    /// Path to the font file
+    pub glyphs: Option<String>,
+}
+
+/// Parse the output of `magick -list font`
+///
+/// Each font starts with a `Font: <name>` line followed by indented `key: value` properties.
+/// Lines naming the type configuration file a group of fonts came from are skipped.
+pub(crate) fn parse_fonts(output: &str) -> Vec<FontInfo> {
+    let mut fonts: Vec<FontInfo> = Vec::new();
+    for line in output.lines() {
+        let Some((key, value)) = line.trim().split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if key == "Font" {
+            fonts.push(FontInfo {
+                name: value.to_string(),
+                ..FontInfo::default()
+            });
+            continue;
+        }
+        let Some(font) = fonts.last_mut() else {
+            continue;
+        };
+        let value = Some(value.to_string()).filter(|value| !value.is_empty());
+        match key {
+            "family" => font.family = value,
+            "style" => font.style = value,
+            "stretch" => font.stretch = value,
+            "weight" => font.weight = value.and_then(|weight| weight.parse().ok()),
+            "glyphs" => font.glyphs = value,
+            _ => {}
+        }
+    }
+    fonts
+}
+
+/// List the fonts available to the installed ImageMagick
+pub(crate) fn list_fonts(magick_runner: &MagickRunner) -> Result<Vec<FontInfo>, OperationError> {
+    let output = magick_runner.execute_args(&["-list", "font"])?;
+    Ok(parse_fonts(&output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIST_OUTPUT: &str = "
+  Path: /etc/ImageMagick-7/type-dejavu.xml
+  Font: DejaVu-Sans
+    family: DejaVu Sans
+    style: Normal
+    stretch: Normal
+    weight: 400
+    glyphs: /usr/share/fonts/truetype/dejavu/DejaVuSans.ttf
+
+  Font: DejaVu-Sans-Bold-Oblique
+    family: DejaVu Sans
+    style: Oblique
+    stretch: Normal
+    weight: 700
+    glyphs: /usr/share/fonts/truetype/dejavu/DejaVuSans-BoldOblique.ttf
+
+  Path: System Fonts
+  Font: Noto-Serif-CJK-JP
+    family: Noto Serif CJK JP
+    glyphs: C:/Windows/Fonts/NotoSerifCJK.ttc
+";
+
+    #[test]
+    fn test_parse_fonts() {
+        let fonts = parse_fonts(LIST_OUTPUT);
+
+        let names: Vec<&str> = fonts.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "DejaVu-Sans",
+                "DejaVu-Sans-Bold-Oblique",
+                "Noto-Serif-CJK-JP"
+            ]
+        );
+        assert_eq!(
+            fonts[1],
+            FontInfo {
+                name: "DejaVu-Sans-Bold-Oblique".to_string(),
+                family: Some("DejaVu Sans".to_string()),
+                style: Some("Oblique".to_string()),
+                stretch: Some("Normal".to_string()),
+                weight: Some(700),
+                glyphs: Some(
+                    "/usr/share/fonts/truetype/dejavu/DejaVuSans-BoldOblique.ttf".to_string()
+                ),
+            }
+        );
+        assert_eq!(fonts[2].style, None);
+        assert_eq!(
+            fonts[2].glyphs.as_deref(),
+            Some("C:/Windows/Fonts/NotoSerifCJK.ttc")
+        );
+    }
+}
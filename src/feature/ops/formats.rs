@@ -0,0 +1,94 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+
+/// An image format known to the installed ImageMagick
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageFormat {
+    /// Format name, e.g. `PNG`
+    pub name: String,
+    /// Human readable description
+    pub description: String,
+    /// Whether ImageMagick can read the format
+    pub readable: bool,
+    /// Whether ImageMagick can write the format
+    pub writable: bool,
+    /// Whether the format supports multiple frames in one file
+    pub multi_frame: bool,
+}
+
+impl ImageFormat {
+    /// Parse one row of `magick -list format`, e.g. `PNG* PNG rw- Portable Network Graphics`
+    ///
+    /// Returns `None` for headers, separators, and continuation lines.
+    fn parse_row(line: &str) -> Option<Self> {
+        let mut parts = line.split_whitespace();
+        let name = parts.next()?.trim_end_matches(['*', '+']);
+        let _module = parts.next()?;
+        let mode = parts.next()?.as_bytes();
+        let valid_mode = mode.len() == 3
+            && matches!(mode[0], b'r' | b'-')
+            && matches!(mode[1], b'w' | b'-')
+            && matches!(mode[2], b'+' | b'-');
+        if !valid_mode || name.is_empty() {
+            return None;
+        }
+        Some(ImageFormat {
+            name: name.to_string(),
+            description: parts.collect::<Vec<_>>().join(" "),
+            readable: mode[0] == b'r',
+            writable: mode[1] == b'w',
+            multi_frame: mode[2] == b'+',
+        })
+    }
+}
+
+/// Parse the output of `magick -list format`
+pub(crate) fn parse_formats(output: &str) -> Vec<ImageFormat> {
+    output.lines().filter_map(ImageFormat::parse_row).collect()
+}
+
+/// List the image formats supported by the installed ImageMagick
+pub(crate) fn list_formats(
+    magick_runner: &MagickRunner,
+) -> Result<Vec<ImageFormat>, OperationError> {
+    let output = magick_runner.execute_args(&["-list", "format"])?;
+    Ok(parse_formats(&output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIST_OUTPUT: &str = "   Format  Module    Mode  Description
+-------------------------------------------------------------------------------
+      3FR  DNG       r--   Hasselblad CFV/H3D39II Raw Format (0.21.2-Release)
+     AVIF  HEIC      rw+   AV1 Image File Format (1.17.6)
+      GIF* GIF       rw+   CompuServe graphics interchange format
+     JPEG* JPEG      rw-   Joint Photographic Experts Group JFIF format (libjpeg-turbo 2.1.5)
+      PNG* PNG       rw-   Portable Network Graphics (libpng 1.6.43)
+                           See http://www.libpng.org/ for details about the PNG format.
+
+* native blob support
+r read support
+";
+
+    #[test]
+    fn test_parse_formats() {
+        let formats = parse_formats(LIST_OUTPUT);
+
+        let names: Vec<&str> = formats.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["3FR", "AVIF", "GIF", "JPEG", "PNG"]);
+        assert_eq!(
+            formats[4],
+            ImageFormat {
+                name: "PNG".to_string(),
+                description: "Portable Network Graphics (libpng 1.6.43)".to_string(),
+                readable: true,
+                writable: true,
+                multi_frame: false,
+            }
+        );
+        assert!(!formats[0].writable);
+        assert!(formats[2].multi_frame);
+    }
+}
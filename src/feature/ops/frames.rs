@@ -0,0 +1,290 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::shell::CommandRunner;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Options for extracting the frames of a multi-frame image
+#[derive(Debug, Clone)]
+pub struct FramesOptions {
+    /// Path to the GIF, animated WebP, multi-page TIFF, or other multi-frame image
+    pub input: String,
+    /// Directory the frames are written to
+    pub output_dir: String,
+    /// Index of the first frame to extract, defaults to the first
+    pub start: Option<u32>,
+    /// Index of the last frame to extract, inclusive, defaults to the last
+    pub end: Option<u32>,
+    /// Render each frame fully rather than as the change from the previous frame
+    pub coalesce: bool,
+    /// File extension of the frames, defaults to `png`
+    pub format: Option<String>,
+}
+
+impl FramesOptions {
+    /// Create options extracting every frame, coalesced, as PNG
+    pub fn new(input: &str, output_dir: &str) -> Self {
+        FramesOptions {
+            input: input.to_string(),
+            output_dir: output_dir.to_string(),
+            start: None,
+            end: None,
+            coalesce: true,
+            format: None,
+        }
+    }
+}
+
+/// A single extracted frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedFrame {
+    /// Index of the frame in the input, which its file is numbered by
+    pub index: u32,
+    pub path: PathBuf,
+}
+
+/// Operation which writes the frames of a multi-frame image to numbered files
+pub struct Frames<'a> {
+    magick_runner: MagickRunner<'a>,
+    base: PathBuf,
+}
+
+impl<'a> Frames<'a> {
+    /// Create a new Frames with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Frames {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+            base: workspace
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(".")),
+        }
+    }
+
+    /// Extract the selected frames as `<name>-<index>.<ext>` files
+    ///
+    /// Frames of animations are often stored as changes from the previous frame, so when
+    /// coalescing every frame is read and rendered before the range is selected. Otherwise
+    /// only the range is read, using `input[start-end]`.
+    ///
+    /// # Returns
+    ///
+    /// Returns the extracted frames in order, or an `OperationError` on failure
+    pub fn run(&self, options: &FramesOptions) -> Result<Vec<ExtractedFrame>, OperationError> {
+        let count = self.frame_count(&options.input)?;
+        let (start, end) = frame_range(options.start, options.end, count)?;
+        let input = Path::new(&options.input);
+        let stem = input
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "frame".to_string());
+        let extension = options
+            .format
+            .as_deref()
+            .unwrap_or("png")
+            .trim_start_matches('.')
+            .to_ascii_lowercase();
+        let digits = (count - 1).to_string().len().max(3);
+        fs::create_dir_all(self.base.join(&options.output_dir))?;
+
+        let pattern = Path::new(&options.output_dir)
+            .join(format!(
+                "{}-%0{digits}d.{extension}",
+                stem.replace('%', "%%")
+            ))
+            .to_string_lossy()
+            .to_string();
+        let args = build_args(options, start, end, count, &pattern);
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+
+        (start..=end)
+            .map(|index| {
+                let path = Path::new(&options.output_dir)
+                    .join(format!("{stem}-{index:0digits$}.{extension}"));
+                if !self.base.join(&path).is_file() {
+                    return Err(OperationError::ParseError(format!(
+                        "expected frame {index} to be written to {}",
+                        path.display()
+                    )));
+                }
+                Ok(ExtractedFrame { index, path })
+            })
+            .collect()
+    }
+
+    /// The number of frames in an image
+    fn frame_count(&self, input: &str) -> Result<u32, OperationError> {
+        let output = self
+            .magick_runner
+            .execute_args(&["identify", "-format", "%n\\n", input])?;
+        output
+            .lines()
+            .next()
+            .and_then(|line| line.trim().parse().ok())
+            .filter(|count| *count > 0)
+            .ok_or_else(|| OperationError::ParseError(format!("unexpected frame count: {output}")))
+    }
+}
+
+/// The inclusive range of frames to extract, checked against the number of frames
+fn frame_range(
+    start: Option<u32>,
+    end: Option<u32>,
+    count: u32,
+) -> Result<(u32, u32), OperationError> {
+    let start = start.unwrap_or(0);
+    let end = end.unwrap_or(count - 1);
+    if start > end {
+        return Err(OperationError::InvalidParameter(format!(
+            "start frame {start} is after end frame {end}"
+        )));
+    }
+    if end >= count {
+        return Err(OperationError::InvalidParameter(format!(
+            "frame {end} is out of range, the image has {count} frames (0 to {})",
+            count - 1
+        )));
+    }
+    Ok((start, end))
+}
+
+/// Build the `magick` arguments writing frames `start` to `end` of `count` to `pattern`
+///
+/// `-scene` numbers the files by their index in the input, and `+adjoin` writes each frame to
+/// its own file even in formats which can hold several.
+fn build_args(
+    options: &FramesOptions,
+    start: u32,
+    end: u32,
+    count: u32,
+    pattern: &str,
+) -> Vec<String> {
+    let mut args = Vec::new();
+    if options.coalesce {
+        args.extend([options.input.clone(), "-coalesce".to_string()]);
+        if end + 1 < count {
+            args.extend(["-delete".to_string(), format!("{}--1", end + 1)]);
+        }
+        if start > 0 {
+            args.extend(["-delete".to_string(), format!("0-{}", start - 1)]);
+        }
+    } else {
+        args.push(format!("{}[{start}-{end}]", options.input));
+    }
+    args.extend([
+        "-scene".to_string(),
+        start.to_string(),
+        "+adjoin".to_string(),
+        pattern.to_string(),
+    ]);
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+    use tempfile::TempDir;
+
+    /// Mock implementation of CommandRunner for a 12 frame image, writing three frames numbered
+    /// from the `-scene` number
+    struct MockCommandRunner {
+        calls: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|s| s.to_string()).collect());
+            if args[0] == "identify" {
+                return Ok("12\n12\n".repeat(6));
+            }
+            let scene = args.iter().position(|arg| *arg == "-scene").unwrap();
+            let start: usize = args[scene + 1].parse().unwrap();
+            for index in start..start + 3 {
+                let pattern = args[args.len() - 1].replace("%03d", &format!("{index:03}"));
+                fs::write(working_dir.unwrap().join(pattern), b"").unwrap();
+            }
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_run_extracts_coalesced_range() {
+        let dir = TempDir::new().unwrap();
+        let runner = MockCommandRunner {
+            calls: RefCell::new(Vec::new()),
+        };
+        let mut options = FramesOptions::new("anim/spin.gif", "frames");
+        options.start = Some(2);
+        options.end = Some(4);
+
+        let frames = Frames::new(&runner, Some(dir.path()))
+            .run(&options)
+            .unwrap();
+
+        assert_eq!(
+            frames,
+            vec![
+                ExtractedFrame {
+                    index: 2,
+                    path: PathBuf::from("frames/spin-002.png")
+                },
+                ExtractedFrame {
+                    index: 3,
+                    path: PathBuf::from("frames/spin-003.png")
+                },
+                ExtractedFrame {
+                    index: 4,
+                    path: PathBuf::from("frames/spin-004.png")
+                },
+            ]
+        );
+        assert_eq!(
+            runner.calls.borrow()[1],
+            vec![
+                "anim/spin.gif",
+                "-coalesce",
+                "-delete",
+                "5--1",
+                "-delete",
+                "0-1",
+                "-scene",
+                "2",
+                "+adjoin",
+                "frames/spin-%03d.png"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_args_without_coalesce() {
+        let mut options = FramesOptions::new("scan.tiff", "pages");
+        options.coalesce = false;
+
+        assert_eq!(
+            build_args(&options, 0, 11, 12, "pages/scan-%03d.png"),
+            vec![
+                "scan.tiff[0-11]",
+                "-scene",
+                "0",
+                "+adjoin",
+                "pages/scan-%03d.png"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_frame_range_validation() {
+        assert_eq!(frame_range(None, None, 12).unwrap(), (0, 11));
+        assert!(frame_range(Some(5), Some(4), 12).is_err());
+        assert!(frame_range(None, Some(12), 12).is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// Longest expression accepted for evaluation
+const MAX_EXPRESSION_LEN: usize = 1024;
+
+/// Operation which evaluates an fx expression over one or two images
+pub struct FxEvaluator<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> FxEvaluator<'a> {
+    /// Create a new FxEvaluator with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        FxEvaluator {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Evaluate an expression and return its scalar result
+    ///
+    /// The first image is available as `u` and the optional second image as `v`.
+    ///
+    /// # Arguments
+    ///
+    /// * `expression` - The fx expression, e.g. `mean` or `abs(u.mean-v.mean)`
+    /// * `image` - Path to the first image
+    /// * `second_image` - Optional path to a second image
+    ///
+    /// # Returns
+    ///
+    /// Returns the evaluated value, or an `OperationError` on failure
+    pub fn evaluate(
+        &self,
+        expression: &str,
+        image: &str,
+        second_image: Option<&str>,
+    ) -> Result<f64, OperationError> {
+        sanitize_expression(expression)?;
+        let format = format!("%[fx:{expression}]\\n");
+        let mut args = vec![image];
+        if let Some(second) = second_image {
+            args.push(second);
+        }
+        args.extend(["-format", &format, "info:"]);
+        let output = self.magick_runner.execute_args(&args)?;
+        let first = output.lines().next().unwrap_or("").trim();
+        first
+            .parse()
+            .map_err(|_| OperationError::ParseError(format!("fx result '{first}' is not a number")))
+    }
+}
+
+/// Validate that an expression only contains fx syntax
+///
+/// Percent signs, quotes, and backslashes are rejected because they could escape the
+/// `%[fx:...]` format and read other properties or files, and brackets must balance so the
+/// expression cannot close the escape early.
+fn sanitize_expression(expression: &str) -> Result<(), OperationError> {
+    if expression.trim().is_empty() {
+        return Err(OperationError::InvalidParameter(
+            "expression must not be empty".to_string(),
+        ));
+    }
+    if expression.len() > MAX_EXPRESSION_LEN {
+        return Err(OperationError::InvalidParameter(format!(
+            "expression is longer than {MAX_EXPRESSION_LEN} characters"
+        )));
+    }
+    if let Some(c) = expression
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || " _.+-*/^()<>=!&|?:;,[]".contains(*c)))
+    {
+        return Err(OperationError::InvalidParameter(format!(
+            "expression contains disallowed character '{c}'"
+        )));
+    }
+    let mut depth = 0i32;
+    for c in expression.chars() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            _ => {}
+        }
+        if depth < 0 {
+            break;
+        }
+    }
+    if depth != 0 {
+        return Err(OperationError::InvalidParameter(
+            "expression has unbalanced brackets".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+
+    /// Mock implementation of CommandRunner which records arguments
+    struct MockCommandRunner {
+        output: String,
+        captured_args: RefCell<Vec<String>>,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            *self.captured_args.borrow_mut() = args.iter().map(|s| s.to_string()).collect();
+            Ok(self.output.clone())
+        }
+    }
+
+    fn mock(output: &str) -> MockCommandRunner {
+        MockCommandRunner {
+            output: output.to_string(),
+            captured_args: RefCell::new(Vec::new()),
+        }
+    }
+
+    #[test]
+    fn test_evaluate_single_image() {
+        let runner = mock("0.5123\n");
+        let evaluator = FxEvaluator::new(&runner, None);
+        let value = evaluator.evaluate("mean", "photo.png", None).unwrap();
+
+        assert_eq!(value, 0.5123);
+        assert_eq!(
+            *runner.captured_args.borrow(),
+            vec!["photo.png", "-format", "%[fx:mean]\\n", "info:"]
+        );
+    }
+
+    #[test]
+    fn test_evaluate_two_images() {
+        let runner = mock("0.01\n0.01\n");
+        let evaluator = FxEvaluator::new(&runner, None);
+        let value = evaluator
+            .evaluate("abs(u.mean-v.mean)", "a.png", Some("b.png"))
+            .unwrap();
+
+        assert_eq!(value, 0.01);
+        assert_eq!(runner.captured_args.borrow()[1], "b.png");
+    }
+
+    #[test]
+    fn test_non_numeric_result() {
+        let runner = mock("nan-ish\n");
+        let evaluator = FxEvaluator::new(&runner, None);
+        let result = evaluator.evaluate("mean", "photo.png", None);
+        assert!(matches!(result, Err(OperationError::ParseError(_))));
+    }
+
+    #[test]
+    fn test_sanitize_expression() {
+        assert!(sanitize_expression("u[1].p[0,0].r * 2").is_ok());
+        assert!(sanitize_expression("maxima.r > 0.9 ? 1 : 0").is_ok());
+        assert!(sanitize_expression("").is_err());
+        assert!(sanitize_expression("mean]%[filename").is_err());
+        assert!(sanitize_expression("mean] [x").is_err());
+        assert!(sanitize_expression("'@/etc/passwd'").is_err());
+    }
+}
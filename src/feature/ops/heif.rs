@@ -0,0 +1,353 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::formats::{ImageFormat, list_formats};
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// An image format ImageMagick reads and writes through libheif
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeifFormat {
+    /// HEVC-coded HEIF, as written by iPhones
+    Heic,
+    /// AV1-coded HEIF
+    Avif,
+}
+
+impl HeifFormat {
+    /// The format of a path from its extension, `None` when it is neither HEIC nor AVIF
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "heic" | "heif" | "hif" => Some(HeifFormat::Heic),
+            "avif" => Some(HeifFormat::Avif),
+            _ => None,
+        }
+    }
+
+    /// The name of the format in `magick -list format`
+    pub fn name(self) -> &'static str {
+        match self {
+            HeifFormat::Heic => "HEIC",
+            HeifFormat::Avif => "AVIF",
+        }
+    }
+
+    /// The libheif plugin decoding the format
+    fn decoder(self) -> &'static str {
+        match self {
+            HeifFormat::Heic => "libde265",
+            HeifFormat::Avif => "dav1d or aom",
+        }
+    }
+
+    /// The libheif plugin encoding the format
+    fn encoder(self) -> &'static str {
+        match self {
+            HeifFormat::Heic => "x265",
+            HeifFormat::Avif => "aom, rav1e, or SVT-AV1",
+        }
+    }
+}
+
+/// Whether the installed ImageMagick can read and write a HEIF format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeifSupport {
+    pub format: HeifFormat,
+    pub readable: bool,
+    pub writable: bool,
+}
+
+impl HeifSupport {
+    /// Find the format in the output of `magick -list format`
+    fn probe(format: HeifFormat, formats: &[ImageFormat]) -> Self {
+        let known = formats
+            .iter()
+            .find(|known| known.name.eq_ignore_ascii_case(format.name()));
+        HeifSupport {
+            format,
+            readable: known.is_some_and(|known| known.readable),
+            writable: known.is_some_and(|known| known.writable),
+        }
+    }
+}
+
+/// Options for converting an image to or from HEIC or AVIF
+#[derive(Debug, Clone)]
+pub struct HeifConvertOptions {
+    /// Path to the source image
+    pub input: String,
+    /// Path to write the converted image to, whose extension picks the format
+    pub output: String,
+    /// Quality from 0 to 100
+    pub quality: Option<u32>,
+}
+
+impl HeifConvertOptions {
+    /// Create options converting at ImageMagick's default quality
+    pub fn new(input: &str, output: &str) -> Self {
+        HeifConvertOptions {
+            input: input.to_string(),
+            output: output.to_string(),
+            quality: None,
+        }
+    }
+}
+
+/// The result of a HEIC or AVIF conversion
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeifConversion {
+    /// The written output
+    pub output: ResolvedOutput,
+    /// Size of the output file in bytes
+    pub size: u64,
+    /// Support for each HEIF format involved, as probed before converting
+    pub support: Vec<HeifSupport>,
+}
+
+/// Operation which converts images to and from HEIC and AVIF, checking for the delegates first
+pub struct HeifConvert<'a> {
+    magick_runner: MagickRunner<'a>,
+    base: PathBuf,
+}
+
+impl<'a> HeifConvert<'a> {
+    /// Create a new HeifConvert with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        HeifConvert {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+            base: workspace
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(".")),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Convert the image once the delegates it needs are known to be installed
+    ///
+    /// # Returns
+    ///
+    /// Returns the written file, its size, and the probed support, or
+    /// `OperationError::MissingDependency` with installation guidance when ImageMagick cannot
+    /// read the input or write the output
+    pub fn run(&self, options: &HeifConvertOptions) -> Result<HeifConversion, OperationError> {
+        let args = build_args(options)?;
+        let support = self.check_support(options)?;
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        if let Err(e) = self.magick_runner.execute_args(&arg_refs) {
+            // The probe can pass while a plugin is still missing, e.g. a listed format with
+            // no usable encoder, which ImageMagick reports as a missing delegate
+            return Err(match involved(options).first() {
+                Some(&(format, write)) if e.to_string().contains("delegate") => {
+                    OperationError::MissingDependency(delegate_instructions(format, write))
+                }
+                _ => e.into(),
+            });
+        }
+
+        let output = self.magick_runner.last_resolved_output(&options.output);
+        let size = fs::metadata(self.base.join(&output.path))?.len();
+        Ok(HeifConversion {
+            output,
+            size,
+            support,
+        })
+    }
+
+    /// Probe `magick -list format` for each HEIF format the conversion reads or writes
+    fn check_support(
+        &self,
+        options: &HeifConvertOptions,
+    ) -> Result<Vec<HeifSupport>, OperationError> {
+        let formats = list_formats(&self.magick_runner)?;
+        let mut support = Vec::new();
+        for (format, write) in involved(options) {
+            let probed = HeifSupport::probe(format, &formats);
+            let supported = if write {
+                probed.writable
+            } else {
+                probed.readable
+            };
+            if !supported {
+                return Err(OperationError::MissingDependency(delegate_instructions(
+                    format, write,
+                )));
+            }
+            if !support.contains(&probed) {
+                support.push(probed);
+            }
+        }
+        Ok(support)
+    }
+}
+
+/// The HEIF formats a conversion involves, each paired with whether it is written
+fn involved(options: &HeifConvertOptions) -> Vec<(HeifFormat, bool)> {
+    let read = HeifFormat::from_path(&options.input).map(|format| (format, false));
+    let write = HeifFormat::from_path(&options.output).map(|format| (format, true));
+    // The output is checked first, since writing needs more of libheif than reading
+    write.into_iter().chain(read).collect()
+}
+
+/// Build the `magick` arguments for the conversion
+fn build_args(options: &HeifConvertOptions) -> Result<Vec<String>, OperationError> {
+    if involved(options).is_empty() {
+        return Err(OperationError::InvalidParameter(
+            "the input or the output must be a .heic, .heif, or .avif file".to_string(),
+        ));
+    }
+    let mut args = vec![options.input.clone()];
+    if let Some(quality) = options.quality {
+        if quality > 100 {
+            return Err(OperationError::InvalidParameter(format!(
+                "quality {quality} must be between 0 and 100"
+            )));
+        }
+        args.extend(["-quality".to_string(), quality.to_string()]);
+    }
+    args.push(options.output.clone());
+    Ok(args)
+}
+
+/// Guidance for installing what ImageMagick needs to read or write a HEIF format
+fn delegate_instructions(format: HeifFormat, write: bool) -> String {
+    let (access, plugin) = if write {
+        ("write", format.encoder())
+    } else {
+        ("read", format.decoder())
+    };
+    let install = match std::env::consts::OS {
+        "macos" => "Install libheif and reinstall ImageMagick so it links against it:\n  brew install libheif\n  brew reinstall imagemagick".to_string(),
+        "linux" => "Install libheif with its plugins, then install an ImageMagick built with HEIC support, or rebuild it with --with-heic=yes:\n  sudo apt install libheif1 libheif-plugin-libde265 libheif-plugin-x265 libheif-plugin-dav1d libheif-plugin-aomenc\n  or\n  sudo dnf install libheif libheif-freeworld".to_string(),
+        "windows" => "Reinstall the ImageMagick binary release, which includes libheif:\n  winget install ImageMagick.Q16-HDRI".to_string(),
+        _ => "Install libheif and rebuild ImageMagick with --with-heic=yes.".to_string(),
+    };
+    format!(
+        "This ImageMagick cannot {access} {name}. It {access}s {name} through libheif, which needs the {plugin} plugin.\n\n{install}\n\nCheck with: magick -list format | grep -i {name}",
+        name = format.name()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+    use tempfile::TempDir;
+
+    /// Lists HEIC as readable only and AVIF as readable and writable, and writes outputs
+    struct MockCommandRunner {
+        calls: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl MockCommandRunner {
+        fn new() -> Self {
+            MockCommandRunner {
+                calls: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|s| s.to_string()).collect());
+            if args == ["-list", "format"] {
+                return Ok(
+                    "     AVIF  HEIC      rw+   AV1 Image File Format (1.17.6)\n\
+                           HEIC  HEIC      r-+   High Efficiency Image Format (1.17.6)\n\
+                           PNG* PNG       rw-   Portable Network Graphics\n"
+                        .to_string(),
+                );
+            }
+            fs::write(
+                working_dir.unwrap().join(args.last().unwrap()),
+                b"converted",
+            )
+            .unwrap();
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_convert_heic_to_avif() {
+        let dir = TempDir::new().unwrap();
+        let runner = MockCommandRunner::new();
+        let mut options = HeifConvertOptions::new("IMG_0001.HEIC", "photo.avif");
+        options.quality = Some(60);
+
+        let conversion = HeifConvert::new(&runner, Some(dir.path()))
+            .run(&options)
+            .unwrap();
+
+        assert_eq!(conversion.output, ResolvedOutput::written("photo.avif"));
+        assert_eq!(conversion.size, 9);
+        assert_eq!(
+            conversion.support,
+            vec![
+                HeifSupport {
+                    format: HeifFormat::Avif,
+                    readable: true,
+                    writable: true
+                },
+                HeifSupport {
+                    format: HeifFormat::Heic,
+                    readable: true,
+                    writable: false
+                }
+            ]
+        );
+        assert_eq!(
+            runner.calls.borrow()[1],
+            vec!["IMG_0001.HEIC", "-quality", "60", "photo.avif"]
+        );
+    }
+
+    #[test]
+    fn test_missing_encoder_gives_guidance() {
+        let dir = TempDir::new().unwrap();
+        let runner = MockCommandRunner::new();
+        let options = HeifConvertOptions::new("photo.png", "photo.heic");
+
+        let error = HeifConvert::new(&runner, Some(dir.path()))
+            .run(&options)
+            .unwrap_err();
+
+        let OperationError::MissingDependency(instructions) = error else {
+            panic!("expected a missing dependency, got {error:?}");
+        };
+        assert!(instructions.starts_with("This ImageMagick cannot write HEIC."));
+        assert!(instructions.contains("x265"));
+        // Nothing is converted once the probe fails
+        assert_eq!(runner.calls.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        assert!(build_args(&HeifConvertOptions::new("photo.png", "photo.webp")).is_err());
+
+        let mut options = HeifConvertOptions::new("photo.avif", "photo.png");
+        options.quality = Some(101);
+        assert!(build_args(&options).is_err());
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(HeifFormat::from_path("a/IMG.HEIF"), Some(HeifFormat::Heic));
+        assert_eq!(HeifFormat::from_path("photo.avif"), Some(HeifFormat::Avif));
+        assert_eq!(HeifFormat::from_path("photo.jpg"), None);
+        assert_eq!(HeifFormat::from_path("heic"), None);
+    }
+}
@@ -0,0 +1,337 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::shell::CommandRunner;
+use serde::Serialize;
+
+/// `magick` subcommands which print their own usage with `-help`
+const SUBCOMMANDS: &[&str] = &[
+    "animate",
+    "compare",
+    "composite",
+    "conjure",
+    "display",
+    "identify",
+    "import",
+    "mogrify",
+    "montage",
+    "stream",
+];
+
+/// `-list` types which are too long, or too loosely formatted, to include as option values
+///
+/// Formats and fonts have their own listings.
+const UNLISTED_TYPES: &[&str] = &["font", "format", "list", "module", "resource"];
+
+/// Options whose values are listed under a `-list` type named differently
+const LIST_TYPE_ALIASES: &[(&str, &str)] = &[
+    ("orient", "Orientation"),
+    ("ordered-dither", "Threshold"),
+    ("quantize", "Colorspace"),
+];
+
+/// Most related options suggested when a topic is not found
+const MAX_SUGGESTIONS: usize = 10;
+
+/// Targeted documentation for one option or subcommand
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct HelpTopic {
+    /// The topic as looked up, e.g. `-resize` or `identify`
+    pub topic: String,
+    /// Usage lines for the option, or the subcommand's full usage
+    pub usage: Vec<String>,
+    /// The `magick -list` type holding the option's accepted values, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub list_type: Option<String>,
+    /// Values the option accepts, from `magick -list`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<String>,
+}
+
+/// Operation which looks up documentation for a single option or subcommand
+///
+/// Only the lines relevant to the topic are returned, so clients do not need the full `--help`
+/// output to answer a question about one option.
+pub struct HelpTopics<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> HelpTopics<'a> {
+    /// Create a new HelpTopics with the provided CommandRunner
+    pub fn new(command_runner: &'a dyn CommandRunner) -> Self {
+        HelpTopics {
+            magick_runner: MagickRunner::new(command_runner, None),
+        }
+    }
+
+    /// Look up an option or subcommand
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - An option such as `-resize`, `resize`, or `+repage`, or a subcommand such as
+    ///   `identify`
+    ///
+    /// # Returns
+    ///
+    /// Returns the `HelpTopic`, or an `OperationError` naming related options if nothing matches
+    pub fn lookup(&self, topic: &str) -> Result<HelpTopic, OperationError> {
+        let topic = topic.trim();
+        let name = topic.trim_start_matches(['-', '+']).to_ascii_lowercase();
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(OperationError::InvalidParameter(format!(
+                "'{topic}' is not an option or subcommand name"
+            )));
+        }
+
+        if topic == name && SUBCOMMANDS.contains(&name.as_str()) {
+            let output = self.magick_runner.execute_args(&[&name, "-help"])?;
+            return Ok(HelpTopic {
+                topic: name,
+                usage: output
+                    .lines()
+                    .map(|line| line.trim_end().to_string())
+                    .collect(),
+                ..HelpTopic::default()
+            });
+        }
+
+        let help = self.magick_runner.execute_args(&["-help"])?;
+        let usage = option_lines(&help, &name);
+        if usage.is_empty() {
+            let suggestions = related_options(&help, &name);
+            let hint = if suggestions.is_empty() {
+                String::new()
+            } else {
+                format!(", related options: {}", suggestions.join(", "))
+            };
+            return Err(OperationError::InvalidParameter(format!(
+                "no documentation found for '{topic}'{hint}"
+            )));
+        }
+
+        let list_type = self.list_type(&name)?;
+        let values = match &list_type {
+            Some(list_type) => {
+                let output = self.magick_runner.execute_args(&["-list", list_type])?;
+                parse_values(&output)
+            }
+            None => Vec::new(),
+        };
+        Ok(HelpTopic {
+            topic: format!("-{name}"),
+            usage,
+            list_type,
+            values,
+        })
+    }
+
+    /// The `-list` type named like an option, e.g. `VirtualPixel` for `-virtual-pixel`, or
+    /// listing its values under another name, e.g. `Orientation` for `-orient`
+    fn list_type(&self, name: &str) -> Result<Option<String>, OperationError> {
+        let key = LIST_TYPE_ALIASES
+            .iter()
+            .find(|(option, _)| *option == name)
+            .map_or_else(
+                || name.replace('-', ""),
+                |(_, list_type)| list_type.to_string(),
+            );
+        if UNLISTED_TYPES.contains(&key.as_str()) {
+            return Ok(None);
+        }
+        let output = self.magick_runner.execute_args(&["-list", "list"])?;
+        Ok(parse_values(&output)
+            .into_iter()
+            .find(|list_type| list_type.eq_ignore_ascii_case(&key)))
+    }
+}
+
+/// The option name a line of `magick -help` documents, e.g. `resize` for
+/// `  -resize geometry     resize the image`
+pub(super) fn documented_option(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    if trimmed.len() == line.len() {
+        return None;
+    }
+    let option = trimmed.split_whitespace().next()?;
+    option
+        .strip_prefix('-')
+        .or_else(|| option.strip_prefix('+'))
+        .filter(|name| !name.is_empty())
+}
+
+/// The lines of `magick -help` documenting an option, including its `+` form
+///
+/// Descriptions wrapped onto indented continuation lines are joined to their option.
+fn option_lines(help: &str, name: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_option = false;
+    for line in help.lines() {
+        match documented_option(line) {
+            Some(option) => {
+                in_option = option == name;
+                if in_option {
+                    lines.push(line.trim().to_string());
+                }
+            }
+            None if in_option && line.starts_with(' ') && !line.trim().is_empty() => {
+                if let Some(last) = lines.last_mut() {
+                    last.push(' ');
+                    last.push_str(line.trim());
+                }
+            }
+            None => in_option = false,
+        }
+    }
+    lines
+}
+
+/// Documented options whose names contain the topic or are contained by it
+fn related_options(help: &str, name: &str) -> Vec<String> {
+    let mut related: Vec<String> = Vec::new();
+    for option in help.lines().filter_map(documented_option) {
+        let option = format!("-{option}");
+        let matches = option.contains(name) || name.contains(&option[1..]);
+        if matches && !related.contains(&option) {
+            related.push(option);
+        }
+    }
+    related.truncate(MAX_SUGGESTIONS);
+    related
+}
+
+/// Parse a `magick -list` listing of one value per line
+fn parse_values(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+    use std::path::Path;
+
+    const HELP_OUTPUT: &str = "Usage: magick tool [ {option} | {image} ... ] {output_image}
+
+Image Settings:
+  -gravity type        horizontal and vertical text placement
+  -virtual-pixel method
+                       access method for pixels outside the boundaries
+
+Image Operators:
+  -resize geometry     resize the image
+  -sample geometry     scale image with pixel sampling
+
+Miscellaneous Options:
+  -repage geometry     size and location of an image canvas
+  +repage              reset the image page geometry
+";
+
+    /// Answers `-help` and `-list` lookups with fixed output
+    struct MockCommandRunner {
+        commands: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl MockCommandRunner {
+        fn new() -> Self {
+            MockCommandRunner {
+                commands: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.commands
+                .borrow_mut()
+                .push(args.iter().map(|arg| arg.to_string()).collect());
+            let output = match args {
+                ["-help"] => HELP_OUTPUT,
+                ["-list", "list"] => "Align\nGravity\nList\nVirtualPixel\n",
+                ["-list", "Gravity"] => "Center\nEast\nNorth\n",
+                ["-list", "VirtualPixel"] => "Edge\nTile\n",
+                ["identify", "-help"] => {
+                    "Usage: identify [options ...] file [ [options ...] file ... ]\n"
+                }
+                _ => "",
+            };
+            Ok(output.to_string())
+        }
+    }
+
+    #[test]
+    fn test_option_with_values() {
+        let runner = MockCommandRunner::new();
+        let topics = HelpTopics::new(&runner);
+
+        let topic = topics.lookup("gravity").unwrap();
+
+        assert_eq!(topic.topic, "-gravity");
+        assert_eq!(
+            topic.usage,
+            vec!["-gravity type        horizontal and vertical text placement"]
+        );
+        assert_eq!(topic.list_type.as_deref(), Some("Gravity"));
+        assert_eq!(topic.values, vec!["Center", "East", "North"]);
+    }
+
+    #[test]
+    fn test_hyphenated_option_matches_list_type() {
+        let runner = MockCommandRunner::new();
+        let topics = HelpTopics::new(&runner);
+
+        let topic = topics.lookup("-virtual-pixel").unwrap();
+
+        assert_eq!(
+            topic.usage,
+            vec!["-virtual-pixel method access method for pixels outside the boundaries"]
+        );
+        assert_eq!(topic.list_type.as_deref(), Some("VirtualPixel"));
+        assert_eq!(topic.values, vec!["Edge", "Tile"]);
+    }
+
+    #[test]
+    fn test_option_without_values_includes_plus_form() {
+        let runner = MockCommandRunner::new();
+        let topics = HelpTopics::new(&runner);
+
+        let topic = topics.lookup("+repage").unwrap();
+
+        assert_eq!(topic.topic, "-repage");
+        assert_eq!(topic.usage.len(), 2);
+        assert_eq!(topic.list_type, None);
+        assert!(topic.values.is_empty());
+    }
+
+    #[test]
+    fn test_subcommand_usage() {
+        let runner = MockCommandRunner::new();
+        let topics = HelpTopics::new(&runner);
+
+        let topic = topics.lookup("identify").unwrap();
+
+        assert_eq!(topic.topic, "identify");
+        assert!(topic.usage[0].starts_with("Usage: identify"));
+        assert_eq!(runner.commands.borrow()[0], vec!["identify", "-help"]);
+    }
+
+    #[test]
+    fn test_unknown_option_suggests_related() {
+        let runner = MockCommandRunner::new();
+        let topics = HelpTopics::new(&runner);
+
+        let error = topics.lookup("-resize-image").unwrap_err().to_string();
+
+        assert!(error.contains("-resize"), "{error}");
+        assert!(topics.lookup("-resize; rm").is_err());
+    }
+}
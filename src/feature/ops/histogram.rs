@@ -0,0 +1,337 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::shell::CommandRunner;
+use serde::Serialize;
+use std::path::Path;
+
+/// Number of histogram bins used when none is requested
+pub const DEFAULT_HISTOGRAM_BINS: u32 = 16;
+/// Number of distinct 8-bit channel values
+const LEVELS: u32 = 256;
+
+/// A channel of an analyzed image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Channel {
+    Gray,
+    Red,
+    Green,
+    Blue,
+    Alpha,
+}
+
+impl Channel {
+    /// Name reported in results
+    fn name(self) -> &'static str {
+        match self {
+            Channel::Gray => "gray",
+            Channel::Red => "red",
+            Channel::Green => "green",
+            Channel::Blue => "blue",
+            Channel::Alpha => "alpha",
+        }
+    }
+
+    /// Channel selector used in fx expressions, where gray images expose their value as `r`
+    fn fx(self) -> &'static str {
+        match self {
+            Channel::Gray | Channel::Red => "r",
+            Channel::Green => "g",
+            Channel::Blue => "b",
+            Channel::Alpha => "a",
+        }
+    }
+
+    /// Name passed to `-channel`
+    fn option(self) -> &'static str {
+        match self {
+            Channel::Gray => "Gray",
+            Channel::Red => "Red",
+            Channel::Green => "Green",
+            Channel::Blue => "Blue",
+            Channel::Alpha => "Alpha",
+        }
+    }
+}
+
+/// Statistics and histogram of a single channel
+///
+/// Statistics are normalized from 0 to 1, while histogram bins cover 8-bit values.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChannelHistogram {
+    /// Channel name: `gray`, `red`, `green`, `blue`, or `alpha`
+    pub channel: String,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Fraction of pixels at 0, e.g. crushed shadows
+    pub clipped_low: f64,
+    /// Fraction of pixels at 255, e.g. blown highlights
+    pub clipped_high: f64,
+    /// Pixel counts for equal ranges of 8-bit values, darkest first
+    pub bins: Vec<u64>,
+}
+
+/// Per-channel statistics of an image
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImageHistogram {
+    /// `gray` for grayscale images, otherwise `srgb`, which other colorspaces are converted to
+    pub colorspace: String,
+    /// Number of pixels counted per channel
+    pub pixels: u64,
+    pub channels: Vec<ChannelHistogram>,
+}
+
+/// Operation which computes per-channel histograms and statistics of an image
+pub struct Histogram<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> Histogram<'a> {
+    /// Create a new Histogram with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Histogram {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Analyze the first frame of an image
+    ///
+    /// Grayscale images are analyzed as a single gray channel, and other images in sRGB.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the image
+    /// * `bins` - Number of histogram bins, from 1 to 256
+    ///
+    /// # Returns
+    ///
+    /// Returns the statistics and histogram of each channel, or an `OperationError` on failure
+    pub fn run(&self, path: &str, bins: u32) -> Result<ImageHistogram, OperationError> {
+        if !(1..=LEVELS).contains(&bins) {
+            return Err(OperationError::InvalidParameter(format!(
+                "bins {bins} must be between 1 and {LEVELS}"
+            )));
+        }
+        let frame = format!("{path}[0]");
+        let described =
+            self.magick_runner
+                .execute_args(&["identify", "-format", "%[channels]", &frame])?;
+        let (gray, channels) = parse_channels(&described);
+        let mut input = vec![frame.as_str()];
+        if !gray {
+            input.extend(["-colorspace", "sRGB"]);
+        }
+
+        let format = stats_format(&channels);
+        let mut args = input.clone();
+        args.extend(["-format", &format, "info:"]);
+        let stats = parse_stats(&self.magick_runner.execute_args(&args)?, channels.len())?;
+
+        let mut pixels = 0;
+        let mut results = Vec::with_capacity(channels.len());
+        for (channel, [mean, std_dev, min, max]) in channels.iter().zip(stats) {
+            let mut args = input.clone();
+            args.extend([
+                "-channel",
+                channel.option(),
+                "-separate",
+                "+channel",
+                "-depth",
+                "8",
+                "-format",
+                "%c",
+                "histogram:info:",
+            ]);
+            let counts = parse_histogram(&self.magick_runner.execute_args(&args)?)?;
+            let total: u64 = counts.iter().sum();
+            pixels = pixels.max(total);
+            let fraction = |count: u64| {
+                if total == 0 {
+                    0.0
+                } else {
+                    count as f64 / total as f64
+                }
+            };
+            results.push(ChannelHistogram {
+                channel: channel.name().to_string(),
+                mean,
+                std_dev,
+                min,
+                max,
+                clipped_low: fraction(counts[0]),
+                clipped_high: fraction(counts[LEVELS as usize - 1]),
+                bins: rebin(&counts, bins),
+            });
+        }
+        Ok(ImageHistogram {
+            colorspace: if gray { "gray" } else { "srgb" }.to_string(),
+            pixels,
+            channels: results,
+        })
+    }
+}
+
+/// Parse `%[channels]`, e.g. `srgba  4.0`, into whether the image is gray and its channels
+fn parse_channels(described: &str) -> (bool, Vec<Channel>) {
+    let colorspace = described
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let gray = colorspace.starts_with("gray");
+    let mut channels = if gray {
+        vec![Channel::Gray]
+    } else {
+        vec![Channel::Red, Channel::Green, Channel::Blue]
+    };
+    if colorspace.ends_with('a') && colorspace != "lab" {
+        channels.push(Channel::Alpha);
+    }
+    (gray, channels)
+}
+
+/// Format printing the mean, standard deviation, minimum, and maximum of each channel per line
+fn stats_format(channels: &[Channel]) -> String {
+    channels
+        .iter()
+        .map(|channel| {
+            let c = channel.fx();
+            format!(
+                "%[fx:mean.{c}] %[fx:standard_deviation.{c}] %[fx:minima.{c}] %[fx:maxima.{c}]\\n"
+            )
+        })
+        .collect()
+}
+
+/// Parse the output of `stats_format`
+fn parse_stats(output: &str, channels: usize) -> Result<Vec<[f64; 4]>, OperationError> {
+    let stats: Vec<[f64; 4]> = output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let values: Vec<f64> = line
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .map_err(|_| {
+                    OperationError::ParseError(format!("unexpected statistics: {line}"))
+                })?;
+            <[f64; 4]>::try_from(values)
+                .map_err(|_| OperationError::ParseError(format!("unexpected statistics: {line}")))
+        })
+        .collect::<Result<_, _>>()?;
+    if stats.len() != channels {
+        return Err(OperationError::ParseError(format!(
+            "expected statistics for {channels} channels, got {}",
+            stats.len()
+        )));
+    }
+    Ok(stats)
+}
+
+/// Parse `histogram:info:` output of a single 8-bit channel into counts per value
+///
+/// Lines look like `     42: (  12,  12,  12) #0C0C0C gray(12)`, where the first value in
+/// parentheses is the channel value.
+fn parse_histogram(output: &str) -> Result<Vec<u64>, OperationError> {
+    let mut counts = vec![0; LEVELS as usize];
+    for line in output.lines().filter(|line| !line.trim().is_empty()) {
+        let parsed = line.split_once(':').and_then(|(count, rest)| {
+            let count: u64 = count.trim().parse().ok()?;
+            let value = rest
+                .split_once('(')?
+                .1
+                .split([',', ')'])
+                .next()?
+                .trim()
+                .parse::<f64>()
+                .ok()?;
+            Some((
+                count,
+                value.round().clamp(0.0, f64::from(LEVELS - 1)) as usize,
+            ))
+        });
+        let (count, value) = parsed
+            .ok_or_else(|| OperationError::ParseError(format!("unexpected histogram: {line}")))?;
+        counts[value] += count;
+    }
+    Ok(counts)
+}
+
+/// Combine counts per 8-bit value into `bins` equal ranges
+fn rebin(counts: &[u64], bins: u32) -> Vec<u64> {
+    let mut rebinned = vec![0; bins as usize];
+    for (value, count) in counts.iter().enumerate() {
+        rebinned[value * bins as usize / LEVELS as usize] += count;
+    }
+    rebinned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+
+    /// Describes a gray image with alpha and answers the statistics and histogram commands
+    struct MockCommandRunner;
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            let output = if args[0] == "identify" {
+                "graya  2.0"
+            } else if args.last() == Some(&"info:") {
+                "0.5 0.25 0 1\n1 0 1 1\n"
+            } else if args.contains(&"Alpha") {
+                "  100: (255,255,255) #FFFFFF gray(255)\n"
+            } else {
+                "   10: (  0,  0,  0) #000000 gray(0)\n   60: (128,128,128) #808080 gray(128)\n   30: (255,255,255) #FFFFFF gray(255)\n"
+            };
+            Ok(output.to_string())
+        }
+    }
+
+    #[test]
+    fn test_histogram() {
+        let runner = MockCommandRunner;
+        let histogram = Histogram::new(&runner, None).run("photo.png", 4).unwrap();
+
+        assert_eq!(histogram.colorspace, "gray");
+        assert_eq!(histogram.pixels, 100);
+        let gray = &histogram.channels[0];
+        assert_eq!(gray.channel, "gray");
+        assert_eq!(
+            (gray.mean, gray.std_dev, gray.min, gray.max),
+            (0.5, 0.25, 0.0, 1.0)
+        );
+        assert_eq!(gray.bins, vec![10, 0, 60, 30]);
+        assert_eq!((gray.clipped_low, gray.clipped_high), (0.1, 0.3));
+        let alpha = &histogram.channels[1];
+        assert_eq!(alpha.channel, "alpha");
+        assert_eq!(alpha.bins, vec![0, 0, 0, 100]);
+    }
+
+    #[test]
+    fn test_parse_channels() {
+        assert_eq!(
+            parse_channels("srgb  3.0"),
+            (false, vec![Channel::Red, Channel::Green, Channel::Blue])
+        );
+        assert_eq!(parse_channels("cmyka 5.0").1.last(), Some(&Channel::Alpha));
+        assert_eq!(parse_channels("gray 1.0"), (true, vec![Channel::Gray]));
+    }
+
+    #[test]
+    fn test_rejects_invalid_bins() {
+        let runner = MockCommandRunner;
+        assert!(matches!(
+            Histogram::new(&runner, None).run("photo.png", 0),
+            Err(OperationError::InvalidParameter(_))
+        ));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+
+/// Pixel dimensions of an image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageDimensions {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ImageDimensions {
+    /// Parse the output of `identify -format "%w %h"`
+    pub fn parse(output: &str) -> Option<Self> {
+        let mut parts = output.split_whitespace();
+        let width = parts.next()?.parse().ok()?;
+        let height = parts.next()?.parse().ok()?;
+        Some(ImageDimensions { width, height })
+    }
+}
+
+/// Basic metadata used to rename and organize images
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageMetadata {
+    pub width: u32,
+    pub height: u32,
+    /// ImageMagick format name, e.g. `JPEG`
+    pub format: String,
+    /// EXIF capture date as `YYYY-MM-DD`, if present
+    pub date: Option<String>,
+}
+
+/// `identify -format` string producing output readable by `ImageMetadata::parse`
+const METADATA_FORMAT: &str = "%w|%h|%m|%[EXIF:DateTimeOriginal]";
+
+impl ImageMetadata {
+    /// Parse the output of `identify -format` using `METADATA_FORMAT`
+    pub fn parse(output: &str) -> Option<Self> {
+        let mut parts = output.trim().splitn(4, '|');
+        let width = parts.next()?.parse().ok()?;
+        let height = parts.next()?.parse().ok()?;
+        let format = parts.next()?.to_string();
+        // EXIF dates look like `2024:03:15 10:22:01`
+        let date = parts
+            .next()
+            .and_then(|d| d.split_whitespace().next())
+            .and_then(exif_date);
+        Some(ImageMetadata {
+            width,
+            height,
+            format,
+            date,
+        })
+    }
+}
+
+/// Convert an EXIF `YYYY:MM:DD` date to `YYYY-MM-DD`, or `None` if it is not one
+fn exif_date(date: &str) -> Option<String> {
+    let mut parts = date.split(':');
+    let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+    let digits =
+        |part: &str, len: usize| part.len() == len && part.bytes().all(|b| b.is_ascii_digit());
+    (parts.next().is_none() && digits(year, 4) && digits(month, 2) && digits(day, 2))
+        .then(|| format!("{year}-{month}-{day}"))
+}
+
+/// Read the dimensions of the first frame of an image
+pub(crate) fn dimensions(
+    magick_runner: &MagickRunner,
+    path: &str,
+) -> Result<ImageDimensions, OperationError> {
+    let frame = format!("{path}[0]");
+    let output = magick_runner.execute_args(&["identify", "-format", "%w %h", &frame])?;
+    ImageDimensions::parse(&output)
+        .ok_or_else(|| OperationError::ParseError(format!("unexpected identify output: {output}")))
+}
+
+/// Read basic metadata from the first frame of an image
+pub(crate) fn metadata(
+    magick_runner: &MagickRunner,
+    path: &str,
+) -> Result<ImageMetadata, OperationError> {
+    let frame = format!("{path}[0]");
+    let output = magick_runner.execute_args(&["identify", "-format", METADATA_FORMAT, &frame])?;
+    ImageMetadata::parse(&output)
+        .ok_or_else(|| OperationError::ParseError(format!("unexpected identify output: {output}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dimensions() {
+        let dims = ImageDimensions::parse("640 480");
+        assert_eq!(
+            dims,
+            Some(ImageDimensions {
+                width: 640,
+                height: 480
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_dimensions() {
+        assert_eq!(ImageDimensions::parse("not dimensions"), None);
+        assert_eq!(ImageDimensions::parse("640"), None);
+    }
+
+    #[test]
+    fn test_parse_metadata_with_exif_date() {
+        let metadata = ImageMetadata::parse("4000|3000|JPEG|2024:03:15 10:22:01").unwrap();
+        assert_eq!(metadata.width, 4000);
+        assert_eq!(metadata.format, "JPEG");
+        assert_eq!(metadata.date, Some("2024-03-15".to_string()));
+    }
+
+    #[test]
+    fn test_parse_metadata_without_exif_date() {
+        let metadata = ImageMetadata::parse("64|64|PNG|").unwrap();
+        assert_eq!(metadata.date, None);
+    }
+
+    #[test]
+    fn test_parse_metadata_with_malformed_exif_date() {
+        for date in [
+            "../../x:yz",
+            "2024:3:15",
+            "2024:03:1a",
+            "0000:00:00:00",
+            "2024-03-15",
+        ] {
+            let metadata = ImageMetadata::parse(&format!("64|64|JPEG|{date} 10:22:01")).unwrap();
+            assert_eq!(metadata.date, None, "{date}");
+        }
+    }
+}
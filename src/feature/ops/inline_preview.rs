@@ -0,0 +1,287 @@
+use crate::feature::TempFile;
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::identify;
+use crate::feature::shell::CommandRunner;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Largest preview sent inline when the caller does not set a limit
+pub const DEFAULT_PREVIEW_BYTES: u64 = 256 * 1024;
+
+/// Longest preview edge when the caller does not set a limit
+pub const DEFAULT_PREVIEW_DIMENSION: u32 = 1024;
+
+/// Compact format previews are transcoded to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PreviewFormat {
+    #[default]
+    Webp,
+    Jpeg,
+}
+
+impl PreviewFormat {
+    /// Parse a preview format name
+    pub fn parse(name: &str) -> Result<Self, OperationError> {
+        match name.to_ascii_lowercase().as_str() {
+            "webp" => Ok(PreviewFormat::Webp),
+            "jpeg" | "jpg" => Ok(PreviewFormat::Jpeg),
+            other => Err(OperationError::InvalidParameter(format!(
+                "unknown preview format '{other}', expected webp or jpeg"
+            ))),
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            PreviewFormat::Webp => "webp",
+            PreviewFormat::Jpeg => "jpg",
+        }
+    }
+
+    fn mime_type(&self) -> &'static str {
+        match self {
+            PreviewFormat::Webp => "image/webp",
+            PreviewFormat::Jpeg => "image/jpeg",
+        }
+    }
+}
+
+/// Limits for an image returned inline to the client
+#[derive(Debug, Clone, Copy)]
+pub struct InlinePreviewOptions {
+    /// Largest encoded size in bytes
+    pub max_bytes: u64,
+    /// Longest edge in pixels
+    pub max_dimension: u32,
+    /// Format used when the image has to be transcoded
+    pub format: PreviewFormat,
+}
+
+impl Default for InlinePreviewOptions {
+    fn default() -> Self {
+        InlinePreviewOptions {
+            max_bytes: DEFAULT_PREVIEW_BYTES,
+            max_dimension: DEFAULT_PREVIEW_DIMENSION,
+            format: PreviewFormat::default(),
+        }
+    }
+}
+
+/// An image ready to return inline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlinePreview {
+    /// The encoded image
+    pub data: Vec<u8>,
+    pub mime_type: &'static str,
+    pub width: u32,
+    pub height: u32,
+    /// Whether this is a transcoded preview rather than the file itself
+    pub transcoded: bool,
+}
+
+/// Operation which fits an image within inline size limits for MCP clients
+///
+/// Images clients can display which already fit are returned as they are. Anything else is
+/// transcoded to a smaller, lower quality preview until it fits, leaving the file on disk alone.
+pub struct InlinePreviewer<'a> {
+    magick_runner: MagickRunner<'a>,
+    base: PathBuf,
+}
+
+impl<'a> InlinePreviewer<'a> {
+    /// Create a new InlinePreviewer with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        // Previews are written to the temporary directory, so the workspace's project rules on
+        // outputs do not apply and paths are made absolute instead
+        InlinePreviewer {
+            magick_runner: MagickRunner::new(command_runner, None),
+            base: workspace
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(".")),
+        }
+    }
+
+    /// Produce an inline preview of an image
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The image, relative to the workspace
+    /// * `options` - Size limits and the preview format
+    ///
+    /// # Returns
+    ///
+    /// Returns the `InlinePreview`, or an `OperationError` if no preview fits the limits
+    pub fn preview(
+        &self,
+        path: &Path,
+        options: &InlinePreviewOptions,
+    ) -> Result<InlinePreview, OperationError> {
+        if options.max_dimension == 0 {
+            return Err(OperationError::InvalidParameter(
+                "max_dimension must be greater than zero".to_string(),
+            ));
+        }
+        let full = self.base.join(path);
+        let full_str = full.to_string_lossy().to_string();
+        let dimensions = identify::dimensions(&self.magick_runner, &full_str)?;
+        let size = fs::metadata(&full)?.len();
+        if let Some(mime_type) = displayable_mime_type(path)
+            && size <= options.max_bytes
+            && dimensions.width.max(dimensions.height) <= options.max_dimension
+        {
+            return Ok(InlinePreview {
+                data: fs::read(&full)?,
+                mime_type,
+                width: dimensions.width,
+                height: dimensions.height,
+                transcoded: false,
+            });
+        }
+
+        let frame = format!("{full_str}[0]");
+        for (divisor, quality) in [(1, 80), (2, 70), (4, 60)] {
+            let edge = (options.max_dimension / divisor).max(1);
+            let temp = TempFile::new(options.format.extension())?;
+            let temp_str = temp.path().to_string_lossy().to_string();
+            let geometry = format!("{edge}x{edge}>");
+            let quality = quality.to_string();
+            self.magick_runner.execute_args(&[
+                &frame,
+                "-auto-orient",
+                "-resize",
+                &geometry,
+                "-strip",
+                "-quality",
+                &quality,
+                &temp_str,
+            ])?;
+            let data = fs::read(temp.path())?;
+            if data.len() as u64 <= options.max_bytes {
+                let preview = identify::dimensions(&self.magick_runner, &temp_str)?;
+                return Ok(InlinePreview {
+                    data,
+                    mime_type: options.format.mime_type(),
+                    width: preview.width,
+                    height: preview.height,
+                    transcoded: true,
+                });
+            }
+        }
+        Err(OperationError::InvalidParameter(format!(
+            "no preview of '{}' fits in {} bytes",
+            path.display(),
+            options.max_bytes
+        )))
+    }
+}
+
+/// MIME type of an image clients can display, from its file extension
+fn displayable_mime_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::output;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+    use tempfile::TempDir;
+
+    /// Reports fixed dimensions for identify and writes a preview of fixed size otherwise
+    struct MockCommandRunner {
+        dimensions: String,
+        preview_bytes: usize,
+        commands: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.commands
+                .borrow_mut()
+                .push(args.iter().map(|arg| arg.to_string()).collect());
+            if args[0] == "identify" {
+                return Ok(self.dimensions.clone());
+            }
+            if let Some(output) = output::output_path(args) {
+                fs::write(output, vec![0; self.preview_bytes]).unwrap();
+            }
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_small_displayable_image_is_returned_as_is() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("out.png"), b"png").unwrap();
+        let runner = MockCommandRunner {
+            dimensions: "100 50".to_string(),
+            preview_bytes: 0,
+            commands: RefCell::new(Vec::new()),
+        };
+        let previewer = InlinePreviewer::new(&runner, Some(dir.path()));
+
+        let preview = previewer
+            .preview(Path::new("out.png"), &InlinePreviewOptions::default())
+            .unwrap();
+
+        assert_eq!(preview.data, b"png");
+        assert_eq!(preview.mime_type, "image/png");
+        assert!(!preview.transcoded);
+    }
+
+    #[test]
+    fn test_large_image_is_transcoded() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("out.tiff"), b"tiff").unwrap();
+        let runner = MockCommandRunner {
+            dimensions: "4000 3000".to_string(),
+            preview_bytes: 100,
+            commands: RefCell::new(Vec::new()),
+        };
+        let previewer = InlinePreviewer::new(&runner, Some(dir.path()));
+
+        let preview = previewer
+            .preview(Path::new("out.tiff"), &InlinePreviewOptions::default())
+            .unwrap();
+
+        assert!(preview.transcoded);
+        assert_eq!(preview.mime_type, "image/webp");
+        assert_eq!(preview.data.len(), 100);
+        let commands = runner.commands.borrow();
+        assert_eq!(commands[1][3], "1024x1024>");
+        assert!(commands[1][7].ends_with(".webp"));
+    }
+
+    #[test]
+    fn test_preview_too_large() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("out.png"), b"png").unwrap();
+        let runner = MockCommandRunner {
+            dimensions: "4000 3000".to_string(),
+            preview_bytes: 1000,
+            commands: RefCell::new(Vec::new()),
+        };
+        let previewer = InlinePreviewer::new(&runner, Some(dir.path()));
+        let options = InlinePreviewOptions {
+            max_bytes: 10,
+            ..InlinePreviewOptions::default()
+        };
+
+        assert!(previewer.preview(Path::new("out.png"), &options).is_err());
+        assert_eq!(runner.commands.borrow().len(), 4);
+    }
+}
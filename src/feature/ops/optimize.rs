@@ -0,0 +1,401 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::{OperationError, OutputSettings};
+use crate::feature::output::{self, OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use crate::feature::temp_file::TempFile;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Lowest quality tried when none is given
+pub const DEFAULT_MIN_QUALITY: u32 = 10;
+
+/// Lossy formats whose size shrinks as the quality is lowered
+const LOSSY_FORMATS: [&str; 6] = ["jpg", "jpeg", "webp", "avif", "heic", "jxl"];
+
+/// Parse a file size such as `200KB`, `1.5MB`, or `51200`
+///
+/// `KB`, `MB`, and `GB` are powers of 1000 while `KiB`, `MiB`, and `GiB` are powers of 1024, and
+/// a number without a unit is a count of bytes. Units are case insensitive.
+///
+/// # Returns
+///
+/// Returns the size in bytes, or an `OperationError` if it cannot be parsed
+pub fn parse_byte_size(text: &str) -> Result<u64, OperationError> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1000,
+        "kib" => 1024,
+        "m" | "mb" => 1000 * 1000,
+        "mib" => 1024 * 1024,
+        "g" | "gb" => 1000 * 1000 * 1000,
+        "gib" => 1024 * 1024 * 1024,
+        _ => 0,
+    };
+    let bytes = number.parse::<f64>().ok().map(|n| n * multiplier as f64);
+    match bytes {
+        Some(bytes) if bytes >= 1.0 => Ok(bytes.floor() as u64),
+        _ => Err(OperationError::InvalidParameter(format!(
+            "invalid size '{text}', expected a size such as 200KB, 1.5MB, or 51200"
+        ))),
+    }
+}
+
+/// Options for compressing an image to fit a file size
+#[derive(Debug, Clone)]
+pub struct OptimizeOptions {
+    /// Path to the source image
+    pub input: String,
+    /// Path to write the compressed image to, whose extension picks a lossy format such as JPEG
+    /// or WebP
+    pub output: String,
+    /// Largest size of the output in bytes
+    pub max_bytes: u64,
+    /// Lowest quality, from 1 to 100, the image may be compressed to
+    pub min_quality: u32,
+    /// Highest quality, from `min_quality` to 100, the image is compressed at
+    pub max_quality: u32,
+    /// Finishing settings applied before every encoding
+    pub settings: OutputSettings,
+}
+
+impl OptimizeOptions {
+    /// Create options compressing the image to at most `max_bytes`
+    pub fn new(input: &str, output: &str, max_bytes: u64) -> Self {
+        OptimizeOptions {
+            input: input.to_string(),
+            output: output.to_string(),
+            max_bytes,
+            min_quality: DEFAULT_MIN_QUALITY,
+            max_quality: 100,
+            settings: OutputSettings::default(),
+        }
+    }
+}
+
+/// The result of compressing an image to fit a file size
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptimizedImage {
+    /// The written output
+    pub output: ResolvedOutput,
+    /// Quality the image was compressed at
+    pub quality: u32,
+    /// Size of the output in bytes
+    pub bytes: u64,
+    /// Number of encodings tried to find the quality
+    pub attempts: u32,
+}
+
+/// Operation which finds the highest quality at which an image fits a file size
+pub struct Optimize<'a> {
+    magick_runner: MagickRunner<'a>,
+    base: PathBuf,
+}
+
+impl<'a> Optimize<'a> {
+    /// Create a new Optimize with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Optimize {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+            base: workspace
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(".")),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Compress the image at the highest quality which fits, and write the output
+    ///
+    /// JPEG lets libjpeg search for the quality with `-define jpeg:extent`, which takes a single
+    /// encoding. Other formats are binary searched over quality, encoding to temporary files.
+    ///
+    /// # Returns
+    ///
+    /// Returns the output, quality, and size, or an `OperationError` if the image does not fit
+    /// even at the lowest quality
+    pub fn run(&self, options: &OptimizeOptions) -> Result<OptimizedImage, OperationError> {
+        if !(1..=100).contains(&options.min_quality) {
+            return Err(OperationError::InvalidParameter(format!(
+                "min_quality {} must be between 1 and 100",
+                options.min_quality
+            )));
+        }
+        if !(options.min_quality..=100).contains(&options.max_quality) {
+            return Err(OperationError::InvalidParameter(format!(
+                "max_quality {} must be between min_quality {} and 100",
+                options.max_quality, options.min_quality
+            )));
+        }
+        let settings = options.settings.args()?;
+        let format = output::output_format(&options.output)
+            .filter(|format| LOSSY_FORMATS.contains(&format.as_str()))
+            .ok_or_else(|| {
+                OperationError::InvalidParameter(format!(
+                    "output '{}' must be a lossy format: {}",
+                    options.output,
+                    LOSSY_FORMATS.join(", ")
+                ))
+            })?;
+
+        let (quality, bytes, attempts) = if format == "jpg" || format == "jpeg" {
+            self.search_jpeg_extent(options, &settings, &format)?
+        } else {
+            self.search_quality(options, &settings, &format)?
+        };
+
+        let args = encode_args(&options.input, &settings, quality, &options.output);
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        let resolved = self.magick_runner.last_resolved_output(&options.output);
+        let bytes = if resolved.skipped {
+            bytes
+        } else {
+            fs::metadata(self.base.join(&resolved.path))?.len()
+        };
+        Ok(OptimizedImage {
+            output: resolved,
+            quality,
+            bytes,
+            attempts,
+        })
+    }
+
+    /// Let libjpeg find the quality, then read it back from the encoding
+    ///
+    /// A quality above `max_quality` is lowered to it, which only makes the file smaller.
+    fn search_jpeg_extent(
+        &self,
+        options: &OptimizeOptions,
+        settings: &[String],
+        format: &str,
+    ) -> Result<(u32, u64, u32), OperationError> {
+        let encoded = TempFile::new(format)?;
+        let encoded_path = encoded.path().to_string_lossy().to_string();
+        let mut args = vec![options.input.clone()];
+        args.extend(settings.iter().cloned());
+        args.extend([
+            "-define".to_string(),
+            format!("jpeg:extent={}", options.max_bytes),
+            encoded_path.clone(),
+        ]);
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        let bytes = fs::metadata(encoded.path())?.len();
+        let quality_output =
+            self.magick_runner
+                .execute_args(&["identify", "-format", "%Q", &encoded_path])?;
+        let quality: u32 = quality_output.trim().parse().map_err(|_| {
+            OperationError::ParseError(format!("unexpected quality: {quality_output}"))
+        })?;
+        if bytes > options.max_bytes || quality < options.min_quality {
+            return Err(too_large(options, bytes, quality));
+        }
+        Ok((quality.min(options.max_quality), bytes, 1))
+    }
+
+    /// Binary search for the highest quality whose encoding fits
+    fn search_quality(
+        &self,
+        options: &OptimizeOptions,
+        settings: &[String],
+        format: &str,
+    ) -> Result<(u32, u64, u32), OperationError> {
+        let mut low = options.min_quality;
+        let mut high = options.max_quality;
+        let mut best = None;
+        let mut smallest = None;
+        let mut attempts = 0;
+        while low <= high {
+            let quality = (low + high) / 2;
+            let bytes = self.encoded_size(&options.input, settings, format, quality)?;
+            attempts += 1;
+            if bytes <= options.max_bytes {
+                best = Some((quality, bytes));
+                low = quality + 1;
+            } else {
+                smallest = Some((quality, bytes));
+                high = quality - 1;
+            }
+        }
+        match (best, smallest) {
+            (Some((quality, bytes)), _) => Ok((quality, bytes, attempts)),
+            (None, Some((quality, bytes))) => Err(too_large(options, bytes, quality)),
+            (None, None) => unreachable!("the quality range is never empty"),
+        }
+    }
+
+    /// Size of the image encoded at a quality
+    fn encoded_size(
+        &self,
+        input: &str,
+        settings: &[String],
+        format: &str,
+        quality: u32,
+    ) -> Result<u64, OperationError> {
+        let encoded = TempFile::new(format)?;
+        let encoded_path = encoded.path().to_string_lossy().to_string();
+        let args = encode_args(input, settings, quality, &encoded_path);
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        Ok(fs::metadata(encoded.path())?.len())
+    }
+}
+
+/// Arguments encoding the input with the settings at a quality
+fn encode_args(input: &str, settings: &[String], quality: u32, output: &str) -> Vec<String> {
+    let mut args = vec![input.to_string()];
+    args.extend(settings.iter().cloned());
+    args.extend([
+        "-quality".to_string(),
+        quality.to_string(),
+        output.to_string(),
+    ]);
+    args
+}
+
+fn too_large(options: &OptimizeOptions, bytes: u64, quality: u32) -> OperationError {
+    OperationError::InvalidParameter(format!(
+        "cannot fit {} in {} bytes: it is {bytes} bytes at quality {quality}; resize it or lower min_quality",
+        options.input, options.max_bytes
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+    use tempfile::TempDir;
+
+    /// Writes encodings of 10 bytes per quality point, and honours `jpeg:extent`
+    struct MockCommandRunner {
+        calls: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl MockCommandRunner {
+        fn new() -> Self {
+            MockCommandRunner {
+                calls: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            let path = working_dir
+                .unwrap_or(Path::new("."))
+                .join(args[args.len() - 1]);
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|arg| arg.to_string()).collect());
+            if args[0] == "identify" {
+                let bytes = fs::metadata(path).unwrap().len();
+                return Ok((bytes / 10).to_string());
+            }
+            let setting = args[args.len() - 2];
+            let quality: u64 = match setting.strip_prefix("jpeg:extent=") {
+                Some(extent) => extent.parse::<u64>().unwrap() / 10,
+                None => setting.parse().unwrap(),
+            };
+            fs::write(path, vec![0; quality as usize * 10]).unwrap();
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_parse_byte_size() {
+        assert_eq!(parse_byte_size("200KB").unwrap(), 200_000);
+        assert_eq!(parse_byte_size("1.5 MiB").unwrap(), 1_572_864);
+        assert_eq!(parse_byte_size("51200").unwrap(), 51_200);
+        assert_eq!(parse_byte_size("2mb").unwrap(), 2_000_000);
+        assert!(parse_byte_size("lots").is_err());
+        assert!(parse_byte_size("200 furlongs").is_err());
+        assert!(parse_byte_size("0").is_err());
+    }
+
+    #[test]
+    fn test_optimize_searches_quality() {
+        let dir = TempDir::new().unwrap();
+        let runner = MockCommandRunner::new();
+        let options = OptimizeOptions::new("photo.png", "photo.webp", 555);
+
+        let optimized = Optimize::new(&runner, Some(dir.path()))
+            .run(&options)
+            .unwrap();
+
+        assert_eq!(optimized.quality, 55);
+        assert_eq!(optimized.bytes, 550);
+        assert_eq!(optimized.attempts, runner.calls.borrow().len() as u32 - 1);
+        assert_eq!(
+            runner.calls.borrow().last().unwrap(),
+            &["photo.png", "-quality", "55", "photo.webp"]
+        );
+    }
+
+    #[test]
+    fn test_optimize_jpeg_uses_extent() {
+        let dir = TempDir::new().unwrap();
+        let runner = MockCommandRunner::new();
+        let options = OptimizeOptions::new("photo.png", "photo.jpg", 720);
+
+        let optimized = Optimize::new(&runner, Some(dir.path()))
+            .run(&options)
+            .unwrap();
+
+        assert_eq!(optimized.quality, 72);
+        assert_eq!(optimized.attempts, 1);
+        let calls = runner.calls.borrow();
+        assert_eq!(calls[0][1..3], ["-define", "jpeg:extent=720"]);
+        assert_eq!(calls[1][..3], ["identify", "-format", "%Q"]);
+    }
+
+    #[test]
+    fn test_optimize_caps_quality_and_applies_settings() {
+        let dir = TempDir::new().unwrap();
+        let runner = MockCommandRunner::new();
+        let mut options = OptimizeOptions::new("photo.png", "photo.webp", 5000);
+        options.max_quality = 80;
+        options.settings.strip = true;
+
+        let optimized = Optimize::new(&runner, Some(dir.path()))
+            .run(&options)
+            .unwrap();
+
+        assert_eq!(optimized.quality, 80);
+        assert!(runner.calls.borrow().iter().all(|call| call[1] == "-strip"));
+    }
+
+    #[test]
+    fn test_optimize_fails_when_target_is_unreachable() {
+        let runner = MockCommandRunner::new();
+        let mut options = OptimizeOptions::new("photo.png", "photo.webp", 50);
+        options.min_quality = 20;
+
+        let error = Optimize::new(&runner, None).run(&options).unwrap_err();
+
+        assert!(error.to_string().contains("200 bytes at quality 20"));
+    }
+
+    #[test]
+    fn test_optimize_requires_lossy_format() {
+        let runner = MockCommandRunner::new();
+        let options = OptimizeOptions::new("photo.jpg", "photo.png", 1000);
+
+        assert!(Optimize::new(&runner, None).run(&options).is_err());
+        assert!(runner.calls.borrow().is_empty());
+    }
+}
@@ -0,0 +1,620 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::help_topic::{HelpTopics, documented_option};
+use crate::feature::shell::CommandRunner;
+use serde::Serialize;
+
+/// Worked examples for common operators, keyed by option name without its dash
+///
+/// Each is a complete command with what it does, so a client can adapt one instead of
+/// assembling arguments from the usage line alone.
+const EXAMPLES: &[(&str, &[(&str, &str)])] = &[
+    (
+        "distort",
+        &[
+            (
+                "magick in.png -distort SRT 30 out.png",
+                "Rotate 30 degrees about the center with scale-rotate-translate",
+            ),
+            (
+                "magick in.png -virtual-pixel transparent -distort Perspective \"0,0 20,10  100,0 90,5  0,100 0,100  100,100 100,100\" out.png",
+                "Move the corners of a 100x100 image, given as source,destination pairs",
+            ),
+            (
+                "magick in.png -distort Arc 60 out.png",
+                "Bend the image into a 60 degree arc",
+            ),
+        ],
+    ),
+    (
+        "resize",
+        &[
+            (
+                "magick in.png -resize 800x600 out.png",
+                "Fit within 800x600, keeping the aspect ratio",
+            ),
+            (
+                "magick in.png -resize 800x600^ -gravity center -extent 800x600 out.png",
+                "Fill 800x600 exactly, cropping the overflow",
+            ),
+            ("magick in.png -resize 50% out.png", "Halve both dimensions"),
+        ],
+    ),
+    (
+        "crop",
+        &[
+            (
+                "magick in.png -crop 400x300+50+20 +repage out.png",
+                "Cut a 400x300 region starting 50 pixels right and 20 down",
+            ),
+            (
+                "magick in.png -gravity center -crop 50%x50%+0+0 +repage out.png",
+                "Keep the middle half of each dimension",
+            ),
+            (
+                "magick in.png -crop 3x3@ +repage tile-%d.png",
+                "Split into a 3x3 grid of equal tiles",
+            ),
+        ],
+    ),
+    (
+        "extent",
+        &[
+            (
+                "magick in.png -gravity center -background white -extent 1000x1000 out.png",
+                "Pad to a 1000x1000 white square, centered",
+            ),
+            (
+                "magick in.png -gravity north -extent 800x200 out.png",
+                "Cut or pad to 800x200, anchored at the top",
+            ),
+        ],
+    ),
+    (
+        "gravity",
+        &[
+            (
+                "magick in.png -gravity southeast -annotate +10+10 \"Sample\" out.png",
+                "Place text 10 pixels in from the bottom right corner",
+            ),
+            (
+                "magick in.png -gravity center -crop 200x200+0+0 +repage out.png",
+                "Crop from the center instead of the top left",
+            ),
+        ],
+    ),
+    (
+        "compose",
+        &[
+            (
+                "magick base.png overlay.png -gravity center -compose over -composite out.png",
+                "Lay the second image over the center of the first",
+            ),
+            (
+                "magick photo.png texture.png -compose multiply -composite out.png",
+                "Darken the photo with a texture",
+            ),
+        ],
+    ),
+    (
+        "composite",
+        &[
+            (
+                "magick base.png logo.png -gravity southeast -geometry +20+20 -composite out.png",
+                "Place a logo 20 pixels in from the bottom right corner",
+            ),
+            (
+                "magick photo.png texture.png -compose multiply -composite out.png",
+                "Blend with a composite method other than over",
+            ),
+        ],
+    ),
+    (
+        "annotate",
+        &[
+            (
+                "magick in.png -gravity north -pointsize 36 -fill white -annotate +0+20 \"Title\" out.png",
+                "Write a white title 20 pixels below the top edge",
+            ),
+            (
+                "magick in.png -fill red -annotate 45x45+100+100 \"Rotated\" out.png",
+                "Write text rotated 45 degrees at 100,100",
+            ),
+        ],
+    ),
+    (
+        "morphology",
+        &[
+            (
+                "magick in.png -morphology Dilate Disk:3 out.png",
+                "Grow light shapes by a disk of radius 3",
+            ),
+            (
+                "magick in.png -morphology EdgeOut Diamond out.png",
+                "Outline shapes from the outside",
+            ),
+            (
+                "magick in.png -morphology Convolve Gaussian:0x2 out.png",
+                "Convolve with a Gaussian kernel of sigma 2",
+            ),
+        ],
+    ),
+    (
+        "level",
+        &[
+            (
+                "magick in.png -level 10%,90% out.png",
+                "Stretch the tones between 10% and 90% over the full range",
+            ),
+            (
+                "magick in.png -level 0%,100%,1.5 out.png",
+                "Brighten the midtones with a gamma of 1.5",
+            ),
+        ],
+    ),
+    (
+        "threshold",
+        &[
+            (
+                "magick in.png -colorspace Gray -threshold 50% out.png",
+                "Turn the image black and white at half brightness",
+            ),
+            (
+                "magick scan.png -threshold 70% out.png",
+                "Keep only the darkest strokes of a scan",
+            ),
+        ],
+    ),
+    (
+        "colorspace",
+        &[
+            (
+                "magick in.png -colorspace Gray out.png",
+                "Convert to grayscale",
+            ),
+            (
+                "magick in.png -colorspace LAB -separate lab-%d.png",
+                "Write the L, a, and b channels to separate files",
+            ),
+        ],
+    ),
+    (
+        "modulate",
+        &[
+            (
+                "magick in.png -modulate 110,130,100 out.png",
+                "Brightness to 110% and saturation to 130%, hue unchanged",
+            ),
+            (
+                "magick in.png -modulate 100,100,150 out.png",
+                "Rotate the hue by 90 degrees, 200 being a full half turn",
+            ),
+        ],
+    ),
+    (
+        "blur",
+        &[
+            (
+                "magick in.png -blur 0x3 out.png",
+                "Blur with sigma 3, letting ImageMagick pick the radius",
+            ),
+            (
+                "magick in.png -channel A -blur 0x8 +channel out.png",
+                "Soften only the edges of the transparency",
+            ),
+        ],
+    ),
+    (
+        "unsharp",
+        &[
+            (
+                "magick in.png -unsharp 0x1+1.0+0.05 out.png",
+                "Sharpen with sigma 1, full gain, ignoring differences under 5%",
+            ),
+            (
+                "magick in.png -resize 50% -unsharp 0x0.75 out.png",
+                "Restore crispness after downscaling",
+            ),
+        ],
+    ),
+    (
+        "rotate",
+        &[
+            (
+                "magick in.png -background none -rotate 15 out.png",
+                "Rotate 15 degrees clockwise with transparent corners",
+            ),
+            (
+                "magick in.png -rotate \"90>\" out.png",
+                "Rotate only when the image is wider than tall",
+            ),
+        ],
+    ),
+    (
+        "border",
+        &[
+            (
+                "magick in.png -bordercolor black -border 10 out.png",
+                "Add a 10 pixel black border on every side",
+            ),
+            (
+                "magick in.png -bordercolor white -border 20x0 out.png",
+                "Pad only the left and right by 20 pixels",
+            ),
+        ],
+    ),
+    (
+        "trim",
+        &[
+            (
+                "magick in.png -trim +repage out.png",
+                "Remove edges the color of the corners",
+            ),
+            (
+                "magick in.png -fuzz 5% -trim +repage out.png",
+                "Also remove edges within 5% of that color, e.g. from JPEG noise",
+            ),
+        ],
+    ),
+    (
+        "draw",
+        &[
+            (
+                "magick in.png -fill none -stroke red -strokewidth 3 -draw \"rectangle 10,10 110,60\" out.png",
+                "Outline a rectangle in red",
+            ),
+            (
+                "magick in.png -fill blue -draw \"circle 50,50 50,10\" out.png",
+                "Fill a circle centered at 50,50 through 50,10",
+            ),
+        ],
+    ),
+    (
+        "sparse-color",
+        &[
+            (
+                "magick -size 400x300 xc: -sparse-color Barycentric \"0,0 red 400,300 blue\" out.png",
+                "Draw a diagonal gradient from red to blue",
+            ),
+            (
+                "magick -size 200x200 xc: -sparse-color Voronoi \"50,50 red 150,150 yellow\" out.png",
+                "Split the canvas between the nearest colored points",
+            ),
+        ],
+    ),
+    (
+        "evaluate",
+        &[
+            (
+                "magick in.png -evaluate multiply 0.5 out.png",
+                "Halve every channel value",
+            ),
+            (
+                "magick in.png -alpha set -channel A -evaluate set 50% +channel out.png",
+                "Make the image half transparent",
+            ),
+        ],
+    ),
+    (
+        "fx",
+        &[
+            (
+                "magick in.png -fx \"(r+g+b)/3\" out.png",
+                "Average the channels into gray",
+            ),
+            (
+                "magick in.png -channel B -fx \"0\" +channel out.png",
+                "Zero the blue channel",
+            ),
+        ],
+    ),
+    (
+        "alpha",
+        &[
+            ("magick in.png -alpha off out.png", "Drop the transparency"),
+            (
+                "magick in.png -alpha extract mask.png",
+                "Write the transparency as a grayscale mask",
+            ),
+        ],
+    ),
+    (
+        "quality",
+        &[
+            (
+                "magick in.png -quality 85 out.jpg",
+                "Write a JPEG at quality 85",
+            ),
+            (
+                "magick in.png -quality 95 out.png",
+                "For PNG, the digits pick zlib level 9 and adaptive filtering",
+            ),
+        ],
+    ),
+    (
+        "define",
+        &[
+            (
+                "magick in.png -define jpeg:extent=200kb out.jpg",
+                "Pick the JPEG quality which stays under 200 KB",
+            ),
+            (
+                "magick in.png -define webp:lossless=true out.webp",
+                "Write lossless WebP",
+            ),
+        ],
+    ),
+    (
+        "layers",
+        &[
+            (
+                "magick frame-*.png -layers optimize anim.gif",
+                "Shrink an animation by storing only the changed pixels",
+            ),
+            (
+                "magick a.png b.png -background none -layers merge +repage out.png",
+                "Merge layers into one image as large as all of them",
+            ),
+        ],
+    ),
+    (
+        "filter",
+        &[
+            (
+                "magick in.png -filter Lanczos -resize 50% out.png",
+                "Downscale with a sharp filter",
+            ),
+            (
+                "magick sprite.png -filter Point -resize 400% out.png",
+                "Upscale pixel art without smoothing",
+            ),
+        ],
+    ),
+    (
+        "fuzz",
+        &[
+            (
+                "magick in.png -fuzz 10% -transparent white out.png",
+                "Make white and near-white transparent",
+            ),
+            (
+                "magick in.png -fuzz 5% -fill white -opaque \"#f0f0f0\" out.png",
+                "Replace a light gray and colors near it with white",
+            ),
+        ],
+    ),
+    (
+        "density",
+        &[
+            (
+                "magick -density 300 doc.pdf page-%d.png",
+                "Render PDF pages at 300 DPI, set before reading",
+            ),
+            (
+                "magick in.png -units PixelsPerInch -density 300 out.png",
+                "Record 300 DPI for print without touching the pixels",
+            ),
+        ],
+    ),
+];
+
+/// One way of writing an option and its arguments
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArgumentForm {
+    /// The option and its placeholders, e.g. `-distort method args`
+    pub form: String,
+    /// The placeholders the option takes, e.g. `method` and `args`
+    pub arguments: Vec<String>,
+    /// What the option does in this form
+    pub description: String,
+}
+
+impl ArgumentForm {
+    /// Parse the line of `magick -help` documenting a form, whose description follows at
+    /// least two spaces or is wrapped onto the next line
+    fn parse(line: &str) -> Self {
+        let line = line.trim();
+        let (form, description) = line
+            .split_once("  ")
+            .map_or((line, ""), |(form, description)| (form, description));
+        ArgumentForm {
+            form: form.to_string(),
+            arguments: form
+                .split_whitespace()
+                .skip(1)
+                .map(str::to_string)
+                .collect(),
+            description: description.trim().to_string(),
+        }
+    }
+}
+
+/// A complete command using an option
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OptionExample {
+    pub command: String,
+    pub description: String,
+}
+
+/// What arguments an operator takes, with examples of using it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OptionHelp {
+    /// The option as looked up, e.g. `-distort`
+    pub option: String,
+    /// Each documented form of the option, including its `+` form
+    pub forms: Vec<ArgumentForm>,
+    /// The `magick -list` type holding the keywords the option accepts, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub list_type: Option<String>,
+    /// Keywords the option accepts, e.g. distortion methods for `-distort`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    /// Examples of using the option, when it is a common one
+    pub examples: Vec<OptionExample>,
+}
+
+/// Operation which explains the arguments of one operator
+pub struct OptionHelper<'a> {
+    magick_runner: MagickRunner<'a>,
+    topics: HelpTopics<'a>,
+}
+
+impl<'a> OptionHelper<'a> {
+    /// Create a new OptionHelper with the provided CommandRunner
+    pub fn new(command_runner: &'a dyn CommandRunner) -> Self {
+        OptionHelper {
+            magick_runner: MagickRunner::new(command_runner, None),
+            topics: HelpTopics::new(command_runner),
+        }
+    }
+
+    /// Look up an operator
+    ///
+    /// # Arguments
+    ///
+    /// * `option` - An operator such as `-distort`, `distort`, or `+repage`
+    ///
+    /// # Returns
+    ///
+    /// Returns the `OptionHelp`, or an `OperationError` naming related options if nothing
+    /// matches
+    pub fn lookup(&self, option: &str) -> Result<OptionHelp, OperationError> {
+        let name = option.trim().trim_start_matches(['-', '+']);
+        // Always look up the option, even when it is named like a subcommand
+        let topic = self.topics.lookup(&format!("-{name}"))?;
+        let name = topic.topic.trim_start_matches('-');
+        let help = self.magick_runner.execute_args(&["-help"])?;
+        let examples = EXAMPLES
+            .iter()
+            .find(|(option, _)| *option == name)
+            .map(|(_, examples)| {
+                examples
+                    .iter()
+                    .map(|(command, description)| OptionExample {
+                        command: command.to_string(),
+                        description: description.to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(OptionHelp {
+            forms: argument_forms(&help, name),
+            option: topic.topic,
+            list_type: topic.list_type,
+            keywords: topic.values,
+            examples,
+        })
+    }
+}
+
+/// The forms of an option documented by `magick -help`, including its `+` form
+///
+/// The usage lines of `HelpTopic` join wrapped descriptions to their option, so the forms are
+/// read from the help text, where the description starts on a line of its own.
+fn argument_forms(help: &str, name: &str) -> Vec<ArgumentForm> {
+    let mut forms: Vec<ArgumentForm> = Vec::new();
+    let mut in_option = false;
+    for line in help.lines() {
+        match documented_option(line) {
+            Some(option) => {
+                in_option = option == name;
+                if in_option {
+                    forms.push(ArgumentForm::parse(line));
+                }
+            }
+            None if in_option && line.starts_with(' ') && !line.trim().is_empty() => {
+                if let Some(form) = forms.last_mut() {
+                    if !form.description.is_empty() {
+                        form.description.push(' ');
+                    }
+                    form.description.push_str(line.trim());
+                }
+            }
+            None => in_option = false,
+        }
+    }
+    forms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::path::Path;
+
+    const HELP_OUTPUT: &str = "Image Operators:
+  -distort method args
+                       distort images according to given method and args
+  -resize geometry     resize the image
+
+Miscellaneous Options:
+  -repage geometry     size and location of an image canvas
+  +repage              reset the image page geometry
+";
+
+    /// Documents -distort and -repage, listing distortion methods
+    struct MockCommandRunner;
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            let output = match args {
+                ["-help"] => HELP_OUTPUT,
+                ["-list", "list"] => "Distort\nGravity\n",
+                ["-list", "Distort"] => "Affine\nArc\nPerspective\nSRT\n",
+                _ => "",
+            };
+            Ok(output.to_string())
+        }
+    }
+
+    #[test]
+    fn test_operator_with_keywords_and_examples() {
+        let help = OptionHelper::new(&MockCommandRunner)
+            .lookup("distort")
+            .unwrap();
+
+        assert_eq!(help.option, "-distort");
+        assert_eq!(
+            help.forms,
+            vec![ArgumentForm {
+                form: "-distort method args".to_string(),
+                arguments: vec!["method".to_string(), "args".to_string()],
+                description: "distort images according to given method and args".to_string(),
+            }]
+        );
+        assert_eq!(help.list_type.as_deref(), Some("Distort"));
+        assert_eq!(help.keywords, vec!["Affine", "Arc", "Perspective", "SRT"]);
+        assert_eq!(help.examples.len(), 3);
+        assert!(help.examples[0].command.contains("-distort SRT"));
+    }
+
+    #[test]
+    fn test_plus_form_without_arguments() {
+        let help = OptionHelper::new(&MockCommandRunner)
+            .lookup("+repage")
+            .unwrap();
+
+        assert_eq!(help.forms.len(), 2);
+        assert_eq!(help.forms[1].form, "+repage");
+        assert!(help.forms[1].arguments.is_empty());
+        assert_eq!(help.forms[1].description, "reset the image page geometry");
+        assert!(help.keywords.is_empty());
+        assert!(help.examples.is_empty());
+    }
+
+    #[test]
+    fn test_examples_use_their_option() {
+        for (option, examples) in EXAMPLES {
+            assert!((2..=3).contains(&examples.len()), "{option}");
+            for (command, _) in *examples {
+                assert!(command.starts_with("magick "), "{command}");
+                assert!(command.contains(&format!("-{option} ")), "{command}");
+            }
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::identify::{self, ImageDimensions};
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// Options for rotating and mirroring an image
+///
+/// The steps are applied in the order auto-orient, rotate, flip, flop.
+#[derive(Debug, Clone)]
+pub struct OrientOptions {
+    /// Path to the source image
+    pub input: String,
+    /// Path to write the reoriented image to
+    pub output: String,
+    /// Turn the image upright according to its EXIF orientation, and reset the orientation tag
+    pub auto_orient: bool,
+    /// Degrees to rotate clockwise, between -360 and 360
+    pub rotate: Option<f64>,
+    /// Mirror the image top to bottom
+    pub flip: bool,
+    /// Mirror the image left to right
+    pub flop: bool,
+    /// Color of the corners uncovered by rotating by an angle which is not a multiple of 90
+    pub background: String,
+}
+
+impl OrientOptions {
+    /// Create options which change nothing until a step is set
+    pub fn new(input: &str, output: &str) -> Self {
+        OrientOptions {
+            input: input.to_string(),
+            output: output.to_string(),
+            auto_orient: false,
+            rotate: None,
+            flip: false,
+            flop: false,
+            background: "white".to_string(),
+        }
+    }
+}
+
+/// The result of reorienting an image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrientedImage {
+    /// The written output
+    pub output: ResolvedOutput,
+    /// Dimensions of the output, which change when the image is turned sideways or rotated
+    pub dimensions: ImageDimensions,
+}
+
+/// Operation which rotates, mirrors, and auto-orients an image
+pub struct Orient<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> Orient<'a> {
+    /// Create a new Orient with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Orient {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Reorient the image and read the dimensions of the result
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output and its dimensions, or an `OperationError` on failure
+    pub fn run(&self, options: &OrientOptions) -> Result<OrientedImage, OperationError> {
+        let args = build_args(options)?;
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        let output = self.magick_runner.last_resolved_output(&options.output);
+        let dimensions = identify::dimensions(&self.magick_runner, &output.path.to_string_lossy())?;
+        Ok(OrientedImage { output, dimensions })
+    }
+}
+
+/// Build the `magick` arguments reorienting the image
+fn build_args(options: &OrientOptions) -> Result<Vec<String>, OperationError> {
+    if !options.auto_orient && options.rotate.is_none() && !options.flip && !options.flop {
+        return Err(OperationError::InvalidParameter(
+            "at least one of auto_orient, rotate, flip, or flop is required".to_string(),
+        ));
+    }
+    let mut args = vec![options.input.clone()];
+    if options.auto_orient {
+        args.push("-auto-orient".to_string());
+    }
+    if let Some(degrees) = options.rotate {
+        if !degrees.is_finite() || !(-360.0..=360.0).contains(&degrees) {
+            return Err(OperationError::InvalidParameter(format!(
+                "rotate {degrees} must be between -360 and 360 degrees"
+            )));
+        }
+        args.extend([
+            "-background".to_string(),
+            options.background.clone(),
+            "-rotate".to_string(),
+            degrees.to_string(),
+        ]);
+    }
+    if options.flip {
+        args.push("-flip".to_string());
+    }
+    if options.flop {
+        args.push("-flop".to_string());
+    }
+    args.extend(["+repage".to_string(), options.output.clone()]);
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+
+    struct MockCommandRunner {
+        calls: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|arg| arg.to_string()).collect());
+            Ok(if args[0] == "identify" {
+                "480 640".to_string()
+            } else {
+                String::new()
+            })
+        }
+    }
+
+    #[test]
+    fn test_orient_reports_new_dimensions() {
+        let runner = MockCommandRunner {
+            calls: RefCell::new(Vec::new()),
+        };
+        let mut options = OrientOptions::new("photo.jpg", "upright.jpg");
+        options.auto_orient = true;
+        options.rotate = Some(90.0);
+        options.flop = true;
+
+        let oriented = Orient::new(&runner, None).run(&options).unwrap();
+
+        assert_eq!(
+            oriented.dimensions,
+            ImageDimensions {
+                width: 480,
+                height: 640
+            }
+        );
+        let calls = runner.calls.borrow();
+        assert_eq!(
+            calls[0],
+            vec![
+                "photo.jpg",
+                "-auto-orient",
+                "-background",
+                "white",
+                "-rotate",
+                "90",
+                "-flop",
+                "+repage",
+                "upright.jpg"
+            ]
+        );
+        assert_eq!(
+            calls[1],
+            vec!["identify", "-format", "%w %h", "upright.jpg[0]"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        assert!(build_args(&OrientOptions::new("in.png", "out.png")).is_err());
+
+        let mut options = OrientOptions::new("in.png", "out.png");
+        options.rotate = Some(720.0);
+        assert!(build_args(&options).is_err());
+
+        options.rotate = Some(f64::NAN);
+        assert!(build_args(&options).is_err());
+
+        options.rotate = Some(-45.5);
+        assert!(build_args(&options).unwrap().contains(&"-45.5".to_string()));
+    }
+}
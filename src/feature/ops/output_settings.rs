@@ -0,0 +1,108 @@
+use crate::feature::config::PresetConfig;
+use crate::feature::ops::OperationError;
+
+/// Finishing settings applied just before an image is written
+///
+/// Shared by the resize, convert, and optimize operations, and usually filled from a preset.
+/// Quality is left to each operation, since optimize searches for its own.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutputSettings {
+    /// Width in pixels larger images are shrunk to fit
+    pub max_width: Option<u32>,
+    /// Height in pixels larger images are shrunk to fit
+    pub max_height: Option<u32>,
+    /// Remove profiles and metadata
+    pub strip: bool,
+    /// Colorspace to convert to, e.g. `sRGB`
+    pub colorspace: Option<String>,
+}
+
+impl OutputSettings {
+    /// Settings taken from a preset
+    pub fn from_preset(preset: &PresetConfig) -> Self {
+        OutputSettings {
+            max_width: preset.max_width,
+            max_height: preset.max_height,
+            strip: preset.strip.unwrap_or(false),
+            colorspace: preset.colorspace.clone(),
+        }
+    }
+
+    /// The `magick` operators applying the settings, in the order they are run
+    ///
+    /// The size limit only ever shrinks, so smaller images are left alone.
+    pub(crate) fn args(&self) -> Result<Vec<String>, OperationError> {
+        let mut args = Vec::new();
+        if self.max_width == Some(0) || self.max_height == Some(0) {
+            return Err(OperationError::InvalidParameter(
+                "max_width and max_height must be greater than zero".to_string(),
+            ));
+        }
+        if self.max_width.is_some() || self.max_height.is_some() {
+            let dimension = |d: Option<u32>| d.map(|d| d.to_string()).unwrap_or_default();
+            args.extend([
+                "-resize".to_string(),
+                format!(
+                    "{}x{}>",
+                    dimension(self.max_width),
+                    dimension(self.max_height)
+                ),
+            ]);
+        }
+        if let Some(colorspace) = &self.colorspace {
+            args.extend(["-colorspace".to_string(), colorspace.clone()]);
+        }
+        if self.strip {
+            args.push("-strip".to_string());
+        }
+        Ok(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_args() {
+        assert!(OutputSettings::default().args().unwrap().is_empty());
+
+        let settings = OutputSettings {
+            max_width: Some(1920),
+            max_height: None,
+            strip: true,
+            colorspace: Some("sRGB".to_string()),
+        };
+        assert_eq!(
+            settings.args().unwrap(),
+            ["-resize", "1920x>", "-colorspace", "sRGB", "-strip"]
+        );
+
+        let settings = OutputSettings {
+            max_height: Some(0),
+            ..Default::default()
+        };
+        assert!(settings.args().is_err());
+    }
+
+    #[test]
+    fn test_from_preset() {
+        let preset = PresetConfig {
+            quality: Some(80),
+            max_height: Some(1080),
+            strip: Some(true),
+            format: Some("webp".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            OutputSettings::from_preset(&preset),
+            OutputSettings {
+                max_width: None,
+                max_height: Some(1080),
+                strip: true,
+                colorspace: None,
+            }
+        );
+    }
+}
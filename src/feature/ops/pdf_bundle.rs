@@ -0,0 +1,319 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// Points per inch, the unit of PDF page sizes and margins
+const POINTS_PER_INCH: u32 = 72;
+
+/// Resolution images are placed at when none is given, in dots per inch
+pub const DEFAULT_BUNDLE_DENSITY: u32 = 150;
+
+/// Size of a PDF page in points
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl PageSize {
+    /// Parse a page size name such as `a4` or `letter`, or a custom size in points such as
+    /// `600x800`
+    pub fn parse(name: &str) -> Result<Self, OperationError> {
+        let (width, height) = match name.to_ascii_lowercase().as_str() {
+            "a3" => (842, 1191),
+            "a4" => (595, 842),
+            "a5" => (420, 595),
+            "letter" => (612, 792),
+            "legal" => (612, 1008),
+            "tabloid" => (792, 1224),
+            other => other
+                .split_once('x')
+                .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+                .filter(|(width, height)| *width > 0 && *height > 0)
+                .ok_or_else(|| {
+                    OperationError::InvalidParameter(format!(
+                        "unknown page size '{name}', expected a3, a4, a5, letter, legal, tabloid, or WIDTHxHEIGHT in points"
+                    ))
+                })?,
+        };
+        Ok(PageSize { width, height })
+    }
+
+    /// The same page turned sideways, so it is wider than it is tall
+    pub fn landscape(self) -> Self {
+        PageSize {
+            width: self.width.max(self.height),
+            height: self.width.min(self.height),
+        }
+    }
+}
+
+/// Options for combining images into a PDF
+#[derive(Debug, Clone)]
+pub struct PdfBundleOptions {
+    /// Paths to the images, in page order
+    pub inputs: Vec<String>,
+    /// Path to write the PDF to
+    pub output: String,
+    /// Size of every page, or `None` for each page to take the size of its image
+    pub page_size: Option<PageSize>,
+    /// Turn the pages sideways
+    pub landscape: bool,
+    /// Blank space around each image, in points
+    pub margin: u32,
+    /// Resolution images are placed at, which also sets the size of pages sized by their
+    /// image, defaults to `DEFAULT_BUNDLE_DENSITY`
+    pub density: Option<u32>,
+    /// Color of the page behind and around the images
+    pub background: String,
+}
+
+impl PdfBundleOptions {
+    /// Create options placing each image on a page of its own size, without margins
+    pub fn new(inputs: Vec<String>, output: &str) -> Self {
+        PdfBundleOptions {
+            inputs,
+            output: output.to_string(),
+            page_size: None,
+            landscape: false,
+            margin: 0,
+            density: None,
+            background: "white".to_string(),
+        }
+    }
+}
+
+/// Operation which combines images into a single PDF, one page per image
+pub struct PdfBundle<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> PdfBundle<'a> {
+    /// Create a new PdfBundle with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        PdfBundle {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Combine the images and write the PDF
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output on success, or an `OperationError` on failure
+    pub fn run(&self, options: &PdfBundleOptions) -> Result<ResolvedOutput, OperationError> {
+        let args = build_args(options)?;
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        Ok(self.magick_runner.last_resolved_output(&options.output))
+    }
+}
+
+/// Build the `magick` arguments combining the images into a PDF
+///
+/// On fixed-size pages each image is scaled to fit inside the margins and centered on a page
+/// sized in pixels at the density, which the PDF writer turns back into the page size in
+/// points. Otherwise each image keeps its size and the margin is added as a border.
+fn build_args(options: &PdfBundleOptions) -> Result<Vec<String>, OperationError> {
+    if options.inputs.is_empty() {
+        return Err(OperationError::InvalidParameter(
+            "at least one input image is required".to_string(),
+        ));
+    }
+    if !options.output.to_ascii_lowercase().ends_with(".pdf") {
+        return Err(OperationError::InvalidParameter(format!(
+            "output '{}' must be a .pdf file",
+            options.output
+        )));
+    }
+    let density = options.density.unwrap_or(DEFAULT_BUNDLE_DENSITY);
+    if density == 0 {
+        return Err(OperationError::InvalidParameter(
+            "density must be greater than 0".to_string(),
+        ));
+    }
+
+    let mut args = options.inputs.clone();
+    args.extend([
+        "-background".to_string(),
+        options.background.clone(),
+        "-alpha".to_string(),
+        "remove".to_string(),
+        "-alpha".to_string(),
+        "off".to_string(),
+    ]);
+    match options.page_size {
+        Some(page_size) => {
+            let page_size = if options.landscape {
+                page_size.landscape()
+            } else {
+                page_size
+            };
+            if options.margin * 2 >= page_size.width.min(page_size.height) {
+                return Err(OperationError::InvalidParameter(format!(
+                    "margin {} leaves no room on a {}x{} point page",
+                    options.margin, page_size.width, page_size.height
+                )));
+            }
+            let pixels = |points: u32| points * density / POINTS_PER_INCH;
+            let page = format!("{}x{}", pixels(page_size.width), pixels(page_size.height));
+            let content = format!(
+                "{}x{}",
+                pixels(page_size.width - options.margin * 2),
+                pixels(page_size.height - options.margin * 2)
+            );
+            args.extend([
+                "-resize".to_string(),
+                content,
+                "-gravity".to_string(),
+                "center".to_string(),
+                "-extent".to_string(),
+                page,
+            ]);
+        }
+        None => {
+            if options.landscape {
+                return Err(OperationError::InvalidParameter(
+                    "landscape requires a page_size".to_string(),
+                ));
+            }
+            if options.margin > 0 {
+                args.extend([
+                    "-bordercolor".to_string(),
+                    options.background.clone(),
+                    "-border".to_string(),
+                    (options.margin * density / POINTS_PER_INCH).to_string(),
+                ]);
+            }
+        }
+    }
+    args.extend([
+        "+repage".to_string(),
+        "-units".to_string(),
+        "PixelsPerInch".to_string(),
+        "-density".to_string(),
+        density.to_string(),
+        options.output.clone(),
+    ]);
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> PdfBundleOptions {
+        PdfBundleOptions::new(
+            vec!["scan-1.jpg".to_string(), "scan-2.png".to_string()],
+            "scans.pdf",
+        )
+    }
+
+    #[test]
+    fn test_parse_page_size() {
+        assert_eq!(
+            PageSize::parse("A4").unwrap(),
+            PageSize {
+                width: 595,
+                height: 842
+            }
+        );
+        assert_eq!(
+            PageSize::parse("400x300").unwrap().landscape(),
+            PageSize {
+                width: 400,
+                height: 300
+            }
+        );
+        assert_eq!(
+            PageSize::parse("letter").unwrap().landscape(),
+            PageSize {
+                width: 792,
+                height: 612
+            }
+        );
+        assert!(PageSize::parse("b5").is_err());
+        assert!(PageSize::parse("0x300").is_err());
+    }
+
+    #[test]
+    fn test_fixed_page_args() {
+        let mut options = options();
+        options.page_size = Some(PageSize::parse("letter").unwrap());
+        options.margin = 36;
+
+        assert_eq!(
+            build_args(&options).unwrap(),
+            vec![
+                "scan-1.jpg",
+                "scan-2.png",
+                "-background",
+                "white",
+                "-alpha",
+                "remove",
+                "-alpha",
+                "off",
+                "-resize",
+                "1125x1500",
+                "-gravity",
+                "center",
+                "-extent",
+                "1275x1650",
+                "+repage",
+                "-units",
+                "PixelsPerInch",
+                "-density",
+                "150",
+                "scans.pdf"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_image_sized_page_args() {
+        let mut options = options();
+        options.margin = 72;
+        options.density = Some(300);
+
+        let args = build_args(&options).unwrap();
+        assert_eq!(
+            args[8..],
+            [
+                "-bordercolor",
+                "white",
+                "-border",
+                "300",
+                "+repage",
+                "-units",
+                "PixelsPerInch",
+                "-density",
+                "300",
+                "scans.pdf"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        let mut options = options();
+        options.output = "scans.png".to_string();
+        assert!(build_args(&options).is_err());
+
+        let mut options = self::options();
+        options.inputs.clear();
+        assert!(build_args(&options).is_err());
+
+        let mut options = self::options();
+        options.page_size = Some(PageSize::parse("a5").unwrap());
+        options.margin = 210;
+        assert!(build_args(&options).is_err());
+    }
+}
@@ -0,0 +1,334 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::shell::CommandRunner;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Resolution PDF pages are rendered at when none is given, in dots per inch
+pub const DEFAULT_PDF_DENSITY: u32 = 150;
+
+/// Options for rendering the pages of a PDF as images
+#[derive(Debug, Clone)]
+pub struct PdfPagesOptions {
+    /// Path to the PDF
+    pub input: String,
+    /// Directory the page images are written to
+    pub output_dir: String,
+    /// Resolution to render at in dots per inch, defaults to `DEFAULT_PDF_DENSITY`
+    pub density: Option<u32>,
+    /// Image format of the pages, `png` or `jpg`, defaults to `png`
+    pub format: Option<String>,
+    /// Number of the first page to render, counting from 1, defaults to the first
+    pub first_page: Option<u32>,
+    /// Number of the last page to render, inclusive, defaults to the last
+    pub last_page: Option<u32>,
+    /// JPEG quality from 1 to 100
+    pub quality: Option<u32>,
+}
+
+impl PdfPagesOptions {
+    /// Create options rendering every page as PNG at the default density
+    pub fn new(input: &str, output_dir: &str) -> Self {
+        PdfPagesOptions {
+            input: input.to_string(),
+            output_dir: output_dir.to_string(),
+            density: None,
+            format: None,
+            first_page: None,
+            last_page: None,
+            quality: None,
+        }
+    }
+}
+
+/// A single rendered page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedPage {
+    /// Number of the page in the PDF, counting from 1, which its file is numbered by
+    pub page: u32,
+    pub path: PathBuf,
+}
+
+/// Operation which renders the pages of a PDF to numbered image files
+///
+/// ImageMagick reads PDFs through Ghostscript, so callers should check it is installed first
+/// to report how to install it rather than a failed command.
+pub struct PdfPages<'a> {
+    magick_runner: MagickRunner<'a>,
+    base: PathBuf,
+}
+
+impl<'a> PdfPages<'a> {
+    /// Create a new PdfPages with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        PdfPages {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+            base: workspace
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(".")),
+        }
+    }
+
+    /// Render the selected pages as `<name>-<page>.<ext>` files
+    ///
+    /// Pages are flattened onto white, since PDF pages without a background would otherwise
+    /// render as black in formats without transparency.
+    ///
+    /// # Returns
+    ///
+    /// Returns the rendered pages in order, or an `OperationError` on failure
+    pub fn run(&self, options: &PdfPagesOptions) -> Result<Vec<RenderedPage>, OperationError> {
+        let extension = page_format(options.format.as_deref())?;
+        let density = options.density.unwrap_or(DEFAULT_PDF_DENSITY);
+        if density == 0 {
+            return Err(OperationError::InvalidParameter(
+                "density must be greater than 0".to_string(),
+            ));
+        }
+        if let Some(quality) = options.quality
+            && !(1..=100).contains(&quality)
+        {
+            return Err(OperationError::InvalidParameter(format!(
+                "quality must be between 1 and 100, got {quality}"
+            )));
+        }
+        let count = self.page_count(&options.input)?;
+        let (first, last) = page_range(options.first_page, options.last_page, count)?;
+        let stem = Path::new(&options.input)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "page".to_string());
+        let digits = count.to_string().len().max(3);
+        fs::create_dir_all(self.base.join(&options.output_dir))?;
+
+        let pattern = Path::new(&options.output_dir)
+            .join(format!(
+                "{}-%0{digits}d.{extension}",
+                stem.replace('%', "%%")
+            ))
+            .to_string_lossy()
+            .to_string();
+        let args = build_args(options, density, extension, first, last, &pattern);
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+
+        (first..=last)
+            .map(|page| {
+                let path = Path::new(&options.output_dir)
+                    .join(format!("{stem}-{page:0digits$}.{extension}"));
+                if !self.base.join(&path).is_file() {
+                    return Err(OperationError::ParseError(format!(
+                        "expected page {page} to be written to {}",
+                        path.display()
+                    )));
+                }
+                Ok(RenderedPage { page, path })
+            })
+            .collect()
+    }
+
+    /// The number of pages in a PDF
+    fn page_count(&self, input: &str) -> Result<u32, OperationError> {
+        let output = self
+            .magick_runner
+            .execute_args(&["identify", "-ping", "-format", "%n\\n", input])?;
+        output
+            .lines()
+            .next()
+            .and_then(|line| line.trim().parse().ok())
+            .filter(|count| *count > 0)
+            .ok_or_else(|| OperationError::ParseError(format!("unexpected page count: {output}")))
+    }
+}
+
+/// The file extension for a requested page format
+fn page_format(format: Option<&str>) -> Result<&'static str, OperationError> {
+    match format
+        .unwrap_or("png")
+        .trim_start_matches('.')
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "png" => Ok("png"),
+        "jpg" | "jpeg" => Ok("jpg"),
+        other => Err(OperationError::InvalidParameter(format!(
+            "unsupported page format '{other}', expected png or jpg"
+        ))),
+    }
+}
+
+/// The inclusive range of pages to render, checked against the number of pages
+fn page_range(
+    first: Option<u32>,
+    last: Option<u32>,
+    count: u32,
+) -> Result<(u32, u32), OperationError> {
+    let first = first.unwrap_or(1);
+    let last = last.unwrap_or(count);
+    if first == 0 {
+        return Err(OperationError::InvalidParameter(
+            "pages are numbered from 1".to_string(),
+        ));
+    }
+    if first > last {
+        return Err(OperationError::InvalidParameter(format!(
+            "first page {first} is after last page {last}"
+        )));
+    }
+    if last > count {
+        return Err(OperationError::InvalidParameter(format!(
+            "page {last} is out of range, the PDF has {count} pages"
+        )));
+    }
+    Ok((first, last))
+}
+
+/// Build the `magick` arguments rendering pages `first` to `last` to `pattern`
+///
+/// `-density` must come before the input for Ghostscript to render at that resolution, and
+/// `input[...]` counts pages from 0.
+fn build_args(
+    options: &PdfPagesOptions,
+    density: u32,
+    extension: &str,
+    first: u32,
+    last: u32,
+    pattern: &str,
+) -> Vec<String> {
+    let mut args = vec![
+        "-density".to_string(),
+        density.to_string(),
+        format!("{}[{}-{}]", options.input, first - 1, last - 1),
+        "-background".to_string(),
+        "white".to_string(),
+        "-alpha".to_string(),
+        "remove".to_string(),
+        "-alpha".to_string(),
+        "off".to_string(),
+    ];
+    if extension == "jpg"
+        && let Some(quality) = options.quality
+    {
+        args.extend(["-quality".to_string(), quality.to_string()]);
+    }
+    args.extend([
+        "-scene".to_string(),
+        first.to_string(),
+        "+adjoin".to_string(),
+        pattern.to_string(),
+    ]);
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+    use tempfile::TempDir;
+
+    /// Mock implementation of CommandRunner for a 5 page PDF, writing every page numbered from
+    /// the `-scene` number up to the last page
+    struct MockCommandRunner {
+        calls: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|s| s.to_string()).collect());
+            if args[0] == "identify" {
+                return Ok("5\n".repeat(5));
+            }
+            let scene = args.iter().position(|arg| *arg == "-scene").unwrap();
+            let first: usize = args[scene + 1].parse().unwrap();
+            for page in first..=5 {
+                let pattern = args[args.len() - 1].replace("%03d", &format!("{page:03}"));
+                fs::write(working_dir.unwrap().join(pattern), b"").unwrap();
+            }
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_run_renders_page_range() {
+        let dir = TempDir::new().unwrap();
+        let runner = MockCommandRunner {
+            calls: RefCell::new(Vec::new()),
+        };
+        let mut options = PdfPagesOptions::new("docs/report.pdf", "pages");
+        options.first_page = Some(4);
+        options.density = Some(300);
+        options.format = Some("jpeg".to_string());
+        options.quality = Some(85);
+
+        let pages = PdfPages::new(&runner, Some(dir.path()))
+            .run(&options)
+            .unwrap();
+
+        assert_eq!(
+            pages,
+            vec![
+                RenderedPage {
+                    page: 4,
+                    path: PathBuf::from("pages/report-004.jpg")
+                },
+                RenderedPage {
+                    page: 5,
+                    path: PathBuf::from("pages/report-005.jpg")
+                },
+            ]
+        );
+        assert_eq!(
+            runner.calls.borrow()[1],
+            vec![
+                "-density",
+                "300",
+                "docs/report.pdf[3-4]",
+                "-background",
+                "white",
+                "-alpha",
+                "remove",
+                "-alpha",
+                "off",
+                "-quality",
+                "85",
+                "-scene",
+                "4",
+                "+adjoin",
+                "pages/report-%03d.jpg"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_run_rejects_invalid_options() {
+        let runner = MockCommandRunner {
+            calls: RefCell::new(Vec::new()),
+        };
+        let pdf_pages = PdfPages::new(&runner, None);
+
+        let mut options = PdfPagesOptions::new("report.pdf", "pages");
+        options.format = Some("gif".to_string());
+        assert!(pdf_pages.run(&options).is_err());
+
+        let mut options = PdfPagesOptions::new("report.pdf", "pages");
+        options.density = Some(0);
+        assert!(pdf_pages.run(&options).is_err());
+        assert!(runner.calls.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_page_range_validation() {
+        assert_eq!(page_range(None, None, 5).unwrap(), (1, 5));
+        assert!(page_range(Some(0), None, 5).is_err());
+        assert!(page_range(Some(3), Some(2), 5).is_err());
+        assert!(page_range(None, Some(6), 5).is_err());
+    }
+}
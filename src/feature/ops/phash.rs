@@ -0,0 +1,126 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+
+/// Width of the grayscale thumbnail the difference hash is computed from
+const HASH_COLUMNS: usize = 9;
+/// Height of the grayscale thumbnail the difference hash is computed from
+const HASH_ROWS: usize = 8;
+
+/// Compute a 64-bit difference hash (dHash) of an image
+///
+/// The image is reduced to a 9x8 grayscale thumbnail and each bit records whether a pixel is
+/// brighter than its right-hand neighbour. Visually similar images produce hashes with a small
+/// Hamming distance, regardless of size or compression.
+pub(crate) fn difference_hash(runner: &MagickRunner, path: &str) -> Result<u64, OperationError> {
+    let input = format!("{path}[0]");
+    let size = format!("{HASH_COLUMNS}x{HASH_ROWS}!");
+    let output = runner.execute_args(&[
+        &input,
+        "-colorspace",
+        "Gray",
+        "-resize",
+        &size,
+        "-depth",
+        "8",
+        "-compress",
+        "none",
+        "pgm:-",
+    ])?;
+    let pixels = parse_plain_pgm(&output)?;
+    Ok(hash_pixels(&pixels))
+}
+
+/// Number of differing bits between two hashes
+pub(crate) fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Parse plain (P2) PGM output into grayscale values, checking the expected dimensions
+fn parse_plain_pgm(output: &str) -> Result<Vec<u8>, OperationError> {
+    let mut tokens = output
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or(""))
+        .flat_map(str::split_whitespace);
+    if tokens.next() != Some("P2") {
+        return Err(OperationError::ParseError(
+            "expected plain PGM output".to_string(),
+        ));
+    }
+    let mut header = [0usize; 3];
+    for value in header.iter_mut() {
+        *value = tokens
+            .next()
+            .and_then(|token| token.parse().ok())
+            .ok_or_else(|| OperationError::ParseError("malformed PGM header".to_string()))?;
+    }
+    let [width, height, _max] = header;
+    if width != HASH_COLUMNS || height != HASH_ROWS {
+        return Err(OperationError::ParseError(format!(
+            "expected {HASH_COLUMNS}x{HASH_ROWS} thumbnail, got {width}x{height}"
+        )));
+    }
+    let pixels: Vec<u8> = tokens
+        .map(|token| token.parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| OperationError::ParseError("malformed PGM pixel value".to_string()))?;
+    if pixels.len() != width * height {
+        return Err(OperationError::ParseError(format!(
+            "expected {} pixels, got {}",
+            width * height,
+            pixels.len()
+        )));
+    }
+    Ok(pixels)
+}
+
+/// Build the hash from row-major 9x8 grayscale pixels
+fn hash_pixels(pixels: &[u8]) -> u64 {
+    let mut hash = 0u64;
+    for row in 0..HASH_ROWS {
+        for column in 0..HASH_COLUMNS - 1 {
+            let left = pixels[row * HASH_COLUMNS + column];
+            let right = pixels[row * HASH_COLUMNS + column + 1];
+            hash = (hash << 1) | u64::from(left > right);
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain_pgm(pixels: &[u8]) -> String {
+        let values: Vec<String> = pixels.iter().map(u8::to_string).collect();
+        format!("P2\n# comment\n9 8\n255\n{}\n", values.join(" "))
+    }
+
+    #[test]
+    fn test_hash_of_descending_rows_sets_every_bit() {
+        let row: Vec<u8> = (0..9).rev().map(|v| v * 10).collect();
+        let pixels = row.repeat(8);
+        let parsed = parse_plain_pgm(&plain_pgm(&pixels)).unwrap();
+        assert_eq!(hash_pixels(&parsed), u64::MAX);
+    }
+
+    #[test]
+    fn test_hash_of_flat_image_is_zero() {
+        let parsed = parse_plain_pgm(&plain_pgm(&[128; 72])).unwrap();
+        assert_eq!(hash_pixels(&parsed), 0);
+    }
+
+    #[test]
+    fn test_parse_rejects_wrong_size() {
+        let output = "P2\n4 4\n255\n0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0\n";
+        assert!(matches!(
+            parse_plain_pgm(output),
+            Err(OperationError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_hamming_distance() {
+        assert_eq!(hamming_distance(0b1011, 0b0001), 2);
+        assert_eq!(hamming_distance(u64::MAX, 0), 64);
+    }
+}
@@ -0,0 +1,230 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// An 8-bit RGB color
+type Rgb = (u8, u8, u8);
+
+/// Widest preview rendered, in terminal columns; wider requests are narrowed to it
+///
+/// The `txt:` enumeration spends about 50 bytes a pixel, so this keeps the largest preview
+/// within the runner's cap on standard output.
+const MAX_COLUMNS: u32 = 256;
+
+/// A decoded grid of pixels
+#[derive(Debug, PartialEq)]
+struct PixelGrid {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl PixelGrid {
+    /// Get the pixel at a position, returning black outside the grid
+    fn get(&self, x: usize, y: usize) -> Rgb {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x]
+        } else {
+            (0, 0, 0)
+        }
+    }
+}
+
+/// Renders small ANSI truecolor previews of images for the terminal
+pub struct Previewer<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> Previewer<'a> {
+    /// Create a new Previewer with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Previewer {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Render an image as rows of half-block characters
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the image to preview
+    /// * `columns` - Maximum width of the preview in terminal columns, at most `MAX_COLUMNS`
+    ///
+    /// # Returns
+    ///
+    /// Returns the ANSI-escaped preview text, or an `OperationError` on failure
+    pub fn render(&self, path: &str, columns: u32) -> Result<String, OperationError> {
+        if columns == 0 {
+            return Err(OperationError::InvalidParameter(
+                "preview width must be greater than zero".to_string(),
+            ));
+        }
+        let frame = format!("{path}[0]");
+        let geometry = resize_geometry(columns);
+        let output = self.magick_runner.execute_args(&[
+            &frame,
+            "-background",
+            "black",
+            "-alpha",
+            "remove",
+            "-resize",
+            &geometry,
+            "-colorspace",
+            "sRGB",
+            "-depth",
+            "8",
+            "txt:-",
+        ])?;
+        let grid = parse_pixel_enumeration(&output)?;
+        Ok(render_half_blocks(&grid))
+    }
+}
+
+/// The `-resize` geometry fitting an image into a preview `columns` wide
+///
+/// Each character cell shows two vertically stacked pixels, so rows are capped at twice the
+/// column count to keep tall images from scrolling off screen.
+fn resize_geometry(columns: u32) -> String {
+    let columns = columns.min(MAX_COLUMNS);
+    format!("{columns}x{}>", columns * 2)
+}
+
+/// Parse ImageMagick's `txt:` pixel enumeration format
+///
+/// Lines look like `3,1: (255,128,0)  #FF8000  srgb(255,128,0)` after a `# ImageMagick pixel
+/// enumeration: W,H,...` header.
+fn parse_pixel_enumeration(output: &str) -> Result<PixelGrid, OperationError> {
+    let mut lines = output.lines();
+    let header = lines
+        .next()
+        .and_then(|line| line.strip_prefix("# ImageMagick pixel enumeration:"))
+        .ok_or_else(|| {
+            OperationError::ParseError("missing pixel enumeration header".to_string())
+        })?;
+    let mut dims = header.trim().split(',');
+    let mut next_dim = || -> Result<usize, OperationError> {
+        dims.next()
+            .and_then(|d| d.trim().parse().ok())
+            .ok_or_else(|| OperationError::ParseError(format!("invalid header: {header}")))
+    };
+    let width = next_dim()?;
+    let height = next_dim()?;
+
+    let mut pixels = vec![(0, 0, 0); width * height];
+    for line in lines {
+        let Some((position, rest)) = line.split_once(':') else {
+            continue;
+        };
+        let Some((x, y)) = position.split_once(',') else {
+            continue;
+        };
+        let (Ok(x), Ok(y)) = (x.trim().parse::<usize>(), y.trim().parse::<usize>()) else {
+            continue;
+        };
+        if x >= width || y >= height {
+            continue;
+        }
+        if let Some(color) = parse_hex_color(rest) {
+            pixels[y * width + x] = color;
+        }
+    }
+    Ok(PixelGrid {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// Extract the `#RRGGBB` color from the remainder of a pixel enumeration line
+fn parse_hex_color(rest: &str) -> Option<Rgb> {
+    let hex = rest
+        .split_whitespace()
+        .find_map(|token| token.strip_prefix('#'))?;
+    if hex.len() < 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Render a pixel grid using upper half-blocks with truecolor foreground/background
+fn render_half_blocks(grid: &PixelGrid) -> String {
+    let mut out = String::new();
+    for y in (0..grid.height).step_by(2) {
+        for x in 0..grid.width {
+            let (tr, tg, tb) = grid.get(x, y);
+            let (br, bg, bb) = grid.get(x, y + 1);
+            out.push_str(&format!(
+                "\x1b[38;2;{tr};{tg};{tb}m\x1b[48;2;{br};{bg};{bb}m\u{2580}"
+            ));
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENUMERATION: &str = "# ImageMagick pixel enumeration: 2,2,0,255,srgb\n\
+        0,0: (255,0,0)  #FF0000  red\n\
+        1,0: (0,255,0)  #00FF00  lime\n\
+        0,1: (0,0,255)  #0000FF  blue\n\
+        1,1: (255,255,255)  #FFFFFF  white\n";
+
+    #[test]
+    fn test_resize_geometry() {
+        assert_eq!(resize_geometry(80), "80x160>");
+        assert_eq!(resize_geometry(u32::MAX), "256x512>");
+    }
+
+    #[test]
+    fn test_max_preview_fits_stdout_cap() {
+        let (width, height) = (MAX_COLUMNS, MAX_COLUMNS * 2);
+        let header = format!("# ImageMagick pixel enumeration: {width},{height},0,255,srgb\n");
+        let widest_line = format!(
+            "{},{}: (255,255,255)  #FFFFFF  srgb(255,255,255)\n",
+            width - 1,
+            height - 1
+        );
+        let total = header.len() + widest_line.len() * (width * height) as usize;
+        assert!(total <= crate::feature::magick::MAX_STDOUT_BYTES);
+    }
+
+    #[test]
+    fn test_parse_pixel_enumeration() {
+        let grid = parse_pixel_enumeration(ENUMERATION).unwrap();
+        assert_eq!(grid.width, 2);
+        assert_eq!(grid.height, 2);
+        assert_eq!(grid.get(0, 0), (255, 0, 0));
+        assert_eq!(grid.get(1, 1), (255, 255, 255));
+    }
+
+    #[test]
+    fn test_parse_pixel_enumeration_missing_header() {
+        assert!(parse_pixel_enumeration("0,0: (0,0,0) #000000 black").is_err());
+    }
+
+    #[test]
+    fn test_render_half_blocks() {
+        let grid = parse_pixel_enumeration(ENUMERATION).unwrap();
+        let rendered = render_half_blocks(&grid);
+
+        assert_eq!(rendered.lines().count(), 1);
+        assert!(rendered.starts_with("\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m\u{2580}"));
+        assert!(rendered.ends_with("\x1b[0m\n"));
+    }
+
+    #[test]
+    fn test_render_odd_height_pads_with_black() {
+        let grid = PixelGrid {
+            width: 1,
+            height: 1,
+            pixels: vec![(10, 20, 30)],
+        };
+        let rendered = render_half_blocks(&grid);
+        assert!(rendered.contains("\x1b[48;2;0;0;0m"));
+    }
+}
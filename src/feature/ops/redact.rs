@@ -0,0 +1,347 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::identify::{self, ImageDimensions};
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// Smallest pixelate block size or blur sigma which visibly obscures a region
+///
+/// A block size of one scales the region to 100% and back, leaving it untouched.
+pub const MIN_REDACT_STRENGTH: u32 = 2;
+
+/// A rectangular region of an image in pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Region {
+    /// Format the region as an ImageMagick `WxH+X+Y` geometry
+    fn geometry(&self) -> String {
+        format!("{}x{}+{}+{}", self.width, self.height, self.x, self.y)
+    }
+
+    /// The part of the region inside an image, or `None` if none of it is
+    fn clip(&self, image: ImageDimensions) -> Option<Region> {
+        let right = self.x.saturating_add(self.width).min(image.width);
+        let bottom = self.y.saturating_add(self.height).min(image.height);
+        (self.x < right && self.y < bottom).then(|| Region {
+            x: self.x,
+            y: self.y,
+            width: right - self.x,
+            height: bottom - self.y,
+        })
+    }
+}
+
+/// How redacted regions are obscured
+#[derive(Debug, Clone, PartialEq)]
+pub enum RedactionMode {
+    /// Downscale then upscale the region so detail is lost in large blocks
+    Pixelate,
+    /// Apply a heavy gaussian blur to the region
+    Blur,
+    /// Paint the region with a solid color
+    Fill(String),
+}
+
+impl RedactionMode {
+    /// Parse a mode name, using `color` for `fill`
+    pub fn parse(mode: &str, color: Option<&str>) -> Result<Self, OperationError> {
+        match mode {
+            "pixelate" => Ok(RedactionMode::Pixelate),
+            "blur" => Ok(RedactionMode::Blur),
+            "fill" => Ok(RedactionMode::Fill(color.unwrap_or("black").to_string())),
+            other => Err(OperationError::InvalidParameter(format!(
+                "unknown redaction mode '{other}', expected pixelate, blur, or fill"
+            ))),
+        }
+    }
+}
+
+/// Options for redacting regions of an image
+#[derive(Debug, Clone)]
+pub struct RedactOptions {
+    /// Path to the source image
+    pub input: String,
+    /// Path to write the redacted copy to
+    pub output: String,
+    /// Regions to obscure
+    pub regions: Vec<Region>,
+    /// How the regions are obscured
+    pub mode: RedactionMode,
+    /// Strength of the effect: pixel block size for pixelate, sigma for blur, at least
+    /// `MIN_REDACT_STRENGTH`
+    pub strength: u32,
+}
+
+/// Operation which obscures rectangular regions of an image
+pub struct Redact<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> Redact<'a> {
+    /// Create a new Redact with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Redact {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Obscure the regions and write the redacted copy
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output on success, or an `OperationError` on failure
+    pub fn run(&self, options: &RedactOptions) -> Result<ResolvedOutput, OperationError> {
+        let image = identify::dimensions(&self.magick_runner, &options.input)?;
+        let args = build_args(options, image)?;
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        Ok(self.magick_runner.last_resolved_output(&options.output))
+    }
+}
+
+/// Build the `magick` arguments for a redaction
+///
+/// Pixelate and blur crop a clone of each region, process it, and composite it back in place.
+/// Fill draws solid rectangles directly. Regions are clipped to the image, and a region which
+/// misses it entirely fails rather than leaving what it was meant to hide untouched. For the same
+/// reason a pixelate or blur strength below `MIN_REDACT_STRENGTH` fails.
+fn build_args(
+    options: &RedactOptions,
+    image: ImageDimensions,
+) -> Result<Vec<String>, OperationError> {
+    if options.regions.is_empty() {
+        return Err(OperationError::InvalidParameter(
+            "at least one region is required".to_string(),
+        ));
+    }
+    let mut regions = Vec::with_capacity(options.regions.len());
+    for region in &options.regions {
+        if region.width == 0 || region.height == 0 {
+            return Err(OperationError::InvalidParameter(format!(
+                "region {} has zero width or height",
+                region.geometry()
+            )));
+        }
+        let clipped = region.clip(image).ok_or_else(|| {
+            OperationError::InvalidParameter(format!(
+                "region {} lies outside the {}x{} image",
+                region.geometry(),
+                image.width,
+                image.height
+            ))
+        })?;
+        regions.push(clipped);
+    }
+    let strength = options.strength;
+    if !matches!(options.mode, RedactionMode::Fill(_)) && strength < MIN_REDACT_STRENGTH {
+        return Err(OperationError::InvalidParameter(format!(
+            "strength {strength} would leave the regions readable, expected at least {MIN_REDACT_STRENGTH}"
+        )));
+    }
+
+    let mut args = vec![options.input.clone()];
+    for region in &regions {
+        match &options.mode {
+            RedactionMode::Fill(color) => {
+                args.extend([
+                    "-fill".to_string(),
+                    color.clone(),
+                    "-draw".to_string(),
+                    format!(
+                        "rectangle {},{} {},{}",
+                        region.x,
+                        region.y,
+                        region.x + (region.width - 1),
+                        region.y + (region.height - 1)
+                    ),
+                ]);
+            }
+            RedactionMode::Pixelate | RedactionMode::Blur => {
+                args.extend([
+                    "(".to_string(),
+                    "-clone".to_string(),
+                    "0".to_string(),
+                    "-crop".to_string(),
+                    region.geometry(),
+                    "+repage".to_string(),
+                ]);
+                if options.mode == RedactionMode::Pixelate {
+                    let percent = 100.0 / f64::from(strength);
+                    args.extend([
+                        "-scale".to_string(),
+                        format!("{percent:.4}%"),
+                        "-scale".to_string(),
+                        format!("{}x{}!", region.width, region.height),
+                    ]);
+                } else {
+                    args.extend(["-blur".to_string(), format!("0x{strength}")]);
+                }
+                args.extend([
+                    ")".to_string(),
+                    "-geometry".to_string(),
+                    format!("+{}+{}", region.x, region.y),
+                    "-composite".to_string(),
+                ]);
+            }
+        }
+    }
+    args.push(options.output.clone());
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IMAGE: ImageDimensions = ImageDimensions {
+        width: 640,
+        height: 480,
+    };
+
+    fn build_args_for(options: &RedactOptions) -> Result<Vec<String>, OperationError> {
+        build_args(options, IMAGE)
+    }
+
+    fn options(mode: RedactionMode) -> RedactOptions {
+        RedactOptions {
+            input: "shot.png".to_string(),
+            output: "shot-redacted.png".to_string(),
+            regions: vec![Region {
+                x: 10,
+                y: 20,
+                width: 100,
+                height: 50,
+            }],
+            mode,
+            strength: 10,
+        }
+    }
+
+    #[test]
+    fn test_pixelate_args() {
+        let args = build_args_for(&options(RedactionMode::Pixelate)).unwrap();
+        assert_eq!(
+            args,
+            vec![
+                "shot.png",
+                "(",
+                "-clone",
+                "0",
+                "-crop",
+                "100x50+10+20",
+                "+repage",
+                "-scale",
+                "10.0000%",
+                "-scale",
+                "100x50!",
+                ")",
+                "-geometry",
+                "+10+20",
+                "-composite",
+                "shot-redacted.png"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_blur_args() {
+        let args = build_args_for(&options(RedactionMode::Blur)).unwrap();
+        assert!(args.contains(&"-blur".to_string()));
+        assert!(args.contains(&"0x10".to_string()));
+    }
+
+    #[test]
+    fn test_fill_args() {
+        let args = build_args_for(&options(RedactionMode::Fill("red".to_string()))).unwrap();
+        assert_eq!(
+            args,
+            vec![
+                "shot.png",
+                "-fill",
+                "red",
+                "-draw",
+                "rectangle 10,20 109,69",
+                "shot-redacted.png"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rejects_empty_regions() {
+        let mut options = options(RedactionMode::Blur);
+        options.regions.clear();
+        assert!(matches!(
+            build_args_for(&options),
+            Err(OperationError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_regions_are_checked_against_the_image() {
+        let mut options = options(RedactionMode::Fill("black".to_string()));
+        let region = |x, y, width, height| Region {
+            x,
+            y,
+            width,
+            height,
+        };
+
+        options.regions = vec![region(10, 20, 0, 50)];
+        assert!(matches!(
+            build_args_for(&options),
+            Err(OperationError::InvalidParameter(_))
+        ));
+
+        options.regions = vec![region(700, 20, 100, 50)];
+        assert!(matches!(
+            build_args_for(&options),
+            Err(OperationError::InvalidParameter(_))
+        ));
+
+        options.regions = vec![region(600, 460, u32::MAX, u32::MAX)];
+        let args = build_args_for(&options).unwrap();
+        assert!(args.contains(&"rectangle 600,460 639,479".to_string()));
+    }
+
+    #[test]
+    fn test_rejects_strengths_which_leave_regions_readable() {
+        for mode in [RedactionMode::Pixelate, RedactionMode::Blur] {
+            let mut options = options(mode);
+            for strength in [0, 1] {
+                options.strength = strength;
+                assert!(matches!(
+                    build_args_for(&options),
+                    Err(OperationError::InvalidParameter(_))
+                ));
+            }
+            options.strength = MIN_REDACT_STRENGTH;
+            assert!(build_args_for(&options).is_ok());
+        }
+
+        let mut options = options(RedactionMode::Fill("black".to_string()));
+        options.strength = 0;
+        assert!(build_args_for(&options).is_ok());
+    }
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(
+            RedactionMode::parse("fill", None).unwrap(),
+            RedactionMode::Fill("black".to_string())
+        );
+        assert!(RedactionMode::parse("smudge", None).is_err());
+    }
+}
@@ -0,0 +1,278 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::watermark::parse_gravity;
+use crate::feature::ops::{OperationError, OutputSettings};
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// How an image is fitted to the requested size
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResizeFit {
+    /// Scale to fit inside the box, keeping the aspect ratio
+    #[default]
+    Contain,
+    /// Scale to cover the box, keeping the aspect ratio, and crop the overflow
+    Cover,
+    /// Scale to fit inside the box and pad the rest with the background color
+    Fill,
+    /// Stretch to exactly the requested size, ignoring the aspect ratio
+    Exact,
+}
+
+impl ResizeFit {
+    /// Parse a fit mode name
+    pub fn parse(name: &str) -> Result<Self, OperationError> {
+        match name {
+            "contain" => Ok(ResizeFit::Contain),
+            "cover" => Ok(ResizeFit::Cover),
+            "fill" => Ok(ResizeFit::Fill),
+            "exact" => Ok(ResizeFit::Exact),
+            other => Err(OperationError::InvalidParameter(format!(
+                "unknown fit '{other}', expected contain, cover, fill, or exact"
+            ))),
+        }
+    }
+}
+
+/// Options for resizing an image
+#[derive(Debug, Clone)]
+pub struct ResizeOptions {
+    /// Path to the source image
+    pub input: String,
+    /// Path to write the resized copy to
+    pub output: String,
+    /// Target width in pixels, optional for `contain`
+    pub width: Option<u32>,
+    /// Target height in pixels, optional for `contain`
+    pub height: Option<u32>,
+    /// How the image is fitted to the target size
+    pub fit: ResizeFit,
+    /// Never enlarge images which are already smaller than the target
+    pub shrink_only: bool,
+    /// Which part of the image is kept by `cover` or where it is placed by `fill`
+    pub gravity: String,
+    /// Padding color used by `fill`
+    pub background: String,
+    /// Quality or compression level from 0 to 100
+    pub quality: Option<u32>,
+    /// Finishing settings applied after resizing
+    pub settings: OutputSettings,
+}
+
+impl ResizeOptions {
+    /// Create options which fit the image inside the given size
+    pub fn new(input: &str, output: &str, width: Option<u32>, height: Option<u32>) -> Self {
+        ResizeOptions {
+            input: input.to_string(),
+            output: output.to_string(),
+            width,
+            height,
+            fit: ResizeFit::Contain,
+            shrink_only: false,
+            gravity: "Center".to_string(),
+            background: "white".to_string(),
+            quality: None,
+            settings: OutputSettings::default(),
+        }
+    }
+}
+
+/// Operation which resizes an image without exposing ImageMagick geometry syntax
+pub struct Resize<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> Resize<'a> {
+    /// Create a new Resize with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Resize {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Resize the image and write the result
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output on success, or an `OperationError` on failure
+    pub fn run(&self, options: &ResizeOptions) -> Result<ResolvedOutput, OperationError> {
+        let args = build_args(options)?;
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        Ok(self.magick_runner.last_resolved_output(&options.output))
+    }
+}
+
+/// Build the `magick` arguments for a resize
+///
+/// `contain` is a plain `-resize WxH`, `exact` adds `!`, `cover` resizes with `^` and crops with
+/// `-extent`, and `fill` pads with `-extent`. `shrink_only` adds `>` to every geometry.
+fn build_args(options: &ResizeOptions) -> Result<Vec<String>, OperationError> {
+    if options.width == Some(0) || options.height == Some(0) {
+        return Err(OperationError::InvalidParameter(
+            "width and height must be greater than zero".to_string(),
+        ));
+    }
+    let size = match (options.width, options.height, options.fit) {
+        (None, None, _) => {
+            return Err(OperationError::InvalidParameter(
+                "at least one of width or height is required".to_string(),
+            ));
+        }
+        (Some(width), Some(height), _) => format!("{width}x{height}"),
+        (width, height, ResizeFit::Contain) => format!(
+            "{}x{}",
+            width.map(|w| w.to_string()).unwrap_or_default(),
+            height.map(|h| h.to_string()).unwrap_or_default()
+        ),
+        _ => {
+            return Err(OperationError::InvalidParameter(
+                "both width and height are required unless fit is contain".to_string(),
+            ));
+        }
+    };
+    let shrink = if options.shrink_only { ">" } else { "" };
+
+    let mut args = vec![options.input.clone(), "-resize".to_string()];
+    match options.fit {
+        ResizeFit::Contain => args.push(format!("{size}{shrink}")),
+        ResizeFit::Exact => args.push(format!("{size}!{shrink}")),
+        ResizeFit::Cover => {
+            let gravity = parse_gravity(&options.gravity)?;
+            args.extend([
+                format!("{size}^{shrink}"),
+                "-gravity".to_string(),
+                gravity.to_string(),
+                "-extent".to_string(),
+                size,
+            ]);
+        }
+        ResizeFit::Fill => {
+            let gravity = parse_gravity(&options.gravity)?;
+            args.extend([
+                format!("{size}{shrink}"),
+                "-background".to_string(),
+                options.background.clone(),
+                "-gravity".to_string(),
+                gravity.to_string(),
+                "-extent".to_string(),
+                size,
+            ]);
+        }
+    }
+    args.extend(options.settings.args()?);
+    if let Some(quality) = options.quality {
+        if quality > 100 {
+            return Err(OperationError::InvalidParameter(format!(
+                "quality {quality} must be between 0 and 100"
+            )));
+        }
+        args.extend(["-quality".to_string(), quality.to_string()]);
+    }
+    args.push(options.output.clone());
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(fit: ResizeFit) -> ResizeOptions {
+        let mut options = ResizeOptions::new("in.jpg", "out.jpg", Some(800), Some(600));
+        options.fit = fit;
+        options
+    }
+
+    #[test]
+    fn test_contain_args() {
+        let args = build_args(&options(ResizeFit::Contain)).unwrap();
+        assert_eq!(args, vec!["in.jpg", "-resize", "800x600", "out.jpg"]);
+
+        let width_only = ResizeOptions::new("in.jpg", "out.jpg", Some(800), None);
+        assert_eq!(build_args(&width_only).unwrap()[2], "800x");
+    }
+
+    #[test]
+    fn test_cover_args() {
+        let mut options = options(ResizeFit::Cover);
+        options.gravity = "north".to_string();
+        let args = build_args(&options).unwrap();
+        assert_eq!(
+            args,
+            vec![
+                "in.jpg", "-resize", "800x600^", "-gravity", "North", "-extent", "800x600",
+                "out.jpg"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fill_args() {
+        let args = build_args(&options(ResizeFit::Fill)).unwrap();
+        assert_eq!(
+            args,
+            vec![
+                "in.jpg",
+                "-resize",
+                "800x600",
+                "-background",
+                "white",
+                "-gravity",
+                "Center",
+                "-extent",
+                "800x600",
+                "out.jpg"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exact_shrink_only_args() {
+        let mut options = options(ResizeFit::Exact);
+        options.shrink_only = true;
+        let args = build_args(&options).unwrap();
+        assert_eq!(args, vec!["in.jpg", "-resize", "800x600!>", "out.jpg"]);
+    }
+
+    #[test]
+    fn test_settings_and_quality_args() {
+        let mut options = options(ResizeFit::Contain);
+        options.quality = Some(85);
+        options.settings.strip = true;
+        options.settings.colorspace = Some("sRGB".to_string());
+        let args = build_args(&options).unwrap();
+        assert_eq!(
+            args,
+            vec![
+                "in.jpg",
+                "-resize",
+                "800x600",
+                "-colorspace",
+                "sRGB",
+                "-strip",
+                "-quality",
+                "85",
+                "out.jpg"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_args_validates_size() {
+        let none = ResizeOptions::new("in.jpg", "out.jpg", None, None);
+        assert!(build_args(&none).is_err());
+
+        let mut cover = ResizeOptions::new("in.jpg", "out.jpg", Some(800), None);
+        cover.fit = ResizeFit::Cover;
+        assert!(build_args(&cover).is_err());
+
+        let zero = ResizeOptions::new("in.jpg", "out.jpg", Some(0), Some(10));
+        assert!(build_args(&zero).is_err());
+    }
+}
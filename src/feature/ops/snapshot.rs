@@ -0,0 +1,368 @@
+use crate::feature::data_dir::data_dir;
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::bench::ssim;
+use crate::feature::ops::identify::{self, ImageDimensions};
+use crate::feature::shell::CommandRunner;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the directory in the data directory holding snapshot baselines
+const SNAPSHOTS_DIR: &str = "snapshots";
+
+/// Lowest structural similarity to the baseline which still matches when none is given
+pub const DEFAULT_SNAPSHOT_THRESHOLD: f64 = 0.99;
+
+/// Options for checking an image against its snapshot baseline
+#[derive(Debug, Clone)]
+pub struct SnapshotCheckOptions {
+    /// Name of the snapshot, made of letters, digits, `-`, `_`, and `.`
+    pub name: String,
+    /// Path to the image to check
+    pub path: String,
+    /// Lowest structural similarity to the baseline, from 0 to 1, which still matches
+    pub threshold: f64,
+    /// Path to write an image highlighting the differences to when the check fails
+    pub diff: Option<String>,
+}
+
+impl SnapshotCheckOptions {
+    /// Create options checking an image with the default threshold
+    pub fn new(name: &str, path: &str) -> Self {
+        SnapshotCheckOptions {
+            name: name.to_string(),
+            path: path.to_string(),
+            threshold: DEFAULT_SNAPSHOT_THRESHOLD,
+            diff: None,
+        }
+    }
+}
+
+/// How an image compared to its baseline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotStatus {
+    /// The image is at least as similar to the baseline as the threshold
+    Match,
+    /// The image differs from the baseline by more than the threshold
+    Mismatch,
+    /// The image and the baseline have different dimensions
+    SizeMismatch,
+    /// No baseline has been stored under the name
+    Missing,
+}
+
+/// The result of checking an image against its baseline
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotCheck {
+    pub status: SnapshotStatus,
+    /// Path to the stored baseline
+    pub baseline: PathBuf,
+    /// Structural similarity to the baseline, where 1 is identical, when the images were compared
+    pub ssim: Option<f64>,
+    /// Dimensions of the checked image
+    pub dimensions: ImageDimensions,
+    /// Dimensions of the baseline, if there is one
+    pub baseline_dimensions: Option<ImageDimensions>,
+    /// Path the difference image was written to, if one was
+    pub diff: Option<PathBuf>,
+}
+
+/// The result of accepting an image as a snapshot baseline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotUpdate {
+    /// Path to the stored baseline
+    pub baseline: PathBuf,
+    /// Whether an earlier baseline was replaced
+    pub replaced: bool,
+}
+
+/// Visual regression checks against baselines stored in the data directory
+///
+/// Baselines are stored as PNG, so checks are never affected by lossy compression of the baseline.
+/// Only the first frame of an image is stored and compared.
+pub struct Snapshots<'a> {
+    magick_runner: MagickRunner<'a>,
+    workspace: Option<&'a Path>,
+    dir: Option<PathBuf>,
+}
+
+impl<'a> Snapshots<'a> {
+    /// Create a new Snapshots with the provided CommandRunner and optional workspace path
+    ///
+    /// Image paths are relative to the workspace, while baselines live in the `snapshots`
+    /// directory of the data directory and are shared by every workspace.
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Self::at(
+            command_runner,
+            workspace,
+            data_dir().map(|dir| dir.join(SNAPSHOTS_DIR)),
+        )
+    }
+
+    fn at(
+        command_runner: &'a dyn CommandRunner,
+        workspace: Option<&'a Path>,
+        dir: Option<PathBuf>,
+    ) -> Self {
+        // Paths are resolved against the workspace here, so the baselines outside of it are
+        // not subject to the workspace's project conventions
+        Snapshots {
+            magick_runner: MagickRunner::new(command_runner, None),
+            workspace,
+            dir,
+        }
+    }
+
+    /// Compare an image to the baseline stored under a name
+    ///
+    /// # Returns
+    ///
+    /// Returns the outcome of the check, or an `OperationError` if the images cannot be read.
+    /// A missing baseline is reported as `SnapshotStatus::Missing` rather than an error.
+    pub fn check(&self, options: &SnapshotCheckOptions) -> Result<SnapshotCheck, OperationError> {
+        if !(0.0..=1.0).contains(&options.threshold) {
+            return Err(OperationError::InvalidParameter(format!(
+                "threshold {} must be between 0 and 1",
+                options.threshold
+            )));
+        }
+        let baseline = self.baseline(&options.name)?;
+        let path = self.resolve(&options.path);
+        let dimensions = identify::dimensions(&self.magick_runner, &path)?;
+        let mut check = SnapshotCheck {
+            status: SnapshotStatus::Missing,
+            baseline: baseline.clone(),
+            ssim: None,
+            dimensions,
+            baseline_dimensions: None,
+            diff: None,
+        };
+        if !baseline.exists() {
+            return Ok(check);
+        }
+
+        let baseline_path = baseline.to_string_lossy();
+        let baseline_dimensions = identify::dimensions(&self.magick_runner, &baseline_path)?;
+        check.baseline_dimensions = Some(baseline_dimensions);
+        if baseline_dimensions != dimensions {
+            check.status = SnapshotStatus::SizeMismatch;
+            return Ok(check);
+        }
+
+        let diff = options.diff.as_deref().map(|diff| self.resolve(diff));
+        let similarity = ssim(
+            &self.magick_runner,
+            &baseline_path,
+            &format!("{path}[0]"),
+            diff.as_deref().unwrap_or("null:"),
+        )?;
+        check.ssim = Some(similarity);
+        if similarity >= options.threshold {
+            check.status = SnapshotStatus::Match;
+            if let Some(diff) = &diff {
+                let _ = fs::remove_file(diff);
+            }
+        } else {
+            check.status = SnapshotStatus::Mismatch;
+            check.diff = options.diff.as_ref().map(PathBuf::from);
+        }
+        Ok(check)
+    }
+
+    /// Store an image as the baseline for a name, replacing any earlier baseline
+    ///
+    /// # Returns
+    ///
+    /// Returns where the baseline was stored, or an `OperationError` on failure
+    pub fn update(&self, name: &str, path: &str) -> Result<SnapshotUpdate, OperationError> {
+        let baseline = self.baseline(name)?;
+        if let Some(dir) = baseline.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let replaced = baseline.exists();
+        let input = format!("{}[0]", self.resolve(path));
+        self.magick_runner
+            .execute_args(&[&input, &baseline.to_string_lossy()])?;
+        Ok(SnapshotUpdate { baseline, replaced })
+    }
+
+    /// Path to the baseline stored under a name
+    fn baseline(&self, name: &str) -> Result<PathBuf, OperationError> {
+        let valid = !name.is_empty()
+            && !name.starts_with('.')
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(OperationError::InvalidParameter(format!(
+                "snapshot name '{name}' may only contain letters, digits, '-', '_', and '.', and may not start with '.'"
+            )));
+        }
+        let dir = self.dir.as_ref().ok_or_else(|| {
+            OperationError::InvalidParameter("could not determine the data directory".to_string())
+        })?;
+        Ok(dir.join(format!("{name}.png")))
+    }
+
+    /// Resolve a path against the workspace
+    fn resolve(&self, path: &str) -> String {
+        match self.workspace {
+            Some(workspace) => workspace.join(path).to_string_lossy().to_string(),
+            None => path.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+    use tempfile::TempDir;
+
+    /// Reports fixed dimensions and similarity, and writes outputs as empty files
+    struct MockCommandRunner {
+        baseline_size: &'static str,
+        ssim: &'static str,
+        calls: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl MockCommandRunner {
+        fn new(baseline_size: &'static str, ssim: &'static str) -> Self {
+            MockCommandRunner {
+                baseline_size,
+                ssim,
+                calls: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|arg| arg.to_string()).collect());
+            match args[0] {
+                "identify" if args[3].ends_with(".png[0]") => Ok(self.baseline_size.to_string()),
+                "identify" => Ok("100 50".to_string()),
+                "compare" => Err(ShellError::NonZeroExit {
+                    exit_code: 1,
+                    command: command.to_string(),
+                    args: args.join(" "),
+                    stdout: String::new(),
+                    stderr: self.ssim.to_string(),
+                }),
+                _ => {
+                    fs::write(args[args.len() - 1], "").unwrap();
+                    Ok(String::new())
+                }
+            }
+        }
+    }
+
+    fn snapshots<'a>(runner: &'a MockCommandRunner, dir: &TempDir) -> Snapshots<'a> {
+        Snapshots::at(runner, None, Some(dir.path().join(SNAPSHOTS_DIR)))
+    }
+
+    #[test]
+    fn test_check_without_baseline_is_missing() {
+        let dir = TempDir::new().unwrap();
+        let runner = MockCommandRunner::new("100 50", "1");
+
+        let check = snapshots(&runner, &dir)
+            .check(&SnapshotCheckOptions::new("home", "home.jpg"))
+            .unwrap();
+
+        assert_eq!(check.status, SnapshotStatus::Missing);
+        assert_eq!(check.baseline, dir.path().join("snapshots/home.png"));
+        assert_eq!(check.ssim, None);
+    }
+
+    #[test]
+    fn test_update_then_check() {
+        let dir = TempDir::new().unwrap();
+        let runner = MockCommandRunner::new("100 50", "0.995");
+        let snapshots = snapshots(&runner, &dir);
+
+        let update = snapshots.update("home", "home.jpg").unwrap();
+        assert!(!update.replaced);
+        assert_eq!(
+            runner.calls.borrow()[0],
+            ["home.jpg[0]", &update.baseline.to_string_lossy()]
+        );
+        assert!(snapshots.update("home", "home.jpg").unwrap().replaced);
+
+        let check = snapshots
+            .check(&SnapshotCheckOptions::new("home", "home.jpg"))
+            .unwrap();
+        assert_eq!(check.status, SnapshotStatus::Match);
+        assert_eq!(check.ssim, Some(0.995));
+    }
+
+    #[test]
+    fn test_check_mismatch_writes_diff() {
+        let dir = TempDir::new().unwrap();
+        let runner = MockCommandRunner::new("100 50", "0.91 (0.91)");
+        let snapshots = snapshots(&runner, &dir);
+        snapshots.update("home", "home.jpg").unwrap();
+
+        let mut options = SnapshotCheckOptions::new("home", "home.jpg");
+        options.diff = Some("home-diff.png".to_string());
+        let check = snapshots.check(&options).unwrap();
+
+        assert_eq!(check.status, SnapshotStatus::Mismatch);
+        assert_eq!(check.diff, Some(PathBuf::from("home-diff.png")));
+        let calls = runner.calls.borrow();
+        let compare = calls.last().unwrap();
+        assert_eq!(compare[..3], ["compare", "-metric", "SSIM"]);
+        assert_eq!(compare[4..], ["home.jpg[0]", "home-diff.png"]);
+
+        options.threshold = 0.9;
+        drop(calls);
+        assert_eq!(
+            snapshots.check(&options).unwrap().status,
+            SnapshotStatus::Match
+        );
+    }
+
+    #[test]
+    fn test_check_size_mismatch() {
+        let dir = TempDir::new().unwrap();
+        let runner = MockCommandRunner::new("200 100", "1");
+        let snapshots = snapshots(&runner, &dir);
+        snapshots.update("home", "home.jpg").unwrap();
+
+        let check = snapshots
+            .check(&SnapshotCheckOptions::new("home", "home.jpg"))
+            .unwrap();
+
+        assert_eq!(check.status, SnapshotStatus::SizeMismatch);
+        assert_eq!(
+            check.baseline_dimensions,
+            Some(ImageDimensions {
+                width: 200,
+                height: 100
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        let dir = TempDir::new().unwrap();
+        let runner = MockCommandRunner::new("100 50", "1");
+        let snapshots = snapshots(&runner, &dir);
+
+        assert!(snapshots.update("../home", "home.jpg").is_err());
+        assert!(snapshots.update(".hidden", "home.jpg").is_err());
+        let mut options = SnapshotCheckOptions::new("home", "home.jpg");
+        options.threshold = 1.5;
+        assert!(snapshots.check(&options).is_err());
+    }
+}
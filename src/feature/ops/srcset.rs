@@ -0,0 +1,341 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::identify::{self, ImageDimensions};
+use crate::feature::shell::CommandRunner;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Widths generated when none are requested, covering common phone to desktop layouts
+pub const DEFAULT_SRCSET_WIDTHS: &[u32] = &[480, 768, 1024, 1600];
+
+/// Web image formats a responsive set can be generated in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebFormat {
+    Avif,
+    Webp,
+    Jpeg,
+    Png,
+}
+
+impl WebFormat {
+    /// Parse a format name, case-insensitively
+    pub fn parse(name: &str) -> Result<Self, OperationError> {
+        match name.to_ascii_lowercase().as_str() {
+            "avif" => Ok(WebFormat::Avif),
+            "webp" => Ok(WebFormat::Webp),
+            "jpeg" | "jpg" => Ok(WebFormat::Jpeg),
+            "png" => Ok(WebFormat::Png),
+            other => Err(OperationError::InvalidParameter(format!(
+                "unsupported web format '{other}', expected avif, webp, jpeg, or png"
+            ))),
+        }
+    }
+
+    /// File extension for the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            WebFormat::Avif => "avif",
+            WebFormat::Webp => "webp",
+            WebFormat::Jpeg => "jpg",
+            WebFormat::Png => "png",
+        }
+    }
+
+    /// MIME type used in `<source type=...>`
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            WebFormat::Avif => "image/avif",
+            WebFormat::Webp => "image/webp",
+            WebFormat::Jpeg => "image/jpeg",
+            WebFormat::Png => "image/png",
+        }
+    }
+
+    /// Whether every browser can display the format, making it suitable for the `<img>` fallback
+    fn is_universal(&self) -> bool {
+        matches!(self, WebFormat::Jpeg | WebFormat::Png)
+    }
+}
+
+/// Options for generating a responsive image set
+#[derive(Debug, Clone)]
+pub struct SrcsetOptions {
+    /// Path to the source image
+    pub input: String,
+    /// Directory the generated files are written to
+    pub output_dir: String,
+    /// Target widths in pixels; widths larger than the source are skipped
+    pub widths: Vec<u32>,
+    /// Also generate double-width variants for high density displays
+    pub retina: bool,
+    /// Formats to generate, in order of preference
+    pub formats: Vec<WebFormat>,
+    /// Encoder quality from 1 to 100
+    pub quality: u32,
+}
+
+impl SrcsetOptions {
+    /// Create options using the default widths in WebP and JPEG at quality 82
+    pub fn new(input: &str, output_dir: &str) -> Self {
+        SrcsetOptions {
+            input: input.to_string(),
+            output_dir: output_dir.to_string(),
+            widths: DEFAULT_SRCSET_WIDTHS.to_vec(),
+            retina: false,
+            formats: vec![WebFormat::Webp, WebFormat::Jpeg],
+            quality: 82,
+        }
+    }
+}
+
+/// A single generated file in a responsive set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrcsetFile {
+    pub path: PathBuf,
+    pub width: u32,
+    pub height: u32,
+    pub format: WebFormat,
+}
+
+/// Operation which generates a set of resized web images from one source
+pub struct Srcset<'a> {
+    magick_runner: MagickRunner<'a>,
+    base: PathBuf,
+}
+
+impl<'a> Srcset<'a> {
+    /// Create a new Srcset with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Srcset {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+            base: workspace
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(".")),
+        }
+    }
+
+    /// Generate every width and format combination
+    ///
+    /// # Returns
+    ///
+    /// Returns the generated files, or an `OperationError` on failure
+    pub fn run(&self, options: &SrcsetOptions) -> Result<Vec<SrcsetFile>, OperationError> {
+        if options.formats.is_empty() {
+            return Err(OperationError::InvalidParameter(
+                "at least one format is required".to_string(),
+            ));
+        }
+        if !(1..=100).contains(&options.quality) {
+            return Err(OperationError::InvalidParameter(format!(
+                "quality {} must be between 1 and 100",
+                options.quality
+            )));
+        }
+        let source = identify::dimensions(&self.magick_runner, &options.input)?;
+        let widths = target_widths(&options.widths, options.retina, source.width);
+        let stem = Path::new(&options.input)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "image".to_string());
+        fs::create_dir_all(self.base.join(&options.output_dir))?;
+
+        let quality = options.quality.to_string();
+        let mut files = Vec::new();
+        for format in &options.formats {
+            for &width in &widths {
+                let path = Path::new(&options.output_dir)
+                    .join(format!("{stem}-{width}.{}", format.extension()));
+                let path_str = path.to_string_lossy().to_string();
+                let resize = format!("{width}x");
+                self.magick_runner.execute_args(&[
+                    &options.input,
+                    "-resize",
+                    &resize,
+                    "-strip",
+                    "-quality",
+                    &quality,
+                    &path_str,
+                ])?;
+                files.push(SrcsetFile {
+                    path,
+                    width,
+                    height: scaled_height(&source, width),
+                    format: *format,
+                });
+            }
+        }
+        Ok(files)
+    }
+}
+
+/// Sorted, deduplicated widths no larger than the source
+///
+/// The source width is used when every requested width would upscale.
+fn target_widths(widths: &[u32], retina: bool, source_width: u32) -> Vec<u32> {
+    let mut targets: Vec<u32> = widths
+        .iter()
+        .flat_map(|&w| if retina { vec![w, w * 2] } else { vec![w] })
+        .filter(|&w| w > 0 && w <= source_width)
+        .collect();
+    targets.sort_unstable();
+    targets.dedup();
+    if targets.is_empty() {
+        targets.push(source_width);
+    }
+    targets
+}
+
+/// Height of the source scaled proportionally to `width`
+fn scaled_height(source: &ImageDimensions, width: u32) -> u32 {
+    let height = u64::from(source.height) * u64::from(width) / u64::from(source.width.max(1));
+    height.max(1) as u32
+}
+
+/// Build a `<picture>` element for the generated files
+///
+/// Each format becomes a `<source>`, and the `<img>` fallback uses the largest file of the first
+/// universally supported format (or of the last format when none is).
+pub fn picture_html(files: &[SrcsetFile], alt: &str) -> String {
+    let mut formats: Vec<WebFormat> = Vec::new();
+    for file in files {
+        if !formats.contains(&file.format) {
+            formats.push(file.format);
+        }
+    }
+    let srcset = |format: WebFormat| {
+        files
+            .iter()
+            .filter(|file| file.format == format)
+            .map(|file| format!("{} {}w", file.path.to_string_lossy(), file.width))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let Some(fallback) = formats
+        .iter()
+        .find(|format| format.is_universal())
+        .or(formats.last())
+        .copied()
+    else {
+        return String::new();
+    };
+
+    let mut html = String::from("<picture>\n");
+    for format in formats.iter().filter(|format| **format != fallback) {
+        html.push_str(&format!(
+            "  <source type=\"{}\" srcset=\"{}\" sizes=\"100vw\">\n",
+            format.mime_type(),
+            srcset(*format)
+        ));
+    }
+    if let Some(largest) = files
+        .iter()
+        .filter(|file| file.format == fallback)
+        .max_by_key(|file| file.width)
+    {
+        html.push_str(&format!(
+            "  <img src=\"{}\" srcset=\"{}\" sizes=\"100vw\" width=\"{}\" height=\"{}\" alt=\"{}\">\n",
+            largest.path.to_string_lossy(),
+            srcset(fallback),
+            largest.width,
+            largest.height,
+            alt.replace('"', "&quot;")
+        ));
+    }
+    html.push_str("</picture>");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+    use tempfile::TempDir;
+
+    /// Mock implementation of CommandRunner reporting a 2000x1000 source and recording calls
+    struct MockCommandRunner {
+        calls: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|s| s.to_string()).collect());
+            Ok("2000 1000".to_string())
+        }
+    }
+
+    #[test]
+    fn test_target_widths() {
+        assert_eq!(
+            target_widths(DEFAULT_SRCSET_WIDTHS, false, 1200),
+            vec![480, 768, 1024]
+        );
+        assert_eq!(
+            target_widths(&[480, 768], true, 1600),
+            vec![480, 768, 960, 1536]
+        );
+        assert_eq!(target_widths(&[1024], false, 300), vec![300]);
+    }
+
+    #[test]
+    fn test_run_generates_each_width_and_format() {
+        let dir = TempDir::new().unwrap();
+        let runner = MockCommandRunner {
+            calls: RefCell::new(Vec::new()),
+        };
+        let srcset = Srcset::new(&runner, Some(dir.path()));
+        let mut options = SrcsetOptions::new("hero.png", "web");
+        options.widths = vec![480, 1024];
+
+        let files = srcset.run(&options).unwrap();
+
+        assert_eq!(files.len(), 4);
+        assert_eq!(files[0].path, PathBuf::from("web/hero-480.webp"));
+        assert_eq!(files[0].height, 240);
+        assert_eq!(files[3].path, PathBuf::from("web/hero-1024.jpg"));
+        assert!(dir.path().join("web").is_dir());
+        assert_eq!(
+            runner.calls.borrow()[1],
+            vec![
+                "hero.png",
+                "-resize",
+                "480x",
+                "-strip",
+                "-quality",
+                "82",
+                "web/hero-480.webp"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_picture_html() {
+        let file = |path: &str, width, format| SrcsetFile {
+            path: PathBuf::from(path),
+            width,
+            height: width / 2,
+            format,
+        };
+        let files = vec![
+            file("a-480.avif", 480, WebFormat::Avif),
+            file("a-480.jpg", 480, WebFormat::Jpeg),
+            file("a-960.jpg", 960, WebFormat::Jpeg),
+        ];
+
+        let html = picture_html(&files, "A \"hero\"");
+
+        assert_eq!(
+            html,
+            "<picture>\n  <source type=\"image/avif\" srcset=\"a-480.avif 480w\" sizes=\"100vw\">\n  \
+             <img src=\"a-960.jpg\" srcset=\"a-480.jpg 480w, a-960.jpg 960w\" sizes=\"100vw\" \
+             width=\"960\" height=\"480\" alt=\"A &quot;hero&quot;\">\n</picture>"
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::output::output_format;
+use crate::feature::shell::CommandRunner;
+use std::path::{Path, PathBuf};
+
+/// Output formats which cannot store an alpha channel
+const OPAQUE_FORMATS: &[&str] = &["jpg", "jpeg", "jpe", "jfif", "pbm", "pgm", "ppm", "pnm"];
+
+/// EXIF orientations which need no rotation
+const UPRIGHT_ORIENTATIONS: &[&str] = &["TopLeft", "Undefined", ""];
+
+/// A likely-unintended consequence of an operation, refused in strict mode unless acknowledged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrictConcern {
+    /// A preset's size limit would shrink the image although no size was given
+    ImplicitDownscale,
+    /// An image with transparent pixels would be written to a format without alpha
+    TransparencyLoss,
+    /// The image has an EXIF rotation which the operation does not apply
+    ExifRotationIgnored,
+    /// The output would replace the input
+    OverwriteSource,
+}
+
+impl StrictConcern {
+    /// Every concern, in the order they are checked
+    pub const ALL: [StrictConcern; 4] = [
+        StrictConcern::ImplicitDownscale,
+        StrictConcern::TransparencyLoss,
+        StrictConcern::ExifRotationIgnored,
+        StrictConcern::OverwriteSource,
+    ];
+
+    /// The code a call passes to acknowledge the concern
+    pub fn code(self) -> &'static str {
+        match self {
+            StrictConcern::ImplicitDownscale => "implicit_downscale",
+            StrictConcern::TransparencyLoss => "transparency_loss",
+            StrictConcern::ExifRotationIgnored => "exif_rotation_ignored",
+            StrictConcern::OverwriteSource => "overwrite_source",
+        }
+    }
+}
+
+/// A concern raised about a call, with a description of the specific image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrictFinding {
+    pub concern: StrictConcern,
+    pub message: String,
+}
+
+/// What a call is about to do, as far as strict mode is concerned
+#[derive(Debug, Clone, Default)]
+pub struct StrictCheckOptions {
+    /// Path to the input image
+    pub input: String,
+    /// Path the output is written to
+    pub output: String,
+    /// Format of the output when it differs from the output's extension
+    pub format: Option<String>,
+    /// Whether the operation applies the EXIF orientation itself
+    pub auto_orients: bool,
+    /// Whether an existing output is replaced rather than skipped or renamed
+    pub overwrites: bool,
+    /// Size limit taken from a preset rather than given with the call
+    pub implicit_max_width: Option<u32>,
+    /// Size limit taken from a preset rather than given with the call
+    pub implicit_max_height: Option<u32>,
+}
+
+/// Operation which inspects the input of a call for likely-unintended consequences
+pub struct StrictChecker<'a> {
+    magick_runner: MagickRunner<'a>,
+    workspace: Option<&'a Path>,
+}
+
+impl<'a> StrictChecker<'a> {
+    /// Create a new StrictChecker with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        StrictChecker {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+            workspace,
+        }
+    }
+
+    /// Check the call and list its concerns
+    ///
+    /// # Returns
+    ///
+    /// Returns the concerns in the order of `StrictConcern::ALL`, or an `OperationError` if the
+    /// input cannot be read
+    pub fn run(&self, options: &StrictCheckOptions) -> Result<Vec<StrictFinding>, OperationError> {
+        let frame = format!("{}[0]", options.input);
+        let output = self.magick_runner.execute_args(&[
+            "identify",
+            "-format",
+            "%w %h %[opaque] %[orientation]",
+            &frame,
+        ])?;
+        let mut fields = output.split_whitespace();
+        let parse_error =
+            || OperationError::ParseError(format!("unexpected identify output: {output}"));
+        let width: u32 = fields
+            .next()
+            .and_then(|w| w.parse().ok())
+            .ok_or_else(parse_error)?;
+        let height: u32 = fields
+            .next()
+            .and_then(|h| h.parse().ok())
+            .ok_or_else(parse_error)?;
+        let opaque = fields
+            .next()
+            .ok_or_else(parse_error)?
+            .eq_ignore_ascii_case("true");
+        let orientation = fields.next().unwrap_or_default();
+
+        let mut findings = Vec::new();
+        let too_wide = options.implicit_max_width.is_some_and(|max| width > max);
+        let too_tall = options.implicit_max_height.is_some_and(|max| height > max);
+        if too_wide || too_tall {
+            findings.push(StrictFinding {
+                concern: StrictConcern::ImplicitDownscale,
+                message: format!(
+                    "the {width}x{height} image would be shrunk to fit the preset's {}x{} limit",
+                    limit(options.implicit_max_width),
+                    limit(options.implicit_max_height)
+                ),
+            });
+        }
+
+        let format = options
+            .format
+            .clone()
+            .or_else(|| output_format(&options.output))
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if !opaque && OPAQUE_FORMATS.contains(&format.as_str()) {
+            findings.push(StrictFinding {
+                concern: StrictConcern::TransparencyLoss,
+                message: format!(
+                    "the image has transparent pixels, which {format} cannot store and are flattened"
+                ),
+            });
+        }
+
+        if !options.auto_orients && !UPRIGHT_ORIENTATIONS.contains(&orientation) {
+            findings.push(StrictFinding {
+                concern: StrictConcern::ExifRotationIgnored,
+                message: format!(
+                    "the image has EXIF orientation {orientation}, which is not applied, so it may be processed sideways or upside down"
+                ),
+            });
+        }
+
+        if options.overwrites && self.same_file(&options.input, &options.output) {
+            findings.push(StrictFinding {
+                concern: StrictConcern::OverwriteSource,
+                message: format!("the output replaces the input {}", options.input),
+            });
+        }
+        Ok(findings)
+    }
+
+    /// Whether two paths name the same file, resolving them against the workspace
+    fn same_file(&self, first: &str, second: &str) -> bool {
+        let resolve = |path: &str| -> PathBuf {
+            match self.workspace {
+                Some(workspace) => workspace.join(path),
+                None => PathBuf::from(path),
+            }
+        };
+        let (first, second) = (resolve(first), resolve(second));
+        match (first.canonicalize(), second.canonicalize()) {
+            (Ok(first), Ok(second)) => first == second,
+            _ => first == second,
+        }
+    }
+}
+
+fn limit(dimension: Option<u32>) -> String {
+    dimension.map(|d| d.to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+
+    /// Reports a fixed identify output
+    struct MockCommandRunner {
+        output: &'static str,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            _args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            Ok(self.output.to_string())
+        }
+    }
+
+    fn options(input: &str, output: &str) -> StrictCheckOptions {
+        StrictCheckOptions {
+            input: input.to_string(),
+            output: output.to_string(),
+            overwrites: true,
+            ..Default::default()
+        }
+    }
+
+    fn concerns(output: &'static str, options: &StrictCheckOptions) -> Vec<StrictConcern> {
+        let runner = MockCommandRunner { output };
+        StrictChecker::new(&runner, None)
+            .run(options)
+            .unwrap()
+            .into_iter()
+            .map(|finding| finding.concern)
+            .collect()
+    }
+
+    #[test]
+    fn test_no_concerns() {
+        let options = options("photo.png", "photo.webp");
+        assert!(concerns("800 600 True TopLeft", &options).is_empty());
+        assert!(concerns("800 600 False Undefined", &options).is_empty());
+    }
+
+    #[test]
+    fn test_every_concern() {
+        let mut options = options("photo.png", "photo.png");
+        options.format = Some("JPEG".to_string());
+        options.implicit_max_width = Some(1920);
+
+        assert_eq!(
+            concerns("4000 3000 False RightTop", &options),
+            StrictConcern::ALL
+        );
+    }
+
+    #[test]
+    fn test_concerns_avoided_by_the_call() {
+        let mut options = options("photo.png", "photo.png");
+        options.overwrites = false;
+        options.auto_orients = true;
+        options.implicit_max_height = Some(4000);
+
+        assert!(concerns("4000 3000 True RightTop", &options).is_empty());
+    }
+
+    #[test]
+    fn test_transparency_loss_uses_output_extension() {
+        let options = options("logo.png", "logo.jpg");
+        assert_eq!(
+            concerns("64 64 False TopLeft", &options),
+            [StrictConcern::TransparencyLoss]
+        );
+    }
+
+    #[test]
+    fn test_unreadable_output_is_an_error() {
+        let runner = MockCommandRunner { output: "" };
+        let options = options("photo.png", "out.png");
+        assert!(StrictChecker::new(&runner, None).run(&options).is_err());
+    }
+}
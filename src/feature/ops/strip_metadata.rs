@@ -0,0 +1,250 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::verbose::parse_verbose;
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Options for writing a copy of an image without its metadata
+#[derive(Debug, Clone)]
+pub struct StripMetadataOptions {
+    /// Path to the source image
+    pub input: String,
+    /// Path to write the sanitized copy to
+    pub output: String,
+    /// Keep the ICC color profile so colors render the same, removing everything else
+    pub keep_color_profile: bool,
+}
+
+impl StripMetadataOptions {
+    /// Create options removing all metadata, including the color profile
+    pub fn new(input: &str, output: &str) -> Self {
+        StripMetadataOptions {
+            input: input.to_string(),
+            output: output.to_string(),
+            keep_color_profile: false,
+        }
+    }
+}
+
+/// The result of stripping metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrippedImage {
+    /// The written output
+    pub output: ResolvedOutput,
+    /// Metadata properties and profiles present in the input but not the output, e.g.
+    /// `exif:GPSLatitude` or `Profile-xmp`
+    pub removed: Vec<String>,
+    /// Metadata properties and profiles which remain in the output
+    pub remaining: Vec<String>,
+}
+
+/// Operation which writes a copy of an image without EXIF, IPTC, XMP, or GPS metadata
+///
+/// The image is auto-oriented first, since removing EXIF also removes the orientation it would
+/// otherwise be displayed with. The removed keys are found by comparing `identify -verbose`
+/// output before and after.
+pub struct StripMetadata<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> StripMetadata<'a> {
+    /// Create a new StripMetadata with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        StripMetadata {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Write the sanitized copy and report what was removed
+    ///
+    /// # Returns
+    ///
+    /// Returns the written file with the removed and remaining metadata keys, or an
+    /// `OperationError` on failure
+    pub fn run(&self, options: &StripMetadataOptions) -> Result<StrippedImage, OperationError> {
+        let before = self.metadata_keys(&options.input)?;
+        let args = build_args(options);
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+
+        let output = self.magick_runner.last_resolved_output(&options.output);
+        let after = self.metadata_keys(&output.path.to_string_lossy())?;
+        Ok(StrippedImage {
+            output,
+            removed: before.difference(&after).cloned().collect(),
+            remaining: after.into_iter().collect(),
+        })
+    }
+
+    fn metadata_keys(&self, path: &str) -> Result<BTreeSet<String>, OperationError> {
+        let frame = format!("{path}[0]");
+        let output = self
+            .magick_runner
+            .execute_args(&["identify", "-verbose", &frame])?;
+        Ok(metadata_keys(&parse_verbose(&output)))
+    }
+}
+
+/// Build the arguments writing the sanitized copy
+fn build_args(options: &StripMetadataOptions) -> Vec<String> {
+    let mut args = vec![options.input.clone(), "-auto-orient".to_string()];
+    if options.keep_color_profile {
+        // `-strip` would also remove the ICC profile, so remove the other profiles by name and
+        // the comment and timestamps `-strip` otherwise clears
+        args.extend(
+            [
+                "+profile",
+                "!icc,*",
+                "+set",
+                "comment",
+                "-define",
+                "png:exclude-chunk=date,time",
+            ]
+            .map(str::to_string),
+        );
+    } else {
+        args.push("-strip".to_string());
+    }
+    args.push(options.output.clone());
+    args
+}
+
+/// The property and profile names in parsed `identify -verbose` output
+///
+/// Properties ImageMagick derives from the file itself, such as its timestamps and pixel
+/// signature, are left out since they are not metadata stored in the image.
+fn metadata_keys(verbose: &Value) -> BTreeSet<String> {
+    let image = match &verbose["Image"] {
+        Value::Object(_) => &verbose["Image"],
+        Value::Array(frames) => frames.first().unwrap_or(verbose),
+        _ => verbose,
+    };
+    let mut keys = BTreeSet::new();
+    for section in ["Properties", "Profiles"] {
+        if let Value::Object(fields) = &image[section] {
+            keys.extend(
+                fields
+                    .keys()
+                    .filter(|key| *key != "_lines" && !is_derived_property(key))
+                    .cloned(),
+            );
+        }
+    }
+    keys
+}
+
+fn is_derived_property(key: &str) -> bool {
+    key.starts_with("date:") || key == "signature" || key.starts_with("filename")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+
+    const BEFORE: &str = "Image:
+  Filename: photo.jpg
+  Properties:
+    date:create: 2024-01-01T10:00:00+00:00
+    exif:GPSLatitude: 51/1, 30/1, 0/1
+    exif:Make: Canon
+    jpeg:colorspace: 2
+    signature: abc
+  Profiles:
+    Profile-exif: 1024 bytes
+    Profile-icc: 3144 bytes
+    Profile-xmp: 512 bytes
+";
+
+    const AFTER: &str = "Image:
+  Filename: clean.jpg
+  Properties:
+    date:create: 2024-01-02T10:00:00+00:00
+    jpeg:colorspace: 2
+    signature: def
+  Profiles:
+    Profile-icc: 3144 bytes
+";
+
+    /// Reports metadata for the input and output, recording the commands it runs
+    struct MockCommandRunner {
+        commands: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.commands
+                .borrow_mut()
+                .push(args.iter().map(|arg| arg.to_string()).collect());
+            let output = match args {
+                ["identify", "-verbose", "photo.jpg[0]"] => BEFORE,
+                ["identify", "-verbose", _] => AFTER,
+                _ => "",
+            };
+            Ok(output.to_string())
+        }
+    }
+
+    #[test]
+    fn test_reports_removed_metadata() {
+        let runner = MockCommandRunner {
+            commands: RefCell::new(Vec::new()),
+        };
+        let strip = StripMetadata::new(&runner, None);
+        let mut options = StripMetadataOptions::new("photo.jpg", "clean.jpg");
+        options.keep_color_profile = true;
+
+        let stripped = strip.run(&options).unwrap();
+
+        assert_eq!(stripped.output.path, Path::new("clean.jpg"));
+        assert_eq!(
+            stripped.removed,
+            vec![
+                "Profile-exif",
+                "Profile-xmp",
+                "exif:GPSLatitude",
+                "exif:Make"
+            ]
+        );
+        assert_eq!(stripped.remaining, vec!["Profile-icc", "jpeg:colorspace"]);
+        assert_eq!(
+            runner.commands.borrow()[1],
+            vec![
+                "photo.jpg",
+                "-auto-orient",
+                "+profile",
+                "!icc,*",
+                "+set",
+                "comment",
+                "-define",
+                "png:exclude-chunk=date,time",
+                "clean.jpg"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_args_strips_everything_by_default() {
+        let options = StripMetadataOptions::new("photo.jpg", "clean.jpg");
+
+        assert_eq!(
+            build_args(&options),
+            vec!["photo.jpg", "-auto-orient", "-strip", "clean.jpg"]
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// An artistic effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// Remove all color
+    Grayscale,
+    /// Warm brown tones of an old photograph
+    Sepia,
+    /// Reduce each channel to a few flat levels, like a poster print
+    Posterize,
+    /// Charcoal drawing
+    Charcoal,
+    /// Pencil sketch
+    Sketch,
+    /// Oil painting
+    OilPaint,
+}
+
+impl Style {
+    /// Parse a style name
+    pub fn parse(name: &str) -> Result<Self, OperationError> {
+        match name {
+            "grayscale" => Ok(Style::Grayscale),
+            "sepia" => Ok(Style::Sepia),
+            "posterize" => Ok(Style::Posterize),
+            "charcoal" => Ok(Style::Charcoal),
+            "sketch" => Ok(Style::Sketch),
+            "oil_paint" => Ok(Style::OilPaint),
+            other => Err(OperationError::InvalidParameter(format!(
+                "unknown style '{other}', expected grayscale, sepia, posterize, charcoal, sketch, or oil_paint"
+            ))),
+        }
+    }
+
+    /// The operators applying the style at full intensity
+    fn operators(self) -> &'static [&'static str] {
+        match self {
+            Style::Grayscale => &["-colorspace", "Gray"],
+            Style::Sepia => &["-sepia-tone", "80%"],
+            Style::Posterize => &["-posterize", "4"],
+            Style::Charcoal => &["-charcoal", "2"],
+            Style::Sketch => &["-colorspace", "Gray", "-sketch", "0x20+120"],
+            Style::OilPaint => &["-paint", "4"],
+        }
+    }
+
+    /// Whether the style leaves the image in the gray colorspace
+    fn is_gray(self) -> bool {
+        matches!(self, Style::Grayscale | Style::Sketch)
+    }
+}
+
+/// Options for applying an artistic effect to an image
+#[derive(Debug, Clone)]
+pub struct StylizeOptions {
+    /// Path to the source image
+    pub input: String,
+    /// Path to write the stylized image to
+    pub output: String,
+    /// The effect to apply
+    pub style: Style,
+    /// How much of the effect to apply, from 0 (none) to 1 (full)
+    pub intensity: f64,
+}
+
+impl StylizeOptions {
+    /// Create options applying the style at full intensity
+    pub fn new(input: &str, output: &str, style: Style) -> Self {
+        StylizeOptions {
+            input: input.to_string(),
+            output: output.to_string(),
+            style,
+            intensity: 1.0,
+        }
+    }
+}
+
+/// Operation which applies an artistic effect to an image
+pub struct Stylize<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> Stylize<'a> {
+    /// Create a new Stylize with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Stylize {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Apply the effect and write the output image
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output on success, or an `OperationError` on failure
+    pub fn run(&self, options: &StylizeOptions) -> Result<ResolvedOutput, OperationError> {
+        let args = build_args(options)?;
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        Ok(self.magick_runner.last_resolved_output(&options.output))
+    }
+}
+
+/// Build the `magick` arguments applying the effect
+///
+/// Below full intensity the effect is applied to a copy of the image which is blended over the
+/// original, so every style fades in the same way.
+fn build_args(options: &StylizeOptions) -> Result<Vec<String>, OperationError> {
+    let intensity = options.intensity;
+    if !(0.0..=1.0).contains(&intensity) {
+        return Err(OperationError::InvalidParameter(format!(
+            "intensity {intensity} must be between 0 and 1"
+        )));
+    }
+    let operators = options.style.operators().iter().map(|op| op.to_string());
+
+    let mut args = vec![options.input.clone()];
+    if intensity == 1.0 {
+        args.extend(operators);
+    } else {
+        args.extend(["(".to_string(), "+clone".to_string()]);
+        args.extend(operators);
+        if options.style.is_gray() {
+            args.extend(["-colorspace".to_string(), "sRGB".to_string()]);
+        }
+        args.extend([
+            ")".to_string(),
+            "-compose".to_string(),
+            "blend".to_string(),
+            "-define".to_string(),
+            format!("compose:args={}", (intensity * 100.0).round()),
+            "-composite".to_string(),
+        ]);
+    }
+    args.push(options.output.clone());
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_intensity_applies_operators() {
+        let options = StylizeOptions::new("in.jpg", "out.jpg", Style::Sepia);
+
+        assert_eq!(
+            build_args(&options).unwrap(),
+            ["in.jpg", "-sepia-tone", "80%", "out.jpg"]
+        );
+    }
+
+    #[test]
+    fn test_partial_intensity_blends_with_original() {
+        let mut options = StylizeOptions::new("in.jpg", "out.jpg", Style::Sketch);
+        options.intensity = 0.4;
+
+        assert_eq!(
+            build_args(&options).unwrap(),
+            [
+                "in.jpg",
+                "(",
+                "+clone",
+                "-colorspace",
+                "Gray",
+                "-sketch",
+                "0x20+120",
+                "-colorspace",
+                "sRGB",
+                ")",
+                "-compose",
+                "blend",
+                "-define",
+                "compose:args=40",
+                "-composite",
+                "out.jpg"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        assert!(Style::parse("vaporwave").is_err());
+        assert_eq!(Style::parse("oil_paint").unwrap(), Style::OilPaint);
+
+        let mut options = StylizeOptions::new("in.jpg", "out.jpg", Style::Charcoal);
+        options.intensity = 1.5;
+        assert!(build_args(&options).is_err());
+    }
+}
@@ -0,0 +1,188 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::shell::CommandRunner;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Sizes generated when none are requested
+pub const DEFAULT_THUMBNAIL_SIZES: &[u32] = &[64, 128, 256, 512];
+
+/// Options for generating thumbnails
+#[derive(Debug, Clone)]
+pub struct ThumbnailOptions {
+    /// Path to the source image
+    pub input: String,
+    /// Directory the thumbnails are written to
+    pub output_dir: String,
+    /// Bounding box sizes in pixels; each thumbnail fits inside a square of this size
+    pub sizes: Vec<u32>,
+    /// File extension of the thumbnails, defaults to the input's
+    pub format: Option<String>,
+}
+
+impl ThumbnailOptions {
+    /// Create options generating the default sizes in the input's format
+    pub fn new(input: &str, output_dir: &str) -> Self {
+        ThumbnailOptions {
+            input: input.to_string(),
+            output_dir: output_dir.to_string(),
+            sizes: DEFAULT_THUMBNAIL_SIZES.to_vec(),
+            format: None,
+        }
+    }
+}
+
+/// A single generated thumbnail
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThumbnailFile {
+    pub path: PathBuf,
+    /// The bounding box size the thumbnail fits inside
+    pub size: u32,
+    /// Size of the file in bytes
+    pub bytes: u64,
+}
+
+/// Operation which generates small, metadata-free thumbnails in several sizes
+pub struct Thumbnail<'a> {
+    magick_runner: MagickRunner<'a>,
+    base: PathBuf,
+}
+
+impl<'a> Thumbnail<'a> {
+    /// Create a new Thumbnail with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Thumbnail {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+            base: workspace
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(".")),
+        }
+    }
+
+    /// Generate a thumbnail for every size
+    ///
+    /// `-thumbnail` is used rather than `-resize` because it also strips profiles and comments,
+    /// which often outweigh the pixels of a small image.
+    ///
+    /// # Returns
+    ///
+    /// Returns the generated files, or an `OperationError` on failure
+    pub fn run(&self, options: &ThumbnailOptions) -> Result<Vec<ThumbnailFile>, OperationError> {
+        let sizes = thumbnail_sizes(&options.sizes)?;
+        let input = Path::new(&options.input);
+        let stem = input
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "image".to_string());
+        let extension = options
+            .format
+            .clone()
+            .or_else(|| input.extension().map(|e| e.to_string_lossy().to_string()))
+            .unwrap_or_else(|| "png".to_string())
+            .to_ascii_lowercase();
+        fs::create_dir_all(self.base.join(&options.output_dir))?;
+
+        let mut files = Vec::new();
+        for size in sizes {
+            let path = Path::new(&options.output_dir).join(format!("{stem}-{size}.{extension}"));
+            let path_str = path.to_string_lossy().to_string();
+            let geometry = format!("{size}x{size}");
+            self.magick_runner.execute_args(&[
+                &options.input,
+                "-thumbnail",
+                &geometry,
+                &path_str,
+            ])?;
+            let written = self.magick_runner.last_resolved_output(&path_str).path;
+            let bytes = fs::metadata(self.base.join(&written))?.len();
+            files.push(ThumbnailFile {
+                path: written,
+                size,
+                bytes,
+            });
+        }
+        Ok(files)
+    }
+}
+
+/// Sorted, deduplicated sizes, rejecting empty and zero sizes
+fn thumbnail_sizes(sizes: &[u32]) -> Result<Vec<u32>, OperationError> {
+    if sizes.is_empty() || sizes.contains(&0) {
+        return Err(OperationError::InvalidParameter(
+            "sizes must contain at least one size greater than zero".to_string(),
+        ));
+    }
+    let mut sizes = sizes.to_vec();
+    sizes.sort_unstable();
+    sizes.dedup();
+    Ok(sizes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+    use tempfile::TempDir;
+
+    /// Mock implementation of CommandRunner which writes one byte per pixel of the size
+    struct MockCommandRunner {
+        calls: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|s| s.to_string()).collect());
+            let size: usize = args[2].split('x').next().unwrap().parse().unwrap();
+            fs::write(working_dir.unwrap().join(args[3]), vec![0; size]).unwrap();
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_run_generates_each_size() {
+        let dir = TempDir::new().unwrap();
+        let runner = MockCommandRunner {
+            calls: RefCell::new(Vec::new()),
+        };
+        let thumbnail = Thumbnail::new(&runner, Some(dir.path()));
+        let mut options = ThumbnailOptions::new("photos/cat.JPG", "thumbs");
+        options.sizes = vec![128, 64, 128];
+
+        let files = thumbnail.run(&options).unwrap();
+
+        assert_eq!(
+            files,
+            vec![
+                ThumbnailFile {
+                    path: PathBuf::from("thumbs/cat-64.jpg"),
+                    size: 64,
+                    bytes: 64
+                },
+                ThumbnailFile {
+                    path: PathBuf::from("thumbs/cat-128.jpg"),
+                    size: 128,
+                    bytes: 128
+                },
+            ]
+        );
+        assert_eq!(
+            runner.calls.borrow()[0],
+            vec!["photos/cat.JPG", "-thumbnail", "64x64", "thumbs/cat-64.jpg"]
+        );
+    }
+
+    #[test]
+    fn test_thumbnail_sizes_validation() {
+        assert!(thumbnail_sizes(&[]).is_err());
+        assert!(thumbnail_sizes(&[0, 64]).is_err());
+        assert_eq!(thumbnail_sizes(&[512, 64]).unwrap(), vec![64, 512]);
+    }
+}
@@ -0,0 +1,186 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::identify::{self, ImageDimensions};
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// Options for trimming the uniform border off an image
+#[derive(Debug, Clone)]
+pub struct TrimOptions {
+    /// Path to the source image
+    pub input: String,
+    /// Path to write the trimmed image to
+    pub output: String,
+    /// Percentage by which border colors may differ from the corner color and still be trimmed
+    pub fuzz: Option<f64>,
+}
+
+impl TrimOptions {
+    /// Create options trimming only pixels exactly matching the border color
+    pub fn new(input: &str, output: &str) -> Self {
+        TrimOptions {
+            input: input.to_string(),
+            output: output.to_string(),
+            fuzz: None,
+        }
+    }
+}
+
+/// The result of trimming an image
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trimmed {
+    /// The written output
+    pub output: ResolvedOutput,
+    /// Dimensions of the input
+    pub before: ImageDimensions,
+    /// Dimensions of the trimmed image, `None` when writing it was skipped
+    pub after: Option<ImageDimensions>,
+}
+
+/// Operation which crops away the uniform border around an image
+pub struct Trimmer<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> Trimmer<'a> {
+    /// Create a new Trimmer with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Trimmer {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Trim the image and measure it before and after
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output with both sizes, or an `OperationError` on failure
+    pub fn run(&self, options: &TrimOptions) -> Result<Trimmed, OperationError> {
+        let args = build_args(options)?;
+        let before = identify::dimensions(&self.magick_runner, &options.input)?;
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        let output = self.magick_runner.last_resolved_output(&options.output);
+        let after = if output.skipped {
+            None
+        } else {
+            Some(identify::dimensions(
+                &self.magick_runner,
+                &output.path.to_string_lossy(),
+            )?)
+        };
+        Ok(Trimmed {
+            output,
+            before,
+            after,
+        })
+    }
+}
+
+/// Build the `magick` arguments trimming the image
+///
+/// `+repage` drops the virtual canvas left behind by `-trim`, so the output's size is its
+/// trimmed size.
+fn build_args(options: &TrimOptions) -> Result<Vec<String>, OperationError> {
+    let mut args = vec![options.input.clone()];
+    if let Some(fuzz) = options.fuzz {
+        if !(0.0..=100.0).contains(&fuzz) {
+            return Err(OperationError::InvalidParameter(format!(
+                "fuzz {fuzz} must be a percentage between 0 and 100"
+            )));
+        }
+        args.extend(["-fuzz".to_string(), format!("{fuzz}%")]);
+    }
+    args.extend([
+        "-trim".to_string(),
+        "+repage".to_string(),
+        options.output.clone(),
+    ]);
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+
+    /// Reports 400x300 for the input and 320x240 for anything else
+    struct MockCommandRunner {
+        calls: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl MockCommandRunner {
+        fn new() -> Self {
+            MockCommandRunner {
+                calls: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|arg| arg.to_string()).collect());
+            match args {
+                ["identify", .., "scan.png[0]"] => Ok("400 300".to_string()),
+                ["identify", ..] => Ok("320 240".to_string()),
+                _ => Ok(String::new()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_trim_reports_both_sizes() {
+        let runner = MockCommandRunner::new();
+        let mut options = TrimOptions::new("scan.png", "trimmed.png");
+        options.fuzz = Some(5.0);
+
+        let trimmed = Trimmer::new(&runner, None).run(&options).unwrap();
+
+        assert_eq!(trimmed.output, ResolvedOutput::written("trimmed.png"));
+        assert_eq!(
+            trimmed.before,
+            ImageDimensions {
+                width: 400,
+                height: 300
+            }
+        );
+        assert_eq!(
+            trimmed.after,
+            Some(ImageDimensions {
+                width: 320,
+                height: 240
+            })
+        );
+        let calls = runner.calls.borrow();
+        assert_eq!(
+            calls[1],
+            ["scan.png", "-fuzz", "5%", "-trim", "+repage", "trimmed.png"]
+        );
+        assert_eq!(calls[2].last().unwrap(), "trimmed.png[0]");
+    }
+
+    #[test]
+    fn test_invalid_fuzz() {
+        let runner = MockCommandRunner::new();
+        let mut options = TrimOptions::new("scan.png", "trimmed.png");
+        options.fuzz = Some(150.0);
+
+        assert!(Trimmer::new(&runner, None).run(&options).is_err());
+        assert!(runner.calls.borrow().is_empty());
+    }
+}
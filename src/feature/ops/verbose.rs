@@ -0,0 +1,211 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::shell::CommandRunner;
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// Longest value kept before truncation
+const MAX_VALUE_LEN: usize = 256;
+/// Most unstructured lines (e.g. binary profile dumps) kept per section
+const MAX_RAW_LINES: usize = 8;
+
+/// Operation which runs `identify -verbose` and parses the output into nested JSON
+pub struct VerboseIdentify<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> VerboseIdentify<'a> {
+    /// Create a new VerboseIdentify with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        VerboseIdentify {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Identify an image and return its metadata as a JSON object
+    ///
+    /// # Returns
+    ///
+    /// Returns the parsed metadata, or an `OperationError` on failure
+    pub fn run(&self, path: &str) -> Result<Value, OperationError> {
+        let output = self
+            .magick_runner
+            .execute_args(&["identify", "-verbose", path])?;
+        Ok(parse_verbose(&output))
+    }
+}
+
+/// A non-blank line of `identify -verbose` output
+struct Line<'a> {
+    indent: usize,
+    text: &'a str,
+}
+
+/// Parse indented `identify -verbose` output into nested JSON
+///
+/// `Key: value` lines become string fields, `Section:` lines become objects holding the more
+/// deeply indented lines that follow, and anything else (such as hex profile dumps) is collected
+/// into a truncated `_lines` array.
+pub(crate) fn parse_verbose(output: &str) -> Value {
+    let lines: Vec<Line> = output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Line {
+            indent: line.len() - line.trim_start().len(),
+            text: line.trim(),
+        })
+        .collect();
+    let mut pos = 0;
+    let indent = lines.first().map(|line| line.indent).unwrap_or(0);
+    Value::Object(parse_block(&lines, &mut pos, indent))
+}
+
+/// Parse lines at or beneath `indent` into an object
+fn parse_block(lines: &[Line], pos: &mut usize, indent: usize) -> Map<String, Value> {
+    let mut map = Map::new();
+    let mut raw_lines = Vec::new();
+    let mut raw_total = 0;
+    while let Some(line) = lines.get(*pos) {
+        if line.indent < indent {
+            break;
+        }
+        *pos += 1;
+        if let Some((key, value)) = line.text.split_once(": ") {
+            insert(&mut map, key.trim(), Value::String(truncate(value.trim())));
+        } else if let Some(key) = line.text.strip_suffix(':') {
+            let child = match lines.get(*pos) {
+                Some(next) if next.indent > line.indent => parse_block(lines, pos, next.indent),
+                _ => Map::new(),
+            };
+            insert(&mut map, key.trim(), Value::Object(child));
+        } else {
+            raw_total += 1;
+            if raw_lines.len() < MAX_RAW_LINES {
+                raw_lines.push(Value::String(truncate(line.text)));
+            }
+        }
+    }
+    if raw_total > raw_lines.len() {
+        raw_lines.push(Value::String(format!(
+            "... {} more lines truncated",
+            raw_total - raw_lines.len()
+        )));
+    }
+    if !raw_lines.is_empty() {
+        map.insert("_lines".to_string(), Value::Array(raw_lines));
+    }
+    map
+}
+
+/// Insert a value, collecting repeated keys (e.g. multiple frames) into an array
+fn insert(map: &mut Map<String, Value>, key: &str, value: Value) {
+    match map.get_mut(key) {
+        Some(Value::Array(values)) => {
+            values.push(value);
+        }
+        Some(existing) => {
+            let previous = existing.take();
+            *existing = Value::Array(vec![previous, value]);
+        }
+        None => {
+            map.insert(key.to_string(), value);
+        }
+    }
+}
+
+/// Truncate overly long values such as embedded binary data
+fn truncate(value: &str) -> String {
+    if value.chars().count() <= MAX_VALUE_LEN {
+        value.to_string()
+    } else {
+        let kept: String = value.chars().take(MAX_VALUE_LEN).collect();
+        format!("{kept}... (truncated)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VERBOSE_OUTPUT: &str = "Image:
+  Filename: test.jpg
+  Format: JPEG (Joint Photographic Experts Group JFIF format)
+  Geometry: 640x480+0+0
+  Channel statistics:
+    Pixels: 307200
+    Red:
+      min: 0  (0)
+      max: 255 (1)
+    Green:
+      min: 3  (0.0117647)
+  Properties:
+    date:create: 2024-01-01T10:00:00+00:00
+    exif:Make: Canon
+  Profiles:
+    Profile-8bim: 12 bytes
+      3842494d 04040000 00000000
+      3842494d 04250000 00000010
+";
+
+    #[test]
+    fn test_parse_nested_sections() {
+        let parsed = parse_verbose(VERBOSE_OUTPUT);
+        let image = &parsed["Image"];
+
+        assert_eq!(image["Filename"], "test.jpg");
+        assert_eq!(image["Geometry"], "640x480+0+0");
+        assert_eq!(image["Channel statistics"]["Pixels"], "307200");
+        assert_eq!(image["Channel statistics"]["Red"]["max"], "255 (1)");
+        assert_eq!(
+            image["Channel statistics"]["Green"]["min"],
+            "3  (0.0117647)"
+        );
+    }
+
+    #[test]
+    fn test_parse_keys_containing_colons() {
+        let parsed = parse_verbose(VERBOSE_OUTPUT);
+        let properties = &parsed["Image"]["Properties"];
+
+        assert_eq!(properties["date:create"], "2024-01-01T10:00:00+00:00");
+        assert_eq!(properties["exif:Make"], "Canon");
+    }
+
+    #[test]
+    fn test_parse_raw_profile_lines() {
+        let parsed = parse_verbose(VERBOSE_OUTPUT);
+        let profiles = &parsed["Image"]["Profiles"];
+
+        assert_eq!(profiles["Profile-8bim"], "12 bytes");
+        assert_eq!(profiles["_lines"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_raw_lines_are_truncated() {
+        let mut output = "Image:\n  Profiles:\n".to_string();
+        for _ in 0..20 {
+            output.push_str("    00ff00ff 00ff00ff\n");
+        }
+        let parsed = parse_verbose(&output);
+        let lines = parsed["Image"]["Profiles"]["_lines"].as_array().unwrap();
+
+        assert_eq!(lines.len(), MAX_RAW_LINES + 1);
+        assert_eq!(lines.last().unwrap(), "... 12 more lines truncated");
+    }
+
+    #[test]
+    fn test_repeated_sections_become_array() {
+        let output = "Image:\n  Scene: 0\nImage:\n  Scene: 1\n";
+        let parsed = parse_verbose(output);
+        let frames = parsed["Image"].as_array().unwrap();
+
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1]["Scene"], "1");
+    }
+
+    #[test]
+    fn test_long_values_are_truncated() {
+        let value = "a".repeat(MAX_VALUE_LEN + 10);
+        assert!(truncate(&value).ends_with("... (truncated)"));
+    }
+}
@@ -0,0 +1,361 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::bench::compare;
+use crate::feature::ops::identify::{self, ImageDimensions};
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// How the difference between two images is measured
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DiffMetric {
+    /// Structural similarity, from 0 to 1 where 1 is identical
+    #[default]
+    Ssim,
+    /// Root mean squared error normalized to 0 to 1, where 0 is identical
+    Rmse,
+    /// Absolute error: the number of pixels which differ
+    Ae,
+}
+
+impl DiffMetric {
+    /// Parse a metric name
+    pub fn parse(name: &str) -> Result<Self, OperationError> {
+        match name {
+            "ssim" => Ok(DiffMetric::Ssim),
+            "rmse" => Ok(DiffMetric::Rmse),
+            "ae" => Ok(DiffMetric::Ae),
+            other => Err(OperationError::InvalidParameter(format!(
+                "unknown metric '{other}', expected ssim, rmse, or ae"
+            ))),
+        }
+    }
+
+    /// The metric's name as reported in results
+    pub fn name(self) -> &'static str {
+        match self {
+            DiffMetric::Ssim => "ssim",
+            DiffMetric::Rmse => "rmse",
+            DiffMetric::Ae => "ae",
+        }
+    }
+
+    /// Threshold used when none is given
+    pub fn default_threshold(self) -> f64 {
+        match self {
+            DiffMetric::Ssim => 0.99,
+            DiffMetric::Rmse => 0.01,
+            DiffMetric::Ae => 0.0,
+        }
+    }
+
+    /// Whether a value passes the threshold
+    ///
+    /// Similarity passes at or above the threshold, and errors at or below it.
+    pub fn passes(self, value: f64, threshold: f64) -> bool {
+        match self {
+            DiffMetric::Ssim => value >= threshold,
+            DiffMetric::Rmse | DiffMetric::Ae => value <= threshold,
+        }
+    }
+
+    /// The value measured for identical images
+    fn identical(self) -> f64 {
+        match self {
+            DiffMetric::Ssim => 1.0,
+            DiffMetric::Rmse | DiffMetric::Ae => 0.0,
+        }
+    }
+
+    /// Read the value from `compare` output
+    ///
+    /// RMSE is reported as `1234.5 (0.0188)`, where the value in parentheses is normalized.
+    fn parse_output(self, output: &str) -> Option<f64> {
+        let mut tokens = output.split_whitespace();
+        let first = tokens.next()?;
+        let normalized = tokens
+            .next()
+            .and_then(|token| token.strip_prefix('(')?.strip_suffix(')'));
+        match (self, normalized) {
+            (DiffMetric::Rmse, Some(normalized)) => normalized.parse().ok(),
+            _ => first.parse().ok(),
+        }
+    }
+}
+
+/// Options for comparing two images
+#[derive(Debug, Clone)]
+pub struct VisualDiffOptions {
+    /// Path to the expected image
+    pub expected: String,
+    /// Path to the image to check against it
+    pub actual: String,
+    /// Path to write the image highlighting the differences to
+    pub diff: String,
+    /// How the difference is measured
+    pub metric: DiffMetric,
+    /// Threshold the value must pass, defaulting to the metric's
+    pub threshold: Option<f64>,
+    /// Percentage by which colors may differ and still count as equal
+    pub fuzz: Option<f64>,
+    /// Color differing pixels are painted in the diff image, ImageMagick's red by default
+    pub highlight_color: Option<String>,
+}
+
+impl VisualDiffOptions {
+    /// Create options comparing the images by structural similarity
+    pub fn new(expected: &str, actual: &str, diff: &str) -> Self {
+        VisualDiffOptions {
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+            diff: diff.to_string(),
+            metric: DiffMetric::Ssim,
+            threshold: None,
+            fuzz: None,
+            highlight_color: None,
+        }
+    }
+}
+
+/// The verdict of comparing two images
+#[derive(Debug, Clone, PartialEq)]
+pub struct VisualDiff {
+    /// Whether the value passed the threshold
+    pub passed: bool,
+    /// The measured value, `None` when the dimensions differ
+    pub value: Option<f64>,
+    /// The threshold the value was checked against
+    pub threshold: f64,
+    /// The written diff image, `None` when the dimensions differ
+    pub diff: Option<ResolvedOutput>,
+    /// Dimensions of the expected image
+    pub expected_dimensions: ImageDimensions,
+    /// Dimensions of the actual image
+    pub actual_dimensions: ImageDimensions,
+}
+
+/// Operation which compares two images and writes a diff image
+pub struct VisualDiffer<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> VisualDiffer<'a> {
+    /// Create a new VisualDiffer with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        VisualDiffer {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Use the provided policy for the diff image
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Compare the images, write the diff image, and check the value against the threshold
+    ///
+    /// Images of different dimensions fail without being compared.
+    ///
+    /// # Returns
+    ///
+    /// Returns the verdict, or an `OperationError` if the images cannot be read or compared
+    pub fn run(&self, options: &VisualDiffOptions) -> Result<VisualDiff, OperationError> {
+        let metric = options.metric;
+        let threshold = options
+            .threshold
+            .unwrap_or_else(|| metric.default_threshold());
+        let valid = match metric {
+            DiffMetric::Ssim | DiffMetric::Rmse => (0.0..=1.0).contains(&threshold),
+            DiffMetric::Ae => threshold >= 0.0,
+        };
+        if !valid {
+            return Err(OperationError::InvalidParameter(format!(
+                "threshold {threshold} is out of range for {}",
+                metric.name()
+            )));
+        }
+
+        let expected_dimensions = identify::dimensions(&self.magick_runner, &options.expected)?;
+        let actual_dimensions = identify::dimensions(&self.magick_runner, &options.actual)?;
+        let mut verdict = VisualDiff {
+            passed: false,
+            value: None,
+            threshold,
+            diff: None,
+            expected_dimensions,
+            actual_dimensions,
+        };
+        if expected_dimensions != actual_dimensions {
+            return Ok(verdict);
+        }
+
+        let args = build_args(options)?;
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        let value = match compare(&self.magick_runner, &arg_refs)? {
+            Some(output) => metric.parse_output(&output).ok_or_else(|| {
+                OperationError::ParseError(format!("unexpected compare output: {output}"))
+            })?,
+            None => metric.identical(),
+        };
+        verdict.passed = metric.passes(value, threshold);
+        verdict.value = Some(value);
+        verdict.diff = Some(self.magick_runner.last_resolved_output(&options.diff));
+        Ok(verdict)
+    }
+}
+
+/// Build the `compare` arguments, without the `compare` itself
+fn build_args(options: &VisualDiffOptions) -> Result<Vec<String>, OperationError> {
+    let mut args = vec![
+        "-metric".to_string(),
+        options.metric.name().to_ascii_uppercase(),
+    ];
+    if let Some(fuzz) = options.fuzz {
+        if !(0.0..=100.0).contains(&fuzz) {
+            return Err(OperationError::InvalidParameter(format!(
+                "fuzz {fuzz} must be a percentage between 0 and 100"
+            )));
+        }
+        args.extend(["-fuzz".to_string(), format!("{fuzz}%")]);
+    }
+    if let Some(color) = &options.highlight_color {
+        args.extend(["-highlight-color".to_string(), color.clone()]);
+    }
+    args.extend([
+        format!("{}[0]", options.expected),
+        format!("{}[0]", options.actual),
+        options.diff.clone(),
+    ]);
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::shell::ShellError;
+    use std::cell::RefCell;
+
+    /// Reports fixed dimensions for each image and a fixed `compare` output
+    struct MockCommandRunner {
+        actual_size: &'static str,
+        metric: Option<&'static str>,
+        calls: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl MockCommandRunner {
+        fn new(actual_size: &'static str, metric: Option<&'static str>) -> Self {
+            MockCommandRunner {
+                actual_size,
+                metric,
+                calls: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn execute(
+            &self,
+            _command: &str,
+            args: &[&str],
+            _working_dir: Option<&Path>,
+        ) -> Result<String, ShellError> {
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|arg| arg.to_string()).collect());
+            match args[0] {
+                "identify" if args[3].starts_with("actual") => Ok(self.actual_size.to_string()),
+                "identify" => Ok("100 50".to_string()),
+                _ => match self.metric {
+                    Some(metric) => Err(ShellError::NonZeroExit {
+                        exit_code: 1,
+                        command: "magick".to_string(),
+                        args: args.join(" "),
+                        stdout: String::new(),
+                        stderr: metric.to_string(),
+                    }),
+                    None => Ok(String::new()),
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn test_ssim_verdict() {
+        let runner = MockCommandRunner::new("100 50", Some("0.9731"));
+        let options = VisualDiffOptions::new("expected.png", "actual.png", "diff.png");
+
+        let verdict = VisualDiffer::new(&runner, None).run(&options).unwrap();
+
+        assert!(!verdict.passed);
+        assert_eq!(verdict.value, Some(0.9731));
+        assert_eq!(verdict.threshold, 0.99);
+        assert_eq!(verdict.diff, Some(ResolvedOutput::written("diff.png")));
+        assert_eq!(
+            runner.calls.borrow()[2],
+            [
+                "compare",
+                "-metric",
+                "SSIM",
+                "expected.png[0]",
+                "actual.png[0]",
+                "diff.png"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rmse_uses_normalized_value() {
+        let runner = MockCommandRunner::new("100 50", Some("327.68 (0.005)"));
+        let mut options = VisualDiffOptions::new("expected.png", "actual.png", "diff.png");
+        options.metric = DiffMetric::Rmse;
+        options.fuzz = Some(2.0);
+        options.highlight_color = Some("magenta".to_string());
+
+        let verdict = VisualDiffer::new(&runner, None).run(&options).unwrap();
+
+        assert!(verdict.passed);
+        assert_eq!(verdict.value, Some(0.005));
+        let calls = runner.calls.borrow();
+        assert_eq!(
+            calls[2][3..7],
+            ["-fuzz", "2%", "-highlight-color", "magenta"]
+        );
+    }
+
+    #[test]
+    fn test_identical_images_pass() {
+        let runner = MockCommandRunner::new("100 50", None);
+        let mut options = VisualDiffOptions::new("expected.png", "actual.png", "diff.png");
+        options.metric = DiffMetric::Ae;
+
+        let verdict = VisualDiffer::new(&runner, None).run(&options).unwrap();
+
+        assert!(verdict.passed);
+        assert_eq!(verdict.value, Some(0.0));
+    }
+
+    #[test]
+    fn test_size_mismatch_fails_without_comparing() {
+        let runner = MockCommandRunner::new("200 50", Some("0.5"));
+        let options = VisualDiffOptions::new("expected.png", "actual.png", "diff.png");
+
+        let verdict = VisualDiffer::new(&runner, None).run(&options).unwrap();
+
+        assert!(!verdict.passed);
+        assert_eq!(verdict.value, None);
+        assert_eq!(verdict.diff, None);
+        assert_eq!(runner.calls.borrow().len(), 2);
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        assert!(DiffMetric::parse("psnr").is_err());
+
+        let runner = MockCommandRunner::new("100 50", None);
+        let mut options = VisualDiffOptions::new("expected.png", "actual.png", "diff.png");
+        options.threshold = Some(1.5);
+        assert!(VisualDiffer::new(&runner, None).run(&options).is_err());
+        assert!(runner.calls.borrow().is_empty());
+    }
+}
@@ -0,0 +1,314 @@
+use crate::feature::magick::MagickRunner;
+use crate::feature::ops::OperationError;
+use crate::feature::ops::caption::escape_caption_text;
+use crate::feature::ops::identify;
+use crate::feature::output::{OutputPolicy, ResolvedOutput};
+use crate::feature::shell::CommandRunner;
+use std::path::Path;
+
+/// Gravity names accepted by ImageMagick
+const GRAVITIES: &[&str] = &[
+    "NorthWest",
+    "North",
+    "NorthEast",
+    "West",
+    "Center",
+    "East",
+    "SouthWest",
+    "South",
+    "SouthEast",
+];
+
+/// Normalize a gravity name, case-insensitively, to the form ImageMagick expects
+pub(crate) fn parse_gravity(gravity: &str) -> Result<&'static str, OperationError> {
+    GRAVITIES
+        .iter()
+        .find(|name| name.eq_ignore_ascii_case(gravity))
+        .copied()
+        .ok_or_else(|| {
+            OperationError::InvalidParameter(format!(
+                "unknown gravity '{gravity}', expected one of {}",
+                GRAVITIES.join(", ")
+            ))
+        })
+}
+
+/// What a watermark shows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatermarkMark {
+    /// Path to a logo image
+    Image(String),
+    /// Text rendered onto the image
+    Text(String),
+}
+
+/// Options for placing a logo or text watermark on an image
+#[derive(Debug, Clone)]
+pub struct WatermarkOptions {
+    /// Path to the source image
+    pub input: String,
+    /// Path to write the watermarked copy to
+    pub output: String,
+    /// The logo or text to place
+    pub mark: WatermarkMark,
+    /// Where the mark is placed, e.g. `SouthEast`
+    pub gravity: String,
+    /// Mark opacity from 0 (invisible) to 100 (opaque)
+    pub opacity: f64,
+    /// Distance in pixels between the mark and the image edge
+    pub margin: u32,
+    /// Width of the mark as a fraction of the image width, e.g. `0.2`. Logos keep their own size
+    /// and text is a quarter of the image width when unset.
+    pub scale: Option<f64>,
+    /// Font for text marks, as listed by `magick -list font`
+    pub font: Option<String>,
+    /// Color of text marks
+    pub color: String,
+}
+
+impl WatermarkOptions {
+    /// Create options with a bottom-right logo at 50% opacity and a 10px margin
+    pub fn new(input: &str, output: &str, logo: &str) -> Self {
+        WatermarkOptions {
+            input: input.to_string(),
+            output: output.to_string(),
+            mark: WatermarkMark::Image(logo.to_string()),
+            gravity: "SouthEast".to_string(),
+            opacity: 50.0,
+            margin: 10,
+            scale: None,
+            font: None,
+            color: "white".to_string(),
+        }
+    }
+
+    /// Create options with bottom-right white text at 50% opacity and a 10px margin
+    pub fn text(input: &str, output: &str, text: &str) -> Self {
+        WatermarkOptions {
+            mark: WatermarkMark::Text(text.to_string()),
+            ..WatermarkOptions::new(input, output, "")
+        }
+    }
+}
+
+/// Width of a text mark as a fraction of the image width when no scale is given
+const DEFAULT_TEXT_SCALE: f64 = 0.25;
+
+/// Operation which composites a semi-transparent logo or text onto an image
+pub struct Watermark<'a> {
+    magick_runner: MagickRunner<'a>,
+}
+
+impl<'a> Watermark<'a> {
+    /// Create a new Watermark with the provided CommandRunner and optional workspace path
+    pub fn new(command_runner: &'a dyn CommandRunner, workspace: Option<&'a Path>) -> Self {
+        Watermark {
+            magick_runner: MagickRunner::new(command_runner, workspace),
+        }
+    }
+
+    /// Use the provided policy for the output file
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.magick_runner = self.magick_runner.with_output_policy(output_policy);
+        self
+    }
+
+    /// Apply the watermark and write the result
+    ///
+    /// The input is identified first when the mark is sized relative to it.
+    ///
+    /// # Returns
+    ///
+    /// Returns the written output on success, or an `OperationError` on failure
+    pub fn run(&self, options: &WatermarkOptions) -> Result<ResolvedOutput, OperationError> {
+        let mark_width = match mark_scale(options)? {
+            Some(scale) => {
+                let dims = identify::dimensions(&self.magick_runner, &options.input)?;
+                Some(((f64::from(dims.width) * scale).round() as u32).max(1))
+            }
+            None => None,
+        };
+        let args = build_args(options, mark_width)?;
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.magick_runner.execute_args(&arg_refs)?;
+        Ok(self.magick_runner.last_resolved_output(&options.output))
+    }
+}
+
+/// The fraction of the image width the mark is sized to, if it is sized relative to the image
+fn mark_scale(options: &WatermarkOptions) -> Result<Option<f64>, OperationError> {
+    if let Some(scale) = options.scale
+        && !(scale > 0.0 && scale <= 1.0)
+    {
+        return Err(OperationError::InvalidParameter(format!(
+            "scale {scale} must be greater than 0 and at most 1"
+        )));
+    }
+    Ok(match options.mark {
+        WatermarkMark::Image(_) => options.scale,
+        WatermarkMark::Text(_) => Some(options.scale.unwrap_or(DEFAULT_TEXT_SCALE)),
+    })
+}
+
+/// Build the `magick` arguments for a watermark
+///
+/// The mark is rendered in parentheses, scaled to `mark_width` pixels wide when given, and its
+/// alpha channel is multiplied by the opacity before it is composited at the gravity.
+fn build_args(
+    options: &WatermarkOptions,
+    mark_width: Option<u32>,
+) -> Result<Vec<String>, OperationError> {
+    if !(0.0..=100.0).contains(&options.opacity) {
+        return Err(OperationError::InvalidParameter(format!(
+            "opacity {} must be between 0 and 100",
+            options.opacity
+        )));
+    }
+    let gravity = parse_gravity(&options.gravity)?;
+    let mut args = vec![options.input.clone(), "(".to_string()];
+    match &options.mark {
+        WatermarkMark::Image(logo) => {
+            args.push(logo.clone());
+            if let Some(width) = mark_width {
+                args.extend(["-resize".to_string(), format!("{width}x")]);
+            }
+        }
+        WatermarkMark::Text(text) => {
+            if text.is_empty() {
+                return Err(OperationError::InvalidParameter(
+                    "watermark text must not be empty".to_string(),
+                ));
+            }
+            args.extend(["-background".to_string(), "none".to_string()]);
+            if let Some(font) = &options.font {
+                args.extend(["-font".to_string(), font.clone()]);
+            }
+            args.extend(["-fill".to_string(), options.color.clone()]);
+            if let Some(width) = mark_width {
+                args.extend(["-size".to_string(), format!("{width}x")]);
+            }
+            args.push(format!("label:{}", escape_caption_text(text)));
+        }
+    }
+    args.extend([
+        "-alpha".to_string(),
+        "set".to_string(),
+        "-channel".to_string(),
+        "A".to_string(),
+        "-evaluate".to_string(),
+        "multiply".to_string(),
+        format!("{}", options.opacity / 100.0),
+        "+channel".to_string(),
+        ")".to_string(),
+        "-gravity".to_string(),
+        gravity.to_string(),
+        "-geometry".to_string(),
+        format!("+{0}+{0}", options.margin),
+        "-composite".to_string(),
+        options.output.clone(),
+    ]);
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_args() {
+        let mut options = WatermarkOptions::new("photo.jpg", "out.jpg", "logo.png");
+        options.gravity = "northwest".to_string();
+        options.opacity = 25.0;
+        options.margin = 4;
+
+        let args = build_args(&options, None).unwrap();
+
+        assert_eq!(
+            args,
+            vec![
+                "photo.jpg",
+                "(",
+                "logo.png",
+                "-alpha",
+                "set",
+                "-channel",
+                "A",
+                "-evaluate",
+                "multiply",
+                "0.25",
+                "+channel",
+                ")",
+                "-gravity",
+                "NorthWest",
+                "-geometry",
+                "+4+4",
+                "-composite",
+                "out.jpg"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_args_rejects_invalid_opacity() {
+        let mut options = WatermarkOptions::new("photo.jpg", "out.jpg", "logo.png");
+        options.opacity = 150.0;
+        assert!(matches!(
+            build_args(&options, None),
+            Err(OperationError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_build_args_text() {
+        let mut options = WatermarkOptions::text("photo.jpg", "out.jpg", "@ 100% mine");
+        options.font = Some("DejaVu-Sans".to_string());
+
+        let args = build_args(&options, Some(250)).unwrap();
+
+        assert_eq!(
+            args[..10],
+            [
+                "photo.jpg",
+                "(",
+                "-background",
+                "none",
+                "-font",
+                "DejaVu-Sans",
+                "-fill",
+                "white",
+                "-size",
+                "250x"
+            ]
+        );
+        assert_eq!(args[10], "label:\\@ 100%% mine");
+        assert_eq!(args.last().unwrap(), "out.jpg");
+    }
+
+    #[test]
+    fn test_mark_scale() {
+        let mut options = WatermarkOptions::new("photo.jpg", "out.jpg", "logo.png");
+        assert_eq!(mark_scale(&options).unwrap(), None);
+        options.scale = Some(0.1);
+        assert_eq!(mark_scale(&options).unwrap(), Some(0.1));
+        options.scale = Some(1.5);
+        assert!(mark_scale(&options).is_err());
+
+        let options = WatermarkOptions::text("photo.jpg", "out.jpg", "mine");
+        assert_eq!(mark_scale(&options).unwrap(), Some(DEFAULT_TEXT_SCALE));
+    }
+
+    #[test]
+    fn test_build_args_resizes_logo() {
+        let options = WatermarkOptions::new("photo.jpg", "out.jpg", "logo.png");
+
+        let args = build_args(&options, Some(120)).unwrap();
+
+        assert_eq!(args[2..5], ["logo.png", "-resize", "120x"]);
+    }
+
+    #[test]
+    fn test_parse_gravity() {
+        assert_eq!(parse_gravity("center").unwrap(), "Center");
+        assert!(parse_gravity("middle").is_err());
+    }
+}
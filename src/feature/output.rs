@@ -0,0 +1,354 @@
+use crate::feature::lock::LockScope;
+use crate::feature::ops::OperationError;
+use crate::feature::shell::ShellError;
+use crate::feature::trash::Trash;
+use std::path::{Path, PathBuf};
+
+/// `magick` subcommands and leading options whose last argument is not an output
+const NON_WRITING_SUBCOMMANDS: &[&str] = &["identify", "mogrify", "-list"];
+
+/// What happens when an output path already exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictStrategy {
+    /// Replace the existing file
+    #[default]
+    Overwrite,
+    /// Leave the existing file and do not run the command
+    Skip,
+    /// Write to the first free name with a `-1`, `-2`, ... suffix
+    Rename,
+}
+
+impl ConflictStrategy {
+    /// Parse a strategy name
+    pub fn parse(name: &str) -> Result<Self, OperationError> {
+        match name {
+            "overwrite" => Ok(ConflictStrategy::Overwrite),
+            "skip" => Ok(ConflictStrategy::Skip),
+            "rename" => Ok(ConflictStrategy::Rename),
+            other => Err(OperationError::InvalidParameter(format!(
+                "unknown conflict strategy '{other}', expected overwrite, skip, or rename"
+            ))),
+        }
+    }
+}
+
+/// How commands treat the files they write
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutputPolicy {
+    /// Create missing parent directories of the output instead of failing
+    pub create_dirs: bool,
+    /// What to do when the output already exists
+    pub on_conflict: ConflictStrategy,
+    /// Which other commands in this process to wait for before writing
+    pub lock: LockScope,
+    /// Skip commands whose output is newer than their inputs and was written by the same command
+    pub skip_if_newer: bool,
+    /// Trash keeping a copy of an existing output before it is overwritten, if any
+    pub trash: Option<Trash>,
+}
+
+impl OutputPolicy {
+    /// The trash an existing output is kept in before a command replaces it, which only happens
+    /// when the conflict strategy allows overwriting
+    pub(crate) fn overwrite_trash(&self) -> Option<&Trash> {
+        self.trash
+            .as_ref()
+            .filter(|_| self.on_conflict == ConflictStrategy::Overwrite)
+    }
+}
+
+/// The file a command wrote, after applying the conflict strategy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedOutput {
+    /// The path that was written, or the existing path when skipped
+    pub path: PathBuf,
+    /// Whether the command was skipped because the output already existed
+    pub skipped: bool,
+}
+
+impl ResolvedOutput {
+    /// An output written to the requested path
+    pub fn written(path: impl Into<PathBuf>) -> Self {
+        ResolvedOutput {
+            path: path.into(),
+            skipped: false,
+        }
+    }
+}
+
+/// Apply the conflict strategy to an output path
+///
+/// Returns the path to write to, or `None` when the command should be skipped. Paths containing
+/// `%` are frame patterns such as `out-%d.png` and are always written as-is.
+///
+/// # Arguments
+///
+/// * `output` - The requested output path
+/// * `workspace` - Optional workspace the output path is relative to
+/// * `strategy` - What to do when the output already exists
+pub(crate) fn resolve_conflict(
+    output: &Path,
+    workspace: Option<&Path>,
+    strategy: ConflictStrategy,
+) -> Option<PathBuf> {
+    let exists = |path: &Path| match workspace {
+        Some(workspace) => workspace.join(path).exists(),
+        None => path.exists(),
+    };
+    if output.to_string_lossy().contains('%') || !exists(output) {
+        return Some(output.to_path_buf());
+    }
+    match strategy {
+        ConflictStrategy::Overwrite => Some(output.to_path_buf()),
+        ConflictStrategy::Skip => None,
+        ConflictStrategy::Rename => {
+            let stem = output
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let extension = output
+                .extension()
+                .map(|s| format!(".{}", s.to_string_lossy()))
+                .unwrap_or_default();
+            (1..)
+                .map(|n| output.with_file_name(format!("{stem}-{n}{extension}")))
+                .find(|candidate| !exists(candidate))
+        }
+    }
+}
+
+/// Replace the path portion of an output argument, keeping any `FORMAT:` prefix
+pub(crate) fn replace_output_path(arg: &str, path: &Path) -> String {
+    let stripped = strip_format_prefix(arg);
+    let prefix = &arg[..arg.len() - stripped.len()];
+    format!("{prefix}{}", path.display())
+}
+
+/// Find the output path of a `magick` invocation
+///
+/// ImageMagick writes to the last argument. Options, bare pseudo-formats such as `info:`, and
+/// standard output (`-` or `png:-`) are not files, and any `FORMAT:` prefix is removed.
+pub(crate) fn output_path(args: &[&str]) -> Option<PathBuf> {
+    if args.len() < 2 || NON_WRITING_SUBCOMMANDS.contains(&args[0]) {
+        return None;
+    }
+    let last = *args.last()?;
+    if last.starts_with('-') || last.ends_with(':') {
+        return None;
+    }
+    let path = strip_format_prefix(last);
+    if path.is_empty() || stream_descriptor(last).is_some() {
+        return None;
+    }
+    Some(PathBuf::from(path))
+}
+
+/// Whether a command writes its image to standard output, rejecting streams it cannot use
+///
+/// Standard input is closed for commands the server runs, so reading it would only fail
+/// confusingly, and descriptors other than 0, 1, and 2 are not open.
+pub(crate) fn writes_stdout(args: &[&str]) -> Result<bool, ShellError> {
+    let all_inputs = args
+        .first()
+        .is_some_and(|arg| NON_WRITING_SUBCOMMANDS.contains(arg));
+    let mut stdout = false;
+    for (index, arg) in args.iter().enumerate() {
+        let Some(descriptor) = stream_descriptor(arg) else {
+            continue;
+        };
+        let is_output = !all_inputs && index > 0 && index == args.len() - 1;
+        let rejected = |message: &str| ShellError::PseudoFile {
+            arg: arg.to_string(),
+            message: message.to_string(),
+        };
+        match (descriptor, is_output) {
+            (0 | 1, true) => stdout = true,
+            (2, true) => {}
+            (0, false) => {
+                return Err(rejected(
+                    "standard input is not available; pass the image as input_base64 and use $input instead",
+                ));
+            }
+            _ => {
+                return Err(rejected(
+                    "only standard input, output, and error are available",
+                ));
+            }
+        }
+    }
+    Ok(stdout)
+}
+
+/// The file descriptor an argument names instead of a file, if any
+///
+/// ImageMagick reads and writes `-` as standard input or output, depending on position, and
+/// `fd:N` as descriptor `N`, either optionally after a `FORMAT:` prefix such as `png:-`. `-` is
+/// reported as descriptor 0 here; callers decide whether it is an output.
+pub(crate) fn stream_descriptor(arg: &str) -> Option<u32> {
+    let stripped = strip_format_prefix(arg);
+    if stripped == "-" {
+        return Some(0);
+    }
+    let descriptor = if stripped.len() < arg.len() && arg.starts_with("fd:") {
+        stripped
+    } else {
+        stripped.strip_prefix("fd:")?
+    };
+    descriptor.parse().ok()
+}
+
+/// The format of an output argument, from its `FORMAT:` prefix or else its file extension
+pub(crate) fn output_format(arg: &str) -> Option<String> {
+    let stripped = strip_format_prefix(arg);
+    if stripped.len() < arg.len() {
+        return Some(arg[..arg.len() - stripped.len() - 1].to_ascii_lowercase());
+    }
+    Path::new(stripped)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+}
+
+/// Find the output directory of a `magick` invocation if it does not exist
+///
+/// # Arguments
+///
+/// * `args` - The arguments passed to `magick`
+/// * `workspace` - Optional workspace the output path is relative to
+pub(crate) fn missing_output_dir(args: &[&str], workspace: Option<&Path>) -> Option<PathBuf> {
+    let output = output_path(args)?;
+    let parent = output.parent()?;
+    if parent.as_os_str().is_empty() {
+        return None;
+    }
+    let resolved = match workspace {
+        Some(workspace) => workspace.join(parent),
+        None => parent.to_path_buf(),
+    };
+    (!resolved.is_dir()).then(|| parent.to_path_buf())
+}
+
+/// Remove an explicit `FORMAT:` prefix such as `png:out.png`
+///
+/// Single letter prefixes are kept so Windows drive letters like `C:` are not mistaken for one.
+pub(crate) fn strip_format_prefix(arg: &str) -> &str {
+    match arg.split_once(':') {
+        Some((prefix, rest))
+            if prefix.len() > 1 && prefix.chars().all(|c| c.is_ascii_alphanumeric()) =>
+        {
+            rest
+        }
+        _ => arg,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_output_path() {
+        assert_eq!(
+            output_path(&["in.png", "-negate", "out/neg.png"]),
+            Some(PathBuf::from("out/neg.png"))
+        );
+        assert_eq!(
+            output_path(&["in.png", "png:out/neg.png"]),
+            Some(PathBuf::from("out/neg.png"))
+        );
+        assert_eq!(output_path(&["in.png", "-format", "%w", "info:"]), None);
+        assert_eq!(output_path(&["in.png", "png:-"]), None);
+        assert_eq!(output_path(&["in.png", "png:fd:1"]), None);
+        assert_eq!(output_path(&["in.png", "fd:1"]), None);
+        assert_eq!(output_path(&["identify", "photos/a.png"]), None);
+        assert_eq!(output_path(&["-version"]), None);
+        assert_eq!(output_path(&["-list", "format"]), None);
+    }
+
+    #[test]
+    fn test_stream_descriptor() {
+        assert_eq!(stream_descriptor("-"), Some(0));
+        assert_eq!(stream_descriptor("png:-"), Some(0));
+        assert_eq!(stream_descriptor("fd:3"), Some(3));
+        assert_eq!(stream_descriptor("png:fd:1"), Some(1));
+        assert_eq!(stream_descriptor("-negate"), None);
+        assert_eq!(stream_descriptor("fd.png"), None);
+        assert_eq!(stream_descriptor("png:out.png"), None);
+    }
+
+    #[test]
+    fn test_writes_stdout() {
+        assert!(writes_stdout(&["in.png", "png:-"]).unwrap());
+        assert!(writes_stdout(&["in.png", "fd:1"]).unwrap());
+        assert!(!writes_stdout(&["in.png", "out.png"]).unwrap());
+        assert!(!writes_stdout(&["in.png", "fd:2"]).unwrap());
+        assert!(writes_stdout(&["-", "out.png"]).is_err());
+        assert!(writes_stdout(&["identify", "-"]).is_err());
+        assert!(writes_stdout(&["in.png", "fd:5"]).is_err());
+    }
+
+    #[test]
+    fn test_resolve_conflict() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("out.png"), b"").unwrap();
+        std::fs::write(dir.path().join("out-1.png"), b"").unwrap();
+        let workspace = Some(dir.path());
+        let output = Path::new("out.png");
+
+        assert_eq!(
+            resolve_conflict(output, workspace, ConflictStrategy::Overwrite),
+            Some(PathBuf::from("out.png"))
+        );
+        assert_eq!(
+            resolve_conflict(output, workspace, ConflictStrategy::Skip),
+            None
+        );
+        assert_eq!(
+            resolve_conflict(output, workspace, ConflictStrategy::Rename),
+            Some(PathBuf::from("out-2.png"))
+        );
+        assert_eq!(
+            resolve_conflict(Path::new("new.png"), workspace, ConflictStrategy::Skip),
+            Some(PathBuf::from("new.png"))
+        );
+    }
+
+    #[test]
+    fn test_replace_output_path() {
+        assert_eq!(
+            replace_output_path("png:out.png", Path::new("out-1.png")),
+            "png:out-1.png"
+        );
+        assert_eq!(
+            replace_output_path("out.png", Path::new("out-1.png")),
+            "out-1.png"
+        );
+    }
+
+    #[test]
+    fn test_output_format() {
+        assert_eq!(output_format("WEBP:out.png").as_deref(), Some("webp"));
+        assert_eq!(output_format("out/photo.JPG").as_deref(), Some("jpg"));
+        assert_eq!(output_format("out/photo"), None);
+    }
+
+    #[test]
+    fn test_missing_output_dir() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("exists")).unwrap();
+
+        assert_eq!(
+            missing_output_dir(&["in.png", "out/a.png"], Some(dir.path())),
+            Some(PathBuf::from("out"))
+        );
+        assert_eq!(
+            missing_output_dir(&["in.png", "exists/a.png"], Some(dir.path())),
+            None
+        );
+        assert_eq!(
+            missing_output_dir(&["in.png", "a.png"], Some(dir.path())),
+            None
+        );
+    }
+}
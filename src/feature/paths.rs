@@ -0,0 +1,175 @@
+use crate::feature::output;
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use unicode_normalization::UnicodeNormalization;
+
+/// Shortest path which needs the `\\?\` prefix on Windows
+///
+/// Files are limited to 259 characters without it, but directories only to 247, and
+/// ImageMagick may create either.
+const LONG_PATH: usize = 248;
+
+/// Prefix which lifts the length limit on Windows paths and disables their normalization
+const VERBATIM_PREFIX: &str = r"\\?\";
+
+/// Extend a long absolute Windows path in an argument with the `\\?\` prefix
+///
+/// Windows rejects paths of [`LONG_PATH`] characters or more unless they are verbatim. Verbatim
+/// paths are not normalized by Windows, so separators are made backslashes and `.` and `..`
+/// components resolved here. Any `FORMAT:` prefix and other arguments are left untouched.
+pub(crate) fn extend_long_path(arg: &str) -> Cow<'_, str> {
+    let path = output::strip_format_prefix(arg);
+    if path.chars().count() < LONG_PATH || path.starts_with(VERBATIM_PREFIX) {
+        return Cow::Borrowed(arg);
+    }
+    let format = &arg[..arg.len() - path.len()];
+    let path = path.replace('/', r"\");
+    let extended = if let Some(share) = path.strip_prefix(r"\\") {
+        format!(r"{VERBATIM_PREFIX}UNC\{}", resolve_dots(share, 2))
+    } else if is_drive_absolute(&path) {
+        format!("{VERBATIM_PREFIX}{}", resolve_dots(&path, 1))
+    } else {
+        return Cow::Borrowed(arg);
+    };
+    Cow::Owned(format!("{format}{extended}"))
+}
+
+/// Remove the `\\?\` prefix from a drive or UNC path so it reads as the user wrote it
+pub(crate) fn strip_verbatim(path: &Path) -> PathBuf {
+    let text = path.to_string_lossy();
+    let Some(rest) = text.strip_prefix(VERBATIM_PREFIX) else {
+        return path.to_path_buf();
+    };
+    if let Some(share) = rest.strip_prefix(r"UNC\") {
+        return PathBuf::from(format!(r"\\{share}"));
+    }
+    if is_drive_absolute(rest) {
+        return PathBuf::from(rest);
+    }
+    path.to_path_buf()
+}
+
+/// Resolve a path to the absolute, symlink-free form reported to clients
+///
+/// Relative paths are taken from `base`. Windows canonical paths carry the `\\?\` prefix,
+/// which is removed so they read as the user would write them. Returns `None` for empty paths
+/// and paths which do not exist.
+pub(crate) fn canonical_path(path: &str, base: &Path) -> Option<PathBuf> {
+    if path.is_empty() {
+        return None;
+    }
+    let canonical = base.join(path).canonicalize().ok()?;
+    Some(strip_verbatim(&canonical))
+}
+
+/// Normalize text to Unicode NFC, the composed form most systems write
+///
+/// macOS stores file names decomposed, so a name typed as `café` can be spelled differently on
+/// disk. Comparing normalized forms matches them regardless.
+pub(crate) fn normalize_name(name: &str) -> Cow<'_, str> {
+    if unicode_normalization::is_nfc(name) {
+        Cow::Borrowed(name)
+    } else {
+        Cow::Owned(name.nfc().collect())
+    }
+}
+
+/// Whether a path starts with a drive letter and a separator, e.g. `C:\`
+fn is_drive_absolute(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && bytes[2] == b'\\'
+}
+
+/// Resolve `.` and `..` components of a backslash separated path, never removing the first
+/// `root` components
+fn resolve_dots(path: &str, root: usize) -> String {
+    let mut components: Vec<&str> = Vec::new();
+    for (index, component) in path.split('\\').enumerate() {
+        match component {
+            _ if index < root => components.push(component),
+            "" | "." => {}
+            ".." => {
+                if components.len() > root {
+                    components.pop();
+                }
+            }
+            _ => components.push(component),
+        }
+    }
+    components.join(r"\")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn long_dir() -> String {
+        "nested".repeat(50)
+    }
+
+    #[test]
+    fn test_extend_long_path() {
+        let dir = long_dir();
+        assert_eq!(
+            extend_long_path(&format!(r"C:\{dir}\a\..\фото.png")),
+            format!(r"\\?\C:\{dir}\фото.png")
+        );
+        assert_eq!(
+            extend_long_path(&format!("png:C:/{dir}/./out.png")),
+            format!(r"png:\\?\C:\{dir}\out.png")
+        );
+        assert_eq!(
+            extend_long_path(&format!(r"\\server\share\{dir}\in.png[0]")),
+            format!(r"\\?\UNC\server\share\{dir}\in.png[0]")
+        );
+        assert_eq!(extend_long_path(r"C:\photos\in.png"), r"C:\photos\in.png");
+        let verbatim = format!(r"\\?\C:\{dir}\in.png");
+        assert_eq!(extend_long_path(&verbatim), verbatim);
+        let relative = format!(r"{dir}\in.png");
+        assert_eq!(extend_long_path(&relative), relative);
+        let caption = format!("caption:{}", "long text ".repeat(30));
+        assert_eq!(extend_long_path(&caption), caption);
+    }
+
+    #[test]
+    fn test_strip_verbatim() {
+        assert_eq!(
+            strip_verbatim(Path::new(r"\\?\C:\photos\out.png")),
+            PathBuf::from(r"C:\photos\out.png")
+        );
+        assert_eq!(
+            strip_verbatim(Path::new(r"\\?\UNC\server\share\out.png")),
+            PathBuf::from(r"\\server\share\out.png")
+        );
+        assert_eq!(
+            strip_verbatim(Path::new("out/фото.png")),
+            PathBuf::from("out/фото.png")
+        );
+    }
+
+    #[test]
+    fn test_canonical_path() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("out")).unwrap();
+        std::fs::write(dir.path().join("out/a.png"), b"").unwrap();
+        let expected = dir.path().canonicalize().unwrap().join("out/a.png");
+
+        assert_eq!(
+            canonical_path("out/../out/a.png", dir.path()),
+            Some(expected.clone())
+        );
+        assert_eq!(
+            canonical_path(&expected.to_string_lossy(), Path::new("/elsewhere")),
+            Some(expected)
+        );
+        assert_eq!(canonical_path("missing.png", dir.path()), None);
+        assert_eq!(canonical_path("", dir.path()), None);
+    }
+
+    #[test]
+    fn test_normalize_name() {
+        let decomposed = "cafe\u{301}.jpg";
+        assert_eq!(normalize_name(decomposed), "caf\u{e9}.jpg");
+        assert!(matches!(normalize_name("café.jpg"), Cow::Borrowed(_)));
+    }
+}
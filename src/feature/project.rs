@@ -0,0 +1,255 @@
+use crate::feature::config::ConfigError;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Name of the project config file looked up at the workspace root
+pub const PROJECT_CONFIG_FILE: &str = ".magickmcp.toml";
+
+/// Per-project conventions read from `.magickmcp.toml` at the workspace root
+///
+/// Paths are relative to the workspace.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ProjectConfig {
+    /// Directory outputs given as a bare file name are written to, e.g. `generated`
+    pub output_dir: Option<String>,
+    /// Output formats commands may write, e.g. `["png", "webp"]`. Empty allows any format
+    pub allowed_formats: Vec<String>,
+    /// Named quality levels usable in place of a number, e.g. `-quality web`
+    pub quality_presets: BTreeMap<String, u32>,
+    /// Directories outputs must stay inside. Empty allows anywhere
+    pub sandbox_roots: Vec<String>,
+    /// Directory of functions scoped to this project
+    pub functions_dir: Option<String>,
+}
+
+impl ProjectConfig {
+    /// Look for `.magickmcp.toml` at the root of a workspace
+    ///
+    /// # Arguments
+    ///
+    /// * `workspace` - The workspace root
+    ///
+    /// # Returns
+    ///
+    /// Returns the parsed config, `None` if the workspace has none, or a `ConfigError` if it cannot
+    /// be read or parsed
+    pub fn discover(workspace: &Path) -> Result<Option<Self>, ConfigError> {
+        let path = workspace.join(PROJECT_CONFIG_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(&path).map_err(|source| ConfigError::IoError {
+            path: path.display().to_string(),
+            source,
+        })?;
+        toml::from_str(&contents)
+            .map(Some)
+            .map_err(|source| ConfigError::ParseError {
+                path: path.display().to_string(),
+                source,
+            })
+    }
+
+    /// Replace quality preset names following `-quality` with their numeric value
+    pub(crate) fn expand_quality_presets(&self, args: &[&str]) -> Result<Vec<String>, String> {
+        let mut expanded: Vec<String> = Vec::with_capacity(args.len());
+        for arg in args {
+            let follows_quality = expanded.last().is_some_and(|prev| prev == "-quality");
+            if follows_quality && arg.parse::<f64>().is_err() {
+                let quality = self.quality_presets.get(*arg).ok_or_else(|| {
+                    format!(
+                        "unknown quality preset '{arg}', expected a number or one of: {}",
+                        self.quality_presets
+                            .keys()
+                            .cloned()
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                })?;
+                expanded.push(quality.to_string());
+            } else {
+                expanded.push(arg.to_string());
+            }
+        }
+        Ok(expanded)
+    }
+
+    /// Move an output given as a bare file name into the project output directory
+    pub(crate) fn place_output(&self, output: &Path) -> Option<PathBuf> {
+        let output_dir = self.output_dir.as_deref()?;
+        let bare = output.components().count() == 1
+            && matches!(output.components().next(), Some(Component::Normal(_)));
+        bare.then(|| Path::new(output_dir).join(output))
+    }
+
+    /// Check an output against the allowed formats and sandbox roots
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - The output path, relative to the workspace or absolute
+    /// * `format` - The output format from an explicit prefix or the file extension
+    /// * `workspace` - The workspace root
+    pub(crate) fn check_output(
+        &self,
+        output: &Path,
+        format: Option<&str>,
+        workspace: &Path,
+    ) -> Result<(), String> {
+        if !self.allowed_formats.is_empty() {
+            let allowed = format.is_some_and(|format| {
+                self.allowed_formats
+                    .iter()
+                    .any(|allowed| allowed.eq_ignore_ascii_case(format))
+            });
+            if !allowed {
+                return Err(format!(
+                    "output '{}' is not one of the allowed formats: {}",
+                    output.display(),
+                    self.allowed_formats.join(", ")
+                ));
+            }
+        }
+        if !self.sandbox_roots.is_empty() {
+            let resolved = normalize(&workspace.join(output));
+            let inside = self
+                .sandbox_roots
+                .iter()
+                .any(|root| resolved.starts_with(normalize(&workspace.join(root))));
+            if !inside {
+                return Err(format!(
+                    "output '{}' is outside the sandbox roots: {}",
+                    output.display(),
+                    self.sandbox_roots.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check a file written other than as a command's output, such as a moved file or a
+    /// `-write` target, with its format taken from the extension
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The written path, relative to the workspace or absolute
+    /// * `workspace` - The workspace root
+    pub(crate) fn check_write(&self, path: &Path, workspace: &Path) -> Result<(), String> {
+        let format = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+        self.check_output(path, format.as_deref(), workspace)
+    }
+}
+
+/// Resolve `.` and `..` components without touching the filesystem, since outputs may not exist
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn project() -> ProjectConfig {
+        toml::from_str(
+            r#"
+output_dir = "generated"
+allowed_formats = ["png", "webp"]
+sandbox_roots = ["generated", "assets"]
+
+[quality_presets]
+web = 80
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_discover() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(ProjectConfig::discover(dir.path()).unwrap(), None);
+
+        fs::write(
+            dir.path().join(PROJECT_CONFIG_FILE),
+            "output_dir = \"out\"\n",
+        )
+        .unwrap();
+        let project = ProjectConfig::discover(dir.path()).unwrap().unwrap();
+        assert_eq!(project.output_dir.as_deref(), Some("out"));
+
+        fs::write(dir.path().join(PROJECT_CONFIG_FILE), "output_dir = 3\n").unwrap();
+        assert!(ProjectConfig::discover(dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_expand_quality_presets() {
+        let project = project();
+        assert_eq!(
+            project
+                .expand_quality_presets(&["in.png", "-quality", "web", "out.webp"])
+                .unwrap(),
+            vec!["in.png", "-quality", "80", "out.webp"]
+        );
+        assert_eq!(
+            project
+                .expand_quality_presets(&["in.png", "-quality", "92", "out.webp"])
+                .unwrap()[2],
+            "92"
+        );
+        assert!(
+            project
+                .expand_quality_presets(&["in.png", "-quality", "print", "out.webp"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_place_output() {
+        let project = project();
+        assert_eq!(
+            project.place_output(Path::new("out.png")),
+            Some(PathBuf::from("generated/out.png"))
+        );
+        assert_eq!(project.place_output(Path::new("assets/out.png")), None);
+    }
+
+    #[test]
+    fn test_check_output() {
+        let project = project();
+        let workspace = Path::new("/work");
+        assert!(
+            project
+                .check_output(Path::new("assets/a.png"), Some("png"), workspace)
+                .is_ok()
+        );
+        assert!(
+            project
+                .check_output(Path::new("assets/a.jpg"), Some("jpg"), workspace)
+                .is_err()
+        );
+        assert!(
+            project
+                .check_output(Path::new("assets/../../a.png"), Some("png"), workspace)
+                .is_err()
+        );
+        assert!(
+            project
+                .check_output(Path::new("src/a.png"), Some("PNG"), workspace)
+                .is_err()
+        );
+    }
+}
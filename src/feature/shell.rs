@@ -1,4 +1,11 @@
-use std::process::Command;
+use crate::feature::config::Config;
+use crate::feature::paths;
+use crate::feature::template::TemplateError;
+use crate::feature::trace::{TRACE_ID_ENV, current_trace_id};
+use crate::feature::wsl;
+use std::io::Read;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::OnceLock;
 use thiserror::Error;
 
 /// Error type for shell command execution failures
@@ -24,6 +31,37 @@ pub enum ShellError {
     },
     #[error("Missing required input variable: command contains $input but no input was provided")]
     MissingInputVariable,
+    #[error(
+        "Output directory '{directory}' does not exist. Create it first or pass create_dirs to create it automatically"
+    )]
+    MissingOutputDirectory { directory: String },
+    #[error("Rejected by the project's .magickmcp.toml: {message}")]
+    ProjectConfig { message: String },
+    #[error("Unsupported pseudo-file '{arg}': {message}")]
+    PseudoFile { arg: String, message: String },
+    #[error("Command wrote more than {limit} bytes to standard output\nCommand: {command} {args}")]
+    OutputTooLarge {
+        limit: usize,
+        command: String,
+        args: String,
+    },
+    #[error("Refused because the estimated cost is too high: {message}. Downscale the input first")]
+    CostLimitExceeded { message: String },
+    #[error("Failed to save command output to '{path}': {message}")]
+    SaveOutputFailed { path: String, message: String },
+    #[error(transparent)]
+    Template(#[from] TemplateError),
+    #[error("Invalid value for parameter '{name}': {message}")]
+    InvalidParameterValue { name: String, message: String },
+    #[error("Transaction failed: {message}")]
+    TransactionFailed { message: String },
+}
+
+/// Both output streams of a command which succeeded
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandOutput {
+    pub stdout: String,
+    pub stderr: String,
 }
 
 /// Trait for executing shell commands in a mockable way
@@ -41,6 +79,88 @@ pub trait CommandRunner {
         args: &[&str],
         working_dir: Option<&std::path::Path>,
     ) -> Result<String, ShellError>;
+
+    /// Execute a command and return both its standard output and standard error
+    ///
+    /// Standard error is otherwise only reported when a command fails. Runners which cannot
+    /// capture it report it as empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command to execute
+    /// * `args` - Arguments to pass to the command
+    /// * `working_dir` - Optional working directory to set for the command
+    fn execute_with_stderr(
+        &self,
+        command: &str,
+        args: &[&str],
+        working_dir: Option<&std::path::Path>,
+    ) -> Result<CommandOutput, ShellError> {
+        let stdout = self.execute(command, args, working_dir)?;
+        Ok(CommandOutput {
+            stdout,
+            stderr: String::new(),
+        })
+    }
+
+    /// Execute a command and return its raw standard output, failing once it exceeds a limit
+    ///
+    /// Used for commands which write an image to standard output, which may not be UTF-8 and
+    /// may be arbitrarily large.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command to execute
+    /// * `args` - Arguments to pass to the command
+    /// * `working_dir` - Optional working directory to set for the command
+    /// * `max_output` - Largest standard output to accept, in bytes
+    fn execute_capped(
+        &self,
+        command: &str,
+        args: &[&str],
+        working_dir: Option<&std::path::Path>,
+        max_output: usize,
+    ) -> Result<Vec<u8>, ShellError> {
+        let output = self.execute(command, args, working_dir)?;
+        if output.len() > max_output {
+            return Err(ShellError::OutputTooLarge {
+                limit: max_output,
+                command: command.to_string(),
+                args: args.join(" "),
+            });
+        }
+        Ok(output.into_bytes())
+    }
+}
+
+impl<R: CommandRunner + ?Sized> CommandRunner for Box<R> {
+    fn execute(
+        &self,
+        command: &str,
+        args: &[&str],
+        working_dir: Option<&std::path::Path>,
+    ) -> Result<String, ShellError> {
+        (**self).execute(command, args, working_dir)
+    }
+
+    fn execute_with_stderr(
+        &self,
+        command: &str,
+        args: &[&str],
+        working_dir: Option<&std::path::Path>,
+    ) -> Result<CommandOutput, ShellError> {
+        (**self).execute_with_stderr(command, args, working_dir)
+    }
+
+    fn execute_capped(
+        &self,
+        command: &str,
+        args: &[&str],
+        working_dir: Option<&std::path::Path>,
+        max_output: usize,
+    ) -> Result<Vec<u8>, ShellError> {
+        (**self).execute_capped(command, args, working_dir, max_output)
+    }
 }
 
 /// Default implementation of CommandRunner using std::process::Command
@@ -53,15 +173,17 @@ impl CommandRunner for DefaultCommandRunner {
         args: &[&str],
         working_dir: Option<&std::path::Path>,
     ) -> Result<String, ShellError> {
-        let path = std::env::var("PATH").ok();
-        let mut cmd = Command::new(command);
-        cmd.args(args).env_clear();
-        if let Some(ref path_val) = path {
-            cmd.env("PATH", path_val);
-        }
-        if let Some(dir) = working_dir {
-            cmd.current_dir(dir);
-        }
+        self.execute_with_stderr(command, args, working_dir)
+            .map(|output| output.stdout)
+    }
+
+    fn execute_with_stderr(
+        &self,
+        command: &str,
+        args: &[&str],
+        working_dir: Option<&std::path::Path>,
+    ) -> Result<CommandOutput, ShellError> {
+        let mut cmd = build_command(command, args, working_dir);
         let args_str = args.join(" ");
         let output = cmd.output().map_err(|e| ShellError::ExecutionFailed {
             message: e.to_string(),
@@ -70,7 +192,7 @@ impl CommandRunner for DefaultCommandRunner {
         })?;
 
         if !output.status.success() {
-            let exit_code = output.status.code().unwrap_or(-1);
+            let exit_code = exit_code(output.status);
             let stdout = String::from_utf8_lossy(&output.stdout).to_string();
             let stderr = String::from_utf8_lossy(&output.stderr).to_string();
             return Err(ShellError::NonZeroExit {
@@ -82,9 +204,170 @@ impl CommandRunner for DefaultCommandRunner {
             });
         }
 
-        String::from_utf8(output.stdout).map_err(|_| ShellError::InvalidUtf8 {
+        let stdout = String::from_utf8(output.stdout).map_err(|_| ShellError::InvalidUtf8 {
             command: command.to_string(),
             args: args_str,
+        })?;
+        Ok(CommandOutput {
+            stdout,
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
         })
     }
+
+    fn execute_capped(
+        &self,
+        command: &str,
+        args: &[&str],
+        working_dir: Option<&std::path::Path>,
+        max_output: usize,
+    ) -> Result<Vec<u8>, ShellError> {
+        let args_str = args.join(" ");
+        let failed = |message: String| ShellError::ExecutionFailed {
+            message,
+            command: command.to_string(),
+            args: args_str.clone(),
+        };
+        let mut child = build_command(command, args, working_dir)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| failed(e.to_string()))?;
+
+        // Drain stderr on its own thread so a chatty command cannot block on a full pipe
+        let mut stderr_pipe = child.stderr.take();
+        let stderr_reader = std::thread::spawn(move || {
+            let mut stderr = Vec::new();
+            if let Some(pipe) = stderr_pipe.as_mut() {
+                let _ = pipe.read_to_end(&mut stderr);
+            }
+            stderr
+        });
+        let mut stdout = Vec::new();
+        if let Some(pipe) = child.stdout.take() {
+            pipe.take(max_output as u64 + 1)
+                .read_to_end(&mut stdout)
+                .map_err(|e| failed(e.to_string()))?;
+        }
+        if stdout.len() > max_output {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(ShellError::OutputTooLarge {
+                limit: max_output,
+                command: command.to_string(),
+                args: args_str,
+            });
+        }
+        let status = child.wait().map_err(|e| failed(e.to_string()))?;
+        let stderr = stderr_reader.join().unwrap_or_default();
+
+        if !status.success() {
+            return Err(ShellError::NonZeroExit {
+                exit_code: exit_code(status),
+                command: command.to_string(),
+                args: args_str,
+                stdout: String::from_utf8_lossy(&stdout).to_string(),
+                stderr: String::from_utf8_lossy(&stderr).to_string(),
+            });
+        }
+        Ok(stdout)
+    }
+}
+
+/// The exit code of a finished command
+///
+/// On Unix a command killed by a signal has no exit code, so this reports 128 plus the signal
+/// number as shells do. Otherwise a missing code is reported as -1.
+fn exit_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+        return 128 + signal;
+    }
+    status.code().unwrap_or(-1)
+}
+
+/// Variables passed to a configured `magick` command, which environment managers such as Nix
+/// and asdf need to find their installs
+const WRAPPER_ENV: &[&str] = &["HOME", "USER", "LOGNAME"];
+
+/// Prefixes of further variables passed to a configured `magick` command
+const WRAPPER_ENV_PREFIXES: &[&str] = &["NIX_", "ASDF_", "DEVENV_", "XDG_"];
+
+/// The program and leading arguments run in place of `magick`, read once from the config file
+fn magick_command() -> Option<&'static [String]> {
+    static COMMAND: OnceLock<Option<Vec<String>>> = OnceLock::new();
+    COMMAND
+        .get_or_init(|| Config::load().ok()?.magick.command_args())
+        .as_deref()
+}
+
+/// The program to run and the arguments to put before the command's own
+///
+/// Only `magick` is replaced by the configured command; other commands run as they are.
+fn resolve_program<'a>(command: &'a str, wrapper: Option<&'a [String]>) -> (&'a str, &'a [String]) {
+    match wrapper.filter(|_| command == "magick") {
+        Some([program, leading @ ..]) => (program, leading),
+        _ => (command, &[]),
+    }
+}
+
+/// Build a command with a minimal environment and no standard input
+///
+/// Standard input is closed so a command reading it sees end of file immediately rather than
+/// waiting on the server's own input stream. On Windows, long absolute paths in the arguments
+/// are given the `\\?\` prefix so they are not rejected for exceeding `MAX_PATH`.
+///
+/// When the config sets `[magick] command`, such as `nix shell nixpkgs#imagemagick -c magick`,
+/// `magick` is run through it along with the variables the wrapping tool needs.
+fn build_command(command: &str, args: &[&str], working_dir: Option<&std::path::Path>) -> Command {
+    let (program, leading) = resolve_program(command, magick_command());
+    let wrapped = program != command || !leading.is_empty();
+    let mut cmd = Command::new(program);
+    cmd.args(leading);
+    if cfg!(windows) {
+        cmd.args(
+            args.iter()
+                .map(|arg| paths::extend_long_path(arg).into_owned()),
+        );
+    } else {
+        cmd.args(args);
+    }
+    cmd.env_clear().stdin(Stdio::null());
+    if let Ok(path) = std::env::var("PATH") {
+        cmd.env("PATH", path);
+    }
+    if wrapped {
+        cmd.envs(std::env::vars().filter(|(name, _)| {
+            WRAPPER_ENV.contains(&name.as_str())
+                || WRAPPER_ENV_PREFIXES
+                    .iter()
+                    .any(|prefix| name.starts_with(prefix))
+        }));
+    }
+    if let Some(trace_id) = current_trace_id() {
+        cmd.env(TRACE_ID_ENV, trace_id);
+    }
+    if let Some(dir) = working_dir {
+        cmd.current_dir(wsl::translate_path(&dir.to_string_lossy()).as_ref());
+    }
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_program_applies_configured_command() {
+        let wrapper: Vec<String> = ["nix", "shell", "nixpkgs#imagemagick", "-c", "magick"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+
+        let (program, leading) = resolve_program("magick", Some(&wrapper));
+        assert_eq!(program, "nix");
+        assert_eq!(leading, &wrapper[1..]);
+
+        assert_eq!(resolve_program("which", Some(&wrapper)), ("which", &[][..]));
+        assert_eq!(resolve_program("magick", None), ("magick", &[][..]));
+    }
 }
@@ -0,0 +1,117 @@
+use crate::feature::cleanup::{self, TEMP_PREFIX};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Name of the file inside its private directory, before the extension
+const FILE_STEM: &str = "image";
+
+/// A file in a private, randomly named directory under the temporary directory, removed with the
+/// directory when dropped
+///
+/// The directory is created exclusively and only the current user can enter it, so nothing can
+/// plant a file or symlink at the path for ImageMagick or `write` to write through. The file
+/// itself is not created until something writes it, so output conflict handling sees a free
+/// path. Both paths are registered with the cleanup guard, so they are also removed if the
+/// process panics or is interrupted while they exist.
+pub(crate) struct TempFile {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl TempFile {
+    /// Reserve a temporary file path with the given extension, creating its directory but not
+    /// the file
+    ///
+    /// # Arguments
+    ///
+    /// * `extension` - File extension telling ImageMagick the format, e.g. `png`
+    pub(crate) fn new(extension: &str) -> io::Result<Self> {
+        if extension.is_empty() || !extension.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid format '{extension}', expected an extension such as png"),
+            ));
+        }
+        let mut builder = tempfile::Builder::new();
+        builder.prefix(TEMP_PREFIX);
+        #[cfg(unix)]
+        builder.permissions(std::os::unix::fs::PermissionsExt::from_mode(0o700));
+        let dir = builder.tempdir()?.keep();
+        let path = dir.join(format!("{FILE_STEM}.{}", extension.to_ascii_lowercase()));
+        // The file is removed before its directory
+        cleanup::register(&path);
+        cleanup::register(&dir);
+        Ok(TempFile { dir, path })
+    }
+
+    /// Write bytes to a new temporary file
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The file contents
+    /// * `extension` - File extension telling ImageMagick the format, e.g. `png`
+    pub(crate) fn write(bytes: &[u8], extension: &str) -> io::Result<Self> {
+        let file = Self::new(extension)?;
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&file.path)?
+            .write_all(bytes)?;
+        Ok(file)
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+        let _ = fs::remove_dir(&self.dir);
+        cleanup::unregister(&self.path);
+        cleanup::unregister(&self.dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temp_file_is_removed_on_drop() {
+        let file = TempFile::write(b"png", "PNG").unwrap();
+        let path = file.path().to_path_buf();
+        assert_eq!(path.extension().unwrap(), "png");
+        assert_eq!(fs::read(&path).unwrap(), b"png");
+
+        drop(file);
+        assert!(!path.exists());
+        assert!(!path.parent().unwrap().exists());
+        assert!(TempFile::write(b"png", "../png").is_err());
+    }
+
+    #[test]
+    fn test_temp_file_paths_are_private_and_distinct() {
+        let first = TempFile::new("png").unwrap();
+        let second = TempFile::new("png").unwrap();
+        assert_ne!(first.path(), second.path());
+        assert!(!first.path().exists());
+
+        let dir = first.path().parent().unwrap();
+        assert!(dir.is_dir());
+        assert!(
+            dir.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with(TEMP_PREFIX)
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(dir).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+    }
+}
@@ -0,0 +1,377 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Prefix of placeholders resolved from environment variables, e.g. `${env.HOME}`
+const ENV_PREFIX: &str = "env.";
+
+/// Error type for parsing and rendering command templates
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TemplateError {
+    #[error("Unterminated {quote} quote in command template: {template}")]
+    UnterminatedQuote { quote: char, template: String },
+    #[error("Unterminated placeholder '${{{name}' in command template: {template}")]
+    UnterminatedPlaceholder { name: String, template: String },
+    #[error("Invalid placeholder name '{name}', use letters, digits, '_' and '.'")]
+    InvalidName { name: String },
+    #[error("No value for placeholder ${name}")]
+    Unbound { name: String },
+    #[error(
+        "Environment variable '{name}' is not listed in the env of the [functions] config section"
+    )]
+    EnvNotAllowed { name: String },
+}
+
+/// A piece of a template argument
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Placeholder(String),
+}
+
+/// A command template split into arguments containing `$name` placeholders
+///
+/// Arguments are separated by whitespace, and text in single or double quotes stays in one
+/// argument with the quotes removed. Placeholders are written `$name` or `${name}`, where the
+/// braced form may contain dots such as `${env.HOME}`, and `$$` is a literal `$`. Placeholders
+/// inside single quotes are not substituted, and a `$` not followed by a name is kept as is.
+///
+/// Rendering inserts each value into the argument containing its placeholder, so values with
+/// spaces, quotes, or `$` never split an argument or expand further.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Template {
+    args: Vec<Vec<Segment>>,
+}
+
+impl Template {
+    /// Parse a command template
+    ///
+    /// # Returns
+    ///
+    /// Returns the parsed template, or a `TemplateError` for unterminated quotes or placeholders
+    pub(crate) fn parse(template: &str) -> Result<Self, TemplateError> {
+        let mut parser = Parser {
+            template,
+            args: Vec::new(),
+            current: Vec::new(),
+            literal: String::new(),
+            started: false,
+        };
+        let mut chars = template.chars().peekable();
+        let mut quote: Option<char> = None;
+        while let Some(c) = chars.next() {
+            match (quote, c) {
+                (None, c) if c.is_whitespace() => parser.end_arg(),
+                (None, '"' | '\'') => {
+                    quote = Some(c);
+                    parser.started = true;
+                }
+                (Some(open), c) if c == open => quote = None,
+                (Some('\''), c) => parser.literal.push(c),
+                (_, '$') => parser.dollar(&mut chars)?,
+                (_, c) => {
+                    parser.literal.push(c);
+                    parser.started = true;
+                }
+            }
+        }
+        if let Some(quote) = quote {
+            return Err(TemplateError::UnterminatedQuote {
+                quote,
+                template: template.to_string(),
+            });
+        }
+        parser.end_arg();
+        Ok(Template { args: parser.args })
+    }
+
+    /// The names of the placeholders in the template, in order of appearance
+    pub(crate) fn placeholders(&self) -> impl Iterator<Item = &str> {
+        self.args
+            .iter()
+            .flatten()
+            .filter_map(|segment| match segment {
+                Segment::Placeholder(name) => Some(name.as_str()),
+                Segment::Literal(_) => None,
+            })
+    }
+
+    /// Whether the template contains a placeholder with the given name
+    pub(crate) fn uses(&self, name: &str) -> bool {
+        self.placeholders().any(|placeholder| placeholder == name)
+    }
+
+    /// Substitute every placeholder, producing the command arguments
+    ///
+    /// # Returns
+    ///
+    /// Returns the arguments, or `TemplateError::Unbound` naming the first placeholder without
+    /// a value, or `TemplateError::EnvNotAllowed` naming an environment variable the bindings
+    /// may not read
+    pub(crate) fn render(&self, bindings: &Bindings) -> Result<Vec<String>, TemplateError> {
+        self.render_with(
+            |text| text.to_string(),
+            |name| bindings.get(name).map(Cow::into_owned),
+        )
+    }
+
+    /// Produce the arguments by mapping literal text and placeholders separately
+    ///
+    /// This lets callers escape literal text for another syntax, such as a Makefile, while
+    /// replacing placeholders with references in that syntax.
+    ///
+    /// # Arguments
+    ///
+    /// * `literal` - Maps literal text to the text to emit
+    /// * `placeholder` - Maps a placeholder name to the text to emit in its place
+    pub(crate) fn render_with(
+        &self,
+        literal: impl Fn(&str) -> String,
+        placeholder: impl Fn(&str) -> Result<String, TemplateError>,
+    ) -> Result<Vec<String>, TemplateError> {
+        self.args
+            .iter()
+            .map(|segments| {
+                let mut arg = String::new();
+                for segment in segments {
+                    match segment {
+                        Segment::Literal(text) => arg.push_str(&literal(text)),
+                        Segment::Placeholder(name) => arg.push_str(&placeholder(name)?),
+                    }
+                }
+                Ok(arg)
+            })
+            .collect()
+    }
+}
+
+/// Values substituted for placeholders when rendering a template
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Bindings {
+    values: HashMap<String, String>,
+    env: Vec<String>,
+}
+
+impl Bindings {
+    /// Create empty bindings, which resolve no placeholders
+    pub(crate) fn new() -> Self {
+        Bindings::default()
+    }
+
+    /// Bind a placeholder name to a value
+    pub(crate) fn with(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    /// Bind a placeholder name to a value, replacing any previous value
+    pub(crate) fn set(&mut self, name: &str, value: impl Into<String>) {
+        self.values.insert(name.to_string(), value.into());
+    }
+
+    /// Also resolve `${env.NAME}` placeholders from the listed environment variables
+    pub(crate) fn with_env(mut self, allowed: &[String]) -> Self {
+        self.env = allowed.to_vec();
+        self
+    }
+
+    /// The value of a placeholder, or why it has none
+    fn get(&self, name: &str) -> Result<Cow<'_, str>, TemplateError> {
+        if let Some(value) = self.values.get(name) {
+            return Ok(Cow::Borrowed(value));
+        }
+        let unbound = || TemplateError::Unbound {
+            name: name.to_string(),
+        };
+        let variable = name.strip_prefix(ENV_PREFIX).ok_or_else(unbound)?;
+        if !self.env.iter().any(|allowed| allowed == variable) {
+            return Err(TemplateError::EnvNotAllowed {
+                name: variable.to_string(),
+            });
+        }
+        std::env::var(variable)
+            .map(Cow::Owned)
+            .map_err(|_| unbound())
+    }
+}
+
+/// State while splitting a template into arguments
+struct Parser<'t> {
+    template: &'t str,
+    args: Vec<Vec<Segment>>,
+    current: Vec<Segment>,
+    literal: String,
+    /// Whether the current argument has begun, so an empty quoted argument is kept
+    started: bool,
+}
+
+impl Parser<'_> {
+    fn end_literal(&mut self) {
+        if !self.literal.is_empty() {
+            self.current
+                .push(Segment::Literal(std::mem::take(&mut self.literal)));
+        }
+    }
+
+    fn end_arg(&mut self) {
+        self.end_literal();
+        if self.started {
+            if self.current.is_empty() {
+                self.current.push(Segment::Literal(String::new()));
+            }
+            self.args.push(std::mem::take(&mut self.current));
+        }
+        self.started = false;
+    }
+
+    /// Handle the text following a `$`
+    fn dollar(
+        &mut self,
+        chars: &mut std::iter::Peekable<std::str::Chars<'_>>,
+    ) -> Result<(), TemplateError> {
+        self.started = true;
+        match chars.peek() {
+            Some('$') => {
+                chars.next();
+                self.literal.push('$');
+            }
+            Some('{') => {
+                chars.next();
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => {
+                            return Err(TemplateError::UnterminatedPlaceholder {
+                                name,
+                                template: self.template.to_string(),
+                            });
+                        }
+                    }
+                }
+                if name.is_empty() || !name.chars().all(|c| is_name_char(c) || c == '.') {
+                    return Err(TemplateError::InvalidName { name });
+                }
+                self.end_literal();
+                self.current.push(Segment::Placeholder(name));
+            }
+            Some(c) if is_name_start(*c) => {
+                let mut name = String::new();
+                while let Some(c) = chars.peek().copied().filter(|c| is_name_char(*c)) {
+                    name.push(c);
+                    chars.next();
+                }
+                self.end_literal();
+                self.current.push(Segment::Placeholder(name));
+            }
+            _ => self.literal.push('$'),
+        }
+        Ok(())
+    }
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str, bindings: &Bindings) -> Vec<String> {
+        Template::parse(template).unwrap().render(bindings).unwrap()
+    }
+
+    #[test]
+    fn test_values_stay_single_arguments() {
+        let bindings = Bindings::new()
+            .with("input", "My Photos/a $b.png")
+            .with("output", "out dir/a.png");
+
+        assert_eq!(
+            render("$input -resize 50% ${output}", &bindings),
+            vec!["My Photos/a $b.png", "-resize", "50%", "out dir/a.png"]
+        );
+        assert_eq!(
+            render("$input.jpg[0] prefix-${input}", &bindings),
+            vec!["My Photos/a $b.png.jpg[0]", "prefix-My Photos/a $b.png"]
+        );
+    }
+
+    #[test]
+    fn test_escaping_and_quotes() {
+        let bindings = Bindings::new().with("name", "x");
+
+        assert_eq!(
+            render(
+                "-annotate +0+0 \"Price: $$5 for $name\" 'literal $name' \"\" a$ $1",
+                &bindings
+            ),
+            vec![
+                "-annotate",
+                "+0+0",
+                "Price: $5 for x",
+                "literal $name",
+                "",
+                "a$",
+                "$1"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_placeholders_and_errors() {
+        let template = Template::parse("$input -negate ${step1.output}").unwrap();
+        assert_eq!(
+            template.placeholders().collect::<Vec<_>>(),
+            vec!["input", "step1.output"]
+        );
+        assert!(template.uses("input"));
+        assert!(!template.uses("output"));
+        assert_eq!(
+            template.render(&Bindings::new().with("input", "a.png")),
+            Err(TemplateError::Unbound {
+                name: "step1.output".to_string()
+            })
+        );
+
+        assert!(matches!(
+            Template::parse("'unterminated"),
+            Err(TemplateError::UnterminatedQuote { quote: '\'', .. })
+        ));
+        assert!(matches!(
+            Template::parse("${input"),
+            Err(TemplateError::UnterminatedPlaceholder { .. })
+        ));
+        assert!(matches!(
+            Template::parse("${in put}"),
+            Err(TemplateError::InvalidName { .. })
+        ));
+    }
+
+    #[test]
+    fn test_env_bindings() {
+        let path = std::env::var("PATH").unwrap();
+        let template = Template::parse("${env.PATH}").unwrap();
+
+        assert_eq!(
+            template
+                .render(&Bindings::new().with_env(&["PATH".to_string()]))
+                .unwrap(),
+            vec![path]
+        );
+        assert!(matches!(
+            template.render(&Bindings::new()),
+            Err(TemplateError::EnvNotAllowed { .. })
+        ));
+        assert!(matches!(
+            template.render(&Bindings::new().with_env(&["HOME".to_string()])),
+            Err(TemplateError::EnvNotAllowed { name }) if name == "PATH"
+        ));
+    }
+}
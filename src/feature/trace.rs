@@ -0,0 +1,72 @@
+use std::collections::hash_map::DefaultHasher;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Environment variable which carries the trace ID into child processes
+pub const TRACE_ID_ENV: &str = "MAGICK_MCP_TRACE_ID";
+
+tokio::task_local! {
+    static TRACE_ID: String;
+}
+
+/// Counter which keeps trace IDs created in the same instant apart
+static TRACE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Create a new 16 character hex trace ID, unique across requests and processes
+pub fn new_trace_id() -> String {
+    let mut hasher = DefaultHasher::new();
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    std::process::id().hash(&mut hasher);
+    TRACE_COUNTER
+        .fetch_add(1, Ordering::Relaxed)
+        .hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// The trace ID of the request currently being handled, if any
+pub fn current_trace_id() -> Option<String> {
+    TRACE_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Run a future with the trace ID attached to it
+pub async fn with_trace_id<F: Future>(trace_id: String, future: F) -> F::Output {
+    TRACE_ID.scope(trace_id, future).await
+}
+
+/// Run a closure with the trace ID attached to it
+///
+/// Used to carry the current trace ID into worker threads, which do not inherit it.
+pub(crate) fn in_trace<R>(trace_id: Option<String>, f: impl FnOnce() -> R) -> R {
+    match trace_id {
+        Some(trace_id) => TRACE_ID.sync_scope(trace_id, f),
+        None => f(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_trace_id_is_unique_hex() {
+        let first = new_trace_id();
+        let second = new_trace_id();
+        assert_eq!(first.len(), 16);
+        assert!(first.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_in_trace_sets_current_trace_id() {
+        assert_eq!(current_trace_id(), None);
+        let inner = in_trace(Some("abc".to_string()), current_trace_id);
+        assert_eq!(inner, Some("abc".to_string()));
+        assert_eq!(in_trace(None, current_trace_id), None);
+    }
+}
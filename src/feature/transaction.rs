@@ -0,0 +1,251 @@
+use crate::feature::incremental;
+use crate::feature::output;
+use crate::feature::trash::{Trash, TrashKind};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Directory within the workspace holding staging areas
+const STATE_DIR: &str = ".magickmcp";
+/// Prefix of each staging area's directory name
+const STAGING_PREFIX: &str = "staging-";
+
+/// Distinguishes the staging areas of transactions started by this process
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// An output written to the staging area instead of its requested path
+struct Staged {
+    /// The absolute path the command asked to write
+    target: PathBuf,
+    /// Directory holding the files written in its place
+    dir: PathBuf,
+}
+
+/// Staged outputs and the incremental build state to record once they are in place
+#[derive(Default)]
+struct State {
+    staged: Vec<Staged>,
+    records: Vec<(Vec<String>, PathBuf)>,
+}
+
+/// Staging area for the outputs of a run which must land all at once or not at all
+///
+/// Outputs are written to a directory within the workspace's `.magickmcp` instead of their
+/// requested paths, and later commands reading them are pointed at the staged files. `commit`
+/// moves every staged file into place, while dropping the transaction without committing
+/// discards them, so a run failing partway leaves the workspace as it was.
+pub(crate) struct Transaction {
+    workspace: PathBuf,
+    root: PathBuf,
+    state: Mutex<State>,
+}
+
+impl Transaction {
+    /// Create an empty staging area
+    ///
+    /// # Arguments
+    ///
+    /// * `workspace` - Optional workspace the outputs are relative to, the current directory if not provided
+    pub(crate) fn begin(workspace: Option<&Path>) -> io::Result<Self> {
+        let workspace = std::path::absolute(workspace.unwrap_or(Path::new(".")))?;
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let root = workspace
+            .join(STATE_DIR)
+            .join(format!("{STAGING_PREFIX}{}-{id}", std::process::id()));
+        fs::create_dir_all(&root)?;
+        Ok(Transaction {
+            workspace,
+            root,
+            state: Mutex::new(State::default()),
+        })
+    }
+
+    /// The absolute path to write instead of an output, staging it on first use
+    ///
+    /// The staged file keeps its name, so its format and frame patterns such as `out-%d.png`
+    /// behave as they would in place.
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - The requested output path, relative to the workspace
+    pub(crate) fn stage(&self, output: &Path) -> io::Result<PathBuf> {
+        let target = self.workspace.join(output);
+        let file_name = target.file_name().unwrap_or_default().to_os_string();
+        let mut state = self.state();
+        if let Some(staged) = state.staged.iter().find(|staged| staged.target == target) {
+            return Ok(staged.dir.join(file_name));
+        }
+        let dir = self.root.join(state.staged.len().to_string());
+        fs::create_dir_all(&dir)?;
+        state.staged.push(Staged {
+            target,
+            dir: dir.clone(),
+        });
+        Ok(dir.join(file_name))
+    }
+
+    /// Point arguments reading a staged output at the staged file
+    ///
+    /// Any `FORMAT:` prefix and `[frames]` suffix are kept. The output argument itself is left
+    /// alone.
+    ///
+    /// # Returns
+    ///
+    /// Returns the rewritten arguments, or `None` when no argument reads a staged output
+    pub(crate) fn read_staged(&self, args: &[&str]) -> Option<Vec<String>> {
+        let state = self.state();
+        if state.staged.is_empty() {
+            return None;
+        }
+        let inputs = match output::output_path(args) {
+            Some(_) => args.len() - 1,
+            None => args.len(),
+        };
+        let mut rewritten: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        let mut changed = false;
+        for arg in rewritten.iter_mut().take(inputs) {
+            let path = output::strip_format_prefix(arg);
+            let path = match path.find('[') {
+                Some(index) if path.ends_with(']') => &path[..index],
+                _ => path,
+            };
+            if path.is_empty() || path.starts_with('-') {
+                continue;
+            }
+            let target = self.workspace.join(path);
+            let Some(staged) = state.staged.iter().find(|staged| staged.target == target) else {
+                continue;
+            };
+            let staged = staged.dir.join(target.file_name().unwrap_or_default());
+            let start = arg.len() - output::strip_format_prefix(arg).len();
+            *arg = format!(
+                "{}{}{}",
+                &arg[..start],
+                staged.display(),
+                &arg[start + path.len()..]
+            );
+            changed = true;
+        }
+        changed.then_some(rewritten)
+    }
+
+    /// Record incremental build state for an output once the transaction commits
+    pub(crate) fn record_on_commit(&self, args: &[&str], output: &Path) {
+        self.state().records.push((
+            args.iter().map(|arg| arg.to_string()).collect(),
+            output.to_path_buf(),
+        ));
+    }
+
+    /// Move every staged file into place, creating missing directories
+    ///
+    /// Every file written for an output is moved next to its requested path, which covers the
+    /// numbered files a frame pattern writes.
+    ///
+    /// # Arguments
+    ///
+    /// * `trash` - Trash keeping a copy of each file a staged output replaces, if any
+    pub(crate) fn commit(self, trash: Option<&Trash>) -> io::Result<()> {
+        let state = std::mem::take(&mut *self.state());
+        for staged in &state.staged {
+            let Some(parent) = staged.target.parent() else {
+                continue;
+            };
+            fs::create_dir_all(parent)?;
+            for entry in fs::read_dir(&staged.dir)? {
+                let entry = entry?;
+                let target = parent.join(entry.file_name());
+                if let Some(trash) = trash
+                    && target.is_file()
+                {
+                    // Failing to trash only means the overwritten output cannot be restored
+                    let _ = trash.keep(&target, TrashKind::Output);
+                }
+                move_file(&entry.path(), &target)?;
+            }
+        }
+        for (args, output) in &state.records {
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            // Failing to record only means the next run cannot skip this output
+            let _ = incremental::record(&args, output, &self.workspace);
+        }
+        Ok(())
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+/// Rename a file, copying it instead when the destination is on another filesystem
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to)?;
+    fs::remove_file(from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_commit_moves_staged_outputs_into_place() {
+        let dir = TempDir::new().unwrap();
+        let transaction = Transaction::begin(Some(dir.path())).unwrap();
+
+        let staged = transaction.stage(Path::new("out/a.png")).unwrap();
+        fs::write(&staged, b"a").unwrap();
+        assert_eq!(transaction.stage(Path::new("out/a.png")).unwrap(), staged);
+        assert!(!dir.path().join("out").exists());
+
+        transaction.commit(None).unwrap();
+        assert_eq!(fs::read(dir.path().join("out/a.png")).unwrap(), b"a");
+        assert_eq!(fs::read_dir(dir.path().join(STATE_DIR)).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_drop_discards_staged_outputs() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.png"), b"original").unwrap();
+        {
+            let transaction = Transaction::begin(Some(dir.path())).unwrap();
+            fs::write(transaction.stage(Path::new("a.png")).unwrap(), b"new").unwrap();
+        }
+        assert_eq!(fs::read(dir.path().join("a.png")).unwrap(), b"original");
+        assert_eq!(fs::read_dir(dir.path().join(STATE_DIR)).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_read_staged_rewrites_inputs() {
+        let dir = TempDir::new().unwrap();
+        let transaction = Transaction::begin(Some(dir.path())).unwrap();
+        assert_eq!(transaction.read_staged(&["a.png", "b.png"]), None);
+
+        let staged = transaction.stage(Path::new("a.png")).unwrap();
+        let staged = staged.display();
+        assert_eq!(
+            transaction.read_staged(&["png:./a.png[0]", "c.png", "-negate", "a.png"]),
+            Some(vec![
+                format!("png:{staged}[0]"),
+                "c.png".to_string(),
+                "-negate".to_string(),
+                "a.png".to_string()
+            ])
+        );
+        assert_eq!(
+            transaction.read_staged(&["identify", "a.png"]),
+            Some(vec!["identify".to_string(), staged.to_string()])
+        );
+    }
+}
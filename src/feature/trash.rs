@@ -0,0 +1,323 @@
+use crate::feature::config::TrashConfig;
+use crate::feature::data_dir::{DataDirLock, data_dir, write_atomic};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Counter keeping the ids of files trashed by one process within a second apart
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Error type for trash operations
+#[derive(Debug, Error)]
+pub enum TrashError {
+    #[error("Trash directory not found")]
+    TrashDirNotFound,
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Failed to parse trash entry: {0}")]
+    ParseError(#[from] serde_json::Error),
+    #[error("No trashed file with id '{0}'")]
+    EntryNotFound(String),
+}
+
+/// Why a file was moved to the trash
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrashKind {
+    /// A function removed with `func_delete`
+    Function,
+    /// An output replaced by a command writing over it
+    Output,
+}
+
+impl TrashKind {
+    /// The kind's name as listed
+    pub fn name(self) -> &'static str {
+        match self {
+            TrashKind::Function => "function",
+            TrashKind::Output => "output",
+        }
+    }
+}
+
+/// A file kept in the trash
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashEntry {
+    /// Id passed to `trash restore`
+    pub id: String,
+    /// Why the file was trashed
+    pub kind: TrashKind,
+    /// Absolute path the file is restored to
+    pub original: PathBuf,
+    /// Seconds since the Unix epoch when the file was trashed
+    pub trashed_at: u64,
+    /// Size of the file in bytes
+    pub bytes: u64,
+}
+
+/// Size of the trash
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct TrashStats {
+    /// Number of trashed files
+    pub entries: u64,
+    /// Total size of the trashed files in bytes
+    pub bytes: u64,
+}
+
+/// Copies of removed functions and overwritten outputs, kept for a retention period
+///
+/// Each file is stored as `<id>.data` next to its `<id>.json` entry in the `trash` directory of
+/// the data directory. Entries older than the retention period are purged whenever a file is
+/// trashed or the trash is listed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trash {
+    dir: Option<PathBuf>,
+    retention: Duration,
+}
+
+impl Trash {
+    /// Create a trash in the `trash` directory of the data directory, keeping files for the
+    /// configured retention period
+    pub fn new(config: &TrashConfig) -> Self {
+        Self::in_data_dir(data_dir(), config)
+    }
+
+    /// Create a trash in the `trash` directory of the provided data directory, keeping files
+    /// for the configured retention period
+    pub(crate) fn in_data_dir(data_dir: Option<PathBuf>, config: &TrashConfig) -> Self {
+        Self::at(data_dir.map(|dir| dir.join("trash")), config.retention())
+    }
+
+    fn at(dir: Option<PathBuf>, retention: Duration) -> Self {
+        Trash { dir, retention }
+    }
+
+    /// The trash keeping outputs before commands overwrite them, if the config asks for it
+    pub fn for_outputs(config: &TrashConfig) -> Option<Self> {
+        config.outputs.then(|| Self::new(config))
+    }
+
+    fn dir(&self) -> Result<&Path, TrashError> {
+        self.dir.as_deref().ok_or(TrashError::TrashDirNotFound)
+    }
+
+    /// Keep a copy of a file before it is removed or overwritten
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file, which is left in place
+    /// * `kind` - Why the file is trashed
+    ///
+    /// # Returns
+    ///
+    /// Returns the new entry, or a `TrashError` if the file cannot be copied
+    pub(crate) fn keep(&self, path: &Path, kind: TrashKind) -> Result<TrashEntry, TrashError> {
+        let dir = self.dir()?;
+        let _lock = DataDirLock::acquire(dir)?;
+        self.purge_expired(dir)?;
+        let trashed_at = now();
+        let id = format!(
+            "{trashed_at}-{}-{}",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        );
+        let entry = TrashEntry {
+            bytes: fs::copy(path, data_path(dir, &id))?,
+            id,
+            kind,
+            original: std::path::absolute(path)?,
+            trashed_at,
+        };
+        // The entry is written last, so a failed copy never lists a file without its data
+        write_atomic(&entry_path(dir, &entry.id), serde_json::to_vec(&entry)?)?;
+        Ok(entry)
+    }
+
+    /// List the trashed files, newest first
+    pub fn list(&self) -> Result<Vec<TrashEntry>, TrashError> {
+        let Some(dir) = self.dir.as_deref().filter(|dir| dir.is_dir()) else {
+            return Ok(Vec::new());
+        };
+        let _lock = DataDirLock::acquire(dir)?;
+        self.purge_expired(dir)?;
+        let mut entries = entries(dir)?;
+        entries.sort_by(|a, b| b.trashed_at.cmp(&a.trashed_at).then(b.id.cmp(&a.id)));
+        Ok(entries)
+    }
+
+    /// Put a trashed file back where it came from
+    ///
+    /// A file now at the original path is trashed in turn, so restoring can be undone too.
+    ///
+    /// # Returns
+    ///
+    /// Returns the restored entry, or `TrashError::EntryNotFound` if there is none with the id
+    pub fn restore(&self, id: &str) -> Result<TrashEntry, TrashError> {
+        let dir = self.dir()?;
+        let entry_file = entry_path(dir, id);
+        if id.contains(['/', '\\']) || !entry_file.is_file() {
+            return Err(TrashError::EntryNotFound(id.to_string()));
+        }
+        let entry: TrashEntry = serde_json::from_slice(&fs::read(&entry_file)?)?;
+        if entry.original.is_file() {
+            self.keep(&entry.original, entry.kind)?;
+        }
+        let _lock = DataDirLock::acquire(dir)?;
+        if let Some(parent) = entry.original.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomic(&entry.original, fs::read(data_path(dir, id))?)?;
+        remove_entry(dir, id)?;
+        Ok(entry)
+    }
+
+    /// Remove every trashed file
+    ///
+    /// # Returns
+    ///
+    /// Returns the size of what was removed, or a `TrashError` on failure
+    pub fn empty(&self) -> Result<TrashStats, TrashError> {
+        let Some(dir) = self.dir.as_deref().filter(|dir| dir.is_dir()) else {
+            return Ok(TrashStats::default());
+        };
+        let _lock = DataDirLock::acquire(dir)?;
+        let mut stats = TrashStats::default();
+        for entry in entries(dir)? {
+            stats.entries += 1;
+            stats.bytes += entry.bytes;
+            remove_entry(dir, &entry.id)?;
+        }
+        Ok(stats)
+    }
+
+    /// Remove entries older than the retention period, with the lock held
+    fn purge_expired(&self, dir: &Path) -> Result<(), TrashError> {
+        let cutoff = now().saturating_sub(self.retention.as_secs());
+        for entry in entries(dir)? {
+            if entry.trashed_at < cutoff {
+                remove_entry(dir, &entry.id)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The entries in a trash directory, skipping any which cannot be read
+fn entries(dir: &Path) -> Result<Vec<TrashEntry>, TrashError> {
+    let mut entries = Vec::new();
+    if !dir.is_dir() {
+        return Ok(entries);
+    }
+    for file in fs::read_dir(dir)? {
+        let path = file?.path();
+        if path.extension().is_some_and(|ext| ext == "json")
+            && let Ok(contents) = fs::read(&path)
+            && let Ok(entry) = serde_json::from_slice(&contents)
+        {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+fn remove_entry(dir: &Path, id: &str) -> io::Result<()> {
+    fs::remove_file(entry_path(dir, id))?;
+    match fs::remove_file(data_path(dir, id)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn entry_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.json"))
+}
+
+fn data_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.data"))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn trash(dir: &TempDir, retention: Duration) -> Trash {
+        Trash::at(Some(dir.path().join("trash")), retention)
+    }
+
+    #[test]
+    fn test_keep_list_and_restore() {
+        let dir = TempDir::new().unwrap();
+        let trash = trash(&dir, Duration::from_secs(3600));
+        let output = dir.path().join("out.png");
+        fs::write(&output, b"first").unwrap();
+
+        let entry = trash.keep(&output, TrashKind::Output).unwrap();
+        fs::write(&output, b"second").unwrap();
+
+        assert_eq!(entry.bytes, 5);
+        assert_eq!(trash.list().unwrap(), std::slice::from_ref(&entry));
+
+        let restored = trash.restore(&entry.id).unwrap();
+        assert_eq!(restored, entry);
+        assert_eq!(fs::read(&output).unwrap(), b"first");
+
+        // The overwritten version was trashed by the restore
+        let entries = trash.list().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].original, std::path::absolute(&output).unwrap());
+        trash.restore(&entries[0].id).unwrap();
+        assert_eq!(fs::read(&output).unwrap(), b"second");
+    }
+
+    #[test]
+    fn test_restore_unknown_id() {
+        let dir = TempDir::new().unwrap();
+        let trash = trash(&dir, Duration::from_secs(3600));
+
+        assert!(matches!(
+            trash.restore("missing"),
+            Err(TrashError::EntryNotFound(_))
+        ));
+        assert!(matches!(
+            trash.restore("../out"),
+            Err(TrashError::EntryNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_empty_and_expiry() {
+        let dir = TempDir::new().unwrap();
+        let function = dir.path().join("grayscale.json");
+        fs::write(&function, b"{}").unwrap();
+
+        let trash = trash(&dir, Duration::from_secs(3600));
+        trash.keep(&function, TrashKind::Function).unwrap();
+        trash.keep(&function, TrashKind::Function).unwrap();
+        assert_eq!(
+            trash.empty().unwrap(),
+            TrashStats {
+                entries: 2,
+                bytes: 4
+            }
+        );
+        assert!(trash.list().unwrap().is_empty());
+
+        let mut entry = trash.keep(&function, TrashKind::Function).unwrap();
+        entry.trashed_at -= 7200;
+        let entry_file = entry_path(&dir.path().join("trash"), &entry.id);
+        fs::write(&entry_file, serde_json::to_vec(&entry).unwrap()).unwrap();
+        assert!(trash.list().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,199 @@
+use crate::feature::output;
+use std::borrow::Cow;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Where magick-mcp is running, which decides how paths from the other side are translated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Host {
+    /// Linux under the Windows Subsystem for Linux, where Windows drives are under `/mnt`
+    Wsl,
+    /// Windows, which reaches WSL's view of its drives as drive letters
+    Windows,
+    /// Anywhere else, where paths are used as given
+    Other,
+}
+
+impl Host {
+    /// Detect the host once per process
+    pub(crate) fn current() -> Host {
+        static HOST: OnceLock<Host> = OnceLock::new();
+        *HOST.get_or_init(|| {
+            if cfg!(windows) {
+                Host::Windows
+            } else if cfg!(target_os = "linux") && is_wsl() {
+                Host::Wsl
+            } else {
+                Host::Other
+            }
+        })
+    }
+}
+
+/// Whether this Linux system is running under WSL
+///
+/// WSL sets `WSL_DISTRO_NAME` for processes it starts, and its kernel names itself in the
+/// release string for processes started some other way.
+fn is_wsl() -> bool {
+    std::env::var_os("WSL_DISTRO_NAME").is_some()
+        || std::fs::read_to_string("/proc/sys/kernel/osrelease")
+            .map(|release| release.to_ascii_lowercase().contains("microsoft"))
+            .unwrap_or(false)
+}
+
+/// Translate a path written for the other side of WSL into this host's form
+///
+/// Under WSL, `C:\Users\me\a.png` becomes `/mnt/c/Users/me/a.png`; on Windows, `/mnt/c/...`
+/// becomes `C:\...`. Any other path is returned as it is.
+pub fn translate_path(path: &str) -> Cow<'_, str> {
+    translate_path_for(Host::current(), path)
+}
+
+/// Translate a path for the given host
+pub(crate) fn translate_path_for(host: Host, path: &str) -> Cow<'_, str> {
+    let translated = match host {
+        Host::Wsl => windows_to_wsl(path),
+        Host::Windows => wsl_to_windows(path),
+        Host::Other => None,
+    };
+    translated.map_or(Cow::Borrowed(path), Cow::Owned)
+}
+
+/// Translate the paths in command arguments, keeping any `FORMAT:` prefix
+///
+/// # Returns
+///
+/// Returns the translated arguments, or `None` when none needed translating
+pub(crate) fn translate_args(args: &[&str]) -> Option<Vec<String>> {
+    translate_args_for(Host::current(), args)
+}
+
+fn translate_args_for(host: Host, args: &[&str]) -> Option<Vec<String>> {
+    if host == Host::Other {
+        return None;
+    }
+    let mut changed = false;
+    let translated = args
+        .iter()
+        .map(|arg| {
+            let path = output::strip_format_prefix(arg);
+            let prefix = &arg[..arg.len() - path.len()];
+            match translate_path_for(host, path) {
+                Cow::Owned(translated) => {
+                    changed = true;
+                    format!("{prefix}{translated}")
+                }
+                Cow::Borrowed(_) => arg.to_string(),
+            }
+        })
+        .collect();
+    changed.then_some(translated)
+}
+
+/// `C:\dir\file` or `C:/dir/file` as `/mnt/c/dir/file`
+fn windows_to_wsl(path: &str) -> Option<String> {
+    let bytes = path.as_bytes();
+    let is_drive = bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && (bytes[2] == b'\\' || bytes[2] == b'/');
+    if !is_drive {
+        return None;
+    }
+    let drive = (bytes[0] as char).to_ascii_lowercase();
+    let rest = path[3..].replace('\\', "/");
+    Some(format!("/mnt/{drive}/{rest}"))
+}
+
+/// `/mnt/c/dir/file` as `C:\dir\file`
+fn wsl_to_windows(path: &str) -> Option<String> {
+    let rest = path.strip_prefix("/mnt/")?;
+    let (drive, rest) = rest.split_at_checked(1)?;
+    let drive = drive.chars().next().filter(char::is_ascii_alphabetic)?;
+    let rest = match rest {
+        "" => "",
+        _ => rest.strip_prefix('/')?,
+    };
+    Some(format!(
+        r"{}:\{}",
+        drive.to_ascii_uppercase(),
+        rest.replace('/', r"\")
+    ))
+}
+
+/// Whether a config file lives on the other side of WSL from this host
+///
+/// Under WSL that is a file on a Windows drive, such as a Windows client's config under
+/// `/mnt/c/Users`. On Windows it is a file inside a distribution, reached through
+/// `\\wsl$\` or `\\wsl.localhost\`.
+pub(crate) fn is_foreign_config(host: Host, path: &Path) -> bool {
+    let path = path.to_string_lossy();
+    match host {
+        Host::Wsl => wsl_to_windows(&path).is_some(),
+        Host::Windows => {
+            let lower = path.to_ascii_lowercase().replace('/', r"\");
+            lower.starts_with(r"\\wsl$\") || lower.starts_with(r"\\wsl.localhost\")
+        }
+        Host::Other => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate_path() {
+        assert_eq!(
+            translate_path_for(Host::Wsl, r"C:\Users\me\My Photos\a.png"),
+            "/mnt/c/Users/me/My Photos/a.png"
+        );
+        assert_eq!(translate_path_for(Host::Wsl, "D:/assets"), "/mnt/d/assets");
+        assert_eq!(
+            translate_path_for(Host::Wsl, "/home/me/a.png"),
+            "/home/me/a.png"
+        );
+        assert_eq!(
+            translate_path_for(Host::Windows, "/mnt/c/Users/me/a.png"),
+            r"C:\Users\me\a.png"
+        );
+        assert_eq!(translate_path_for(Host::Windows, "/mnt/d"), r"D:\");
+        assert_eq!(
+            translate_path_for(Host::Windows, "/mnt/data/a.png"),
+            "/mnt/data/a.png"
+        );
+        assert_eq!(translate_path_for(Host::Other, r"C:\a.png"), r"C:\a.png");
+    }
+
+    #[test]
+    fn test_translate_args_keeps_format_prefix() {
+        assert_eq!(
+            translate_args_for(
+                Host::Wsl,
+                &[r"C:\in.png[0]", "-negate", r"png:C:\out\a.png"]
+            ),
+            Some(vec![
+                "/mnt/c/in.png[0]".to_string(),
+                "-negate".to_string(),
+                "png:/mnt/c/out/a.png".to_string()
+            ])
+        );
+        assert_eq!(translate_args_for(Host::Wsl, &["in.png", "out.png"]), None);
+        assert_eq!(translate_args_for(Host::Other, &[r"C:\in.png"]), None);
+    }
+
+    #[test]
+    fn test_is_foreign_config() {
+        let windows_config = Path::new("/mnt/c/Users/me/.cursor/mcp.json");
+        assert!(is_foreign_config(Host::Wsl, windows_config));
+        assert!(!is_foreign_config(
+            Host::Wsl,
+            Path::new("/home/me/.claude.json")
+        ));
+        assert!(is_foreign_config(
+            Host::Windows,
+            Path::new(r"\\wsl.localhost\Ubuntu\home\me\.claude.json")
+        ));
+        assert!(!is_foreign_config(Host::Other, windows_config));
+    }
+}
@@ -2,43 +2,421 @@ pub mod cli;
 mod feature;
 mod mcp;
 
-use feature::DefaultWhichChecker;
+use feature::BuildCache;
+use feature::HistoryStore;
 use feature::InstallError;
 use feature::MCPInstaller;
-use feature::MagickChecker;
-use feature::{CommandRunner, DefaultCommandRunner, ShellError};
-use feature::{Function, FunctionRunner, FunctionStore, FunctionStoreError};
+use feature::Trash;
+use feature::{
+    Annotate, AppIcons, Append, Bench, Border, Canvas, Capabilities, CaptionOverlay, Channels,
+    CollageMaker, Composite, Convert, Crop, Density, DocumentCleanup, Estimator, Favicon, Filter,
+    Frames, FxEvaluator, HeifConvert, HelpTopics, Histogram, InlinePreviewer, OperationError,
+    Optimize, OptionHelper, Orient, PdfBundle, PdfPages, Previewer, Redact, Resize, Snapshots,
+    Srcset, StrictChecker, StripMetadata, Stylize, Thumbnail, Trimmer, VerboseIdentify,
+    VisualDiffer, Watermark,
+};
+use feature::{Bootstrapper, Doctor, MagickChecker};
+use feature::{BuiltinUpdater, Function, FunctionRunner, FunctionStore, FunctionStoreError};
+use feature::{CommandRunner, DefaultCommandRunner, FaultConfig, FaultyCommandRunner};
+use feature::{Deduper, MagickBatch, Organizer, RenameByMetadata, WatermarkBatch};
+use feature::{DefaultWhichChecker, WhichChecker};
 
-pub use feature::{ClientType, ConfigPaths};
+pub use feature::{
+    AnnotateOptions, AppIconFile, AppIconOptions, AppendDirection, AppendOptions, AppendedImage,
+    ArgumentForm, BatchFileResult, BenchOptions, BenchReport, BenchResult, BootstrapReport,
+    BorderOptions, BuiltinBundle, BuiltinUpdate, BuiltinsConfig, BuiltinsError, CONFIG_PATH_ENV,
+    CacheStats, CanvasFill, CanvasOptions, CapabilityReport, CaptionOverlayOptions,
+    ChannelColorspace, ChannelFile, ChannelHistogram, CheckReport, CleanReport, CleanedDocument,
+    ClientType, Collage, CollageCell, CollageFit, CollageLayout, CollageOptions, CompositeOptions,
+    Config, ConfigCapabilities, ConfigError, ConfigPaths, ConflictStrategy, ContrastStretch,
+    ConvertOptions, ConvertedFile, CostEstimate, CropOptions, CropRect, CropUnit, CroppedImage,
+    DEFAULT_BUILTINS_URL, DEFAULT_BUNDLE_DENSITY, DEFAULT_DEDUPE_THRESHOLD, DEFAULT_FAVICON_DIR,
+    DEFAULT_HISTOGRAM_BINS, DEFAULT_MIN_QUALITY, DEFAULT_PDF_DENSITY, DEFAULT_POINT_SIZE,
+    DEFAULT_PREVIEW_BYTES, DEFAULT_PREVIEW_DIMENSION, DEFAULT_SNAPSHOT_THRESHOLD,
+    DEFAULT_SRCSET_WIDTHS, DEFAULT_THUMBNAIL_SIZES, DedupeAction, DedupeReport, DensityChange,
+    DensityOptions, DensityUnits, DiffMetric, DoctorCheck, DoctorReport, DoctorStatus,
+    DocumentCleanupOptions, DropShadow, DuplicateGroup, EstimateOptions, EstimateVerdict,
+    ExportFormat, ExtractedFrame, FaviconFile, FaviconOptions, FileDigest, FilterOperation,
+    FilterOptions, FontInfo, FormatSupport, FramesOptions, FunctionEdit, FunctionOutput,
+    FunctionSource, FunctionsConfig, HeifConversion, HeifConvertOptions, HeifFormat, HeifSupport,
+    HelpTopic, HistoryEntry, HistoryError, IconPlatform, ImageDimensions, ImageFormat,
+    ImageHistogram, InlinePreview, InlinePreviewOptions, Language, LegacyRewrite, LimitsConfig,
+    LockScope, MIN_REDACT_STRENGTH, MagickConfig, MagickOutput, ManagedEnvironment, Message,
+    OptimizeOptions, OptimizedImage, OptionExample, OptionHelp, OrganizeBy, OrientOptions,
+    OrientedImage, OutputPolicy, OutputSettings, PROJECT_CONFIG_FILE, PROJECT_FUNCTIONS_DIR,
+    PackageManager, PageSize, ParamType, PdfBundleOptions, PdfPagesOptions, Placement,
+    PlacementStatus, PresetConfig, PreviewFormat, PrintSize, ProjectConfig, RedactOptions,
+    RedactionMode, RefreshStatus, RefreshedConfig, Region, RenameEntry, RenameStatus, RenderedPage,
+    ResizeFit, ResizeOptions, ResolvedOutput, RgbaChannel, ServerConfig, ShellError, SnapshotCheck,
+    SnapshotCheckOptions, SnapshotStatus, SnapshotUpdate, SrcsetFile, SrcsetOptions, StreamFiles,
+    StrictCheckOptions, StrictConcern, StrictFinding, StripMetadataOptions, StrippedImage, Style,
+    StylizeOptions, TRACE_ID_ENV, TemplateError, ThumbnailFile, ThumbnailOptions, TrashConfig,
+    TrashEntry, TrashError, TrashKind, TrashStats, TrimOptions, Trimmed, VisualDiff,
+    VisualDiffOptions, WatermarkFileResult, WatermarkMark, WatermarkOptions, WebFormat, begin_call,
+    clean, config_path, current_trace_id, end_call, install_cleanup_guard, install_crash_reporter,
+    latest_crash_report, log_line, new_trace_id, parse_byte_size, picture_html, remove_managed,
+    rewrite_legacy, take_lock_wait, translate_path, with_trace_id,
+};
+
+/// The runner for every command magick-mcp runs
+///
+/// In debug builds, setting `MAGICK_MCP_FAULTS` wraps it in a `FaultyCommandRunner` which fails
+/// commands at random, for resilience testing.
+fn command_runner() -> Box<dyn CommandRunner + Sync> {
+    match FaultConfig::from_env() {
+        Some(config) => Box::new(FaultyCommandRunner::new(DefaultCommandRunner, config)),
+        None => Box::new(DefaultCommandRunner),
+    }
+}
 
 /// Check if ImageMagick is installed and return version or installation instructions
 pub fn check() -> Result<String, String> {
     let which_checker = DefaultWhichChecker;
-    let command_runner = DefaultCommandRunner;
-    let checker = MagickChecker::new(&which_checker, &command_runner);
-    checker.check_magick()
+    let command_runner = command_runner();
+    magick_checker(&which_checker, &command_runner).check_magick()
+}
+
+/// Check if ImageMagick is installed and return a typed report
+pub fn check_report() -> CheckReport {
+    let config = Config::load().unwrap_or_default();
+    check_report_with(config.language.as_deref(), &config.magick)
+}
+
+/// Check if ImageMagick is installed with a language and `magick` command already read from
+/// the config
+pub(crate) fn check_report_with(language: Option<&str>, magick: &MagickConfig) -> CheckReport {
+    let which_checker = DefaultWhichChecker;
+    let command_runner = command_runner();
+    configured_checker(&which_checker, &command_runner, language, magick).report()
+}
+
+/// Diagnose the ImageMagick installation, config file, and data directory
+pub fn doctor() -> DoctorReport {
+    let which_checker = DefaultWhichChecker;
+    let command_runner = command_runner();
+    Doctor::new(magick_checker(&which_checker, &command_runner)).run()
+}
+
+/// A checker using the configured language and `magick` command, which looks for an
+/// environment manager in the current directory
+fn magick_checker<'a>(
+    which_checker: &'a DefaultWhichChecker,
+    command_runner: &'a dyn CommandRunner,
+) -> MagickChecker<'a> {
+    let config = Config::load().unwrap_or_default();
+    configured_checker(
+        which_checker,
+        command_runner,
+        config.language.as_deref(),
+        &config.magick,
+    )
+}
+
+/// A checker using the given language and `magick` command, which looks for an environment
+/// manager in the current directory
+fn configured_checker<'a>(
+    which_checker: &'a DefaultWhichChecker,
+    command_runner: &'a dyn CommandRunner,
+    language: Option<&str>,
+    magick: &MagickConfig,
+) -> MagickChecker<'a> {
+    let mut checker = MagickChecker::new(which_checker, command_runner)
+        .with_language(Language::detect(language))
+        .with_magick_command(magick.command.clone());
+    if let Ok(dir) = std::env::current_dir() {
+        checker = checker.with_project_dir(dir);
+    }
+    checker
+}
+
+/// Install ImageMagick if needed and verify the delegates required by functions
+///
+/// # Arguments
+///
+/// * `install` - Whether to install ImageMagick when missing, rather than only reporting the commands
+/// * `functions` - Names of functions whose delegates must be available
+/// * `delegates` - Additional delegates which must be available, e.g. `webp`
+///
+/// # Returns
+///
+/// Returns the `BootstrapReport`, or a `FunctionStoreError` if a function cannot be loaded
+pub fn bootstrap(
+    install: bool,
+    functions: &[String],
+    delegates: &[String],
+) -> Result<BootstrapReport, FunctionStoreError> {
+    let store = FunctionStore::new();
+    let mut required = delegates.to_vec();
+    for name in functions {
+        required.extend(feature::required_delegates(&store.load(name)?));
+    }
+    let which_checker = DefaultWhichChecker;
+    let command_runner = command_runner();
+    Ok(Bootstrapper::new(&which_checker, &command_runner)
+        .with_required_delegates(required)
+        .with_install(install)
+        .run())
+}
+
+/// Export the recorded tool call history as JSON lines
+///
+/// # Arguments
+///
+/// * `out` - File to write, replacing any existing contents. Writes to stdout when `None`
+///
+/// # Returns
+///
+/// Returns the number of entries exported, or a `HistoryError` on failure
+pub fn export_history(out: Option<&std::path::Path>) -> Result<usize, HistoryError> {
+    let store = HistoryStore::new();
+    match out {
+        Some(out) => {
+            let mut file = std::io::BufWriter::new(std::fs::File::create(out)?);
+            let count = store.export_jsonl(&mut file)?;
+            std::io::Write::flush(&mut file)?;
+            Ok(count)
+        }
+        None => store.export_jsonl(&mut std::io::stdout().lock()),
+    }
+}
+
+/// Count the outputs in the build cache and their size
+pub fn cache_stats() -> std::io::Result<CacheStats> {
+    BuildCache::new().stats()
+}
+
+/// Remove every output from the build cache
+///
+/// # Returns
+///
+/// Returns the size of what was removed, or an `io::Error` on failure
+pub fn clear_cache() -> std::io::Result<CacheStats> {
+    BuildCache::new().clear()
+}
+
+/// List the removed functions and overwritten outputs in the trash, newest first
+///
+/// # Arguments
+///
+/// * `trash` - The `[trash]` section of the config, setting which expired entries are purged
+pub fn trash_list(trash: &TrashConfig) -> Result<Vec<TrashEntry>, TrashError> {
+    Trash::new(trash).list()
+}
+
+/// Put a trashed file back where it came from
+///
+/// # Arguments
+///
+/// * `id` - The id of the entry, as listed by `trash_list`
+/// * `trash` - The `[trash]` section of the config
+///
+/// # Returns
+///
+/// Returns the restored entry, or a `TrashError` if there is none with the id
+pub fn trash_restore(id: &str, trash: &TrashConfig) -> Result<TrashEntry, TrashError> {
+    Trash::new(trash).restore(id)
+}
+
+/// Remove every file from the trash
+///
+/// # Arguments
+///
+/// * `trash` - The `[trash]` section of the config
+///
+/// # Returns
+///
+/// Returns the size of what was removed, or a `TrashError` on failure
+pub fn empty_trash(trash: &TrashConfig) -> Result<TrashStats, TrashError> {
+    Trash::new(trash).empty()
+}
+
+/// Get the language for user-facing messages
+///
+/// Uses the `language` from the config file when set, otherwise `LC_ALL`, `LC_MESSAGES`, or
+/// `LANG`, falling back to English.
+pub fn language() -> Language {
+    let configured = Config::load().ok().and_then(|config| config.language);
+    Language::detect(configured.as_deref())
 }
 
 /// Install magick-mcp to MCP client configuration
+///
+/// Each updated config is recorded so `refresh_install_paths` can update it later
 pub fn install(client_type: ClientType, config_paths: ConfigPaths) -> Result<(), InstallError> {
-    let installer = MCPInstaller::new(client_type, config_paths);
+    let installer = MCPInstaller::new(client_type, config_paths)
+        .with_recorded_installs()
+        .with_magick_path(DefaultWhichChecker.find("magick").ok());
     installer.install()
 }
 
+/// Point every client config magick-mcp was installed to at the current executable and
+/// ImageMagick locations, after an upgrade moved either of them
+///
+/// # Returns
+///
+/// Returns what happened to each recorded config, or an `InstallError` on failure
+pub fn refresh_install_paths() -> Result<Vec<RefreshedConfig>, InstallError> {
+    let config_paths = ConfigPaths::from_home_dir()?;
+    let installer = MCPInstaller::new(ClientType::Both, config_paths)
+        .with_recorded_installs()
+        .with_magick_path(DefaultWhichChecker.find("magick").ok());
+    installer.refresh_paths()
+}
+
 /// Execute an ImageMagick command
 ///
 /// # Arguments
 ///
 /// * `command` - A string containing ImageMagick command arguments, e.g., "test.png -negate test_negate.png"
 /// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the command treats the file it writes, e.g. creating missing directories
+/// * `stream_files` - Files to save the raw output streams to, summarizing them in the result
+///
+/// # Returns
+///
+/// Returns the command output and the file it wrote, or a ShellError if execution fails
+pub fn magick(
+    command: &str,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+    stream_files: StreamFiles,
+) -> Result<MagickOutput, ShellError> {
+    let rewrite = legacy_rewrite(command);
+    let command = rewrite
+        .as_ref()
+        .map_or(command, |rewrite| rewrite.command.as_str());
+    let command_runner = command_runner();
+    refuse_over_limits(&command_runner, command, workspace)?;
+    let runner = feature::MagickRunner::new(&command_runner, workspace)
+        .with_output_policy(output_policy)
+        .with_stream_files(stream_files);
+    let output = runner.execute(command)?;
+    Ok(MagickOutput {
+        output,
+        file: runner.take_resolved_outputs().pop(),
+        rewrite,
+    })
+}
+
+/// The corrected command when a command wrongly starts with `magick`
+///
+/// The suggestion is also rewritten from legacy ImageMagick 6 syntax, so `magick convert
+/// in.png -matte out.png` suggests `in.png -alpha set out.png`.
+///
+/// # Arguments
+///
+/// * `command` - ImageMagick command arguments, which should not name the program
+///
+/// # Returns
+///
+/// Returns the command without the prefix, or `None` when it has none
+pub fn bad_prefix(command: &str) -> Option<String> {
+    let stripped = feature::strip_bad_prefix(command)?;
+    Some(match rewrite_legacy(&stripped) {
+        Some(rewrite) => rewrite.command,
+        None => stripped,
+    })
+}
+
+/// Rewrite a legacy ImageMagick 6 command, logging the rewrite to stderr
+fn legacy_rewrite(command: &str) -> Option<LegacyRewrite> {
+    let rewrite = rewrite_legacy(command)?;
+    let trace = current_trace_id()
+        .map(|trace_id| format!(" trace_id={trace_id}"))
+        .unwrap_or_default();
+    log_line(format!(
+        "magick-mcp{trace} rewrote legacy command '{}' to '{}' ({})",
+        rewrite.original,
+        rewrite.command,
+        rewrite.changes.join(", ")
+    ));
+    Some(rewrite)
+}
+
+/// Execute an ImageMagick command on an image passed as bytes rather than a file
+///
+/// The bytes are written to a temporary file which replaces `$input` in the command and is
+/// removed afterward.
+///
+/// # Arguments
+///
+/// * `command` - ImageMagick command arguments containing `$input`, e.g. "$input -negate out.png"
+/// * `input` - The encoded input image
+/// * `input_format` - File extension of the input, e.g. `png`
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the command treats the file it writes
+/// * `stream_files` - Files to save the raw output streams to, summarizing them in the result
 ///
 /// # Returns
 ///
-/// Returns the command output as a String, or a ShellError if execution fails
-pub fn magick(command: &str, workspace: Option<&std::path::Path>) -> Result<String, ShellError> {
-    let command_runner = DefaultCommandRunner;
-    let runner = feature::MagickRunner::new(&command_runner, workspace);
-    runner.execute(command)
+/// Returns the `MagickOutput`, or a ShellError if execution fails
+pub fn magick_with_input(
+    command: &str,
+    input: &[u8],
+    input_format: &str,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+    stream_files: StreamFiles,
+) -> Result<MagickOutput, ShellError> {
+    let rewrite = legacy_rewrite(command);
+    let command = rewrite
+        .as_ref()
+        .map_or(command, |rewrite| rewrite.command.as_str());
+    let template = feature::Template::parse(command)?;
+    if !template.uses("input") {
+        return Err(ShellError::ExecutionFailed {
+            message: "command must contain $input where the inline image is used".to_string(),
+            command: "magick".to_string(),
+            args: command.to_string(),
+        });
+    }
+    let temp =
+        feature::TempFile::write(input, input_format).map_err(|e| ShellError::ExecutionFailed {
+            message: format!("failed to write inline input: {e}"),
+            command: "magick".to_string(),
+            args: command.to_string(),
+        })?;
+    let path = temp.path().to_string_lossy();
+    let args = template.render(&feature::Bindings::new().with("input", path.as_ref()))?;
+    let command_runner = command_runner();
+    refuse_over_limits(&command_runner, &args.join(" "), workspace)?;
+    let runner = feature::MagickRunner::new(&command_runner, workspace)
+        .with_output_policy(output_policy)
+        .with_stream_files(stream_files);
+    let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+    let output = runner.execute_args(&arg_refs)?;
+    Ok(MagickOutput {
+        output,
+        file: runner.take_resolved_outputs().pop(),
+        rewrite,
+    })
+}
+
+/// Refuse a command whose estimated cost exceeds a configured `max_` limit
+///
+/// Commands whose inputs cannot be identified are left for ImageMagick to report.
+fn refuse_over_limits(
+    command_runner: &dyn CommandRunner,
+    command: &str,
+    workspace: Option<&std::path::Path>,
+) -> Result<(), ShellError> {
+    let limits = Config::load()
+        .map(|config| config.limits)
+        .unwrap_or_default();
+    if limits.max_memory_mb.is_none() && limits.max_seconds.is_none() {
+        return Ok(());
+    }
+    let estimate = Estimator::new(command_runner, workspace)
+        .with_limits(limits)
+        .estimate(&EstimateOptions::new(command));
+    match estimate {
+        Ok(estimate) if estimate.verdict == EstimateVerdict::Refuse => {
+            Err(ShellError::CostLimitExceeded {
+                message: estimate.warnings.join(", "),
+            })
+        }
+        _ => Ok(()),
+    }
 }
 
 /// Get ImageMagick help documentation
@@ -47,7 +425,7 @@ pub fn magick(command: &str, workspace: Option<&std::path::Path>) -> Result<Stri
 ///
 /// Returns the help output from `magick --help` as a String, or a ShellError if execution fails
 pub fn help() -> Result<String, ShellError> {
-    let command_runner = DefaultCommandRunner;
+    let command_runner = command_runner();
     CommandRunner::execute(&command_runner, "magick", &["--help"], None)
 }
 
@@ -70,36 +448,100 @@ pub fn save_function(function: Function) -> Result<(), FunctionStoreError> {
 /// # Arguments
 ///
 /// * `name` - The name of the function to load
+/// * `workspace` - Optional workspace whose project functions take precedence
 ///
 /// # Returns
 ///
 /// Returns the `Function` on success, or a `FunctionStoreError` on failure
-pub fn load_function(name: &str) -> Result<Function, FunctionStoreError> {
-    let store = FunctionStore::new();
+pub fn load_function(
+    name: &str,
+    workspace: Option<&std::path::Path>,
+) -> Result<Function, FunctionStoreError> {
+    let store = FunctionStore::new().with_workspace(workspace);
     store.load(name)
 }
 
+/// Load a magick function along with where it was found
+///
+/// # Arguments
+///
+/// * `name` - The name of the function to load
+/// * `workspace` - Optional workspace whose project functions take precedence
+///
+/// # Returns
+///
+/// Returns the `Function` and its `FunctionSource`, or a `FunctionStoreError` on failure
+pub fn locate_function(
+    name: &str,
+    workspace: Option<&std::path::Path>,
+) -> Result<(Function, FunctionSource), FunctionStoreError> {
+    let store = FunctionStore::new().with_workspace(workspace);
+    store.locate(name)
+}
+
+/// Render a magick function as Makefile targets or a justfile recipe
+///
+/// # Arguments
+///
+/// * `function` - The function to export
+/// * `format` - The build system to render for
+///
+/// # Returns
+///
+/// Returns the file contents, or a `TemplateError` if a command is malformed or uses a
+/// placeholder the function does not provide
+pub fn export_function(function: &Function, format: ExportFormat) -> Result<String, TemplateError> {
+    feature::export_function(function, format)
+}
+
 /// List all available magick function names
 ///
+/// # Arguments
+///
+/// * `workspace` - Optional workspace whose project functions are listed first
+///
 /// # Returns
 ///
 /// Returns a vector of function names, or a `FunctionStoreError` on failure
-pub fn list_functions() -> Result<Vec<String>, FunctionStoreError> {
-    let store = FunctionStore::new();
+pub fn list_functions(
+    workspace: Option<&std::path::Path>,
+) -> Result<Vec<String>, FunctionStoreError> {
+    let store = FunctionStore::new().with_workspace(workspace);
     store.list()
 }
 
+/// Apply partial edits to a saved magick function
+///
+/// # Arguments
+///
+/// * `name` - The name of the function to edit
+/// * `edits` - Renames and command changes, applied in order
+/// * `workspace` - Optional workspace whose project functions are edited in place
+///
+/// # Returns
+///
+/// Returns the updated `Function`, or a `FunctionStoreError` on failure
+pub fn update_function(
+    name: &str,
+    edits: &[FunctionEdit],
+    workspace: Option<&std::path::Path>,
+) -> Result<Function, FunctionStoreError> {
+    let store = FunctionStore::new().with_workspace(workspace);
+    store.update(name, edits)
+}
+
 /// Delete a magick function from disk
 ///
 /// # Arguments
 ///
 /// * `name` - The name of the function to delete
+/// * `trash` - The `[trash]` section of the config, setting how long the deleted copy is kept
 ///
 /// # Returns
 ///
 /// Returns `Ok(())` on success, or a `FunctionStoreError` on failure
-pub fn delete_function(name: &str) -> Result<(), FunctionStoreError> {
-    let store = FunctionStore::new();
+pub fn delete_function(name: &str, trash: &TrashConfig) -> Result<(), FunctionStoreError> {
+    let store = FunctionStore::new().with_trash(Trash::new(trash));
     store.delete(name)
 }
 
@@ -110,20 +552,1247 @@ pub fn delete_function(name: &str) -> Result<(), FunctionStoreError> {
 /// * `function` - The function containing commands to execute
 /// * `workspace` - Optional workspace path to set as the working directory for commands
 /// * `input` - Optional input value to replace `$input` placeholders in commands
+/// * `params` - Values for the function's declared parameters
+/// * `output_policy` - How commands treat the files they write
+/// * `transactional` - Stage the written files and only move them into place if every command succeeds
+/// * `functions` - The `[functions]` section of the config, listing the environment variables
+///   commands may read
 ///
 /// # Returns
 ///
-/// Returns a vector of command outputs, or the first `ShellError` encountered
+/// Returns the command outputs and written files, or the first `ShellError` encountered
 ///
 /// # Errors
 ///
-/// Returns `ShellError::MissingInputVariable` if a command contains `$input` but no input was provided
+/// Returns `ShellError::MissingInputVariable` if a command contains `$input` but no input was provided,
+/// `ShellError::InvalidParameterValue` if a value is undeclared or has the wrong type, or
+/// `ShellError::TransactionFailed` if the staged files could not be moved into place
 pub fn run_function(
     function: &Function,
     workspace: Option<&std::path::Path>,
     input: Option<&str>,
-) -> Result<Vec<String>, ShellError> {
-    let command_runner = DefaultCommandRunner;
-    let runner = FunctionRunner::new(&command_runner, workspace);
-    runner.run(function, input)
+    params: &std::collections::BTreeMap<String, String>,
+    output_policy: OutputPolicy,
+    transactional: bool,
+    functions: &FunctionsConfig,
+) -> Result<FunctionOutput, ShellError> {
+    let command_runner = command_runner();
+    let runner = FunctionRunner::new(&command_runner, workspace)
+        .with_output_policy(output_policy)
+        .with_transactional(transactional)
+        .with_env(functions.env.clone());
+    let outputs = runner.run(function, input, params)?;
+    Ok(FunctionOutput {
+        outputs,
+        files: runner.take_resolved_outputs(),
+    })
+}
+
+/// Overlay top and/or bottom caption text on an image
+///
+/// # Arguments
+///
+/// * `options` - The caption text, styling, and input/output paths
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output on success, or an `OperationError` on failure
+pub fn caption_overlay(
+    options: &CaptionOverlayOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<ResolvedOutput, OperationError> {
+    let command_runner = command_runner();
+    let overlay = CaptionOverlay::new(&command_runner, workspace).with_output_policy(output_policy);
+    overlay.run(options)
+}
+
+/// Obscure rectangular regions of an image by pixelating, blurring, or filling them
+///
+/// # Arguments
+///
+/// * `options` - The regions, redaction mode, and input/output paths
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output on success, or an `OperationError` on failure
+pub fn redact(
+    options: &RedactOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<ResolvedOutput, OperationError> {
+    let command_runner = command_runner();
+    let redact = Redact::new(&command_runner, workspace).with_output_policy(output_policy);
+    redact.run(options)
+}
+
+/// Render an ANSI truecolor preview of an image for display in a terminal
+///
+/// # Arguments
+///
+/// * `path` - Path to the image to preview
+/// * `columns` - Maximum width of the preview in terminal columns
+///
+/// # Returns
+///
+/// Returns the preview text, or an `OperationError` on failure
+pub fn preview(path: &str, columns: u32) -> Result<String, OperationError> {
+    let command_runner = command_runner();
+    let previewer = Previewer::new(&command_runner, None);
+    previewer.render(path, columns)
+}
+
+/// Run `identify -verbose` on an image and parse the output into nested JSON
+///
+/// # Arguments
+///
+/// * `path` - Path to the image to identify
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+///
+/// # Returns
+///
+/// Returns the parsed metadata, or an `OperationError` on failure
+pub fn identify_verbose(
+    path: &str,
+    workspace: Option<&std::path::Path>,
+) -> Result<serde_json::Value, OperationError> {
+    let command_runner = command_runner();
+    let identify = VerboseIdentify::new(&command_runner, workspace);
+    identify.run(path)
+}
+
+/// Separate an image into one grayscale file per channel
+///
+/// # Arguments
+///
+/// * `input` - Path to the source image
+/// * `colorspace` - Colorspace to convert to before separating
+/// * `output_pattern` - Output path containing `%d`, replaced by the channel index
+/// * `include_alpha` - Whether to also write the alpha channel
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How each output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the channel files which exist after the split, or an `OperationError` on failure
+pub fn channels_split(
+    input: &str,
+    colorspace: ChannelColorspace,
+    output_pattern: &str,
+    include_alpha: bool,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<Vec<ChannelFile>, OperationError> {
+    let command_runner = command_runner();
+    let channels = Channels::new(&command_runner, workspace).with_output_policy(output_policy);
+    channels.split(input, colorspace, output_pattern, include_alpha)
+}
+
+/// Combine per-channel grayscale images into a single image
+///
+/// # Arguments
+///
+/// * `inputs` - Channel images in colorspace order, optionally followed by alpha
+/// * `colorspace` - Colorspace the channels belong to
+/// * `output` - Path to write the combined image to
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output, or an `OperationError` on failure
+pub fn channels_combine(
+    inputs: &[String],
+    colorspace: ChannelColorspace,
+    output: &str,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<ResolvedOutput, OperationError> {
+    let command_runner = command_runner();
+    let channels = Channels::new(&command_runner, workspace).with_output_policy(output_policy);
+    channels.combine(inputs, colorspace, output)
+}
+
+/// Write chosen RGBA channels of an image to one grayscale file each
+///
+/// # Arguments
+///
+/// * `input` - Path to the source image
+/// * `channels` - The channels to write, in order
+/// * `output_pattern` - Output path containing `{channel}`, replaced by the channel name
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How each output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written channel files, or an `OperationError` on failure
+pub fn channels_extract(
+    input: &str,
+    channels: &[RgbaChannel],
+    output_pattern: &str,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<Vec<ChannelFile>, OperationError> {
+    let command_runner = command_runner();
+    let channels_op = Channels::new(&command_runner, workspace).with_output_policy(output_policy);
+    channels_op.extract(input, channels, output_pattern)
+}
+
+/// Exchange two RGBA channels of an image
+///
+/// # Arguments
+///
+/// * `input` - Path to the source image
+/// * `first` - One channel to swap
+/// * `second` - The channel to swap it with
+/// * `output` - Path to write the swapped image to
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output, or an `OperationError` on failure
+pub fn channels_swap(
+    input: &str,
+    first: RgbaChannel,
+    second: RgbaChannel,
+    output: &str,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<ResolvedOutput, OperationError> {
+    let command_runner = command_runner();
+    let channels = Channels::new(&command_runner, workspace).with_output_policy(output_policy);
+    channels.swap(input, first, second, output)
+}
+
+/// Replace an image's alpha channel with the brightness of a mask image
+///
+/// # Arguments
+///
+/// * `input` - Path to the source image
+/// * `mask` - Path to the mask image, white where the result is opaque
+/// * `output` - Path to write the masked image to
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output, or an `OperationError` on failure
+pub fn channels_apply_alpha_mask(
+    input: &str,
+    mask: &str,
+    output: &str,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<ResolvedOutput, OperationError> {
+    let command_runner = command_runner();
+    let channels = Channels::new(&command_runner, workspace).with_output_policy(output_policy);
+    channels.apply_alpha_mask(input, mask, output)
+}
+
+/// Evaluate an ImageMagick fx expression over one or two images
+///
+/// # Arguments
+///
+/// * `expression` - The fx expression; the first image is `u` and the second is `v`
+/// * `image` - Path to the first image
+/// * `second_image` - Optional path to a second image
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+///
+/// # Returns
+///
+/// Returns the scalar result, or an `OperationError` on failure
+pub fn fx_eval(
+    expression: &str,
+    image: &str,
+    second_image: Option<&str>,
+    workspace: Option<&std::path::Path>,
+) -> Result<f64, OperationError> {
+    let command_runner = command_runner();
+    let evaluator = FxEvaluator::new(&command_runner, workspace);
+    evaluator.evaluate(expression, image, second_image)
+}
+
+/// Rename image files matching a glob using a template of metadata fields
+///
+/// # Arguments
+///
+/// * `pattern` - Glob pattern selecting files, relative to the workspace
+/// * `template` - Template for the new file stem, e.g. `{date}_{width}x{height}_{orig}`
+/// * `dry_run` - When true only the plan is returned and no files are renamed
+/// * `workspace` - Optional workspace path the pattern and commands are relative to
+///
+/// # Returns
+///
+/// Returns the rename plan with per-file status, or an `OperationError` on failure
+pub fn rename_by_metadata(
+    pattern: &str,
+    template: &str,
+    dry_run: bool,
+    workspace: Option<&std::path::Path>,
+) -> Result<Vec<RenameEntry>, OperationError> {
+    let command_runner = command_runner();
+    let renamer = RenameByMetadata::new(&command_runner, workspace);
+    renamer.run(pattern, template, dry_run)
+}
+
+/// Sort image files matching a glob into subdirectories by date, dimensions class, or format
+///
+/// # Arguments
+///
+/// * `pattern` - Glob pattern selecting files, relative to the workspace
+/// * `by` - How images are grouped
+/// * `destination` - Directory the group subdirectories are created in, relative to the workspace
+/// * `copy` - Copy files instead of moving them
+/// * `dry_run` - When true only the plan is returned and no files are touched
+/// * `workspace` - Optional workspace path the pattern and commands are relative to
+///
+/// # Returns
+///
+/// Returns the organize plan with per-file status, or an `OperationError` on failure
+pub fn organize(
+    pattern: &str,
+    by: OrganizeBy,
+    destination: &std::path::Path,
+    copy: bool,
+    dry_run: bool,
+    workspace: Option<&std::path::Path>,
+) -> Result<Vec<Placement>, OperationError> {
+    let command_runner = command_runner();
+    let organizer = Organizer::new(&command_runner, workspace);
+    organizer.run(pattern, by, destination, copy, dry_run)
+}
+
+/// Group near-duplicate images matching a glob and prune all but the highest-resolution copy
+///
+/// # Arguments
+///
+/// * `pattern` - Glob pattern selecting files, relative to the workspace
+/// * `threshold` - Maximum Hamming distance between perceptual hashes of near-duplicates
+/// * `action` - Whether to report, move, or delete duplicates
+/// * `confirm` - The `plan_id` from a previous dry run; the action is only applied when it matches
+/// * `workspace` - Optional workspace path the pattern and commands are relative to
+///
+/// # Returns
+///
+/// Returns the dedupe report, or an `OperationError` on failure
+pub fn dedupe(
+    pattern: &str,
+    threshold: u32,
+    action: &DedupeAction,
+    confirm: Option<&str>,
+    workspace: Option<&std::path::Path>,
+) -> Result<DedupeReport, OperationError> {
+    let command_runner = command_runner();
+    let deduper = Deduper::new(&command_runner, workspace);
+    deduper.run(pattern, threshold, action, confirm)
+}
+
+/// Apply a logo watermark to every image matching a glob, writing copies into a directory
+///
+/// # Arguments
+///
+/// * `pattern` - Glob pattern selecting files, relative to the workspace
+/// * `output_dir` - Directory the watermarked copies are written to, relative to the workspace
+/// * `template` - Logo, gravity, opacity, and margin applied to each file
+/// * `workspace` - Optional workspace path the pattern and commands are relative to
+/// * `output_policy` - How each output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns per-file results, or an `OperationError` if the batch could not start
+pub fn watermark_batch(
+    pattern: &str,
+    output_dir: &std::path::Path,
+    template: &WatermarkOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<Vec<WatermarkFileResult>, OperationError> {
+    let command_runner = command_runner();
+    let batch = WatermarkBatch::new(&command_runner, workspace).with_output_policy(output_policy);
+    batch.run(pattern, output_dir, template)
+}
+
+/// Run a `magick` command template against every file matching a glob
+///
+/// # Arguments
+///
+/// * `pattern` - Glob pattern selecting files, relative to the workspace
+/// * `template` - `magick` arguments containing `$input` and `$output` placeholders
+/// * `output_dir` - Directory the outputs are written to
+/// * `extension` - Optional extension for the outputs, e.g. `webp`
+/// * `workspace` - Optional workspace path the glob and commands are relative to
+/// * `output_policy` - How each output file is treated when it already exists
+/// * `transactional` - Stage the outputs and only move them into place if every file succeeds
+///
+/// # Returns
+///
+/// Returns per-file results, or an `OperationError` if the batch could not start
+pub fn magick_batch(
+    pattern: &str,
+    template: &str,
+    output_dir: &std::path::Path,
+    extension: Option<&str>,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+    transactional: bool,
+) -> Result<Vec<BatchFileResult>, OperationError> {
+    let command_runner = command_runner();
+    let batch = MagickBatch::new(&command_runner, workspace)
+        .with_output_policy(output_policy)
+        .with_transactional(transactional);
+    batch.run(pattern, template, output_dir, extension)
+}
+
+/// Generate a responsive set of resized web images from one source
+///
+/// # Arguments
+///
+/// * `options` - Source, output directory, widths, and formats
+/// * `workspace` - Optional workspace path commands are relative to
+///
+/// # Returns
+///
+/// Returns the generated files, or an `OperationError` on failure
+pub fn srcset(
+    options: &SrcsetOptions,
+    workspace: Option<&std::path::Path>,
+) -> Result<Vec<SrcsetFile>, OperationError> {
+    let command_runner = command_runner();
+    let srcset = Srcset::new(&command_runner, workspace);
+    srcset.run(options)
+}
+
+/// Generate iOS and/or Android app icon sets from one square source image
+///
+/// # Arguments
+///
+/// * `options` - Source image, platforms, and project directories
+/// * `workspace` - Optional workspace path the project directories are relative to
+///
+/// # Returns
+///
+/// Returns every written file, or an `OperationError` on failure
+pub fn app_icons(
+    options: &AppIconOptions,
+    workspace: Option<&std::path::Path>,
+) -> Result<Vec<AppIconFile>, OperationError> {
+    let command_runner = command_runner();
+    let icons = AppIcons::new(&command_runner, workspace);
+    icons.run(options)
+}
+
+/// Resize an image to a width and height using a fit mode
+///
+/// # Arguments
+///
+/// * `options` - The target size, fit mode, and input/output paths
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output on success, or an `OperationError` on failure
+pub fn resize(
+    options: &ResizeOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<ResolvedOutput, OperationError> {
+    let command_runner = command_runner();
+    let resize = Resize::new(&command_runner, workspace).with_output_policy(output_policy);
+    resize.run(options)
+}
+
+/// Load the magick-mcp config file
+///
+/// # Returns
+///
+/// Returns the `Config`, which is the default when no config file exists, or a `ConfigError`
+/// if the file cannot be read or parsed
+pub fn load_config() -> Result<Config, ConfigError> {
+    Config::load()
+}
+
+/// Load a named preset from the config file
+///
+/// # Arguments
+///
+/// * `name` - The preset's name, e.g. `web`
+///
+/// # Returns
+///
+/// Returns the preset, or a `ConfigError` if the config cannot be loaded or has no such preset
+pub fn load_preset(name: &str) -> Result<PresetConfig, ConfigError> {
+    Config::load()?.preset(name).cloned()
+}
+
+/// Load the `.magickmcp.toml` project config at the root of a workspace
+///
+/// # Arguments
+///
+/// * `workspace` - The workspace root
+///
+/// # Returns
+///
+/// Returns the project config, `None` if the workspace has none, or a `ConfigError` on failure
+pub fn load_project_config(
+    workspace: &std::path::Path,
+) -> Result<Option<ProjectConfig>, ConfigError> {
+    ProjectConfig::discover(workspace)
+}
+
+/// Convert an image to another format
+///
+/// # Arguments
+///
+/// * `options` - The target format, quality, and input/output paths
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written file and its size, or an `OperationError` if the format is not
+/// writable or the conversion fails
+pub fn convert_format(
+    options: &ConvertOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<ConvertedFile, OperationError> {
+    let command_runner = command_runner();
+    let convert = Convert::new(&command_runner, workspace).with_output_policy(output_policy);
+    convert.run(options)
+}
+
+/// Download, verify, and install the latest built-in functions
+///
+/// The URL and signing key come from the `[builtins]` section of the config file when set.
+///
+/// # Arguments
+///
+/// * `url` - Optional URL overriding the configured release channel
+///
+/// # Returns
+///
+/// Returns the previous and current built-in versions, or a `BuiltinsError` on failure
+pub fn update_builtins(url: Option<&str>) -> Result<BuiltinUpdate, BuiltinsError> {
+    let config = Config::load()?;
+    let command_runner = command_runner();
+    let mut updater = BuiltinUpdater::new(&command_runner);
+    if let Some(url) = url.or(config.builtins.url.as_deref()) {
+        updater = updater.with_url(url);
+    }
+    if let Some(public_key) = config.builtins.public_key.as_deref() {
+        updater = updater.with_public_key(public_key);
+    }
+    updater.update()
+}
+
+/// Overlay one image on another with a gravity, offset, blend mode, and opacity
+///
+/// # Arguments
+///
+/// * `options` - The base and overlay images, placement, blend mode, and output path
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output on success, or an `OperationError` on failure
+pub fn composite(
+    options: &CompositeOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<ResolvedOutput, OperationError> {
+    let command_runner = command_runner();
+    let composite = Composite::new(&command_runner, workspace).with_output_policy(output_policy);
+    composite.run(options)
+}
+
+/// Crop an image to a pixel or percentage region, or the largest region with an aspect ratio
+///
+/// # Arguments
+///
+/// * `options` - The region to crop and input/output paths
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output and cropped region, or an `OperationError` on failure
+pub fn crop(
+    options: &CropOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<CroppedImage, OperationError> {
+    let command_runner = command_runner();
+    let crop = Crop::new(&command_runner, workspace).with_output_policy(output_policy);
+    crop.run(options)
+}
+
+/// Generate thumbnails of an image in several sizes
+///
+/// # Arguments
+///
+/// * `options` - The source image, output directory, sizes, and format
+/// * `workspace` - Optional workspace path to set as the working directory for the commands
+///
+/// # Returns
+///
+/// Returns the generated files with their sizes, or an `OperationError` on failure
+pub fn thumbnail(
+    options: &ThumbnailOptions,
+    workspace: Option<&std::path::Path>,
+) -> Result<Vec<ThumbnailFile>, OperationError> {
+    let command_runner = command_runner();
+    let thumbnail = Thumbnail::new(&command_runner, workspace);
+    thumbnail.run(options)
+}
+
+/// Estimate the memory and time cost of a command before running it
+///
+/// # Arguments
+///
+/// * `options` - The command and, optionally, the input dimensions
+/// * `workspace` - Optional workspace path the command is relative to
+///
+/// # Returns
+///
+/// Returns the `CostEstimate` judged against the configured limits, or an `OperationError` on
+/// failure
+pub fn estimate(
+    options: &EstimateOptions,
+    workspace: Option<&std::path::Path>,
+) -> Result<CostEstimate, OperationError> {
+    let limits = Config::load()
+        .map(|config| config.limits)
+        .unwrap_or_default();
+    let command_runner = command_runner();
+    Estimator::new(&command_runner, workspace)
+        .with_limits(limits)
+        .estimate(options)
+}
+
+/// Fit an image within inline size limits, transcoding a compact preview when needed
+///
+/// # Arguments
+///
+/// * `path` - The image, relative to the workspace
+/// * `options` - Size limits and the preview format
+/// * `workspace` - Optional workspace path the image is relative to
+///
+/// # Returns
+///
+/// Returns the `InlinePreview`, or an `OperationError` if no preview fits the limits
+pub fn inline_preview(
+    path: &std::path::Path,
+    options: &InlinePreviewOptions,
+    workspace: Option<&std::path::Path>,
+) -> Result<InlinePreview, OperationError> {
+    let command_runner = command_runner();
+    InlinePreviewer::new(&command_runner, workspace).preview(path, options)
+}
+
+/// Look up documentation for a single ImageMagick option or subcommand
+///
+/// # Arguments
+///
+/// * `topic` - An option such as `-resize` or a subcommand such as `identify`
+///
+/// # Returns
+///
+/// Returns the matching usage lines and accepted values, or an `OperationError` on failure
+pub fn help_topic(topic: &str) -> Result<HelpTopic, OperationError> {
+    let command_runner = command_runner();
+    let topics = HelpTopics::new(&command_runner);
+    topics.lookup(topic)
+}
+
+/// List the image formats supported by the installed ImageMagick
+///
+/// # Returns
+///
+/// Returns each format with whether it can be read, written, and hold several frames, or an
+/// `OperationError` on failure
+pub fn list_formats() -> Result<Vec<ImageFormat>, OperationError> {
+    let command_runner = command_runner();
+    let magick_runner = feature::MagickRunner::new(&command_runner, None);
+    feature::list_formats(&magick_runner)
+}
+
+/// List the fonts available to the installed ImageMagick
+///
+/// # Returns
+///
+/// Returns each font with its family, style, weight, and file, or an `OperationError` on failure
+pub fn list_fonts() -> Result<Vec<FontInfo>, OperationError> {
+    let command_runner = command_runner();
+    let magick_runner = feature::MagickRunner::new(&command_runner, None);
+    feature::list_fonts(&magick_runner)
+}
+
+/// Write a copy of an image without EXIF, IPTC, XMP, or GPS metadata
+///
+/// # Arguments
+///
+/// * `options` - The input and output paths and whether to keep the color profile
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output with the metadata keys removed, or an `OperationError` on failure
+pub fn strip_metadata(
+    options: &StripMetadataOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<StrippedImage, OperationError> {
+    let command_runner = command_runner();
+    let strip = StripMetadata::new(&command_runner, workspace).with_output_policy(output_policy);
+    strip.run(options)
+}
+
+/// Place a logo or text watermark on an image
+///
+/// # Arguments
+///
+/// * `options` - The mark, placement, opacity, scale, and input/output paths
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output on success, or an `OperationError` on failure
+pub fn watermark(
+    options: &WatermarkOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<ResolvedOutput, OperationError> {
+    let command_runner = command_runner();
+    let watermark = Watermark::new(&command_runner, workspace).with_output_policy(output_policy);
+    watermark.run(options)
+}
+
+/// Compute per-channel histograms and statistics of an image
+///
+/// # Arguments
+///
+/// * `path` - Path to the image
+/// * `bins` - Number of histogram bins, from 1 to 256
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+///
+/// # Returns
+///
+/// Returns the statistics and histogram of each channel, or an `OperationError` on failure
+pub fn histogram(
+    path: &str,
+    bins: u32,
+    workspace: Option<&std::path::Path>,
+) -> Result<ImageHistogram, OperationError> {
+    let command_runner = command_runner();
+    let histogram = Histogram::new(&command_runner, workspace);
+    histogram.run(path, bins)
+}
+
+/// Extract the frames of a multi-frame image such as a GIF or multi-page TIFF
+///
+/// # Arguments
+///
+/// * `options` - The source image, output directory, frame range, and format
+/// * `workspace` - Optional workspace path to set as the working directory for the commands
+///
+/// # Returns
+///
+/// Returns the extracted frames in order, or an `OperationError` on failure
+pub fn extract_frames(
+    options: &FramesOptions,
+    workspace: Option<&std::path::Path>,
+) -> Result<Vec<ExtractedFrame>, OperationError> {
+    let command_runner = command_runner();
+    let frames = Frames::new(&command_runner, workspace);
+    frames.run(options)
+}
+
+/// Render the pages of a PDF as PNG or JPEG images
+///
+/// Ghostscript is checked for first, so a missing install is reported with instructions
+/// rather than as a failed command.
+///
+/// # Arguments
+///
+/// * `options` - The PDF, output directory, density, format, and page range
+/// * `workspace` - Optional workspace path. Commands run from this directory.
+///
+/// # Returns
+///
+/// Returns the rendered pages in order, or an `OperationError` on failure
+pub fn pdf_to_images(
+    options: &PdfPagesOptions,
+    workspace: Option<&std::path::Path>,
+) -> Result<Vec<RenderedPage>, OperationError> {
+    let which_checker = DefaultWhichChecker;
+    let command_runner = command_runner();
+    magick_checker(&which_checker, &command_runner)
+        .check_ghostscript()
+        .map_err(OperationError::MissingDependency)?;
+    PdfPages::new(&command_runner, workspace).run(options)
+}
+
+/// Combine images into a single PDF, one page per image in the order given
+///
+/// # Arguments
+///
+/// * `options` - The images, output path, page size, and margin
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output on success, or an `OperationError` on failure
+pub fn images_to_pdf(
+    options: &PdfBundleOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<ResolvedOutput, OperationError> {
+    let command_runner = command_runner();
+    let bundle = PdfBundle::new(&command_runner, workspace).with_output_policy(output_policy);
+    bundle.run(options)
+}
+
+/// Draw text on an image
+///
+/// # Arguments
+///
+/// * `options` - The text, its position and styling, and input/output paths
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output on success, or an `OperationError` on failure
+pub fn annotate(
+    options: &AnnotateOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<ResolvedOutput, OperationError> {
+    let command_runner = command_runner();
+    let annotate = Annotate::new(&command_runner, workspace).with_output_policy(output_policy);
+    annotate.run(options)
+}
+
+/// Add a border, frame, rounded corners, or drop shadow to an image
+///
+/// # Arguments
+///
+/// * `options` - The effects to apply and input/output paths
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output on success, or an `OperationError` on failure
+pub fn border(
+    options: &BorderOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<ResolvedOutput, OperationError> {
+    let command_runner = command_runner();
+    let border = Border::new(&command_runner, workspace).with_output_policy(output_policy);
+    border.run(options)
+}
+
+/// Encode an image at every combination of format and quality and compare the results
+///
+/// # Arguments
+///
+/// * `options` - The input image and the formats and qualities to try
+///
+/// # Returns
+///
+/// Returns the time, size, and similarity to the original of each encoding, or an
+/// `OperationError` on failure
+pub fn bench(options: &BenchOptions) -> Result<BenchReport, OperationError> {
+    let command_runner = command_runner();
+    let bench = Bench::new(&command_runner, None);
+    bench.run(options)
+}
+
+/// Rotate, mirror, or auto-orient an image
+///
+/// # Arguments
+///
+/// * `options` - The steps to apply and input/output paths
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output and its dimensions on success, or an `OperationError` on failure
+pub fn orient(
+    options: &OrientOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<OrientedImage, OperationError> {
+    let command_runner = command_runner();
+    let orient = Orient::new(&command_runner, workspace).with_output_policy(output_policy);
+    orient.run(options)
+}
+
+/// Blur, sharpen, or denoise an image
+///
+/// # Arguments
+///
+/// * `options` - The filter, its strength, and input/output paths
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output on success, or an `OperationError` on failure
+pub fn filter(
+    options: &FilterOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<ResolvedOutput, OperationError> {
+    let command_runner = command_runner();
+    let filter = Filter::new(&command_runner, workspace).with_output_policy(output_policy);
+    filter.run(options)
+}
+
+/// Compare an image against the snapshot baseline stored under a name
+///
+/// # Arguments
+///
+/// * `options` - The snapshot name, image path, threshold, and optional difference image
+/// * `workspace` - Optional workspace path the image paths are relative to
+///
+/// # Returns
+///
+/// Returns the outcome of the check, or an `OperationError` on failure
+pub fn snapshot_check(
+    options: &SnapshotCheckOptions,
+    workspace: Option<&std::path::Path>,
+) -> Result<SnapshotCheck, OperationError> {
+    let command_runner = command_runner();
+    let snapshots = Snapshots::new(&command_runner, workspace);
+    snapshots.check(options)
+}
+
+/// Store an image as the snapshot baseline for a name
+///
+/// # Arguments
+///
+/// * `name` - Name of the snapshot
+/// * `path` - Path to the image to store
+/// * `workspace` - Optional workspace path the image path is relative to
+///
+/// # Returns
+///
+/// Returns where the baseline was stored, or an `OperationError` on failure
+pub fn snapshot_update(
+    name: &str,
+    path: &str,
+    workspace: Option<&std::path::Path>,
+) -> Result<SnapshotUpdate, OperationError> {
+    let command_runner = command_runner();
+    let snapshots = Snapshots::new(&command_runner, workspace);
+    snapshots.update(name, path)
+}
+
+/// Apply an artistic effect to an image
+///
+/// # Arguments
+///
+/// * `options` - The style, its intensity, and input/output paths
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output on success, or an `OperationError` on failure
+pub fn stylize(
+    options: &StylizeOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<ResolvedOutput, OperationError> {
+    let command_runner = command_runner();
+    let stylize = Stylize::new(&command_runner, workspace).with_output_policy(output_policy);
+    stylize.run(options)
+}
+
+/// Compress an image to fit a file size at the highest quality which fits
+///
+/// # Arguments
+///
+/// * `options` - The input and output paths, size limit, and lowest acceptable quality
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the output, quality, and size, or an `OperationError` on failure
+pub fn optimize(
+    options: &OptimizeOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<OptimizedImage, OperationError> {
+    let command_runner = command_runner();
+    let optimize = Optimize::new(&command_runner, workspace).with_output_policy(output_policy);
+    optimize.run(options)
+}
+
+/// Generate a favicon set and multi-size `favicon.ico` from one source image
+///
+/// # Arguments
+///
+/// * `options` - Source image and output directory
+/// * `workspace` - Optional workspace path the output directory is relative to
+///
+/// # Returns
+///
+/// Returns every written file, or an `OperationError` on failure
+pub fn favicon(
+    options: &FaviconOptions,
+    workspace: Option<&std::path::Path>,
+) -> Result<Vec<FaviconFile>, OperationError> {
+    let command_runner = command_runner();
+    let favicon = Favicon::new(&command_runner, workspace);
+    favicon.run(options)
+}
+
+/// Summarize the ImageMagick installation and magick-mcp's config in one report
+///
+/// # Returns
+///
+/// Returns the version, delegates, formats, resource limits, and config. Parts which could not
+/// be read are described in its `errors`.
+pub fn capabilities() -> CapabilityReport {
+    let which_checker = DefaultWhichChecker;
+    let command_runner = command_runner();
+    let (config, config_error) = match Config::load() {
+        Ok(config) => (config, None),
+        Err(e) => (
+            Config::default(),
+            Some(format!("failed to load config: {e}")),
+        ),
+    };
+    let checker = magick_checker(&which_checker, &command_runner);
+    let mut report = Capabilities::new(checker, &command_runner, config).run();
+    report.errors.extend(config_error);
+    report
+}
+
+/// Compare two images, write a diff image, and check the difference against a threshold
+///
+/// # Arguments
+///
+/// * `options` - The images, diff path, metric, and threshold
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the diff file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the pass/fail verdict and the diff image, or an `OperationError` on failure
+pub fn visual_diff(
+    options: &VisualDiffOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<VisualDiff, OperationError> {
+    let command_runner = command_runner();
+    let differ = VisualDiffer::new(&command_runner, workspace).with_output_policy(output_policy);
+    differ.run(options)
+}
+
+/// Generate a new image filled with a color, gradient, plasma, or pattern
+///
+/// # Arguments
+///
+/// * `options` - The size, fill, colors, and output path
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output on success, or an `OperationError` on failure
+pub fn canvas(
+    options: &CanvasOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<ResolvedOutput, OperationError> {
+    let command_runner = command_runner();
+    let canvas = Canvas::new(&command_runner, workspace).with_output_policy(output_policy);
+    canvas.run(options)
+}
+
+/// Inspect the input of a call for consequences the caller likely did not intend
+///
+/// Used by the server's strict mode before running a tool.
+///
+/// # Arguments
+///
+/// * `options` - The input, output, and settings of the call
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+///
+/// # Returns
+///
+/// Returns the concerns found, or an `OperationError` if the input cannot be read
+pub fn strict_check(
+    options: &StrictCheckOptions,
+    workspace: Option<&std::path::Path>,
+) -> Result<Vec<StrictFinding>, OperationError> {
+    let command_runner = command_runner();
+    StrictChecker::new(&command_runner, workspace).run(options)
+}
+
+/// Crop away the uniform border around an image
+///
+/// # Arguments
+///
+/// * `options` - The input, output, and fuzz
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output with the sizes before and after trimming, or an
+/// `OperationError` on failure
+pub fn trim(
+    options: &TrimOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<Trimmed, OperationError> {
+    let command_runner = command_runner();
+    let trimmer = Trimmer::new(&command_runner, workspace).with_output_policy(output_policy);
+    trimmer.run(options)
+}
+
+/// Join images side by side or stack them into one image
+///
+/// # Arguments
+///
+/// * `options` - The inputs, output, direction, spacing, and background
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output with its dimensions, or an `OperationError` on failure
+pub fn append(
+    options: &AppendOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<AppendedImage, OperationError> {
+    let command_runner = command_runner();
+    let append = Append::new(&command_runner, workspace).with_output_policy(output_policy);
+    append.run(options)
+}
+
+/// Set or resample the density of an image for print
+///
+/// # Arguments
+///
+/// * `options` - The input, output, density, units, and whether to resample
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output with the print sizes before and after, or an
+/// `OperationError` on failure
+pub fn density(
+    options: &DensityOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<DensityChange, OperationError> {
+    let command_runner = command_runner();
+    let density = Density::new(&command_runner, workspace).with_output_policy(output_policy);
+    density.run(options)
+}
+
+/// Straighten and clean up a scanned document
+///
+/// # Arguments
+///
+/// * `options` - The input, output, and which cleanup steps to apply
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output with its dimensions and deskew angle, or an
+/// `OperationError` on failure
+pub fn clean_document(
+    options: &DocumentCleanupOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<CleanedDocument, OperationError> {
+    let command_runner = command_runner();
+    let cleanup =
+        DocumentCleanup::new(&command_runner, workspace).with_output_policy(output_policy);
+    cleanup.run(options)
+}
+
+/// Convert an image to or from HEIC or AVIF
+///
+/// The installed ImageMagick is probed for the formats first, so a missing libheif or plugin
+/// is reported with installation guidance rather than as a delegate error.
+///
+/// # Arguments
+///
+/// * `options` - The input, output, and quality
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written file, its size, and the probed support, or an `OperationError` on
+/// failure
+pub fn heif_convert(
+    options: &HeifConvertOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<HeifConversion, OperationError> {
+    let command_runner = command_runner();
+    let convert = HeifConvert::new(&command_runner, workspace).with_output_policy(output_policy);
+    convert.run(options)
+}
+
+/// Explain the arguments of one ImageMagick operator
+///
+/// # Arguments
+///
+/// * `option` - An operator such as `-distort`
+///
+/// # Returns
+///
+/// Returns the option's argument forms, accepted keywords, and examples, or an
+/// `OperationError` on failure
+pub fn option_help(option: &str) -> Result<OptionHelp, OperationError> {
+    let command_runner = command_runner();
+    OptionHelper::new(&command_runner).lookup(option)
+}
+
+/// Arrange images into a collage
+///
+/// # Arguments
+///
+/// * `options` - The images, output, layout, fit, width, gap, and background
+/// * `workspace` - Optional workspace path to set as the working directory for the command
+/// * `output_policy` - How the output file is treated when it already exists
+///
+/// # Returns
+///
+/// Returns the written output with its size and the cell of each image, or an
+/// `OperationError` on failure
+pub fn collage(
+    options: &CollageOptions,
+    workspace: Option<&std::path::Path>,
+    output_policy: OutputPolicy,
+) -> Result<Collage, OperationError> {
+    let command_runner = command_runner();
+    let maker = CollageMaker::new(&command_runner, workspace).with_output_policy(output_policy);
+    maker.run(options)
 }
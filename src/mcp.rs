@@ -1,39 +1,309 @@
+pub mod annotate_tool;
+pub mod app_icons_tool;
+pub mod append_tool;
+pub mod border_tool;
+pub mod canonical_paths;
+pub mod canvas_tool;
+pub mod capabilities_resource;
+pub mod caption_overlay_tool;
+pub mod channel_ops_tool;
+pub mod channels_combine_tool;
+pub mod channels_split_tool;
 pub mod check_tool;
+pub mod collage_tool;
+pub mod composite_tool;
+pub mod convert_format_tool;
+pub mod crop_tool;
+pub mod density_tool;
+pub mod document_cleanup_tool;
+pub mod extract_frames_tool;
+pub mod favicon_tool;
+pub mod filter_tool;
+pub mod func_delete_tool;
 pub mod func_execute_tool;
+pub mod func_get_tool;
 pub mod func_list_tool;
 pub mod func_save_tool;
+pub mod func_update_tool;
+pub mod function_catalog;
+pub mod functions_resource;
+pub mod fx_eval_tool;
+pub mod heif_convert_tool;
 pub mod help_resource;
+pub mod help_topic_tool;
+pub mod histogram_tool;
+pub mod identify_verbose_tool;
+pub mod idle;
+pub mod images_to_pdf_tool;
+pub mod inline_image;
+pub mod list_fonts_tool;
+pub mod list_formats_tool;
+pub mod magick_batch_tool;
 pub mod magick_tool;
+pub mod not_installed;
+pub mod optimize_tool;
+pub mod option_help_tool;
+pub mod organize_tool;
+pub mod orient_tool;
+pub mod pagination;
+pub mod pdf_to_images_tool;
+pub mod preflight;
+pub mod redact_tool;
+pub mod rename_by_metadata_tool;
+pub mod resize_tool;
 pub mod server;
+pub mod signal;
+pub mod snapshot_check_tool;
+pub mod snapshot_update_tool;
+pub mod srcset_tool;
+pub mod state;
+pub mod strict;
+pub mod strip_metadata_tool;
+pub mod stylize_tool;
+pub mod subscriptions;
+pub mod thumbnail_tool;
+pub mod tool_args;
+pub mod tool_call;
+pub mod trace;
+pub mod trim_tool;
+pub mod visual_diff_tool;
+pub mod watermark_batch_tool;
+pub mod watermark_tool;
+pub mod workspace;
+pub mod workspace_set_tool;
+pub mod wsl_paths;
 
+use crate::mcp::annotate_tool::annotate_tool_route;
+use crate::mcp::app_icons_tool::app_icons_tool_route;
+use crate::mcp::append_tool::append_tool_route;
+use crate::mcp::border_tool::border_tool_route;
+use crate::mcp::canonical_paths::CanonicalPathService;
+use crate::mcp::canvas_tool::canvas_tool_route;
+use crate::mcp::caption_overlay_tool::caption_overlay_tool_route;
+use crate::mcp::channel_ops_tool::channel_ops_tool_route;
+use crate::mcp::channels_combine_tool::channels_combine_tool_route;
+use crate::mcp::channels_split_tool::channels_split_tool_route;
 use crate::mcp::check_tool::check_tool_route;
+use crate::mcp::collage_tool::collage_tool_route;
+use crate::mcp::composite_tool::composite_tool_route;
+use crate::mcp::convert_format_tool::convert_format_tool_route;
+use crate::mcp::crop_tool::crop_tool_route;
+use crate::mcp::density_tool::density_tool_route;
+use crate::mcp::document_cleanup_tool::document_cleanup_tool_route;
+use crate::mcp::extract_frames_tool::extract_frames_tool_route;
+use crate::mcp::favicon_tool::favicon_tool_route;
+use crate::mcp::filter_tool::filter_tool_route;
+use crate::mcp::func_delete_tool::func_delete_tool_route;
 use crate::mcp::func_execute_tool::func_execute_tool_route;
+use crate::mcp::func_get_tool::func_get_tool_route;
 use crate::mcp::func_list_tool::func_list_tool_route;
 use crate::mcp::func_save_tool::func_save_tool_route;
+use crate::mcp::func_update_tool::func_update_tool_route;
+use crate::mcp::function_catalog::FunctionCatalogService;
+use crate::mcp::fx_eval_tool::fx_eval_tool_route;
+use crate::mcp::heif_convert_tool::heif_convert_tool_route;
+use crate::mcp::help_topic_tool::help_topic_tool_route;
+use crate::mcp::histogram_tool::histogram_tool_route;
+use crate::mcp::identify_verbose_tool::identify_verbose_tool_route;
+use crate::mcp::idle::{ActivityTracker, ActivityTrackingService, shutdown_when_idle};
+use crate::mcp::images_to_pdf_tool::images_to_pdf_tool_route;
+use crate::mcp::list_fonts_tool::list_fonts_tool_route;
+use crate::mcp::list_formats_tool::list_formats_tool_route;
+use crate::mcp::magick_batch_tool::magick_batch_tool_route;
 use crate::mcp::magick_tool::magick_tool_route;
+use crate::mcp::not_installed::{NotInstalledService, magick_tools};
+use crate::mcp::optimize_tool::optimize_tool_route;
+use crate::mcp::option_help_tool::option_help_tool_route;
+use crate::mcp::organize_tool::organize_tool_route;
+use crate::mcp::orient_tool::orient_tool_route;
+use crate::mcp::pdf_to_images_tool::pdf_to_images_tool_route;
+use crate::mcp::preflight::report_preflight;
+use crate::mcp::redact_tool::redact_tool_route;
+use crate::mcp::rename_by_metadata_tool::rename_by_metadata_tool_route;
+use crate::mcp::resize_tool::resize_tool_route;
+use crate::mcp::snapshot_check_tool::snapshot_check_tool_route;
+use crate::mcp::snapshot_update_tool::snapshot_update_tool_route;
+use crate::mcp::srcset_tool::srcset_tool_route;
+use crate::mcp::state::ServerState;
+use crate::mcp::strict::{StrictService, strict_tools};
+use crate::mcp::strip_metadata_tool::strip_metadata_tool_route;
+use crate::mcp::stylize_tool::stylize_tool_route;
+use crate::mcp::subscriptions::notify_function_changes;
+use crate::mcp::thumbnail_tool::thumbnail_tool_route;
+use crate::mcp::trace::TracingService;
+use crate::mcp::trim_tool::trim_tool_route;
+use crate::mcp::visual_diff_tool::visual_diff_tool_route;
+use crate::mcp::watermark_batch_tool::watermark_batch_tool_route;
+use crate::mcp::watermark_tool::watermark_tool_route;
+use crate::mcp::workspace::{WorkspaceService, workspace_tools};
+use crate::mcp::workspace_set_tool::workspace_set_tool_route;
+use crate::mcp::wsl_paths::PathTranslationService;
+use crate::{Config, HistoryStore};
 use rmcp::handler::server::router::Router;
-use rmcp::service::ServiceExt;
+use rmcp::service::{RoleServer, Service, ServiceExt};
 use rmcp::transport::io::stdio;
 use server::MagickServerHandler;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Options controlling the lifetime of the MCP server
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ServerOptions {
+    /// Shut the server down after this long without a request, or run until the client
+    /// disconnects when `None`
+    pub idle_timeout: Option<Duration>,
+    /// Refuse tool calls with likely-unintended consequences unless they are acknowledged
+    pub strict: bool,
+}
 
 /// Run the MCP server over stdio
-pub async fn run_server() -> Result<(), Box<dyn std::error::Error>> {
-    let handler = MagickServerHandler;
-    let router = Router::new(handler)
-        .with_tool(check_tool_route())
-        .with_tool(magick_tool_route())
-        .with_tool(func_list_tool_route())
-        .with_tool(func_save_tool_route())
-        .with_tool(func_execute_tool_route());
+pub async fn run_server(options: ServerOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let tracker = ActivityTracker::new();
+    let (service, state) = server_service(
+        options,
+        tracker.clone(),
+        crate::load_config()?,
+        crate::feature::data_dir(),
+    );
 
     // Create stdio transport
     let (stdin, stdout) = stdio();
 
-    // Serve over stdio
-    let running_service = router.serve((stdin, stdout)).await?;
+    // Serve over stdio, recording activity so an idle server can shut itself down
+    let running_service = service.serve((stdin, stdout)).await?;
+
+    tokio::spawn(signal::exit_on_signal());
+    tokio::spawn(report_preflight(running_service.peer().clone()));
+    tokio::spawn(notify_function_changes(
+        state,
+        running_service.peer().clone(),
+    ));
+
+    if let Some(timeout) = options.idle_timeout {
+        tokio::spawn(shutdown_when_idle(
+            tracker,
+            timeout,
+            running_service.cancellation_token(),
+        ));
+    }
 
     // Wait for the service to complete
     running_service.waiting().await?;
 
     Ok(())
 }
+
+/// Build the server with every layer a request passes through
+///
+/// # Arguments
+///
+/// * `options` - The server options; only `strict` affects the layers
+/// * `tracker` - Records each request, so an idle server can shut itself down
+/// * `config` - The config the server runs with
+/// * `data_dir` - Directory holding saved functions and the execution history
+///
+/// # Returns
+///
+/// Returns the service with the state its handler shares
+fn server_service(
+    options: ServerOptions,
+    tracker: ActivityTracker,
+    config: Config,
+    data_dir: Option<PathBuf>,
+) -> (impl Service<RoleServer>, Arc<ServerState>) {
+    let handler = MagickServerHandler::new(config, data_dir.clone());
+    let state = handler.state().clone();
+    let workspace_defaults = handler.state().workspace_defaults().clone();
+    let router = Router::new(handler)
+        .with_tool(check_tool_route())
+        .with_tool(magick_tool_route())
+        .with_tool(func_list_tool_route())
+        .with_tool(func_get_tool_route())
+        .with_tool(func_save_tool_route())
+        .with_tool(func_update_tool_route())
+        .with_tool(func_execute_tool_route())
+        .with_tool(func_delete_tool_route())
+        .with_tool(caption_overlay_tool_route())
+        .with_tool(redact_tool_route())
+        .with_tool(identify_verbose_tool_route())
+        .with_tool(channels_split_tool_route())
+        .with_tool(channels_combine_tool_route())
+        .with_tool(fx_eval_tool_route())
+        .with_tool(rename_by_metadata_tool_route())
+        .with_tool(organize_tool_route())
+        .with_tool(watermark_batch_tool_route())
+        .with_tool(srcset_tool_route())
+        .with_tool(app_icons_tool_route())
+        .with_tool(resize_tool_route())
+        .with_tool(convert_format_tool_route())
+        .with_tool(composite_tool_route())
+        .with_tool(crop_tool_route())
+        .with_tool(thumbnail_tool_route())
+        .with_tool(magick_batch_tool_route())
+        .with_tool(help_topic_tool_route())
+        .with_tool(list_formats_tool_route())
+        .with_tool(list_fonts_tool_route())
+        .with_tool(strip_metadata_tool_route())
+        .with_tool(watermark_tool_route())
+        .with_tool(histogram_tool_route())
+        .with_tool(extract_frames_tool_route())
+        .with_tool(pdf_to_images_tool_route())
+        .with_tool(images_to_pdf_tool_route())
+        .with_tool(annotate_tool_route())
+        .with_tool(border_tool_route())
+        .with_tool(orient_tool_route())
+        .with_tool(filter_tool_route())
+        .with_tool(snapshot_check_tool_route())
+        .with_tool(snapshot_update_tool_route())
+        .with_tool(stylize_tool_route())
+        .with_tool(optimize_tool_route())
+        .with_tool(favicon_tool_route())
+        .with_tool(workspace_set_tool_route())
+        .with_tool(visual_diff_tool_route())
+        .with_tool(canvas_tool_route())
+        .with_tool(trim_tool_route())
+        .with_tool(append_tool_route())
+        .with_tool(channel_ops_tool_route())
+        .with_tool(density_tool_route())
+        .with_tool(document_cleanup_tool_route())
+        .with_tool(heif_convert_tool_route())
+        .with_tool(option_help_tool_route())
+        .with_tool(collage_tool_route());
+    let tools = router.tool_router.list_all();
+    let workspace_tools = workspace_tools(&tools);
+    let magick_tools = magick_tools(&tools);
+    let strict_tools = if options.strict {
+        strict_tools(&tools)
+    } else {
+        HashSet::new()
+    };
+
+    let service = ActivityTrackingService::new(
+        TracingService::new(
+            PathTranslationService::new(CanonicalPathService::new(WorkspaceService::new(
+                NotInstalledService::new(
+                    StrictService::new(
+                        FunctionCatalogService::new(
+                            router,
+                            workspace_defaults.clone(),
+                            data_dir.clone(),
+                        ),
+                        strict_tools,
+                    ),
+                    magick_tools,
+                    {
+                        let state = state.clone();
+                        move || state.check_report()
+                    },
+                ),
+                workspace_defaults,
+                workspace_tools,
+            ))),
+            HistoryStore::in_data_dir(data_dir),
+        ),
+        tracker,
+    );
+    (service, state)
+}
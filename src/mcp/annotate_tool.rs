@@ -0,0 +1,140 @@
+use crate::AnnotateOptions;
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    optional_f64, optional_i32, optional_str, output_policy, required_str, workspace,
+};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Draw text on an image
+async fn annotate_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output = required_str(arguments, "output")?;
+    let text = required_str(arguments, "text")?;
+
+    let mut options = AnnotateOptions::new(input, output, text);
+    if let Some(gravity) = optional_str(arguments, "gravity") {
+        options.gravity = gravity.to_string();
+    }
+    options.x = optional_i32(arguments, "x")?.unwrap_or(0);
+    options.y = optional_i32(arguments, "y")?.unwrap_or(0);
+    options.angle = optional_f64(arguments, "angle")?.unwrap_or(0.0);
+    options.font = optional_str(arguments, "font").map(str::to_string);
+    if let Some(point_size) = optional_f64(arguments, "point_size")? {
+        options.point_size = point_size;
+    }
+    if let Some(fill) = optional_str(arguments, "fill") {
+        options.fill = fill.to_string();
+    }
+    options.stroke = optional_str(arguments, "stroke").map(str::to_string);
+    if let Some(stroke_width) = optional_f64(arguments, "stroke_width")? {
+        options.stroke_width = stroke_width;
+    }
+    options.background = optional_str(arguments, "background").map(str::to_string);
+
+    match crate::annotate(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(output) => {
+            let result = json!({
+                "output": output.path.to_string_lossy(),
+                "skipped": output.skipped,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Annotate failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the annotate tool route
+pub fn annotate_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the source image."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the annotated image to."
+            },
+            "text": {
+                "type": "string",
+                "description": "Text to draw, exactly as given. Spaces, quotes, and newlines need no escaping."
+            },
+            "gravity": {
+                "type": "string",
+                "enum": ["NorthWest", "North", "NorthEast", "West", "Center", "East", "SouthWest", "South", "SouthEast"],
+                "description": "Edge or corner x and y are measured from. Defaults to NorthWest, the top left corner."
+            },
+            "x": {
+                "type": "integer",
+                "description": "Horizontal offset in pixels from the gravity edge. Defaults to 0."
+            },
+            "y": {
+                "type": "integer",
+                "description": "Vertical offset in pixels from the gravity edge. Defaults to 0."
+            },
+            "angle": {
+                "type": "number",
+                "description": "Rotation of the text in degrees, clockwise. Defaults to 0."
+            },
+            "font": {
+                "type": "string",
+                "description": "Font name as listed by list_fonts, or path to a font file."
+            },
+            "point_size": {
+                "type": "number",
+                "description": "Text size in points. Defaults to 24."
+            },
+            "fill": {
+                "type": "string",
+                "description": "Text color. Defaults to black."
+            },
+            "stroke": {
+                "type": "string",
+                "description": "Outline color. No outline when omitted."
+            },
+            "stroke_width": {
+                "type": "number",
+                "description": "Outline width in pixels when stroke is set. Defaults to 1."
+            },
+            "background": {
+                "type": "string",
+                "description": "Color of a box drawn behind the text, e.g. '#00000080' for translucent black. No box when omitted."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when the output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen name is reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["input", "output", "text"]
+    });
+    let tool = Tool::new(
+        "annotate",
+        "Draw text on an image at a position, with a font, point size, fill and outline colors, and an optional background box. Use this instead of the magick tool for text, which does not need quoting here.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(annotate_tool(context)))
+}
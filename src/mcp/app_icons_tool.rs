@@ -0,0 +1,90 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{invalid_params, optional_str, required_str, workspace};
+use crate::{AppIconOptions, IconPlatform};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Generate platform app icon sets from one source image
+async fn app_icons_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+
+    let mut options = AppIconOptions::new(input);
+    if let Some(platform) = optional_str(arguments, "platform") {
+        options.platform =
+            IconPlatform::parse(platform).map_err(|e| invalid_params(e.to_string()))?;
+    }
+    if let Some(ios_dir) = optional_str(arguments, "ios_dir") {
+        options.ios_dir = ios_dir.to_string();
+    }
+    if let Some(android_dir) = optional_str(arguments, "android_dir") {
+        options.android_dir = android_dir.to_string();
+    }
+
+    match crate::app_icons(&options, workspace(arguments)) {
+        Ok(files) => {
+            let files: Vec<_> = files
+                .iter()
+                .map(|file| {
+                    json!({
+                        "path": file.path.to_string_lossy(),
+                        "size": file.size
+                    })
+                })
+                .collect();
+            let result = json!({
+                "files": files,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("App icon generation failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the app_icons tool route
+pub fn app_icons_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to a square source image, at least 1024x1024."
+            },
+            "platform": {
+                "type": "string",
+                "enum": ["ios", "android", "both"],
+                "description": "Platforms to generate icons for. Defaults to both."
+            },
+            "ios_dir": {
+                "type": "string",
+                "description": "AppIcon.appiconset directory for iOS icons. Defaults to 'ios/AppIcon.appiconset'."
+            },
+            "android_dir": {
+                "type": "string",
+                "description": "Android res directory the mipmap-* directories are written into. Defaults to 'android/app/src/main/res'."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Project root the output directories are relative to."
+            }
+        },
+        "required": ["input"]
+    });
+    let tool = Tool::new(
+        "app_icons",
+        "Generate the full iOS AppIcon set (with Contents.json) and Android mipmap launcher icons from one 1024px square source, writing them into the project structure.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(app_icons_tool(context)))
+}
@@ -0,0 +1,102 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    invalid_params, optional_str, optional_u32, output_policy, required_str, required_str_array,
+    workspace,
+};
+use crate::{AppendDirection, AppendOptions};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Join images side by side or stack them into one image
+async fn append_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let inputs = required_str_array(arguments, "inputs")?;
+    let output = required_str(arguments, "output")?;
+
+    let mut options = AppendOptions::new(inputs, output);
+    if let Some(direction) = optional_str(arguments, "direction") {
+        options.direction =
+            AppendDirection::parse(direction).map_err(|e| invalid_params(e.to_string()))?;
+    }
+    options.spacing = optional_u32(arguments, "spacing")?.unwrap_or(0);
+    options.background = optional_str(arguments, "background").map(str::to_string);
+
+    match crate::append(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(appended) => {
+            let result = json!({
+                "output": appended.output.path.to_string_lossy(),
+                "skipped": appended.output.skipped,
+                "images": options.inputs.len(),
+                "width": appended.dimensions.map(|d| d.width),
+                "height": appended.dimensions.map(|d| d.height),
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Append failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the append tool route
+pub fn append_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "inputs": {
+                "type": "array",
+                "items": { "type": "string" },
+                "minItems": 2,
+                "description": "Paths to the images, in the order they are joined."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the combined image to."
+            },
+            "direction": {
+                "type": "string",
+                "enum": ["horizontal", "vertical"],
+                "description": "horizontal: side by side, left to right (default). vertical: stacked top to bottom."
+            },
+            "spacing": {
+                "type": "integer",
+                "minimum": 0,
+                "description": "Pixels of background between neighboring images. Defaults to 0."
+            },
+            "background": {
+                "type": "string",
+                "description": "Color of the spacing and of the area beside images smaller than the largest, e.g. 'white', '#eeeeee', or 'none' for transparent. Defaults to white."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when the output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen name is reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["inputs", "output"]
+    });
+    let tool = Tool::new(
+        "append",
+        "Concatenate images side by side or stack them top to bottom into one image, with optional spacing and background color. Returns the combined width and height.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(append_tool(context)))
+}
@@ -0,0 +1,149 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    optional_bool, optional_f64, optional_i32, optional_str, optional_u32, output_policy,
+    required_str, workspace,
+};
+use crate::{BorderOptions, DropShadow};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Add a border, frame, rounded corners, or drop shadow to an image
+async fn border_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output = required_str(arguments, "output")?;
+
+    let mut options = BorderOptions::new(input, output);
+    options.corner_radius = optional_u32(arguments, "corner_radius")?;
+    options.border_width = optional_u32(arguments, "border_width")?.unwrap_or(0);
+    if let Some(border_color) = optional_str(arguments, "border_color") {
+        options.border_color = border_color.to_string();
+    }
+    options.frame_width = optional_u32(arguments, "frame_width")?.unwrap_or(0);
+    if let Some(frame_color) = optional_str(arguments, "frame_color") {
+        options.frame_color = frame_color.to_string();
+    }
+    if optional_bool(arguments, "shadow").unwrap_or(false) {
+        let mut shadow = DropShadow::default();
+        if let Some(color) = optional_str(arguments, "shadow_color") {
+            shadow.color = color.to_string();
+        }
+        if let Some(opacity) = optional_u32(arguments, "shadow_opacity")? {
+            shadow.opacity = opacity;
+        }
+        if let Some(blur) = optional_f64(arguments, "shadow_blur")? {
+            shadow.blur = blur;
+        }
+        if let Some(x) = optional_i32(arguments, "shadow_x")? {
+            shadow.x = x;
+        }
+        if let Some(y) = optional_i32(arguments, "shadow_y")? {
+            shadow.y = y;
+        }
+        options.shadow = Some(shadow);
+    }
+
+    match crate::border(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(output) => {
+            let result = json!({
+                "output": output.path.to_string_lossy(),
+                "skipped": output.skipped,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Border failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the border tool route
+pub fn border_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the source image."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the result to. Use a format with transparency such as PNG or WebP for rounded corners and shadows."
+            },
+            "corner_radius": {
+                "type": "integer",
+                "description": "Round the image's corners to this radius in pixels. The corners become transparent."
+            },
+            "border_width": {
+                "type": "integer",
+                "description": "Width in pixels of a solid border around the image. Defaults to 0, no border."
+            },
+            "border_color": {
+                "type": "string",
+                "description": "Color of the solid border. Defaults to black."
+            },
+            "frame_width": {
+                "type": "integer",
+                "description": "Width in pixels of a bevelled, picture-frame style border. Defaults to 0, no frame."
+            },
+            "frame_color": {
+                "type": "string",
+                "description": "Color of the bevelled frame. Defaults to gray."
+            },
+            "shadow": {
+                "type": "boolean",
+                "description": "Cast a drop shadow behind the image, growing the canvas to fit it. Defaults to false."
+            },
+            "shadow_color": {
+                "type": "string",
+                "description": "Shadow color. Defaults to black."
+            },
+            "shadow_opacity": {
+                "type": "integer",
+                "description": "Shadow opacity from 0 to 100. Defaults to 60."
+            },
+            "shadow_blur": {
+                "type": "number",
+                "description": "Softness of the shadow's edge as a blur sigma in pixels. Defaults to 4."
+            },
+            "shadow_x": {
+                "type": "integer",
+                "description": "Pixels the shadow is moved right, negative for left. Defaults to 6."
+            },
+            "shadow_y": {
+                "type": "integer",
+                "description": "Pixels the shadow is moved down, negative for up. Defaults to 6."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when the output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen name is reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["input", "output"]
+    });
+    let tool = Tool::new(
+        "border",
+        "Add a solid border, a bevelled frame, rounded corners, and/or a drop shadow to an image. Effects combine in the order corners, border, frame, shadow.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(border_tool(context)))
+}
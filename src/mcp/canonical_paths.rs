@@ -0,0 +1,131 @@
+use crate::feature::canonical_path;
+use crate::mcp::trace::with_structured_fields;
+use rmcp::ErrorData;
+use rmcp::model::{ClientNotification, ClientRequest, ServerInfo, ServerResult};
+use rmcp::service::{NotificationContext, RequestContext, RoleServer, Service};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Service wrapper which reports every file in tool results by its canonical path
+///
+/// Tools report paths as the caller wrote them, often relative to the workspace. Every string
+/// in a structured result which names an existing file or directory is replaced with its
+/// absolute path, with symlinks resolved and without the `\\?\` prefix Windows adds, so later
+/// calls and other tools can reference it regardless of their working directory. Relative
+/// paths are resolved against the result's `workspace`, or the server's current directory.
+pub struct CanonicalPathService<S> {
+    inner: S,
+}
+
+impl<S> CanonicalPathService<S> {
+    /// Wrap a service so the paths in its tool results are canonicalized
+    pub fn new(inner: S) -> Self {
+        CanonicalPathService { inner }
+    }
+}
+
+impl<S: Service<RoleServer>> Service<RoleServer> for CanonicalPathService<S> {
+    async fn handle_request(
+        &self,
+        request: ClientRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, ErrorData> {
+        match self.inner.handle_request(request, context).await? {
+            ServerResult::CallToolResult(result) => {
+                let Some(Value::Object(structured)) = &result.structured_content else {
+                    return Ok(ServerResult::CallToolResult(result));
+                };
+                let base = structured
+                    .get("workspace")
+                    .and_then(Value::as_str)
+                    .map(PathBuf::from)
+                    .or_else(|| std::env::current_dir().ok())
+                    .unwrap_or_default();
+                let fields: Vec<(String, Value)> = structured
+                    .iter()
+                    .map(|(name, value)| {
+                        let mut value = value.clone();
+                        canonicalize_value(&mut value, &base);
+                        (name.clone(), value)
+                    })
+                    .collect();
+                Ok(ServerResult::CallToolResult(with_structured_fields(
+                    result,
+                    fields
+                        .iter()
+                        .map(|(name, value)| (name.as_str(), value.clone())),
+                )))
+            }
+            other => Ok(other),
+        }
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ClientNotification,
+        context: NotificationContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.inner.handle_notification(notification, context).await
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        self.inner.get_info()
+    }
+}
+
+/// Replace every string in a value which names an existing path with its canonical form
+fn canonicalize_value(value: &mut Value, base: &Path) {
+    match value {
+        Value::String(text) => {
+            if let Some(canonical) = canonical_path(text, base) {
+                *text = canonical.to_string_lossy().into_owned();
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                canonicalize_value(item, base);
+            }
+        }
+        Value::Object(fields) => {
+            for field in fields.values_mut() {
+                canonicalize_value(field, base);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_canonicalize_value_rewrites_existing_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("frames")).unwrap();
+        std::fs::write(dir.path().join("out.png"), b"").unwrap();
+        std::fs::write(dir.path().join("frames/0.png"), b"").unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let mut result = json!({
+            "output": "./out.png",
+            "frames": ["frames/0.png", "frames/1.png"],
+            "format": "png",
+            "width": 64,
+            "error": ""
+        });
+
+        canonicalize_value(&mut result, dir.path());
+
+        assert_eq!(
+            result,
+            json!({
+                "output": root.join("out.png").to_string_lossy(),
+                "frames": [root.join("frames/0.png").to_string_lossy(), "frames/1.png"],
+                "format": "png",
+                "width": 64,
+                "error": ""
+            })
+        );
+    }
+}
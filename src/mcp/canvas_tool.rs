@@ -0,0 +1,127 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    invalid_params, optional_f64, optional_str, optional_u32, output_policy, required_str,
+    workspace,
+};
+use crate::{CanvasFill, CanvasOptions};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Generate a new image filled with a color, gradient, plasma, or pattern
+async fn canvas_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let output = required_str(arguments, "output")?;
+    let width = optional_u32(arguments, "width")?
+        .ok_or_else(|| invalid_params("Missing required parameter: width"))?;
+    let height = optional_u32(arguments, "height")?
+        .ok_or_else(|| invalid_params("Missing required parameter: height"))?;
+    let fill = match optional_str(arguments, "fill") {
+        Some(fill) => CanvasFill::parse(fill).map_err(|e| invalid_params(e.to_string()))?,
+        None => CanvasFill::Solid,
+    };
+
+    let mut options = CanvasOptions::new(output, width, height, fill);
+    options.color = optional_str(arguments, "color").map(str::to_string);
+    options.from = optional_str(arguments, "from").map(str::to_string);
+    options.to = optional_str(arguments, "to").map(str::to_string);
+    options.angle = optional_f64(arguments, "angle")?;
+    options.pattern = optional_str(arguments, "pattern").map(str::to_string);
+    options.seed = optional_u32(arguments, "seed")?;
+
+    match crate::canvas(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(output) => {
+            let result = json!({
+                "output": output.path.to_string_lossy(),
+                "skipped": output.skipped,
+                "width": width,
+                "height": height,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Canvas failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the canvas tool route
+pub fn canvas_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "output": {
+                "type": "string",
+                "description": "Path to write the new image to. Its extension picks the format."
+            },
+            "width": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Width in pixels."
+            },
+            "height": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Height in pixels."
+            },
+            "fill": {
+                "type": "string",
+                "enum": ["solid", "gradient", "radial_gradient", "plasma", "pattern"],
+                "description": "solid: a single color (default). gradient: a linear gradient from one color to another. radial_gradient: a gradient from the center outwards. plasma: random fractal clouds. pattern: a tiled built-in pattern."
+            },
+            "color": {
+                "type": "string",
+                "description": "Color of a solid fill, e.g. 'white', '#336699', or 'none' for transparent. Defaults to white."
+            },
+            "from": {
+                "type": "string",
+                "description": "Start color of a gradient or plasma, or the color replacing a pattern's black. Gradients default to white."
+            },
+            "to": {
+                "type": "string",
+                "description": "End color of a gradient or plasma, or the color replacing a pattern's white. Gradients default to black."
+            },
+            "angle": {
+                "type": "number",
+                "description": "Direction of a linear gradient in degrees. 0 runs top to bottom (the default) and 90 left to right."
+            },
+            "pattern": {
+                "type": "string",
+                "description": "Built-in pattern for the pattern fill, e.g. 'checkerboard', 'bricks', 'hexagons', 'circles', 'crosshatch', 'horizontal', or 'vertical'."
+            },
+            "seed": {
+                "type": "integer",
+                "description": "Random seed making a plasma fill reproducible."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when the output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen name is reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["output", "width", "height"]
+    });
+    let tool = Tool::new(
+        "canvas",
+        "Create a new image of a given size filled with a solid color, linear or radial gradient, plasma, or tiled pattern, e.g. for placeholder assets and backgrounds.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(canvas_tool(context)))
+}
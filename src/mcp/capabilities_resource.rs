@@ -0,0 +1,29 @@
+use rmcp::model::Resource;
+
+/// URI for the capabilities resource
+pub const CAPABILITIES_RESOURCE_URI: &str = "magick://capabilities";
+
+/// Create the capabilities resource metadata
+pub fn capabilities_resource() -> Resource {
+    Resource::new(
+        rmcp::model::RawResource {
+            uri: CAPABILITIES_RESOURCE_URI.to_string(),
+            name: "Capabilities".to_string(),
+            title: Some("ImageMagick and magick-mcp Capabilities".to_string()),
+            description: Some("One JSON document with the ImageMagick version and delegates, the formats it can read and write, its resource limits from policy.xml, and the limits magick-mcp is configured with. Read it before planning work to avoid unsupported formats and oversized commands.".to_string()),
+            mime_type: Some("application/json".to_string()),
+            size: None,
+            icons: None,
+        },
+        None,
+    )
+}
+
+/// Read the capabilities resource contents
+///
+/// # Returns
+///
+/// Returns the capability report as JSON, or an error if it cannot be serialized
+pub fn read_capabilities_resource() -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(&crate::capabilities())
+}
@@ -0,0 +1,108 @@
+use crate::CaptionOverlayOptions;
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{optional_f64, optional_str, output_policy, required_str, workspace};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Overlay meme-style top/bottom captions on an image
+async fn caption_overlay_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output = required_str(arguments, "output")?;
+
+    let mut options = CaptionOverlayOptions::new(input, output);
+    options.top_text = optional_str(arguments, "top_text").map(str::to_string);
+    options.bottom_text = optional_str(arguments, "bottom_text").map(str::to_string);
+    options.font = optional_str(arguments, "font").map(str::to_string);
+    if let Some(fill) = optional_str(arguments, "fill") {
+        options.fill = fill.to_string();
+    }
+    if let Some(stroke) = optional_str(arguments, "stroke") {
+        options.stroke = stroke.to_string();
+    }
+    options.stroke_width = optional_f64(arguments, "stroke_width")?;
+
+    match crate::caption_overlay(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(output) => {
+            let result = json!({
+                "output": output.path.to_string_lossy(),
+                "skipped": output.skipped,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Caption overlay failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the caption_overlay tool route
+pub fn caption_overlay_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the source image."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the captioned image to."
+            },
+            "top_text": {
+                "type": "string",
+                "description": "Text to place along the top edge. Long text is wrapped automatically."
+            },
+            "bottom_text": {
+                "type": "string",
+                "description": "Text to place along the bottom edge. Long text is wrapped automatically."
+            },
+            "font": {
+                "type": "string",
+                "description": "Font name or path to a font file (e.g. 'Impact')."
+            },
+            "fill": {
+                "type": "string",
+                "description": "Text color. Defaults to white."
+            },
+            "stroke": {
+                "type": "string",
+                "description": "Outline color. Defaults to black."
+            },
+            "stroke_width": {
+                "type": "number",
+                "description": "Outline width in pixels. Defaults to a width scaled to the image size."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when an output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen names are reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["input", "output"]
+    });
+    let tool = Tool::new(
+        "caption_overlay",
+        "Place top and/or bottom caption text on an image with automatic word-wrapping, font sizing, and an outline for readability.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(caption_overlay_tool(context)))
+}
@@ -0,0 +1,151 @@
+use crate::RgbaChannel;
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    invalid_params, optional_str_array, output_policy, required_str, workspace,
+};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, JsonObject, Tool};
+use serde_json::{Value, json};
+
+/// Extract, swap, or mask the RGBA channels of an image
+async fn channel_ops_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let operation = required_str(arguments, "operation")?;
+    let input = required_str(arguments, "input")?;
+    let output = required_str(arguments, "output")?;
+    let channels = channels(arguments)?;
+    let workspace = workspace(arguments);
+    let output_policy = output_policy(&context)?;
+
+    let result = match operation {
+        "extract" => {
+            let channels = if channels.is_empty() {
+                RgbaChannel::ALL.to_vec()
+            } else {
+                channels
+            };
+            crate::channels_extract(input, &channels, output, workspace, output_policy).map(
+                |files| {
+                    json!({
+                        "operation": operation,
+                        "files": files
+                            .iter()
+                            .map(|file| json!({ "channel": file.channel, "path": file.path }))
+                            .collect::<Vec<_>>(),
+                        "success": true
+                    })
+                },
+            )
+        }
+        "swap" => {
+            let [first, second] = channels[..] else {
+                return Err(invalid_params(
+                    "swap takes exactly two channels".to_string(),
+                ));
+            };
+            crate::channels_swap(input, first, second, output, workspace, output_policy).map(
+                |written| {
+                    json!({
+                        "operation": operation,
+                        "output": written.path.to_string_lossy(),
+                        "skipped": written.skipped,
+                        "success": true
+                    })
+                },
+            )
+        }
+        "alpha_mask" => {
+            let mask = required_str(arguments, "mask")?;
+            crate::channels_apply_alpha_mask(input, mask, output, workspace, output_policy).map(
+                |written| {
+                    json!({
+                        "operation": operation,
+                        "output": written.path.to_string_lossy(),
+                        "skipped": written.skipped,
+                        "success": true
+                    })
+                },
+            )
+        }
+        other => {
+            return Err(invalid_params(format!(
+                "unknown operation '{other}', expected extract, swap, or alpha_mask"
+            )));
+        }
+    };
+
+    match result {
+        Ok(result) => Ok(CallToolResult::structured(result)),
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Channel operation failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// The channels named in the `channels` argument, empty when it is absent
+fn channels(arguments: &Option<JsonObject>) -> Result<Vec<RgbaChannel>, ErrorData> {
+    optional_str_array(arguments, "channels")?
+        .unwrap_or_default()
+        .iter()
+        .map(|name| RgbaChannel::parse(name).map_err(|e| invalid_params(e.to_string())))
+        .collect()
+}
+
+/// Create the channel_ops tool route
+pub fn channel_ops_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: Value = json!({
+        "type": "object",
+        "properties": {
+            "operation": {
+                "type": "string",
+                "enum": ["extract", "swap", "alpha_mask"],
+                "description": "extract writes each chosen channel to its own grayscale file, swap exchanges two channels, and alpha_mask replaces the alpha channel with the brightness of a mask image."
+            },
+            "input": {
+                "type": "string",
+                "description": "Path to the source image."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the result to. For extract, it must contain {channel}, replaced by each channel's name, e.g. logo-{channel}.png."
+            },
+            "channels": {
+                "type": "array",
+                "items": { "type": "string", "enum": ["red", "green", "blue", "alpha"] },
+                "description": "For extract, the channels to write, all four by default; alpha is written as a mask, white where opaque. For swap, the two channels to exchange, e.g. [\"red\", \"blue\"]."
+            },
+            "mask": {
+                "type": "string",
+                "description": "For alpha_mask, path to the mask image. White becomes opaque and black transparent; it should be the same size as the input."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when an output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen names are reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["operation", "input", "output"]
+    });
+    let tool = Tool::new(
+        "channel_ops",
+        "Work with the red, green, blue, and alpha channels of an image: extract channels to separate grayscale files, swap two channels, or apply an alpha mask from another image.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(channel_ops_tool(context)))
+}
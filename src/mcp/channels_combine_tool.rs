@@ -0,0 +1,90 @@
+use crate::ChannelColorspace;
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    invalid_params, optional_str, output_policy, required_str, required_str_array, workspace,
+};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Combine per-channel images into one image
+async fn channels_combine_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let inputs = required_str_array(arguments, "inputs")?;
+    let output = required_str(arguments, "output")?;
+    let colorspace =
+        ChannelColorspace::parse(optional_str(arguments, "colorspace").unwrap_or("sRGB"))
+            .map_err(|e| invalid_params(e.to_string()))?;
+
+    match crate::channels_combine(
+        &inputs,
+        colorspace,
+        output,
+        workspace(arguments),
+        output_policy(&context)?,
+    ) {
+        Ok(output) => {
+            let result = json!({
+                "output": output.path.to_string_lossy(),
+                "skipped": output.skipped,
+                "colorspace": colorspace.magick_name(),
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Channel combine failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the channels_combine tool route
+pub fn channels_combine_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "inputs": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Grayscale channel images in colorspace order (e.g. R, G, B), optionally followed by an alpha image."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the combined image to."
+            },
+            "colorspace": {
+                "type": "string",
+                "enum": ["sRGB", "CMYK", "Lab", "HSL", "YCbCr"],
+                "description": "Colorspace the channels belong to. Defaults to sRGB."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when an output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen names are reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["inputs", "output"]
+    });
+    let tool = Tool::new(
+        "channels_combine",
+        "Combine grayscale channel images back into a single image. The number of inputs is validated against the colorspace.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(channels_combine_tool(context)))
+}
@@ -0,0 +1,98 @@
+use crate::ChannelColorspace;
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    invalid_params, optional_bool, optional_str, output_policy, required_str, workspace,
+};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Separate an image into per-channel files
+async fn channels_split_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output_pattern = required_str(arguments, "output_pattern")?;
+    let colorspace =
+        ChannelColorspace::parse(optional_str(arguments, "colorspace").unwrap_or("sRGB"))
+            .map_err(|e| invalid_params(e.to_string()))?;
+    let include_alpha = optional_bool(arguments, "include_alpha").unwrap_or(false);
+
+    match crate::channels_split(
+        input,
+        colorspace,
+        output_pattern,
+        include_alpha,
+        workspace(arguments),
+        output_policy(&context)?,
+    ) {
+        Ok(files) => {
+            let channels: Vec<_> = files
+                .iter()
+                .map(|f| json!({ "channel": f.channel, "path": f.path }))
+                .collect();
+            let result = json!({
+                "channels": channels,
+                "colorspace": colorspace.magick_name(),
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Channel split failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the channels_split tool route
+pub fn channels_split_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the source image."
+            },
+            "output_pattern": {
+                "type": "string",
+                "description": "Output path containing %d, which is replaced by the channel index (e.g. 'plate-%d.tif')."
+            },
+            "colorspace": {
+                "type": "string",
+                "enum": ["sRGB", "CMYK", "Lab", "HSL", "YCbCr"],
+                "description": "Colorspace to separate in. Defaults to sRGB."
+            },
+            "include_alpha": {
+                "type": "boolean",
+                "description": "Also write the alpha channel as the last file, white where the image is opaque. Defaults to false."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when an output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen names are reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["input", "output_pattern"]
+    });
+    let tool = Tool::new(
+        "channels_split",
+        "Separate an image into one grayscale file per channel (R/G/B, C/M/Y/K, Lab, HSL, or YCbCr, optionally alpha). Returns which file holds which channel.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(channels_split_tool(context)))
+}
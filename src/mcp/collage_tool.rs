@@ -0,0 +1,136 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    invalid_params, optional_str, optional_u32, output_policy, required_str, required_str_array,
+    workspace,
+};
+use crate::{CollageFit, CollageLayout, CollageOptions};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::{Value, json};
+
+/// Arrange images into a collage
+async fn collage_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let inputs = required_str_array(arguments, "inputs")?;
+    let output = required_str(arguments, "output")?;
+
+    let mut options = CollageOptions::new(inputs, output);
+    if let Some(layout) = optional_str(arguments, "layout") {
+        options.layout = CollageLayout::parse(layout).map_err(|e| invalid_params(e.to_string()))?;
+    }
+    if let Some(fit) = optional_str(arguments, "fit") {
+        options.fit = CollageFit::parse(fit).map_err(|e| invalid_params(e.to_string()))?;
+    }
+    if let Some(width) = optional_u32(arguments, "width")? {
+        options.width = width;
+    }
+    if let Some(gap) = optional_u32(arguments, "gap")? {
+        options.gap = gap;
+    }
+    if let Some(background) = optional_str(arguments, "background") {
+        options.background = background.to_string();
+    }
+
+    match crate::collage(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(collage) => {
+            let cells: Vec<_> = collage
+                .cells
+                .iter()
+                .map(|cell| {
+                    json!({
+                        "input": cell.input,
+                        "x": cell.x,
+                        "y": cell.y,
+                        "width": cell.width,
+                        "height": cell.height
+                    })
+                })
+                .collect();
+            let result = json!({
+                "output": collage.output.path.to_string_lossy(),
+                "skipped": collage.output.skipped,
+                "width": collage.width,
+                "height": collage.height,
+                "cells": cells,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Collage failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the collage tool route
+pub fn collage_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: Value = json!({
+        "type": "object",
+        "properties": {
+            "inputs": {
+                "type": "array",
+                "items": { "type": "string" },
+                "minItems": 2,
+                "maxItems": 9,
+                "description": "Paths to 2 to 9 images, in the order they are placed: left to right, then top to bottom."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the collage to."
+            },
+            "layout": {
+                "type": "string",
+                "enum": ["grid", "hero_strip", "side_by_side"],
+                "description": "grid (the default) places square cells in rows and columns with a short last row centered, hero_strip shows the first image full width at 16:9 above a row of the others, and side_by_side places every image in one row of squares."
+            },
+            "fit": {
+                "type": "string",
+                "enum": ["fill", "fit"],
+                "description": "fill (the default) covers each cell, cropping from the center; fit shows each image whole with background beside it."
+            },
+            "width": {
+                "type": "integer",
+                "minimum": 1,
+                "maximum": 16384,
+                "description": "Width of the collage in pixels, 1200 by default and at most 16384. The height follows from the layout."
+            },
+            "gap": {
+                "type": "integer",
+                "minimum": 0,
+                "description": "Pixels between cells and around the edge, 10 by default."
+            },
+            "background": {
+                "type": "string",
+                "description": "Color of the gaps and of the space beside fitted images, e.g. 'white' (the default), '#222', or 'none' for transparent."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when the output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen name is reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["inputs", "output"]
+    });
+    let tool = Tool::new(
+        "collage",
+        "Arrange 2 to 9 images into a collage using a grid, hero_strip, or side_by_side layout, with a configurable width, gap, and background. Each image is resized and cropped or fitted into its cell. Returns the collage size and each image's cell.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(collage_tool(context)))
+}
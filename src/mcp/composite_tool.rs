@@ -0,0 +1,114 @@
+use crate::CompositeOptions;
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    optional_f64, optional_i32, optional_str, output_policy, required_str, workspace,
+};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Overlay one image on another with typed placement and blending
+async fn composite_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let base = required_str(arguments, "base")?;
+    let overlay = required_str(arguments, "overlay")?;
+    let output = required_str(arguments, "output")?;
+
+    let mut options = CompositeOptions::new(base, overlay, output);
+    if let Some(gravity) = optional_str(arguments, "gravity") {
+        options.gravity = gravity.to_string();
+    }
+    options.x = optional_i32(arguments, "x")?.unwrap_or(0);
+    options.y = optional_i32(arguments, "y")?.unwrap_or(0);
+    if let Some(blend) = optional_str(arguments, "blend") {
+        options.blend = blend.to_string();
+    }
+    if let Some(opacity) = optional_f64(arguments, "opacity")? {
+        options.opacity = opacity;
+    }
+
+    match crate::composite(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(output) => {
+            let result = json!({
+                "output": output.path.to_string_lossy(),
+                "skipped": output.skipped,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Composite failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the composite tool route
+pub fn composite_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "base": {
+                "type": "string",
+                "description": "Path to the base image, which stays underneath."
+            },
+            "overlay": {
+                "type": "string",
+                "description": "Path to the image placed on top of the base."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the composited image to."
+            },
+            "gravity": {
+                "type": "string",
+                "enum": ["NorthWest", "North", "NorthEast", "West", "Center", "East", "SouthWest", "South", "SouthEast"],
+                "description": "Where the overlay is anchored on the base. Defaults to NorthWest."
+            },
+            "x": {
+                "type": "integer",
+                "description": "Horizontal offset in pixels from the gravity position. Defaults to 0."
+            },
+            "y": {
+                "type": "integer",
+                "description": "Vertical offset in pixels from the gravity position. Defaults to 0."
+            },
+            "blend": {
+                "type": "string",
+                "enum": ["Over", "Multiply", "Screen", "Overlay", "Darken", "Lighten", "ColorDodge", "ColorBurn", "HardLight", "SoftLight", "Difference", "Exclusion", "Plus", "Minus"],
+                "description": "How overlay pixels are combined with the base. Defaults to Over, which simply places the overlay on top."
+            },
+            "opacity": {
+                "type": "number",
+                "description": "Overlay opacity from 0 to 100. Defaults to 100."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when an output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen names are reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["base", "overlay", "output"]
+    });
+    let tool = Tool::new(
+        "composite",
+        "Overlay one image on another with a gravity, pixel offset, blend mode, and opacity. Prefer this over the magick tool for compositing so the image order is always correct.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(composite_tool(context)))
+}
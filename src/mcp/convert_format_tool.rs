@@ -0,0 +1,126 @@
+use crate::ConvertOptions;
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    invalid_params, optional_str, optional_u32, output_policy, output_settings, preset,
+    required_str, workspace,
+};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Convert an image to another format
+async fn convert_format_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let preset = preset(arguments)?;
+    let format = match optional_str(arguments, "format") {
+        Some(format) => format,
+        None => preset
+            .as_ref()
+            .and_then(|preset| preset.format.as_deref())
+            .ok_or_else(|| invalid_params("Missing required parameter: format"))?,
+    };
+
+    let mut options = ConvertOptions::new(input, format);
+    options.output = optional_str(arguments, "output").map(str::to_string);
+    options.quality = match optional_u32(arguments, "quality")? {
+        Some(quality) => Some(quality),
+        None => preset.as_ref().and_then(|preset| preset.quality),
+    };
+    options.settings = output_settings(arguments, preset.as_ref())?;
+    options.compression = optional_str(arguments, "compression").map(str::to_string);
+
+    match crate::convert_format(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(converted) => {
+            let result = json!({
+                "output": converted.output.path.to_string_lossy(),
+                "skipped": converted.output.skipped,
+                "size_bytes": converted.size,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Conversion failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the convert_format tool route
+pub fn convert_format_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the source image."
+            },
+            "format": {
+                "type": "string",
+                "description": "Target format, e.g. 'webp', 'png', 'jpeg', 'avif', or 'tiff'. Checked against `magick -list format` before converting. Required unless the preset sets a format."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the converted image to. Defaults to the input path with the format as its extension."
+            },
+            "quality": {
+                "type": "integer",
+                "minimum": 0,
+                "maximum": 100,
+                "description": "Quality for lossy formats, or compression level for formats like PNG. Defaults to ImageMagick's choice for the format."
+            },
+            "compression": {
+                "type": "string",
+                "description": "Compression type passed to -compress, e.g. 'LZW' or 'Zip' for TIFF."
+            },
+            "preset": {
+                "type": "string",
+                "description": "Name of a preset from the [presets] section of the config file, e.g. 'web', supplying defaults for format, quality, max dimensions, strip, and colorspace. Arguments given with the call take precedence."
+            },
+            "max_width": {
+                "type": "integer",
+                "description": "Shrink images wider than this many pixels, keeping the aspect ratio."
+            },
+            "max_height": {
+                "type": "integer",
+                "description": "Shrink images taller than this many pixels, keeping the aspect ratio."
+            },
+            "strip": {
+                "type": "boolean",
+                "description": "Remove profiles and metadata such as EXIF."
+            },
+            "colorspace": {
+                "type": "string",
+                "description": "Colorspace to convert to, e.g. 'sRGB', 'Gray', or 'CMYK'."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when an output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen names are reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["input"]
+    });
+    let tool = Tool::new(
+        "convert_format",
+        "Convert an image to another format with optional quality and compression, returning the output path and its size in bytes.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(convert_format_tool(context)))
+}
@@ -0,0 +1,123 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    invalid_params, optional_f64, optional_str, output_policy, required_str, workspace,
+};
+use crate::{CropOptions, CropUnit};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Crop an image to a region or aspect ratio
+async fn crop_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output = required_str(arguments, "output")?;
+
+    let mut options = CropOptions::new(input, output);
+    options.x = optional_f64(arguments, "x")?;
+    options.y = optional_f64(arguments, "y")?;
+    options.width = optional_f64(arguments, "width")?;
+    options.height = optional_f64(arguments, "height")?;
+    if let Some(unit) = optional_str(arguments, "unit") {
+        options.unit = CropUnit::parse(unit).map_err(|e| invalid_params(e.to_string()))?;
+    }
+    options.aspect_ratio = optional_str(arguments, "aspect_ratio").map(str::to_string);
+    if let Some(gravity) = optional_str(arguments, "gravity") {
+        options.gravity = gravity.to_string();
+    }
+
+    match crate::crop(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(cropped) => {
+            let result = json!({
+                "output": cropped.output.path.to_string_lossy(),
+                "skipped": cropped.output.skipped,
+                "region": {
+                    "x": cropped.rect.x,
+                    "y": cropped.rect.y,
+                    "width": cropped.rect.width,
+                    "height": cropped.rect.height
+                },
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Crop failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the crop tool route
+pub fn crop_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the source image."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the cropped image to."
+            },
+            "x": {
+                "type": "number",
+                "description": "Left edge of the region. Defaults to 0."
+            },
+            "y": {
+                "type": "number",
+                "description": "Top edge of the region. Defaults to 0."
+            },
+            "width": {
+                "type": "number",
+                "description": "Width of the region. Required unless aspect_ratio is set."
+            },
+            "height": {
+                "type": "number",
+                "description": "Height of the region. Required unless aspect_ratio is set."
+            },
+            "unit": {
+                "type": "string",
+                "enum": ["pixels", "percent"],
+                "description": "Units of x, y, width, and height. percent is relative to the image size. Defaults to pixels."
+            },
+            "aspect_ratio": {
+                "type": "string",
+                "description": "Crop the largest region with this aspect ratio, e.g. '16:9' or '1:1', instead of giving width and height."
+            },
+            "gravity": {
+                "type": "string",
+                "enum": ["NorthWest", "North", "NorthEast", "West", "Center", "East", "SouthWest", "South", "SouthEast"],
+                "description": "Where the aspect_ratio region is placed. Defaults to Center."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when an output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen names are reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["input", "output"]
+    });
+    let tool = Tool::new(
+        "crop",
+        "Crop an image to a region in pixels or percent, or to the largest region with an aspect ratio. The region is validated against the image's dimensions.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(crop_tool(context)))
+}
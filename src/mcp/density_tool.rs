@@ -0,0 +1,115 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    invalid_params, optional_bool, optional_f64, optional_str, output_policy, required_str,
+    workspace,
+};
+use crate::{DensityOptions, DensityUnits, PrintSize};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::{Value, json};
+
+/// Set or resample the density of an image for print
+async fn density_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output = required_str(arguments, "output")?;
+    let density = optional_f64(arguments, "density")?
+        .ok_or_else(|| invalid_params("Missing required parameter: density".to_string()))?;
+
+    let mut options = DensityOptions::new(input, output, density);
+    if let Some(units) = optional_str(arguments, "units") {
+        options.units = DensityUnits::parse(units).map_err(|e| invalid_params(e.to_string()))?;
+    }
+    options.resample = optional_bool(arguments, "resample").unwrap_or(false);
+
+    match crate::density(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(change) => {
+            let result = json!({
+                "output": change.output.path.to_string_lossy(),
+                "skipped": change.output.skipped,
+                "resampled": options.resample,
+                "before": print_size_json(&change.before),
+                "after": change.after.as_ref().map(print_size_json),
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Density change failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Describe an image's pixel size, density, and printed size, rounded to hundredths
+fn print_size_json(size: &PrintSize) -> Value {
+    let round = |value: f64| (value * 100.0).round() / 100.0;
+    json!({
+        "width": size.width,
+        "height": size.height,
+        "x_density": size.x_density,
+        "y_density": size.y_density,
+        "units": size.units,
+        "print_width_in": size.inches().map(|(width, _)| round(width)),
+        "print_height_in": size.inches().map(|(_, height)| round(height)),
+        "print_width_cm": size.centimeters().map(|(width, _)| round(width)),
+        "print_height_cm": size.centimeters().map(|(_, height)| round(height)),
+    })
+}
+
+/// Create the density tool route
+pub fn density_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the source image."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the image to."
+            },
+            "density": {
+                "type": "number",
+                "description": "The new density in units, e.g. 300 for 300 DPI print output."
+            },
+            "units": {
+                "type": "string",
+                "enum": ["inch", "cm"],
+                "description": "Unit of the density: pixels per inch (the default) or pixels per centimeter."
+            },
+            "resample": {
+                "type": "boolean",
+                "description": "When false (the default), only the density recorded in the file changes: the pixels are untouched and the print size changes. When true, the pixels are resampled from the current density to the new one so the print size stays the same, e.g. downsampling a 600 DPI scan to 300 DPI halves its pixel dimensions."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when the output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen name is reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["input", "output", "density"]
+    });
+    let tool = Tool::new(
+        "density",
+        "Set the DPI of an image for print, either changing only the recorded density or resampling the pixels to it. Returns the pixel size, density, and printed size in inches and centimeters before and after.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(density_tool(context)))
+}
@@ -0,0 +1,139 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{optional_bool, optional_f64, output_policy, required_str, workspace};
+use crate::{ContrastStretch, DocumentCleanupOptions};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::{Value, json};
+
+/// Deskew a scanned document and clean up its background
+async fn document_cleanup_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output = required_str(arguments, "output")?;
+
+    let mut options = DocumentCleanupOptions::new(input, output);
+    if optional_bool(arguments, "deskew").unwrap_or(true) {
+        if let Some(threshold) = optional_f64(arguments, "deskew_threshold")? {
+            options.deskew = Some(threshold);
+        }
+    } else {
+        options.deskew = None;
+    }
+    if optional_bool(arguments, "contrast_stretch").unwrap_or(true) {
+        let defaults = ContrastStretch::default();
+        options.contrast_stretch = Some(ContrastStretch {
+            black_point: optional_f64(arguments, "black_point")?.unwrap_or(defaults.black_point),
+            white_point: optional_f64(arguments, "white_point")?.unwrap_or(defaults.white_point),
+        });
+    } else {
+        options.contrast_stretch = None;
+    }
+    if optional_bool(arguments, "whiten_background").unwrap_or(true) {
+        if let Some(threshold) = optional_f64(arguments, "background_threshold")? {
+            options.whiten_background = Some(threshold);
+        }
+    } else {
+        options.whiten_background = None;
+    }
+    if optional_bool(arguments, "binarize").unwrap_or(false) {
+        options.binarize = Some(optional_f64(arguments, "binarize_threshold")?.unwrap_or(50.0));
+    }
+
+    match crate::clean_document(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(cleaned) => {
+            let result = json!({
+                "output": cleaned.output.path.to_string_lossy(),
+                "skipped": cleaned.output.skipped,
+                "width": cleaned.dimensions.as_ref().map(|dimensions| dimensions.width),
+                "height": cleaned.dimensions.as_ref().map(|dimensions| dimensions.height),
+                "deskew_angle": cleaned.deskew_angle,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Document cleanup failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the document_cleanup tool route
+pub fn document_cleanup_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the scanned page."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the cleaned page to."
+            },
+            "deskew": {
+                "type": "boolean",
+                "description": "Straighten the page, filling the uncovered corners white. Defaults to true."
+            },
+            "deskew_threshold": {
+                "type": "number",
+                "description": "Percentage threshold deskew detects lines at, 0 to 100. Defaults to 40."
+            },
+            "contrast_stretch": {
+                "type": "boolean",
+                "description": "Spread the page's tones over the full range. Defaults to true."
+            },
+            "black_point": {
+                "type": "number",
+                "description": "Percentage of the darkest pixels the contrast stretch turns black. Defaults to 1."
+            },
+            "white_point": {
+                "type": "number",
+                "description": "Percentage of the lightest pixels the contrast stretch turns white. Defaults to 1."
+            },
+            "whiten_background": {
+                "type": "boolean",
+                "description": "Turn everything brighter than background_threshold white, removing paper tint and show-through. Defaults to true."
+            },
+            "background_threshold": {
+                "type": "number",
+                "description": "Brightness percentage above which pixels become white, 1 to 100. Lower values whiten more. Defaults to 85."
+            },
+            "binarize": {
+                "type": "boolean",
+                "description": "Reduce the page to pure black and white, e.g. for OCR or fax. Defaults to false."
+            },
+            "binarize_threshold": {
+                "type": "number",
+                "description": "Brightness percentage above which pixels become white when binarizing. Defaults to 50."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when the output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen name is reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["input", "output"]
+    });
+    let tool = Tool::new(
+        "document_cleanup",
+        "Clean up a scanned document: deskew the page, stretch its contrast, whiten the background, and optionally binarize it to black and white. Returns the output size and the angle the page was straightened by.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(document_cleanup_tool(context)))
+}
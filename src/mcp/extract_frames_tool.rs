@@ -0,0 +1,95 @@
+use crate::FramesOptions;
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{optional_bool, optional_str, optional_u32, required_str, workspace};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Write the frames of a multi-frame image to numbered files
+async fn extract_frames_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output_dir = required_str(arguments, "output_dir")?;
+
+    let mut options = FramesOptions::new(input, output_dir);
+    options.start = optional_u32(arguments, "start")?;
+    options.end = optional_u32(arguments, "end")?;
+    if let Some(coalesce) = optional_bool(arguments, "coalesce") {
+        options.coalesce = coalesce;
+    }
+    options.format = optional_str(arguments, "format").map(str::to_string);
+
+    match crate::extract_frames(&options, workspace(arguments)) {
+        Ok(frames) => {
+            let files: Vec<_> = frames
+                .iter()
+                .map(|frame| {
+                    json!({
+                        "index": frame.index,
+                        "path": frame.path.to_string_lossy()
+                    })
+                })
+                .collect();
+            let result = json!({
+                "frames": files,
+                "count": frames.len(),
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Frame extraction failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the extract_frames tool route
+pub fn extract_frames_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the multi-frame image, e.g. a GIF, animated WebP, or multi-page TIFF."
+            },
+            "output_dir": {
+                "type": "string",
+                "description": "Directory the frames are written to. Files are named <name>-<index>.<ext>, numbered by the frame's index in the input, e.g. spin-000.png."
+            },
+            "start": {
+                "type": "integer",
+                "description": "Index of the first frame to extract, counting from 0. Defaults to the first frame."
+            },
+            "end": {
+                "type": "integer",
+                "description": "Index of the last frame to extract, inclusive. Defaults to the last frame."
+            },
+            "coalesce": {
+                "type": "boolean",
+                "description": "Render each frame fully, as it appears in the animation, instead of only the part that changed from the previous frame. Defaults to true; pass false for multi-page documents to read only the selected pages."
+            },
+            "format": {
+                "type": "string",
+                "description": "File extension of the frames, e.g. 'jpg'. Defaults to 'png'."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path. Commands run from this directory."
+            }
+        },
+        "required": ["input", "output_dir"]
+    });
+    let tool = Tool::new(
+        "extract_frames",
+        "Explode a multi-frame image (GIF, animated WebP, multi-page TIFF) into numbered files, optionally only a range of frames, and return the index and path of each.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(extract_frames_tool(context)))
+}
@@ -0,0 +1,74 @@
+use crate::FaviconOptions;
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{optional_str, required_str, workspace};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Generate a favicon set from one source image
+async fn favicon_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+
+    let mut options = FaviconOptions::new(input);
+    if let Some(output_dir) = optional_str(arguments, "output_dir") {
+        options.output_dir = output_dir.to_string();
+    }
+
+    match crate::favicon(&options, workspace(arguments)) {
+        Ok(files) => {
+            let files: Vec<_> = files
+                .iter()
+                .map(|file| {
+                    json!({
+                        "path": file.path.to_string_lossy(),
+                        "sizes": file.sizes
+                    })
+                })
+                .collect();
+            let result = json!({
+                "files": files,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Favicon generation failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the favicon tool route
+pub fn favicon_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the source image, ideally square and at least 512x512. Other shapes are centered on a transparent square."
+            },
+            "output_dir": {
+                "type": "string",
+                "description": "Directory to write the favicon set into. Defaults to 'favicons'."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Project root the output directory is relative to."
+            }
+        },
+        "required": ["input"]
+    });
+    let tool = Tool::new(
+        "favicon",
+        "Generate a complete favicon set from one source image: 16, 32, and 48px PNGs, a 180px apple-touch-icon, 192 and 512px android-chrome PNGs, and a favicon.ico holding 16, 32, and 48px. Returns every generated path.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(favicon_tool(context)))
+}
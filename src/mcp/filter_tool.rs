@@ -0,0 +1,96 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{invalid_params, optional_f64, output_policy, required_str, workspace};
+use crate::{FilterOperation, FilterOptions};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Blur, sharpen, or denoise an image
+async fn filter_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output = required_str(arguments, "output")?;
+    let operation = FilterOperation::parse(required_str(arguments, "operation")?)
+        .map_err(|e| invalid_params(e.to_string()))?;
+
+    let mut options = FilterOptions::new(input, output, operation);
+    options.strength = optional_f64(arguments, "strength")?;
+    options.amount = optional_f64(arguments, "amount")?;
+    options.threshold = optional_f64(arguments, "threshold")?;
+
+    match crate::filter(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(output) => {
+            let result = json!({
+                "output": output.path.to_string_lossy(),
+                "skipped": output.skipped,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Filter failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the filter tool route
+pub fn filter_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the source image."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the filtered image to."
+            },
+            "operation": {
+                "type": "string",
+                "enum": ["blur", "sharpen", "unsharp", "despeckle", "denoise"],
+                "description": "blur softens the whole image, sharpen crisps every edge, unsharp sharpens only edges with enough contrast (best for photos), despeckle removes speckles while keeping edges, and denoise removes salt and pepper noise with a median filter."
+            },
+            "strength": {
+                "type": "number",
+                "description": "How strongly to filter. For blur, sharpen, and unsharp it is the blur sigma in pixels (defaults 2, 1, and 1; 0.5 is subtle, 5 is heavy). For despeckle it is the number of passes from 1 to 10 (default 1). For denoise it is the radius in pixels from 1 to 25 (default 1)."
+            },
+            "amount": {
+                "type": "number",
+                "description": "unsharp only: how much of the sharpening is applied, e.g. 0.5 for subtle or 2 for strong. Defaults to 1."
+            },
+            "threshold": {
+                "type": "number",
+                "description": "unsharp only: fraction from 0 to 1 of the range an edge's contrast must exceed to be sharpened, which keeps smooth areas from gaining noise. Defaults to 0.05."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when the output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen name is reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["input", "output", "operation"]
+    });
+    let tool = Tool::new(
+        "filter",
+        "Blur, sharpen, unsharp mask, despeckle, or denoise an image with a single strength setting, without writing ImageMagick radius and sigma geometry.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(filter_tool(context)))
+}
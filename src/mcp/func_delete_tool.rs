@@ -0,0 +1,53 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{invalid_params, required_str};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Delete a saved magick function
+async fn func_delete_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let name = required_str(&context.arguments, "name")?;
+    if name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(invalid_params(format!("Invalid function name: {name}")));
+    }
+
+    match context.service.state().function_store(None).delete(name) {
+        Ok(_) => {
+            let result = json!({
+                "success": true,
+                "message": format!("Function '{}' deleted successfully", name)
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Failed to delete function: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the func_delete tool route
+pub fn func_delete_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "name": {
+                "type": "string",
+                "description": "Name of the saved function to delete"
+            }
+        },
+        "required": ["name"]
+    });
+    let tool = Tool::new(
+        "func_delete",
+        "Delete a saved magick function. Built-in and project functions cannot be deleted.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(func_delete_tool(context)))
+}
@@ -1,8 +1,10 @@
 use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{invalid_params, optional_bool, output_policy};
 use rmcp::handler::server::router::tool::ToolRoute;
 use rmcp::handler::server::tool::ToolCallContext;
 use rmcp::model::{CallToolResult, ErrorCode, ErrorData, Tool};
-use serde_json::json;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
 use std::path::Path;
 
 /// Execute a magick function by name
@@ -36,8 +38,32 @@ async fn func_execute_tool(
         .and_then(|args| args.get("input"))
         .and_then(|v| v.as_str());
 
+    // Extract optional parameter values, accepting numbers as well as strings
+    let mut params = BTreeMap::new();
+    if let Some(values) = context
+        .arguments
+        .as_ref()
+        .and_then(|args| args.get("params"))
+    {
+        let values = values
+            .as_object()
+            .ok_or_else(|| invalid_params("Parameter 'params' must be an object"))?;
+        for (param, value) in values {
+            let value = match value {
+                Value::String(text) => text.clone(),
+                Value::Number(number) => number.to_string(),
+                _ => {
+                    return Err(invalid_params(format!(
+                        "Value of parameter '{param}' must be a string or number"
+                    )));
+                }
+            };
+            params.insert(param.clone(), value);
+        }
+    }
+
     // Load the function
-    let function = match crate::load_function(name) {
+    let function = match context.service.state().function_store(workspace).load(name) {
         Ok(f) => f,
         Err(e) => {
             let error_result = json!({
@@ -49,10 +75,29 @@ async fn func_execute_tool(
     };
 
     // Execute the function
-    match crate::run_function(&function, workspace, input) {
-        Ok(outputs) => {
+    match crate::run_function(
+        &function,
+        workspace,
+        input,
+        &params,
+        output_policy(&context)?,
+        optional_bool(&context.arguments, "transactional").unwrap_or(false),
+        context.service.state().functions_config(),
+    ) {
+        Ok(output) => {
+            let files: Vec<_> = output
+                .files
+                .iter()
+                .map(|file| {
+                    json!({
+                        "path": file.path.to_string_lossy(),
+                        "skipped": file.skipped
+                    })
+                })
+                .collect();
             let result = json!({
-                "outputs": outputs,
+                "outputs": output.outputs,
+                "files": files,
                 "success": true,
                 "function_name": name
             });
@@ -77,6 +122,28 @@ pub fn func_execute_tool_route() -> ToolRoute<MagickServerHandler> {
                 "type": "string",
                 "description": "Name of the function to execute"
             },
+            "create_dirs": {
+                "type": "boolean",
+                "description": "Create missing output directories instead of failing. Defaults to false."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when an output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen names are reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "skip_if_newer": {
+                "type": "boolean",
+                "description": "Skip commands whose output is newer than every input and was written by the same command on an earlier run with skip_if_newer. Skipped outputs are reported as skipped. Defaults to false."
+            },
+            "transactional": {
+                "type": "boolean",
+                "description": "Stage every file the commands write and only move them into place once all commands succeed. If a command fails, the staged files are discarded and the workspace is left as it was. Defaults to false."
+            },
             "workspace": {
                 "type": "string",
                 "description": "Workspace path to set as the working directory for commands"
@@ -84,9 +151,14 @@ pub fn func_execute_tool_route() -> ToolRoute<MagickServerHandler> {
             "input": {
                 "type": "string",
                 "description": "Optional input value to replace $input placeholders in commands"
+            },
+            "params": {
+                "type": "object",
+                "additionalProperties": { "type": ["string", "number"] },
+                "description": "Values for the parameters the function declares, e.g. {\"size\": \"800x600\", \"color\": \"white\"}. Each value is checked against the parameter's type, and values which look like options are rejected."
             }
         },
-        "required": ["name", "workspace"]
+        "required": ["name"]
     });
     let tool = Tool::new(
         "func_execute",
@@ -0,0 +1,64 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{required_str, workspace};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Return the full definition of a magick function
+async fn func_get_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let name = required_str(&context.arguments, "name")?;
+
+    let store = context
+        .service
+        .state()
+        .function_store(workspace(&context.arguments));
+    match store.locate(name) {
+        Ok((function, source)) => {
+            let uses_input = function.uses_input();
+            let result = json!({
+                "name": function.name,
+                "commands": function.commands,
+                "source": source,
+                "uses_input": uses_input,
+                "params": function.params,
+                "required_delegates": crate::feature::required_delegates(&function),
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Failed to get function: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the func_get tool route
+pub fn func_get_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "name": {
+                "type": "string",
+                "description": "Name of the function to inspect"
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path whose project functions in .magickmcp/functions take precedence."
+            }
+        },
+        "required": ["name"]
+    });
+    let tool = Tool::new(
+        "func_get",
+        "Get a magick function's full definition: its commands, whether it is a project, saved, or built-in function, whether it takes $input, and the delegates it needs",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(func_get_tool(context)))
+}
@@ -1,4 +1,6 @@
+use crate::mcp::pagination::{DEFAULT_PAGE_SIZE, paginate};
 use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{optional_str, optional_u32, workspace};
 use rmcp::handler::server::router::tool::ToolRoute;
 use rmcp::handler::server::tool::ToolCallContext;
 use rmcp::model::{CallToolResult, ErrorData, Tool};
@@ -6,13 +8,21 @@ use serde_json::json;
 
 /// List all available magick functions
 async fn func_list_tool(
-    _context: ToolCallContext<'_, MagickServerHandler>,
+    context: ToolCallContext<'_, MagickServerHandler>,
 ) -> Result<CallToolResult, ErrorData> {
-    match crate::list_functions() {
+    let arguments = &context.arguments;
+    let cursor = optional_str(arguments, "cursor");
+    let page_size = optional_u32(arguments, "limit")?.map_or(DEFAULT_PAGE_SIZE, |l| l as usize);
+
+    let store = context.service.state().function_store(workspace(arguments));
+    match store.list() {
         Ok(functions) => {
+            let count = functions.len();
+            let page = paginate(functions, cursor, page_size)?;
             let result = json!({
-                "functions": functions,
-                "count": functions.len()
+                "functions": page.items,
+                "count": count,
+                "next_cursor": page.next_cursor
             });
             Ok(CallToolResult::structured(result))
         }
@@ -29,12 +39,25 @@ async fn func_list_tool(
 pub fn func_list_tool_route() -> ToolRoute<MagickServerHandler> {
     let input_schema: serde_json::Value = json!({
         "type": "object",
-        "properties": {},
+        "properties": {
+            "cursor": {
+                "type": "string",
+                "description": "The next_cursor returned with the previous page, to list the functions after it."
+            },
+            "limit": {
+                "type": "integer",
+                "description": "Largest number of functions to return. Defaults to 100."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path whose project functions in .magickmcp/functions are listed first."
+            }
+        },
         "required": []
     });
     let tool = Tool::new(
         "func_list",
-        "List all available magick functions",
+        "List all available magick functions, a page at a time. count is the total number of functions; when next_cursor is set, pass it as cursor to get the next page.",
         input_schema.as_object().unwrap().clone(),
     );
     ToolRoute::new_dyn(tool, |context| Box::pin(func_list_tool(context)))
@@ -50,12 +50,30 @@ async fn func_save_tool(
         })
         .collect::<Result<Vec<String>, ErrorData>>()?;
 
+    // Extract optional parameter declarations, mapping each name to its type
+    let params = match context
+        .arguments
+        .as_ref()
+        .and_then(|args| args.get("params"))
+    {
+        Some(params) => serde_json::from_value(params.clone()).map_err(|e| ErrorData {
+            code: ErrorCode::INVALID_PARAMS,
+            message: format!(
+                "Parameter 'params' must map names to path, number, geometry, color, or text: {e}"
+            )
+            .into(),
+            data: None,
+        })?,
+        None => Default::default(),
+    };
+
     let function = crate::Function {
         name: name.to_string(),
         commands,
+        params,
     };
 
-    match crate::save_function(function) {
+    match context.service.state().function_store(None).save(&function) {
         Ok(_) => {
             let result = json!({
                 "success": true,
@@ -88,6 +106,14 @@ pub fn func_save_tool_route() -> ToolRoute<MagickServerHandler> {
                     "type": "string"
                 },
                 "description": "Array of ImageMagick command strings to execute in sequence. Important: Use $input to represent the input file, this will be replaced during execution. You can also use $input for output and intermediate file names."
+            },
+            "params": {
+                "type": "object",
+                "additionalProperties": {
+                    "type": "string",
+                    "enum": ["path", "number", "geometry", "color", "text"]
+                },
+                "description": "Parameters the commands use as $name, mapped to their type, e.g. {\"size\": \"geometry\", \"color\": \"color\"}. Values passed to func_execute are checked against these types."
             }
         },
         "required": ["name", "commands"]
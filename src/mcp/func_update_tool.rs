@@ -0,0 +1,139 @@
+use crate::FunctionEdit;
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{invalid_params, required_str, workspace};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::{Value, json};
+
+/// Apply partial edits to a saved magick function
+async fn func_update_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let name = required_str(&context.arguments, "name")?;
+    let edits = context
+        .arguments
+        .as_ref()
+        .and_then(|args| args.get("edits"))
+        .and_then(Value::as_array)
+        .ok_or_else(|| invalid_params("Missing required parameter: edits"))?
+        .iter()
+        .map(parse_edit)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let store = context
+        .service
+        .state()
+        .function_store(workspace(&context.arguments));
+    match store.update(name, &edits) {
+        Ok(function) => {
+            let result = json!({
+                "name": function.name,
+                "commands": function.commands,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Failed to update function: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Parse one entry of the `edits` array
+fn parse_edit(edit: &Value) -> Result<FunctionEdit, ErrorData> {
+    let field = |name: &str| edit.get(name);
+    let string = |name: &str| {
+        field(name)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| invalid_params(format!("Edit is missing string '{name}'")))
+    };
+    let index = || {
+        field("index")
+            .and_then(Value::as_u64)
+            .map(|index| index as usize)
+            .ok_or_else(|| invalid_params("Edit is missing non-negative integer 'index'"))
+    };
+    match field("op").and_then(Value::as_str) {
+        Some("rename") => Ok(FunctionEdit::Rename(string("name")?)),
+        Some("append") => Ok(FunctionEdit::Append(string("command")?)),
+        Some("insert") => Ok(FunctionEdit::Insert {
+            index: index()?,
+            command: string("command")?,
+        }),
+        Some("remove") => Ok(FunctionEdit::Remove(index()?)),
+        Some("replace") => field("commands")
+            .and_then(Value::as_array)
+            .and_then(|commands| {
+                commands
+                    .iter()
+                    .map(|command| command.as_str().map(str::to_string))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .map(FunctionEdit::Replace)
+            .ok_or_else(|| invalid_params("Edit is missing string array 'commands'")),
+        other => Err(invalid_params(format!(
+            "Unknown edit op {other:?}, expected rename, append, insert, remove, or replace"
+        ))),
+    }
+}
+
+/// Create the func_update tool route
+pub fn func_update_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "name": {
+                "type": "string",
+                "description": "Name of the function to edit"
+            },
+            "edits": {
+                "type": "array",
+                "description": "Edits applied in order. Nothing is saved unless every edit succeeds.",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "op": {
+                            "type": "string",
+                            "enum": ["rename", "append", "insert", "remove", "replace"]
+                        },
+                        "name": {
+                            "type": "string",
+                            "description": "New function name, for rename"
+                        },
+                        "command": {
+                            "type": "string",
+                            "description": "Command to add, for append and insert"
+                        },
+                        "index": {
+                            "type": "integer",
+                            "description": "Zero-based command position, for insert and remove"
+                        },
+                        "commands": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "New command list, for replace"
+                        }
+                    },
+                    "required": ["op"]
+                }
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path whose project functions in .magickmcp/functions are edited in place."
+            }
+        },
+        "required": ["name", "edits"]
+    });
+    let tool = Tool::new(
+        "func_update",
+        "Edit a saved magick function without resending it: rename it, append, insert, or remove individual commands, or replace the command list. Editing a project function edits it in the project, while editing a built-in function saves an edited copy.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(func_update_tool(context)))
+}
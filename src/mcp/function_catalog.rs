@@ -0,0 +1,127 @@
+use crate::feature::FunctionStore;
+use crate::mcp::workspace::WorkspaceDefaults;
+use rmcp::ErrorData;
+use rmcp::model::{ClientNotification, ClientRequest, ServerInfo, ServerResult, Tool};
+use rmcp::service::{NotificationContext, RequestContext, RoleServer, Service};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// Tools whose description lists the functions they can be called with
+const CATALOG_TOOLS: &[&str] = &["func_execute", "func_get"];
+
+/// Service wrapper which lists the current functions in the descriptions of the function tools
+///
+/// The list is read when the client lists tools, so functions saved, edited, or deleted on disk
+/// show up without restarting the server; the server tells the client to list tools again
+/// whenever the function files change. Files which fail to parse or validate are left out.
+pub struct FunctionCatalogService<S> {
+    inner: S,
+    defaults: WorkspaceDefaults,
+    data_dir: Option<PathBuf>,
+}
+
+impl<S> FunctionCatalogService<S> {
+    /// Wrap a service, listing the functions saved in the data directory along with those of
+    /// the default workspace's project
+    pub fn new(inner: S, defaults: WorkspaceDefaults, data_dir: Option<PathBuf>) -> Self {
+        FunctionCatalogService {
+            inner,
+            defaults,
+            data_dir,
+        }
+    }
+
+    /// Names of the usable functions, or `None` when they cannot be listed
+    fn function_names(&self) -> Option<Vec<String>> {
+        let workspace = self.defaults.resolve(None).path;
+        let store =
+            FunctionStore::in_data_dir(self.data_dir.clone()).with_workspace(Some(&workspace));
+        let invalid: HashSet<String> = store
+            .invalid_saved()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|invalid| Some(invalid.path.file_stem()?.to_string_lossy().into_owned()))
+            .collect();
+        let names = store.list().ok()?;
+        Some(
+            names
+                .into_iter()
+                .filter(|name| !invalid.contains(name))
+                .collect(),
+        )
+    }
+}
+
+impl<S: Service<RoleServer>> Service<RoleServer> for FunctionCatalogService<S> {
+    async fn handle_request(
+        &self,
+        request: ClientRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, ErrorData> {
+        let lists_tools = matches!(request, ClientRequest::ListToolsRequest(_));
+        match self.inner.handle_request(request, context).await? {
+            ServerResult::ListToolsResult(mut result) if lists_tools => {
+                if let Some(names) = self.function_names() {
+                    for tool in &mut result.tools {
+                        if CATALOG_TOOLS.contains(&tool.name.as_ref()) {
+                            add_catalog(tool, &names);
+                        }
+                    }
+                }
+                Ok(ServerResult::ListToolsResult(result))
+            }
+            other => Ok(other),
+        }
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ClientNotification,
+        context: NotificationContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.inner.handle_notification(notification, context).await
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        self.inner.get_info()
+    }
+}
+
+/// Append the function names to a tool's description
+fn add_catalog(tool: &mut Tool, names: &[String]) {
+    let catalog = if names.is_empty() {
+        "No functions are available yet.".to_string()
+    } else {
+        format!("Available functions: {}.", names.join(", "))
+    };
+    let description = tool.description.as_deref().unwrap_or_default();
+    tool.description = Some(
+        format!("{description} {catalog}")
+            .trim_start()
+            .to_string()
+            .into(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_add_catalog() {
+        let schema = json!({ "type": "object" });
+        let mut tool = Tool::new(
+            "func_execute",
+            "Execute a magick function by name.",
+            schema.as_object().unwrap().clone(),
+        );
+
+        add_catalog(&mut tool, &["grayscale".to_string(), "thumb".to_string()]);
+
+        assert_eq!(
+            tool.description.as_deref(),
+            Some("Execute a magick function by name. Available functions: grayscale, thumb.")
+        );
+    }
+}
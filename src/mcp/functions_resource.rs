@@ -0,0 +1,41 @@
+use rmcp::model::Resource;
+use serde_json::json;
+
+/// URI for the functions list resource
+pub const FUNCTIONS_RESOURCE_URI: &str = "magick://functions";
+
+/// Create the functions list resource metadata
+pub fn functions_resource() -> Resource {
+    Resource::new(
+        rmcp::model::RawResource {
+            uri: FUNCTIONS_RESOURCE_URI.to_string(),
+            name: "Magick Functions".to_string(),
+            title: Some("Available Magick Functions".to_string()),
+            description: Some("Names of the saved and built-in magick functions, as listed by func_list. Subscribe to be notified whenever a function is saved, updated, deleted, or imported.".to_string()),
+            mime_type: Some("application/json".to_string()),
+            size: None,
+            icons: None,
+        },
+        None,
+    )
+}
+
+/// Read the functions list resource contents
+///
+/// # Arguments
+///
+/// * `store` - The store to list the functions of
+///
+/// # Returns
+///
+/// Returns the function names as JSON, or an error if the functions cannot be listed
+pub fn read_functions_resource(
+    store: &crate::FunctionStore,
+) -> Result<String, crate::feature::FunctionStoreError> {
+    let functions = store.list()?;
+    let contents = json!({
+        "functions": functions,
+        "count": functions.len()
+    });
+    Ok(contents.to_string())
+}
@@ -0,0 +1,66 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{optional_str, required_str, workspace};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Evaluate an fx expression and return the scalar result
+async fn fx_eval_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let expression = required_str(arguments, "expression")?;
+    let image = required_str(arguments, "image")?;
+    let second_image = optional_str(arguments, "second_image");
+
+    match crate::fx_eval(expression, image, second_image, workspace(arguments)) {
+        Ok(value) => {
+            let result = json!({
+                "value": value,
+                "expression": expression,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("fx evaluation failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the fx_eval tool route
+pub fn fx_eval_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "expression": {
+                "type": "string",
+                "description": "ImageMagick fx expression, e.g. 'mean', 'maxima.r', or 'abs(u.mean-v.mean)'. The first image is u and the second is v. Values are normalized to 0-1."
+            },
+            "image": {
+                "type": "string",
+                "description": "Path to the first image."
+            },
+            "second_image": {
+                "type": "string",
+                "description": "Optional path to a second image, available as v."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["expression", "image"]
+    });
+    let tool = Tool::new(
+        "fx_eval",
+        "Evaluate an ImageMagick fx expression over one or two images and return the scalar result, without writing any output files. Useful for quantitative checks such as mean brightness or the difference between two images.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(fx_eval_tool(context)))
+}
@@ -0,0 +1,103 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{optional_u32, output_policy, required_str, workspace};
+use crate::{HeifConvertOptions, OperationError};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::{Value, json};
+
+/// Convert an image to or from HEIC or AVIF
+async fn heif_convert_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output = required_str(arguments, "output")?;
+
+    let mut options = HeifConvertOptions::new(input, output);
+    options.quality = optional_u32(arguments, "quality")?;
+
+    match crate::heif_convert(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(conversion) => {
+            let support: Vec<_> = conversion
+                .support
+                .iter()
+                .map(|support| {
+                    json!({
+                        "format": support.format.name(),
+                        "readable": support.readable,
+                        "writable": support.writable
+                    })
+                })
+                .collect();
+            let result = json!({
+                "output": conversion.output.path.to_string_lossy(),
+                "skipped": conversion.output.skipped,
+                "size": conversion.size,
+                "support": support,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(OperationError::MissingDependency(instructions)) => {
+            let error_result = json!({
+                "error": "This ImageMagick installation is missing the libheif support the conversion needs",
+                "code": "missing_delegate",
+                "instructions": instructions,
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("HEIF conversion failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the heif_convert tool route
+pub fn heif_convert_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the source image, e.g. an iPhone .heic photo."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the converted image to. Its extension picks the format, e.g. photo.avif or photo.jpg. The input or the output must be .heic, .heif, or .avif."
+            },
+            "quality": {
+                "type": "integer",
+                "minimum": 0,
+                "maximum": 100,
+                "description": "Quality from 0 to 100. Defaults to ImageMagick's default for the output format."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when the output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen name is reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["input", "output"]
+    });
+    let tool = Tool::new(
+        "heif_convert",
+        "Convert an image to or from HEIC or AVIF. Checks first that this ImageMagick can read and write the formats, and when libheif or one of its plugins is missing returns a missing_delegate error with installation instructions.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(heif_convert_tool(context)))
+}
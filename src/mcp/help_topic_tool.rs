@@ -0,0 +1,48 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::required_str;
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Look up documentation for a single option or subcommand
+async fn help_topic_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let topic = required_str(&context.arguments, "topic")?;
+
+    match crate::help_topic(topic) {
+        Ok(help) => {
+            let mut result = json!(help);
+            result["success"] = json!(true);
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Help lookup failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the help_topic tool route
+pub fn help_topic_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "topic": {
+                "type": "string",
+                "description": "An option such as '-resize', 'gravity', or '+repage', or a subcommand such as 'identify' or 'compare'."
+            }
+        },
+        "required": ["topic"]
+    });
+    let tool = Tool::new(
+        "help_topic",
+        "Look up documentation for one ImageMagick option or subcommand. Returns the matching usage lines from 'magick -help' and, for options taking a named value such as -gravity or -filter, the accepted values from 'magick -list'. Prefer this over the full help resource when asking about a specific option.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(help_topic_tool(context)))
+}
@@ -0,0 +1,62 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{optional_u32, required_str, workspace};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Compute per-channel histograms and statistics of an image
+async fn histogram_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let path = required_str(arguments, "path")?;
+    let bins = optional_u32(arguments, "bins")?.unwrap_or(crate::DEFAULT_HISTOGRAM_BINS);
+
+    match crate::histogram(path, bins, workspace(arguments)) {
+        Ok(histogram) => {
+            let result = json!({
+                "colorspace": histogram.colorspace,
+                "pixels": histogram.pixels,
+                "channels": histogram.channels,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Histogram failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the histogram tool route
+pub fn histogram_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "path": {
+                "type": "string",
+                "description": "Path to the image to analyze. Only the first frame is analyzed."
+            },
+            "bins": {
+                "type": "integer",
+                "description": "Number of histogram bins per channel, from 1 to 256. Defaults to 16."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["path"]
+    });
+    let tool = Tool::new(
+        "histogram",
+        "Compute a histogram and statistics for each channel of an image. Grayscale images report one gray channel, others red, green, and blue in sRGB, plus alpha when present. Each channel has its mean, std_dev, min, and max from 0 to 1, the fraction of clipped_low (0) and clipped_high (255) pixels, and pixel counts per bin from darkest to brightest. Use it to judge exposure and contrast before adjusting levels.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(histogram_tool(context)))
+}
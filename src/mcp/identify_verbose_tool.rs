@@ -0,0 +1,55 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{required_str, workspace};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Return full image metadata parsed from `identify -verbose`
+async fn identify_verbose_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let path = required_str(arguments, "path")?;
+
+    match crate::identify_verbose(path, workspace(arguments)) {
+        Ok(metadata) => {
+            let result = json!({
+                "metadata": metadata,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Identify failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the identify_verbose tool route
+pub fn identify_verbose_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "path": {
+                "type": "string",
+                "description": "Path to the image to identify."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["path"]
+    });
+    let tool = Tool::new(
+        "identify_verbose",
+        "Return full image metadata (geometry, channel statistics, chromaticity, properties, EXIF, profiles) from 'identify -verbose' as nested JSON. Binary profile dumps are truncated.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(identify_verbose_tool(context)))
+}
@@ -0,0 +1,162 @@
+use rmcp::ErrorData;
+use rmcp::model::{ClientNotification, ClientRequest, ServerInfo, ServerResult};
+use rmcp::service::{
+    NotificationContext, RequestContext, RoleServer, RunningServiceCancellationToken, Service,
+};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Records when the server last handled a message
+///
+/// A request counts as activity for as long as it is running, so a slow tool call never looks idle.
+#[derive(Debug, Clone)]
+pub struct ActivityTracker {
+    state: Arc<Mutex<ActivityState>>,
+}
+
+#[derive(Debug)]
+struct ActivityState {
+    last_activity: Instant,
+    in_flight: usize,
+}
+
+/// Marks a request as running until it is dropped
+pub struct ActivityGuard {
+    tracker: ActivityTracker,
+}
+
+impl ActivityTracker {
+    /// Create a tracker whose last activity is now
+    pub fn new() -> Self {
+        ActivityTracker {
+            state: Arc::new(Mutex::new(ActivityState {
+                last_activity: Instant::now(),
+                in_flight: 0,
+            })),
+        }
+    }
+
+    /// Record activity which completes immediately, such as a notification
+    pub fn touch(&self) {
+        self.state().last_activity = Instant::now();
+    }
+
+    /// Record the start of a request, which ends when the returned guard is dropped
+    pub fn begin(&self) -> ActivityGuard {
+        let mut state = self.state();
+        state.in_flight += 1;
+        state.last_activity = Instant::now();
+        ActivityGuard {
+            tracker: self.clone(),
+        }
+    }
+
+    /// How long the server has been idle, or `None` while a request is running
+    pub fn idle_for(&self) -> Option<Duration> {
+        let state = self.state();
+        (state.in_flight == 0).then(|| state.last_activity.elapsed())
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, ActivityState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for ActivityTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ActivityGuard {
+    fn drop(&mut self) {
+        let mut state = self.tracker.state();
+        state.in_flight = state.in_flight.saturating_sub(1);
+        state.last_activity = Instant::now();
+    }
+}
+
+/// Service wrapper which reports every request and notification to an `ActivityTracker`
+pub struct ActivityTrackingService<S> {
+    inner: S,
+    tracker: ActivityTracker,
+}
+
+impl<S> ActivityTrackingService<S> {
+    /// Wrap a service so its activity is recorded by the tracker
+    pub fn new(inner: S, tracker: ActivityTracker) -> Self {
+        ActivityTrackingService { inner, tracker }
+    }
+}
+
+impl<S: Service<RoleServer>> Service<RoleServer> for ActivityTrackingService<S> {
+    async fn handle_request(
+        &self,
+        request: ClientRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, ErrorData> {
+        let _guard = self.tracker.begin();
+        self.inner.handle_request(request, context).await
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ClientNotification,
+        context: NotificationContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.tracker.touch();
+        self.inner.handle_notification(notification, context).await
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        self.inner.get_info()
+    }
+}
+
+/// Cancel the running service once it has been idle for the timeout
+///
+/// # Arguments
+///
+/// * `tracker` - The tracker the service reports its activity to
+/// * `timeout` - How long the service may be idle before it is shut down
+/// * `cancellation_token` - Token which stops the running service
+pub async fn shutdown_when_idle(
+    tracker: ActivityTracker,
+    timeout: Duration,
+    cancellation_token: RunningServiceCancellationToken,
+) {
+    loop {
+        let wait = match tracker.idle_for() {
+            Some(idle) if idle >= timeout => {
+                cancellation_token.cancel();
+                return;
+            }
+            Some(idle) => timeout - idle,
+            None => timeout,
+        };
+        tokio::time::sleep(wait).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_for_is_none_while_request_running() {
+        let tracker = ActivityTracker::new();
+        let guard = tracker.begin();
+        assert_eq!(tracker.idle_for(), None);
+        drop(guard);
+        assert!(tracker.idle_for().is_some());
+    }
+
+    #[test]
+    fn test_touch_resets_idle_time() {
+        let tracker = ActivityTracker::new();
+        std::thread::sleep(Duration::from_millis(20));
+        let before = tracker.idle_for().unwrap();
+        tracker.touch();
+        assert!(tracker.idle_for().unwrap() < before);
+    }
+}
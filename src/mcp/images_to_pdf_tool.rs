@@ -0,0 +1,109 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    invalid_params, optional_bool, optional_str, optional_u32, output_policy, required_str,
+    required_str_array, workspace,
+};
+use crate::{PageSize, PdfBundleOptions};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Combine an ordered list of images into a single PDF
+async fn images_to_pdf_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let inputs = required_str_array(arguments, "inputs")?;
+    let output = required_str(arguments, "output")?;
+
+    let mut options = PdfBundleOptions::new(inputs, output);
+    if let Some(page_size) = optional_str(arguments, "page_size") {
+        options.page_size =
+            Some(PageSize::parse(page_size).map_err(|e| invalid_params(e.to_string()))?);
+    }
+    options.landscape = optional_bool(arguments, "landscape").unwrap_or(false);
+    options.margin = optional_u32(arguments, "margin")?.unwrap_or(0);
+    options.density = optional_u32(arguments, "density")?;
+    if let Some(background) = optional_str(arguments, "background") {
+        options.background = background.to_string();
+    }
+
+    match crate::images_to_pdf(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(output) => {
+            let result = json!({
+                "output": output.path.to_string_lossy(),
+                "pages": options.inputs.len(),
+                "skipped": output.skipped,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("PDF creation failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the images_to_pdf tool route
+pub fn images_to_pdf_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "inputs": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Paths to the images, one page each, in page order."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the PDF to, ending in .pdf."
+            },
+            "page_size": {
+                "type": "string",
+                "description": "Size of every page: a3, a4, a5, letter, legal, tabloid, or WIDTHxHEIGHT in points (1/72 inch), e.g. '600x800'. Images are scaled to fit inside the margins and centered. When omitted, each page takes the size of its image."
+            },
+            "landscape": {
+                "type": "boolean",
+                "description": "Turn the pages sideways. Requires page_size. Defaults to false."
+            },
+            "margin": {
+                "type": "integer",
+                "description": "Blank space around each image in points (1/72 inch), e.g. 36 for half an inch. Defaults to 0."
+            },
+            "density": {
+                "type": "integer",
+                "description": "Resolution images are placed at in dots per inch. Defaults to 150; higher keeps more detail in larger files."
+            },
+            "background": {
+                "type": "string",
+                "description": "Color of the page around the images and behind transparent areas. Defaults to white."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when the output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen name is reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["inputs", "output"]
+    });
+    let tool = Tool::new(
+        "images_to_pdf",
+        "Combine an ordered list of images into a single PDF, one page per image, with an optional page size, orientation, and margin.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(images_to_pdf_tool(context)))
+}
@@ -0,0 +1,21 @@
+use crate::InlinePreview;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use rmcp::model::Content;
+use serde_json::{Value, json};
+
+/// Encode a preview as a base64 image content block
+pub fn image_content(preview: &InlinePreview) -> Content {
+    Content::image(STANDARD.encode(&preview.data), preview.mime_type)
+}
+
+/// Describe a preview in a structured result, next to the full-resolution file
+pub fn preview_summary(preview: &InlinePreview) -> Value {
+    json!({
+        "mime_type": preview.mime_type,
+        "bytes": preview.data.len(),
+        "width": preview.width,
+        "height": preview.height,
+        "transcoded": preview.transcoded
+    })
+}
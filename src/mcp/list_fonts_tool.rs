@@ -0,0 +1,63 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::optional_str;
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// List the fonts available to the installed ImageMagick
+async fn list_fonts_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let query = optional_str(&context.arguments, "query").map(str::to_lowercase);
+
+    match crate::list_fonts() {
+        Ok(fonts) => {
+            let fonts: Vec<_> = fonts
+                .into_iter()
+                .filter(|font| {
+                    query.as_ref().is_none_or(|query| {
+                        font.name.to_lowercase().contains(query)
+                            || font
+                                .family
+                                .as_ref()
+                                .is_some_and(|family| family.to_lowercase().contains(query))
+                    })
+                })
+                .collect();
+            let result = json!({
+                "fonts": fonts,
+                "count": fonts.len(),
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Failed to list fonts: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the list_fonts tool route
+pub fn list_fonts_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "query": {
+                "type": "string",
+                "description": "Only list fonts whose name or family contains this text, ignoring case, e.g. 'dejavu' or 'serif'. Omit to list every font."
+            }
+        },
+        "required": []
+    });
+    let tool = Tool::new(
+        "list_fonts",
+        "List the fonts available to ImageMagick, from 'magick -list font'. Each font has the name to pass to -font, its family, style, stretch, weight, and glyphs file. Use this to pick a valid font before running -annotate, label:, or caption: commands.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(list_fonts_tool(context)))
+}
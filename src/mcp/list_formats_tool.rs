@@ -0,0 +1,77 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::optional_str_array;
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// List the image formats supported by the installed ImageMagick
+async fn list_formats_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let names = optional_str_array(&context.arguments, "names")?;
+
+    match crate::list_formats() {
+        Ok(formats) => {
+            let selected: Vec<_> = formats
+                .iter()
+                .filter(|format| {
+                    names.as_ref().is_none_or(|names| {
+                        names
+                            .iter()
+                            .any(|name| name.eq_ignore_ascii_case(&format.name))
+                    })
+                })
+                .map(|format| {
+                    json!({
+                        "format": format.name,
+                        "readable": format.readable,
+                        "writable": format.writable,
+                        "multiframe": format.multi_frame,
+                        "description": format.description
+                    })
+                })
+                .collect();
+            let missing: Vec<&String> = names
+                .iter()
+                .flatten()
+                .filter(|name| !formats.iter().any(|f| f.name.eq_ignore_ascii_case(name)))
+                .collect();
+            let result = json!({
+                "formats": selected,
+                "count": selected.len(),
+                "missing": missing,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Failed to list formats: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the list_formats tool route
+pub fn list_formats_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "names": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Only report these formats, e.g. ['HEIC', 'AVIF', 'WEBP']. Names the installed ImageMagick does not know are listed in 'missing'. Omit to list every format."
+            }
+        },
+        "required": []
+    });
+    let tool = Tool::new(
+        "list_formats",
+        "List the image formats the installed ImageMagick supports, from 'magick -list format'. Each format reports whether it can be read, written, and hold multiple frames, so support for formats such as HEIC, AVIF, or WebP can be checked before converting.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(list_formats_tool(context)))
+}
@@ -0,0 +1,112 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{optional_bool, optional_str, output_policy, required_str, workspace};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+use std::path::Path;
+
+/// Run a magick command template against every file matching a glob
+async fn magick_batch_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let pattern = required_str(arguments, "glob")?;
+    let command = required_str(arguments, "command")?;
+    let output_dir = required_str(arguments, "output_dir")?;
+    let extension = optional_str(arguments, "extension");
+
+    match crate::magick_batch(
+        pattern,
+        command,
+        Path::new(output_dir),
+        extension,
+        workspace(arguments),
+        output_policy(&context)?,
+        optional_bool(arguments, "transactional").unwrap_or(false),
+    ) {
+        Ok(results) => {
+            let failed = results.iter().filter(|r| r.error.is_some()).count();
+            let files: Vec<_> = results
+                .iter()
+                .map(|result| {
+                    json!({
+                        "input": result.input.to_string_lossy(),
+                        "output": result.output.to_string_lossy(),
+                        "skipped": result.skipped,
+                        "success": result.error.is_none(),
+                        "error": result.error
+                    })
+                })
+                .collect();
+            let result = json!({
+                "files": files,
+                "processed": results.len(),
+                "failed": failed,
+                "success": failed == 0
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Batch failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the magick_batch tool route
+pub fn magick_batch_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "glob": {
+                "type": "string",
+                "description": "Glob pattern selecting files relative to the workspace (e.g. '*.png' or 'photos/**/*.jpg')."
+            },
+            "command": {
+                "type": "string",
+                "description": "ImageMagick arguments without the 'magick' prefix, containing $input and $output placeholders (e.g. '$input -resize 50% $output')."
+            },
+            "output_dir": {
+                "type": "string",
+                "description": "Directory the outputs are written to. $output is the input's file name in this directory."
+            },
+            "extension": {
+                "type": "string",
+                "description": "Optional extension for the outputs, e.g. 'webp' to convert every file."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when an output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen names are reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "skip_if_newer": {
+                "type": "boolean",
+                "description": "Skip commands whose output is newer than every input and was written by the same command on an earlier run with skip_if_newer. Skipped outputs are reported as skipped. Defaults to false."
+            },
+            "transactional": {
+                "type": "boolean",
+                "description": "Stage every output and only move them into the output directory if the command succeeds for every file. When any file fails, nothing is written and the other files report that their output was discarded. Defaults to false."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path the glob and output directory are relative to."
+            }
+        },
+        "required": ["glob", "command", "output_dir"]
+    });
+    let tool = Tool::new(
+        "magick_batch",
+        "Run one ImageMagick command template against every file matching a glob, writing into an output directory. Files are processed in parallel and each file reports its own result.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(magick_batch_tool(context)))
+}
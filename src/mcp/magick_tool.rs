@@ -1,44 +1,128 @@
+use crate::mcp::inline_image::{image_content, preview_summary};
 use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    invalid_params, optional_bool, optional_str, optional_u32, output_policy, required_str,
+    workspace,
+};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
 use rmcp::handler::server::router::tool::ToolRoute;
 use rmcp::handler::server::tool::ToolCallContext;
-use rmcp::model::{CallToolResult, ErrorCode, ErrorData, Tool};
+use rmcp::model::{CallToolResult, ErrorData, Tool};
 use serde_json::json;
-use std::path::Path;
+use std::path::PathBuf;
 
 /// Execute an ImageMagick command
 ///
-/// The provided text should be an ImageMagick command (don't include 'magick').
-/// It should not contain subcommands like 'convert', 'identify', etc.
+/// The provided text should be an ImageMagick command (don't include 'magick'), which is
+/// rejected with a suggestion. Legacy ImageMagick 6 commands such as `convert in.png out.jpg`
+/// are rewritten to ImageMagick 7 syntax and the rewrite is reported in the result.
 async fn magick_tool(
     context: ToolCallContext<'_, MagickServerHandler>,
 ) -> Result<CallToolResult, ErrorData> {
-    // Extract command parameter from context
-    let command = context
-        .arguments
-        .as_ref()
-        .and_then(|args| args.get("command"))
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| ErrorData {
-            code: ErrorCode::INVALID_PARAMS,
-            message: "Missing required parameter: command".to_string().into(),
-            data: None,
-        })?;
+    let command = required_str(&context.arguments, "command")?;
+    let workspace = workspace(&context.arguments);
 
-    // Extract optional workspace parameter from context
-    let workspace = context
-        .arguments
-        .as_ref()
-        .and_then(|args| args.get("workspace"))
-        .and_then(|v| v.as_str())
-        .map(Path::new);
+    if let Some(suggested) = crate::bad_prefix(command) {
+        return Ok(CallToolResult::structured_error(json!({
+            "error": format!(
+                "Commands must not start with 'magick', the server runs magick itself. Retry with: {suggested}"
+            ),
+            "error_code": "bad_prefix",
+            "suggested_command": suggested,
+            "success": false
+        })));
+    }
+
+    if optional_bool(&context.arguments, "estimate").unwrap_or(false) {
+        let mut options = crate::EstimateOptions::new(command);
+        options.width = optional_u32(&context.arguments, "width")?;
+        options.height = optional_u32(&context.arguments, "height")?;
+        return match crate::estimate(&options, workspace) {
+            Ok(estimate) => Ok(CallToolResult::structured(json!({
+                "estimate": estimate,
+                "success": true
+            }))),
+            Err(e) => Ok(CallToolResult::structured_error(json!({
+                "error": format!("Failed to estimate command: {}", e),
+                "success": false
+            }))),
+        };
+    }
+
+    let return_image = optional_bool(&context.arguments, "return_image").unwrap_or(false);
+    let preview_options = crate::InlinePreviewOptions {
+        max_bytes: optional_u32(&context.arguments, "max_image_bytes")?
+            .map(u64::from)
+            .unwrap_or(crate::DEFAULT_PREVIEW_BYTES),
+        max_dimension: optional_u32(&context.arguments, "max_image_dimension")?
+            .unwrap_or(crate::DEFAULT_PREVIEW_DIMENSION),
+        format: match optional_str(&context.arguments, "preview_format") {
+            Some(format) => {
+                crate::PreviewFormat::parse(format).map_err(|e| invalid_params(e.to_string()))?
+            }
+            None => crate::PreviewFormat::default(),
+        },
+    };
+
+    let policy = output_policy(&context)?;
+    let stream_files = crate::StreamFiles {
+        stdout: optional_str(&context.arguments, "save_stdout").map(PathBuf::from),
+        stderr: optional_str(&context.arguments, "save_stderr").map(PathBuf::from),
+    };
+    let executed = match optional_str(&context.arguments, "input_base64") {
+        Some(encoded) => {
+            let input_format = optional_str(&context.arguments, "input_format")
+                .ok_or_else(|| invalid_params("input_format is required with input_base64"))?;
+            let input = STANDARD
+                .decode(encoded.trim())
+                .map_err(|e| invalid_params(format!("input_base64 is not valid base64: {e}")))?;
+            crate::magick_with_input(
+                command,
+                &input,
+                input_format,
+                workspace,
+                policy,
+                stream_files.clone(),
+            )
+        }
+        None => crate::magick(command, workspace, policy, stream_files.clone()),
+    };
 
-    match crate::magick(command, workspace) {
+    match executed {
         Ok(output) => {
-            let result = json!({
-                "output": output,
+            let preview = match (&output.file, return_image) {
+                (Some(file), true) if !file.skipped || file.path.exists() => Some(
+                    crate::inline_preview(&file.path, &preview_options, workspace),
+                ),
+                _ => None,
+            };
+            let mut result = json!({
+                "output": output.output,
+                "file": output.file.as_ref().map(|file| file.path.to_string_lossy().to_string()),
+                "skipped": output.file.is_some_and(|file| file.skipped),
                 "success": true
             });
-            Ok(CallToolResult::structured(result))
+            if let Some(rewrite) = &output.rewrite {
+                result["rewritten_command"] = json!(rewrite.command);
+                result["rewrites"] = json!(rewrite.changes);
+            }
+            if let Some(path) = &stream_files.stdout {
+                result["stdout_file"] = json!(path.to_string_lossy());
+            }
+            if let Some(path) = &stream_files.stderr {
+                result["stderr_file"] = json!(path.to_string_lossy());
+            }
+            match &preview {
+                Some(Ok(preview)) => result["preview"] = preview_summary(preview),
+                Some(Err(e)) => result["image_error"] = json!(e.to_string()),
+                None => {}
+            }
+            let mut result = CallToolResult::structured(result);
+            if let Some(Ok(preview)) = &preview {
+                result.content.push(image_content(preview));
+            }
+            Ok(result)
         }
         Err(e) => {
             let error_result = json!({
@@ -59,16 +143,75 @@ pub fn magick_tool_route() -> ToolRoute<MagickServerHandler> {
                 "type": "string",
                 "description": "ImageMagick command arguments (e.g., 'test.png -negate out.png')."
             },
+            "save_stdout": {
+                "type": "string",
+                "description": "Save the command's full standard output to this file, relative to the workspace, and return only its first lines. Useful for verbose output such as identify -verbose or -list."
+            },
+            "save_stderr": {
+                "type": "string",
+                "description": "Save the command's standard error, including warnings from a successful command, to this file relative to the workspace."
+            },
+            "create_dirs": {
+                "type": "boolean",
+                "description": "Create missing output directories instead of failing. Defaults to false."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when an output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen names are reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "input_base64": {
+                "type": "string",
+                "description": "Base64 encoded input image, for images received inline rather than as a file. It is written to a temporary file which replaces $input in the command and is removed afterward."
+            },
+            "input_format": {
+                "type": "string",
+                "description": "File extension of input_base64, e.g. png or jpg. Required with input_base64."
+            },
+            "return_image": {
+                "type": "boolean",
+                "description": "Also return the output image inline as image content so it can be viewed without reading the file. Outputs which are too large or not PNG, JPEG, GIF, or WebP are sent as a compact transcoded preview while the full-resolution file stays on disk; the result's preview field reports what was sent. Defaults to false."
+            },
+            "max_image_bytes": {
+                "type": "integer",
+                "description": "Largest inline image in bytes. Defaults to 262144."
+            },
+            "max_image_dimension": {
+                "type": "integer",
+                "description": "Longest edge of the inline image in pixels. Defaults to 1024."
+            },
+            "preview_format": {
+                "type": "string",
+                "enum": ["webp", "jpeg"],
+                "description": "Format of transcoded previews. Defaults to webp."
+            },
+            "estimate": {
+                "type": "boolean",
+                "description": "Only predict the command's memory and time cost from the input size instead of running it. The verdict is ok, warn, or refuse against the configured limits; downscale first when it is not ok."
+            },
+            "width": {
+                "type": "integer",
+                "description": "Input width for estimate. Read from the input files when omitted."
+            },
+            "height": {
+                "type": "integer",
+                "description": "Input height for estimate. Read from the input files when omitted."
+            },
             "workspace": {
                 "type": "string",
                 "description": "Workspace path to set as the working directory for the command."
             }
         },
-        "required": ["command", "workspace"]
+        "required": ["command"]
     });
     let tool = Tool::new(
         "magick",
-        "Execute an ImageMagick command. The provided text should be an ImageMagick command (don't include 'magick').",
+        "Execute an ImageMagick command. The provided text should be an ImageMagick command without the program name: don't start it with 'magick'. Commands which do are rejected with error_code 'bad_prefix' and a suggested_command. Legacy ImageMagick 6 commands such as 'convert in.png out.jpg' or options such as -matte are rewritten to ImageMagick 7 syntax, reported as rewritten_command and rewrites.",
         input_schema.as_object().unwrap().clone(),
     );
     ToolRoute::new_dyn(tool, |context| Box::pin(magick_tool(context)))
@@ -0,0 +1,245 @@
+use crate::CheckReport;
+use rmcp::ErrorData;
+use rmcp::model::{
+    CallToolResult, ClientNotification, ClientRequest, ServerInfo, ServerResult, Tool,
+};
+use rmcp::service::{NotificationContext, RequestContext, RoleServer, Service};
+use serde_json::json;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Shortest time between checks of a missing installation, since each runs `magick -version`
+const RECHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Check of the ImageMagick installation
+type Check = Arc<dyn Fn() -> CheckReport + Send + Sync>;
+
+/// Tools which work without ImageMagick, managing functions or the session
+const TOOLS_WITHOUT_MAGICK: &[&str] = &[
+    "check",
+    "func_list",
+    "func_get",
+    "func_save",
+    "func_update",
+    "func_delete",
+    "workspace_set",
+];
+
+/// Names of the tools which run ImageMagick
+pub fn magick_tools(tools: &[Tool]) -> HashSet<String> {
+    tools
+        .iter()
+        .map(|tool| tool.name.to_string())
+        .filter(|name| !TOOLS_WITHOUT_MAGICK.contains(&name.as_str()))
+        .collect()
+}
+
+/// Service wrapper which answers calls needing ImageMagick with its installation instructions
+/// while it is missing
+///
+/// The server still starts and lists every tool without ImageMagick. Calls to the named tools
+/// are answered with a `not_installed` error carrying the platform's installation instructions
+/// instead of failing to spawn `magick`, and the server's instructions tell the client up front.
+/// Such a call checks the installation again, at most once every `RECHECK_INTERVAL` and on a
+/// blocking thread, so installing ImageMagick takes effect without restarting the server.
+pub struct NotInstalledService<S> {
+    inner: S,
+    tools: HashSet<String>,
+    check: Check,
+    status: Mutex<InstallStatus>,
+}
+
+/// The outcome of the latest installation check
+struct InstallStatus {
+    /// Installation instructions, `None` once ImageMagick is found
+    missing: Option<String>,
+    checked_at: Instant,
+}
+
+impl<S> NotInstalledService<S> {
+    /// Wrap a service, checking the installation now with the provided check
+    pub fn new(
+        inner: S,
+        tools: HashSet<String>,
+        check: impl Fn() -> CheckReport + Send + Sync + 'static,
+    ) -> Self {
+        let status = InstallStatus {
+            missing: missing_instructions(check()),
+            checked_at: Instant::now(),
+        };
+        NotInstalledService {
+            inner,
+            tools,
+            check: Arc::new(check),
+            status: Mutex::new(status),
+        }
+    }
+
+    /// The installation instructions while ImageMagick is missing, checking again if it was
+    /// and the last check is older than `RECHECK_INTERVAL`
+    async fn recheck(&self) -> Option<String> {
+        {
+            let mut status = self.status.lock().unwrap_or_else(|e| e.into_inner());
+            if status.missing.is_none() || status.checked_at.elapsed() < RECHECK_INTERVAL {
+                return status.missing.clone();
+            }
+            // Claim this check so concurrent calls answer from the last result meanwhile
+            status.checked_at = Instant::now();
+        }
+        let check = self.check.clone();
+        let checked = tokio::task::spawn_blocking(move || missing_instructions(check())).await;
+        let mut status = self.status.lock().unwrap_or_else(|e| e.into_inner());
+        if let Ok(missing) = checked {
+            status.missing = missing;
+        }
+        status.missing.clone()
+    }
+}
+
+impl<S: Service<RoleServer>> Service<RoleServer> for NotInstalledService<S> {
+    async fn handle_request(
+        &self,
+        request: ClientRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, ErrorData> {
+        if let ClientRequest::CallToolRequest(call) = &request
+            && self.tools.contains(call.params.name.as_ref())
+            && let Some(instructions) = self.recheck().await
+        {
+            return Ok(ServerResult::CallToolResult(not_installed_result(
+                &call.params.name,
+                &instructions,
+            )));
+        }
+        self.inner.handle_request(request, context).await
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ClientNotification,
+        context: NotificationContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.inner.handle_notification(notification, context).await
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        let mut info = self.inner.get_info();
+        let status = self.status.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(instructions) = status.missing.as_ref() {
+            info.instructions = Some(with_not_installed_notice(
+                info.instructions.as_deref(),
+                instructions,
+            ));
+        }
+        info
+    }
+}
+
+/// The installation instructions when the report finds ImageMagick missing
+fn missing_instructions(report: CheckReport) -> Option<String> {
+    if report.installed {
+        return None;
+    }
+    Some(
+        report.instructions.unwrap_or_else(|| {
+            "Install ImageMagick 7 so the magick command is available.".to_string()
+        }),
+    )
+}
+
+/// The structured error answering a call which needs ImageMagick
+fn not_installed_result(tool: &str, instructions: &str) -> CallToolResult {
+    CallToolResult::structured_error(json!({
+        "error": format!("{tool} needs ImageMagick, which is not installed"),
+        "code": "not_installed",
+        "instructions": instructions,
+        "success": false
+    }))
+}
+
+/// Add the notice that ImageMagick is missing to the server's instructions
+fn with_not_installed_notice(instructions: Option<&str>, install: &str) -> String {
+    let notice = format!(
+        "Tools which run ImageMagick return a not_installed error until it is installed; only check, the func_ tools, and workspace_set work meanwhile.\n\n{install}"
+    );
+    match instructions {
+        Some(instructions) if !instructions.is_empty() => format!("{instructions}\n\n{notice}"),
+        _ => notice,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn tool(name: &'static str) -> Tool {
+        let schema = json!({ "type": "object" });
+        Tool::new(name, "", schema.as_object().unwrap().clone())
+    }
+
+    #[test]
+    fn test_magick_tools_leave_out_tools_without_magick() {
+        let tools = [tool("resize"), tool("check"), tool("func_save")];
+        assert_eq!(magick_tools(&tools), HashSet::from(["resize".to_string()]));
+    }
+
+    #[test]
+    fn test_missing_instructions() {
+        let installed = CheckReport {
+            installed: true,
+            ..Default::default()
+        };
+        assert_eq!(missing_instructions(installed), None);
+
+        let missing = CheckReport {
+            instructions: Some("brew install imagemagick".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            missing_instructions(missing).as_deref(),
+            Some("brew install imagemagick")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recheck_is_rate_limited() {
+        let checks = Arc::new(AtomicUsize::new(0));
+        let service = NotInstalledService::new((), HashSet::new(), {
+            let checks = checks.clone();
+            // Missing on the first check, installed on every later one
+            move || CheckReport {
+                installed: checks.fetch_add(1, Ordering::SeqCst) > 0,
+                ..Default::default()
+            }
+        });
+
+        assert!(service.recheck().await.is_some());
+        assert_eq!(checks.load(Ordering::SeqCst), 1);
+
+        service.status.lock().unwrap().checked_at = Instant::now() - RECHECK_INTERVAL;
+        assert_eq!(service.recheck().await, None);
+        assert_eq!(checks.load(Ordering::SeqCst), 2);
+
+        assert_eq!(service.recheck().await, None);
+        assert_eq!(checks.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_not_installed_result() {
+        let result = not_installed_result("resize", "brew install imagemagick");
+
+        assert_eq!(result.is_error, Some(true));
+        let structured = result.structured_content.unwrap();
+        assert_eq!(structured["code"], "not_installed");
+        assert_eq!(structured["instructions"], "brew install imagemagick");
+    }
+
+    #[test]
+    fn test_notice_follows_instructions() {
+        let instructions = with_not_installed_notice(Some("Be brief."), "brew install imagemagick");
+        assert!(instructions.starts_with("Be brief.\n\nTools which run ImageMagick"));
+        assert!(instructions.ends_with("brew install imagemagick"));
+    }
+}
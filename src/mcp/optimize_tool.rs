@@ -0,0 +1,124 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    invalid_params, optional_u32, output_policy, output_settings, preset, required_str, workspace,
+};
+use crate::{OptimizeOptions, parse_byte_size};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Compress an image to fit a file size
+async fn optimize_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output = required_str(arguments, "output")?;
+    let max_bytes = parse_byte_size(required_str(arguments, "max_size")?)
+        .map_err(|e| invalid_params(e.to_string()))?;
+
+    let mut options = OptimizeOptions::new(input, output, max_bytes);
+    let preset = preset(arguments)?;
+    if let Some(min_quality) = optional_u32(arguments, "min_quality")? {
+        options.min_quality = min_quality;
+    }
+    if let Some(max_quality) = optional_u32(arguments, "max_quality")?
+        .or_else(|| preset.as_ref().and_then(|preset| preset.quality))
+    {
+        options.max_quality = max_quality;
+    }
+    options.settings = output_settings(arguments, preset.as_ref())?;
+
+    match crate::optimize(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(optimized) => {
+            let result = json!({
+                "output": optimized.output.path.to_string_lossy(),
+                "skipped": optimized.output.skipped,
+                "quality": optimized.quality,
+                "bytes": optimized.bytes,
+                "max_bytes": max_bytes,
+                "attempts": optimized.attempts,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Optimize failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the optimize tool route
+pub fn optimize_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the source image."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the compressed image to. Its extension picks the format, which must be lossy: jpg, jpeg, webp, avif, heic, or jxl."
+            },
+            "max_size": {
+                "type": "string",
+                "description": "Largest size of the output, e.g. '200KB', '1.5MB', or '51200' bytes. KB and MB are powers of 1000; use KiB or MiB for powers of 1024."
+            },
+            "min_quality": {
+                "type": "integer",
+                "description": "Lowest quality, from 1 to 100, to compress to before giving up. Defaults to 10. If the image does not fit even at this quality, resize it first."
+            },
+            "max_quality": {
+                "type": "integer",
+                "description": "Highest quality, up to 100, to compress at even when a higher one fits. Defaults to the preset's quality, or 100."
+            },
+            "preset": {
+                "type": "string",
+                "description": "Name of a preset from the [presets] section of the config file, e.g. 'web', supplying defaults for the highest quality, max dimensions, strip, and colorspace. Arguments given with the call take precedence."
+            },
+            "max_width": {
+                "type": "integer",
+                "description": "Shrink images wider than this many pixels, keeping the aspect ratio."
+            },
+            "max_height": {
+                "type": "integer",
+                "description": "Shrink images taller than this many pixels, keeping the aspect ratio."
+            },
+            "strip": {
+                "type": "boolean",
+                "description": "Remove profiles and metadata such as EXIF."
+            },
+            "colorspace": {
+                "type": "string",
+                "description": "Colorspace to convert to, e.g. 'sRGB', 'Gray', or 'CMYK'."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when the output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen name is reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["input", "output", "max_size"]
+    });
+    let tool = Tool::new(
+        "optimize",
+        "Compress an image to fit a maximum file size, e.g. for an upload limit, at the highest quality which fits. Returns the chosen quality and the final size in bytes.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(optimize_tool(context)))
+}
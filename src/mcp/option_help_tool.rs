@@ -0,0 +1,48 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::required_str;
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Explain the arguments of one operator
+async fn option_help_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let option = required_str(&context.arguments, "option")?;
+
+    match crate::option_help(option) {
+        Ok(help) => {
+            let mut result = json!(help);
+            result["success"] = json!(true);
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Option help failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the option_help tool route
+pub fn option_help_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "option": {
+                "type": "string",
+                "description": "An operator such as '-distort', 'morphology', or '+repage'."
+            }
+        },
+        "required": ["option"]
+    });
+    let tool = Tool::new(
+        "option_help",
+        "Explain what arguments an ImageMagick operator takes. Returns each form of the option with its placeholder arguments, the keywords it accepts from 'magick -list' (e.g. distortion methods for -distort), and worked example commands for common operators. Use this to build a command for one operator without reading the full help resource.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(option_help_tool(context)))
+}
@@ -0,0 +1,104 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{invalid_params, optional_bool, optional_str, required_str, workspace};
+use crate::{OrganizeBy, PlacementStatus};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+use std::path::Path;
+
+/// Sort images matching a glob into subdirectories
+async fn organize_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let pattern = required_str(arguments, "glob")?;
+    let by = OrganizeBy::parse(required_str(arguments, "by")?)
+        .map_err(|e| invalid_params(e.to_string()))?;
+    let destination = optional_str(arguments, "destination").unwrap_or("");
+    let copy = optional_bool(arguments, "copy").unwrap_or(false);
+    let dry_run = optional_bool(arguments, "dry_run").unwrap_or(true);
+
+    match crate::organize(
+        pattern,
+        by,
+        Path::new(destination),
+        copy,
+        dry_run,
+        workspace(arguments),
+    ) {
+        Ok(placements) => {
+            let plan: Vec<_> = placements
+                .iter()
+                .map(|placement| {
+                    let (status, reason) = match &placement.status {
+                        PlacementStatus::Planned => ("planned", None),
+                        PlacementStatus::Moved => ("moved", None),
+                        PlacementStatus::Copied => ("copied", None),
+                        PlacementStatus::Skipped(reason) => ("skipped", Some(reason)),
+                    };
+                    json!({
+                        "from": placement.from.to_string_lossy(),
+                        "to": placement.to.to_string_lossy(),
+                        "status": status,
+                        "reason": reason
+                    })
+                })
+                .collect();
+            let result = json!({
+                "placements": plan,
+                "dry_run": dry_run,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Organize failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the organize tool route
+pub fn organize_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "glob": {
+                "type": "string",
+                "description": "Glob pattern selecting files relative to the workspace (e.g. 'inbox/*.jpg')."
+            },
+            "by": {
+                "type": "string",
+                "enum": ["date", "dimensions", "format"],
+                "description": "How to group images: 'date' uses YYYY/MM from the EXIF capture date (or 'undated'), 'dimensions' uses thumbnail (<=256px), web (<=2048px), or print, and 'format' uses the lowercase format name."
+            },
+            "destination": {
+                "type": "string",
+                "description": "Directory, relative to the workspace, the group subdirectories are created in. Defaults to the workspace root."
+            },
+            "copy": {
+                "type": "boolean",
+                "description": "Copy files instead of moving them. Defaults to false."
+            },
+            "dry_run": {
+                "type": "boolean",
+                "description": "When true (the default) only the plan is returned. Set to false to apply it."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path the glob is relative to."
+            }
+        },
+        "required": ["glob", "by"]
+    });
+    let tool = Tool::new(
+        "organize",
+        "Sort images matching a glob into subdirectories by capture date, dimensions class, or format, moving or copying them. Returns the plan; review it with dry_run before applying.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(organize_tool(context)))
+}
@@ -0,0 +1,105 @@
+use crate::OrientOptions;
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    optional_bool, optional_f64, optional_str, output_policy, required_str, workspace,
+};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Rotate, mirror, or auto-orient an image
+async fn orient_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output = required_str(arguments, "output")?;
+
+    let mut options = OrientOptions::new(input, output);
+    options.auto_orient = optional_bool(arguments, "auto_orient").unwrap_or(false);
+    options.rotate = optional_f64(arguments, "rotate")?;
+    options.flip = optional_bool(arguments, "flip").unwrap_or(false);
+    options.flop = optional_bool(arguments, "flop").unwrap_or(false);
+    if let Some(background) = optional_str(arguments, "background") {
+        options.background = background.to_string();
+    }
+
+    match crate::orient(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(oriented) => {
+            let result = json!({
+                "output": oriented.output.path.to_string_lossy(),
+                "skipped": oriented.output.skipped,
+                "width": oriented.dimensions.width,
+                "height": oriented.dimensions.height,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Orient failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the orient tool route
+pub fn orient_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the source image."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the reoriented image to."
+            },
+            "auto_orient": {
+                "type": "boolean",
+                "description": "Turn the image upright according to its EXIF orientation, as cameras and phones record it, and reset the tag. Applied first. Defaults to false."
+            },
+            "rotate": {
+                "type": "number",
+                "description": "Degrees to rotate clockwise, from -360 to 360. Angles which are not a multiple of 90 enlarge the canvas to fit the rotated image."
+            },
+            "flip": {
+                "type": "boolean",
+                "description": "Mirror the image top to bottom. Defaults to false."
+            },
+            "flop": {
+                "type": "boolean",
+                "description": "Mirror the image left to right. Defaults to false."
+            },
+            "background": {
+                "type": "string",
+                "description": "Color of the corners uncovered by rotating by an angle which is not a multiple of 90, e.g. 'none' for transparent. Defaults to white."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when the output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen name is reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["input", "output"]
+    });
+    let tool = Tool::new(
+        "orient",
+        "Rotate an image by degrees, flip or flop it, and/or turn it upright from its EXIF orientation. Returns the dimensions of the result.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(orient_tool(context)))
+}
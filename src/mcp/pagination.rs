@@ -0,0 +1,94 @@
+use crate::mcp::tool_args::invalid_params;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use rmcp::model::ErrorData;
+
+/// Number of items in a page when the client does not ask for a size
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// One page of a list, with the cursor for the next page if there is one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+/// Take the page of items starting at a cursor
+///
+/// Cursors are opaque to clients. They encode the offset of the first item of the page, so a
+/// list which changes between requests may skip or repeat items, but never fails.
+///
+/// # Arguments
+///
+/// * `items` - Every item in the list, in a stable order
+/// * `cursor` - The cursor returned with the previous page, or `None` for the first page
+/// * `page_size` - Largest number of items in the page
+///
+/// # Returns
+///
+/// Returns the page, or an invalid params error if the cursor is malformed
+pub fn paginate<T>(
+    items: Vec<T>,
+    cursor: Option<&str>,
+    page_size: usize,
+) -> Result<Page<T>, ErrorData> {
+    let offset = match cursor {
+        Some(cursor) => decode_cursor(cursor)
+            .ok_or_else(|| invalid_params(format!("Invalid cursor: {cursor}")))?,
+        None => 0,
+    };
+    let page_size = page_size.max(1);
+    let end = offset.saturating_add(page_size);
+    let next_cursor = (end < items.len()).then(|| encode_cursor(end));
+    let items = items.into_iter().skip(offset).take(page_size).collect();
+    Ok(Page { items, next_cursor })
+}
+
+fn encode_cursor(offset: usize) -> String {
+    URL_SAFE_NO_PAD.encode(format!("offset:{offset}"))
+}
+
+fn decode_cursor(cursor: &str) -> Option<usize> {
+    let decoded = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+    String::from_utf8(decoded)
+        .ok()?
+        .strip_prefix("offset:")?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paginate_walks_every_page() {
+        let items: Vec<u32> = (0..5).collect();
+
+        let first = paginate(items.clone(), None, 2).unwrap();
+        assert_eq!(first.items, [0, 1]);
+        let second = paginate(items.clone(), first.next_cursor.as_deref(), 2).unwrap();
+        assert_eq!(second.items, [2, 3]);
+        let last = paginate(items.clone(), second.next_cursor.as_deref(), 2).unwrap();
+        assert_eq!(last.items, [4]);
+        assert_eq!(last.next_cursor, None);
+
+        let whole = paginate(items, None, DEFAULT_PAGE_SIZE).unwrap();
+        assert_eq!(whole.items.len(), 5);
+        assert_eq!(whole.next_cursor, None);
+    }
+
+    #[test]
+    fn test_paginate_rejects_malformed_cursor() {
+        assert!(paginate(vec![1, 2, 3], Some("not a cursor"), 2).is_err());
+        assert!(paginate(vec![1, 2, 3], Some(&URL_SAFE_NO_PAD.encode("7")), 2).is_err());
+    }
+
+    #[test]
+    fn test_paginate_past_the_end_is_empty() {
+        let page = paginate(vec![1, 2, 3], Some(&encode_cursor(10)), 2).unwrap();
+
+        assert!(page.items.is_empty());
+        assert_eq!(page.next_cursor, None);
+    }
+}
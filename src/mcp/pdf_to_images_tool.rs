@@ -0,0 +1,107 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{optional_str, optional_u32, required_str, workspace};
+use crate::{OperationError, PdfPagesOptions};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Render the pages of a PDF as images
+async fn pdf_to_images_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output_dir = required_str(arguments, "output_dir")?;
+
+    let mut options = PdfPagesOptions::new(input, output_dir);
+    options.density = optional_u32(arguments, "density")?;
+    options.format = optional_str(arguments, "format").map(str::to_string);
+    options.first_page = optional_u32(arguments, "first_page")?;
+    options.last_page = optional_u32(arguments, "last_page")?;
+    options.quality = optional_u32(arguments, "quality")?;
+
+    match crate::pdf_to_images(&options, workspace(arguments)) {
+        Ok(pages) => {
+            let files: Vec<_> = pages
+                .iter()
+                .map(|page| {
+                    json!({
+                        "page": page.page,
+                        "path": page.path.to_string_lossy()
+                    })
+                })
+                .collect();
+            let result = json!({
+                "pages": files,
+                "count": pages.len(),
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(OperationError::MissingDependency(instructions)) => {
+            let error_result = json!({
+                "error": "Ghostscript is required to read PDFs but is not installed",
+                "instructions": instructions,
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("PDF rasterization failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the pdf_to_images tool route
+pub fn pdf_to_images_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the PDF."
+            },
+            "output_dir": {
+                "type": "string",
+                "description": "Directory the page images are written to. Files are named <name>-<page>.<ext>, numbered from 1, e.g. report-001.png."
+            },
+            "density": {
+                "type": "integer",
+                "description": "Resolution to render at in dots per inch. Defaults to 150; use 300 for print quality or OCR."
+            },
+            "format": {
+                "type": "string",
+                "enum": ["png", "jpg", "jpeg"],
+                "description": "Image format of the pages. Defaults to 'png'."
+            },
+            "first_page": {
+                "type": "integer",
+                "description": "Number of the first page to render, counting from 1. Defaults to the first page."
+            },
+            "last_page": {
+                "type": "integer",
+                "description": "Number of the last page to render, inclusive. Defaults to the last page."
+            },
+            "quality": {
+                "type": "integer",
+                "description": "JPEG quality from 1 to 100. Ignored for PNG."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path. Commands run from this directory."
+            }
+        },
+        "required": ["input", "output_dir"]
+    });
+    let tool = Tool::new(
+        "pdf_to_images",
+        "Render the pages of a PDF as PNG or JPEG images at a given density, optionally only a range of pages. Requires Ghostscript; when it is missing, returns instructions for installing it.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(pdf_to_images_tool(context)))
+}
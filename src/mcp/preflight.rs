@@ -0,0 +1,83 @@
+use crate::{DoctorReport, DoctorStatus, log_line};
+use rmcp::model::{LoggingLevel, LoggingMessageNotificationParam};
+use rmcp::service::{Peer, RoleServer};
+use serde_json::json;
+
+/// Check the environment once the server starts and report the result to the log and client
+///
+/// The checks are those of `magick-mcp doctor`: the ImageMagick binary and version, its
+/// delegates, the config file, the data and functions directories, and the cache size. Each is
+/// written to the log, and the whole report is sent to the client as one log message, so a
+/// misconfiguration shows up right away rather than at the first failing tool call.
+///
+/// # Arguments
+///
+/// * `peer` - The connected client
+pub async fn report_preflight(peer: Peer<RoleServer>) {
+    let Ok(report) = tokio::task::spawn_blocking(crate::doctor).await else {
+        return;
+    };
+    for check in &report.checks {
+        log_line(format!(
+            "magick-mcp preflight {} {}: {}",
+            check.name, check.status, check.message
+        ));
+    }
+    // A client which has gone away is noticed by the service itself
+    let _ = peer
+        .notify_logging_message(preflight_message(&report))
+        .await;
+}
+
+/// The log message summarizing a preflight, at the level of its worst check
+fn preflight_message(report: &DoctorReport) -> LoggingMessageNotificationParam {
+    let worst = |status| report.checks.iter().any(|check| check.status == status);
+    let level = if worst(DoctorStatus::Error) {
+        LoggingLevel::Error
+    } else if worst(DoctorStatus::Warning) {
+        LoggingLevel::Warning
+    } else {
+        LoggingLevel::Info
+    };
+    LoggingMessageNotificationParam {
+        level,
+        logger: Some("magick-mcp".to_string()),
+        data: json!({
+            "preflight": report
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CheckReport, DoctorCheck};
+
+    fn report(statuses: &[DoctorStatus]) -> DoctorReport {
+        DoctorReport {
+            ok: !statuses.contains(&DoctorStatus::Error),
+            magick: CheckReport::default(),
+            checks: statuses
+                .iter()
+                .map(|status| DoctorCheck {
+                    name: "cache".to_string(),
+                    status: *status,
+                    message: String::new(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_preflight_level_follows_worst_check() {
+        let message = preflight_message(&report(&[DoctorStatus::Ok]));
+        assert_eq!(message.level, LoggingLevel::Info);
+        assert_eq!(message.data["preflight"]["ok"], true);
+
+        let message = preflight_message(&report(&[DoctorStatus::Warning, DoctorStatus::Ok]));
+        assert_eq!(message.level, LoggingLevel::Warning);
+
+        let message = preflight_message(&report(&[DoctorStatus::Warning, DoctorStatus::Error]));
+        assert_eq!(message.level, LoggingLevel::Error);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    invalid_params, optional_f64, optional_str, output_policy, required_str, workspace,
+};
+use crate::{RedactOptions, RedactionMode, Region};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, JsonObject, Tool};
+use serde_json::json;
+
+/// Parse the `regions` argument into a list of rectangles
+fn parse_regions(arguments: &Option<JsonObject>) -> Result<Vec<Region>, ErrorData> {
+    let regions = arguments
+        .as_ref()
+        .and_then(|args| args.get("regions"))
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| invalid_params("Missing required parameter: regions"))?;
+
+    regions
+        .iter()
+        .map(|region| {
+            let field = |name: &str| {
+                region
+                    .get(name)
+                    .and_then(|v| v.as_u64())
+                    .and_then(|v| u32::try_from(v).ok())
+                    .ok_or_else(|| {
+                        invalid_params(format!(
+                            "Each region must have a non-negative integer '{name}'"
+                        ))
+                    })
+            };
+            Ok(Region {
+                x: field("x")?,
+                y: field("y")?,
+                width: field("width")?,
+                height: field("height")?,
+            })
+        })
+        .collect()
+}
+
+/// Redact regions of an image
+async fn redact_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output = required_str(arguments, "output")?;
+    let regions = parse_regions(arguments)?;
+    let mode = optional_str(arguments, "mode").unwrap_or("pixelate");
+    let mode = RedactionMode::parse(mode, optional_str(arguments, "color"))
+        .map_err(|e| invalid_params(e.to_string()))?;
+    let strength = optional_f64(arguments, "strength")?.unwrap_or(12.0).round();
+    if strength < f64::from(crate::MIN_REDACT_STRENGTH) {
+        return Err(invalid_params(format!(
+            "Parameter 'strength' must be at least {}, lower values leave the regions readable",
+            crate::MIN_REDACT_STRENGTH
+        )));
+    }
+    let strength = strength as u32;
+
+    let options = RedactOptions {
+        input: input.to_string(),
+        output: output.to_string(),
+        regions,
+        mode,
+        strength,
+    };
+
+    match crate::redact(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(output) => {
+            let result = json!({
+                "output": output.path.to_string_lossy(),
+                "skipped": output.skipped,
+                "regions": options.regions.len(),
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Redaction failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the redact tool route
+pub fn redact_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the source image."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the redacted copy to."
+            },
+            "regions": {
+                "type": "array",
+                "description": "Rectangles to redact, in pixels.",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "x": { "type": "integer" },
+                        "y": { "type": "integer" },
+                        "width": { "type": "integer" },
+                        "height": { "type": "integer" }
+                    },
+                    "required": ["x", "y", "width", "height"]
+                }
+            },
+            "mode": {
+                "type": "string",
+                "enum": ["pixelate", "blur", "fill"],
+                "description": "How to obscure the regions. Defaults to pixelate."
+            },
+            "color": {
+                "type": "string",
+                "description": "Fill color when mode is 'fill'. Defaults to black."
+            },
+            "strength": {
+                "type": "number",
+                "description": "Pixel block size for pixelate or blur sigma for blur, at least 2. Defaults to 12."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when an output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen names are reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["input", "output", "regions"]
+    });
+    let tool = Tool::new(
+        "redact",
+        "Redact rectangular regions of an image (e.g. secrets in screenshots) by pixelating, blurring, or filling them with a solid color. The source image is left untouched.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(redact_tool(context)))
+}
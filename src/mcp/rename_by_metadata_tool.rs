@@ -0,0 +1,83 @@
+use crate::RenameStatus;
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{optional_bool, required_str, workspace};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Rename files matching a glob using image metadata
+async fn rename_by_metadata_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let pattern = required_str(arguments, "glob")?;
+    let template = required_str(arguments, "template")?;
+    let dry_run = optional_bool(arguments, "dry_run").unwrap_or(true);
+
+    match crate::rename_by_metadata(pattern, template, dry_run, workspace(arguments)) {
+        Ok(entries) => {
+            let renames: Vec<_> = entries
+                .iter()
+                .map(|entry| {
+                    let (status, reason) = match &entry.status {
+                        RenameStatus::Planned => ("planned", None),
+                        RenameStatus::Renamed => ("renamed", None),
+                        RenameStatus::Skipped(reason) => ("skipped", Some(reason)),
+                    };
+                    json!({
+                        "from": entry.from.to_string_lossy(),
+                        "to": entry.to.to_string_lossy(),
+                        "status": status,
+                        "reason": reason
+                    })
+                })
+                .collect();
+            let result = json!({
+                "renames": renames,
+                "dry_run": dry_run,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Rename failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the rename_by_metadata tool route
+pub fn rename_by_metadata_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "glob": {
+                "type": "string",
+                "description": "Glob pattern selecting files relative to the workspace (e.g. 'photos/*.jpg' or '**/*.png')."
+            },
+            "template": {
+                "type": "string",
+                "description": "Template for the new file name. Fields: {date} (EXIF capture date), {width}, {height}, {format}, {orig} (original name), {ext}, {index}. The original extension is appended unless {ext} is used."
+            },
+            "dry_run": {
+                "type": "boolean",
+                "description": "When true (the default) only the rename plan is returned. Set to false to apply it."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path the glob is relative to."
+            }
+        },
+        "required": ["glob", "template"]
+    });
+    let tool = Tool::new(
+        "rename_by_metadata",
+        "Rename image files matching a glob using a template of metadata fields such as capture date and dimensions. Returns the rename plan; review it with dry_run before applying.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(rename_by_metadata_tool(context)))
+}
@@ -0,0 +1,146 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    invalid_params, optional_bool, optional_str, optional_u32, output_policy, output_settings,
+    preset, required_str, workspace,
+};
+use crate::{ResizeFit, ResizeOptions};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Resize an image to a width and height using a fit mode
+async fn resize_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output = required_str(arguments, "output")?;
+    let preset = preset(arguments)?;
+    let mut settings = output_settings(arguments, preset.as_ref())?;
+    let mut width = optional_u32(arguments, "width")?;
+    let mut height = optional_u32(arguments, "height")?;
+    // Without a size, the preset's limits become the target, only ever shrinking
+    let limits_as_size = width.is_none() && height.is_none();
+    if limits_as_size {
+        width = settings.max_width.take();
+        height = settings.max_height.take();
+    }
+
+    let mut options = ResizeOptions::new(input, output, width, height);
+    if let Some(fit) = optional_str(arguments, "fit") {
+        options.fit = ResizeFit::parse(fit).map_err(|e| invalid_params(e.to_string()))?;
+    }
+    options.shrink_only = optional_bool(arguments, "shrink_only").unwrap_or(limits_as_size);
+    if let Some(gravity) = optional_str(arguments, "gravity") {
+        options.gravity = gravity.to_string();
+    }
+    if let Some(background) = optional_str(arguments, "background") {
+        options.background = background.to_string();
+    }
+    options.quality = match optional_u32(arguments, "quality")? {
+        Some(quality) => Some(quality),
+        None => preset.as_ref().and_then(|preset| preset.quality),
+    };
+    options.settings = settings;
+
+    match crate::resize(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(output) => {
+            let result = json!({
+                "output": output.path.to_string_lossy(),
+                "skipped": output.skipped,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Resize failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the resize tool route
+pub fn resize_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the source image."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the resized image to."
+            },
+            "width": {
+                "type": "integer",
+                "description": "Target width in pixels. May be omitted with fit 'contain' to scale by height alone. When neither width nor height is given, the preset's max_width and max_height are used with shrink_only."
+            },
+            "height": {
+                "type": "integer",
+                "description": "Target height in pixels. May be omitted with fit 'contain' to scale by width alone."
+            },
+            "fit": {
+                "type": "string",
+                "enum": ["contain", "cover", "fill", "exact"],
+                "description": "contain: fit inside the box keeping the aspect ratio (default). cover: fill the box keeping the aspect ratio and crop the overflow. fill: fit inside the box and pad to exactly width x height with the background color. exact: stretch to exactly width x height."
+            },
+            "shrink_only": {
+                "type": "boolean",
+                "description": "Only shrink images larger than the target, never enlarge. Defaults to false."
+            },
+            "gravity": {
+                "type": "string",
+                "enum": ["NorthWest", "North", "NorthEast", "West", "Center", "East", "SouthWest", "South", "SouthEast"],
+                "description": "Part of the image kept by cover, or where the image is placed by fill. Defaults to Center."
+            },
+            "background": {
+                "type": "string",
+                "description": "Padding color for fill, e.g. 'white' or 'none' for transparent. Defaults to white."
+            },
+            "preset": {
+                "type": "string",
+                "description": "Name of a preset from the [presets] section of the config file, e.g. 'web', supplying defaults for quality, max dimensions, strip, and colorspace. Arguments given with the call take precedence."
+            },
+            "quality": {
+                "type": "integer",
+                "minimum": 0,
+                "maximum": 100,
+                "description": "Quality for lossy formats, or compression level for formats like PNG."
+            },
+            "strip": {
+                "type": "boolean",
+                "description": "Remove profiles and metadata such as EXIF."
+            },
+            "colorspace": {
+                "type": "string",
+                "description": "Colorspace to convert to, e.g. 'sRGB', 'Gray', or 'CMYK'."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when an output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen names are reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["input", "output"]
+    });
+    let tool = Tool::new(
+        "resize",
+        "Resize an image to a width and height with a fit mode, without needing ImageMagick geometry syntax.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(resize_tool(context)))
+}
@@ -1,46 +1,116 @@
 use rmcp::handler::server::ServerHandler;
 use rmcp::model::{
     ErrorCode, ErrorData, ListResourcesResult, ReadResourceResult, ResourceContents, ServerInfo,
+    SetLevelRequestParam, SubscribeRequestParam, UnsubscribeRequestParam,
 };
 use rmcp::service::{RequestContext, RoleServer};
 
+use crate::Config;
+use crate::mcp::capabilities_resource::{
+    CAPABILITIES_RESOURCE_URI, capabilities_resource, read_capabilities_resource,
+};
+use crate::mcp::functions_resource::{
+    FUNCTIONS_RESOURCE_URI, functions_resource, read_functions_resource,
+};
 use crate::mcp::help_resource::{HELP_RESOURCE_URI, help_resource, read_help_resource};
+use crate::mcp::pagination::{DEFAULT_PAGE_SIZE, paginate};
+use crate::mcp::state::ServerState;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Instructions given to every client, before any configured additions
+const DEFAULT_INSTRUCTIONS: &str =
+    "A Model Context Protocol server for checking ImageMagick installation.";
 
 /// Server handler for MCP tools
-pub struct MagickServerHandler;
+///
+/// Clones of the handler, including the one each tool route is called with, share one
+/// `ServerState`.
+#[derive(Debug, Clone, Default)]
+pub struct MagickServerHandler {
+    state: Arc<ServerState>,
+}
+
+impl MagickServerHandler {
+    /// Create a handler which presents itself using the configured metadata and keeps
+    /// functions and the trash in the provided data directory
+    pub fn new(config: Config, data_dir: Option<PathBuf>) -> Self {
+        MagickServerHandler {
+            state: Arc::new(ServerState::new(config, data_dir)),
+        }
+    }
+
+    /// The state shared by every clone of the handler
+    pub fn state(&self) -> &Arc<ServerState> {
+        &self.state
+    }
+
+    /// The built-in instructions followed by any configured instructions
+    fn instructions(&self) -> String {
+        match self
+            .state
+            .server_config()
+            .instructions
+            .as_deref()
+            .map(str::trim)
+        {
+            Some(extra) if !extra.is_empty() => format!("{DEFAULT_INSTRUCTIONS}\n\n{extra}"),
+            _ => DEFAULT_INSTRUCTIONS.to_string(),
+        }
+    }
+}
 
 impl ServerHandler for MagickServerHandler {
     fn get_info(&self) -> ServerInfo {
+        let server_config = self.state.server_config();
         ServerInfo {
             protocol_version: rmcp::model::ProtocolVersion::LATEST,
             capabilities: rmcp::model::ServerCapabilities {
-                tools: Some(rmcp::model::ToolsCapability { list_changed: None }),
-                resources: Some(rmcp::model::ResourcesCapability::default()),
+                tools: Some(rmcp::model::ToolsCapability {
+                    list_changed: Some(true),
+                }),
+                resources: Some(rmcp::model::ResourcesCapability {
+                    subscribe: Some(true),
+                    list_changed: None,
+                }),
+                logging: Some(rmcp::model::JsonObject::new()),
                 ..Default::default()
             },
             server_info: rmcp::model::Implementation {
-                name: "magick-mcp".to_string(),
+                name: server_config
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| "magick-mcp".to_string()),
                 version: env!("CARGO_PKG_VERSION").to_string(),
-                title: None,
+                title: server_config.title.clone(),
                 icons: None,
-                website_url: None,
+                website_url: server_config.website_url.clone(),
             },
-            instructions: Some(
-                "A Model Context Protocol server for checking ImageMagick installation."
-                    .to_string(),
-            ),
+            instructions: Some(self.instructions()),
         }
     }
 
     fn list_resources(
         &self,
-        _request: Option<rmcp::model::PaginatedRequestParam>,
+        request: Option<rmcp::model::PaginatedRequestParam>,
         _context: RequestContext<RoleServer>,
     ) -> impl std::future::Future<Output = Result<ListResourcesResult, ErrorData>> + Send + '_ {
-        std::future::ready(Ok(ListResourcesResult {
-            resources: vec![help_resource()],
-            next_cursor: None,
-        }))
+        let cursor = request.and_then(|request| request.cursor);
+        std::future::ready(
+            paginate(
+                vec![
+                    help_resource(),
+                    functions_resource(),
+                    capabilities_resource(),
+                ],
+                cursor.as_deref(),
+                DEFAULT_PAGE_SIZE,
+            )
+            .map(|page| ListResourcesResult {
+                resources: page.items,
+                next_cursor: page.next_cursor,
+            }),
+        )
     }
 
     fn read_resource(
@@ -60,13 +130,131 @@ impl ServerHandler for MagickServerHandler {
                         data: None,
                     }),
                 }
+            } else if request.uri == FUNCTIONS_RESOURCE_URI {
+                match read_functions_resource(&self.state.function_store(None)) {
+                    Ok(functions) => Ok(ReadResourceResult {
+                        contents: vec![ResourceContents::text(functions, FUNCTIONS_RESOURCE_URI)],
+                    }),
+                    Err(e) => Err(ErrorData {
+                        code: ErrorCode::INTERNAL_ERROR,
+                        message: format!("Failed to list functions: {e}").into(),
+                        data: None,
+                    }),
+                }
+            } else if request.uri == CAPABILITIES_RESOURCE_URI {
+                match read_capabilities_resource() {
+                    Ok(capabilities) => Ok(ReadResourceResult {
+                        contents: vec![ResourceContents::text(
+                            capabilities,
+                            CAPABILITIES_RESOURCE_URI,
+                        )],
+                    }),
+                    Err(e) => Err(ErrorData {
+                        code: ErrorCode::INTERNAL_ERROR,
+                        message: format!("Failed to read capabilities: {e}").into(),
+                        data: None,
+                    }),
+                }
             } else {
-                Err(ErrorData {
-                    code: ErrorCode::INVALID_PARAMS,
-                    message: format!("Unknown resource URI: {}", request.uri).into(),
-                    data: None,
-                })
+                Err(unknown_resource(&request.uri))
             }
         })
     }
+
+    fn subscribe(
+        &self,
+        request: SubscribeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> impl std::future::Future<Output = Result<(), ErrorData>> + Send + '_ {
+        std::future::ready(match request.uri.as_str() {
+            FUNCTIONS_RESOURCE_URI => {
+                self.state.functions_subscription().subscribe(context.peer);
+                Ok(())
+            }
+            // These never change while the server runs, so there is nothing to notify
+            HELP_RESOURCE_URI | CAPABILITIES_RESOURCE_URI => Ok(()),
+            uri => Err(unknown_resource(uri)),
+        })
+    }
+
+    fn set_level(
+        &self,
+        _request: SetLevelRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> impl std::future::Future<Output = Result<(), ErrorData>> + Send + '_ {
+        // The only log message is the preflight summary sent once at startup, so there is no
+        // later output for a level to filter
+        std::future::ready(Ok(()))
+    }
+
+    fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> impl std::future::Future<Output = Result<(), ErrorData>> + Send + '_ {
+        std::future::ready(match request.uri.as_str() {
+            FUNCTIONS_RESOURCE_URI => {
+                self.state.functions_subscription().unsubscribe();
+                Ok(())
+            }
+            HELP_RESOURCE_URI | CAPABILITIES_RESOURCE_URI => Ok(()),
+            uri => Err(unknown_resource(uri)),
+        })
+    }
+}
+
+/// Error for a resource URI the server does not provide
+fn unknown_resource(uri: &str) -> ErrorData {
+    ErrorData {
+        code: ErrorCode::INVALID_PARAMS,
+        message: format!("Unknown resource URI: {uri}").into(),
+        data: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ServerConfig;
+
+    #[test]
+    fn test_get_info_defaults() {
+        let info = MagickServerHandler::default().get_info();
+        assert_eq!(info.server_info.name, "magick-mcp");
+        assert_eq!(info.server_info.title, None);
+        assert_eq!(info.instructions.as_deref(), Some(DEFAULT_INSTRUCTIONS));
+        assert_eq!(info.capabilities.resources.unwrap().subscribe, Some(true));
+    }
+
+    #[test]
+    fn test_get_info_uses_server_config() {
+        let handler = MagickServerHandler::new(
+            Config {
+                server: ServerConfig {
+                    name: Some("acme-images".to_string()),
+                    title: Some("Acme Images".to_string()),
+                    website_url: Some("https://example.com".to_string()),
+                    instructions: Some("Always write outputs to ./generated".to_string()),
+                    workspace: None,
+                },
+                ..Default::default()
+            },
+            None,
+        );
+
+        let info = handler.get_info();
+
+        assert_eq!(info.server_info.name, "acme-images");
+        assert_eq!(info.server_info.title.as_deref(), Some("Acme Images"));
+        assert_eq!(
+            info.server_info.website_url.as_deref(),
+            Some("https://example.com")
+        );
+        assert_eq!(
+            info.instructions.as_deref(),
+            Some(
+                "A Model Context Protocol server for checking ImageMagick installation.\n\nAlways write outputs to ./generated"
+            )
+        );
+    }
 }
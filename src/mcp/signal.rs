@@ -0,0 +1,29 @@
+/// Wait for an interrupt or termination signal, then remove temporary files and exit
+///
+/// Exiting skips destructors, so files held by in-flight tool calls are removed through the
+/// cleanup guard instead. The exit status follows the shell convention of 128 plus the signal
+/// number.
+pub async fn exit_on_signal() {
+    let signal = wait_for_signal().await;
+    crate::remove_managed();
+    std::process::exit(128 + signal);
+}
+
+#[cfg(unix)]
+async fn wait_for_signal() -> i32 {
+    use tokio::signal::unix::{SignalKind, signal};
+    let Ok(mut terminate) = signal(SignalKind::terminate()) else {
+        let _ = tokio::signal::ctrl_c().await;
+        return 2;
+    };
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => 2,
+        _ = terminate.recv() => 15,
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() -> i32 {
+    let _ = tokio::signal::ctrl_c().await;
+    2
+}
@@ -0,0 +1,83 @@
+use crate::SnapshotCheckOptions;
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{optional_f64, optional_str, required_str, workspace};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Compare an image against its stored snapshot baseline
+async fn snapshot_check_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let name = required_str(arguments, "name")?;
+    let path = required_str(arguments, "path")?;
+
+    let mut options = SnapshotCheckOptions::new(name, path);
+    if let Some(threshold) = optional_f64(arguments, "threshold")? {
+        options.threshold = threshold;
+    }
+    options.diff = optional_str(arguments, "diff").map(str::to_string);
+
+    match crate::snapshot_check(&options, workspace(arguments)) {
+        Ok(check) => {
+            let result = json!({
+                "status": check.status,
+                "ssim": check.ssim,
+                "threshold": options.threshold,
+                "baseline": check.baseline.to_string_lossy(),
+                "width": check.dimensions.width,
+                "height": check.dimensions.height,
+                "baseline_width": check.baseline_dimensions.map(|d| d.width),
+                "baseline_height": check.baseline_dimensions.map(|d| d.height),
+                "diff": check.diff.map(|diff| diff.to_string_lossy().to_string()),
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Snapshot check failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the snapshot_check tool route
+pub fn snapshot_check_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "name": {
+                "type": "string",
+                "description": "Name of the snapshot, made of letters, digits, '-', '_', and '.', e.g. 'home-page'."
+            },
+            "path": {
+                "type": "string",
+                "description": "Path to the image to check. Only the first frame is compared."
+            },
+            "threshold": {
+                "type": "number",
+                "description": "Lowest structural similarity (SSIM) to the baseline, from 0 to 1, which still matches. Defaults to 0.99; lower it to tolerate small rendering differences."
+            },
+            "diff": {
+                "type": "string",
+                "description": "Path to write an image highlighting the differences to when the check fails."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["name", "path"]
+    });
+    let tool = Tool::new(
+        "snapshot_check",
+        "Visual regression check: compare an image to the baseline stored under a name. The status is match, mismatch (similarity below the threshold), size_mismatch (different dimensions), or missing (no baseline yet). Call snapshot_update to accept the image as the new baseline.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(snapshot_check_tool(context)))
+}
@@ -0,0 +1,61 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{required_str, workspace};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Accept an image as the snapshot baseline for a name
+async fn snapshot_update_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let name = required_str(arguments, "name")?;
+    let path = required_str(arguments, "path")?;
+
+    match crate::snapshot_update(name, path, workspace(arguments)) {
+        Ok(update) => {
+            let result = json!({
+                "baseline": update.baseline.to_string_lossy(),
+                "replaced": update.replaced,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Snapshot update failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the snapshot_update tool route
+pub fn snapshot_update_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "name": {
+                "type": "string",
+                "description": "Name of the snapshot, made of letters, digits, '-', '_', and '.', e.g. 'home-page'."
+            },
+            "path": {
+                "type": "string",
+                "description": "Path to the image to store as the baseline. Only the first frame is stored."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["name", "path"]
+    });
+    let tool = Tool::new(
+        "snapshot_update",
+        "Store an image as the snapshot baseline for a name, replacing any earlier baseline. Use it to record a new snapshot or accept an intended change reported by snapshot_check.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(snapshot_update_tool(context)))
+}
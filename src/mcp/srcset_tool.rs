@@ -0,0 +1,122 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    invalid_params, optional_bool, optional_f64, optional_str, optional_str_array,
+    optional_u32_array, required_str, workspace,
+};
+use crate::{SrcsetOptions, WebFormat};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Generate a responsive image set from one source image
+async fn srcset_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output_dir = required_str(arguments, "output_dir")?;
+
+    let mut options = SrcsetOptions::new(input, output_dir);
+    if let Some(widths) = optional_u32_array(arguments, "widths")? {
+        options.widths = widths;
+    }
+    if let Some(formats) = optional_str_array(arguments, "formats")? {
+        options.formats = formats
+            .iter()
+            .map(|format| WebFormat::parse(format))
+            .collect::<Result<_, _>>()
+            .map_err(|e| invalid_params(e.to_string()))?;
+    }
+    if let Some(quality) = optional_f64(arguments, "quality")? {
+        options.quality = quality as u32;
+    }
+    options.retina = optional_bool(arguments, "retina").unwrap_or(false);
+    let html = optional_bool(arguments, "html").unwrap_or(false);
+    let alt = optional_str(arguments, "alt").unwrap_or("");
+
+    match crate::srcset(&options, workspace(arguments)) {
+        Ok(files) => {
+            let manifest: Vec<_> = files
+                .iter()
+                .map(|file| {
+                    json!({
+                        "path": file.path.to_string_lossy(),
+                        "width": file.width,
+                        "height": file.height,
+                        "type": file.format.mime_type()
+                    })
+                })
+                .collect();
+            let mut result = json!({
+                "files": manifest,
+                "success": true
+            });
+            if html {
+                result["html"] = json!(crate::picture_html(&files, alt));
+            }
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Srcset generation failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the srcset tool route
+pub fn srcset_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the source image."
+            },
+            "output_dir": {
+                "type": "string",
+                "description": "Directory the generated files are written to. Files are named <name>-<width>.<ext>."
+            },
+            "widths": {
+                "type": "array",
+                "items": { "type": "integer" },
+                "description": "Target widths in pixels. Defaults to [480, 768, 1024, 1600]. Widths larger than the source are skipped."
+            },
+            "retina": {
+                "type": "boolean",
+                "description": "Also generate double-width variants for high density displays."
+            },
+            "formats": {
+                "type": "array",
+                "items": { "type": "string", "enum": ["avif", "webp", "jpeg", "png"] },
+                "description": "Formats to generate, in order of preference. Defaults to ['webp', 'jpeg']."
+            },
+            "quality": {
+                "type": "number",
+                "description": "Encoder quality from 1 to 100. Defaults to 82."
+            },
+            "html": {
+                "type": "boolean",
+                "description": "Also return an HTML <picture> snippet for the generated files."
+            },
+            "alt": {
+                "type": "string",
+                "description": "Alt text for the <img> in the HTML snippet."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path. Commands run from this directory."
+            }
+        },
+        "required": ["input", "output_dir"]
+    });
+    let tool = Tool::new(
+        "srcset",
+        "Generate a responsive set of resized web images (WebP, AVIF, JPEG, PNG) from one source and return a JSON manifest, optionally with an HTML <picture> snippet.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(srcset_tool(context)))
+}
@@ -0,0 +1,185 @@
+use crate::mcp::subscriptions::FunctionsSubscription;
+use crate::mcp::workspace::WorkspaceDefaults;
+use crate::{
+    CheckReport, Config, FunctionStore, FunctionsConfig, MagickConfig, ServerConfig, Trash,
+    TrashConfig,
+};
+use std::path::{Path, PathBuf};
+
+/// State shared by the handler, every tool route, and the tasks running beside the server
+///
+/// The handler holds it behind an `Arc`, so every clone of the handler, and every tool call
+/// running concurrently, sees the same state. Anything mutable guards itself, so calls only
+/// ever need a shared reference.
+#[derive(Debug, Default)]
+pub struct ServerState {
+    server_config: ServerConfig,
+    workspace_defaults: WorkspaceDefaults,
+    functions_subscription: FunctionsSubscription,
+    trash_config: TrashConfig,
+    functions_config: FunctionsConfig,
+    language: Option<String>,
+    magick_config: MagickConfig,
+    data_dir: Option<PathBuf>,
+}
+
+impl ServerState {
+    /// Create the state of a server using the configured metadata, workspace, trash, function
+    /// settings, and `magick` command
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The config the server runs with
+    /// * `data_dir` - Directory holding saved functions and the trash, if there is one
+    pub fn new(config: Config, data_dir: Option<PathBuf>) -> Self {
+        ServerState {
+            workspace_defaults: WorkspaceDefaults::new(config.server.workspace.as_deref()),
+            server_config: config.server,
+            functions_subscription: FunctionsSubscription::default(),
+            trash_config: config.trash,
+            functions_config: config.functions,
+            language: config.language,
+            magick_config: config.magick,
+            data_dir,
+        }
+    }
+
+    /// The function store in the server's data directory, layering the workspace's functions
+    /// over it
+    pub fn function_store(&self, workspace: Option<&Path>) -> FunctionStore {
+        FunctionStore::in_data_dir(self.data_dir.clone())
+            .with_workspace(workspace)
+            .with_trash(self.trash())
+    }
+
+    /// The trash in the server's data directory, with the configured retention
+    pub fn trash(&self) -> Trash {
+        Trash::in_data_dir(self.data_dir.clone(), &self.trash_config)
+    }
+
+    /// The trash keeping outputs before tool calls overwrite them, if the config asks for it
+    pub fn output_trash(&self) -> Option<Trash> {
+        self.trash_config.outputs.then(|| self.trash())
+    }
+
+    /// The `[server]` section of the config the server started with
+    pub fn server_config(&self) -> &ServerConfig {
+        &self.server_config
+    }
+
+    /// The `[functions]` section of the config the server started with
+    pub fn functions_config(&self) -> &FunctionsConfig {
+        &self.functions_config
+    }
+
+    /// Check the ImageMagick installation with the configured language and `magick` command
+    ///
+    /// Runs `magick -version`, so async callers should run it on a blocking thread.
+    pub fn check_report(&self) -> CheckReport {
+        crate::check_report_with(self.language.as_deref(), &self.magick_config)
+    }
+
+    /// The fallback workspaces of tool calls, including the session default
+    pub fn workspace_defaults(&self) -> &WorkspaceDefaults {
+        &self.workspace_defaults
+    }
+
+    /// The subscription to the functions resource
+    pub fn functions_subscription(&self) -> &FunctionsSubscription {
+        &self.functions_subscription
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::workspace::WorkspaceSource;
+    use std::sync::Arc;
+    use std::thread;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_concurrent_calls_share_the_session_workspace() {
+        let state = Arc::new(ServerState::default());
+        let dirs: Vec<TempDir> = (0..8).map(|_| TempDir::new().unwrap()).collect();
+        let paths: Arc<Vec<_>> =
+            Arc::new(dirs.iter().map(|dir| dir.path().to_path_buf()).collect());
+
+        let handles: Vec<_> = (0..paths.len())
+            .map(|index| {
+                let state = Arc::clone(&state);
+                let paths = Arc::clone(&paths);
+                thread::spawn(move || {
+                    for _ in 0..100 {
+                        state
+                            .workspace_defaults()
+                            .set_session(Some(paths[index].clone()));
+                        let resolved = state.workspace_defaults().resolve(None);
+                        // Another call may have set its own session in between, but never a
+                        // workspace no call set
+                        assert_eq!(resolved.source, WorkspaceSource::Session);
+                        assert!(paths.contains(&resolved.path));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let resolved = state.workspace_defaults().resolve(None);
+        assert!(paths.contains(&resolved.path));
+    }
+
+    #[test]
+    fn test_concurrent_calls_see_a_cleared_session() {
+        let dir = TempDir::new().unwrap();
+        let state = Arc::new(ServerState::new(
+            Config {
+                server: ServerConfig {
+                    workspace: Some(dir.path().to_string_lossy().into_owned()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            None,
+        ));
+        state
+            .workspace_defaults()
+            .set_session(Some(dir.path().join("session")));
+
+        let clearing = {
+            let state = Arc::clone(&state);
+            thread::spawn(move || state.workspace_defaults().set_session(None))
+        };
+        clearing.join().unwrap();
+
+        let resolved = state.workspace_defaults().resolve(None);
+        assert_eq!(resolved.source, WorkspaceSource::Config);
+        assert_eq!(resolved.path, dir.path());
+    }
+
+    #[test]
+    fn test_output_trash_uses_the_injected_config_and_data_dir() {
+        let dir = TempDir::new().unwrap();
+        let data_dir = Some(dir.path().to_path_buf());
+        let state = ServerState::new(Config::default(), data_dir.clone());
+        assert_eq!(state.output_trash(), None);
+
+        let trash_config = TrashConfig {
+            retention_days: Some(30),
+            outputs: true,
+        };
+        let state = ServerState::new(
+            Config {
+                trash: trash_config.clone(),
+                ..Default::default()
+            },
+            data_dir.clone(),
+        );
+        assert_eq!(
+            state.output_trash(),
+            Some(Trash::in_data_dir(data_dir, &trash_config))
+        );
+    }
+}
@@ -0,0 +1,276 @@
+use crate::{PresetConfig, StrictCheckOptions, StrictFinding};
+use rmcp::ErrorData;
+use rmcp::model::{
+    CallToolResult, ClientNotification, ClientRequest, JsonObject, ServerInfo, ServerResult, Tool,
+};
+use rmcp::service::{NotificationContext, RequestContext, RoleServer, Service};
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Arguments which set the output size explicitly, so a preset's limits are not implicit
+const SIZE_ARGUMENTS: &[&str] = &["width", "height", "max_width", "max_height"];
+
+/// Names of the tools whose input schema has both an `input` and an `output` property
+pub fn strict_tools(tools: &[Tool]) -> HashSet<String> {
+    tools
+        .iter()
+        .filter(|tool| {
+            tool.input_schema
+                .get("properties")
+                .and_then(Value::as_object)
+                .is_some_and(|properties| {
+                    properties.contains_key("input") && properties.contains_key("output")
+                })
+        })
+        .map(|tool| tool.name.to_string())
+        .collect()
+}
+
+/// Service wrapper which refuses calls with likely-unintended consequences in strict mode
+///
+/// Before a call to one of the named tools runs, its input is checked for an implicit
+/// downscale by a preset, transparency lost to an opaque format, an EXIF rotation left
+/// unapplied, and the output replacing the input. The call is refused with the list of
+/// concerns unless each one's code is passed in its `acknowledge` argument, which is added to
+/// the tools' schemas.
+pub struct StrictService<S> {
+    inner: S,
+    tools: HashSet<String>,
+}
+
+impl<S> StrictService<S> {
+    /// Wrap a service so calls to the named tools are checked, leaving others untouched
+    pub fn new(inner: S, tools: HashSet<String>) -> Self {
+        StrictService { inner, tools }
+    }
+}
+
+impl<S: Service<RoleServer>> Service<RoleServer> for StrictService<S> {
+    async fn handle_request(
+        &self,
+        request: ClientRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, ErrorData> {
+        if let ClientRequest::CallToolRequest(call) = &request
+            && self.tools.contains(call.params.name.as_ref())
+        {
+            let arguments = call.params.arguments.clone().unwrap_or_default();
+            if let Some(refusal) = review(&call.params.name, &arguments) {
+                return Ok(ServerResult::CallToolResult(refusal));
+            }
+        }
+        let lists_tools = matches!(request, ClientRequest::ListToolsRequest(_));
+        match self.inner.handle_request(request, context).await? {
+            ServerResult::ListToolsResult(mut result) if lists_tools => {
+                for tool in &mut result.tools {
+                    if self.tools.contains(tool.name.as_ref()) {
+                        add_acknowledge_property(tool);
+                    }
+                }
+                Ok(ServerResult::ListToolsResult(result))
+            }
+            other => Ok(other),
+        }
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ClientNotification,
+        context: NotificationContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.inner.handle_notification(notification, context).await
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        self.inner.get_info()
+    }
+}
+
+/// Check a call and build the refusal when it has unacknowledged concerns
+///
+/// Calls whose input cannot be read are let through, so the tool reports the problem itself.
+fn review(tool: &str, arguments: &JsonObject) -> Option<CallToolResult> {
+    let preset = arguments
+        .get("preset")
+        .and_then(Value::as_str)
+        .and_then(|name| crate::load_preset(name).ok());
+    let options = check_options(tool, arguments, preset.as_ref())?;
+    let workspace = arguments
+        .get("workspace")
+        .and_then(Value::as_str)
+        .map(Path::new);
+    let findings = crate::strict_check(&options, workspace).ok()?;
+
+    let acknowledged = acknowledged(arguments);
+    let concerns: Vec<&StrictFinding> = findings
+        .iter()
+        .filter(|finding| !acknowledged.contains(finding.concern.code()))
+        .collect();
+    if concerns.is_empty() {
+        return None;
+    }
+    let codes: Vec<&str> = concerns
+        .iter()
+        .map(|finding| finding.concern.code())
+        .collect();
+    Some(CallToolResult::structured_error(json!({
+        "error": format!(
+            "Strict mode refused the call: {}. Pass the codes in acknowledge to proceed anyway.",
+            codes.join(", ")
+        ),
+        "concerns": concerns
+            .iter()
+            .map(|finding| json!({
+                "code": finding.concern.code(),
+                "message": finding.message,
+            }))
+            .collect::<Vec<_>>(),
+        "success": false
+    })))
+}
+
+/// Describe what a call is about to do, or `None` when it names no input
+///
+/// # Arguments
+///
+/// * `tool` - The name of the tool called
+/// * `arguments` - The arguments of the call
+/// * `preset` - The preset named by the call, if it exists
+fn check_options(
+    tool: &str,
+    arguments: &JsonObject,
+    preset: Option<&PresetConfig>,
+) -> Option<StrictCheckOptions> {
+    let argument = |name: &str| arguments.get(name).and_then(Value::as_str);
+    let input = argument("input")?;
+    // convert_format names the output format and defaults the output path from it
+    let format = argument("format")
+        .map(str::to_string)
+        .or_else(|| preset.and_then(|preset| preset.format.clone()))
+        .filter(|_| tool == "convert_format");
+    let output = match (argument("output"), &format) {
+        (Some(output), _) => output.to_string(),
+        (None, Some(format)) => Path::new(input)
+            .with_extension(format.to_ascii_lowercase())
+            .to_string_lossy()
+            .into_owned(),
+        (None, None) => return None,
+    };
+    let sized = SIZE_ARGUMENTS
+        .iter()
+        .any(|name| arguments.contains_key(*name));
+    let implicit_limits = preset.filter(|_| !sized);
+    Some(StrictCheckOptions {
+        input: input.to_string(),
+        output,
+        format,
+        auto_orients: tool == "strip_metadata"
+            || (tool == "orient" && arguments.get("auto_orient") == Some(&Value::Bool(true))),
+        overwrites: argument("on_conflict").is_none_or(|policy| policy == "overwrite"),
+        implicit_max_width: implicit_limits.and_then(|preset| preset.max_width),
+        implicit_max_height: implicit_limits.and_then(|preset| preset.max_height),
+    })
+}
+
+/// The concern codes passed in the `acknowledge` argument
+fn acknowledged(arguments: &JsonObject) -> HashSet<&str> {
+    arguments
+        .get("acknowledge")
+        .and_then(Value::as_array)
+        .map(|codes| codes.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// Add the `acknowledge` property to a tool's input schema
+fn add_acknowledge_property(tool: &mut Tool) {
+    let schema = Arc::make_mut(&mut tool.input_schema);
+    if let Some(Value::Object(properties)) = schema.get_mut("properties") {
+        properties.insert(
+            "acknowledge".to_string(),
+            json!({
+                "type": "array",
+                "items": {
+                    "type": "string",
+                    "enum": crate::StrictConcern::ALL.map(|concern| concern.code())
+                },
+                "description": "The server runs in strict mode and refuses calls with likely-unintended consequences, listing them as concerns. Pass the codes of the concerns to accept them: implicit_downscale (a preset shrinks the image although no size was given), transparency_loss (transparent pixels are flattened by an opaque format), exif_rotation_ignored (the EXIF orientation is not applied), overwrite_source (the output replaces the input)."
+            }),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arguments(value: Value) -> JsonObject {
+        value.as_object().unwrap().clone()
+    }
+
+    fn tool(name: &'static str, properties: Value) -> Tool {
+        let schema = json!({ "type": "object", "properties": properties });
+        Tool::new(name, "", schema.as_object().unwrap().clone())
+    }
+
+    #[test]
+    fn test_strict_tools_take_input_and_output() {
+        let tools = [
+            tool("resize", json!({ "input": {}, "output": {} })),
+            tool("canvas", json!({ "output": {} })),
+            tool("check", json!({})),
+        ];
+
+        assert_eq!(strict_tools(&tools), HashSet::from(["resize".to_string()]));
+    }
+
+    #[test]
+    fn test_check_options_uses_preset_limits_only_without_a_size() {
+        let preset = PresetConfig {
+            max_width: Some(1920),
+            ..Default::default()
+        };
+        let call = arguments(json!({ "input": "a.png", "output": "b.png", "preset": "web" }));
+        let options = check_options("resize", &call, Some(&preset)).unwrap();
+        assert_eq!(options.implicit_max_width, Some(1920));
+        assert!(options.overwrites);
+        assert!(!options.auto_orients);
+
+        let call = arguments(json!({
+            "input": "a.png",
+            "output": "b.png",
+            "width": 800,
+            "on_conflict": "skip"
+        }));
+        let options = check_options("resize", &call, Some(&preset)).unwrap();
+        assert_eq!(options.implicit_max_width, None);
+        assert!(!options.overwrites);
+    }
+
+    #[test]
+    fn test_check_options_defaults_convert_output() {
+        let call = arguments(json!({ "input": "photos/a.png", "format": "JPEG" }));
+        let options = check_options("convert_format", &call, None).unwrap();
+        assert_eq!(options.output, "photos/a.jpeg");
+        assert_eq!(options.format.as_deref(), Some("JPEG"));
+
+        assert!(check_options("resize", &call, None).is_none());
+
+        let call = arguments(json!({ "input": "a.jpg", "output": "b.jpg", "auto_orient": true }));
+        assert!(check_options("orient", &call, None).unwrap().auto_orients);
+    }
+
+    #[test]
+    fn test_acknowledge_property_is_added() {
+        let call = arguments(json!({ "acknowledge": ["overwrite_source", 3] }));
+        assert_eq!(acknowledged(&call), HashSet::from(["overwrite_source"]));
+
+        let mut tool = tool("resize", json!({ "input": {}, "output": {} }));
+        add_acknowledge_property(&mut tool);
+        assert_eq!(
+            tool.input_schema["properties"]["acknowledge"]["items"]["enum"][3],
+            "overwrite_source"
+        );
+    }
+}
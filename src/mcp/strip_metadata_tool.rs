@@ -0,0 +1,81 @@
+use crate::StripMetadataOptions;
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{optional_bool, output_policy, required_str, workspace};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Write a copy of an image without its metadata
+async fn strip_metadata_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output = required_str(arguments, "output")?;
+
+    let mut options = StripMetadataOptions::new(input, output);
+    options.keep_color_profile = optional_bool(arguments, "keep_color_profile").unwrap_or(false);
+
+    match crate::strip_metadata(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(stripped) => {
+            let result = json!({
+                "output": stripped.output.path.to_string_lossy(),
+                "skipped": stripped.output.skipped,
+                "removed": stripped.removed,
+                "remaining": stripped.remaining,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Strip metadata failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the strip_metadata tool route
+pub fn strip_metadata_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the source image."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the sanitized copy to."
+            },
+            "keep_color_profile": {
+                "type": "boolean",
+                "description": "Keep the ICC color profile so colors render the same, removing all other metadata. Defaults to false, which removes the color profile too."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when an output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen names are reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["input", "output"]
+    });
+    let tool = Tool::new(
+        "strip_metadata",
+        "Write a copy of an image without EXIF, IPTC, XMP, or GPS metadata so it can be shared without leaking location, camera, or editing details. The image is auto-oriented first so it still displays upright. The result lists the metadata properties and profiles that were removed and any that remain.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(strip_metadata_tool(context)))
+}
@@ -0,0 +1,88 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{invalid_params, optional_f64, output_policy, required_str, workspace};
+use crate::{Style, StylizeOptions};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Apply an artistic effect to an image
+async fn stylize_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output = required_str(arguments, "output")?;
+    let style = Style::parse(required_str(arguments, "style")?)
+        .map_err(|e| invalid_params(e.to_string()))?;
+
+    let mut options = StylizeOptions::new(input, output, style);
+    if let Some(intensity) = optional_f64(arguments, "intensity")? {
+        options.intensity = intensity;
+    }
+
+    match crate::stylize(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(output) => {
+            let result = json!({
+                "output": output.path.to_string_lossy(),
+                "skipped": output.skipped,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Stylize failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the stylize tool route
+pub fn stylize_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the source image."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the stylized image to."
+            },
+            "style": {
+                "type": "string",
+                "enum": ["grayscale", "sepia", "posterize", "charcoal", "sketch", "oil_paint"],
+                "description": "The effect: grayscale removes color, sepia gives the brown tones of an old photograph, posterize reduces colors to a few flat levels, charcoal and sketch turn the image into a drawing, and oil_paint into a painting."
+            },
+            "intensity": {
+                "type": "number",
+                "description": "How much of the effect to apply, from 0 (none) to 1 (full). Below 1 the effect is blended with the original. Defaults to 1."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when the output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen name is reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["input", "output", "style"]
+    });
+    let tool = Tool::new(
+        "stylize",
+        "Apply an artistic effect (grayscale, sepia, posterize, charcoal, sketch, or oil_paint) to an image at an intensity from 0 to 1, without building ImageMagick operator chains.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(stylize_tool(context)))
+}
@@ -0,0 +1,88 @@
+use crate::feature::{FunctionStore, FunctionsWatcher};
+use crate::log_line;
+use crate::mcp::functions_resource::FUNCTIONS_RESOURCE_URI;
+use crate::mcp::state::ServerState;
+use rmcp::model::ResourceUpdatedNotificationParam;
+use rmcp::service::{Peer, RoleServer};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// How often the functions are checked for changes
+const FUNCTIONS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The client subscribed to updates of the functions resource, if any
+///
+/// A stdio server has a single client, so a subscription is just the peer to notify.
+#[derive(Debug, Clone, Default)]
+pub struct FunctionsSubscription {
+    peer: Arc<Mutex<Option<Peer<RoleServer>>>>,
+}
+
+impl FunctionsSubscription {
+    /// Notify the peer whenever the functions change
+    pub fn subscribe(&self, peer: Peer<RoleServer>) {
+        *self.peer() = Some(peer);
+    }
+
+    /// Stop notifying the peer
+    pub fn unsubscribe(&self) {
+        *self.peer() = None;
+    }
+
+    fn peer(&self) -> MutexGuard<'_, Option<Peer<RoleServer>>> {
+        self.peer.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Tell the client whenever the functions change, after checking the changed files
+///
+/// Every client is told to list tools again, since the function tools list the current
+/// functions, and a client subscribed to the functions resource is also sent a resource updated
+/// notification. Saved function files which no longer parse or validate, e.g. after a hand edit
+/// or a `git pull`, are reported to the log. The functions are polled rather than watched, so
+/// changes made by other processes, such as the CLI or another client's server, are noticed as
+/// well. The project functions of the workspace calls currently default to are watched too, and
+/// follow it when `workspace_set` or the client's roots change it.
+///
+/// # Arguments
+///
+/// * `state` - The state shared with the server handler
+/// * `peer` - The connected client
+pub async fn notify_function_changes(state: Arc<ServerState>, peer: Peer<RoleServer>) {
+    let mut watcher = FunctionsWatcher::new();
+    loop {
+        tokio::time::sleep(FUNCTIONS_POLL_INTERVAL).await;
+        let workspace = state.workspace_defaults().resolve(None).path;
+        let store = state.function_store(Some(&workspace));
+        watcher.watch_project_dir(store.project_functions_path().ok().flatten());
+        if !watcher.poll() {
+            continue;
+        }
+        report_invalid_functions(&store);
+        // A client which has gone away is noticed by the service itself
+        let _ = peer.notify_tool_list_changed().await;
+        let Some(subscriber) = state.functions_subscription().peer().clone() else {
+            continue;
+        };
+        let notification = ResourceUpdatedNotificationParam {
+            uri: FUNCTIONS_RESOURCE_URI.to_string(),
+        };
+        let _ = subscriber.notify_resource_updated(notification).await;
+    }
+}
+
+/// Log each saved function file which cannot be used
+fn report_invalid_functions(store: &FunctionStore) {
+    match store.invalid_saved() {
+        Ok(invalid) => {
+            for function in invalid {
+                log_line(format!(
+                    "magick-mcp skipped invalid function file {}: {}",
+                    function.path.display(),
+                    function.error
+                ));
+            }
+        }
+        Err(e) => log_line(format!("magick-mcp failed to check functions: {e}")),
+    }
+}
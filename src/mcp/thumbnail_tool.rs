@@ -0,0 +1,86 @@
+use crate::ThumbnailOptions;
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{optional_str, optional_u32_array, required_str, workspace};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Generate thumbnails of an image in several sizes
+async fn thumbnail_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output_dir = required_str(arguments, "output_dir")?;
+
+    let mut options = ThumbnailOptions::new(input, output_dir);
+    if let Some(sizes) = optional_u32_array(arguments, "sizes")? {
+        options.sizes = sizes;
+    }
+    options.format = optional_str(arguments, "format").map(str::to_string);
+
+    match crate::thumbnail(&options, workspace(arguments)) {
+        Ok(files) => {
+            let files: Vec<_> = files
+                .iter()
+                .map(|file| {
+                    json!({
+                        "path": file.path.to_string_lossy(),
+                        "size": file.size,
+                        "bytes": file.bytes
+                    })
+                })
+                .collect();
+            let result = json!({
+                "files": files,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Thumbnail generation failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the thumbnail tool route
+pub fn thumbnail_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the source image."
+            },
+            "output_dir": {
+                "type": "string",
+                "description": "Directory the thumbnails are written to. Files are named <name>-<size>.<ext>."
+            },
+            "sizes": {
+                "type": "array",
+                "items": { "type": "integer" },
+                "description": "Bounding box sizes in pixels. Each thumbnail fits inside a square of this size. Defaults to [64, 128, 256, 512]."
+            },
+            "format": {
+                "type": "string",
+                "description": "File extension of the thumbnails, e.g. 'webp'. Defaults to the input's extension."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path. Commands run from this directory."
+            }
+        },
+        "required": ["input", "output_dir"]
+    });
+    let tool = Tool::new(
+        "thumbnail",
+        "Generate small, metadata-free thumbnails of an image in several sizes in one call and return the path, size, and byte count of each.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(thumbnail_tool(context)))
+}
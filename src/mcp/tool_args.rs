@@ -0,0 +1,196 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::{ConflictStrategy, LockScope, OutputPolicy, OutputSettings, PresetConfig};
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{ErrorCode, ErrorData, JsonObject};
+use std::path::Path;
+
+/// Build an `INVALID_PARAMS` error with the given message
+pub fn invalid_params(message: impl Into<String>) -> ErrorData {
+    ErrorData {
+        code: ErrorCode::INVALID_PARAMS,
+        message: message.into().into(),
+        data: None,
+    }
+}
+
+/// Get a required string argument
+pub fn required_str<'a>(
+    arguments: &'a Option<JsonObject>,
+    name: &str,
+) -> Result<&'a str, ErrorData> {
+    optional_str(arguments, name)
+        .ok_or_else(|| invalid_params(format!("Missing required parameter: {name}")))
+}
+
+/// Get an optional string argument
+pub fn optional_str<'a>(arguments: &'a Option<JsonObject>, name: &str) -> Option<&'a str> {
+    arguments
+        .as_ref()
+        .and_then(|args| args.get(name))
+        .and_then(|v| v.as_str())
+}
+
+/// Get an optional numeric argument
+pub fn optional_f64(arguments: &Option<JsonObject>, name: &str) -> Result<Option<f64>, ErrorData> {
+    let Some(value) = arguments.as_ref().and_then(|args| args.get(name)) else {
+        return Ok(None);
+    };
+    value
+        .as_f64()
+        .map(Some)
+        .ok_or_else(|| invalid_params(format!("Parameter '{name}' must be a number")))
+}
+
+/// Get an optional non-negative integer argument
+pub fn optional_u32(arguments: &Option<JsonObject>, name: &str) -> Result<Option<u32>, ErrorData> {
+    let Some(value) = arguments.as_ref().and_then(|args| args.get(name)) else {
+        return Ok(None);
+    };
+    value
+        .as_u64()
+        .and_then(|n| u32::try_from(n).ok())
+        .map(Some)
+        .ok_or_else(|| invalid_params(format!("Parameter '{name}' must be a non-negative integer")))
+}
+
+/// Get an optional integer argument which may be negative
+pub fn optional_i32(arguments: &Option<JsonObject>, name: &str) -> Result<Option<i32>, ErrorData> {
+    let Some(value) = arguments.as_ref().and_then(|args| args.get(name)) else {
+        return Ok(None);
+    };
+    value
+        .as_i64()
+        .and_then(|n| i32::try_from(n).ok())
+        .map(Some)
+        .ok_or_else(|| invalid_params(format!("Parameter '{name}' must be an integer")))
+}
+
+/// Get an optional boolean argument
+pub fn optional_bool(arguments: &Option<JsonObject>, name: &str) -> Option<bool> {
+    arguments
+        .as_ref()
+        .and_then(|args| args.get(name))
+        .and_then(|v| v.as_bool())
+}
+
+/// Get the `workspace` argument as a path
+///
+/// The server fills it in from the workspace precedence rules before the tool runs, so it is
+/// only `None` when a tool is called outside the server.
+pub fn workspace(arguments: &Option<JsonObject>) -> Option<&Path> {
+    optional_str(arguments, "workspace").map(Path::new)
+}
+
+/// Load the config preset named by the optional `preset` argument
+pub fn preset(arguments: &Option<JsonObject>) -> Result<Option<PresetConfig>, ErrorData> {
+    match optional_str(arguments, "preset") {
+        Some(name) => crate::load_preset(name)
+            .map(Some)
+            .map_err(|e| invalid_params(e.to_string())),
+        None => Ok(None),
+    }
+}
+
+/// Build finishing settings from a preset, overridden by the optional `max_width`,
+/// `max_height`, `strip`, and `colorspace` arguments
+pub fn output_settings(
+    arguments: &Option<JsonObject>,
+    preset: Option<&PresetConfig>,
+) -> Result<OutputSettings, ErrorData> {
+    let mut settings = preset.map(OutputSettings::from_preset).unwrap_or_default();
+    if let Some(max_width) = optional_u32(arguments, "max_width")? {
+        settings.max_width = Some(max_width);
+    }
+    if let Some(max_height) = optional_u32(arguments, "max_height")? {
+        settings.max_height = Some(max_height);
+    }
+    if let Some(strip) = optional_bool(arguments, "strip") {
+        settings.strip = strip;
+    }
+    if let Some(colorspace) = optional_str(arguments, "colorspace") {
+        settings.colorspace = Some(colorspace.to_string());
+    }
+    Ok(settings)
+}
+
+/// Build the output policy from the optional `create_dirs`, `on_conflict`, `lock`, and
+/// `skip_if_newer` arguments, keeping overwritten outputs in the server's trash when its config
+/// asks for it
+pub fn output_policy(
+    context: &ToolCallContext<'_, MagickServerHandler>,
+) -> Result<OutputPolicy, ErrorData> {
+    let arguments = &context.arguments;
+    let on_conflict = match optional_str(arguments, "on_conflict") {
+        Some(strategy) => {
+            ConflictStrategy::parse(strategy).map_err(|e| invalid_params(e.to_string()))?
+        }
+        None => ConflictStrategy::default(),
+    };
+    let lock = match optional_str(arguments, "lock") {
+        Some(scope) => LockScope::parse(scope).map_err(|e| invalid_params(e.to_string()))?,
+        None => LockScope::default(),
+    };
+    Ok(OutputPolicy {
+        create_dirs: optional_bool(arguments, "create_dirs").unwrap_or(false),
+        on_conflict,
+        lock,
+        skip_if_newer: optional_bool(arguments, "skip_if_newer").unwrap_or(false),
+        trash: context.service.state().output_trash(),
+    })
+}
+
+/// Get a required array-of-strings argument
+pub fn required_str_array(
+    arguments: &Option<JsonObject>,
+    name: &str,
+) -> Result<Vec<String>, ErrorData> {
+    arguments
+        .as_ref()
+        .and_then(|args| args.get(name))
+        .ok_or_else(|| invalid_params(format!("Missing required parameter: {name}")))?
+        .as_array()
+        .ok_or_else(|| invalid_params(format!("Parameter '{name}' must be an array")))?
+        .iter()
+        .map(|v| {
+            v.as_str().map(str::to_string).ok_or_else(|| {
+                invalid_params(format!("All items in '{name}' array must be strings"))
+            })
+        })
+        .collect()
+}
+
+/// Get an optional array-of-strings argument
+pub fn optional_str_array(
+    arguments: &Option<JsonObject>,
+    name: &str,
+) -> Result<Option<Vec<String>>, ErrorData> {
+    match arguments.as_ref().and_then(|args| args.get(name)) {
+        Some(_) => required_str_array(arguments, name).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Get an optional array-of-integers argument
+pub fn optional_u32_array(
+    arguments: &Option<JsonObject>,
+    name: &str,
+) -> Result<Option<Vec<u32>>, ErrorData> {
+    let Some(value) = arguments.as_ref().and_then(|args| args.get(name)) else {
+        return Ok(None);
+    };
+    value
+        .as_array()
+        .ok_or_else(|| invalid_params(format!("Parameter '{name}' must be an array")))?
+        .iter()
+        .map(|v| {
+            v.as_u64()
+                .and_then(|n| u32::try_from(n).ok())
+                .ok_or_else(|| {
+                    invalid_params(format!(
+                        "All items in '{name}' array must be non-negative integers"
+                    ))
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Some)
+}
@@ -0,0 +1,327 @@
+use crate::Config;
+use crate::mcp::ServerOptions;
+use crate::mcp::idle::ActivityTracker;
+use rmcp::model::{CallToolResult, JsonObject, ListToolsResult, ProtocolVersion, Tool};
+use rmcp::service::ServiceExt;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+use tokio::io::{
+    AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream, Lines, ReadHalf, WriteHalf,
+};
+
+/// Size of the in-memory pipe between the caller and the server
+const PIPE_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Error type for talking to the server without a client
+#[derive(Debug, Error)]
+pub enum ToolCallError {
+    #[error("Failed to start the server: {0}")]
+    Server(String),
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Failed to parse server message: {0}")]
+    ParseError(#[from] serde_json::Error),
+    #[error("The server rejected the call: {0}")]
+    Rejected(String),
+    #[error("The server closed the connection without answering")]
+    Closed,
+}
+
+/// Call a tool the way a client would, through the same server a client talks to
+///
+/// The server is started in memory and sent the `initialize` handshake followed by a single
+/// `tools/call` request, so the call passes through every layer a client's call does, such as
+/// workspace resolution, strict mode, and path translation.
+///
+/// # Arguments
+///
+/// * `name` - The name of the tool, e.g. `resize`
+/// * `arguments` - The tool's arguments, as a client would send them
+/// * `options` - The server options, e.g. to call the tool in strict mode
+///
+/// # Returns
+///
+/// Returns the tool's result, including results reporting a failed operation, or a
+/// `ToolCallError` when the server refuses the call, e.g. for an unknown tool or missing argument
+pub async fn call_tool(
+    name: &str,
+    arguments: JsonObject,
+    options: ServerOptions,
+) -> Result<CallToolResult, ToolCallError> {
+    let params = json!({ "name": name, "arguments": arguments });
+    let result = request(options, "tools/call", params).await?;
+    Ok(serde_json::from_value(result)?)
+}
+
+/// List the tools the way a client would, as the server presents them to clients
+///
+/// The descriptions and schemas include what the server's layers add, such as the current
+/// functions in the function tools' descriptions and, in strict mode, the `acknowledge`
+/// argument.
+///
+/// # Arguments
+///
+/// * `options` - The server options, e.g. to list the tools of a strict server
+///
+/// # Returns
+///
+/// Returns the tools sorted by name, or a `ToolCallError` on failure
+pub async fn list_tools(options: ServerOptions) -> Result<Vec<Tool>, ToolCallError> {
+    let result = request(options, "tools/list", json!({})).await?;
+    let mut tools = serde_json::from_value::<ListToolsResult>(result)?.tools;
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(tools)
+}
+
+/// Start the server in memory, initialize it, and send it one request
+///
+/// # Returns
+///
+/// Returns the result of the request, or a `ToolCallError` if the server fails or refuses it
+async fn request(
+    options: ServerOptions,
+    method: &str,
+    params: Value,
+) -> Result<Value, ToolCallError> {
+    let config = crate::load_config().map_err(|e| ToolCallError::Server(e.to_string()))?;
+    let mut session = LocalSession::start(options, config, crate::feature::data_dir()).await?;
+    let id = session.send(method, params).await?;
+    session.response(id).await
+}
+
+/// A connection to a server running in memory, acting as its client
+struct LocalSession {
+    lines: Lines<BufReader<ReadHalf<DuplexStream>>>,
+    write: WriteHalf<DuplexStream>,
+    next_id: u64,
+    /// Responses read while waiting for another one, by request id
+    pending: HashMap<u64, Value>,
+}
+
+impl LocalSession {
+    /// Start a server and complete the `initialize` handshake with it
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The server options
+    /// * `config` - The config the server runs with
+    /// * `data_dir` - Directory holding saved functions and the execution history
+    async fn start(
+        options: ServerOptions,
+        config: Config,
+        data_dir: Option<PathBuf>,
+    ) -> Result<Self, ToolCallError> {
+        let (service, _) = super::server_service(options, ActivityTracker::new(), config, data_dir);
+        let (client, server) = tokio::io::duplex(PIPE_BUFFER_SIZE);
+        tokio::spawn(async move {
+            // A server which fails to start closes its end, which the caller reports
+            if let Ok(running_service) = service.serve(tokio::io::split(server)).await {
+                let _ = running_service.waiting().await;
+            }
+        });
+
+        let (read, write) = tokio::io::split(client);
+        let mut session = LocalSession {
+            lines: BufReader::new(read).lines(),
+            write,
+            next_id: 1,
+            pending: HashMap::new(),
+        };
+        let params = json!({
+            "protocolVersion": ProtocolVersion::LATEST,
+            "capabilities": {},
+            "clientInfo": {
+                "name": "magick-mcp",
+                "version": env!("CARGO_PKG_VERSION")
+            }
+        });
+        let id = session.send("initialize", params).await?;
+        session.response(id).await?;
+        session
+            .write_message(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+            .await?;
+        Ok(session)
+    }
+
+    /// Send a request without waiting for its response
+    ///
+    /// # Returns
+    ///
+    /// Returns the id to wait for the response with
+    async fn send(&mut self, method: &str, params: Value) -> Result<u64, ToolCallError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.write_message(
+            json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }),
+        )
+        .await?;
+        Ok(id)
+    }
+
+    /// Wait for the response to a request, keeping responses to others for later
+    async fn response(&mut self, id: u64) -> Result<Value, ToolCallError> {
+        loop {
+            let mut message = match self.pending.remove(&id) {
+                Some(message) => message,
+                None => {
+                    let line = self.lines.next_line().await?.ok_or(ToolCallError::Closed)?;
+                    let message: Value = serde_json::from_str(&line)?;
+                    // Notifications and requests from the server carry a method
+                    if message.get("method").is_some() {
+                        continue;
+                    }
+                    match message.get("id").and_then(Value::as_u64) {
+                        Some(message_id) if message_id == id => message,
+                        Some(message_id) => {
+                            self.pending.insert(message_id, message);
+                            continue;
+                        }
+                        None => continue,
+                    }
+                }
+            };
+            if let Some(error) = message.get("error") {
+                let text = error
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+                    .unwrap_or_else(|| error.to_string());
+                return Err(ToolCallError::Rejected(text));
+            }
+            return Ok(message["result"].take());
+        }
+    }
+
+    /// Write one JSON-RPC message to the server
+    async fn write_message(&mut self, message: Value) -> Result<(), ToolCallError> {
+        let mut line = serde_json::to_vec(&message)?;
+        line.push(b'\n');
+        self.write.write_all(&line).await?;
+        self.write.flush().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// Start a server with the default config, keeping its data in `data_dir`
+    async fn start(options: ServerOptions, data_dir: &TempDir) -> LocalSession {
+        let data_dir = Some(data_dir.path().to_path_buf());
+        LocalSession::start(options, Config::default(), data_dir)
+            .await
+            .unwrap()
+    }
+
+    fn call(
+        data_dir: &TempDir,
+        name: &str,
+        arguments: Value,
+    ) -> Result<CallToolResult, ToolCallError> {
+        let params = json!({ "name": name, "arguments": arguments });
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let mut session = start(ServerOptions::default(), data_dir).await;
+            let id = session.send("tools/call", params).await?;
+            Ok(serde_json::from_value(session.response(id).await?)?)
+        })
+    }
+
+    #[test]
+    fn test_call_tool_returns_structured_result() {
+        let dir = TempDir::new().unwrap();
+        let data_dir = TempDir::new().unwrap();
+        let path = dir.path().to_string_lossy().into_owned();
+
+        let result = call(&data_dir, "workspace_set", json!({ "path": path })).unwrap();
+
+        let structured = result.structured_content.unwrap();
+        assert_eq!(structured["success"], true);
+        assert_eq!(structured["workspace_source"], "session");
+        assert_eq!(result.is_error, Some(false));
+        // The call is recorded in the server's own data directory
+        let history = fs::read_to_string(data_dir.path().join("history.jsonl")).unwrap();
+        assert!(history.contains("workspace_set"));
+    }
+
+    #[test]
+    fn test_list_tools_as_presented_to_clients() {
+        let data_dir = TempDir::new().unwrap();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let result = runtime.block_on(async {
+            let options = ServerOptions {
+                strict: true,
+                ..Default::default()
+            };
+            let mut session = start(options, &data_dir).await;
+            let id = session.send("tools/list", json!({})).await.unwrap();
+            session.response(id).await.unwrap()
+        });
+        let mut tools = serde_json::from_value::<ListToolsResult>(result)
+            .unwrap()
+            .tools;
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+
+        assert!(tools.is_sorted_by(|a, b| a.name < b.name));
+        let resize = tools.iter().find(|tool| tool.name == "resize").unwrap();
+        assert!(resize.input_schema["properties"]["acknowledge"].is_object());
+        let func_execute = tools
+            .iter()
+            .find(|tool| tool.name == "func_execute")
+            .unwrap();
+        assert!(
+            func_execute
+                .description
+                .as_deref()
+                .unwrap_or_default()
+                .contains("functions")
+        );
+    }
+
+    #[test]
+    fn test_concurrent_calls_share_server_state() {
+        let dirs: Vec<TempDir> = (0..8).map(|_| TempDir::new().unwrap()).collect();
+        let paths: Vec<String> = dirs
+            .iter()
+            .map(|dir| dir.path().to_string_lossy().into_owned())
+            .collect();
+
+        let data_dir = TempDir::new().unwrap();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let workspace = runtime.block_on(async {
+            let mut session = start(ServerOptions::default(), &data_dir).await;
+            // Every call is sent before any response is read, so the server runs them together
+            let mut ids = Vec::new();
+            for path in &paths {
+                let params = json!({ "name": "workspace_set", "arguments": { "path": path } });
+                ids.push(session.send("tools/call", params).await.unwrap());
+            }
+            for id in ids {
+                let result = session.response(id).await.unwrap();
+                assert_eq!(result["structuredContent"]["success"], true);
+            }
+
+            // A later call on the same server sees the session workspace one of them set
+            let params = json!({ "name": "func_list", "arguments": {} });
+            let id = session.send("tools/call", params).await.unwrap();
+            session.response(id).await.unwrap()["structuredContent"].take()
+        });
+
+        assert_eq!(workspace["workspace_source"], "session");
+        let workspace = workspace["workspace"].as_str().unwrap();
+        assert!(paths.iter().any(|path| path == workspace));
+    }
+
+    #[test]
+    fn test_call_unknown_tool_is_rejected() {
+        assert!(matches!(
+            call(&TempDir::new().unwrap(), "no_such_tool", json!({})),
+            Err(ToolCallError::Rejected(_))
+        ));
+    }
+}
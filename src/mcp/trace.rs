@@ -0,0 +1,207 @@
+use crate::{
+    HistoryEntry, HistoryStore, begin_call, end_call, log_line, new_trace_id, take_lock_wait,
+    with_trace_id,
+};
+use rmcp::ErrorData;
+use rmcp::model::{
+    CallToolResult, ClientNotification, ClientRequest, Content, ServerInfo, ServerResult,
+};
+use rmcp::service::{NotificationContext, RequestContext, RoleServer, Service};
+use serde_json::{Value, json};
+use std::time::{Duration, Instant};
+
+/// Service wrapper which assigns every tool call a trace ID
+///
+/// The ID is attached to the request while it runs, so commands it launches receive it through
+/// `MAGICK_MCP_TRACE_ID`. It is added to the structured result or error, logged to stderr
+/// with the tool name and outcome, and appended to the execution history. Results of calls which
+/// took a write lock also report the time spent waiting for it. While the call runs it is
+/// named, with its argument values redacted, in any crash report.
+pub struct TracingService<S> {
+    inner: S,
+    history: HistoryStore,
+}
+
+impl<S> TracingService<S> {
+    /// Wrap a service so its tool calls are traced and appended to the provided history
+    pub fn new(inner: S, history: HistoryStore) -> Self {
+        TracingService { inner, history }
+    }
+}
+
+impl<S: Service<RoleServer>> Service<RoleServer> for TracingService<S> {
+    async fn handle_request(
+        &self,
+        request: ClientRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, ErrorData> {
+        let ClientRequest::CallToolRequest(call) = &request else {
+            return self.inner.handle_request(request, context).await;
+        };
+        let tool = call.params.name.to_string();
+        let arguments = Value::Object(call.params.arguments.clone().unwrap_or_default());
+        let trace_id = new_trace_id();
+        let started = Instant::now();
+        begin_call(&trace_id, &tool, &arguments);
+
+        let response = with_trace_id(
+            trace_id.clone(),
+            self.inner.handle_request(request, context),
+        )
+        .await;
+        end_call(&trace_id);
+
+        let status = match &response {
+            Ok(ServerResult::CallToolResult(result)) if result.is_error == Some(true) => "error",
+            Ok(_) => "ok",
+            Err(_) => "invalid",
+        };
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        let lock_wait = take_lock_wait(&trace_id);
+        log_line(format!(
+            "magick-mcp trace_id={trace_id} tool={tool} status={status} elapsed_ms={elapsed_ms}"
+        ));
+
+        let result = match &response {
+            Ok(ServerResult::CallToolResult(result)) => result.structured_content.clone(),
+            Ok(_) => None,
+            Err(error) => Some(json!({ "message": error.message, "data": error.data })),
+        };
+        let entry = HistoryEntry::new(&trace_id, &tool, arguments, status, elapsed_ms, result);
+        if let Err(e) = self.history.append(&entry) {
+            log_line(format!(
+                "magick-mcp trace_id={trace_id} failed to record history: {e}"
+            ));
+        }
+
+        match response {
+            Ok(ServerResult::CallToolResult(result)) => Ok(ServerResult::CallToolResult(
+                with_trace_id_field(result, &trace_id, lock_wait),
+            )),
+            Ok(other) => Ok(other),
+            Err(mut error) => {
+                error.data = Some(with_trace_id_value(error.data.take(), &trace_id));
+                Err(error)
+            }
+        }
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ClientNotification,
+        context: NotificationContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.inner.handle_notification(notification, context).await
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        self.inner.get_info()
+    }
+}
+
+/// Add the trace ID and any lock wait to a structured tool result, keeping its text content in sync
+fn with_trace_id_field(
+    result: CallToolResult,
+    trace_id: &str,
+    lock_wait: Option<Duration>,
+) -> CallToolResult {
+    let mut fields = vec![("trace_id", json!(trace_id))];
+    if let Some(lock_wait) = lock_wait {
+        fields.push(("lock_wait_ms", json!(lock_wait.as_millis() as u64)));
+    }
+    with_structured_fields(result, fields)
+}
+
+/// Add fields to a structured tool result, keeping its text content in sync
+///
+/// Results without structured content are returned unchanged.
+pub(crate) fn with_structured_fields<'a>(
+    mut result: CallToolResult,
+    fields: impl IntoIterator<Item = (&'a str, Value)>,
+) -> CallToolResult {
+    let Some(Value::Object(structured)) = &mut result.structured_content else {
+        return result;
+    };
+    let previous_text = Value::Object(structured.clone()).to_string();
+    for (name, value) in fields {
+        structured.insert(name.to_string(), value);
+    }
+    let updated_text = Value::Object(structured.clone()).to_string();
+
+    for content in &mut result.content {
+        if content
+            .as_text()
+            .is_some_and(|text| text.text == previous_text)
+        {
+            *content = Content::text(updated_text.clone());
+        }
+    }
+    result
+}
+
+/// Add the trace ID to the data of an error, wrapping any non-object data
+fn with_trace_id_value(data: Option<Value>, trace_id: &str) -> Value {
+    match data {
+        Some(Value::Object(mut object)) => {
+            object.insert("trace_id".to_string(), json!(trace_id));
+            Value::Object(object)
+        }
+        Some(other) => json!({ "details": other, "trace_id": trace_id }),
+        None => json!({ "trace_id": trace_id }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_trace_id_field_updates_structured_and_text() {
+        let result = CallToolResult::structured_error(json!({
+            "error": "boom",
+            "success": false
+        }));
+
+        let traced = with_trace_id_field(result, "abc123", None);
+
+        let expected = json!({
+            "error": "boom",
+            "success": false,
+            "trace_id": "abc123"
+        });
+        assert_eq!(traced.structured_content, Some(expected.clone()));
+        assert_eq!(
+            traced.content[0].as_text().unwrap().text,
+            expected.to_string()
+        );
+        assert_eq!(traced.is_error, Some(true));
+    }
+
+    #[test]
+    fn test_with_trace_id_field_adds_lock_wait() {
+        let result = CallToolResult::structured(json!({ "success": true }));
+
+        let traced = with_trace_id_field(result, "abc123", Some(Duration::from_millis(42)));
+
+        assert_eq!(
+            traced.structured_content,
+            Some(json!({ "success": true, "trace_id": "abc123", "lock_wait_ms": 42 }))
+        );
+    }
+
+    #[test]
+    fn test_with_trace_id_value() {
+        assert_eq!(
+            with_trace_id_value(None, "abc"),
+            json!({ "trace_id": "abc" })
+        );
+        assert_eq!(
+            with_trace_id_value(Some(json!({ "field": 1 })), "abc"),
+            json!({ "field": 1, "trace_id": "abc" })
+        );
+        assert_eq!(
+            with_trace_id_value(Some(json!("text")), "abc"),
+            json!({ "details": "text", "trace_id": "abc" })
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use crate::TrimOptions;
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{optional_f64, output_policy, required_str, workspace};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Crop away the uniform border around an image
+async fn trim_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output = required_str(arguments, "output")?;
+
+    let mut options = TrimOptions::new(input, output);
+    options.fuzz = optional_f64(arguments, "fuzz")?;
+
+    match crate::trim(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(trimmed) => {
+            let result = json!({
+                "output": trimmed.output.path.to_string_lossy(),
+                "skipped": trimmed.output.skipped,
+                "before_width": trimmed.before.width,
+                "before_height": trimmed.before.height,
+                "after_width": trimmed.after.map(|after| after.width),
+                "after_height": trimmed.after.map(|after| after.height),
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Trim failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the trim tool route
+pub fn trim_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the source image."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the trimmed image to."
+            },
+            "fuzz": {
+                "type": "number",
+                "description": "Percentage, from 0 to 100, by which border pixels may differ from the corner color and still be trimmed, e.g. 5 for scans or JPEGs with noisy backgrounds. Defaults to exact matches."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when the output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen name is reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["input", "output"]
+    });
+    let tool = Tool::new(
+        "trim",
+        "Auto-crop an image by trimming the uniform border or background around its content. Returns the size before and after so you can check how much was cropped.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(trim_tool(context)))
+}
@@ -0,0 +1,112 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    invalid_params, optional_f64, optional_str, output_policy, required_str, workspace,
+};
+use crate::{DiffMetric, VisualDiffOptions};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Compare two images and return a pass/fail verdict with a diff image
+async fn visual_diff_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let expected = required_str(arguments, "expected")?;
+    let actual = required_str(arguments, "actual")?;
+    let diff = required_str(arguments, "diff")?;
+
+    let mut options = VisualDiffOptions::new(expected, actual, diff);
+    if let Some(metric) = optional_str(arguments, "metric") {
+        options.metric = DiffMetric::parse(metric).map_err(|e| invalid_params(e.to_string()))?;
+    }
+    options.threshold = optional_f64(arguments, "threshold")?;
+    options.fuzz = optional_f64(arguments, "fuzz")?;
+    options.highlight_color = optional_str(arguments, "highlight_color").map(str::to_string);
+
+    match crate::visual_diff(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(verdict) => {
+            let result = json!({
+                "passed": verdict.passed,
+                "metric": options.metric.name(),
+                "value": verdict.value,
+                "threshold": verdict.threshold,
+                "diff": verdict.diff.as_ref().map(|diff| diff.path.to_string_lossy()),
+                "size_mismatch": verdict.expected_dimensions != verdict.actual_dimensions,
+                "expected_width": verdict.expected_dimensions.width,
+                "expected_height": verdict.expected_dimensions.height,
+                "actual_width": verdict.actual_dimensions.width,
+                "actual_height": verdict.actual_dimensions.height,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Visual diff failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the visual_diff tool route
+pub fn visual_diff_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "expected": {
+                "type": "string",
+                "description": "Path to the expected (reference) image. Only the first frame is compared."
+            },
+            "actual": {
+                "type": "string",
+                "description": "Path to the image to check. Only the first frame is compared."
+            },
+            "diff": {
+                "type": "string",
+                "description": "Path to write an image highlighting the differences to, e.g. 'diff.png'."
+            },
+            "metric": {
+                "type": "string",
+                "enum": ["ssim", "rmse", "ae"],
+                "description": "ssim: structural similarity from 0 to 1, passing at or above the threshold (default). rmse: normalized root mean squared error from 0 to 1, passing at or below the threshold. ae: number of differing pixels, passing at or below the threshold."
+            },
+            "threshold": {
+                "type": "number",
+                "description": "Threshold the value must pass. Defaults to 0.99 for ssim, 0.01 for rmse, and 0 for ae."
+            },
+            "fuzz": {
+                "type": "number",
+                "description": "Percentage, from 0 to 100, by which colors may differ and still count as equal, e.g. 2 to ignore anti-aliasing noise."
+            },
+            "highlight_color": {
+                "type": "string",
+                "description": "Color differing pixels are painted in the diff image. Defaults to red."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when the diff file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen name is reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["expected", "actual", "diff"]
+    });
+    let tool = Tool::new(
+        "visual_diff",
+        "Compare an image against an expected image in one call: writes a diff image highlighting the differences and returns a pass/fail verdict for an SSIM, RMSE, or pixel count threshold. Use it for visual regression checks.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(visual_diff_tool(context)))
+}
@@ -0,0 +1,126 @@
+use crate::WatermarkOptions;
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{optional_f64, optional_str, output_policy, required_str, workspace};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+use std::path::Path;
+
+/// Watermark every image matching a glob into an output directory
+async fn watermark_batch_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let pattern = required_str(arguments, "glob")?;
+    let logo = required_str(arguments, "logo")?;
+    let output_dir = required_str(arguments, "output_dir")?;
+
+    let mut template = WatermarkOptions::new("", "", logo);
+    if let Some(gravity) = optional_str(arguments, "gravity") {
+        template.gravity = gravity.to_string();
+    }
+    if let Some(opacity) = optional_f64(arguments, "opacity")? {
+        template.opacity = opacity;
+    }
+    if let Some(margin) = optional_f64(arguments, "margin")? {
+        template.margin = margin.max(0.0) as u32;
+    }
+
+    match crate::watermark_batch(
+        pattern,
+        Path::new(output_dir),
+        &template,
+        workspace(arguments),
+        output_policy(&context)?,
+    ) {
+        Ok(results) => {
+            let failed = results.iter().filter(|r| r.error.is_some()).count();
+            let files: Vec<_> = results
+                .iter()
+                .map(|result| {
+                    json!({
+                        "input": result.input.to_string_lossy(),
+                        "output": result.output.to_string_lossy(),
+                        "skipped": result.skipped,
+                        "success": result.error.is_none(),
+                        "error": result.error
+                    })
+                })
+                .collect();
+            let result = json!({
+                "files": files,
+                "processed": results.len(),
+                "failed": failed,
+                "success": failed == 0
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Watermark batch failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the watermark_batch tool route
+pub fn watermark_batch_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "glob": {
+                "type": "string",
+                "description": "Glob pattern selecting images relative to the workspace (e.g. 'photos/*.jpg')."
+            },
+            "logo": {
+                "type": "string",
+                "description": "Path to the logo image, ideally a PNG with transparency."
+            },
+            "output_dir": {
+                "type": "string",
+                "description": "Directory the watermarked copies are written to. Files keep their names."
+            },
+            "gravity": {
+                "type": "string",
+                "enum": ["NorthWest", "North", "NorthEast", "West", "Center", "East", "SouthWest", "South", "SouthEast"],
+                "description": "Where the logo is placed. Defaults to SouthEast."
+            },
+            "opacity": {
+                "type": "number",
+                "description": "Logo opacity from 0 to 100. Defaults to 50."
+            },
+            "margin": {
+                "type": "number",
+                "description": "Distance in pixels between the logo and the image edge. Defaults to 10."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when an output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen names are reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "skip_if_newer": {
+                "type": "boolean",
+                "description": "Skip commands whose output is newer than every input and was written by the same command on an earlier run with skip_if_newer. Skipped outputs are reported as skipped. Defaults to false."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path the glob and output directory are relative to."
+            }
+        },
+        "required": ["glob", "logo", "output_dir"]
+    });
+    let tool = Tool::new(
+        "watermark_batch",
+        "Apply a logo watermark to every image matching a glob, writing the results into an output directory. Files are processed in parallel and each file reports its own result.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(watermark_batch_tool(context)))
+}
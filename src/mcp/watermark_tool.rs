@@ -0,0 +1,130 @@
+use crate::WatermarkOptions;
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::{
+    invalid_params, optional_f64, optional_str, output_policy, required_str, workspace,
+};
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Place a logo or text watermark on an image
+async fn watermark_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let arguments = &context.arguments;
+    let input = required_str(arguments, "input")?;
+    let output = required_str(arguments, "output")?;
+
+    let mut options = match (
+        optional_str(arguments, "text"),
+        optional_str(arguments, "image"),
+    ) {
+        (Some(text), None) => WatermarkOptions::text(input, output, text),
+        (None, Some(image)) => WatermarkOptions::new(input, output, image),
+        _ => return Err(invalid_params("provide exactly one of text or image")),
+    };
+    if let Some(gravity) = optional_str(arguments, "gravity") {
+        options.gravity = gravity.to_string();
+    }
+    if let Some(opacity) = optional_f64(arguments, "opacity")? {
+        options.opacity = opacity;
+    }
+    if let Some(margin) = optional_f64(arguments, "margin")? {
+        options.margin = margin.max(0.0) as u32;
+    }
+    options.scale = optional_f64(arguments, "scale")?;
+    options.font = optional_str(arguments, "font").map(str::to_string);
+    if let Some(color) = optional_str(arguments, "color") {
+        options.color = color.to_string();
+    }
+
+    match crate::watermark(&options, workspace(arguments), output_policy(&context)?) {
+        Ok(output) => {
+            let result = json!({
+                "output": output.path.to_string_lossy(),
+                "skipped": output.skipped,
+                "success": true
+            });
+            Ok(CallToolResult::structured(result))
+        }
+        Err(e) => {
+            let error_result = json!({
+                "error": format!("Watermark failed: {}", e),
+                "success": false
+            });
+            Ok(CallToolResult::structured_error(error_result))
+        }
+    }
+}
+
+/// Create the watermark tool route
+pub fn watermark_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "input": {
+                "type": "string",
+                "description": "Path to the source image."
+            },
+            "output": {
+                "type": "string",
+                "description": "Path to write the watermarked copy to."
+            },
+            "text": {
+                "type": "string",
+                "description": "Text to place on the image, e.g. '© 2026 Example'. Provide either text or image."
+            },
+            "image": {
+                "type": "string",
+                "description": "Path to a logo image to place on the image, ideally a PNG with transparency. Provide either text or image."
+            },
+            "gravity": {
+                "type": "string",
+                "enum": ["NorthWest", "North", "NorthEast", "West", "Center", "East", "SouthWest", "South", "SouthEast"],
+                "description": "Where the watermark is placed. Defaults to SouthEast."
+            },
+            "opacity": {
+                "type": "number",
+                "description": "Watermark opacity from 0 to 100. Defaults to 50."
+            },
+            "margin": {
+                "type": "number",
+                "description": "Distance in pixels between the watermark and the image edge. Defaults to 10."
+            },
+            "scale": {
+                "type": "number",
+                "description": "Width of the watermark as a fraction of the image width, greater than 0 and at most 1, e.g. 0.2. Logos keep their own size and text is 0.25 of the image width when omitted."
+            },
+            "font": {
+                "type": "string",
+                "description": "Font for text, as listed by the list_fonts tool. Uses ImageMagick's default when omitted."
+            },
+            "color": {
+                "type": "string",
+                "description": "Text color, e.g. 'white' or '#ff0000'. Defaults to white."
+            },
+            "on_conflict": {
+                "type": "string",
+                "enum": ["overwrite", "skip", "rename"],
+                "description": "What to do when an output file already exists: overwrite it (the default), skip the command, or write to a new name with a -1, -2, ... suffix. The chosen names are reported in the result."
+            },
+            "lock": {
+                "type": "string",
+                "enum": ["none", "output", "workspace"],
+                "description": "Wait for other calls writing the same output file (output) or the same workspace (workspace) before writing, instead of racing them. Defaults to none. The time spent waiting is reported as lock_wait_ms."
+            },
+            "workspace": {
+                "type": "string",
+                "description": "Workspace path to set as the working directory for the command."
+            }
+        },
+        "required": ["input", "output"]
+    });
+    let tool = Tool::new(
+        "watermark",
+        "Place a semi-transparent text or logo watermark on an image at a gravity, with an opacity and a size relative to the image width, without needing ImageMagick compositing syntax.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(watermark_tool(context)))
+}
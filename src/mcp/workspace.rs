@@ -0,0 +1,367 @@
+use crate::mcp::trace::with_structured_fields;
+use crate::{log_line, translate_path};
+use rmcp::ErrorData;
+use rmcp::model::{ClientNotification, ClientRequest, ServerInfo, ServerResult, Tool};
+use rmcp::service::{NotificationContext, Peer, RequestContext, RoleServer, Service};
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Where the workspace of a tool call came from
+///
+/// Variants are listed in order of precedence: the first source which provides a workspace is
+/// used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkspaceSource {
+    /// The `workspace` argument of the call
+    Argument,
+    /// The session default set with the `workspace_set` tool
+    Session,
+    /// The first file root shared by the client
+    ClientRoot,
+    /// `workspace` in the `[server]` section of `config.toml`
+    Config,
+    /// The directory the server was started in
+    ServerCwd,
+}
+
+impl WorkspaceSource {
+    /// The name reported in tool results
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WorkspaceSource::Argument => "argument",
+            WorkspaceSource::Session => "session",
+            WorkspaceSource::ClientRoot => "client_root",
+            WorkspaceSource::Config => "config",
+            WorkspaceSource::ServerCwd => "server_cwd",
+        }
+    }
+}
+
+/// The workspace a tool call runs in, and where it came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedWorkspace {
+    pub path: PathBuf,
+    pub source: WorkspaceSource,
+}
+
+/// The fallback workspaces used when a tool call names none
+///
+/// Shared by every clone, so the session default and client root set by one part of the server
+/// are seen by the resolver.
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceDefaults {
+    session: Arc<Mutex<Option<PathBuf>>>,
+    client_root: Arc<Mutex<Option<PathBuf>>>,
+    config: Option<PathBuf>,
+}
+
+impl WorkspaceDefaults {
+    /// Create defaults falling back to the configured workspace, if any
+    pub fn new(config: Option<&str>) -> Self {
+        WorkspaceDefaults {
+            config: config.map(PathBuf::from),
+            ..Default::default()
+        }
+    }
+
+    /// Set or clear the session default
+    pub fn set_session(&self, path: Option<PathBuf>) {
+        *lock(&self.session) = path;
+    }
+
+    /// Set or clear the workspace taken from the client's roots
+    pub fn set_client_root(&self, path: Option<PathBuf>) {
+        *lock(&self.client_root) = path;
+    }
+
+    /// Resolve the workspace of a call
+    ///
+    /// # Arguments
+    ///
+    /// * `argument` - The `workspace` argument of the call, if given
+    ///
+    /// # Returns
+    ///
+    /// Returns the workspace from the first source which provides one, falling back to the
+    /// server's current directory
+    pub fn resolve(&self, argument: Option<&str>) -> ResolvedWorkspace {
+        let session = lock(&self.session).clone();
+        let client_root = lock(&self.client_root).clone();
+        let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        resolve_workspace(
+            argument,
+            session.as_deref(),
+            client_root.as_deref(),
+            self.config.as_deref(),
+            cwd,
+        )
+    }
+}
+
+fn lock(path: &Mutex<Option<PathBuf>>) -> MutexGuard<'_, Option<PathBuf>> {
+    path.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Pick the workspace from the first source which provides one
+fn resolve_workspace(
+    argument: Option<&str>,
+    session: Option<&Path>,
+    client_root: Option<&Path>,
+    config: Option<&Path>,
+    cwd: PathBuf,
+) -> ResolvedWorkspace {
+    let candidates = [
+        (
+            argument.filter(|path| !path.is_empty()).map(Path::new),
+            WorkspaceSource::Argument,
+        ),
+        (session, WorkspaceSource::Session),
+        (client_root, WorkspaceSource::ClientRoot),
+        (config, WorkspaceSource::Config),
+    ];
+    candidates
+        .into_iter()
+        .find_map(|(path, source)| {
+            path.map(|path| ResolvedWorkspace {
+                path: path.to_path_buf(),
+                source,
+            })
+        })
+        .unwrap_or(ResolvedWorkspace {
+            path: cwd,
+            source: WorkspaceSource::ServerCwd,
+        })
+}
+
+/// Names of the tools whose input schema has a `workspace` property
+pub fn workspace_tools(tools: &[Tool]) -> HashSet<String> {
+    tools
+        .iter()
+        .filter(|tool| {
+            tool.input_schema
+                .get("properties")
+                .and_then(Value::as_object)
+                .is_some_and(|properties| properties.contains_key("workspace"))
+        })
+        .map(|tool| tool.name.to_string())
+        .collect()
+}
+
+/// Service wrapper which resolves the workspace of every tool call in one place
+///
+/// Calls to tools taking a `workspace` argument have it filled in from the first of: the
+/// argument itself, the session default, the client's first file root, the configured default,
+/// and the server's current directory. The chosen path and its source are added to the
+/// structured result as `workspace` and `workspace_source`. The client's roots are fetched
+/// when it finishes initializing and again whenever it reports they changed.
+pub struct WorkspaceService<S> {
+    inner: S,
+    defaults: WorkspaceDefaults,
+    tools: HashSet<String>,
+}
+
+impl<S> WorkspaceService<S> {
+    /// Wrap a service so the named tools have their workspace resolved
+    pub fn new(inner: S, defaults: WorkspaceDefaults, tools: HashSet<String>) -> Self {
+        WorkspaceService {
+            inner,
+            defaults,
+            tools,
+        }
+    }
+}
+
+impl<S: Service<RoleServer>> Service<RoleServer> for WorkspaceService<S> {
+    async fn handle_request(
+        &self,
+        mut request: ClientRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, ErrorData> {
+        let ClientRequest::CallToolRequest(call) = &mut request else {
+            return self.inner.handle_request(request, context).await;
+        };
+        if !self.tools.contains(call.params.name.as_ref()) {
+            return self.inner.handle_request(request, context).await;
+        }
+        let arguments = call.params.arguments.get_or_insert_with(Default::default);
+        let resolved = self
+            .defaults
+            .resolve(arguments.get("workspace").and_then(Value::as_str));
+        let path = resolved.path.to_string_lossy().into_owned();
+        arguments.insert("workspace".to_string(), json!(path));
+
+        match self.inner.handle_request(request, context).await? {
+            ServerResult::CallToolResult(result) => {
+                Ok(ServerResult::CallToolResult(with_structured_fields(
+                    result,
+                    [
+                        ("workspace", json!(path)),
+                        ("workspace_source", json!(resolved.source.as_str())),
+                    ],
+                )))
+            }
+            other => Ok(other),
+        }
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ClientNotification,
+        context: NotificationContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        if matches!(
+            notification,
+            ClientNotification::InitializedNotification(_)
+                | ClientNotification::RootsListChangedNotification(_)
+        ) {
+            // The response arrives through the service loop, which this notification holds up
+            tokio::spawn(fetch_client_root(
+                context.peer.clone(),
+                self.defaults.clone(),
+            ));
+        }
+        self.inner.handle_notification(notification, context).await
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        self.inner.get_info()
+    }
+}
+
+/// Ask the client for its roots and use the first file root as the client root workspace
+async fn fetch_client_root(peer: Peer<RoleServer>, defaults: WorkspaceDefaults) {
+    let supports_roots = peer
+        .peer_info()
+        .is_some_and(|info| info.capabilities.roots.is_some());
+    if !supports_roots {
+        return;
+    }
+    match peer.list_roots().await {
+        Ok(result) => {
+            defaults.set_client_root(result.roots.iter().find_map(|root| root_path(&root.uri)))
+        }
+        Err(e) => log_line(format!("magick-mcp failed to list client roots: {e}")),
+    }
+}
+
+/// Convert a `file://` root URI to a path this host can open
+///
+/// Returns `None` for roots which are not local files.
+fn root_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    let rest = rest.strip_prefix("localhost").unwrap_or(rest);
+    if !rest.starts_with('/') {
+        return None;
+    }
+    let decoded = percent_decode(rest)?;
+    // file:///C:/Users/me names the Windows path C:/Users/me
+    let path = match decoded.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => &decoded[1..],
+        _ => decoded.as_str(),
+    };
+    Some(PathBuf::from(translate_path(path).into_owned()))
+}
+
+/// Decode `%XX` escapes, or `None` when they are malformed or not UTF-8
+fn percent_decode(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let hex = text.get(index + 1..index + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_workspace_precedence() {
+        let cwd = PathBuf::from("/cwd");
+        let session = Path::new("/session");
+        let root = Path::new("/root");
+        let config = Path::new("/config");
+
+        let resolved = resolve_workspace(
+            Some("/arg"),
+            Some(session),
+            Some(root),
+            Some(config),
+            cwd.clone(),
+        );
+        assert_eq!(resolved.path, PathBuf::from("/arg"));
+        assert_eq!(resolved.source, WorkspaceSource::Argument);
+
+        let resolved = resolve_workspace(None, Some(session), Some(root), None, cwd.clone());
+        assert_eq!(resolved.source, WorkspaceSource::Session);
+
+        let resolved = resolve_workspace(Some(""), None, Some(root), Some(config), cwd.clone());
+        assert_eq!(resolved.path, PathBuf::from("/root"));
+        assert_eq!(resolved.source, WorkspaceSource::ClientRoot);
+
+        let resolved = resolve_workspace(None, None, None, Some(config), cwd.clone());
+        assert_eq!(resolved.source, WorkspaceSource::Config);
+
+        let resolved = resolve_workspace(None, None, None, None, cwd);
+        assert_eq!(resolved.path, PathBuf::from("/cwd"));
+        assert_eq!(resolved.source, WorkspaceSource::ServerCwd);
+    }
+
+    #[test]
+    fn test_defaults_session_overrides_config() {
+        let defaults = WorkspaceDefaults::new(Some("/config"));
+        assert_eq!(defaults.resolve(None).source, WorkspaceSource::Config);
+
+        defaults
+            .clone()
+            .set_session(Some(PathBuf::from("/session")));
+        assert_eq!(defaults.resolve(None).path, PathBuf::from("/session"));
+
+        defaults.set_session(None);
+        assert_eq!(defaults.resolve(None).source, WorkspaceSource::Config);
+    }
+
+    #[test]
+    fn test_root_path() {
+        assert_eq!(
+            root_path("file:///home/me/My%20Images"),
+            Some(PathBuf::from("/home/me/My Images"))
+        );
+        assert_eq!(
+            root_path("file://localhost/srv/images"),
+            Some(PathBuf::from("/srv/images"))
+        );
+        assert_eq!(root_path("https://example.com/images"), None);
+        assert_eq!(root_path("file:///bad%2"), None);
+    }
+
+    #[test]
+    fn test_workspace_tools() {
+        let schema = |properties: Value| {
+            json!({ "type": "object", "properties": properties })
+                .as_object()
+                .unwrap()
+                .clone()
+        };
+        let tools = [
+            Tool::new("resize", "", schema(json!({ "workspace": {} }))),
+            Tool::new("check", "", schema(json!({}))),
+        ];
+
+        assert_eq!(
+            workspace_tools(&tools),
+            HashSet::from(["resize".to_string()])
+        );
+    }
+}
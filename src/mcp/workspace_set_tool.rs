@@ -0,0 +1,62 @@
+use crate::mcp::server::MagickServerHandler;
+use crate::mcp::tool_args::optional_str;
+use rmcp::handler::server::router::tool::ToolRoute;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, ErrorData, Tool};
+use serde_json::json;
+
+/// Set or clear the session default workspace
+async fn workspace_set_tool(
+    context: ToolCallContext<'_, MagickServerHandler>,
+) -> Result<CallToolResult, ErrorData> {
+    let defaults = context.service.state().workspace_defaults();
+    let session = match optional_str(&context.arguments, "path") {
+        Some(path) => match std::path::absolute(path) {
+            Ok(absolute) if absolute.is_dir() => Some(absolute),
+            Ok(_) => {
+                let error_result = json!({
+                    "error": format!("Set workspace failed: {} is not a directory", path),
+                    "success": false
+                });
+                return Ok(CallToolResult::structured_error(error_result));
+            }
+            Err(e) => {
+                let error_result = json!({
+                    "error": format!("Set workspace failed: {}", e),
+                    "success": false
+                });
+                return Ok(CallToolResult::structured_error(error_result));
+            }
+        },
+        None => None,
+    };
+    defaults.set_session(session);
+
+    let resolved = defaults.resolve(None);
+    let result = json!({
+        "workspace": resolved.path.to_string_lossy(),
+        "workspace_source": resolved.source.as_str(),
+        "success": true
+    });
+    Ok(CallToolResult::structured(result))
+}
+
+/// Create the workspace_set tool route
+pub fn workspace_set_tool_route() -> ToolRoute<MagickServerHandler> {
+    let input_schema: serde_json::Value = json!({
+        "type": "object",
+        "properties": {
+            "path": {
+                "type": "string",
+                "description": "Directory to use as the workspace of later tool calls which give no workspace argument. Omit to clear the session default."
+            }
+        },
+        "required": []
+    });
+    let tool = Tool::new(
+        "workspace_set",
+        "Set the session default workspace. Tool calls without a workspace argument use, in order: the session default, the client's first root, the configured default, then the server's current directory. Returns the workspace calls now resolve to and its source.",
+        input_schema.as_object().unwrap().clone(),
+    );
+    ToolRoute::new_dyn(tool, |context| Box::pin(workspace_set_tool(context)))
+}
@@ -0,0 +1,108 @@
+use crate::translate_path;
+use rmcp::ErrorData;
+use rmcp::model::{ClientNotification, ClientRequest, ServerInfo, ServerResult};
+use rmcp::service::{NotificationContext, RequestContext, RoleServer, Service};
+use serde_json::Value;
+use std::borrow::Cow;
+
+/// Service wrapper which translates paths in tool arguments between Windows and WSL forms
+///
+/// A client on Windows talking to a server inside WSL sends paths such as `C:\Users\me\a.png`,
+/// and a client inside WSL talking to a server on Windows sends `/mnt/c/...`. Every string
+/// argument which is one such path, including `workspace` and the items of arrays, is rewritten
+/// to the form this host can open before the tool runs. Elsewhere arguments are left alone.
+pub struct PathTranslationService<S> {
+    inner: S,
+}
+
+impl<S> PathTranslationService<S> {
+    /// Wrap a service so its tool arguments are translated
+    pub fn new(inner: S) -> Self {
+        PathTranslationService { inner }
+    }
+}
+
+impl<S: Service<RoleServer>> Service<RoleServer> for PathTranslationService<S> {
+    async fn handle_request(
+        &self,
+        mut request: ClientRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, ErrorData> {
+        if let ClientRequest::CallToolRequest(call) = &mut request
+            && let Some(arguments) = &mut call.params.arguments
+        {
+            for value in arguments.values_mut() {
+                translate_value(value, &translate_path);
+            }
+        }
+        self.inner.handle_request(request, context).await
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ClientNotification,
+        context: NotificationContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.inner.handle_notification(notification, context).await
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        self.inner.get_info()
+    }
+}
+
+/// Translate every string in a value which is a path in the other side's form
+fn translate_value(value: &mut Value, translate: &impl Fn(&str) -> Cow<'_, str>) {
+    match value {
+        Value::String(text) => {
+            if let Cow::Owned(translated) = translate(text) {
+                *text = translated;
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                translate_value(item, translate);
+            }
+        }
+        Value::Object(fields) => {
+            for field in fields.values_mut() {
+                translate_value(field, translate);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_translate_value_rewrites_nested_paths() {
+        fn translate(text: &str) -> Cow<'_, str> {
+            match text.strip_prefix(r"C:\") {
+                Some(rest) => Cow::Owned(format!("/mnt/c/{}", rest.replace('\\', "/"))),
+                None => Cow::Borrowed(text),
+            }
+        }
+        let mut arguments = json!({
+            "workspace": r"C:\work",
+            "inputs": [r"C:\work\a.png", "b.png"],
+            "params": { "logo": r"C:\brand\logo.png" },
+            "margin": 36
+        });
+
+        translate_value(&mut arguments, &translate);
+
+        assert_eq!(
+            arguments,
+            json!({
+                "workspace": "/mnt/c/work",
+                "inputs": ["/mnt/c/work/a.png", "b.png"],
+                "params": { "logo": "/mnt/c/brand/logo.png" },
+                "margin": 36
+            })
+        );
+    }
+}